@@ -38,12 +38,16 @@ pub struct BlockCacheUnit {
     /// If set, the block is modified and need to be synchronized to
     /// the target device.
     dirty: bool,
+
+    /// Time when the block was first found dirty by [`BlockCache::sync_expired`].
+    dirty_since: Option<usize>,
 }
 
 impl CacheUnit for BlockCacheUnit {
     fn sync(&mut self) {
         if self.dirty {
             self.dirty = false;
+            self.dirty_since = None;
             self.device.write_block(self.id, &self.data);
         }
     }
@@ -56,6 +60,10 @@ impl CacheUnit for BlockCacheUnit {
         self.dirty = true;
     }
 
+    fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
     fn size(&self) -> usize {
         BLOCK_SIZE
     }
@@ -74,6 +82,30 @@ impl BlockCacheUnit {
             data,
             device: block_dev,
             dirty: false,
+            dirty_since: None,
+        }
+    }
+
+    /// Synchronizes this block if it has been dirty for at least `expire`.
+    ///
+    /// A dirty block is stamped with `now` when first checked, so it will be
+    /// written back by a later check.
+    ///
+    /// Returns true if the block is written back.
+    pub fn sync_expired(&mut self, now: usize, expire: usize) -> bool {
+        if !self.dirty {
+            return false;
+        }
+        match self.dirty_since {
+            Some(since) if now.saturating_sub(since) >= expire => {
+                self.sync();
+                true
+            }
+            Some(_) => false,
+            None => {
+                self.dirty_since = Some(now);
+                false
+            }
         }
     }
 }
@@ -97,6 +129,17 @@ pub trait BlockCache {
 
     /// Synchronize all block cache units to block device.
     fn sync_all(&self);
+
+    /// Synchronizes at most `max` blocks which have been dirty for at least
+    /// `expire` to block device.
+    ///
+    /// Blocks being used are skipped to avoid blocking foreground I/O.
+    ///
+    /// Returns the number of blocks written back.
+    fn sync_expired(&self, now: usize, expire: usize, max: usize) -> usize;
+
+    /// Counts the block cache units not synchronized to block device.
+    fn dirty_count(&self) -> usize;
 }
 
 pub struct FIFOBlockCache {
@@ -155,6 +198,28 @@ impl BlockCache for FIFOBlockCache {
             unit.lock().sync();
         }
     }
+
+    fn sync_expired(&self, now: usize, expire: usize, max: usize) -> usize {
+        let mut count = 0;
+        for (_, unit) in self.inner.iter() {
+            if count == max {
+                break;
+            }
+            if let Some(mut unit) = unit.try_lock() {
+                if unit.sync_expired(now, expire) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    fn dirty_count(&self) -> usize {
+        self.inner
+            .iter()
+            .filter(|(_, unit)| unit.lock().is_dirty())
+            .count()
+    }
 }
 
 impl fmt::Debug for FIFOBlockCache {
@@ -226,6 +291,28 @@ impl BlockCache for LRUBlockCache {
             unit.lock().sync();
         }
     }
+
+    fn sync_expired(&self, now: usize, expire: usize, max: usize) -> usize {
+        let mut count = 0;
+        for (_, unit) in self.inner.iter() {
+            if count == max {
+                break;
+            }
+            if let Some(mut unit) = unit.try_lock() {
+                if unit.sync_expired(now, expire) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    fn dirty_count(&self) -> usize {
+        self.inner
+            .iter()
+            .filter(|(_, unit)| unit.lock().is_dirty())
+            .count()
+    }
 }
 
 impl fmt::Debug for LRUBlockCache {
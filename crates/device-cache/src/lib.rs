@@ -5,6 +5,8 @@
 extern crate alloc;
 
 mod block;
+#[cfg(test)]
+mod test;
 
 use core::any::Any;
 
@@ -24,6 +26,9 @@ pub trait CacheUnit: Send + Sync + Any {
     /// the next level of memory system.
     fn set_dirty(&mut self);
 
+    /// Checks if this cache unit has been modified since the last synchronization.
+    fn is_dirty(&self) -> bool;
+
     /// The size of this unit in bytes
     fn size(&self) -> usize;
 
@@ -0,0 +1,91 @@
+use alloc::{sync::Arc, vec::Vec};
+use kernel_sync::SpinLock;
+
+use crate::*;
+
+extern crate std;
+
+/// A block device recording ids of blocks written.
+struct MockDevice {
+    written: SpinLock<Vec<usize>>,
+}
+
+impl BlockDevice for MockDevice {
+    fn read_block(&self, _block_id: usize, buf: &mut [u8]) {
+        buf.fill(0);
+    }
+
+    fn write_block(&self, block_id: usize, _buf: &[u8]) {
+        self.written.lock().push(block_id);
+    }
+}
+
+#[test]
+fn test_sync_expired() {
+    let device = Arc::new(MockDevice {
+        written: SpinLock::new(Vec::new()),
+    });
+    let mut cache = LRUBlockCache::new(4);
+    for id in 0..3 {
+        cache
+            .get_block(id, device.clone())
+            .lock()
+            .write(0, |v: &mut u8| *v = 1);
+    }
+
+    assert_eq!(cache.dirty_count(), 3);
+
+    // Dirty blocks are stamped at the first check.
+    assert_eq!(cache.sync_expired(100, 30, 8), 0);
+    assert_eq!(cache.sync_expired(120, 30, 8), 0);
+    assert!(device.written.lock().is_empty());
+
+    // Blocks being used are skipped.
+    let busy = cache.get_block(1, device.clone());
+    let locked = busy.lock();
+    assert_eq!(cache.sync_expired(130, 30, 8), 2);
+    assert_eq!(*device.written.lock(), [0, 2]);
+    drop(locked);
+
+    // Written back blocks are clean.
+    assert_eq!(cache.sync_expired(200, 30, 8), 1);
+    assert_eq!(cache.sync_expired(300, 30, 8), 0);
+    assert_eq!(*device.written.lock(), [0, 2, 1]);
+}
+
+#[test]
+fn test_sync_expired_batch() {
+    let device = Arc::new(MockDevice {
+        written: SpinLock::new(Vec::new()),
+    });
+    let mut cache = FIFOBlockCache::new(4);
+    for id in 0..4 {
+        cache
+            .get_block(id, device.clone())
+            .lock()
+            .write(0, |v: &mut u8| *v = 1);
+    }
+    cache.sync_expired(0, 10, 8);
+    assert_eq!(cache.sync_expired(10, 10, 3), 3);
+    assert_eq!(cache.sync_expired(10, 10, 3), 1);
+    assert_eq!(device.written.lock().len(), 4);
+}
+
+#[test]
+fn test_sync_all() {
+    let device = Arc::new(MockDevice {
+        written: SpinLock::new(Vec::new()),
+    });
+    let mut cache = LRUBlockCache::new(4);
+    for id in 0..4 {
+        let block = cache.get_block(id, device.clone());
+        if id % 2 == 0 {
+            block.lock().write(0, |v: &mut u8| *v = 1);
+        }
+    }
+    assert_eq!(cache.dirty_count(), 2);
+
+    cache.sync_all();
+    assert_eq!(cache.dirty_count(), 0);
+    assert_eq!(*device.written.lock(), [0, 2]);
+}
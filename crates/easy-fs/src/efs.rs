@@ -1,5 +1,6 @@
 use super::{
     block_cache_sync_all, get_block_cache, Bitmap, DiskInode, DiskInodeType, Inode, SuperBlock,
+    DEFAULT_MODE,
 };
 use crate::BLOCK_SZ;
 use alloc::sync::Arc;
@@ -15,11 +16,73 @@ pub struct EasyFileSystem {
     pub data_bitmap: Bitmap,
     inode_area_start_block: u32,
     data_area_start_block: u32,
+    data_area_blocks: u32,
 }
 
 type DataBlock = [u8; BLOCK_SZ];
+
+/// Errors of an easy fs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FsError {
+    /// Magic number in the super block mismatched
+    BadMagic,
+    /// Block counts in the super block are inconsistent
+    BadLayout,
+    /// The device is too small for the requested layout, or no inode or block is free
+    NoSpace,
+    /// The name already exists in the directory
+    Exists,
+    /// The name does not exist in the directory
+    NotFound,
+}
+
 /// An easy fs over a block device
 impl EasyFileSystem {
+    /// Formats the block device and creates an easy fs on it.
+    ///
+    /// Returns [`FsError::NoSpace`] if no data block is left after the super
+    /// block, bitmaps and inode area.
+    pub fn format(
+        block_device: Arc<dyn BlockDevice>,
+        total_blocks: u32,
+        inode_bitmap_blocks: u32,
+    ) -> Result<Arc<SpinLock<Self>>, FsError> {
+        if inode_bitmap_blocks == 0 {
+            return Err(FsError::NoSpace);
+        }
+        let inode_num = Bitmap::new(1, inode_bitmap_blocks as usize).maximum();
+        let inode_area_blocks =
+            (inode_num * core::mem::size_of::<DiskInode>()).div_ceil(BLOCK_SZ) as u32;
+        // super block, inode bitmap, inode area, and at least a data bitmap block
+        // with a data block
+        if (total_blocks as u64) < 3 + inode_bitmap_blocks as u64 + inode_area_blocks as u64 {
+            return Err(FsError::NoSpace);
+        }
+        Ok(Self::create(block_device, total_blocks, inode_bitmap_blocks))
+    }
+    /// Mounts the easy fs on the block device, validating the super block.
+    pub fn mount(block_device: Arc<dyn BlockDevice>) -> Result<Arc<SpinLock<Self>>, FsError> {
+        get_block_cache(0, Arc::clone(&block_device))
+            .lock()
+            .read(0, |super_block: &SuperBlock| {
+                if !super_block.is_valid() {
+                    return Err(FsError::BadMagic);
+                }
+                let used_blocks = 1
+                    + super_block.inode_bitmap_blocks as u64
+                    + super_block.inode_area_blocks as u64
+                    + super_block.data_bitmap_blocks as u64
+                    + super_block.data_area_blocks as u64;
+                if super_block.inode_bitmap_blocks == 0
+                    || super_block.data_bitmap_blocks == 0
+                    || used_blocks != super_block.total_blocks as u64
+                {
+                    return Err(FsError::BadLayout);
+                }
+                Ok(())
+            })?;
+        Ok(Self::open(block_device))
+    }
     /// A data block of block size
     pub fn create(
         block_device: Arc<dyn BlockDevice>,
@@ -45,6 +108,7 @@ impl EasyFileSystem {
             data_bitmap,
             inode_area_start_block: 1 + inode_bitmap_blocks,
             data_area_start_block: 1 + inode_total_blocks + data_bitmap_blocks,
+            data_area_blocks,
         };
         // clear all blocks
         for i in 0..total_blocks {
@@ -71,12 +135,12 @@ impl EasyFileSystem {
         );
         // write back immediately
         // create a inode for root node "/"
-        assert_eq!(efs.alloc_inode(), 0);
+        assert_eq!(efs.alloc_inode(), Some(0));
         let (root_inode_block_id, root_inode_offset) = efs.get_disk_inode_pos(0);
         get_block_cache(root_inode_block_id as usize, Arc::clone(&block_device))
             .lock()
             .write(root_inode_offset, |disk_inode: &mut DiskInode| {
-                disk_inode.initialize(DiskInodeType::Directory);
+                disk_inode.initialize(DiskInodeType::Directory, DEFAULT_MODE);
             });
         block_cache_sync_all();
         Arc::new(SpinLock::new(efs))
@@ -99,6 +163,7 @@ impl EasyFileSystem {
                     ),
                     inode_area_start_block: 1 + super_block.inode_bitmap_blocks,
                     data_area_start_block: 1 + inode_total_blocks + super_block.data_bitmap_blocks,
+                    data_area_blocks: super_block.data_area_blocks,
                 };
                 Arc::new(SpinLock::new(efs))
             })
@@ -125,14 +190,27 @@ impl EasyFileSystem {
     pub fn get_data_block_id(&self, data_block_id: u32) -> u32 {
         self.data_area_start_block + data_block_id
     }
-    /// Allocate a new inode
-    pub fn alloc_inode(&mut self) -> u32 {
-        self.inode_bitmap.alloc(&self.block_device).unwrap() as u32
+    /// Allocate a new inode, or return None if no inode is free
+    pub fn alloc_inode(&mut self) -> Option<u32> {
+        self.inode_bitmap
+            .alloc(&self.block_device)
+            .map(|inode_id| inode_id as u32)
+    }
+
+    /// Deallocate an inode
+    pub fn dealloc_inode(&mut self, inode_id: u32) {
+        self.inode_bitmap.dealloc(&self.block_device, inode_id as usize)
     }
 
-    /// Allocate a data block
-    pub fn alloc_data(&mut self) -> u32 {
-        self.data_bitmap.alloc(&self.block_device).unwrap() as u32 + self.data_area_start_block
+    /// Allocate a data block, or return None if no block is free
+    pub fn alloc_data(&mut self) -> Option<u32> {
+        let block_id = self.data_bitmap.alloc(&self.block_device)?;
+        // The bitmap covers more blocks than the data area.
+        if block_id >= self.data_area_blocks as usize {
+            self.data_bitmap.dealloc(&self.block_device, block_id);
+            return None;
+        }
+        Some(block_id as u32 + self.data_area_start_block)
     }
     /// Deallocate a data block
     pub fn dealloc_data(&mut self, block_id: u32) {
@@ -10,6 +10,8 @@ const EFS_MAGIC: u32 = 0x3b800001;
 const INODE_DIRECT_COUNT: usize = 28;
 /// The max length of inode name
 const NAME_LENGTH_LIMIT: usize = 27;
+/// Permission bits of inodes created without a mode
+pub const DEFAULT_MODE: u16 = 0o777;
 /// The max number of indirect1 inodes
 const INODE_INDIRECT1_COUNT: usize = BLOCK_SZ / 4;
 /// The max number of indirect2 inodes
@@ -87,17 +89,24 @@ pub struct DiskInode {
     pub indirect1: u32,
     pub indirect2: u32,
     type_: DiskInodeType,
+    /// Permission bits, stored in the padding after the type.
+    mode: u16,
 }
 
 impl DiskInode {
     /// Initialize a disk inode, as well as all direct inodes under it
     /// indirect1 and indirect2 block are allocated only when they are needed
-    pub fn initialize(&mut self, type_: DiskInodeType) {
+    pub fn initialize(&mut self, type_: DiskInodeType, mode: u16) {
         self.size = 0;
         self.direct.iter_mut().for_each(|v| *v = 0);
         self.indirect1 = 0;
         self.indirect2 = 0;
         self.type_ = type_;
+        self.mode = mode;
+    }
+    /// Permission bits of this inode
+    pub fn mode(&self) -> u16 {
+        self.mode
     }
     /// Whether this inode is a directory
     pub fn is_dir(&self) -> bool {
@@ -0,0 +1,183 @@
+use alloc::{sync::Arc, vec, vec::Vec};
+use device_cache::CacheUnit;
+use kernel_sync::SpinLock;
+
+use crate::*;
+
+extern crate std;
+
+/// A block device in memory.
+struct MemDevice(SpinLock<Vec<u8>>);
+
+impl MemDevice {
+    fn new(blocks: usize) -> Arc<Self> {
+        Arc::new(Self(SpinLock::new(vec![0; blocks * BLOCK_SZ])))
+    }
+}
+
+impl BlockDevice for MemDevice {
+    fn read_block(&self, block_id: usize, buf: &mut [u8]) {
+        let data = self.0.lock();
+        let start = block_id * BLOCK_SZ;
+        buf.copy_from_slice(&data[start..start + buf.len()]);
+    }
+
+    fn write_block(&self, block_id: usize, buf: &[u8]) {
+        let mut data = self.0.lock();
+        let start = block_id * BLOCK_SZ;
+        data[start..start + buf.len()].copy_from_slice(buf);
+    }
+}
+
+/// The block cache is global and keyed by block id, so all cases share one device
+/// and run one by one.
+fn device() -> (std::sync::MutexGuard<'static, ()>, Arc<MemDevice>) {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    static DEVICE: spin::Lazy<Arc<MemDevice>> = spin::Lazy::new(|| MemDevice::new(2048));
+    let guard = LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    (guard, DEVICE.clone())
+}
+
+#[test]
+fn test_format_mount() {
+    let (_guard, device) = device();
+    assert_eq!(
+        EasyFileSystem::format(device.clone(), 1024, 1).err(),
+        Some(FsError::NoSpace)
+    );
+    assert_eq!(
+        EasyFileSystem::format(device.clone(), 2048, 0).err(),
+        Some(FsError::NoSpace)
+    );
+
+    let efs = EasyFileSystem::format(device.clone(), 2048, 1).unwrap();
+    let root = EasyFileSystem::root_inode(&efs);
+    root.create("hello").unwrap();
+    drop(root);
+    drop(efs);
+
+    let efs = EasyFileSystem::mount(device.clone()).unwrap();
+    let root = EasyFileSystem::root_inode(&efs);
+    assert!(root.find("hello").is_some());
+    drop(root);
+    drop(efs);
+
+    // Corrupt the magic number.
+    block_cache::get_block_cache(0, device.clone())
+        .lock()
+        .write(0, |magic: &mut u32| *magic = 0);
+    assert_eq!(
+        EasyFileSystem::mount(device.clone()).err(),
+        Some(FsError::BadMagic)
+    );
+}
+
+#[test]
+fn test_inode_ops() {
+    let (_guard, device) = device();
+    let efs = EasyFileSystem::format(device.clone(), 2048, 1).unwrap();
+    let root = EasyFileSystem::root_inode(&efs);
+
+    let dir = root.create_dir("dir", 0o755).unwrap();
+    assert!(dir.is_dir());
+    assert_eq!(dir.mode(), 0o755);
+    let file = dir.create("a").unwrap();
+    assert!(!file.is_dir());
+    assert_eq!(file.write_at(0, b"hello"), 5);
+    assert_eq!(file.size(), 5);
+
+    // Move the file to root, replacing an existing one.
+    root.create("b").unwrap().write_at(0, b"old");
+    assert_eq!(dir.rename("a", &root, "b"), Ok(()));
+    assert!(dir.find("a").is_none());
+    assert!(dir.readdir().is_empty());
+    let mut buf = [0u8; 8];
+    assert_eq!(root.find("b").unwrap().read_at(0, &mut buf), 5);
+    assert_eq!(&buf[..5], b"hello");
+
+    // Rename in the same directory.
+    assert_eq!(root.rename("b", &root, "c"), Ok(()));
+    assert_eq!(root.rename("b", &root, "c"), Err(FsError::NotFound));
+    assert_eq!(root.readdir(), ["dir", "c"]);
+
+    // The empty slot is reused.
+    assert!(root.unlink("dir"));
+    assert!(!root.unlink("dir"));
+    root.create("d").unwrap();
+    assert_eq!(root.readdir(), ["d", "c"]);
+}
+
+#[test]
+fn test_no_space() {
+    let (_guard, device) = device();
+    let efs = EasyFileSystem::format(device.clone(), 2048, 1).unwrap();
+    let root = EasyFileSystem::root_inode(&efs);
+
+    // Fill up the data area.
+    let big = root.create("big").unwrap();
+    let chunk = [1u8; BLOCK_SZ];
+    let mut size = 0;
+    loop {
+        match big.write_at(size, &chunk) {
+            0 => break,
+            len => size += len,
+        }
+    }
+    assert_eq!(big.size(), size);
+
+    // The root directory cannot grow beyond its first block.
+    let names: Vec<_> = (0..BLOCK_SZ / DIRENT_SZ)
+        .map(|i| std::format!("f{}", i))
+        .collect();
+    let created = names
+        .iter()
+        .take_while(|name| root.create(name).is_ok())
+        .count();
+    assert!(created < names.len());
+    let name = &names[created];
+    assert_eq!(root.create(name).err(), Some(FsError::NoSpace));
+    assert!(root.find(name).is_none());
+    assert_eq!(root.create("big").err(), Some(FsError::Exists));
+    assert_eq!(root.rename("f0", &root, name), Err(FsError::NoSpace));
+    assert!(root.find("f0").is_some());
+
+    // The inode allocated is released, and the blocks freed are reused.
+    assert!(root.unlink("big"));
+    root.create(name).unwrap();
+    assert!(root.find(name).is_some());
+}
+
+#[test]
+fn test_concurrent_create() {
+    let (_guard, device) = device();
+    let efs = EasyFileSystem::format(device.clone(), 2048, 1).unwrap();
+    let root = Arc::new(EasyFileSystem::root_inode(&efs));
+
+    let tasks: Vec<_> = (0..2)
+        .map(|task| {
+            let root = root.clone();
+            std::thread::spawn(move || {
+                let mut created = 0;
+                for i in 0..20 {
+                    if root.create(&std::format!("f{}-{}", task, i)).is_ok() {
+                        created += 1;
+                    }
+                    // Both tasks race for the same names.
+                    if root.create(&std::format!("shared{}", i)).is_ok() {
+                        created += 1;
+                    }
+                }
+                created
+            })
+        })
+        .collect();
+    let created: usize = tasks.into_iter().map(|task| task.join().unwrap()).sum();
+    assert_eq!(created, 60);
+
+    let mut names = root.readdir();
+    assert_eq!(names.len(), 60);
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), 60);
+    assert!(names.iter().all(|name| root.find(name).is_some()));
+}
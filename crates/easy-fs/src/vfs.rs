@@ -1,6 +1,6 @@
 use super::{
     block_cache_sync_all, get_block_cache, DirEntry, DiskInode, DiskInodeType, EasyFileSystem,
-    DIRENT_SZ,
+    FsError, DEFAULT_MODE, DIRENT_SZ,
 };
 use alloc::string::String;
 use alloc::sync::Arc;
@@ -8,6 +8,16 @@ use alloc::vec::Vec;
 use device_cache::{BlockDevice, CacheUnit};
 use kernel_sync::{SpinLock, SpinLockGuard};
 /// Virtual filesystem layer over easy-fs
+///
+/// Every method holds the filesystem lock `fs` for its whole duration, so operations
+/// on the same filesystem are serialized. Locks must be acquired in the order of:
+///
+/// 1. the filesystem lock `fs`;
+/// 2. the global block cache manager, in `get_block_cache` and `block_cache_sync_all`;
+/// 3. a block cache unit.
+///
+/// Since `block_cache_sync_all` locks every block cache unit with the manager held,
+/// it must be called with `fs` held, when no block cache unit is locked by others.
 pub struct Inode {
     block_id: usize,
     block_offset: usize,
@@ -47,8 +57,18 @@ impl Inode {
 
     /// Find inode under a disk inode by name
     fn find_inode_id(&self, name: &str, disk_inode: &DiskInode) -> Option<u32> {
+        self.find_dirent(name, disk_inode)
+            .map(|(_, inode_id)| inode_id)
+    }
+
+    /// Find the index and inode id of a dirent under a disk inode by name.
+    /// Empty dirents left by unlinked files are never matched.
+    fn find_dirent(&self, name: &str, disk_inode: &DiskInode) -> Option<(usize, u32)> {
         // assert it is a directory
         assert!(disk_inode.is_dir());
+        if name.is_empty() {
+            return None;
+        }
         let file_count = (disk_inode.size as usize) / DIRENT_SZ;
         let mut dirent = DirEntry::empty();
         for i in 0..file_count {
@@ -57,79 +77,156 @@ impl Inode {
                 DIRENT_SZ,
             );
             if dirent.name() == name {
-                return Some(dirent.inode_number() as u32);
+                return Some((i, dirent.inode_number() as u32));
             }
         }
         None
     }
 
+    /// Write a dirent into the first empty slot of a disk inode, or append it.
+    /// Return false if the directory cannot grow.
+    fn add_dirent(
+        &self,
+        name: &str,
+        inode_id: u32,
+        disk_inode: &mut DiskInode,
+        fs: &mut SpinLockGuard<EasyFileSystem>,
+    ) -> bool {
+        let file_count = (disk_inode.size as usize) / DIRENT_SZ;
+        let mut dirent = DirEntry::empty();
+        let index = match (0..file_count).find(|&i| {
+            disk_inode.read_at(DIRENT_SZ * i, dirent.as_bytes_mut(), &self.block_device);
+            dirent.name().is_empty()
+        }) {
+            Some(index) => index,
+            // increase size
+            None if self.increase_size(((file_count + 1) * DIRENT_SZ) as u32, disk_inode, fs) => {
+                file_count
+            }
+            None => return false,
+        };
+        let dirent = DirEntry::new(name, inode_id);
+        disk_inode.write_at(index * DIRENT_SZ, dirent.as_bytes(), &self.block_device);
+        true
+    }
+
+    /// Create an inode handle by id
+    fn from_id(&self, fs: &EasyFileSystem, inode_id: u32) -> Arc<Inode> {
+        let (block_id, block_offset) = fs.get_disk_inode_pos(inode_id);
+        Arc::new(Self::new(
+            block_id,
+            block_offset,
+            self.fs.clone(),
+            self.block_device.clone(),
+        ))
+    }
+
+    /// Whether current inode is a directory
+    pub fn is_dir(&self) -> bool {
+        let _fs = self.fs.lock();
+        self.read_disk_inode(|disk_inode| disk_inode.is_dir())
+    }
+
+    /// Permission bits of current inode
+    pub fn mode(&self) -> u16 {
+        let _fs = self.fs.lock();
+        self.read_disk_inode(|disk_inode| disk_inode.mode())
+    }
+
+    /// Size of current inode in bytes
+    pub fn size(&self) -> usize {
+        let _fs = self.fs.lock();
+        self.read_disk_inode(|disk_inode| disk_inode.size as usize)
+    }
+
     /// Find inode under current inode by name
     pub fn find(&self, name: &str) -> Option<Arc<Inode>> {
         let fs = self.fs.lock();
         self.read_disk_inode(|disk_inode| {
-            self.find_inode_id(name, disk_inode).map(|inode_id| {
-                let (block_id, block_offset) = fs.get_disk_inode_pos(inode_id);
-                Arc::new(Self::new(
-                    block_id,
-                    block_offset,
-                    self.fs.clone(),
-                    self.block_device.clone(),
-                ))
-            })
+            self.find_inode_id(name, disk_inode)
+                .map(|inode_id| self.from_id(&fs, inode_id))
         })
     }
 
-    /// Increase the size of a disk inode
+    /// Increase the size of a disk inode, which requires the filesystem lock.
+    /// Return false and leave the size unchanged if not enough blocks are free.
     fn increase_size(
         &self,
         new_size: u32,
         disk_inode: &mut DiskInode,
         fs: &mut SpinLockGuard<EasyFileSystem>,
-    ) {
+    ) -> bool {
         if new_size < disk_inode.size {
-            return;
+            return true;
         }
         let blocks_needed = disk_inode.blocks_num_needed(new_size);
         let mut v: Vec<u32> = Vec::new();
         for _ in 0..blocks_needed {
-            v.push(fs.alloc_data());
+            match fs.alloc_data() {
+                Some(block_id) => v.push(block_id),
+                None => {
+                    for block_id in v {
+                        fs.dealloc_data(block_id);
+                    }
+                    return false;
+                }
+            }
         }
         disk_inode.increase_size(new_size, v, &self.block_device);
+        true
     }
 
     /// Create inode under current inode by name.
-    /// Attention: use find previously to ensure the new file not existing.
-    pub fn create(&self, name: &str) -> Option<Arc<Inode>> {
+    /// Return [`FsError::Exists`] if the name already exists, or [`FsError::NoSpace`]
+    /// if no inode or block is free.
+    pub fn create(&self, name: &str) -> Result<Arc<Inode>, FsError> {
+        self.create_inode(name, DiskInodeType::File, DEFAULT_MODE)
+    }
+
+    /// Create a directory with permission bits `mode` under current inode by name.
+    /// Return [`FsError::Exists`] if the name already exists, or [`FsError::NoSpace`]
+    /// if no inode or block is free.
+    pub fn create_dir(&self, name: &str, mode: u16) -> Result<Arc<Inode>, FsError> {
+        self.create_inode(name, DiskInodeType::Directory, mode)
+    }
+
+    /// Create inode of the given type under current inode by name
+    fn create_inode(
+        &self,
+        name: &str,
+        type_: DiskInodeType,
+        mode: u16,
+    ) -> Result<Arc<Inode>, FsError> {
         let mut fs = self.fs.lock();
+        // check and create with the same lock held
+        if self
+            .read_disk_inode(|disk_inode| self.find_inode_id(name, disk_inode))
+            .is_some()
+        {
+            return Err(FsError::Exists);
+        }
         // create a new file
         // alloc a inode with an indirect block
-        let new_inode_id = fs.alloc_inode();
+        let new_inode_id = fs.alloc_inode().ok_or(FsError::NoSpace)?;
         // initialize inode
         let (new_inode_block_id, new_inode_block_offset) = fs.get_disk_inode_pos(new_inode_id);
         get_block_cache(new_inode_block_id as usize, Arc::clone(&self.block_device))
             .lock()
             .write(new_inode_block_offset, |new_inode: &mut DiskInode| {
-                new_inode.initialize(DiskInodeType::File);
+                new_inode.initialize(type_, mode);
             });
-        self.modify_disk_inode(|root_inode| {
-            // append file in the dirent
-            let file_count = (root_inode.size as usize) / DIRENT_SZ;
-            let new_size = (file_count + 1) * DIRENT_SZ;
-            // increase size
-            self.increase_size(new_size as u32, root_inode, &mut fs);
-            // write dirent
-            let dirent = DirEntry::new(name, new_inode_id);
-            root_inode.write_at(
-                file_count * DIRENT_SZ,
-                dirent.as_bytes(),
-                &self.block_device,
-            );
-        });
+        // write dirent
+        if !self.modify_disk_inode(|root_inode| {
+            self.add_dirent(name, new_inode_id, root_inode, &mut fs)
+        }) {
+            fs.dealloc_inode(new_inode_id);
+            return Err(FsError::NoSpace);
+        }
 
         let (block_id, block_offset) = fs.get_disk_inode_pos(new_inode_id);
         block_cache_sync_all();
         // return inode
-        Some(Arc::new(Self::new(
+        Ok(Arc::new(Self::new(
             block_id,
             block_offset,
             self.fs.clone(),
@@ -138,6 +235,83 @@ impl Inode {
         // release efs lock automatically by compiler
     }
 
+    /// Remove the dirent of a name under current inode, then release the data
+    /// and the inode it refers to.
+    ///
+    /// Return false if the name does not exist.
+    pub fn unlink(&self, name: &str) -> bool {
+        let mut fs = self.fs.lock();
+        let inode_id = self.modify_disk_inode(|disk_inode| {
+            let (index, inode_id) = self.find_dirent(name, disk_inode)?;
+            let dirent = DirEntry::empty();
+            disk_inode.write_at(index * DIRENT_SZ, dirent.as_bytes(), &self.block_device);
+            Some(inode_id)
+        });
+        match inode_id {
+            Some(inode_id) => {
+                self.from_id(&fs, inode_id).release(inode_id, &mut fs);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Move the dirent of `old_name` under current inode to `new_name` under `new_dir`.
+    /// The inode referred by `new_name` is released if it exists.
+    ///
+    /// Return [`FsError::NotFound`] if `old_name` does not exist, or
+    /// [`FsError::NoSpace`] if `new_dir` cannot grow.
+    pub fn rename(&self, old_name: &str, new_dir: &Inode, new_name: &str) -> Result<(), FsError> {
+        let mut fs = self.fs.lock();
+        let inode_id =
+            match self.read_disk_inode(|disk_inode| self.find_dirent(old_name, disk_inode)) {
+                Some((_, inode_id)) => inode_id,
+                None => return Err(FsError::NotFound),
+            };
+        let same_dir =
+            self.block_id == new_dir.block_id && self.block_offset == new_dir.block_offset;
+        if same_dir && old_name == new_name {
+            return Ok(());
+        }
+        let replaced = new_dir.modify_disk_inode(|disk_inode| {
+            match new_dir.find_dirent(new_name, disk_inode) {
+                Some((index, replaced)) => {
+                    let dirent = DirEntry::new(new_name, inode_id);
+                    disk_inode.write_at(index * DIRENT_SZ, dirent.as_bytes(), &self.block_device);
+                    Ok(Some(replaced))
+                }
+                None if new_dir.add_dirent(new_name, inode_id, disk_inode, &mut fs) => Ok(None),
+                None => Err(FsError::NoSpace),
+            }
+        })?;
+        self.modify_disk_inode(|disk_inode| {
+            // The old dirent must be found again, since it might be moved in the same directory.
+            let index = (0..(disk_inode.size as usize) / DIRENT_SZ).find(|&i| {
+                let mut dirent = DirEntry::empty();
+                disk_inode.read_at(i * DIRENT_SZ, dirent.as_bytes_mut(), &self.block_device);
+                dirent.name() == old_name && dirent.inode_number() == inode_id
+            });
+            if let Some(index) = index {
+                let dirent = DirEntry::empty();
+                disk_inode.write_at(index * DIRENT_SZ, dirent.as_bytes(), &self.block_device);
+            }
+        });
+        match replaced {
+            Some(replaced) if replaced != inode_id => {
+                self.from_id(&fs, replaced).release(replaced, &mut fs);
+            }
+            _ => block_cache_sync_all(),
+        }
+        Ok(())
+    }
+
+    /// Release the data and the inode id of current inode
+    fn release(&self, inode_id: u32, fs: &mut SpinLockGuard<EasyFileSystem>) {
+        self.clear_data(fs);
+        fs.dealloc_inode(inode_id);
+        block_cache_sync_all();
+    }
+
     /// List inodes by id under current inode
     pub fn readdir(&self) -> Vec<String> {
         let _fs = self.fs.lock();
@@ -150,7 +324,9 @@ impl Inode {
                     disk_inode.read_at(i * DIRENT_SZ, dirent.as_bytes_mut(), &self.block_device,),
                     DIRENT_SZ,
                 );
-                v.push(String::from(dirent.name()));
+                if !dirent.name().is_empty() {
+                    v.push(String::from(dirent.name()));
+                }
             }
             v
         })
@@ -166,8 +342,11 @@ impl Inode {
     pub fn write_at(&self, offset: usize, buf: &[u8]) -> usize {
         let mut fs = self.fs.lock();
         let size = self.modify_disk_inode(|disk_inode| {
-            self.increase_size((offset + buf.len()) as u32, disk_inode, &mut fs);
-            disk_inode.write_at(offset, buf, &self.block_device)
+            if self.increase_size((offset + buf.len()) as u32, disk_inode, &mut fs) {
+                disk_inode.write_at(offset, buf, &self.block_device)
+            } else {
+                0
+            }
         });
         block_cache_sync_all();
         size
@@ -176,6 +355,12 @@ impl Inode {
     /// Clear the data in current inode
     pub fn clear(&self) {
         let mut fs = self.fs.lock();
+        self.clear_data(&mut fs);
+        block_cache_sync_all();
+    }
+
+    /// Deallocate data blocks of current inode, which requires the filesystem lock
+    fn clear_data(&self, fs: &mut SpinLockGuard<EasyFileSystem>) {
         self.modify_disk_inode(|disk_inode| {
             let size = disk_inode.size;
             let data_blocks_dealloc = disk_inode.clear_size(&self.block_device);
@@ -184,6 +369,5 @@ impl Inode {
                 fs.dealloc_data(data_block);
             }
         });
-        block_cache_sync_all();
     }
 }
@@ -1,8 +1,12 @@
 #![no_std]
 #![allow(non_camel_case_types)]
 
+use core::fmt;
 use numeric_enum_macro::numeric_enum;
 
+#[cfg(test)]
+mod test;
+
 numeric_enum! {
     #[repr(isize)]
     #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash, Clone, Copy)]
@@ -269,3 +273,165 @@ numeric_enum! {
         EKEYREJECTED = 129,
     }
 }
+
+impl Errno {
+    /// Returns the standard message describing the errno, as `strerror(3)` does.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Errno::NONE => "Success",
+            Errno::EPERM => "Operation not permitted",
+            Errno::ENOENT => "No such file or directory",
+            Errno::ESRCH => "No such process",
+            Errno::EINTR => "Interrupted system call",
+            Errno::EIO => "I/O error",
+            Errno::ENXIO => "No such device or address",
+            Errno::E2BIG => "Argument list too long",
+            Errno::ENOEXEC => "Exec format error",
+            Errno::EBADF => "Bad file number",
+            Errno::ECHILD => "No child processes",
+            Errno::EAGAIN => "Try again",
+            Errno::ENOMEM => "Out of memory",
+            Errno::EACCES => "Permission denied",
+            Errno::EFAULT => "Bad address",
+            Errno::ENOTBLK => "Block device required",
+            Errno::EBUSY => "Device or resource busy",
+            Errno::EEXIST => "File exists",
+            Errno::EXDEV => "Cross-device link",
+            Errno::ENODEV => "No such device",
+            Errno::ENOTDIR => "Not a directory",
+            Errno::EISDIR => "Is a directory",
+            Errno::EINVAL => "Invalid argument",
+            Errno::ENFILE => "File table overflow",
+            Errno::EMFILE => "Too many open files",
+            Errno::ENOTTY => "Not a typewriter",
+            Errno::ETXTBSY => "Text file busy",
+            Errno::EFBIG => "File too large",
+            Errno::ENOSPC => "No space left on device",
+            Errno::ESPIPE => "Illegal seek",
+            Errno::EROFS => "Read-only file system",
+            Errno::EMLINK => "Too many links",
+            Errno::EPIPE => "Broken pipe",
+            Errno::EDOM => "Math argument out of domain of func",
+            Errno::ERANGE => "Math result not representable",
+            Errno::EDEADLK => "Resource deadlock would occur",
+            Errno::ENAMETOOLONG => "File name too long",
+            Errno::ENOLCK => "No record locks available",
+            Errno::ENOSYS => "Function not implemented",
+            Errno::ENOTEMPTY => "Directory not empty",
+            Errno::ELOOP => "Too many symbolic links encountered",
+            Errno::EWOULDBLOCK => "Operation would block",
+            Errno::ENOMSG => "No message of desired type",
+            Errno::EIDRM => "Identifier removed",
+            Errno::ECHRNG => "Channel number out of range",
+            Errno::EL2NSYNC => "Level 2 not synchronized",
+            Errno::EL3HLT => "Level 3 halted",
+            Errno::EL3RST => "Level 3 reset",
+            Errno::ELNRNG => "Link number out of range",
+            Errno::EUNATCH => "Protocol driver not attached",
+            Errno::ENOCSI => "No CSI structure available",
+            Errno::EL2HLT => "Level 2 halted",
+            Errno::EBADE => "Invalid exchange",
+            Errno::EBADR => "Invalid request descriptor",
+            Errno::EXFULL => "Exchange full",
+            Errno::ENOANO => "No anode",
+            Errno::EBADRQC => "Invalid request code",
+            Errno::EBADSLT => "Invalid slot",
+            Errno::EBFONT => "Bad font file format",
+            Errno::ENOSTR => "Device not a stream",
+            Errno::ENODATA => "No data available",
+            Errno::ETIME => "Timer expired",
+            Errno::ENOSR => "Out of streams resources",
+            Errno::ENONET => "Machine is not on the network",
+            Errno::ENOPKG => "Package not installed",
+            Errno::EREMOTE => "Object is remote",
+            Errno::ENOLINK => "Link has been severed",
+            Errno::EADV => "Advertise error",
+            Errno::ESRMNT => "Srmount error",
+            Errno::ECOMM => "Communication error on send",
+            Errno::EPROTO => "Protocol error",
+            Errno::EMULTIHOP => "Multihop attempted",
+            Errno::EDOTDOT => "RFS specific error",
+            Errno::EBADMSG => "Not a data message",
+            Errno::EOVERFLOW => "Value too large for defined data type",
+            Errno::ENOTUNIQ => "Name not unique on network",
+            Errno::EBADFD => "File descriptor in bad state",
+            Errno::EREMCHG => "Remote address changed",
+            Errno::ELIBACC => "Can not access a needed shared library",
+            Errno::ELIBBAD => "Accessing a corrupted shared library",
+            Errno::ELIBSCN => ".lib section in a.out corrupted",
+            Errno::ELIBMAX => "Attempting to link in too many shared libraries",
+            Errno::ELIBEXEC => "Cannot exec a shared library directly",
+            Errno::EILSEQ => "Illegal byte sequence",
+            Errno::ERESTART => "Interrupted system call should be restarted",
+            Errno::ESTRPIPE => "Streams pipe error",
+            Errno::EUSERS => "Too many users",
+            Errno::ENOTSOCK => "Socket operation on non-socket",
+            Errno::EDESTADDRREQ => "Destination address required",
+            Errno::EMSGSIZE => "Message too long",
+            Errno::EPROTOTYPE => "Protocol wrong type for socket",
+            Errno::ENOPROTOOPT => "Protocol not available",
+            Errno::EPROTONOSUPPORT => "Protocol not supported",
+            Errno::ESOCKTNOSUPPORT => "Socket type not supported",
+            Errno::EOPNOTSUPP => "Operation not supported on transport endpoint",
+            Errno::EPFNOSUPPORT => "Protocol family not supported",
+            Errno::EAFNOSUPPORT => "Address family not supported by protocol",
+            Errno::EADDRINUSE => "Address already in use",
+            Errno::EADDRNOTAVAIL => "Cannot assign requested address",
+            Errno::ENETDOWN => "Network is down",
+            Errno::ENETUNREACH => "Network is unreachable",
+            Errno::ENETRESET => "Network dropped connection because of reset",
+            Errno::ECONNABORTED => "Software caused connection abort",
+            Errno::ECONNRESET => "Connection reset by peer",
+            Errno::ENOBUFS => "No buffer space available",
+            Errno::EISCONN => "Transport endpoint is already connected",
+            Errno::ENOTCONN => "Transport endpoint is not connected",
+            Errno::ESHUTDOWN => "Cannot send after transport endpoint shutdown",
+            Errno::ETOOMANYREFS => "Too many references: cannot splice",
+            Errno::ETIMEDOUT => "Connection timed out",
+            Errno::ECONNREFUSED => "Connection refused",
+            Errno::EHOSTDOWN => "Host is down",
+            Errno::EHOSTUNREACH => "No route to host",
+            Errno::EALREADY => "Operation already in progress",
+            Errno::EINPROGRESS => "Operation now in progress",
+            Errno::ESTALE => "Stale file handle",
+            Errno::EUCLEAN => "Structure needs cleaning",
+            Errno::ENOTNAM => "Not a XENIX named type file",
+            Errno::ENAVAIL => "No XENIX semaphores available",
+            Errno::EISNAM => "Is a named type file",
+            Errno::EREMOTEIO => "Remote I/O error",
+            Errno::EDQUOT => "Quota exceeded",
+            Errno::ENOMEDIUM => "No medium found",
+            Errno::EMEDIUMTYPE => "Wrong medium type",
+            Errno::ECANCELED => "Operation Canceled",
+            Errno::ENOKEY => "Required key not available",
+            Errno::EKEYEXPIRED => "Key has expired",
+            Errno::EKEYREVOKED => "Key has been revoked",
+            Errno::EKEYREJECTED => "Key was rejected by service",
+        }
+    }
+
+    /// Encodes the errno as a syscall return value, which is the negated errno
+    /// number placed in the return register.
+    pub fn as_ret(self) -> usize {
+        -isize::from(self) as usize
+    }
+
+    /// Decodes a syscall return value.
+    ///
+    /// Returns `None` if the value is not in the range of negated errno numbers,
+    /// which means the syscall succeeded.
+    pub fn from_ret(ret: usize) -> Option<Self> {
+        let ret = ret as isize;
+        if ret < 0 {
+            Self::try_from(-ret).ok()
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Errno {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
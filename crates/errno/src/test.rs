@@ -0,0 +1,42 @@
+use crate::Errno;
+
+/// Errno numbers defined in Linux, excluding the gap at 58.
+fn all_errnos() -> impl Iterator<Item = Errno> {
+    (1..=129).filter_map(|num| Errno::try_from(num as isize).ok())
+}
+
+#[test]
+fn test_errno_isize() {
+    assert_eq!(all_errnos().count(), 128);
+    for errno in all_errnos() {
+        assert_eq!(Errno::try_from(isize::from(errno)), Ok(errno));
+    }
+    assert!(Errno::try_from(58).is_err());
+    assert!(Errno::try_from(130).is_err());
+}
+
+#[test]
+fn test_errno_ret() {
+    for errno in all_errnos() {
+        let ret = errno.as_ret();
+        assert!(ret as isize >= -4095 && (ret as isize) < 0);
+        assert_eq!(Errno::from_ret(ret), Some(errno));
+    }
+    assert_eq!(Errno::from_ret(0), None);
+    assert_eq!(Errno::from_ret(42), None);
+}
+
+#[test]
+fn test_errno_description() {
+    extern crate std;
+    use std::string::ToString;
+
+    assert_eq!(Errno::ENOENT.description(), "No such file or directory");
+    assert_eq!(Errno::EPERM.description(), "Operation not permitted");
+    assert_eq!(Errno::EAGAIN.description(), "Try again");
+    assert_eq!(Errno::EBADF.to_string(), "Bad file number");
+    assert_eq!(Errno::ENOTTY.to_string(), "Not a typewriter");
+    for errno in all_errnos() {
+        assert!(!errno.description().is_empty());
+    }
+}
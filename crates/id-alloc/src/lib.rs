@@ -2,37 +2,51 @@
 
 extern crate alloc;
 
-use alloc::vec::Vec;
+use alloc::{vec, vec::Vec};
 use core::option::Option::Some;
 
 /// Allocate identifications using different algorithms
 pub trait IDAllocator {
-    fn alloc(&mut self) -> usize;
+    /// Allocates a new id, or returns `None` if all ids are in use.
+    fn alloc(&mut self) -> Option<usize>;
     fn dealloc(&mut self, id: usize);
 }
 
+/// Allocates ids in `[start, limit)`, reusing deallocated ids first.
 pub struct RecycleAllocator {
     current: usize,
+    limit: usize,
     recycled: Vec<usize>,
 }
 
 impl RecycleAllocator {
     pub fn new(current: usize) -> Self {
+        Self::with_limit(current, usize::MAX)
+    }
+
+    pub fn with_limit(start: usize, limit: usize) -> Self {
         Self {
-            current,
+            current: start,
+            limit,
             recycled: Vec::new(),
         }
     }
+
+    /// Allocates a new id, panicking if all ids are in use.
+    pub fn alloc_unchecked(&mut self) -> usize {
+        self.alloc().expect("ids exhausted")
+    }
 }
 
 impl IDAllocator for RecycleAllocator {
-    fn alloc(&mut self) -> usize {
+    fn alloc(&mut self) -> Option<usize> {
         if let Some(id) = self.recycled.pop() {
-            id
-        } else {
+            Some(id)
+        } else if self.current < self.limit {
             self.current += 1;
-            assert_ne!(self.current, usize::MAX);
-            self.current - 1
+            Some(self.current - 1)
+        } else {
+            None
         }
     }
 
@@ -41,6 +55,61 @@ impl IDAllocator for RecycleAllocator {
     }
 }
 
+/// Allocates the lowest free id within a fixed capacity.
+pub struct BitmapAllocator {
+    capacity: usize,
+    bitmap: Vec<u64>,
+}
+
+impl BitmapAllocator {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            bitmap: vec![0; capacity.div_ceil(64)],
+        }
+    }
+
+    /// Allocates the given id.
+    ///
+    /// Returns false if the id is out of range or already in use.
+    pub fn alloc_fixed(&mut self, id: usize) -> bool {
+        if id >= self.capacity || self.is_allocated(id) {
+            return false;
+        }
+        self.bitmap[id / 64] |= 1 << (id % 64);
+        true
+    }
+
+    /// Returns true if the id is in use.
+    pub fn is_allocated(&self, id: usize) -> bool {
+        id < self.capacity && self.bitmap[id / 64] & (1 << (id % 64)) != 0
+    }
+}
+
+impl IDAllocator for BitmapAllocator {
+    /// Allocates the lowest free id.
+    fn alloc(&mut self) -> Option<usize> {
+        for (i, bits) in self.bitmap.iter_mut().enumerate() {
+            if *bits == u64::MAX {
+                continue;
+            }
+            let id = i * 64 + (!*bits).trailing_zeros() as usize;
+            if id >= self.capacity {
+                return None;
+            }
+            *bits |= 1 << (id % 64);
+            return Some(id);
+        }
+        None
+    }
+
+    /// Panics if the id is not in use.
+    fn dealloc(&mut self, id: usize) {
+        assert!(self.is_allocated(id), "id {} deallocated twice", id);
+        self.bitmap[id / 64] &= !(1 << (id % 64));
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -48,10 +117,54 @@ mod tests {
     #[test]
     fn test_id_alloc() {
         let mut r = RecycleAllocator::new(0);
-        assert_eq!(r.alloc(), 0);
-        assert_eq!(r.alloc(), 1);
-        assert_eq!(r.alloc(), 2);
+        assert_eq!(r.alloc(), Some(0));
+        assert_eq!(r.alloc(), Some(1));
+        assert_eq!(r.alloc(), Some(2));
         r.dealloc(1);
-        assert_eq!(r.alloc(), 1);
+        assert_eq!(r.alloc(), Some(1));
+    }
+
+    #[test]
+    fn test_id_alloc_limit() {
+        let mut r = RecycleAllocator::with_limit(1, 4);
+        assert_eq!(r.alloc(), Some(1));
+        assert_eq!(r.alloc(), Some(2));
+        assert_eq!(r.alloc_unchecked(), 3);
+        assert_eq!(r.alloc(), None);
+        r.dealloc(2);
+        assert_eq!(r.alloc(), Some(2));
+        assert_eq!(r.alloc(), None);
+    }
+
+    #[test]
+    fn test_bitmap_exhausted() {
+        let mut b = BitmapAllocator::new(65);
+        for i in 0..65 {
+            assert_eq!(b.alloc(), Some(i));
+        }
+        assert_eq!(b.alloc(), None);
+        b.dealloc(3);
+        assert!(!b.is_allocated(3));
+        assert_eq!(b.alloc(), Some(3));
+    }
+
+    #[test]
+    fn test_bitmap_fixed() {
+        let mut b = BitmapAllocator::new(8);
+        assert!(b.alloc_fixed(0));
+        assert!(b.alloc_fixed(2));
+        assert!(!b.alloc_fixed(2));
+        assert!(!b.alloc_fixed(8));
+        assert_eq!(b.alloc(), Some(1));
+        assert_eq!(b.alloc(), Some(3));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bitmap_double_free() {
+        let mut b = BitmapAllocator::new(8);
+        let id = b.alloc().unwrap();
+        b.dealloc(id);
+        b.dealloc(id);
     }
 }
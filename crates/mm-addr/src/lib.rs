@@ -17,6 +17,23 @@ pub use core::{
 pub use derive_more::*;
 pub use paste::paste;
 
+/// A size in bytes displayed in the largest binary unit dividing it, e.g. `2 MiB`,
+/// `512 KiB` or `100 B`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HumanSize(pub usize);
+
+impl fmt::Display for HumanSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        const UNITS: [(&str, usize); 3] = [("GiB", 1 << 30), ("MiB", 1 << 20), ("KiB", 1 << 10)];
+        for (unit, size) in UNITS {
+            if self.0 != 0 && self.0 % size == 0 {
+                return write!(f, "{} {}", self.0 / size, unit);
+            }
+        }
+        write!(f, "{} B", self.0)
+    }
+}
+
 /// A macro for defining `VirtualAddress` and `PhysicalAddress` structs and implementing their
 /// common traits.
 #[macro_export]
@@ -83,6 +100,27 @@ macro_rules! implement_address {
                     self.[<$chunk _offset>]() == 0
                 }
 
+                /// Returns if the address is aligned to `align`, which must be a power of two.
+                pub const fn is_aligned_to(&self, align: usize) -> bool {
+                    debug_assert!(align.is_power_of_two());
+                    self.0 & (align - 1) == 0
+                }
+
+                /// Rounds the address up to the multiple of `align`, which must be a power of two.
+                ///
+                /// The same as `Add<usize>`, the address saturates at the top of the address
+                /// space, thus the result is the last aligned address.
+                pub const fn align_up(&self, align: usize) -> $TypeName {
+                    debug_assert!(align.is_power_of_two());
+                    $TypeName::new_canonical(self.0.saturating_add(align - 1) & !(align - 1))
+                }
+
+                /// Rounds the address down to the multiple of `align`, which must be a power of two.
+                pub const fn align_down(&self, align: usize) -> $TypeName {
+                    debug_assert!(align.is_power_of_two());
+                    $TypeName(self.0 & !(align - 1))
+                }
+
                 #[doc ="Returns an immutable reference of `T` starting from the physical address."]
                 pub fn get_ref<T>(&self) -> &'static T {
                     unsafe { (self.0 as *const T).as_ref().unwrap() }
@@ -362,6 +400,11 @@ macro_rules! implement_page_frame_range {
                     self.[<size_in_ $chunk:lower s>]() * $page_size
                 }
 
+                /// Returns the size of this range in human units, e.g. `2 MiB`.
+                pub const fn size_in_bytes_human(&self) -> $crate::HumanSize {
+                    $crate::HumanSize(self.size_in_bytes())
+                }
+
                 #[doc = "Returns `true` if this `" $TypeName "` contains the given \
                     [`" $address "`]."]
                 pub fn contains_address(&self, addr: $address) -> bool {
@@ -434,6 +477,18 @@ macro_rules! implement_page_frame_range {
                     write!(f, "{:?} -> {:?}", self.start, self.end)
                 }
             }
+            impl fmt::Display for $TypeName {
+                fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    write!(
+                        f,
+                        concat!("[{:#X}..{:#X}] ({} ", stringify!([<$chunk:lower s>]), ", {})"),
+                        self.start.start_address(),
+                        self.end.start_address(),
+                        self.[<size_in_ $chunk:lower s>](),
+                        self.size_in_bytes_human()
+                    )
+                }
+            }
             impl IntoIterator for $TypeName {
                 type Item = $chunk;
                 type IntoIter = Range<$chunk>;
@@ -1,6 +1,6 @@
 extern crate std;
 
-use self::std::println;
+use self::std::{format, println};
 
 use super::*;
 
@@ -49,3 +49,49 @@ fn it_works() {
     assert!(iter.next() == Some(4));
     assert!(iter.next() == None);
 }
+
+#[test]
+fn test_align() {
+    const MIB2: usize = 0x20_0000;
+    for align in [16, 0x1000, MIB2] {
+        assert_eq!(VirtAddr::zero().align_up(align), VirtAddr::zero());
+        assert_eq!(VirtAddr::zero().align_down(align), VirtAddr::zero());
+
+        // already aligned
+        let va = VirtAddr::new(3 * align).unwrap();
+        assert!(va.is_aligned_to(align));
+        assert_eq!(va.align_up(align), va);
+        assert_eq!(va.align_down(align), va);
+
+        let va = VirtAddr::new(3 * align + 1).unwrap();
+        assert!(!va.is_aligned_to(align));
+        assert_eq!(va.align_up(align).value(), 4 * align);
+        assert_eq!(va.align_down(align).value(), 3 * align);
+
+        let va = VirtAddr::new(4 * align - 1).unwrap();
+        assert_eq!(va.align_up(align).value(), 4 * align);
+        assert_eq!(va.align_down(align).value(), 3 * align);
+
+        // saturates at the top
+        let va = VirtAddr::new(usize::MAX).unwrap();
+        assert_eq!(va.align_up(align).value(), usize::MAX & !(align - 1));
+    }
+    assert!(VirtAddr::new(MIB2).unwrap().is_aligned_to(0x1000));
+    assert!(!VirtAddr::new(0x1000).unwrap().is_aligned_to(MIB2));
+    assert_eq!(VirtAddr::new(0x1234).unwrap().align_up(MIB2).value(), MIB2);
+}
+
+#[test]
+fn test_human_size() {
+    assert_eq!(format!("{}", HumanSize(0)), "0 B");
+    assert_eq!(format!("{}", HumanSize(100)), "100 B");
+    assert_eq!(format!("{}", HumanSize(4096)), "4 KiB");
+    assert_eq!(format!("{}", HumanSize(0x8_0000)), "512 KiB");
+    assert_eq!(format!("{}", HumanSize(0x30_0000)), "3 MiB");
+    assert_eq!(format!("{}", HumanSize(0x1000_1000)), "262148 KiB");
+
+    let pr = PageRange::new(Page::from(1), Page::from(513));
+    assert_eq!(format!("{}", pr.size_in_bytes_human()), "2 MiB");
+    assert_eq!(format!("{}", pr), "[0x1000..0x201000] (512 pages, 2 MiB)");
+    assert_eq!(format!("{:?}", pr), "Page(0x1000) -> Page(0x201000)");
+}
@@ -41,6 +41,9 @@ pub struct AllocatedFrame {
 impl AllocatedFrame {
     /// Allocates a single frame.
     /// Use global allocator to track allocated frames.
+    ///
+    /// The frame is guaranteed to be filled with zeros if `flush` is set, otherwise its
+    /// contents are left over by the last owner.
     pub fn new(flush: bool) -> Result<Self, &'static str> {
         if let Some(frame) = frame_alloc(1) {
             let frame = Frame::from(frame);
@@ -94,7 +97,8 @@ impl AllocatedFrameRange {
     /// # Argument
     ///
     /// - `count`: the number of frames
-    /// - `flush`: if set, flush the memory region with the start address of allocated frames.
+    /// - `flush`: if set, fill all allocated frames with zeros, otherwise their contents are
+    ///   left over by the last owners.
     ///
     /// # Return
     ///
@@ -124,6 +128,32 @@ impl AllocatedFrameRange {
         }
     }
 
+    /// Allocates `count` contiguous frames filled with zeros, e.g. for BSS or fresh anonymous
+    /// mappings.
+    pub fn new_zeroed(count: usize) -> Result<Self, &'static str> {
+        Self::new(count, true)
+    }
+
+    /// Returns an immutable slice over all frames in this range.
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe {
+            core::slice::from_raw_parts(
+                self.start_address().value() as *const u8,
+                self.size_in_bytes(),
+            )
+        }
+    }
+
+    /// Returns a mutable slice over all frames in this range.
+    pub fn as_slice_mut(&mut self) -> &mut [u8] {
+        unsafe {
+            core::slice::from_raw_parts_mut(
+                self.start_address().value() as *mut u8,
+                self.size_in_bytes(),
+            )
+        }
+    }
+
     /// Splits this [`AllocatedFrameRange`] into two separate objects:
     /// - `[beginning : at_frame - 1]`
     /// - `[at_frame : end]`
@@ -131,7 +161,7 @@ impl AllocatedFrameRange {
     /// Returns [`None`] if `at_frame` is otherwise out of bounds.
     pub fn split_at(&mut self, at_frame: Frame, new_below: bool) -> Option<Self> {
         let (left, right) = if at_frame == self.start {
-            (FrameRange::empty(), FrameRange::new(at_frame, self.start))
+            (FrameRange::empty(), FrameRange::new(at_frame, self.end))
         } else if at_frame == self.end {
             (FrameRange::new(self.start, at_frame), FrameRange::empty())
         } else if at_frame > self.start && at_frame < self.end {
@@ -166,7 +196,11 @@ impl fmt::Debug for AllocatedFrameRange {
 }
 
 impl Drop for AllocatedFrameRange {
+    /// Deallocates all frames in this range, which are owned by no other object since
+    /// [`Self::split_at`] leaves disjoint ranges.
     fn drop(&mut self) {
-        frame_dealloc(self.start.number(), self.size_in_frames());
+        if !self.is_empty() {
+            frame_dealloc(self.start.number(), self.size_in_frames());
+        }
     }
 }
@@ -8,127 +8,304 @@ use alloc::{
     string::String,
     vec::Vec,
 };
-use core::{fmt, slice::Iter};
+use core::{
+    fmt,
+    slice::Iter,
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+};
 use kernel_sync::SpinLock;
 use log::{debug, info, trace, warn};
-use spin::Lazy;
+use spin::{Lazy, Once};
+use time_subsys::TimeSpec;
 
+mod report;
+#[cfg(test)]
+mod test;
 pub mod testcases;
 
+pub use report::*;
+
+/// Manages oscomp testcases, which may be run by multiple harts concurrently.
 pub struct TestManger {
-    pub cases: Option<&'static [&'static str]>,
+    pub cases: Once<&'static [&'static str]>,
+
+    /// Index of the next testcase to be handed out
+    next: AtomicUsize,
 
     /// The number of passed tests
-    pub passed: usize,
+    passed: AtomicUsize,
 
-    /// Current test
-    pub running: BTreeMap<String, usize>,
+    /// The number of tests with results, which is updated after `passed` and `failed`
+    finished: AtomicUsize,
+
+    /// Set if the final results have been shown
+    reported: AtomicBool,
+
+    /// Start time of running instances of each test
+    running: SpinLock<BTreeMap<String, Vec<TimeSpec>>>,
 
     /// A list of failed tests
-    pub failed: Vec<String>,
+    failed: SpinLock<Vec<String>>,
+
+    /// Total run time of tests with the same name
+    durations: SpinLock<BTreeMap<String, TimeSpec>>,
+
+    /// Gets current time to measure tests
+    clock: Once<fn() -> TimeSpec>,
 }
 
 impl TestManger {
     pub fn new() -> Self {
         Self {
-            cases: None,
-            passed: 0,
-            running: BTreeMap::new(),
-            failed: Vec::new(),
+            cases: Once::new(),
+            next: AtomicUsize::new(0),
+            passed: AtomicUsize::new(0),
+            finished: AtomicUsize::new(0),
+            reported: AtomicBool::new(false),
+            running: SpinLock::new(BTreeMap::new()),
+            failed: SpinLock::new(Vec::new()),
+            durations: SpinLock::new(BTreeMap::new()),
+            clock: Once::new(),
         }
     }
 
-    /// Initialize the manager with target testcases.
-    pub fn init(&mut self, cases: &'static [&'static str]) {
-        self.cases = Some(cases);
+    /// Initialize the manager with target testcases, which are measured by `clock`.
+    pub fn init(&self, cases: &'static [&'static str], clock: fn() -> TimeSpec) {
+        self.cases.call_once(|| cases);
+        self.clock.call_once(|| clock);
+    }
+
+    /// Returns current time, or zero if the manager has not been initialized.
+    fn now(&self) -> TimeSpec {
+        self.clock.get().map_or(TimeSpec::default(), |clock| clock())
+    }
+
+    /// Returns the number of testcases.
+    pub fn total(&self) -> usize {
+        self.cases.get().map_or(0, |cases| cases.len())
+    }
+
+    /// Hands out up to `count` distinct testcases that have not been fetched yet,
+    /// and loads them. Each element contains the arguments of a test.
+    ///
+    /// Fewer testcases are returned if some of them are malformed.
+    pub fn fetch_n(&self, count: usize) -> Vec<Vec<String>> {
+        let cases = match self.cases.get() {
+            Some(cases) => *cases,
+            None => return Vec::new(),
+        };
+        // Claims a range of testcases, so that no testcase is handed out twice.
+        let mut start = self.next.load(Ordering::Acquire);
+        let end = loop {
+            let end = (start + count).min(cases.len());
+            match self
+                .next
+                .compare_exchange_weak(start, end, Ordering::AcqRel, Ordering::Acquire)
+            {
+                Ok(_) => break end,
+                Err(next) => start = next,
+            }
+        };
+        cases[start..end]
+            .iter()
+            .filter_map(|user_command| match split_argv(user_command) {
+                Some(argv) if !argv.is_empty() => {
+                    self.load(&argv[0]);
+                    Some(argv)
+                }
+                // Malformed testcases are never run but fail.
+                _ => {
+                    warn!("{} malformed", user_command);
+                    self.failed.lock().push(String::from(*user_command));
+                    self.finished.fetch_add(1, Ordering::AcqRel);
+                    None
+                }
+            })
+            .collect()
     }
 
     /// Load a test.
-    pub fn load(&mut self, name: &String) {
+    pub fn load(&self, name: &String) {
+        let now = self.now();
         self.running
+            .lock()
             .entry(name.clone())
-            .and_modify(|e| *e += 1)
-            .or_insert(1);
+            .or_default()
+            .push(now);
     }
 
     /// Update test result.
-    pub fn exit(&mut self, exit_code: i32, name: &String) {
-        if self.running.get(name).is_none() {
-            return;
-        }
+    ///
+    /// Instances of tests with the same name are assumed to exit in order.
+    pub fn exit(&self, exit_code: i32, name: &String) {
+        let mut running = self.running.lock();
+        let start = match running.get_mut(name) {
+            Some(starts) if !starts.is_empty() => starts.remove(0),
+            _ => return,
+        };
+        drop(running);
+        let duration = self.now() - start;
+        *self.durations.lock().entry(name.clone()).or_default() += duration;
         match exit_code {
             0 => {
                 debug!("{} passed", name);
-                self.passed += 1;
+                self.passed.fetch_add(1, Ordering::AcqRel);
             }
             _ => {
                 warn!("{} failed", name);
-                self.failed.push(name.clone());
+                self.failed.lock().push(name.clone());
             }
         }
-        self.running.entry(name.clone()).and_modify(|e| *e -= 1);
+        self.finished.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Returns the number of passed tests.
+    pub fn passed(&self) -> usize {
+        self.passed.load(Ordering::Acquire)
+    }
+
+    /// Returns the names of failed tests.
+    pub fn failed(&self) -> Vec<String> {
+        self.failed.lock().clone()
+    }
+
+    /// Returns true if all tests have results.
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire) == self.total()
+    }
+
+    /// Shows test status if all tests have results.
+    ///
+    /// Returns true only for the first caller that shows the results.
+    pub fn info_once(&self) -> bool {
+        if !self.is_finished() || self.reported.swap(true, Ordering::AcqRel) {
+            return false;
+        }
+        self.info();
+        true
+    }
+
+    /// Collects test results.
+    pub fn report(&self) -> TestReport {
+        TestReport {
+            total: self.total(),
+            passed: self.passed(),
+            failed: self.failed(),
+            durations: self.durations.lock().clone(),
+        }
+    }
+
+    /// Writes test results as a JSON document in a line between [`REPORT_BEGIN`] and
+    /// [`REPORT_END`], so that it can be picked out of the console output.
+    pub fn report_json(&self, out: &mut dyn fmt::Write) -> fmt::Result {
+        writeln!(out, "{}", REPORT_BEGIN)?;
+        self.report().write_json(out)?;
+        writeln!(out)?;
+        writeln!(out, "{}", REPORT_END)
     }
 
     /// Show test status
     pub fn info(&self) {
-        info!("Passed {} / {}", self.passed, self.cases.unwrap().len());
-        info!("Failed {} tests:", self.failed.len());
-        for test in &self.failed {
+        let failed = self.failed.lock();
+        info!("Passed {} / {}", self.passed(), self.total());
+        info!("Failed {} tests:", failed.len());
+        for test in failed.iter() {
             info!("\t {}", test);
         }
     }
 }
 
-fn split_argv(s: &[u8]) -> Vec<String> {
+/// Splits a command line into arguments like a shell.
+///
+/// Arguments are separated by runs of spaces or tabs outside quotes. A backslash
+/// escapes the next character, or only `"` and `\` inside quotes.
+///
+/// Returns `None` if a quote is not terminated.
+fn split_argv(s: &str) -> Option<Vec<String>> {
     let mut argv: Vec<String> = Vec::new();
+    let mut arg = String::new();
+    // Set if an argument has started, which may be empty, e.g. `""`.
+    let mut in_arg = false;
     let mut in_quotation = false;
-    let mut start = 0;
-    assert!(s.len() > 0);
-    for pos in 0..s.len() {
-        if s[pos] == '\"' as u8 {
-            in_quotation = !in_quotation;
-        } else if s[pos] == ' ' as u8 && !in_quotation {
-            if pos > start {
-                argv.push(core::str::from_utf8(&s[start..pos]).unwrap().into());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                in_arg = true;
+                match chars.clone().next() {
+                    Some(next) if !in_quotation || next == '"' || next == '\\' => {
+                        arg.push(next);
+                        chars.next();
+                    }
+                    _ => arg.push(c),
+                }
+            }
+            '"' => {
+                in_arg = true;
+                in_quotation = !in_quotation;
+            }
+            ' ' | '\t' if !in_quotation => {
+                if in_arg {
+                    argv.push(core::mem::take(&mut arg));
+                    in_arg = false;
+                }
+            }
+            _ => {
+                in_arg = true;
+                arg.push(c);
             }
-            start = pos + 1;
         }
     }
-    if start < s.len() {
-        argv.push(core::str::from_utf8(&s[start..]).unwrap().into());
+    if in_quotation {
+        return None;
+    }
+    if in_arg {
+        argv.push(arg);
     }
-    argv
+    Some(argv)
 }
 
-static TEST_MANAGER: Lazy<SpinLock<TestManger>> = Lazy::new(|| SpinLock::new(TestManger::new()));
-static TEST_ITER: Lazy<SpinLock<core::slice::Iter<&str>>> =
-    Lazy::new(|| SpinLock::new(TEST_MANAGER.lock().cases.unwrap().into_iter()));
+static TEST_MANAGER: Lazy<TestManger> = Lazy::new(TestManger::new);
+
+/// Prints to the console of the kernel.
+static CONSOLE: Once<fn(fmt::Arguments)> = Once::new();
 
-pub fn init(cases: &'static [&'static str]) {
-    TEST_MANAGER.lock().init(cases);
+/// Writes to [`CONSOLE`].
+struct Console;
+
+impl fmt::Write for Console {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if let Some(print) = CONSOLE.get() {
+            print(format_args!("{}", s));
+        }
+        Ok(())
+    }
+}
+
+/// Initializes testcases.
+///
+/// - `clock`: Gets current time to measure tests.
+/// - `console`: Prints the final results in JSON.
+pub fn init(
+    cases: &'static [&'static str],
+    clock: fn() -> TimeSpec,
+    console: fn(fmt::Arguments),
+) {
+    TEST_MANAGER.init(cases, clock);
+    CONSOLE.call_once(|| console);
 }
 
 /// Returns arguments of the test.
 pub fn fetch_test() -> Option<Vec<String>> {
-    TEST_ITER.lock().next().map_or_else(
-        || {
-            let test_manager = TEST_MANAGER.lock();
-            if test_manager.passed + test_manager.failed.len() == test_manager.cases.unwrap().len()
-            {
-                test_manager.info();
-                panic!("TEST END");
-            }
-            None
-        },
-        |&user_command| {
-            let argv = split_argv(user_command.as_bytes());
-            TEST_MANAGER.lock().load(&argv[0]);
-            Some(argv)
-        },
-    )
+    let test = TEST_MANAGER.fetch_n(1).pop();
+    if test.is_none() && TEST_MANAGER.info_once() {
+        TEST_MANAGER.report_json(&mut Console).unwrap();
+        panic!("TEST END");
+    }
+    test
 }
 
 /// Finish the test with exit code.
 pub fn finish_test(exit_code: i32, name: &String) {
-    TEST_MANAGER.lock().exit(exit_code, name);
+    TEST_MANAGER.exit(exit_code, name);
 }
@@ -0,0 +1,76 @@
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::fmt::{self, Write};
+use time_subsys::TimeSpec;
+
+/// Printed in a line before the JSON document of [`TestReport`].
+pub const REPORT_BEGIN: &str = "==== OSCOMP REPORT BEGIN ====";
+
+/// Printed in a line after the JSON document of [`TestReport`].
+pub const REPORT_END: &str = "==== OSCOMP REPORT END ====";
+
+/// Results of oscomp testcases, which can be parsed by harness outside.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TestReport {
+    /// The number of testcases
+    pub total: usize,
+
+    /// The number of passed tests
+    pub passed: usize,
+
+    /// A list of failed tests
+    pub failed: Vec<String>,
+
+    /// Total run time of tests with the same name
+    pub durations: BTreeMap<String, TimeSpec>,
+}
+
+impl TestReport {
+    /// Writes a JSON document in a single line, e.g.
+    /// `{"total":2,"passed":1,"failed":["b"],"durations":{"a":0.500000000,"b":1.000000000}}`,
+    /// where durations are in seconds.
+    pub fn write_json(&self, out: &mut dyn Write) -> fmt::Result {
+        write!(
+            out,
+            "{{\"total\":{},\"passed\":{},\"failed\":[",
+            self.total, self.passed
+        )?;
+        for (i, name) in self.failed.iter().enumerate() {
+            if i > 0 {
+                out.write_char(',')?;
+            }
+            write_json_str(out, name)?;
+        }
+        out.write_str("],\"durations\":{")?;
+        for (i, (name, time)) in self.durations.iter().enumerate() {
+            if i > 0 {
+                out.write_char(',')?;
+            }
+            write_json_str(out, name)?;
+            write!(out, ":{}.{:09}", time.tv_sec, time.tv_nsec)?;
+        }
+        out.write_str("}}")
+    }
+
+    /// Returns the JSON document written by [`TestReport::write_json`].
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write_json(&mut json).unwrap();
+        json
+    }
+}
+
+/// Writes a JSON string with quotes, backslashes and control characters escaped.
+fn write_json_str(out: &mut dyn Write, s: &str) -> fmt::Result {
+    out.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\t' => out.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
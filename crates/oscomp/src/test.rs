@@ -0,0 +1,178 @@
+#![cfg(test)]
+
+extern crate std;
+
+use alloc::{string::String, sync::Arc, vec::Vec};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::*;
+
+/// Current time in seconds, which is advanced by tests.
+static NOW: AtomicUsize = AtomicUsize::new(0);
+
+fn clock() -> TimeSpec {
+    TimeSpec {
+        tv_sec: NOW.load(Ordering::Acquire),
+        tv_nsec: 0,
+    }
+}
+
+const CASES: &[&str] = &[
+    "test_a",
+    "test_b",
+    "test_c 1",
+    "test_d",
+    "test_e",
+    "test_f \"x y\"",
+    "test_g",
+];
+
+#[test]
+fn test_fetch_n_two_harts() {
+    let manager = Arc::new(TestManger::new());
+    manager.init(CASES, clock);
+
+    // Each "hart" pulls at most two testcases at a time and runs them to exit.
+    let harts: Vec<_> = (0..2)
+        .map(|hart| {
+            let manager = manager.clone();
+            thread::spawn(move || {
+                let mut fetched = Vec::new();
+                let mut reported = 0;
+                loop {
+                    let tests = manager.fetch_n(2);
+                    if tests.is_empty() {
+                        if manager.info_once() {
+                            reported += 1;
+                        }
+                        if manager.is_finished() {
+                            break;
+                        }
+                        continue;
+                    }
+                    for argv in tests {
+                        // Odd harts fail their tests.
+                        manager.exit(hart, &argv[0]);
+                        fetched.push(argv[0].clone());
+                    }
+                }
+                (fetched, reported)
+            })
+        })
+        .collect();
+
+    let mut fetched = Vec::new();
+    let mut reported = 0;
+    for hart in harts {
+        let (names, count) = hart.join().unwrap();
+        fetched.extend(names);
+        reported += count;
+    }
+    fetched.sort();
+    let mut expected: Vec<String> = CASES
+        .iter()
+        .map(|case| String::from(case.split(' ').next().unwrap()))
+        .collect();
+    expected.sort();
+    assert_eq!(fetched, expected);
+    assert_eq!(reported, 1);
+    assert_eq!(manager.passed() + manager.failed().len(), CASES.len());
+    assert!(manager.fetch_n(1).is_empty());
+    assert!(!manager.info_once());
+}
+
+#[test]
+fn test_exit_unknown() {
+    let manager = TestManger::new();
+    manager.init(CASES, clock);
+    let argv = manager.fetch_n(1).pop().unwrap();
+    // Tests which are not running do not count.
+    manager.exit(0, &String::from("test_b"));
+    manager.exit(0, &argv[0]);
+    manager.exit(0, &argv[0]);
+    assert_eq!(manager.passed(), 1);
+    assert!(!manager.is_finished());
+}
+
+#[test]
+fn test_split_argv_quotes() {
+    assert_eq!(split_argv("echo \"a b\"").unwrap(), ["echo", "a b"]);
+    assert_eq!(split_argv("echo a\\\"b").unwrap(), ["echo", "a\"b"]);
+    assert_eq!(
+        split_argv("echo \"a\\\"b\\n\"").unwrap(),
+        ["echo", "a\"b\\n"]
+    );
+    assert_eq!(split_argv("echo \"\" x").unwrap(), ["echo", "", "x"]);
+    assert!(split_argv("echo \"a b").is_none());
+}
+
+#[test]
+fn test_split_argv_separators() {
+    assert_eq!(split_argv("echo\ta\t\tb").unwrap(), ["echo", "a", "b"]);
+    assert_eq!(split_argv("  echo \t a  ").unwrap(), ["echo", "a"]);
+    assert_eq!(split_argv("echo \"a\tb\"").unwrap(), ["echo", "a\tb"]);
+    assert!(split_argv(" \t ").unwrap().is_empty());
+}
+
+#[test]
+fn test_fetch_malformed() {
+    static MALFORMED: &[&str] = &["test_a \"x", "test_b"];
+    let manager = TestManger::new();
+    manager.init(MALFORMED, clock);
+    let tests = manager.fetch_n(2);
+    assert_eq!(tests.len(), 1);
+    assert_eq!(manager.failed(), ["test_a \"x"]);
+    manager.exit(0, &tests[0][0]);
+    assert!(manager.is_finished());
+}
+
+#[test]
+fn test_report_json() {
+    static SMALL: &[&str] = &["test_a", "test_b \"x\"", "test_a 1"];
+    let manager = TestManger::new();
+    manager.init(SMALL, clock);
+
+    let start = NOW.load(Ordering::Acquire);
+    let tests = manager.fetch_n(3);
+    NOW.store(start + 2, Ordering::Release);
+    manager.exit(0, &tests[0][0]);
+    manager.exit(1, &tests[1][0]);
+    manager.exit(0, &tests[2][0]);
+
+    let report = manager.report();
+    assert_eq!(report.total, 3);
+    assert_eq!(report.passed, 2);
+    assert_eq!(report.failed, ["test_b"]);
+    // Other tests may advance the clock as well.
+    assert!(report.durations["test_a"].tv_sec >= 4);
+
+    let mut out = String::new();
+    manager.report_json(&mut out).unwrap();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert_eq!(lines[0], REPORT_BEGIN);
+    assert!(
+        lines[1].starts_with("{\"total\":3,\"passed\":2,\"failed\":[\"test_b\"],\"durations\":{")
+    );
+    assert!(lines[1].contains("\"test_a\":"));
+    assert!(lines[1].ends_with("}}"));
+    assert_eq!(lines[2], REPORT_END);
+}
+
+#[test]
+fn test_report_json_escape() {
+    let mut report = TestReport::default();
+    report.failed.push(String::from("a\"b\\c\n"));
+    report.durations.insert(
+        String::from("a"),
+        TimeSpec {
+            tv_sec: 1,
+            tv_nsec: 5,
+        },
+    );
+    assert_eq!(
+        report.to_json(),
+        "{\"total\":0,\"passed\":0,\"failed\":[\"a\\\"b\\\\c\\n\"],\"durations\":{\"a\":1.000000005}}"
+    );
+}
@@ -0,0 +1,170 @@
+#![no_std]
+
+extern crate alloc;
+
+#[cfg(test)]
+mod test;
+
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    hint::spin_loop,
+    ops::Deref,
+    ptr::NonNull,
+    sync::atomic::{AtomicPtr, AtomicUsize, Ordering},
+};
+use kernel_sync::SpinLock;
+
+/// An object replaced in an [`RcuCell`], which is dropped once no reader may see it.
+pub struct RcuDrop<T>(NonNull<T>);
+
+unsafe impl<T: Send> Send for RcuDrop<T> {}
+
+impl<T> Drop for RcuDrop<T> {
+    fn drop(&mut self) {
+        drop(unsafe { Box::from_raw(self.0.as_ptr()) });
+    }
+}
+
+/// Maximum number of replaced objects kept before a writer waits for a grace period.
+pub const RETIRED_MAX: usize = 64;
+
+/// A cell read without locking, while writers copy the data, modify the copy and
+/// publish it in place of the old one.
+///
+/// Replaced objects are kept as [`RcuDrop`] until a grace period, i.e. a moment
+/// observed by a writer when there are no readers. Readers holding an
+/// [`RcuReadGuard`] for a long time delay the reclamation without blocking writers,
+/// until [`RETIRED_MAX`] objects are kept and the writer waits for the readers that
+/// may see them. Thus a writer must not hold a guard of the same cell.
+pub struct RcuCell<T> {
+    /// Current object allocated in a [`Box`].
+    ptr: AtomicPtr<T>,
+
+    /// Number of living [`RcuReadGuard`]s counted in each epoch.
+    readers: [AtomicUsize; 2],
+
+    /// Readers count themselves in `readers[epoch & 1]`, flipped by writers waiting
+    /// for a grace period, so that readers coming later never delay it.
+    epoch: AtomicUsize,
+
+    /// Replaced objects waiting for a grace period, whose lock serializes writers.
+    retired: SpinLock<Vec<RcuDrop<T>>>,
+}
+
+unsafe impl<T: Send + Sync> Send for RcuCell<T> {}
+unsafe impl<T: Send + Sync> Sync for RcuCell<T> {}
+
+impl<T> RcuCell<T> {
+    pub fn new(data: T) -> Self {
+        Self {
+            ptr: AtomicPtr::new(Box::into_raw(Box::new(data))),
+            readers: [AtomicUsize::new(0), AtomicUsize::new(0)],
+            epoch: AtomicUsize::new(0),
+            retired: SpinLock::new(Vec::new()),
+        }
+    }
+
+    /// Gets the current object, which is never dropped while the guard is alive.
+    pub fn read(&self) -> RcuReadGuard<'_, T> {
+        // Sequentially consistent with the check of readers in `publish`, so the
+        // object loaded here is either current or not yet reclaimed.
+        let idx = self.epoch.load(Ordering::SeqCst) & 1;
+        self.readers[idx].fetch_add(1, Ordering::SeqCst);
+        let data = unsafe { &*self.ptr.load(Ordering::SeqCst) };
+        RcuReadGuard {
+            cell: self,
+            data,
+            idx,
+        }
+    }
+
+    /// Replaces the current object with `data`.
+    pub fn write(&self, data: T) {
+        let mut retired = self.retired.lock();
+        self.publish(&mut retired, data);
+    }
+
+    /// Copies the current object, modifies the copy with `f` and publishes it.
+    ///
+    /// Returns the result of `f`. Writers are serialized, so no modification is lost.
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> R
+    where
+        T: Clone,
+    {
+        let mut retired = self.retired.lock();
+        // Only writers replace the object, which cannot be reclaimed under the lock.
+        let mut data = unsafe { &*self.ptr.load(Ordering::SeqCst) }.clone();
+        let ret = f(&mut data);
+        self.publish(&mut retired, data);
+        ret
+    }
+
+    /// Publishes `data` and reclaims replaced objects if there are no readers, or
+    /// after a grace period if too many objects are kept.
+    fn publish(&self, retired: &mut Vec<RcuDrop<T>>, data: T) {
+        let old = self
+            .ptr
+            .swap(Box::into_raw(Box::new(data)), Ordering::SeqCst);
+        retired.push(RcuDrop(NonNull::new(old).unwrap()));
+        // Readers coming later only see the new object.
+        if self
+            .readers
+            .iter()
+            .all(|readers| readers.load(Ordering::SeqCst) == 0)
+        {
+            retired.clear();
+        } else if retired.len() >= RETIRED_MAX {
+            self.synchronize();
+            retired.clear();
+        }
+    }
+
+    /// Waits until all readers present when called have dropped their guards.
+    ///
+    /// Each epoch is flipped and then drained, so a reader counted in the old epoch
+    /// after the flip can only see the new object.
+    fn synchronize(&self) {
+        for _ in 0..2 {
+            let idx = self.epoch.fetch_add(1, Ordering::SeqCst) & 1;
+            while self.readers[idx].load(Ordering::SeqCst) != 0 {
+                spin_loop();
+            }
+        }
+    }
+
+    /// Returns the number of replaced objects not reclaimed yet.
+    pub fn retired(&self) -> usize {
+        self.retired.lock().len()
+    }
+}
+
+impl<T> Drop for RcuCell<T> {
+    fn drop(&mut self) {
+        drop(RcuDrop(NonNull::new(*self.ptr.get_mut()).unwrap()));
+    }
+}
+
+/// A reference to the object in an [`RcuCell`] when [`RcuCell::read`] is called.
+///
+/// Writes after that are not visible through this guard.
+pub struct RcuReadGuard<'a, T> {
+    cell: &'a RcuCell<T>,
+    data: &'a T,
+
+    /// Epoch in which the reader is counted.
+    idx: usize,
+}
+
+impl<T> Deref for RcuReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.data
+    }
+}
+
+impl<T> Drop for RcuReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.cell.readers[self.idx].fetch_sub(1, Ordering::SeqCst);
+    }
+}
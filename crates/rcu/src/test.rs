@@ -0,0 +1,77 @@
+extern crate std;
+
+use alloc::{vec, vec::Vec};
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use crate::*;
+
+#[test]
+fn test_rcu_update() {
+    let cell = RcuCell::new(vec![1]);
+    {
+        let old = cell.read();
+        cell.update(|data| data.push(2));
+        // Readers keep the object when it is read.
+        assert_eq!(*old, [1]);
+        assert_eq!(*cell.read(), [1, 2]);
+        assert_eq!(cell.retired(), 1);
+    }
+    // Reclaimed by the next writer without readers.
+    cell.write(vec![3]);
+    assert_eq!(cell.retired(), 0);
+    assert_eq!(*cell.read(), [3]);
+}
+
+#[test]
+fn test_rcu_concurrent() {
+    const READERS: usize = 8;
+    const UPDATES: usize = 10_000;
+
+    // All slots are always equal unless an update is observed halfway.
+    let cell = RcuCell::new(vec![0usize; 16]);
+    let done = AtomicBool::new(false);
+    thread::scope(|scope| {
+        for _ in 0..READERS {
+            scope.spawn(|| {
+                let mut last = 0;
+                while !done.load(Ordering::Relaxed) {
+                    let data = cell.read();
+                    assert!(data.iter().all(|slot| *slot == data[0]));
+                    assert!(data[0] >= last);
+                    last = data[0];
+                }
+            });
+        }
+        for i in 1..=UPDATES {
+            cell.update(|data| data.iter_mut().for_each(|slot| *slot = i));
+            assert!(cell.retired() < RETIRED_MAX);
+        }
+        done.store(true, Ordering::Relaxed);
+    });
+    assert_eq!(*cell.read(), Vec::from([UPDATES; 16]));
+    cell.write(Vec::new());
+    assert_eq!(cell.retired(), 0);
+}
+
+#[test]
+fn test_rcu_retired_max() {
+    let cell = RcuCell::new(0);
+    let old = cell.read();
+    thread::scope(|scope| {
+        let writer = scope.spawn(|| {
+            for i in 1..=RETIRED_MAX {
+                cell.write(i);
+            }
+        });
+        // The last write waits for the reader holding the first object.
+        while *cell.read() != RETIRED_MAX {
+            thread::yield_now();
+        }
+        assert!(!writer.is_finished());
+        assert_eq!(*old, 0);
+        drop(old);
+        writer.join().unwrap();
+    });
+    assert_eq!(cell.retired(), 0);
+}
@@ -471,6 +471,10 @@ impl DirEntryEditor {
         }
     }
 
+    pub(crate) fn pos(&self) -> u64 {
+        self.pos
+    }
+
     pub(crate) fn inner(&self) -> &DirFileEntryData {
         &self.data
     }
@@ -141,7 +141,8 @@ impl<'a, IO: ReadWriteSeek, TP, OCC> File<'a, IO, TP, OCC> {
         }
     }
 
-    fn flush_dir_entry(&mut self) -> Result<(), Error<IO::Error>> {
+    /// Writes the modified directory entry of this file without flushing the storage.
+    pub fn flush_dir_entry(&mut self) -> Result<(), Error<IO::Error>> {
         if let Some(ref mut e) = self.entry {
             e.flush(self.fs)?;
         }
@@ -207,10 +208,16 @@ impl<'a, IO: ReadWriteSeek, TP, OCC> File<'a, IO, TP, OCC> {
         }
     }
 
-    pub(crate) fn first_cluster(&self) -> Option<u32> {
+    /// Returns the first cluster of this file, or `None` if no cluster is allocated yet.
+    pub fn first_cluster(&self) -> Option<u32> {
         self.first_cluster
     }
 
+    /// Returns the position of the directory entry of this file on the disk.
+    pub fn entry_pos(&self) -> Option<u64> {
+        self.entry.as_ref().map(DirEntryEditor::pos)
+    }
+
     fn flush(&mut self) -> Result<(), Error<IO::Error>> {
         self.flush_dir_entry()?;
         let mut disk = self.fs.disk.borrow_mut();
@@ -3,13 +3,19 @@
 
 extern crate alloc;
 
+#[cfg(test)]
+mod test;
+
 mod sigaction;
+mod sigaltstack;
 mod siginfo;
 mod signo;
 mod sigpending;
 mod sigset;
 
+pub use errno::Errno;
 pub use sigaction::*;
+pub use sigaltstack::*;
 pub use siginfo::*;
 pub use signo::*;
 pub use sigpending::*;
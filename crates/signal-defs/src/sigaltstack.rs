@@ -0,0 +1,140 @@
+use errno::Errno;
+
+use crate::{SigAction, SigActionFlags};
+
+/// The thread is currently executing on the alternate signal stack.
+pub const SS_ONSTACK: i32 = 1;
+
+/// The alternate signal stack is currently disabled.
+pub const SS_DISABLE: i32 = 2;
+
+/// Clear the alternate signal stack settings on entry to the signal handler.
+pub const SS_AUTODISARM: i32 = 1 << 31;
+
+/// Minimum size of an alternate signal stack.
+pub const MINSIGSTKSZ: usize = 2048;
+
+/// Canonical size of an alternate signal stack.
+pub const SIGSTKSZ: usize = 8192;
+
+/// The `stack_t` structure used by `sigaltstack(2)`.
+///
+/// An alternate signal stack is used during the execution of signal handlers
+/// if the establishment of that handler (see sigaction(2)) requested it with
+/// `SA_ONSTACK`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SigAltStack {
+    /// Base address of stack
+    pub sp: usize,
+
+    /// Flags
+    pub flags: i32,
+
+    /// Number of bytes in stack
+    pub size: usize,
+}
+
+impl Default for SigAltStack {
+    fn default() -> Self {
+        Self {
+            sp: 0,
+            flags: SS_DISABLE,
+            size: 0,
+        }
+    }
+}
+
+impl SigAltStack {
+    /// Creates a new disabled `SigAltStack`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the alternate signal stack is disabled.
+    pub fn is_disabled(&self) -> bool {
+        self.flags & SS_DISABLE != 0 || self.size == 0
+    }
+
+    /// Returns true if the stack pointer lies in the alternate signal stack.
+    pub fn on_stack(&self, sp: usize) -> bool {
+        !self.is_disabled() && sp > self.sp && sp - self.sp <= self.size
+    }
+
+    /// Returns the stack pointer the signal handler starts with.
+    ///
+    /// The alternate signal stack is used only if the handler is established with
+    /// `SA_ONSTACK` and the thread is not already executing on it; otherwise the
+    /// current stack pointer `sp` is kept.
+    pub fn handler_sp(&self, sp: usize, action: &SigAction) -> usize {
+        if action.flags.contains(SigActionFlags::SA_ONSTACK)
+            && !self.is_disabled()
+            && !self.on_stack(sp)
+        {
+            self.sp + self.size
+        } else {
+            sp
+        }
+    }
+}
+
+/// A helper for `sigaltstack(2)`, which defines a new alternate signal stack and/or
+/// retrieves the state of an existing one.
+///
+/// - `curr`: the alternate signal stack of current thread.
+/// - `new`: if not `None`, the new stack to be established.
+/// - `sp`: the stack pointer of current thread, used to check if the thread is
+///   executing on the alternate signal stack.
+///
+/// Returns the old state of the alternate signal stack, with `SS_ONSTACK` set if the
+/// thread is executing on it.
+///
+/// # Error
+/// - `EINVAL`: `new` is not `None` and the `flags` field contains a flag other than
+///   `SS_DISABLE` or `SS_AUTODISARM`.
+/// - `ENOMEM`: The specified size of the new alternate signal stack was less than
+///   `MINSIGSTKSZ`.
+/// - `EPERM`: An attempt was made to change the alternate signal stack while it was
+///   active (i.e., the thread was already executing on the current alternate signal stack).
+pub fn sigaltstack(
+    curr: &mut SigAltStack,
+    new: Option<&SigAltStack>,
+    sp: usize,
+) -> Result<SigAltStack, Errno> {
+    let on_stack = curr.on_stack(sp);
+
+    let mut old = *curr;
+    if on_stack {
+        old.flags |= SS_ONSTACK;
+    } else if curr.is_disabled() {
+        old.flags = SS_DISABLE;
+    }
+
+    if let Some(new) = new {
+        if on_stack {
+            return Err(Errno::EPERM);
+        }
+        // SS_ONSTACK is accepted for compatibility with old applications
+        let flags = new.flags & !SS_ONSTACK;
+        if flags & !(SS_DISABLE | SS_AUTODISARM) != 0 {
+            return Err(Errno::EINVAL);
+        }
+        if flags & SS_DISABLE != 0 {
+            *curr = SigAltStack::new();
+        } else {
+            if new.size < MINSIGSTKSZ {
+                return Err(Errno::ENOMEM);
+            }
+            if new.sp.checked_add(new.size).is_none() {
+                return Err(Errno::EINVAL);
+            }
+            *curr = SigAltStack {
+                sp: new.sp,
+                flags,
+                size: new.size,
+            };
+        }
+    }
+
+    Ok(old)
+}
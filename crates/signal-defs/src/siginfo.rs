@@ -15,6 +15,14 @@ pub struct SigInfo {
     pub code: i32,
 }
 
+/* si_codes of signals sent by user */
+/// sent by kill, sigsend, raise
+pub const SI_USER: i32 = 0;
+/// sent by the kernel from somewhere
+pub const SI_KERNEL: i32 = 0x80;
+/// sent by tkill or tgkill
+pub const SI_TKILL: i32 = -6;
+
 /* SIGCHLD si_codes */
 /// child has exited
 pub const CLD_EXITED: usize = 1;
@@ -28,4 +36,10 @@ pub const CLD_TRAPPED: usize = 4;
 pub const CLD_STOPPED: usize = 5;
 /// stopped child has continued
 pub const CLD_CONTINUED: usize = 6;
-pub const NSIGCHLD: usize = 6;
\ No newline at end of file
+pub const NSIGCHLD: usize = 6;
+
+/* SIGSEGV si_codes */
+/// address not mapped to object
+pub const SEGV_MAPERR: usize = 1;
+/// invalid permissions for mapped object
+pub const SEGV_ACCERR: usize = 2;
@@ -0,0 +1,74 @@
+use crate::*;
+use errno::Errno;
+
+const STACK_BASE: usize = 0x1000_0000;
+
+fn onstack_action() -> SigAction {
+    let mut action = SigAction::new();
+    action.handler = 0x1234;
+    action.flags = SigActionFlags::SA_ONSTACK;
+    action
+}
+
+#[test]
+fn test_sigaltstack() {
+    let mut altstack = SigAltStack::new();
+    let new = SigAltStack {
+        sp: STACK_BASE,
+        flags: 0,
+        size: SIGSTKSZ,
+    };
+
+    let old = sigaltstack(&mut altstack, Some(&new), 0x8000_0000).unwrap();
+    assert_eq!(old.flags, SS_DISABLE);
+
+    // handler stack pointer lands inside the alternate stack
+    let sp = altstack.handler_sp(0x8000_0000, &onstack_action());
+    assert!(sp > STACK_BASE && sp <= STACK_BASE + SIGSTKSZ);
+    assert!(altstack.on_stack(sp));
+
+    // not switched without SA_ONSTACK
+    assert_eq!(altstack.handler_sp(0x8000_0000, &SigAction::new()), 0x8000_0000);
+
+    // nested signal keeps the current stack
+    assert_eq!(altstack.handler_sp(sp - 0x100, &onstack_action()), sp - 0x100);
+
+    let old = sigaltstack(&mut altstack, None, sp - 0x100).unwrap();
+    assert_eq!(old.flags & SS_ONSTACK, SS_ONSTACK);
+    assert_eq!(old.sp, STACK_BASE);
+}
+
+#[test]
+fn test_sigaltstack_invalid() {
+    let mut altstack = SigAltStack::new();
+    let mut new = SigAltStack {
+        sp: STACK_BASE,
+        flags: 0,
+        size: MINSIGSTKSZ - 1,
+    };
+    assert_eq!(
+        sigaltstack(&mut altstack, Some(&new), 0).unwrap_err(),
+        Errno::ENOMEM
+    );
+
+    new.size = MINSIGSTKSZ;
+    new.flags = 0x4;
+    assert_eq!(
+        sigaltstack(&mut altstack, Some(&new), 0).unwrap_err(),
+        Errno::EINVAL
+    );
+
+    new.flags = 0;
+    sigaltstack(&mut altstack, Some(&new), 0).unwrap();
+
+    // cannot change the stack while executing on it
+    new.flags = SS_DISABLE;
+    assert_eq!(
+        sigaltstack(&mut altstack, Some(&new), STACK_BASE + 0x10).unwrap_err(),
+        Errno::EPERM
+    );
+
+    sigaltstack(&mut altstack, Some(&new), 0).unwrap();
+    assert!(altstack.is_disabled());
+    assert_eq!(altstack.handler_sp(0x100, &onstack_action()), 0x100);
+}
@@ -1,4 +1,48 @@
-use crate::SyscallResult;
+use crate::{IoVec, SyscallResult};
+
+/// Tests that the value at the futex word still contains the expected value, and if so,
+/// then sleeps waiting for a `FUTEX_WAKE` operation on the futex word.
+pub const FUTEX_WAIT: usize = 0;
+/// Wakes at most `val` of the waiters that are waiting on the futex word.
+pub const FUTEX_WAKE: usize = 1;
+/// The futex is process-private and not shared with another process.
+pub const FUTEX_PRIVATE_FLAG: usize = 128;
+/// The timeout is measured against `CLOCK_REALTIME` instead of `CLOCK_MONOTONIC`.
+pub const FUTEX_CLOCK_REALTIME: usize = 256;
+/// Mask of the futex operation, with option flags removed.
+pub const FUTEX_CMD_MASK: usize = !(FUTEX_PRIVATE_FLAG | FUTEX_CLOCK_REALTIME);
+
+/// Set in a robust futex word if there are waiters.
+pub const FUTEX_WAITERS: u32 = 0x8000_0000;
+/// Set in a robust futex word by the kernel if the owner exits without unlocking it.
+pub const FUTEX_OWNER_DIED: u32 = 0x4000_0000;
+/// Mask of the owner thread ID in a robust futex word.
+pub const FUTEX_TID_MASK: u32 = 0x3fff_ffff;
+
+/// Head of the per-thread list of robust futexes held, registered by `set_robust_list`.
+///
+/// Each entry in the circular list is embedded in a lock, and the futex word of the lock
+/// is at `futex_offset` from the entry. The lowest bit of a list pointer is set if the
+/// lock is a priority-inheritance futex.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RobustListHead {
+    /// Pointer to the first entry, or to this head if the list is empty.
+    pub list: usize,
+    /// Offset of the futex word relative to each entry.
+    pub futex_offset: isize,
+    /// Entry being locked or unlocked, which may not be in the list yet.
+    pub list_op_pending: usize,
+}
+
+/// Attempt to move pages instead of copying, which is only a hint.
+pub const SPLICE_F_MOVE: usize = 1;
+/// Do not block on I/O of the pipe.
+pub const SPLICE_F_NONBLOCK: usize = 2;
+/// More data will be coming in a subsequent splice, which is only a hint.
+pub const SPLICE_F_MORE: usize = 4;
+/// Unused for splice.
+pub const SPLICE_F_GIFT: usize = 8;
 
 pub trait SyscallComm {
     /// Creates a pipe, a unidirectional data channel that can be used for
@@ -8,11 +52,191 @@ pub trait SyscallComm {
     /// the ends of the pipe. pipefd\[0\] refers to the read end of the pipe.
     /// pipefd\[1\] refers to the write end of the pipe.
     ///
+    /// # Argument
+    /// - `flags`: If flags is 0, then pipe2() is the same as pipe(). The following values
+    ///   can be bitwise ORed in flags to obtain different behavior:
+    ///   - `O_CLOEXEC`: Set the close-on-exec flag on the two new file descriptors.
+    ///   - `O_NONBLOCK`: Set the O_NONBLOCK file status flag on the open file descriptions
+    ///     referred to by the new file descriptors.
+    ///
     /// # Error
     /// - `EFAULT`: pipefd is not valid.
+    /// - `EINVAL`: Invalid value in flags.
     /// - `EMFILE`: The per-process limit on the number of open file descriptor
     /// has been reached.
-    fn pipe(pipefd: *const u32, flags: usize) -> SyscallResult {
+    fn pipe2(pipefd: *const u32, flags: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Moves data between two file descriptors without copying between kernel address
+    /// space and user address space, where one of the file descriptors must refer to a
+    /// pipe.
+    ///
+    /// Returns the number of bytes spliced to or from the pipe. A return value of 0 means
+    /// end of input, i.e. the write end of the pipe is closed with no data left, or the
+    /// file has no more data to read.
+    ///
+    /// # Argument
+    /// - `off_in`: If `fd_in` refers to a pipe, then `off_in` must be NULL. Otherwise, if
+    ///   `off_in` is NULL, bytes are read from `fd_in` starting from the file offset, and the
+    ///   file offset is adjusted appropriately; if not, it points to a buffer which specifies
+    ///   the starting offset from which bytes will be read from `fd_in`, and the file offset
+    ///   of `fd_in` is not changed but the buffer is adjusted.
+    /// - `off_out`: Analogous statements apply for `fd_out`.
+    /// - `flags`: A bit mask composed by ORing together zero or more of the following values:
+    ///   - `SPLICE_F_MOVE`, `SPLICE_F_MORE`, `SPLICE_F_GIFT`: Hints which are ignored.
+    ///   - `SPLICE_F_NONBLOCK`: Do not block on I/O of the pipe.
+    ///
+    /// # Error
+    /// - `EAGAIN`: `SPLICE_F_NONBLOCK` was specified in flags or one of the file descriptors
+    ///   had been marked as nonblocking, and the operation would block.
+    /// - `EBADF`: One or both file descriptors are not valid, or do not have proper
+    ///   read-write mode.
+    /// - `EINVAL`: Neither or both of the file descriptors refer to a pipe, or flags
+    ///   contains an unknown value.
+    /// - `EPIPE`: The read end of the output pipe is closed.
+    /// - `ESPIPE`: Either `off_in` or `off_out` was not NULL, but the corresponding file
+    ///   descriptor refers to a pipe.
+    fn splice(
+        fd_in: usize,
+        off_in: usize,
+        fd_out: usize,
+        off_out: usize,
+        len: usize,
+        flags: usize,
+    ) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Splices the user memory described by `nr_segs` buffers in `iov` into the pipe
+    /// referred to by the file descriptor `fd`.
+    ///
+    /// Returns the number of bytes transferred to the pipe.
+    ///
+    /// # Argument
+    /// - `flags`: The same as [`Self::splice`], except that `SPLICE_F_GIFT` gifts the user
+    ///   pages to the kernel, which is only a hint.
+    ///
+    /// # Error
+    /// - `EAGAIN`: `SPLICE_F_NONBLOCK` was specified in flags, and the operation would block.
+    /// - `EBADF`: fd either not valid, or doesn't refer to the write end of a pipe.
+    /// - `EFAULT`: Some user buffers are not mapped or readable.
+    /// - `EINVAL`: `nr_segs` is greater than `IOV_MAX`, or flags contains an unknown value.
+    fn vmsplice(fd: usize, iov: *const IoVec, nr_segs: usize, flags: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Duplicates up to `len` bytes of data from the pipe referred to by the file
+    /// descriptor `fd_in` to the pipe referred to by the file descriptor `fd_out`.
+    /// It does not consume the data that is duplicated from `fd_in`; therefore, that
+    /// data can be copied by a subsequent [`Self::splice`].
+    ///
+    /// Returns the number of bytes that were duplicated between the input and output.
+    /// A return value of 0 means that there was no data to transfer, and it would not
+    /// make sense to block, because there are no writers connected to the write end of
+    /// the pipe referred to by `fd_in`.
+    ///
+    /// # Argument
+    /// - `flags`: The same as [`Self::splice`].
+    ///
+    /// # Error
+    /// - `EAGAIN`: `SPLICE_F_NONBLOCK` was specified in flags or one of the file descriptors
+    ///   had been marked as nonblocking, and the operation would block.
+    /// - `EINVAL`: `fd_in` or `fd_out` does not refer to a pipe; or `fd_in` and `fd_out` refer
+    ///   to the same pipe.
+    fn tee(fd_in: usize, fd_out: usize, len: usize, flags: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Transfers data from the remote memory of the process `pid` described by `riovcnt`
+    /// buffers in `remote_iov` to the local memory described by `liovcnt` buffers in
+    /// `local_iov`, without passing through the kernel twice.
+    ///
+    /// Returns the number of bytes read, which may be less than requested if a remote
+    /// buffer is not accessible. Partial transfers apply at the granularity of remote
+    /// buffers.
+    ///
+    /// # Argument
+    /// - `flags`: Unused and must be 0.
+    ///
+    /// # Error
+    /// - `EFAULT`: The memory described by `local_iov` or `remote_iov` is outside the
+    ///   accessible address space of the caller or the remote process.
+    /// - `EINVAL`: The sum of lengths overflows an `ssize_t`, `liovcnt` or `riovcnt` is
+    ///   greater than `IOV_MAX`, or flags is not 0.
+    /// - `EPERM`: The caller does not have permission to access the address space of the
+    ///   process `pid`.
+    /// - `ESRCH`: No process with ID `pid` exists.
+    fn process_vm_readv(
+        pid: usize,
+        local_iov: *const IoVec,
+        liovcnt: usize,
+        remote_iov: *const IoVec,
+        riovcnt: usize,
+        flags: usize,
+    ) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// The converse of [`Self::process_vm_readv`], which transfers data from the local
+    /// memory to the remote memory of the process `pid`.
+    ///
+    /// Returns the number of bytes written. The remote memory must be writable.
+    ///
+    /// # Error
+    /// The same as [`Self::process_vm_readv`].
+    fn process_vm_writev(
+        pid: usize,
+        local_iov: *const IoVec,
+        liovcnt: usize,
+        remote_iov: *const IoVec,
+        riovcnt: usize,
+        flags: usize,
+    ) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Sends the signal `sig` to any process or process group.
+    ///
+    /// - If `pid` is positive, the signal is sent to the process with the ID `pid`.
+    /// - If `pid` is 0, the signal is sent to every process in the process group of the
+    ///   calling process.
+    /// - If `pid` is -1, the signal is sent to every process except the init process and
+    ///   the calling process.
+    /// - If `pid` is less than -1, the signal is sent to every process in the process
+    ///   group `-pid`.
+    ///
+    /// If `sig` is 0, no signal is sent, but existence checks are still performed.
+    ///
+    /// # Error
+    /// - `EINVAL`: An invalid signal was specified.
+    /// - `ESRCH`: The target process or process group does not exist.
+    fn kill(pid: isize, sig: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Sends the signal `sig` to the thread with the thread ID `tid`.
+    ///
+    /// An obsolete predecessor to [`Self::tgkill`], which cannot tell if the thread ID has
+    /// been recycled by another thread.
+    ///
+    /// # Error
+    /// - `EINVAL`: An invalid thread ID or signal was specified.
+    /// - `ESRCH`: No such thread.
+    fn tkill(tid: usize, sig: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Sends the signal `sig` to the thread with the thread ID `tid` in the thread group
+    /// `tgid`. The signal is directed to this thread only, even if other threads in the
+    /// group have it unblocked.
+    ///
+    /// If `sig` is 0, then no signal is sent, but existence checks are still performed.
+    ///
+    /// # Error
+    /// - `EINVAL`: An invalid thread ID, thread group ID, or signal was specified.
+    /// - `ESRCH`: No such thread, or the thread does not belong to the thread group.
+    fn tgkill(tgid: usize, tid: usize, sig: usize) -> SyscallResult {
         Ok(0)
     }
 
@@ -84,23 +308,115 @@ pub trait SyscallComm {
         Ok(0)
     }
 
+    /// Allows a thread to define a new alternate signal stack and/or retrieve the
+    /// state of an existing alternate signal stack. An alternate signal stack is used
+    /// during the execution of a signal handler if the establishment of that handler
+    /// (see sigaction(2)) requested it with `SA_ONSTACK`.
+    ///
+    /// # Argument
+    /// - `ss`: If non-NULL, specifies a new alternate signal stack for the thread.
+    /// - `old_ss`: If non-NULL, returns information about the alternate signal stack
+    ///   that was in effect prior to the call to sigaltstack().
+    ///
+    /// # Error
+    /// - `EFAULT`: Either `ss` or `old_ss` is not NULL and points to an area outside of
+    ///   the process's address space.
+    /// - `EINVAL`: `ss` is not NULL and the `ss_flags` field contains an invalid flag.
+    /// - `ENOMEM`: The specified size of the new alternate signal stack `ss.ss_size` was
+    ///   less than `MINSIGSTKSZ`.
+    /// - `EPERM`: An attempt was made to change the alternate signal stack while it was
+    ///   active (i.e., the thread was already executing on the current alternate signal stack).
+    fn sigaltstack(ss: usize, old_ss: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Returns from the signal handler and cleans up the stack frame pushed when the
+    /// signal is delivered, which is issued by the trampoline the handler returns to.
+    ///
+    /// The saved user context and signal mask are restored, thus the interrupted
+    /// program resumes as if the handler had never been called.
+    ///
+    /// # Return
+    ///
+    /// Never returns to the caller, and `a0` of the restored context is kept.
+    ///
+    /// # Error
+    /// - `EFAULT`: The signal frame on top of the stack is not accessible.
+    fn sigreturn() -> SyscallResult {
+        Ok(0)
+    }
 
     /// The sigtimedwait() function shall be equivalent to sigwaitinfo() except that if none of the signals
     /// specified by set are pending, sigtimedwait() shall wait for the time interval specified in the timespec
     /// structure referenced by timeout. If the timespec structure pointed to by timeout is zero-valued and if
     /// none of the signals specified by set are pending, then sigtimedwait() shall return immediately with an error.
     /// If timeout is the null pointer, the behavior is unspecified.
-    /// 
+    ///
     /// The sigwaitinfo() function selects the pending signal from the set specified by set. Should any of multiple
     /// pending signals in the range SIGRTMIN to SIGRTMAX be selected, it shall be the lowest numbered one.
     /// The selection order between realtime and non-realtime signals, or between multiple pending non-realtime signals,
     /// is unspecified. If no signal in set is pending at the time of the call, the calling thread shall be suspended
     /// until one or more signals in set become pending or until it is interrupted by an unblocked, caught signal.
-    /// 
+    ///
     /// # Return
     /// Upon successful completion (that is, one of the signals specified by set is pending or is generated) sigwaitinfo()
-    /// and sigtimedwait() shall return the selected signal number. 
+    /// and sigtimedwait() shall return the selected signal number.
     fn sigtimedwait(set: usize, info: usize, timeout: usize) -> SyscallResult {
         Ok(0)
     }
+
+    /// Registers the head of the robust futex list of the calling thread.
+    ///
+    /// When the thread exits, the kernel walks the list and marks each futex still held
+    /// by the thread with [`FUTEX_OWNER_DIED`], waking up a waiter if any.
+    ///
+    /// # Error
+    /// - `EINVAL`: `len` does not equal the size of [`RobustListHead`].
+    fn set_robust_list(head: usize, len: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Returns the head of the robust futex list of the thread `pid`, or the calling
+    /// thread if `pid` is 0, in the location pointed to by `head_ptr`, and the size of
+    /// the head in the location pointed to by `len_ptr`.
+    ///
+    /// # Error
+    /// - `EFAULT`: The head or length could not be stored.
+    /// - `ESRCH`: No thread with the ID `pid` could be found.
+    fn get_robust_list(pid: usize, head_ptr: usize, len_ptr: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Provides a method for waiting until a certain condition becomes true. It is typically used as
+    /// a blocking construct in the context of shared-memory synchronization.
+    ///
+    /// # Argument
+    /// - `uaddr`: points to the futex word, a 4-byte aligned 32-bit integer.
+    /// - `futex_op`: one of [`FUTEX_WAIT`] and [`FUTEX_WAKE`], optionally ORed with
+    ///   [`FUTEX_PRIVATE_FLAG`] or [`FUTEX_CLOCK_REALTIME`].
+    /// - `val`: the expected value of the futex word for `FUTEX_WAIT`, or the maximum number
+    ///   of waiters to wake up for `FUTEX_WAKE`.
+    /// - `timeout`: if non-NULL, points to a relative timeout for `FUTEX_WAIT`.
+    ///
+    /// # Return
+    /// `FUTEX_WAIT` returns 0 if the caller was woken up. `FUTEX_WAKE` returns the number of
+    /// waiters that were woken up.
+    ///
+    /// # Error
+    /// - `EAGAIN`: (`FUTEX_WAIT`) The value pointed to by uaddr was not equal to the expected
+    ///   value val at the time of the call.
+    /// - `EFAULT`: A required pointer argument did not point to a valid user-space address.
+    /// - `EINVAL`: uaddr is not aligned, or the supplied timeout is invalid.
+    /// - `ENOSYS`: Invalid operation specified in futex_op.
+    /// - `ETIMEDOUT`: (`FUTEX_WAIT`) The operation timed out before being woken up.
+    fn futex(
+        uaddr: usize,
+        futex_op: usize,
+        val: usize,
+        timeout: usize,
+        uaddr2: usize,
+        val3: usize,
+    ) -> SyscallResult {
+        Ok(0)
+    }
 }
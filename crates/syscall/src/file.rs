@@ -3,9 +3,47 @@ use crate::SyscallResult;
 /// Special value for dirfd.
 pub const AT_FDCWD: usize = -100isize as usize;
 
+/// Do not follow symbolic links.
+pub const AT_SYMLINK_NOFOLLOW: usize = 0x100;
+
+/// Dereference oldpath if it is a symbolic link in `linkat`.
+pub const AT_SYMLINK_FOLLOW: usize = 0x400;
+
 /// Remove directory instead of unlinking file.
 pub const AT_REMOVEDIR: usize = 0x200;
 
+/// Operate on dirfd itself if pathname is an empty string.
+pub const AT_EMPTY_PATH: usize = 0x1000;
+
+/// Check access using the effective user and group IDs in `faccessat`.
+pub const AT_EACCESS: usize = 0x200;
+
+/// Duplicates the file descriptor using the lowest-numbered available file
+/// descriptor greater than or equal to arg.
+pub const F_DUPFD: usize = 0;
+
+/// Returns the file descriptor flags.
+pub const F_GETFD: usize = 1;
+
+/// Sets the file descriptor flags to the value specified by arg.
+pub const F_SETFD: usize = 2;
+
+/// Returns the file access mode and the file status flags.
+pub const F_GETFL: usize = 3;
+
+/// Sets the file status flags to the value specified by arg.
+pub const F_SETFL: usize = 4;
+
+/// As for `F_DUPFD`, but additionally set the close-on-exec flag for the
+/// duplicate file descriptor.
+pub const F_DUPFD_CLOEXEC: usize = 1030;
+
+/// The close-on-exec flag in file descriptor flags.
+pub const FD_CLOEXEC: usize = 1;
+
+/// Maximum number of buffers in an array of [`IoVec`].
+pub const IOV_MAX: usize = 1024;
+
 /// Used in readv and writev.
 ///
 /// Defined in sys/uio.h.
@@ -65,6 +103,64 @@ pub trait SyscallFile {
         Ok(0)
     }
 
+    /// Allocates a new file descriptor that refers to the same open file description
+    /// as the descriptor `oldfd`. The new file descriptor number is guaranteed to be
+    /// the lowest-numbered file descriptor that was unused in the calling process.
+    ///
+    /// The two file descriptors do not share file descriptor flags (the close-on-exec
+    /// flag). The close-on-exec flag for the duplicate descriptor is off.
+    ///
+    /// # Error
+    /// - `EBADF`: `oldfd` isn't an open file descriptor.
+    /// - `EMFILE`: The per-process limit on the number of open file descriptors has
+    ///   been reached.
+    fn dup(oldfd: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Creates a copy of the file descriptor `oldfd` using the file descriptor number
+    /// specified in `newfd`. If the file descriptor `newfd` was previously open, it is
+    /// closed before being reused; the close is performed silently.
+    ///
+    /// The caller can force the close-on-exec flag to be set for the new file descriptor
+    /// by specifying `O_CLOEXEC` in `flags`.
+    ///
+    /// # Error
+    /// - `EBADF`: `oldfd` isn't an open file descriptor, or `newfd` is out of the allowed
+    ///   range for file descriptors.
+    /// - `EINVAL`: Invalid `flags`, or `oldfd` was equal to `newfd`.
+    fn dup3(oldfd: usize, newfd: usize, flags: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Performs one of the operations described below on the open file descriptor `fd`.
+    /// The operation is determined by `cmd`.
+    ///
+    /// # Argument
+    /// - `cmd`:
+    ///   - [`F_DUPFD`]: Duplicates the file descriptor `fd` using the lowest-numbered
+    ///     available file descriptor greater than or equal to `arg`.
+    ///   - [`F_DUPFD_CLOEXEC`]: As for `F_DUPFD`, but additionally set the close-on-exec
+    ///     flag for the duplicate file descriptor.
+    ///   - [`F_GETFD`]: Returns (as the function result) the file descriptor flags; `arg`
+    ///     is ignored.
+    ///   - [`F_SETFD`]: Sets the file descriptor flags to the value specified by `arg`.
+    ///   - [`F_GETFL`]: Returns (as the function result) the file access mode and the file
+    ///     status flags; `arg` is ignored.
+    ///   - [`F_SETFL`]: Sets the file status flags to the value specified by `arg`. File
+    ///     access mode and file creation flags in `arg` are ignored. Only `O_APPEND` and
+    ///     `O_NONBLOCK` flags can be changed.
+    ///
+    /// # Error
+    /// - `EBADF`: `fd` is not an open file descriptor.
+    /// - `EINVAL`: The value specified in `cmd` is not recognized by this kernel, or `arg`
+    ///   is negative or is greater than the maximum allowable value for `F_DUPFD`.
+    /// - `EMFILE`: The per-process limit on the number of open file descriptors has been
+    ///   reached for `F_DUPFD`.
+    fn fcntl(fd: usize, cmd: usize, arg: usize) -> SyscallResult {
+        Ok(0)
+    }
+
     /// Writes to a file descriptor.
     ///
     ///
@@ -101,6 +197,8 @@ pub trait SyscallFile {
     /// or the file was opened with the O_DIRECT flag, and either the address
     /// specified in buf, the value specified in count, or the file offset is
     /// not suitably aligned.
+    /// - `EINTR`: The call was interrupted by a signal before any data was read,
+    ///   unless the handler is established with `SA_RESTART`; see signal(7).
     fn read(fd: usize, buf: *mut u8, count: usize) -> SyscallResult {
         Ok(0)
     }
@@ -116,6 +214,8 @@ pub trait SyscallFile {
     /// # Error
     /// - `EBADF`: fd is not an open file descriptor.
     /// - `EINVAL`: whence is not valid.
+    /// - `ENXIO`: whence is `SEEK_DATA` or `SEEK_HOLE`, and offset is beyond the end
+    ///   of the file.
     /// - `ESPIPE`: fd is associated with a pipe, socket, or FIFO.
     /// - `EOVERFLOW`: The resulting file offset cannot be represented.
     fn lseek(fd: usize, off: usize, whence: usize) -> SyscallResult {
@@ -125,19 +225,84 @@ pub trait SyscallFile {
     /// Reads `iovcnt` buffers from the file associated with the file descriptor
     /// `fd` into the buffers described by `iov`.
     ///
+    /// Buffers with zero length are skipped. The read stops at the first buffer
+    /// which is filled with fewer bytes than its length, and the total number of
+    /// bytes read is returned.
+    ///
     /// See [`Self::read`].
+    ///
+    /// # Error
+    /// - `EINVAL`: `iovcnt` is greater than [`IOV_MAX`], or the sum of lengths
+    ///   overflows an `ssize_t`.
     fn readv(fd: usize, iov: *const IoVec, iovcnt: usize) -> SyscallResult {
         Ok(0)
     }
 
-    /// Reads `iovcnt` buffers from the file associated with the file descriptor
-    /// `fd` into the buffers described by `iov`.
+    /// Writes `iovcnt` buffers described by `iov` to the file associated with the
+    /// file descriptor `fd`.
+    ///
+    /// Buffers with zero length are skipped. The write stops at the first buffer
+    /// of which fewer bytes than its length are written, and the total number of
+    /// bytes written is returned.
     ///
     /// See [`Self::write`].
+    ///
+    /// # Error
+    /// - `EINVAL`: `iovcnt` is greater than [`IOV_MAX`], or the sum of lengths
+    ///   overflows an `ssize_t`.
     fn writev(fd: usize, iov: *const IoVec, iovcnt: usize) -> SyscallResult {
         Ok(0)
     }
 
+    /// Writes up to `count` bytes from the buffer starting at `buf` to the file
+    /// descriptor `fd` at offset `offset`. The file offset is not changed.
+    ///
+    /// See [`Self::write`].
+    ///
+    /// # Error
+    /// - `EBADF`: `fd` is not a valid file descriptor or is not open for writing.
+    /// - `EINVAL`: `offset` is negative.
+    /// - `ESPIPE`: `fd` is associated with a pipe, socket, or FIFO.
+    fn pwrite(fd: usize, buf: *const u8, count: usize, offset: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Writes `iovcnt` buffers described by `iov` to the file associated with the
+    /// file descriptor `fd` at offset `offset`. The file offset is not changed.
+    ///
+    /// See [`Self::writev`] and [`Self::pwrite`].
+    fn pwritev(fd: usize, iov: *const IoVec, iovcnt: usize, offset: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Copies data between one file descriptor and another. Because this copying is done
+    /// within the kernel, sendfile() is more efficient than the combination of read(2)
+    /// and write(2), which would require transferring data to and from user space.
+    ///
+    /// Returns the number of bytes written to `out_fd`.
+    ///
+    /// # Argument
+    /// - `out_fd`: A file descriptor open for writing, which may be a pipe.
+    /// - `in_fd`: A file descriptor open for reading, which must support reading at an offset.
+    /// - `offset`: If it is not NULL, then it points to a variable holding the file offset
+    ///   from which sendfile() will start reading data from `in_fd`. When sendfile() returns,
+    ///   this variable will be set to the offset of the byte following the last byte that was
+    ///   read, and the file offset of `in_fd` is not modified. Otherwise, data will be read from
+    ///   `in_fd` starting at the file offset, and the file offset will be updated by the call.
+    /// - `count`: The number of bytes to copy between the file descriptors.
+    ///
+    /// # Error
+    /// - `EAGAIN`: Nonblocking I/O has been selected using O_NONBLOCK and the write would block.
+    /// - `EBADF`: The input file was not opened for reading or the output file was not opened
+    ///   for writing.
+    /// - `EFAULT`: Bad address.
+    /// - `EINVAL`: Descriptor is not valid or locked, or an mmap(2)-like operation is not
+    ///   available for `in_fd`.
+    /// - `EPIPE`: `out_fd` is connected to a pipe whose reading end is closed.
+    fn sendfile(out_fd: usize, in_fd: usize, offset: usize, count: usize) -> SyscallResult {
+        Ok(0)
+    }
+
     /// Deletes a name from the filesystem.  If that name was the last link to a file
     /// and no processes have the file open, the file is deleted and the space it was
     /// using is made available for reuse.
@@ -156,4 +321,265 @@ pub trait SyscallFile {
     fn unlinkat(dirfd: usize, pathname: *const u8, flags: usize) -> SyscallResult {
         Ok(0)
     }
+
+    /// Creates a new link (also known as a hard link) to an existing file. If newpath
+    /// exists, it will not be overwritten.
+    ///
+    /// If the pathnames are relative, then they are interpreted relative to the
+    /// directories referred to by the file descriptors olddirfd and newdirfd, or the
+    /// current working directory if the file descriptors are [`AT_FDCWD`].
+    ///
+    /// # Argument
+    /// - `flags`: Can either be 0, or include one or more of the following flags ORed:
+    ///   - [`AT_EMPTY_PATH`]: If oldpath is an empty string, create a link to the file
+    ///     referenced by olddirfd.
+    ///   - [`AT_SYMLINK_FOLLOW`]: By default, linkat() does not dereference oldpath if
+    ///     it is a symbolic link. This flag dereferences it.
+    ///
+    /// # Error
+    /// - `EBADF`: olddirfd or newdirfd is not a valid file descriptor.
+    /// - `EEXIST`: newpath already exists.
+    /// - `EFAULT`: oldpath or newpath points outside your accessible address space.
+    /// - `EINVAL`: An invalid flag value was specified in flags.
+    /// - `ENOENT`: A directory component in oldpath or newpath does not exist, or
+    ///   oldpath is an empty string and [`AT_EMPTY_PATH`] is not specified.
+    /// - `ENOTDIR`: A component used as a directory in oldpath or newpath is not a
+    ///   directory.
+    /// - `EPERM`: oldpath is a directory.
+    /// - `EXDEV`: oldpath and newpath are not on the same mounted filesystem.
+    fn linkat(
+        olddirfd: usize,
+        oldpath: *const u8,
+        newdirfd: usize,
+        newpath: *const u8,
+        flags: usize,
+    ) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Renames a file, moving it between directories if required. If newpath already
+    /// exists, it will be atomically replaced.
+    ///
+    /// If the pathnames are relative, then they are interpreted relative to the
+    /// directories referred to by the file descriptors olddirfd and newdirfd, or the
+    /// current working directory if the file descriptors are [`AT_FDCWD`].
+    ///
+    /// # Argument
+    /// - `flags`: Can either be 0, or one of the following flags:
+    ///   - `RENAME_NOREPLACE`: Don't overwrite newpath of the rename.
+    ///   - `RENAME_EXCHANGE`: Atomically exchange oldpath and newpath.
+    ///
+    /// # Error
+    /// - `EBADF`: olddirfd or newdirfd is not a valid file descriptor.
+    /// - `EBUSY`: oldpath or newpath is the root directory.
+    /// - `EEXIST`: flags contains `RENAME_NOREPLACE` and newpath already exists.
+    /// - `EFAULT`: oldpath or newpath points outside your accessible address space.
+    /// - `EINVAL`: An invalid flag was specified in flags, or both `RENAME_NOREPLACE`
+    ///   and `RENAME_EXCHANGE` were specified.
+    /// - `EISDIR`: newpath is an existing directory, but oldpath is not a directory.
+    /// - `ENOENT`: oldpath does not exist, or flags contains `RENAME_EXCHANGE` and
+    ///   newpath does not exist.
+    /// - `ENOTEMPTY`: newpath is a nonempty directory.
+    /// - `EXDEV`: oldpath and newpath are not on the same mounted filesystem.
+    fn renameat2(
+        olddirfd: usize,
+        oldpath: *const u8,
+        newdirfd: usize,
+        newpath: *const u8,
+        flags: usize,
+    ) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Reads several `linux_dirent64` structures from the directory referred to by the
+    /// open file descriptor fd into the buffer pointed to by dirp. The argument count
+    /// specifies the size of that buffer.
+    ///
+    /// Returns the number of bytes read on success. On end of directory, 0 is returned.
+    ///
+    /// # Error
+    /// - `EBADF`: Invalid file descriptor fd.
+    /// - `EFAULT`: Argument points outside the calling process's address space.
+    /// - `EINVAL`: Result buffer is too small.
+    /// - `ENOTDIR`: File descriptor does not refer to a directory.
+    fn getdents64(fd: usize, dirp: *mut u8, count: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Returns information about the file referred to by the open file descriptor fd
+    /// in the buffer pointed to by statbuf.
+    ///
+    /// # Error
+    /// - `EBADF`: fd is not a valid open file descriptor.
+    /// - `EFAULT`: Bad address.
+    fn fstat(fd: usize, statbuf: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Places the contents of the symbolic link pathname in the buffer buf, which has
+    /// size bufsiz. A null byte is not appended to buf, and the contents are truncated
+    /// if the buffer is too small to hold all of the contents.
+    ///
+    /// If the pathname is relative, then it is interpreted relative to the directory
+    /// referred to by the file descriptor dirfd. If pathname is relative and dirfd is
+    /// the special value [`AT_FDCWD`], then pathname is interpreted relative to the current
+    /// working directory of the calling process.
+    ///
+    /// Returns the number of bytes placed in buf.
+    ///
+    /// # Error
+    /// - `EBADF`: dirfd is not a valid open file descriptor.
+    /// - `EFAULT`: buf extends outside the process's allocated address space.
+    /// - `EINVAL`: bufsiz is not positive, or the named file is not a symbolic link.
+    /// - `ELOOP`: Too many symbolic links were encountered in translating the pathname.
+    /// - `ENOENT`: The named file does not exist.
+    /// - `ENOTDIR`: A component of the path prefix is not a directory.
+    fn readlinkat(dirfd: usize, pathname: *const u8, buf: *mut u8, bufsiz: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Checks whether the calling process can access the file pathname. The mode
+    /// specifies the accessibility checks to be performed, and is either the value
+    /// `F_OK`, or a mask consisting of the bitwise OR of one or more of `R_OK`, `W_OK`,
+    /// and `X_OK`. `F_OK` tests for the existence of the file.
+    ///
+    /// If the pathname is relative, then it is interpreted relative to the directory
+    /// referred to by the file descriptor dirfd. If pathname is relative and dirfd is
+    /// the special value [`AT_FDCWD`], then pathname is interpreted relative to the current
+    /// working directory of the calling process.
+    ///
+    /// # Argument
+    /// - `flags`: Can either be 0, or include one or more of the following flags ORed:
+    ///   - [`AT_EACCESS`]: Perform access checks using the effective user and group IDs.
+    ///   - [`AT_SYMLINK_NOFOLLOW`]: If pathname is a symbolic link, do not dereference it.
+    ///
+    /// # Error
+    /// - `EACCES`: The requested access would be denied to the file.
+    /// - `EBADF`: dirfd is not a valid open file descriptor.
+    /// - `EFAULT`: pathname points outside your accessible address space.
+    /// - `EINVAL`: mode or flags was incorrectly specified.
+    /// - `ENOENT`: A component of pathname does not exist or is a dangling symbolic link.
+    /// - `ENOTDIR`: A component used as a directory in pathname is not, in fact, a directory.
+    fn faccessat(dirfd: usize, pathname: *const u8, mode: usize, flags: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Returns information about a file in the buffer pointed to by statbuf.
+    ///
+    /// If the pathname is relative, then it is interpreted relative to the directory
+    /// referred to by the file descriptor dirfd. If pathname is relative and dirfd is
+    /// the special value [`AT_FDCWD`], then pathname is interpreted relative to the current
+    /// working directory of the calling process.
+    ///
+    /// # Argument
+    /// - `flags`: Can either be 0, or include one or more of the following flags ORed:
+    ///   - [`AT_EMPTY_PATH`]: If pathname is an empty string, operate on the file referred
+    ///     to by dirfd.
+    ///   - [`AT_SYMLINK_NOFOLLOW`]: If pathname is a symbolic link, do not dereference it.
+    ///
+    /// # Error
+    /// - `EBADF`: dirfd is not a valid open file descriptor.
+    /// - `EFAULT`: Bad address.
+    /// - `EINVAL`: Invalid flag specified in flags.
+    /// - `ENOENT`: A component of pathname does not exist or is a dangling symbolic link.
+    /// - `ENOTDIR`: A component of the path prefix of pathname is not a directory.
+    fn fstatat(dirfd: usize, pathname: *const u8, statbuf: usize, flags: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Copies an absolute pathname of the current working directory to the array
+    /// pointed to by buf, which is of length size.
+    ///
+    /// Returns buf on success.
+    ///
+    /// # Error
+    /// - `EFAULT`: buf points to a bad address.
+    /// - `EINVAL`: The size argument is zero and buf is not a null pointer.
+    /// - `ERANGE`: The size argument is less than the length of the absolute pathname
+    ///   of the working directory, including the terminating null byte.
+    fn getcwd(buf: *mut u8, size: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Causes all pending modifications to filesystem metadata and cached file data
+    /// to be written to the underlying filesystems.
+    ///
+    /// `sync()` is always successful.
+    fn sync() -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Changes the current working directory of the calling process to the directory
+    /// specified in path.
+    ///
+    /// # Error
+    /// - `EFAULT`: path points outside your accessible address space.
+    /// - `ENOENT`: The directory specified in path does not exist.
+    /// - `ENOTDIR`: A component of path is not a directory.
+    fn chdir(path: *const u8) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Changes the root directory of the calling process to that specified in path.
+    /// This directory will be used for pathnames beginning with `/`, and `..` in the
+    /// root directory refers to the root directory itself.
+    ///
+    /// This call does not change the current working directory, so that after the call
+    /// `.` can be outside the tree rooted at `/`.
+    ///
+    /// # Error
+    /// - `EFAULT`: path points outside your accessible address space.
+    /// - `ENOENT`: The file does not exist.
+    /// - `ENOTDIR`: A component of path is not a directory.
+    fn chroot(path: *const u8) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Attempts to create a directory named pathname.
+    ///
+    /// If the pathname is relative, then it is interpreted relative to the directory
+    /// referred to by the file descriptor dirfd. If pathname is relative and dirfd is
+    /// the special value [`AT_FDCWD`], then pathname is interpreted relative to the current
+    /// working directory of the calling process.
+    ///
+    /// # Argument
+    /// - `mode`: Specifies the mode for the new directory. Only permission bits are
+    ///   used, which are not kept by filesystems without file modes, e.g. FAT.
+    ///
+    /// # Error
+    /// - `EBADF`: pathname is relative but dirfd is neither [`AT_FDCWD`] nor a valid file
+    ///   descriptor.
+    /// - `EEXIST`: pathname already exists (not necessarily as a directory).
+    /// - `EFAULT`: pathname points outside your accessible address space.
+    /// - `ENOENT`: A directory component in pathname does not exist.
+    /// - `ENOTDIR`: A component used as a directory in pathname is not, in fact, a
+    ///   directory, or pathname is relative and dirfd is a file descriptor referring to a
+    ///   file other than a directory.
+    fn mkdirat(dirfd: usize, pathname: *const u8, mode: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Creates a filesystem node (file, device special file, or named pipe) named pathname,
+    /// with attributes specified by mode and dev.
+    ///
+    /// If the pathname is relative, then it is interpreted relative to the directory
+    /// referred to by the file descriptor dirfd. If pathname is relative and dirfd is
+    /// the special value [`AT_FDCWD`], then pathname is interpreted relative to the current
+    /// working directory of the calling process.
+    ///
+    /// # Argument
+    /// - `mode`: Specifies both the file mode to use and the type of node to be created.
+    ///   Only `S_IFCHR` (character special file) and `S_IFBLK` (block special file) are
+    ///   supported currently.
+    /// - `dev`: The major and minor numbers of the newly created device special file.
+    ///
+    /// # Error
+    /// - `EEXIST`: pathname already exists.
+    /// - `EFAULT`: pathname points outside your accessible address space.
+    /// - `EINVAL`: mode requested creation of something other than a device special file.
+    /// - `ENOENT`: A directory component in pathname does not exist.
+    /// - `EPERM`: The filesystem containing pathname does not support the type of node requested.
+    fn mknodat(dirfd: usize, pathname: *const u8, mode: usize, dev: usize) -> SyscallResult {
+        Ok(0)
+    }
 }
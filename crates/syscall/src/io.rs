@@ -1,5 +1,213 @@
 use crate::SyscallResult;
 
+/* Terminal ioctl requests, defined in asm-generic/ioctls.h */
+
+/// Gets the current serial port settings.
+pub const TCGETS: usize = 0x5401;
+
+/// Sets the current serial port settings.
+pub const TCSETS: usize = 0x5402;
+
+/// Sets the current serial port settings after all output is written.
+pub const TCSETSW: usize = 0x5403;
+
+/// Sets the current serial port settings after all output is written, discarding
+/// pending input.
+pub const TCSETSF: usize = 0x5404;
+
+/// Gets the foreground process group ID of the terminal.
+pub const TIOCGPGRP: usize = 0x540F;
+
+/// Sets the foreground process group ID of the terminal.
+pub const TIOCSPGRP: usize = 0x5410;
+
+/// Gets the window size.
+pub const TIOCGWINSZ: usize = 0x5413;
+
+/// Sets the window size.
+pub const TIOCSWINSZ: usize = 0x5414;
+
+/// Gets the number of bytes available to read.
+pub const FIONREAD: usize = 0x541B;
+
+/// Sets or clears non-blocking mode of the open file description.
+pub const FIONBIO: usize = 0x5421;
+
+/// Do not block if the entropy pool is not initialized yet.
+pub const GRND_NONBLOCK: usize = 1;
+/// Draw from the random source instead of the urandom source.
+pub const GRND_RANDOM: usize = 2;
+/// Return non-cryptographic random bytes even if not initialized yet.
+pub const GRND_INSECURE: usize = 4;
+
+/// Window size of a terminal.
+///
+/// Defined in asm-generic/termios.h.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WinSize {
+    /// Rows, in characters
+    pub ws_row: u16,
+    /// Columns, in characters
+    pub ws_col: u16,
+    /// Horizontal size, in pixels (unused)
+    pub ws_xpixel: u16,
+    /// Vertical size, in pixels (unused)
+    pub ws_ypixel: u16,
+}
+
+/// Number of control characters in [`Termios`].
+pub const NCCS: usize = 19;
+
+/// Terminal settings got by `TCGETS` and set by `TCSETS`.
+///
+/// Defined in asm-generic/termbits.h.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Termios {
+    /// Input modes
+    pub c_iflag: u32,
+    /// Output modes
+    pub c_oflag: u32,
+    /// Control modes
+    pub c_cflag: u32,
+    /// Local modes
+    pub c_lflag: u32,
+    /// Line discipline
+    pub c_line: u8,
+    /// Control characters
+    pub c_cc: [u8; NCCS],
+}
+
+bitflags::bitflags! {
+    /// Events of a file descriptor requested or returned by `poll(2)`.
+    ///
+    /// Defined in asm-generic/poll.h.
+    #[derive(Default)]
+    pub struct PollEvents: i16 {
+        /// There is data to read.
+        const POLLIN = 0x001;
+        /// There is some exceptional condition on the file descriptor.
+        const POLLPRI = 0x002;
+        /// Writing is now possible.
+        const POLLOUT = 0x004;
+        /// Error condition (only returned in revents).
+        const POLLERR = 0x008;
+        /// Hang up (only returned in revents).
+        const POLLHUP = 0x010;
+        /// Invalid request: fd not open (only returned in revents).
+        const POLLNVAL = 0x020;
+    }
+}
+
+/// Returns the events occurred on a file given its readiness in bits shared by
+/// [`PollEvents`] and [`EpollEvents`].
+fn occurred_bits(events: u32, read_ready: bool, write_ready: bool, hangup: bool) -> u32 {
+    let mut revents = 0;
+    if events & PollEvents::POLLIN.bits() as u32 != 0 && read_ready {
+        revents |= PollEvents::POLLIN.bits() as u32;
+    }
+    if events & PollEvents::POLLOUT.bits() as u32 != 0 && write_ready {
+        revents |= PollEvents::POLLOUT.bits() as u32;
+    }
+    if hangup {
+        revents |= PollEvents::POLLHUP.bits() as u32;
+    }
+    revents
+}
+
+impl PollEvents {
+    /// Returns the events occurred on a file given its readiness.
+    ///
+    /// `POLLIN` and `POLLOUT` are only returned if requested in `self`, while
+    /// `POLLHUP` is always returned if the peer has hung up.
+    pub fn occurred(&self, read_ready: bool, write_ready: bool, hangup: bool) -> Self {
+        let bits = occurred_bits(self.bits() as u32, read_ready, write_ready, hangup);
+        Self::from_bits_truncate(bits as i16)
+    }
+}
+
+/// Sets the close-on-exec flag on the new file descriptor created by `epoll_create1(2)`.
+pub const EPOLL_CLOEXEC: usize = 0o2000000;
+
+/// Reads from the new eventfd decrement the counter by one instead of resetting it.
+pub const EFD_SEMAPHORE: usize = 1;
+/// Sets the close-on-exec flag on the new file descriptor created by `eventfd2(2)`.
+pub const EFD_CLOEXEC: usize = 0o2000000;
+/// Sets the `O_NONBLOCK` file status flag on the new eventfd.
+pub const EFD_NONBLOCK: usize = 0o4000;
+
+/// Adds an entry to the interest list of the epoll file descriptor.
+pub const EPOLL_CTL_ADD: usize = 1;
+/// Removes the target file descriptor from the interest list.
+pub const EPOLL_CTL_DEL: usize = 2;
+/// Changes the settings associated with the target file descriptor in the interest list.
+pub const EPOLL_CTL_MOD: usize = 3;
+
+bitflags::bitflags! {
+    /// Events of a file descriptor requested or returned by `epoll(7)`.
+    ///
+    /// Defined in linux/eventpoll.h.
+    #[derive(Default)]
+    pub struct EpollEvents: u32 {
+        /// The associated file is available for read operations.
+        const EPOLLIN = 0x001;
+        /// There is an exceptional condition on the file descriptor.
+        const EPOLLPRI = 0x002;
+        /// The associated file is available for write operations.
+        const EPOLLOUT = 0x004;
+        /// Error condition happened on the associated file descriptor (always reported).
+        const EPOLLERR = 0x008;
+        /// Hang up happened on the associated file descriptor (always reported).
+        const EPOLLHUP = 0x010;
+        /// Stream socket peer closed connection, or shut down writing half of connection.
+        const EPOLLRDHUP = 0x2000;
+        /// Sets an exclusive wakeup mode for the epoll file descriptor.
+        const EPOLLEXCLUSIVE = 1 << 28;
+        /// Prevents system suspend while the event is being processed.
+        const EPOLLWAKEUP = 1 << 29;
+        /// Disables the file descriptor after an event is reported, until it is
+        /// rearmed with `EPOLL_CTL_MOD`.
+        const EPOLLONESHOT = 1 << 30;
+        /// Requests edge-triggered notification.
+        const EPOLLET = 1 << 31;
+    }
+}
+
+impl EpollEvents {
+    /// Returns the events occurred on a file given its readiness.
+    ///
+    /// `EPOLLIN` and `EPOLLOUT` are only returned if requested in `self`, while
+    /// `EPOLLHUP` is always returned if the peer has hung up.
+    pub fn occurred(&self, read_ready: bool, write_ready: bool, hangup: bool) -> Self {
+        Self::from_bits_truncate(occurred_bits(self.bits(), read_ready, write_ready, hangup))
+    }
+}
+
+/// An event registered by `epoll_ctl(2)` or returned by `epoll_pwait(2)`.
+///
+/// Defined in linux/eventpoll.h, which is not packed except on x86_64.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EpollEvent {
+    /// Epoll events.
+    pub events: EpollEvents,
+    /// User data returned with the events.
+    pub data: u64,
+}
+
+/// A file descriptor to be monitored by `poll(2)`.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PollFd {
+    /// File descriptor, ignored if negative.
+    pub fd: i32,
+    /// Requested events.
+    pub events: PollEvents,
+    /// Returned events.
+    pub revents: PollEvents,
+}
+
 pub trait SyscallIO {
     /// Manipulates the underlying device parameters of special files.
     ///
@@ -7,7 +215,128 @@ pub trait SyscallIO {
     /// - `EBADF`: fd is not a valid file descriptor.
     /// - `EFAULT`: argp references an inaccessible memory area.
     /// - `EINVAL`: request or argp is not valid.
+    /// - `ENOTTY`: The specified request does not apply to the kind of object that the
+    ///   file descriptor fd references.
     fn ioctl(fd: usize, request: usize, argp: *const usize) -> SyscallResult {
         Ok(0)
     }
+
+    /// Waits for one of a set of file descriptors to become ready to perform I/O.
+    ///
+    /// Returns the number of file descriptors whose `revents` fields have been set
+    /// to a nonzero value. A value of 0 indicates that the call timed out and no
+    /// file descriptors were ready.
+    ///
+    /// # Argument
+    /// - `fds`: An array of [`PollFd`] structures.
+    /// - `nfds`: The number of items in the `fds` array.
+    /// - `tmo_p`: A pointer to a `TimeSpec` specifying the upper limit on the amount
+    ///   of time that the call will block. If it is NULL, then the call can block
+    ///   indefinitely. A zero timeout causes the call to return immediately.
+    /// - `sigmask`: The signal mask to be installed while waiting.
+    ///
+    /// # Error
+    /// - `EFAULT`: fds points outside the process's accessible address space.
+    /// - `EINVAL`: The nfds value exceeds the `RLIMIT_NOFILE` value, or the timeout
+    ///   value is invalid.
+    fn ppoll(fds: usize, nfds: usize, tmo_p: usize, sigmask: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Fills the buffer pointed to by `buf` with up to `buflen` random bytes.
+    ///
+    /// Returns the number of bytes copied to the buffer.
+    ///
+    /// # Argument
+    /// - `flags`: A bit mask of `GRND_NONBLOCK`, `GRND_RANDOM` and `GRND_INSECURE`.
+    ///
+    /// # Error
+    /// - `EAGAIN`: The requested entropy was not available, and `GRND_NONBLOCK` was set.
+    /// - `EFAULT`: The address referred to by buf is outside the accessible address space.
+    /// - `EINVAL`: An invalid flag was specified in flags.
+    fn getrandom(buf: *mut u8, buflen: usize, flags: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Creates an eventfd object holding a 64-bit counter initialized to `initval`,
+    /// returning a file descriptor referring to it.
+    ///
+    /// A read returns the counter and resets it, or returns 1 and decrements it with
+    /// `EFD_SEMAPHORE`, blocking while it is zero. A write adds an 8-byte integer to it,
+    /// blocking if the counter would exceed `0xfffffffffffffffe`.
+    ///
+    /// # Argument
+    /// - `flags`: A bit mask of `EFD_SEMAPHORE`, `EFD_CLOEXEC` and `EFD_NONBLOCK`.
+    ///
+    /// # Error
+    /// - `EINVAL`: An unsupported value was specified in flags.
+    /// - `EMFILE`: The per-process limit on the number of open file descriptors has
+    ///   been reached.
+    fn eventfd2(initval: usize, flags: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Creates a new epoll instance, returning a file descriptor referring to it.
+    ///
+    /// # Argument
+    /// - `flags`: `EPOLL_CLOEXEC` sets the close-on-exec flag on the new file descriptor.
+    ///
+    /// # Error
+    /// - `EINVAL`: Invalid value specified in flags.
+    /// - `EMFILE`: The per-process limit on the number of open file descriptors has
+    ///   been reached.
+    fn epoll_create1(flags: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Adds, modifies, or removes entries in the interest list of the epoll instance
+    /// referred to by the file descriptor `epfd`.
+    ///
+    /// `EPOLLET` is accepted, but events are always reported level-triggered.
+    ///
+    /// # Argument
+    /// - `op`: One of `EPOLL_CTL_ADD`, `EPOLL_CTL_MOD` and `EPOLL_CTL_DEL`.
+    /// - `fd`: The target file descriptor.
+    /// - `event`: A pointer to an [`EpollEvent`], ignored by `EPOLL_CTL_DEL`.
+    ///
+    /// # Error
+    /// - `EBADF`: `epfd` or `fd` is not a valid file descriptor.
+    /// - `EEXIST`: op was `EPOLL_CTL_ADD`, and `fd` is already registered.
+    /// - `EFAULT`: `event` points outside the accessible address space.
+    /// - `EINVAL`: `epfd` is not an epoll file descriptor, or `fd` is the same as `epfd`,
+    ///   or the requested operation is not supported.
+    /// - `ENOENT`: op was `EPOLL_CTL_MOD` or `EPOLL_CTL_DEL`, and `fd` is not registered.
+    /// - `EPERM`: The target file `fd` does not support epoll, e.g. a regular file.
+    fn epoll_ctl(epfd: usize, op: usize, fd: usize, event: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Waits for events on the epoll instance referred to by the file descriptor `epfd`.
+    ///
+    /// Returns the number of file descriptors ready for the requested I/O, or zero if
+    /// no file descriptor became ready during the requested timeout milliseconds.
+    ///
+    /// # Argument
+    /// - `events`: A buffer of at most `maxevents` [`EpollEvent`]s to return.
+    /// - `timeout`: The number of milliseconds to block. Specifying a timeout of -1
+    ///   causes the call to block indefinitely, while zero causes it to return immediately.
+    /// - `sigmask`: The signal mask to be installed while waiting.
+    ///
+    /// # Error
+    /// - `EBADF`: `epfd` is not a valid file descriptor.
+    /// - `EFAULT`: The memory area pointed to by `events` is not accessible with write
+    ///   permissions.
+    /// - `EINTR`: The call was interrupted by a signal handler before any requested
+    ///   event occurred or the timeout expired.
+    /// - `EINVAL`: `epfd` is not an epoll file descriptor, or `maxevents` is less than
+    ///   or equal to zero.
+    fn epoll_pwait(
+        epfd: usize,
+        events: usize,
+        maxevents: usize,
+        timeout: isize,
+        sigmask: usize,
+    ) -> SyscallResult {
+        Ok(0)
+    }
 }
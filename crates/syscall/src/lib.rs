@@ -20,47 +20,93 @@
 #![allow(unused)]
 #![allow(non_camel_case_types)]
 
+extern crate alloc;
+
+#[cfg(test)]
+mod test;
+
 mod comm;
 mod file;
 mod io;
 mod proc;
 mod timer;
+mod trace;
 
 pub use comm::*;
-use errno::Errno;
+pub use errno::Errno;
 pub use file::*;
 pub use io::*;
 use numeric_enum_macro::numeric_enum;
 pub use proc::*;
 pub use timer::*;
+pub use trace::*;
 
 numeric_enum! {
     #[repr(usize)]
-    #[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Hash)]
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Ord, Eq, Hash)]
     #[allow(non_camel_case_types)]
     pub enum SyscallNO {
+        GETCWD = 17,
+        EVENTFD2 = 19,
+        EPOLL_CREATE1 = 20,
+        EPOLL_CTL = 21,
+        EPOLL_PWAIT = 22,
+        DUP = 23,
+        DUP3 = 24,
+        FCNTL = 25,
         IOCTL = 29,
+        MKNODAT = 33,
         MKDIRAT = 34,
         UNLINKAT = 35,
         LINKAT = 37,
+        FACCESSAT = 48,
+        CHDIR = 49,
+        CHROOT = 51,
         OPENAT = 56,
         CLOSE = 57,
-        PIPE = 59,
+        PIPE2 = 59,
+        GETDENTS64 = 61,
         LSEEK = 62,
         READ = 63,
         WRTIE = 64,
         READV = 65,
         WRITEV = 66,
         PREAD = 67,
+        PWRITE = 68,
+        PWRITEV = 70,
+        SENDFILE = 71,
+        PPOLL = 73,
+        VMSPLICE = 75,
+        SPLICE = 76,
+        TEE = 77,
+        READLINKAT = 78,
+        NEWFSTATAT = 79,
+        FSTAT = 80,
+        SYNC = 81,
         EXIT = 93,
         EXIT_GROUP = 94,
         SET_TID_ADDRESS = 96,
+        FUTEX = 98,
+        SET_ROBUST_LIST = 99,
+        GET_ROBUST_LIST = 100,
         NANOSLEEP = 101,
+        GETITIMER = 102,
+        SETITIMER = 103,
         CLOCK_GET_TIME = 113,
+        CLOCK_GETRES = 114,
+        CLOCK_NANOSLEEP = 115,
+        KILL = 129,
+        TKILL = 130,
+        TGKILL = 131,
+        SIGALTSTACK = 132,
         SIGACTION = 134,
         SIGPROCMASK = 135,
         SIGTIMEDWAIT = 137,
         SIGRETURN = 139,
+        SETPGID = 154,
+        GETPGID = 155,
+        SETSID = 157,
+        UNAME = 160,
         GET_TIME_OF_DAY = 169,
         GETPID = 172,
         GETTID = 178,
@@ -70,8 +116,16 @@ numeric_enum! {
         EXECVE = 221,
         MMAP = 222,
         MPROTECT = 226,
+        MLOCK = 228,
+        MUNLOCK = 229,
+        MADVISE = 233,
         WAIT4 = 260,
         PRLIMIT64 = 261,
+        PROCESS_VM_READV = 270,
+        PROCESS_VM_WRITEV = 271,
+        RENAMEAT2 = 276,
+        GETRANDOM = 278,
+        MEMBARRIER = 283,
 
         // UINTR
         UINTR_REGISTER_RECEIVER = 244,
@@ -14,7 +14,7 @@ pub const P_PIDFD: usize = 3;
 
 /// `rlimit` structure.
 #[repr(C)]
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct Rlimit {
     /// The soft limit is the value that the kernel enforces for the corresponding resource.
     pub rlim_cur: u64,
@@ -25,6 +25,9 @@ pub struct Rlimit {
     pub rlim_max: u64,
 }
 
+/// No limit on a resource.
+pub const RLIM_INFINITY: u64 = u64::MAX;
+
 /// This is the maximum size of the process stack, in bytes. Upon reaching this limit, a SIGSEGV
 /// signal is generated. To handle this signal, a process must employ an alternate signal stack.
 ///
@@ -35,11 +38,82 @@ pub const RLIMIT_STACK: i32 = 3;
 /// by this process. Attempts (open(2), pipe(2), dup(2), etc.) to exceed this limit yield the error
 /// EMFILE. (Historically, this limit was named RLIMIT_OFILE on BSD.)
 pub const RLIMIT_NOFILE: i32 = 7;
+/// This is the maximum number of bytes of memory that may be locked into RAM. This limit is in
+/// effect rounded down to the nearest multiple of the system page size. This limit affects
+/// mlock(2), mlockall(2), and the mmap(2) MAP_LOCKED operation.
+pub const RLIMIT_MEMLOCK: i32 = 8;
 /// This is the maximum size of the process's virtual memory (address space). The limit is specified
 /// in bytes, and is rounded down to the system page size. This limit affects calls to brk(2), mmap(2),
 /// and mremap(2), which fail with the error ENOMEM upon exceeding this limit.
 pub const RLIMIT_AS: i32 = 9;
 
+/// Length of each field in [`UtsName`], including the terminating NUL.
+pub const UTSNAME_LEN: usize = 65;
+
+/// System information returned by `uname(2)`.
+///
+/// Defined in linux/utsname.h as `new_utsname`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct UtsName {
+    /// Operating system name (e.g., "Linux")
+    pub sysname: [u8; UTSNAME_LEN],
+    /// Name within some implementation-defined network
+    pub nodename: [u8; UTSNAME_LEN],
+    /// Operating system release (e.g., "2.6.28")
+    pub release: [u8; UTSNAME_LEN],
+    /// Operating system version
+    pub version: [u8; UTSNAME_LEN],
+    /// Hardware identifier
+    pub machine: [u8; UTSNAME_LEN],
+    /// NIS or YP domain name
+    pub domainname: [u8; UTSNAME_LEN],
+}
+
+impl UtsName {
+    /// Converts a string into a field, truncated to leave room for the terminating NUL,
+    /// with the unused tail zeroed.
+    pub fn field(s: &str) -> [u8; UTSNAME_LEN] {
+        let mut field = [0u8; UTSNAME_LEN];
+        let len = s.len().min(UTSNAME_LEN - 1);
+        field[..len].copy_from_slice(&s.as_bytes()[..len]);
+        field
+    }
+}
+
+/// No special treatment, the default for `madvise(2)`.
+pub const MADV_NORMAL: usize = 0;
+/// Expect page references in random order.
+pub const MADV_RANDOM: usize = 1;
+/// Expect page references in sequential order.
+pub const MADV_SEQUENTIAL: usize = 2;
+/// Expect access in the near future, so the pages may be read ahead.
+pub const MADV_WILLNEED: usize = 3;
+/// Do not expect access in the near future. Subsequent accesses of pages in the range
+/// will succeed, but will result in repopulating the memory contents from the up-to-date
+/// contents of the underlying mapped file, or zero-fill-on-demand pages for anonymous
+/// private mappings.
+pub const MADV_DONTNEED: usize = 4;
+
+/// Queries the set of supported commands of `membarrier(2)`.
+pub const MEMBARRIER_CMD_QUERY: usize = 0;
+/// All threads on the system pass a memory barrier.
+pub const MEMBARRIER_CMD_GLOBAL: usize = 1 << 0;
+/// All running threads of processes registered with
+/// [`MEMBARRIER_CMD_REGISTER_GLOBAL_EXPEDITED`] pass a memory barrier.
+pub const MEMBARRIER_CMD_GLOBAL_EXPEDITED: usize = 1 << 1;
+/// Registers the intent to receive [`MEMBARRIER_CMD_GLOBAL_EXPEDITED`] barriers.
+pub const MEMBARRIER_CMD_REGISTER_GLOBAL_EXPEDITED: usize = 1 << 2;
+/// All running threads of the calling process pass a memory barrier.
+pub const MEMBARRIER_CMD_PRIVATE_EXPEDITED: usize = 1 << 3;
+/// Registers the intent to use [`MEMBARRIER_CMD_PRIVATE_EXPEDITED`].
+pub const MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED: usize = 1 << 4;
+/// In addition to [`MEMBARRIER_CMD_PRIVATE_EXPEDITED`], all running threads of the calling
+/// process execute a core serializing instruction, e.g. after code is modified.
+pub const MEMBARRIER_CMD_PRIVATE_EXPEDITED_SYNC_CORE: usize = 1 << 5;
+/// Registers the intent to use [`MEMBARRIER_CMD_PRIVATE_EXPEDITED_SYNC_CORE`].
+pub const MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED_SYNC_CORE: usize = 1 << 6;
+
 pub trait SyscallProc {
     /// Terminate the calling process.
     fn exit(status: usize) -> !;
@@ -87,6 +161,14 @@ pub trait SyscallProc {
     /// - `-1`: meaning wait for any child process.
     /// - `0`: meaning wait for any child process whose process group ID is equal to that of the calling process.
     /// - `> 0`: meaning wait for the child whose process ID is equal the value of `pid`.
+    ///
+    /// If `WNOHANG` is set in `options` and no child has changed state yet, 0 is returned
+    /// immediately. If `rusage` is not NULL, the resource usage of the child is stored.
+    ///
+    /// # Error
+    /// - `ECHILD`: The process specified by `pid` does not exist or is not a child.
+    /// - `EINVAL`: The `options` argument was invalid.
+    /// - `EFAULT`: `wstatus` or `rusage` points outside the accessible address space.
     fn wait4(pid: isize, wstatus: usize, options: usize, rusage: usize) -> SyscallResult {
         Ok(0)
     }
@@ -100,10 +182,24 @@ pub trait SyscallProc {
     ///
     /// The pid argument specifies the ID of the process on which the call is to operate. If pid is 0, then the call
     /// applies to the calling process.
+    ///
+    /// # Error
+    /// - `EFAULT`: A pointer argument points to a location outside the accessible address space.
+    /// - `EINVAL`: The value specified in resource is not valid, or `rlim_cur` was greater than `rlim_max`.
+    /// - `EPERM`: An unprivileged process tried to raise the hard limit.
+    /// - `ESRCH`: Could not find a process with the ID specified in pid.
     fn prlimit64(pid: isize, resource: i32, new_limit: usize, old_limit: usize) -> SyscallResult {
         Ok(0)
     }
 
+    /// Returns system information in the [`UtsName`] structure pointed to by `buf`.
+    ///
+    /// # Error
+    /// - `EFAULT`: buf is not valid.
+    fn uname(buf: usize) -> SyscallResult {
+        Ok(0)
+    }
+
     /// Provides more precise control over which child state changes to wait for.
     fn waittid(idtype: usize, id: isize, infop: usize, options: usize) -> SyscallResult {
         Ok(0)
@@ -119,6 +215,46 @@ pub trait SyscallProc {
         Ok(0)
     }
 
+    /// Sets the process group ID of the process `pid` to `pgid`.
+    ///
+    /// If `pid` is 0, the process ID of the calling process is used. If `pgid` is 0, the
+    /// process ID of the process specified by `pid` is used, making it a process group
+    /// leader. Otherwise the process is moved into an existing process group in the
+    /// same session.
+    ///
+    /// # Error
+    /// - `EINVAL`: `pgid` is less than 0.
+    /// - `EPERM`: An attempt was made to move a process into a process group in a
+    ///   different session, or to change the process group ID of a session leader, or
+    ///   of a child of the calling process in a different session.
+    /// - `ESRCH`: `pid` is not the calling process and not a child of the calling process.
+    fn setpgid(pid: usize, pgid: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Returns the process group ID of the process `pid`, or the calling process if
+    /// `pid` is 0.
+    ///
+    /// # Error
+    /// - `ESRCH`: No process with the process ID `pid` was found.
+    fn getpgid(pid: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Creates a new session if the calling process is not a process group leader.
+    ///
+    /// The calling process becomes the leader of the new session and of a new process
+    /// group in the session, the IDs of which are both its process ID.
+    ///
+    /// Returns the new session ID.
+    ///
+    /// # Error
+    /// - `EPERM`: The process group ID of any process equals the process ID of the
+    ///   calling process.
+    fn setsid() -> SyscallResult {
+        Ok(0)
+    }
+
     /// Sets the clear_child_tid value for the calling thread to `tidptr`.
     ///
     /// # Return
@@ -202,6 +338,9 @@ pub trait SyscallProc {
     /// requested, but fd is not open for reading. Or MAP_SHARED was requested and PROT_WRITE
     /// is set, but fd is not open in read/write (O_RDWR) mode. Or PROT_WRITE is set, but the
     /// file is append-only.
+    /// - `EEXIST`: `MAP_FIXED_NOREPLACE` was specified and the range collides with an
+    ///   existing mapping.
+    /// - `EPERM`: a fixed `addr` is lower than the minimum address of mappings.
     fn mmap(
         addr: usize,
         len: usize,
@@ -233,4 +372,54 @@ pub trait SyscallProc {
     fn mprotect(addr: usize, len: usize, prot: usize) -> SyscallResult {
         Ok(0)
     }
+
+    /// Advises the kernel about how to handle paging input/output in the address range
+    /// beginning at address `addr` and with size `len` bytes.
+    ///
+    /// # Error
+    /// - `EINVAL`: `addr` is not page-aligned, `len` is negative or `advice` is not valid.
+    /// - `ENOMEM`: Addresses in the specified range are not currently mapped.
+    fn madvise(addr: usize, len: usize, advice: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Locks pages in the address range starting at `addr` and continuing for `len` bytes.
+    /// All pages that contain a part of the specified address range are guaranteed to be
+    /// resident in RAM when the call returns successfully; the pages are guaranteed to stay
+    /// in RAM until later unlocked.
+    ///
+    /// # Error
+    /// - `EINVAL`: Some of the specified address range cannot be locked.
+    /// - `ENOMEM`:
+    ///   - Some of the specified address range does not correspond to mapped pages in the
+    ///     address space of the process.
+    ///   - Locking would result in the total number of locked bytes exceeding the soft
+    ///     limit of `RLIMIT_MEMLOCK`.
+    fn mlock(addr: usize, len: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Unlocks pages in the address range starting at `addr` and continuing for `len` bytes.
+    /// After this call, all pages that contain a part of the specified memory range can be
+    /// moved to external swap space again by the kernel.
+    ///
+    /// # Error
+    /// - `ENOMEM`: Some of the specified address range does not correspond to mapped pages
+    ///   in the address space of the process.
+    fn munlock(addr: usize, len: usize) -> SyscallResult {
+        Ok(0)
+    }
+
+    /// Issues memory barriers on a set of threads, which orders their memory accesses
+    /// against those of the calling thread.
+    ///
+    /// # Return
+    /// [`MEMBARRIER_CMD_QUERY`] returns a bit mask of supported commands, while other
+    /// commands return zero.
+    ///
+    /// # Error
+    /// - `EINVAL`: `cmd` is invalid, or `flags` is not zero.
+    fn membarrier(cmd: usize, flags: usize, cpu_id: usize) -> SyscallResult {
+        Ok(0)
+    }
 }
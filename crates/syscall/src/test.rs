@@ -0,0 +1,104 @@
+extern crate std;
+
+use std::{format, string::ToString, vec};
+
+use crate::*;
+use vfs::OpenFlags;
+
+#[test]
+fn test_syscall_category() {
+    assert_eq!(SyscallNO::OPENAT.category(), SyscallCategory::File);
+    assert_eq!(SyscallNO::IOCTL.category(), SyscallCategory::File);
+    assert_eq!(SyscallNO::MMAP.category(), SyscallCategory::Mem);
+    assert_eq!(SyscallNO::CLONE.category(), SyscallCategory::Proc);
+    assert_eq!(SyscallNO::SIGACTION.category(), SyscallCategory::Proc);
+}
+
+#[test]
+fn test_syscall_trace_raw() {
+    let args = raw_args(&[AT_FDCWD, 0x1000, 0, 0, 0, 0]);
+    let result = Err(Errno::ENOENT);
+    let trace = SyscallTrace {
+        id: &SyscallNO::OPENAT,
+        args: &args,
+        result: &result,
+    }
+    .to_string();
+    assert!(trace.starts_with("openat(0xffffffffffffff9c, 0x1000, "));
+    assert!(trace.ends_with(" = -2 ENOENT (No such file or directory)"));
+}
+
+#[test]
+fn test_syscall_trace_decoded() {
+    let args = vec![
+        SyscallArg::Fd(AT_FDCWD),
+        SyscallArg::Str("/lib/x".to_string()),
+        SyscallArg::Named(format!("{:?}", OpenFlags::O_WRONLY | OpenFlags::O_CREAT)),
+    ];
+    let result = Ok(3);
+    let trace = SyscallTrace {
+        id: &SyscallNO::OPENAT,
+        args: &args,
+        result: &result,
+    }
+    .to_string();
+    assert_eq!(trace, "openat(AT_FDCWD, \"/lib/x\", O_WRONLY | O_CREAT) = 0x3");
+
+    let args = vec![SyscallArg::Named(format!("{:?}", OpenFlags::O_RDONLY))];
+    let result = Err(Errno::ENOENT);
+    let trace = SyscallTrace {
+        id: &SyscallNO::OPENAT,
+        args: &args,
+        result: &result,
+    }
+    .to_string();
+    assert!(trace.contains("O_RDONLY"));
+}
+
+#[test]
+fn test_poll_events() {
+    let events = PollEvents::POLLIN;
+
+    // a ready stdin-like file
+    assert_eq!(events.occurred(true, true, false), PollEvents::POLLIN);
+
+    // a pipe with no data
+    assert!(events.occurred(false, false, false).is_empty());
+
+    // POLLOUT is reported only if requested
+    let events = PollEvents::POLLIN | PollEvents::POLLOUT;
+    assert_eq!(events.occurred(false, true, false), PollEvents::POLLOUT);
+
+    // POLLHUP is always reported
+    assert_eq!(
+        PollEvents::empty().occurred(true, true, true),
+        PollEvents::POLLHUP
+    );
+    assert_eq!(core::mem::size_of::<PollFd>(), 8);
+}
+
+#[test]
+fn test_epoll_events() {
+    let events = EpollEvents::EPOLLIN | EpollEvents::EPOLLONESHOT;
+    assert_eq!(events.occurred(true, true, false), EpollEvents::EPOLLIN);
+    assert!(events.occurred(false, true, false).is_empty());
+    assert_eq!(
+        EpollEvents::EPOLLOUT.occurred(true, true, true),
+        EpollEvents::EPOLLOUT | EpollEvents::EPOLLHUP
+    );
+    assert_eq!(EpollEvents::EPOLLET.bits(), 0x8000_0000);
+    assert_eq!(core::mem::size_of::<EpollEvent>(), 16);
+}
+
+#[test]
+fn test_utsname_field() {
+    let field = UtsName::field("riscv64");
+    assert_eq!(&field[..7], b"riscv64");
+    assert!(field[7..].iter().all(|&b| b == 0));
+
+    // a long string is truncated to keep the terminating NUL
+    let field = UtsName::field(&"x".repeat(100));
+    assert!(field[..UTSNAME_LEN - 1].iter().all(|&b| b == b'x'));
+    assert_eq!(field[UTSNAME_LEN - 1], 0);
+    assert_eq!(core::mem::size_of::<UtsName>(), 6 * UTSNAME_LEN);
+}
@@ -9,6 +9,16 @@ pub trait SyscallTimer {
         Ok(0)
     }
 
+    /// Finds the resolution (precision) of the specified clock `clockid`, and, if `res`
+    /// is non-NULL, stores it in the struct timespec pointed to by `res`.
+    ///
+    /// # Error
+    /// - `EFAULT`: `res` points outside the accessible address space.
+    /// - `EINVAL`: The `clockid` specified is not supported on this system.
+    fn clock_getres(clockid: usize, res: usize) -> SyscallResult {
+        Ok(0)
+    }
+
     /*
         These system calls provide access to interval timers, that is, timers that
         initially expire at some point in the future, and (optionally) at regular
@@ -67,4 +77,24 @@ pub trait SyscallTimer {
     fn nanosleep(req: usize, rem: usize) -> SyscallResult {
         Ok(0)
     }
+
+    /// Like [`SyscallTimer::nanosleep`], suspends the execution of the calling thread, but allows
+    /// the caller to select the clock against which the sleep interval is to be measured, and
+    /// allows the sleep interval to be specified as either an absolute or a relative value.
+    ///
+    /// # Argument
+    /// - `clockid`: `CLOCK_REALTIME` or `CLOCK_MONOTONIC`.
+    /// - `flags`: If `TIMER_ABSTIME` is set, `request` is an absolute time measured by the clock,
+    ///   and `remain` is unused. If the time has already passed, the call returns immediately.
+    ///
+    /// # Error
+    /// - `EFAULT`: request or remain specified an invalid address.
+    /// - `EINTR`: The sleep was interrupted by a signal handler. The remaining time is written
+    ///   into *remain for a relative sleep.
+    /// - `EINVAL`: The value in the tv_nsec field was not in the range 0 to 999999999, or clockid
+    ///   was invalid.
+    /// - `EOPNOTSUPP`: The kernel does not support sleeping against this clockid.
+    fn clock_nanosleep(clockid: usize, flags: usize, request: usize, remain: usize) -> SyscallResult {
+        Ok(0)
+    }
 }
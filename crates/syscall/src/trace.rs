@@ -0,0 +1,150 @@
+use core::fmt::{self, Write};
+
+use alloc::{string::String, vec::Vec};
+
+use crate::{SyscallNO, SyscallResult, AT_FDCWD};
+
+/// Categories of system calls, used to enable tracing selectively.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyscallCategory {
+    /// File management and device I/O.
+    File,
+
+    /// Process control, signals and time.
+    Proc,
+
+    /// Memory management.
+    Mem,
+}
+
+impl SyscallNO {
+    /// Returns the category of this system call.
+    pub fn category(&self) -> SyscallCategory {
+        match self {
+            SyscallNO::GETCWD
+            | SyscallNO::DUP
+            | SyscallNO::DUP3
+            | SyscallNO::FCNTL
+            | SyscallNO::IOCTL
+            | SyscallNO::MKNODAT
+            | SyscallNO::MKDIRAT
+            | SyscallNO::UNLINKAT
+            | SyscallNO::LINKAT
+            | SyscallNO::FACCESSAT
+            | SyscallNO::CHDIR
+            | SyscallNO::CHROOT
+            | SyscallNO::OPENAT
+            | SyscallNO::CLOSE
+            | SyscallNO::PIPE2
+            | SyscallNO::GETDENTS64
+            | SyscallNO::LSEEK
+            | SyscallNO::READ
+            | SyscallNO::WRTIE
+            | SyscallNO::READV
+            | SyscallNO::WRITEV
+            | SyscallNO::PREAD
+            | SyscallNO::PWRITE
+            | SyscallNO::PWRITEV
+            | SyscallNO::SENDFILE
+            | SyscallNO::PPOLL
+            | SyscallNO::EVENTFD2
+            | SyscallNO::EPOLL_CREATE1
+            | SyscallNO::EPOLL_CTL
+            | SyscallNO::EPOLL_PWAIT
+            | SyscallNO::VMSPLICE
+            | SyscallNO::SPLICE
+            | SyscallNO::TEE
+            | SyscallNO::READLINKAT
+            | SyscallNO::NEWFSTATAT
+            | SyscallNO::FSTAT
+            | SyscallNO::SYNC
+            | SyscallNO::RENAMEAT2 => SyscallCategory::File,
+            SyscallNO::BRK
+            | SyscallNO::MUNMAP
+            | SyscallNO::MMAP
+            | SyscallNO::MPROTECT
+            | SyscallNO::MLOCK
+            | SyscallNO::MUNLOCK
+            | SyscallNO::MADVISE => SyscallCategory::Mem,
+            _ => SyscallCategory::Proc,
+        }
+    }
+}
+
+/// A decoded argument of a system call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyscallArg {
+    /// A raw value, displayed in hexadecimal.
+    Hex(usize),
+
+    /// A signed integer, displayed in decimal.
+    Int(isize),
+
+    /// A file descriptor, displayed in decimal or as `AT_FDCWD`.
+    Fd(usize),
+
+    /// A string copied from user space, displayed quoted.
+    Str(String),
+
+    /// A value already rendered by the caller, e.g. names of flags.
+    Named(String),
+}
+
+impl fmt::Display for SyscallArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SyscallArg::Hex(value) => write!(f, "{:#x}", value),
+            SyscallArg::Int(value) => write!(f, "{}", value),
+            SyscallArg::Fd(fd) if *fd == AT_FDCWD => write!(f, "AT_FDCWD"),
+            SyscallArg::Fd(fd) => write!(f, "{}", *fd as isize),
+            SyscallArg::Str(s) => write!(f, "{:?}", s),
+            SyscallArg::Named(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+/// Decodes arguments as raw values.
+pub fn raw_args(args: &[usize; 6]) -> Vec<SyscallArg> {
+    args.iter().map(|&arg| SyscallArg::Hex(arg)).collect()
+}
+
+/// A record of a finished system call, displayed as `name(args) = result`.
+///
+/// For example, a failed `openat` is displayed as:
+///
+/// ```text
+/// openat(AT_FDCWD, "/lib/x", O_RDONLY) = -2 ENOENT (No such file or directory)
+/// ```
+pub struct SyscallTrace<'a> {
+    pub id: &'a SyscallNO,
+    pub args: &'a [SyscallArg],
+    pub result: &'a SyscallResult,
+}
+
+impl<'a> fmt::Display for SyscallTrace<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(Lowercase(f), "{:?}(", self.id)?;
+        for (i, arg) in self.args.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", arg)?;
+        }
+        match self.result {
+            Ok(ret) => write!(f, ") = {:#x}", ret),
+            Err(errno) => write!(f, ") = {} {:?} ({})", -isize::from(*errno), errno, errno),
+        }
+    }
+}
+
+/// Writes lowercase characters to the inner formatter.
+struct Lowercase<'a, 'b>(&'a mut fmt::Formatter<'b>);
+
+impl<'a, 'b> fmt::Write for Lowercase<'a, 'b> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for c in s.chars() {
+            self.0.write_char(c.to_ascii_lowercase())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,50 @@
+/// Run time accumulated by a task, counted in clock cycles.
+///
+/// The scheduler starts a time slice when the task is switched to, and stops
+/// it when the task is descheduled.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CpuTime {
+    /// Run time of finished time slices.
+    total: usize,
+
+    /// Clock cycles when the current time slice started, or `None` if the task
+    /// is not running.
+    stamp: Option<usize>,
+}
+
+impl CpuTime {
+    /// Creates a task that has never run.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a time slice at `now`.
+    pub fn start(&mut self, now: usize) {
+        self.stamp = Some(now);
+    }
+
+    /// Stops the current time slice at `now`.
+    ///
+    /// Returns the length of the time slice, or zero if the task is not running.
+    pub fn stop(&mut self, now: usize) -> usize {
+        match self.stamp.take() {
+            Some(stamp) => {
+                let slice = now.saturating_sub(stamp);
+                self.total += slice;
+                slice
+            }
+            None => 0,
+        }
+    }
+
+    /// Returns the length of the current time slice at `now`, or zero if the task
+    /// is not running.
+    pub fn running(&self, now: usize) -> usize {
+        self.stamp.map_or(0, |stamp| now.saturating_sub(stamp))
+    }
+
+    /// Returns the run time at `now`, including the current time slice.
+    pub fn get(&self, now: usize) -> usize {
+        self.total + self.running(now)
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{ITimerVal, TimeSpec};
+
+/// An interval timer, which initially expires at some point in the future, and
+/// (optionally) at regular intervals after that.
+///
+/// When a timer expires, a signal is generated for the calling process, and the
+/// timer is reset to the specified interval (if the interval is nonzero).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ITimer {
+    /// Interval for periodic timer.
+    ///
+    /// If it is zero, then this is a single-shot timer.
+    pub interval: TimeSpec,
+
+    /// Time until next expiration.
+    ///
+    /// If it is zero, then this timer is currently disarmed.
+    pub value: TimeSpec,
+}
+
+impl ITimer {
+    /// Creates a new disarmed timer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the timer is armed.
+    pub fn is_armed(&self) -> bool {
+        !self.value.is_zero()
+    }
+
+    /// Counts down the timer with the time elapsed since last update.
+    ///
+    /// Returns true if the timer expires. An expired timer will be rearmed from
+    /// `interval`, or disarmed if `interval` is zero. Expirations overrun within
+    /// one update are coalesced, just like a pending signal.
+    pub fn update(&mut self, elapsed: TimeSpec) -> bool {
+        if !self.is_armed() {
+            return false;
+        }
+
+        if elapsed < self.value {
+            self.value -= elapsed;
+            return false;
+        }
+
+        let overrun = (elapsed - self.value).as_nanos();
+        self.value = if self.interval.is_zero() {
+            TimeSpec::default()
+        } else {
+            let interval = self.interval.as_nanos();
+            TimeSpec::from_nanos(interval - overrun % interval)
+        };
+        true
+    }
+}
+
+impl From<&ITimerVal> for ITimer {
+    fn from(value: &ITimerVal) -> Self {
+        Self {
+            interval: value.it_interval.into(),
+            value: value.it_value.into(),
+        }
+    }
+}
+
+impl From<ITimer> for ITimerVal {
+    fn from(value: ITimer) -> Self {
+        Self {
+            it_interval: value.interval.into(),
+            it_value: value.value.into(),
+        }
+    }
+}
+
+/// Arms or disarms the timer in `slot`, returning the previous value of the timer.
+pub fn set_itimer(slot: &mut ITimer, new: ITimer) -> ITimer {
+    let old = *slot;
+    *slot = new;
+    old
+}
+
+/// Gets the current value of the timer in `slot`.
+pub fn get_itimer(slot: &ITimer) -> ITimer {
+    *slot
+}
@@ -1,12 +1,22 @@
 #![no_std]
 
+extern crate alloc;
+
 mod config;
+mod cputime;
+mod itimer;
+mod seqlock;
 mod spec;
 mod test;
+mod wheel;
 
 pub use config::*;
+pub use cputime::*;
+pub use itimer::*;
 use numeric_enum_macro::numeric_enum;
+pub use seqlock::SeqLock;
 pub use spec::*;
+pub use wheel::*;
 
 numeric_enum! {
     #[repr(usize)]
@@ -17,9 +27,13 @@ numeric_enum! {
         MONOTONIC = 1,
         PROCESS_CPUTIME_ID = 2,
         THREAD_CPUTIME_ID = 3,
+        BOOTTIME = 7,
     }
 }
 
+/// The request of `clock_nanosleep(2)` is an absolute time instead of an interval.
+pub const TIMER_ABSTIME: usize = 1;
+
 numeric_enum! {
     #[repr(usize)]
     #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -76,6 +90,21 @@ impl ClockID {
     pub fn is_proc(&self) -> bool {
         (self.0 as usize & 4) == 0
     }
+
+    /// Returns whether it is a CPU clock of a process or thread identified by the clock id,
+    /// instead of a [`ClockType`].
+    pub fn is_dynamic(&self) -> bool {
+        self.0 < 0
+    }
+
+    /// Gets the [`ClockType`], or `None` if it is dynamic or invalid.
+    pub fn get_clock(&self) -> Option<ClockType> {
+        if self.is_dynamic() {
+            None
+        } else {
+            ClockType::try_from(self.0 as usize).ok()
+        }
+    }
 }
 
 /// System clock abstraction for different clocks.  
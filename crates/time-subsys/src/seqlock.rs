@@ -0,0 +1,74 @@
+use core::{
+    cell::UnsafeCell,
+    hint::spin_loop,
+    ptr,
+    sync::atomic::{fence, AtomicUsize, Ordering},
+};
+
+/// A sequence lock for data frequently read but rarely written, e.g. the current time
+/// updated by timer interrupts.
+///
+/// Readers never block writers. A reader copies the data and retries if a writer
+/// has changed it in the meantime, which is detected by the sequence number being
+/// odd during a write or changed after the copy. Writers are serialized by the
+/// sequence number itself.
+pub struct SeqLock<T: Copy> {
+    /// Incremented before and after each write.
+    seq: AtomicUsize,
+
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Copy + Send> Send for SeqLock<T> {}
+unsafe impl<T: Copy + Send> Sync for SeqLock<T> {}
+
+impl<T: Copy> SeqLock<T> {
+    pub const fn new(data: T) -> Self {
+        Self {
+            seq: AtomicUsize::new(0),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// Returns a copy of the data, which is never torn by a concurrent write.
+    pub fn read(&self) -> T {
+        loop {
+            let seq = self.seq.load(Ordering::Acquire);
+            if seq & 1 == 0 {
+                // The copy may be torn, which is discarded if the sequence changes.
+                let data = unsafe { ptr::read_volatile(self.data.get()) };
+                fence(Ordering::Acquire);
+                if self.seq.load(Ordering::Relaxed) == seq {
+                    return data;
+                }
+            }
+            spin_loop();
+        }
+    }
+
+    /// Modifies the data with `f`, spinning while another writer holds the lock.
+    ///
+    /// Readers retry until `f` returns, so it should be short.
+    pub fn write(&self, f: impl FnOnce(&mut T)) {
+        let mut seq = self.seq.load(Ordering::Relaxed);
+        loop {
+            if seq & 1 == 0 {
+                match self.seq.compare_exchange_weak(
+                    seq,
+                    seq.wrapping_add(1),
+                    Ordering::Acquire,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => break,
+                    Err(curr) => seq = curr,
+                }
+            } else {
+                spin_loop();
+                seq = self.seq.load(Ordering::Relaxed);
+            }
+        }
+        fence(Ordering::Release);
+        f(unsafe { &mut *self.data.get() });
+        self.seq.store(seq.wrapping_add(2), Ordering::Release);
+    }
+}
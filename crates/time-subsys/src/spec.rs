@@ -1,8 +1,8 @@
-use core::ops::{Add, AddAssign};
+use core::ops::{Add, AddAssign, Sub, SubAssign};
 
 use numeric_enum_macro::numeric_enum;
 
-use crate::{config::NSEC_PER_SEC, USEC_PER_SEC};
+use crate::{config::NSEC_PER_SEC, NSEC_PER_USEC, USEC_PER_SEC};
 
 /// Represents an elapsed time.
 #[repr(C)]
@@ -28,41 +28,62 @@ impl TimeSpec {
     pub fn time_in_sec(&self) -> f64 {
         self.tv_sec as f64 + self.tv_nsec as f64 / NSEC_PER_SEC as f64
     }
+
+    /// Creates a new time specification from nanoseconds.
+    pub fn from_nanos(nanos: u64) -> Self {
+        Self {
+            tv_sec: (nanos / NSEC_PER_SEC as u64) as usize,
+            tv_nsec: (nanos % NSEC_PER_SEC as u64) as usize,
+        }
+    }
+
+    /// Returns total time in nanoseconds.
+    pub fn as_nanos(&self) -> u64 {
+        self.tv_sec as u64 * NSEC_PER_SEC as u64 + self.tv_nsec as u64
+    }
+
+    /// Returns true if the elapsed time is zero.
+    pub fn is_zero(&self) -> bool {
+        self.tv_sec == 0 && self.tv_nsec == 0
+    }
 }
 
 impl Add for TimeSpec {
     type Output = TimeSpec;
 
     fn add(self, rhs: Self) -> Self::Output {
-        let mut new_ts = Self {
-            tv_sec: self.tv_sec + rhs.tv_sec,
-            tv_nsec: self.tv_nsec + rhs.tv_nsec,
-        };
-        if new_ts.tv_nsec >= NSEC_PER_SEC {
-            new_ts.tv_sec += 1;
-            new_ts.tv_nsec -= NSEC_PER_SEC;
+        let nsec = self.tv_nsec + rhs.tv_nsec;
+        Self {
+            tv_sec: self.tv_sec + rhs.tv_sec + nsec / NSEC_PER_SEC,
+            tv_nsec: nsec % NSEC_PER_SEC,
         }
-        new_ts
     }
 }
 
 impl AddAssign for TimeSpec {
     fn add_assign(&mut self, rhs: Self) {
-        let mut new_ts = Self {
-            tv_sec: self.tv_sec + rhs.tv_sec,
-            tv_nsec: self.tv_nsec + rhs.tv_nsec,
-        };
-        if new_ts.tv_nsec >= NSEC_PER_SEC {
-            new_ts.tv_sec += 1;
-            new_ts.tv_nsec -= NSEC_PER_SEC;
-        }
-        *self = new_ts;
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for TimeSpec {
+    type Output = TimeSpec;
+
+    /// Saturates to zero if `rhs` is later than `self`.
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::from_nanos(self.as_nanos().saturating_sub(rhs.as_nanos()))
+    }
+}
+
+impl SubAssign for TimeSpec {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
     }
 }
 
 /// Represents an elapsed time.
 #[repr(C)]
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone, Copy, Default)]
 pub struct TimeVal {
     /// Number of whole seconds of elapsed time.
     pub tv_sec: usize,
@@ -86,6 +107,24 @@ impl TimeVal {
     }
 }
 
+impl From<TimeVal> for TimeSpec {
+    fn from(value: TimeVal) -> Self {
+        Self {
+            tv_sec: value.tv_sec,
+            tv_nsec: value.tv_usec * NSEC_PER_USEC,
+        }
+    }
+}
+
+impl From<TimeSpec> for TimeVal {
+    fn from(value: TimeSpec) -> Self {
+        Self {
+            tv_sec: value.tv_sec,
+            tv_usec: value.tv_nsec / NSEC_PER_USEC,
+        }
+    }
+}
+
 /// Syscall `times()` stores current process times in this struct.
 #[repr(C)]
 #[derive(Debug)]
@@ -103,9 +142,62 @@ pub struct TMS {
     pub cstime: usize,
 }
 
+/// Syscalls `getrusage()` and `wait4()` store resource usage in this struct.
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Rusage {
+    /// User CPU time used
+    pub ru_utime: TimeVal,
+
+    /// System CPU time used
+    pub ru_stime: TimeVal,
+
+    /// Maximum resident set size
+    pub ru_maxrss: usize,
+
+    /// Integral shared memory size
+    pub ru_ixrss: usize,
+
+    /// Integral unshared data size
+    pub ru_idrss: usize,
+
+    /// Integral unshared stack size
+    pub ru_isrss: usize,
+
+    /// Page reclaims (soft page faults)
+    pub ru_minflt: usize,
+
+    /// Page faults (hard page faults)
+    pub ru_majflt: usize,
+
+    /// Swaps
+    pub ru_nswap: usize,
+
+    /// Block input operations
+    pub ru_inblock: usize,
+
+    /// Block output operations
+    pub ru_oublock: usize,
+
+    /// IPC messages sent
+    pub ru_msgsnd: usize,
+
+    /// IPC messages received
+    pub ru_msgrcv: usize,
+
+    /// Signals received
+    pub ru_nsignals: usize,
+
+    /// Voluntary context switches
+    pub ru_nvcsw: usize,
+
+    /// Involuntary context switches
+    pub ru_nivcsw: usize,
+}
+
 numeric_enum! {
     #[repr(usize)]
-    #[derive(Debug)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum ITimerType {
         /// This timer counts down in real (i.e., wall clock) time.
         /// At each expiration, a SIGALRM signal is generated.
@@ -130,7 +222,7 @@ numeric_enum! {
 
 /// Syscall `getitimer()` and `setitimer` handle user timer with this struct.
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Default)]
 pub struct ITimerVal {
     /// Interval for periodic timer.
     ///
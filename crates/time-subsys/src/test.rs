@@ -0,0 +1,219 @@
+#![cfg(test)]
+
+extern crate std;
+
+use std::thread;
+
+use crate::*;
+
+/// Simulates timer interrupts every 10ms, returns the number of expirations.
+fn advance(timer: &mut ITimer, ticks: usize) -> usize {
+    let tick = TimeSpec {
+        tv_sec: 0,
+        tv_nsec: 10 * NSEC_PER_MSEC,
+    };
+    (0..ticks).filter(|_| timer.update(tick)).count()
+}
+
+#[test]
+fn test_itimer_oneshot() {
+    let mut slot = ITimer::new();
+    let old = set_itimer(
+        &mut slot,
+        ITimer {
+            interval: TimeSpec::default(),
+            value: TimeSpec::new(0.05),
+        },
+    );
+    assert!(!old.is_armed());
+    assert!(slot.is_armed());
+
+    assert_eq!(advance(&mut slot, 4), 0);
+    assert_eq!(advance(&mut slot, 1), 1);
+    assert!(!get_itimer(&slot).is_armed());
+    assert_eq!(advance(&mut slot, 100), 0);
+}
+
+#[test]
+fn test_itimer_periodic() {
+    let mut slot = ITimer::new();
+    set_itimer(
+        &mut slot,
+        ITimer {
+            interval: TimeSpec {
+                tv_sec: 0,
+                tv_nsec: 20 * NSEC_PER_MSEC,
+            },
+            value: TimeSpec {
+                tv_sec: 0,
+                tv_nsec: 50 * NSEC_PER_MSEC,
+            },
+        },
+    );
+
+    assert_eq!(advance(&mut slot, 5), 1);
+    assert_eq!(advance(&mut slot, 20), 10);
+    assert!(get_itimer(&slot).is_armed());
+
+    // overrun expirations are coalesced
+    assert!(slot.update(TimeSpec {
+        tv_sec: 1,
+        tv_nsec: 5 * NSEC_PER_MSEC,
+    }));
+    assert_eq!(slot.value.tv_nsec, 15 * NSEC_PER_MSEC);
+
+    // disarm
+    let old = set_itimer(&mut slot, ITimer::new());
+    assert_eq!(old.interval.tv_nsec, 20 * NSEC_PER_MSEC);
+    assert_eq!(advance(&mut slot, 100), 0);
+}
+
+#[test]
+fn test_timespec_carry() {
+    let a = TimeSpec {
+        tv_sec: 1,
+        tv_nsec: 999_999_999,
+    };
+    let b = TimeSpec {
+        tv_sec: 0,
+        tv_nsec: 2,
+    };
+    let sum = a + b;
+    assert_eq!(sum.tv_sec, 2);
+    assert_eq!(sum.tv_nsec, 1);
+
+    // borrow across the second boundary
+    let diff = sum - TimeSpec {
+        tv_sec: 0,
+        tv_nsec: 3,
+    };
+    assert_eq!(diff.tv_sec, 1);
+    assert_eq!(diff.tv_nsec, 999_999_998);
+
+    let mut ts = a;
+    ts += a;
+    assert_eq!(ts, TimeSpec::from_nanos(3_999_999_998));
+    ts -= a;
+    assert_eq!(ts, a);
+}
+
+#[test]
+fn test_timespec_saturate() {
+    let a = TimeSpec::from_nanos(5);
+    let b = TimeSpec::from_nanos(NSEC_PER_SEC as u64);
+    assert!((a - b).is_zero());
+    assert_eq!(b - a, TimeSpec::from_nanos(999_999_995));
+    assert_eq!(b.as_nanos(), NSEC_PER_SEC as u64);
+}
+
+#[test]
+fn test_timespec_ordering() {
+    let a = TimeSpec {
+        tv_sec: 1,
+        tv_nsec: 999_999_999,
+    };
+    let b = TimeSpec {
+        tv_sec: 2,
+        tv_nsec: 0,
+    };
+    let c = TimeSpec::from_nanos(2_000_000_001);
+    assert!(a < b);
+    assert!(b < c);
+    assert!(a + TimeSpec::from_nanos(1) == b);
+    assert_eq!(a.max(c), c);
+    assert!(TimeSpec::default().is_zero());
+}
+
+#[test]
+fn test_timer_wheel() {
+    let mut wheel = TimerWheel::new();
+    wheel.add(TimeSpec::from_nanos(300), 3);
+    wheel.add(TimeSpec::from_nanos(100), 1);
+    wheel.add(TimeSpec::from_nanos(500), 5);
+    wheel.add(TimeSpec::from_nanos(200), 2);
+    wheel.add(TimeSpec::from_nanos(100), 4);
+    assert_eq!(wheel.len(), 5);
+    assert_eq!(wheel.next_deadline(), Some(TimeSpec::from_nanos(100)));
+
+    assert!(wheel.expire(TimeSpec::from_nanos(99)).is_empty());
+    assert_eq!(wheel.expire(TimeSpec::from_nanos(100)), [1, 4]);
+    assert_eq!(wheel.expire(TimeSpec::from_nanos(350)), [2, 3]);
+
+    // cancel and re-add
+    assert_eq!(wheel.cancel(5), Some(TimeSpec::from_nanos(500)));
+    assert_eq!(wheel.cancel(5), None);
+    wheel.add(TimeSpec::from_nanos(400), 6);
+    wheel.add(TimeSpec::from_nanos(1000), 6);
+    assert_eq!(wheel.expire(TimeSpec::from_nanos(999)), []);
+    assert_eq!(wheel.expire(TimeSpec::from_nanos(1000)), [6]);
+    assert!(wheel.is_empty());
+}
+
+#[test]
+fn test_sleep_deadline() {
+    let now = TimeSpec::new(3.5);
+    let req = TimeSpec::from_nanos(2_000);
+
+    // A relative sleep advances the clock by at least `req`.
+    let mut wheel = TimerWheel::new();
+    let deadline = sleep_deadline(now, req, 0);
+    wheel.add(deadline, 1);
+    assert!(wheel.expire(now + req - TimeSpec::from_nanos(1)).is_empty());
+    assert_eq!(wheel.expire(now + req), [1]);
+    assert!(deadline - now >= req);
+
+    // An absolute sleep in the past expires immediately.
+    let deadline = sleep_deadline(now, req, TIMER_ABSTIME);
+    assert_eq!(deadline, req);
+    wheel.add(deadline, 2);
+    assert_eq!(wheel.expire(now), [2]);
+    assert!((deadline - now).is_zero());
+}
+
+#[test]
+fn test_cpu_time() {
+    let mut busy = CpuTime::new();
+    let mut idle = CpuTime::new();
+    assert_eq!(busy.get(0), 0);
+
+    // `busy` runs in every time slice, while `idle` is always sleeping.
+    for tick in 0..10 {
+        busy.start(tick * 100);
+        assert_eq!(busy.get(tick * 100 + 50), tick * 100 + 50);
+        assert_eq!(busy.running(tick * 100 + 50), 50);
+        assert_eq!(busy.stop(tick * 100 + 100), 100);
+    }
+    assert_eq!(busy.get(5000), 1000);
+    assert_eq!(idle.get(5000), 0);
+    assert_eq!(idle.stop(5000), 0);
+
+    idle.start(5000);
+    assert_eq!(idle.stop(5010), 10);
+    assert_eq!(idle.get(6000), 10);
+}
+
+#[test]
+fn test_seqlock() {
+    const WRITES: usize = 100_000;
+
+    // Both fields are always equal unless a write is observed halfway.
+    let time = SeqLock::new(TimeSpec::default());
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            for i in 1..=WRITES {
+                time.write(|time| {
+                    time.tv_sec = i;
+                    time.tv_nsec = i;
+                });
+            }
+        });
+        let mut last = 0;
+        while last < WRITES {
+            let now = time.read();
+            assert_eq!(now.tv_sec, now.tv_nsec);
+            assert!(now.tv_sec >= last);
+            last = now.tv_sec;
+        }
+    });
+    assert_eq!(time.read().tv_sec, WRITES);
+}
@@ -0,0 +1,89 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    vec::Vec,
+};
+
+use crate::{TimeSpec, TIMER_ABSTIME};
+
+/// Returns the deadline of a sleep request `req` made at `now`.
+///
+/// `req` is an absolute time if `flags` contains [`TIMER_ABSTIME`], which might
+/// have passed already, otherwise it is an interval relative to `now`.
+pub fn sleep_deadline(now: TimeSpec, req: TimeSpec, flags: usize) -> TimeSpec {
+    if flags & TIMER_ABSTIME != 0 {
+        req
+    } else {
+        now + req
+    }
+}
+
+/// Pending timeouts waiting to be expired, e.g. tasks sleeping in `nanosleep(2)`
+/// or waiting on a futex with a timeout.
+///
+/// Timers are kept sorted by deadline, so both adding a timer and expiring timers
+/// are `O(log n)` in the number of pending timers (plus the number of expired ones).
+#[derive(Debug, Default)]
+pub struct TimerWheel {
+    /// Pending timers sorted by deadline, then by id.
+    timers: BTreeSet<(TimeSpec, usize)>,
+
+    /// Deadline of each pending timer, used to cancel a timer by id.
+    deadlines: BTreeMap<usize, TimeSpec>,
+}
+
+impl TimerWheel {
+    /// Creates an empty timer wheel.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a timer which expires at `deadline`.
+    ///
+    /// The old timer will be replaced if `id` is already pending.
+    pub fn add(&mut self, deadline: TimeSpec, id: usize) {
+        if let Some(old) = self.deadlines.insert(id, deadline) {
+            self.timers.remove(&(old, id));
+        }
+        self.timers.insert((deadline, id));
+    }
+
+    /// Cancels a pending timer.
+    ///
+    /// Returns the deadline of the timer, or `None` if not found.
+    pub fn cancel(&mut self, id: usize) -> Option<TimeSpec> {
+        let deadline = self.deadlines.remove(&id)?;
+        self.timers.remove(&(deadline, id));
+        Some(deadline)
+    }
+
+    /// Removes all timers whose deadline has passed at `now`.
+    ///
+    /// Returns the ids of expired timers in the order of their deadlines.
+    pub fn expire(&mut self, now: TimeSpec) -> Vec<usize> {
+        let mut expired = Vec::new();
+        while let Some(&(deadline, id)) = self.timers.first() {
+            if deadline > now {
+                break;
+            }
+            self.timers.pop_first();
+            self.deadlines.remove(&id);
+            expired.push(id);
+        }
+        expired
+    }
+
+    /// Returns the earliest deadline of pending timers.
+    pub fn next_deadline(&self) -> Option<TimeSpec> {
+        self.timers.first().map(|&(deadline, _)| deadline)
+    }
+
+    /// Returns the number of pending timers.
+    pub fn len(&self) -> usize {
+        self.deadlines.len()
+    }
+
+    /// Returns true if no timer is pending.
+    pub fn is_empty(&self) -> bool {
+        self.deadlines.is_empty()
+    }
+}
@@ -6,7 +6,11 @@ mod test;
 extern crate alloc;
 
 use alloc::vec::Vec;
-use core::iter::{IntoIterator, Iterator};
+use core::{
+    iter::{IntoIterator, Iterator},
+    mem::{size_of, MaybeUninit},
+    slice,
+};
 
 pub struct UserBuffer {
     pub inner: Vec<&'static mut [u8]>,
@@ -17,6 +21,116 @@ impl UserBuffer {
     pub fn new(buffers: Vec<&'static mut [u8]>) -> Self {
         Self { inner: buffers }
     }
+
+    /// Returns the total length of inner buffers.
+    pub fn len(&self) -> usize {
+        self.inner.iter().map(|buf| buf.len()).sum()
+    }
+
+    /// Returns true if inner buffers hold no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.inner.iter().all(|buf| buf.is_empty())
+    }
+
+    /// Copies bytes from `src` to this buffer chunk by chunk.
+    ///
+    /// Returns the number of bytes copied, which is the smaller of both lengths.
+    pub fn copy_from_slice(&mut self, src: &[u8]) -> usize {
+        let mut pos = 0;
+        for buf in self.inner.iter_mut() {
+            let len = buf.len().min(src.len() - pos);
+            buf[..len].copy_from_slice(&src[pos..pos + len]);
+            pos += len;
+            if pos == src.len() {
+                break;
+            }
+        }
+        pos
+    }
+
+    /// Copies bytes from this buffer to `dst` chunk by chunk.
+    ///
+    /// Returns the number of bytes copied, which is the smaller of both lengths.
+    pub fn copy_to_slice(&self, dst: &mut [u8]) -> usize {
+        let mut pos = 0;
+        for buf in self.inner.iter() {
+            let len = buf.len().min(dst.len() - pos);
+            dst[pos..pos + len].copy_from_slice(&buf[..len]);
+            pos += len;
+            if pos == dst.len() {
+                break;
+            }
+        }
+        pos
+    }
+
+    /// Copies bytes starting at `off` in this buffer to `dst`.
+    ///
+    /// Returns `None` without copying if `dst` does not fit in this buffer.
+    fn copy_out(&self, off: usize, dst: &mut [u8]) -> Option<()> {
+        if off.checked_add(dst.len())? > self.len() {
+            return None;
+        }
+        let mut off = off;
+        let mut pos = 0;
+        for buf in self.inner.iter() {
+            if pos == dst.len() {
+                break;
+            }
+            if off >= buf.len() {
+                off -= buf.len();
+                continue;
+            }
+            let len = (buf.len() - off).min(dst.len() - pos);
+            dst[pos..pos + len].copy_from_slice(&buf[off..off + len]);
+            pos += len;
+            off = 0;
+        }
+        Some(())
+    }
+
+    /// Copies bytes from `src` to this buffer starting at `off`.
+    ///
+    /// Returns `None` without copying if `src` does not fit in this buffer.
+    fn copy_in(&mut self, off: usize, src: &[u8]) -> Option<()> {
+        if off.checked_add(src.len())? > self.len() {
+            return None;
+        }
+        let mut off = off;
+        let mut pos = 0;
+        for buf in self.inner.iter_mut() {
+            if pos == src.len() {
+                break;
+            }
+            if off >= buf.len() {
+                off -= buf.len();
+                continue;
+            }
+            let len = (buf.len() - off).min(src.len() - pos);
+            buf[off..off + len].copy_from_slice(&src[pos..pos + len]);
+            pos += len;
+            off = 0;
+        }
+        Some(())
+    }
+
+    /// Reads a value of type `T` at `off`, which may straddle inner buffers.
+    ///
+    /// Returns `None` if the whole value does not fit in this buffer.
+    pub fn read_at<T: Copy>(&self, off: usize) -> Option<T> {
+        let mut val = MaybeUninit::<T>::uninit();
+        let dst = unsafe { slice::from_raw_parts_mut(val.as_mut_ptr() as *mut u8, size_of::<T>()) };
+        self.copy_out(off, dst)?;
+        Some(unsafe { val.assume_init() })
+    }
+
+    /// Writes a value of type `T` at `off`, which may straddle inner buffers.
+    ///
+    /// Returns `None` if the whole value does not fit in this buffer.
+    pub fn write_at<T: Copy>(&mut self, off: usize, val: T) -> Option<()> {
+        let src = unsafe { slice::from_raw_parts(&val as *const T as *const u8, size_of::<T>()) };
+        self.copy_in(off, src)
+    }
 }
 
 pub struct UserBufferIterator {
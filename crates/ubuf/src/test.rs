@@ -3,6 +3,8 @@ use core::slice;
 
 extern crate std;
 
+use std::{boxed::Box, vec};
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct A {
@@ -38,3 +40,54 @@ fn test_read() {
         std::println!("{:x?}", a);
     }
 }
+
+fn new_buf(lens: &[usize]) -> UserBuffer {
+    UserBuffer::new(
+        lens.iter()
+            .map(|&len| &mut Box::leak(vec![0u8; len].into_boxed_slice())[..])
+            .collect(),
+    )
+}
+
+#[test]
+fn test_read_write_at() {
+    let mut ubuf = new_buf(&[3, 6, 7]);
+    assert_eq!(ubuf.len(), 16);
+    assert!(!ubuf.is_empty());
+    assert!(new_buf(&[0, 0]).is_empty());
+    // straddles the first and second chunks
+    assert_eq!(ubuf.write_at(1, 0x0102030405060708u64), Some(()));
+    assert_eq!(ubuf.read_at::<u64>(1), Some(0x0102030405060708u64));
+    assert_eq!(ubuf.inner[0][1..], [0x08, 0x07]);
+    // ends exactly at the last chunk
+    assert_eq!(ubuf.write_at(8, u64::MAX), Some(()));
+    assert_eq!(ubuf.read_at::<u64>(8), Some(u64::MAX));
+}
+
+#[test]
+fn test_read_write_at_overflow() {
+    let mut ubuf = new_buf(&[4, 6]);
+    assert_eq!(ubuf.read_at::<u64>(3), None);
+    assert_eq!(ubuf.write_at(3, 1u64), None);
+    assert_eq!(ubuf.read_at::<u64>(usize::MAX), None);
+    assert!(ubuf.inner.iter().all(|buf| buf.iter().all(|&b| b == 0)));
+}
+
+#[test]
+fn test_copy_slice() {
+    let mut ubuf = new_buf(&[3, 2, 4]);
+    let src: Vec<u8> = (1..=12).collect();
+    assert_eq!(ubuf.copy_from_slice(&src), 9);
+    assert_eq!(ubuf.inner[1][..], [4, 5]);
+    assert_eq!(ubuf.inner[2][..], [6, 7, 8, 9]);
+
+    let mut dst = [0u8; 4];
+    assert_eq!(ubuf.copy_to_slice(&mut dst), 4);
+    assert_eq!(dst, [1, 2, 3, 4]);
+    let mut dst = [0u8; 12];
+    assert_eq!(ubuf.copy_to_slice(&mut dst), 9);
+    assert_eq!(dst[..9], src[..9]);
+
+    assert_eq!(ubuf.copy_from_slice(&[0xff; 4]), 4);
+    assert_eq!(ubuf.inner[1][..], [0xff, 5]);
+}
@@ -0,0 +1,93 @@
+use alloc::string::String;
+
+/// Unknown file type.
+pub const DT_UNKNOWN: u8 = 0;
+/// Named pipe.
+pub const DT_FIFO: u8 = 1;
+/// Character device.
+pub const DT_CHR: u8 = 2;
+/// Directory.
+pub const DT_DIR: u8 = 4;
+/// Block device.
+pub const DT_BLK: u8 = 6;
+/// Regular file.
+pub const DT_REG: u8 = 8;
+/// Symbolic link.
+pub const DT_LNK: u8 = 10;
+/// UNIX domain socket.
+pub const DT_SOCK: u8 = 12;
+
+/// An entry of a directory returned by [`crate::File::read_dir`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntry {
+    /// Inode number.
+    pub ino: u64,
+
+    /// File type, one of `DT_*`.
+    pub d_type: u8,
+
+    /// File name without `'/'`.
+    pub name: String,
+}
+
+/// Offset of `d_name` in `struct linux_dirent64`, which is laid out as:
+///
+/// ```c
+/// struct linux_dirent64 {
+///     ino64_t        d_ino;    /* 64-bit inode number */
+///     off64_t        d_off;    /* 64-bit offset to next structure */
+///     unsigned short d_reclen; /* Size of this dirent */
+///     unsigned char  d_type;   /* File type */
+///     char           d_name[]; /* Filename (null-terminated) */
+/// };
+/// ```
+///
+/// See `<https://man7.org/linux/man-pages/man2/getdents.2.html>`.
+const DIRENT64_NAME_OFFSET: usize = 19;
+
+impl DirEntry {
+    /// Returns the size of this entry in `struct linux_dirent64` layout, including
+    /// the terminating NUL of the name and the padding to 8-byte alignment.
+    pub fn reclen(&self) -> usize {
+        (DIRENT64_NAME_OFFSET + self.name.len() + 1 + 7) & !7
+    }
+
+    /// Serializes this entry into `buf` in `struct linux_dirent64` layout.
+    ///
+    /// - `off`: the offset of the next entry, which will be stored in `d_off`.
+    ///
+    /// Returns the number of bytes written, or `None` if `buf` is too small.
+    pub fn write_dirent64(&self, off: u64, buf: &mut [u8]) -> Option<usize> {
+        let reclen = self.reclen();
+        if buf.len() < reclen {
+            return None;
+        }
+        let buf = &mut buf[..reclen];
+        buf.fill(0);
+        buf[0..8].copy_from_slice(&self.ino.to_ne_bytes());
+        buf[8..16].copy_from_slice(&off.to_ne_bytes());
+        buf[16..18].copy_from_slice(&(reclen as u16).to_ne_bytes());
+        buf[18] = self.d_type;
+        buf[DIRENT64_NAME_OFFSET..DIRENT64_NAME_OFFSET + self.name.len()]
+            .copy_from_slice(self.name.as_bytes());
+        Some(reclen)
+    }
+}
+
+/// Serializes as many whole entries starting from `entries[start]` as fit in `buf`.
+///
+/// Returns the number of bytes written and the index of the next entry to serialize.
+pub fn write_dirents64(entries: &[DirEntry], start: usize, buf: &mut [u8]) -> (usize, usize) {
+    let mut pos = 0;
+    let mut next = start;
+    for entry in entries.iter().skip(start) {
+        match entry.write_dirent64(next as u64 + 1, &mut buf[pos..]) {
+            Some(len) => {
+                pos += len;
+                next += 1;
+            }
+            None => break,
+        }
+    }
+    (pos, next)
+}
@@ -70,6 +70,14 @@ bitflags::bitflags! {
         /// When O_PATH is specified in flags, flag bits other than `O_CLOEXEC`, `O_DIRECTORY`,
         /// and `O_NOFOLLOW` are ignored.
         const O_PATH = 0o10000000;
+
+        /// Create an unnamed temporary regular file. The pathname argument specifies a
+        /// directory; an unnamed inode will be created in that directory's filesystem.
+        /// Anything written to the resulting file will be lost when the last file
+        /// descriptor is closed, unless the file is given a name.
+        ///
+        /// `O_TMPFILE` must be specified with one of `O_RDWR` or `O_WRONLY`.
+        const O_TMPFILE = 0o20200000;
     }
 }
 
@@ -93,6 +101,36 @@ impl OpenFlags {
     }
 }
 
+bitflags::bitflags! {
+    /// Accessibility checked by `faccessat`.
+    pub struct AccessMode: u32 {
+        /// Only checks for the existence of the file.
+        const F_OK = 0;
+
+        /// Execute permission, or search permission for a directory.
+        const X_OK = 1;
+
+        /// Write permission.
+        const W_OK = 2;
+
+        /// Read permission.
+        const R_OK = 4;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags of `renameat2`.
+    pub struct RenameFlags: u32 {
+        /// Don't overwrite newpath of the rename. Return an error if newpath already exists.
+        const RENAME_NOREPLACE = 1 << 0;
+
+        /// Atomically exchange oldpath and newpath. Both pathnames must exist but may be
+        /// of different types (e.g., one could be a non-empty directory and the other a
+        /// symbolic link).
+        const RENAME_EXCHANGE = 1 << 1;
+    }
+}
+
 numeric_enum_macro::numeric_enum! {
     #[repr(usize)]
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -105,5 +143,29 @@ numeric_enum_macro::numeric_enum! {
 
         /// Sets the offset to the size of this object plus the specified number of bytes.
         End = 2,
+
+        /// Sets the offset to the next location containing data greater than or equal
+        /// to the specified offset.
+        Data = 3,
+
+        /// Sets the offset to the next hole greater than or equal to the specified offset.
+        Hole = 4,
+    }
+}
+
+impl SeekWhence {
+    /// Finds the offset of [`SeekWhence::Data`] or [`SeekWhence::Hole`] in a file of
+    /// `size` bytes without holes, where the end of the file is an implicit hole.
+    ///
+    /// Returns `None` if `offset` is not less than `size`, which is `ENXIO`.
+    pub fn data_or_hole(self, offset: usize, size: usize) -> Option<usize> {
+        if offset >= size {
+            return None;
+        }
+        match self {
+            Self::Data => Some(offset),
+            Self::Hole => Some(size),
+            _ => None,
+        }
     }
 }
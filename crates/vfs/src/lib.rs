@@ -1,6 +1,7 @@
 #![no_std]
 #![allow(unused)]
 
+mod dirent;
 mod flags;
 mod link;
 mod path;
@@ -11,13 +12,16 @@ extern crate alloc;
 
 use alloc::{sync::Arc, vec::Vec};
 use core::any::Any;
-use errno::Errno;
+pub use errno::Errno;
 
+pub use dirent::*;
 pub use flags::*;
 pub use link::*;
 pub use path::*;
 pub use stat::*;
 
+use ring_buf::Waker;
+
 /// In UNIX, everything is a File, such as:
 ///
 /// 1. A normal file staying on disk.
@@ -38,6 +42,38 @@ pub trait File: Send + Sync + AsAny {
         None
     }
 
+    /// Reads bytes from this file to the buffer like [`File::read`], reporting why
+    /// nothing can be read.
+    ///
+    /// By default, returns `EAGAIN` if [`File::read`] fails in non-blocking mode, or
+    /// `EBADF` otherwise. Files override this to report their own errors, e.g. `ERESTART`
+    /// if a blocked read is interrupted by a signal.
+    fn try_read(&self, buf: &mut [u8]) -> Result<usize, Errno> {
+        self.read(buf).ok_or_else(|| {
+            if self.open_flags().contains(OpenFlags::O_NONBLOCK) {
+                Errno::EAGAIN
+            } else {
+                Errno::EBADF
+            }
+        })
+    }
+
+    /// Writes bytes from the buffer to this file like [`File::write`], reporting why
+    /// nothing can be written.
+    ///
+    /// By default, returns `EAGAIN` if [`File::write`] fails in non-blocking mode, or
+    /// `EBADF` otherwise. Files override this to report their own errors, e.g. `ENOSPC`
+    /// if the disk is full.
+    fn try_write(&self, buf: &[u8]) -> Result<usize, Errno> {
+        self.write(buf).ok_or_else(|| {
+            if self.open_flags().contains(OpenFlags::O_NONBLOCK) {
+                Errno::EAGAIN
+            } else {
+                Errno::EBADF
+            }
+        })
+    }
+
     fn readable(&self) -> bool {
         false
     }
@@ -88,6 +124,25 @@ pub trait File: Send + Sync + AsAny {
         false
     }
 
+    /// Registers wakers of the task `id` created by `waker`, called once the file may
+    /// become ready, e.g. data arrives in a pipe or its peer hangs up.
+    ///
+    /// Returns false if the file never calls wakers, which is the default, so that the
+    /// caller has to poll its readiness.
+    fn poll_wait(&self, id: usize, waker: &dyn Fn() -> Waker) -> bool {
+        false
+    }
+
+    /// Removes the wakers of the task `id` registered by [`File::poll_wait`] without
+    /// calling them.
+    fn poll_cancel(&self, id: usize) {}
+
+    /// Returns if the peer of this file has hung up, e.g. the other end of a pipe
+    /// has been closed.
+    fn hangup(&self) -> bool {
+        false
+    }
+
     /// Moves the cursor with [`SeekWhence`] flags.
     ///
     ///
@@ -101,6 +156,12 @@ pub trait File: Send + Sync + AsAny {
         OpenFlags::empty()
     }
 
+    /// Sets the file status flags of the open file description.
+    ///
+    /// File access mode and file creation flags are ignored. Does nothing if
+    /// the file does not support changing its status flags.
+    fn set_open_flags(&self, flags: OpenFlags) {}
+
     /// Gets file `stat`.
     fn get_stat(&self, stat: *mut Stat) -> bool {
         false
@@ -116,6 +177,13 @@ pub trait File: Send + Sync + AsAny {
         self.seek(0, SeekWhence::Current).unwrap()
     }
 
+    /// Reads all entries of this directory.
+    ///
+    /// Returns [`None`] if this file is not a directory.
+    fn read_dir(&self) -> Option<Vec<DirEntry>> {
+        None
+    }
+
     /// If this file is a directory.
     fn is_dir(&self) -> bool {
         false
@@ -139,6 +207,17 @@ pub trait File: Send + Sync + AsAny {
     fn is_uintr(&self) -> bool {
         false
     }
+
+    /// Manipulates the underlying device parameters of special files.
+    ///
+    /// - `cmd`: device-dependent request code.
+    /// - `arg`: an untyped argument, usually a pointer to user memory.
+    ///
+    /// Returns `ENOTTY` if the file is not associated with a character special
+    /// device or the request does not apply to this kind of file.
+    fn ioctl(&self, cmd: usize, arg: usize) -> Result<usize, Errno> {
+        Err(Errno::ENOTTY)
+    }
 }
 
 pub trait AsAny {
@@ -164,11 +243,71 @@ pub trait VFS: Send + Sync {
     ///
     /// - `pdir`: Absolute path which must start with '/'.
     /// - `name`: the name of the new directory.
-    fn mkdir(&self, pdir: &Path, name: &str) -> Result<(), Errno>;
+    /// - `mode`: permission bits of the new directory, ignored by filesystems which
+    ///   do not store them.
+    fn mkdir(&self, pdir: &Path, name: &str, mode: StatMode) -> Result<(), Errno>;
 
     /// Checks for existance.
     fn check(&self, path: &Path) -> bool;
 
     /// Removes a file.
     fn remove(&self, pdir: &Path, name: &str) -> Result<(), Errno>;
+
+    /// Renames a file, replacing the existing file of the new name.
+    ///
+    /// - `old_pdir`, `new_pdir`: Absolute paths which must start with '/'.
+    fn rename(
+        &self,
+        old_pdir: &Path,
+        old_name: &str,
+        new_pdir: &Path,
+        new_name: &str,
+    ) -> Result<(), Errno>;
+
+    /// Atomically exchanges two existing files, which may be of different types.
+    ///
+    /// - `old_pdir`, `new_pdir`: Absolute paths which must start with '/'.
+    ///
+    /// Returns `EINVAL` by default, for filesystems which cannot swap files without
+    /// exposing an intermediate state.
+    fn exchange(
+        &self,
+        old_pdir: &Path,
+        old_name: &str,
+        new_pdir: &Path,
+        new_name: &str,
+    ) -> Result<(), Errno> {
+        Err(Errno::EINVAL)
+    }
+
+    /// Creates a hard link `new` to the existing file `old`, both of which are paths
+    /// after following links in this filesystem.
+    ///
+    /// By default the link is recorded in the link table, see [`add_link`], for
+    /// filesystems which cannot link files by themselves.
+    ///
+    /// Returns `ENOENT` if `old` or the parent directory of `new` does not exist, or
+    /// `EEXIST` if `new` exists.
+    fn link(&self, old: &Path, new: &Path) -> Result<(), Errno> {
+        let mut pdir = new.clone();
+        pdir.pop();
+        if new.is_dir() || !self.check(old) || !self.check(&pdir) {
+            return Err(Errno::ENOENT);
+        }
+        if self.check(new) || get_path(new) != *new || read_symlink(new).is_some() {
+            return Err(Errno::EEXIST);
+        }
+        add_link(old, new);
+        Ok(())
+    }
+
+    /// Writes back all modified data and metadata cached in memory.
+    fn sync(&self) -> Result<(), Errno> {
+        Ok(())
+    }
+
+    /// Writes back everything before the filesystem is detached, e.g. on shutdown.
+    fn unmount(&self) -> Result<(), Errno> {
+        self.sync()
+    }
 }
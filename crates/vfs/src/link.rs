@@ -1,4 +1,4 @@
-use alloc::collections::BTreeMap;
+use alloc::{collections::BTreeMap, string::String};
 use kernel_sync::SpinLock;
 use spin::Lazy;
 
@@ -12,6 +12,11 @@ static LINK_PATH_MAP: Lazy<SpinLock<BTreeMap<Path, Path>>> =
 static LINK_COUNT_MAP: Lazy<SpinLock<BTreeMap<Path, usize>>> =
     Lazy::new(|| SpinLock::new(BTreeMap::new()));
 
+/// Symbolic link path mapped to the target, since the disk filesystem does not
+/// support symbolic links.
+static SYMLINK_MAP: Lazy<SpinLock<BTreeMap<Path, String>>> =
+    Lazy::new(|| SpinLock::new(BTreeMap::new()));
+
 /// Gets the real path of a given path.
 ///
 /// Returns a `clone` of the path if the path is not existing in the map,
@@ -80,3 +85,30 @@ pub fn remove_link(path: &Path) -> Option<Path> {
         }
     }
 }
+
+/// Creates a symbolic link `path` containing the string `target`, which is not
+/// resolved until the link is followed.
+///
+/// Returns `false` if `path` is already a symbolic link.
+pub fn add_symlink(path: &Path, target: &str) -> bool {
+    let mut symlink_map = SYMLINK_MAP.lock();
+    if symlink_map.contains_key(path) {
+        return false;
+    }
+    symlink_map.insert(path.clone(), String::from(target));
+    true
+}
+
+/// Gets the target of a symbolic link without following it.
+///
+/// Returns `None` if `path` is not a symbolic link.
+pub fn read_symlink(path: &Path) -> Option<String> {
+    SYMLINK_MAP.lock().get(path).cloned()
+}
+
+/// Removes a symbolic link, but not the file it refers to.
+///
+/// Returns `false` if `path` is not a symbolic link.
+pub fn remove_symlink(path: &Path) -> bool {
+    SYMLINK_MAP.lock().remove(path).is_some()
+}
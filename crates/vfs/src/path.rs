@@ -3,6 +3,24 @@
 use core::str::FromStr;
 
 use alloc::{string::String, vec::Vec};
+use errno::Errno;
+
+/// Maximum length in bytes of a file name.
+pub const NAME_MAX: usize = 255;
+
+/// Checks if the name of a new directory entry is valid.
+///
+/// Returns `EINVAL` if the name is empty or contains `'/'`, or `ENAMETOOLONG`
+/// if the name is longer than [`NAME_MAX`].
+pub fn check_name(name: &str) -> Result<(), Errno> {
+    if name.is_empty() || name.contains('/') {
+        Err(Errno::EINVAL)
+    } else if name.len() > NAME_MAX {
+        Err(Errno::ENAMETOOLONG)
+    } else {
+        Ok(())
+    }
+}
 
 /// A wrapper for an absolute path which starts with `'/'` but ends with no `'/'`.
 ///
@@ -141,6 +159,32 @@ impl Path {
         }
     }
 
+    /// Maps this path, which is relative to a new root directory `root` such as a chroot
+    /// jail, to the path from the real root.
+    ///
+    /// Since this path is canonical, `".."` never escapes `root`.
+    pub fn under(&self, root: &Path) -> Path {
+        assert!(root.is_dir());
+        let mut path = root.clone();
+        path.0 += self.rela();
+        path
+    }
+
+    /// Maps this path from the real root to the path relative to a new root directory
+    /// `root`, which is the reverse of [`Path::under`].
+    ///
+    /// Returns `None` if this path is not below `root`.
+    pub fn strip_root(&self, root: &Path) -> Option<Path> {
+        assert!(root.is_dir());
+        if self.0.len() + 1 == root.0.len() && root.0.starts_with(self.0.as_str()) {
+            // The root directory itself without a trailing '/'.
+            return Some(Self::root());
+        }
+        self.0
+            .strip_prefix(root.as_str())
+            .map(|rela| Self(String::from("/") + rela))
+    }
+
     /// Splits the path into a vector of items.
     ///
     /// 1. Removes `"."` and `".."`;
@@ -1,5 +1,52 @@
+use alloc::{boxed::Box, vec::Vec};
+
 use crate::{File, SeekWhence};
 
+/// Callback to wake up a blocked reader or writer.
+pub type Waker = Box<dyn FnOnce() + Send>;
+
+/// Wakers of blocked tasks, each registered with the id of its task.
+///
+/// A task woken up by others, e.g. a signal, removes its wakers with the id once it
+/// stops waiting, thus wakers never called do not pile up.
+#[derive(Default)]
+pub struct WaitQueue {
+    wakers: Vec<(usize, Waker)>,
+}
+
+impl WaitQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a waker of the task `id`.
+    pub fn push(&mut self, id: usize, waker: Waker) {
+        self.wakers.push((id, waker));
+    }
+
+    /// Removes the wakers of the task `id` without calling them.
+    pub fn remove(&mut self, id: usize) {
+        self.wakers.retain(|(waiter, _)| *waiter != id);
+    }
+
+    /// Calls and removes all wakers.
+    pub fn wake_all(&mut self) {
+        for (_, waker) in self.wakers.drain(..) {
+            waker();
+        }
+    }
+
+    /// Returns the number of wakers registered.
+    pub fn len(&self) -> usize {
+        self.wakers.len()
+    }
+
+    /// Returns true if no waker is registered.
+    pub fn is_empty(&self) -> bool {
+        self.wakers.is_empty()
+    }
+}
+
 /// Ring buffer with underlying file.
 pub struct RingBuffer<F: File> {
     /// Inner data.
@@ -16,6 +63,15 @@ pub struct RingBuffer<F: File> {
 
     /// Maximum size.
     max_size: usize,
+
+    /// Readers waiting for data.
+    read_wakers: WaitQueue,
+
+    /// Writers waiting for space.
+    write_wakers: WaitQueue,
+
+    /// Set once either end is closed, e.g. of a pipe.
+    closed: bool,
 }
 
 impl<F: File> RingBuffer<F> {
@@ -27,12 +83,20 @@ impl<F: File> RingBuffer<F> {
             tail: 0,
             len: 0,
             max_size: limit,
+            read_wakers: WaitQueue::new(),
+            write_wakers: WaitQueue::new(),
+            closed: false,
         }
     }
 
     /// Reads data from the buffer as much as possible.
+    ///
+    /// Writers waiting for space are woken up if any data is read.
     pub fn read(&mut self, buf: &mut [u8]) -> usize {
         let read_len = buf.len().min(self.len);
+        if read_len == 0 {
+            return 0;
+        }
         self.len -= read_len;
         let data = self.data.as_ref().unwrap();
         // Read from head
@@ -47,12 +111,48 @@ impl<F: File> RingBuffer<F> {
             data.read(&mut buf[self.max_size - self.head..read_len]);
             self.head = self.head + read_len - self.max_size;
         }
+        self.write_wakers.wake_all();
         read_len
     }
 
+    /// Copies data from the buffer as much as possible without consuming it.
+    pub fn peek(&self, buf: &mut [u8]) -> usize {
+        let peek_len = buf.len().min(self.len);
+        let data = self.data.as_ref().unwrap();
+        data.seek(self.head, SeekWhence::Set);
+        if self.head + peek_len <= self.max_size {
+            data.read(&mut buf[..peek_len]);
+        } else {
+            data.read(&mut buf[..self.max_size - self.head]);
+            // Rollback to the start.
+            data.seek(0, SeekWhence::Set);
+            data.read(&mut buf[self.max_size - self.head..peek_len]);
+        }
+        peek_len
+    }
+
+    /// Consumes at most `n` bytes in the buffer without copying.
+    ///
+    /// Writers waiting for space are woken up if any data is skipped.
+    pub fn skip(&mut self, n: usize) -> usize {
+        let skip_len = n.min(self.len);
+        if skip_len == 0 {
+            return 0;
+        }
+        self.len -= skip_len;
+        self.head = (self.head + skip_len) % self.max_size;
+        self.write_wakers.wake_all();
+        skip_len
+    }
+
     /// Writes data to the buffer as much as possible.
+    ///
+    /// Readers waiting for data are woken up if any data is written.
     pub fn write(&mut self, buf: &[u8]) -> usize {
         let write_len = buf.len().min(self.max_size - self.len);
+        if write_len == 0 {
+            return 0;
+        }
         self.len += write_len;
         let data = self.data.as_ref().unwrap();
         // Write to tail
@@ -67,9 +167,160 @@ impl<F: File> RingBuffer<F> {
             data.write(&buf[self.max_size - self.tail..write_len]);
             self.tail = self.tail + write_len - self.max_size;
         }
+        self.read_wakers.wake_all();
         write_len
     }
 
+    /// Moves at most `n` bytes out of the buffer without an intermediate buffer.
+    ///
+    /// Each contiguous segment of data is handed to `sink` as `(file, offset, len)` in the
+    /// underlying file. `sink` returns the number of bytes it consumed, and moving stops at
+    /// a short count.
+    ///
+    /// Writers waiting for space are woken up if any data is consumed.
+    pub fn splice_out(
+        &mut self,
+        n: usize,
+        mut sink: impl FnMut(&F, usize, usize) -> usize,
+    ) -> usize {
+        let splice_len = n.min(self.len);
+        let mut moved = 0;
+        while moved < splice_len {
+            let seg_len = (splice_len - moved).min(self.max_size - self.head);
+            let count = sink(self.data.as_ref().unwrap(), self.head, seg_len).min(seg_len);
+            moved += count;
+            self.len -= count;
+            self.head = (self.head + count) % self.max_size;
+            if count < seg_len {
+                break;
+            }
+        }
+        if moved > 0 {
+            self.write_wakers.wake_all();
+        }
+        moved
+    }
+
+    /// Moves at most `n` bytes into the buffer without an intermediate buffer.
+    ///
+    /// Each contiguous segment of free space is handed to `source` as `(file, offset, len)`
+    /// in the underlying file. `source` returns the number of bytes it filled, and moving
+    /// stops at a short count.
+    ///
+    /// Readers waiting for data are woken up if any data is filled.
+    pub fn splice_in(
+        &mut self,
+        n: usize,
+        mut source: impl FnMut(&F, usize, usize) -> usize,
+    ) -> usize {
+        let splice_len = n.min(self.max_size - self.len);
+        let mut moved = 0;
+        while moved < splice_len {
+            let seg_len = (splice_len - moved).min(self.max_size - self.tail);
+            let count = source(self.data.as_ref().unwrap(), self.tail, seg_len).min(seg_len);
+            moved += count;
+            self.len += count;
+            self.tail = (self.tail + count) % self.max_size;
+            if count < seg_len {
+                break;
+            }
+        }
+        if moved > 0 {
+            self.read_wakers.wake_all();
+        }
+        moved
+    }
+
+    /// Registers a waker of the task `id` to be called when data arrives.
+    pub fn wait_read(&mut self, id: usize, waker: Waker) {
+        self.read_wakers.push(id, waker);
+    }
+
+    /// Registers a waker of the task `id` to be called when space frees.
+    pub fn wait_write(&mut self, id: usize, waker: Waker) {
+        self.write_wakers.push(id, waker);
+    }
+
+    /// Removes the wakers of the task `id` not called yet, once it stops waiting.
+    pub fn cancel_wait(&mut self, id: usize) {
+        self.read_wakers.remove(id);
+        self.write_wakers.remove(id);
+    }
+
+    /// Reads data from the buffer, or registers a waker of the task `id` to be called
+    /// when data arrives if the buffer is empty.
+    ///
+    /// - `waker`: creates the waker, only called if the caller needs to block.
+    ///
+    /// Returns `None` if the caller needs to block until woken up and try again.
+    pub fn read_blocking(
+        &mut self,
+        buf: &mut [u8],
+        id: usize,
+        waker: impl FnOnce() -> Waker,
+    ) -> Option<usize> {
+        if self.is_empty() && !buf.is_empty() {
+            self.read_wakers.push(id, waker());
+            None
+        } else {
+            Some(self.read(buf))
+        }
+    }
+
+    /// Writes data to the buffer, or registers a waker of the task `id` to be called
+    /// when space frees if the buffer is full.
+    ///
+    /// - `waker`: creates the waker, only called if the caller needs to block.
+    ///
+    /// Returns `None` if the caller needs to block until woken up and try again.
+    pub fn write_blocking(
+        &mut self,
+        buf: &[u8],
+        id: usize,
+        waker: impl FnOnce() -> Waker,
+    ) -> Option<usize> {
+        if self.is_full() && !buf.is_empty() {
+            self.write_wakers.push(id, waker());
+            None
+        } else {
+            Some(self.write(buf))
+        }
+    }
+
+    /// Wakes up all blocked readers and writers, e.g. when one end of a pipe
+    /// is closed.
+    pub fn wake_all(&mut self) {
+        self.read_wakers.wake_all();
+        self.write_wakers.wake_all();
+    }
+
+    /// Marks the buffer closed and wakes up all blocked readers and writers, which
+    /// see the flag under the same lock once woken up.
+    pub fn close(&mut self) {
+        self.closed = true;
+        self.wake_all();
+    }
+
+    /// Returns true if the buffer has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Returns the number of bytes which can be read.
+    pub fn available_read(&self) -> usize {
+        self.len
+    }
+
+    /// Returns the number of bytes which can be written.
+    pub fn available_write(&self) -> usize {
+        self.max_size - self.len
+    }
+
+    /// Returns the number of bytes in the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
     /// Returns true if the buffer has a length of 0.
     pub fn is_empty(&self) -> bool {
         self.len == 0
@@ -1,5 +1,7 @@
 use bitflags::bitflags;
 
+use crate::AccessMode;
+
 bitflags! {
     pub struct StatMode: u32 {
         /// bit mask for the file type bit field
@@ -51,6 +53,22 @@ bitflags! {
     }
 }
 
+impl StatMode {
+    /// Everyone has read, write, and execute permission, i.e. `0777`.
+    pub const S_IRWXUGO: Self = Self { bits: 0o777 };
+
+    /// Checks the permission bits of the owner against `mode`.
+    pub fn owner_allows(&self, mode: AccessMode) -> bool {
+        [
+            (AccessMode::R_OK, StatMode::S_IRUSR),
+            (AccessMode::W_OK, StatMode::S_IWUSR),
+            (AccessMode::X_OK, StatMode::S_IXUSR),
+        ]
+        .iter()
+        .all(|&(access, perm)| !mode.contains(access) || self.contains(perm))
+    }
+}
+
 /// Store the file attributes from a supported file.
 #[repr(C)]
 #[derive(Debug, Default)]
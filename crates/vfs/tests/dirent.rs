@@ -0,0 +1,62 @@
+use vfs::{write_dirents64, DirEntry, DT_DIR, DT_REG};
+
+fn entries() -> Vec<DirEntry> {
+    ["a.txt", "bin", "longer_file_name.txt"]
+        .iter()
+        .enumerate()
+        .map(|(i, name)| DirEntry {
+            ino: i as u64 + 1,
+            d_type: if *name == "bin" { DT_DIR } else { DT_REG },
+            name: String::from(*name),
+        })
+        .collect()
+}
+
+/// Parses `struct linux_dirent64` records into `(d_ino, d_off, d_type, d_name)`.
+fn parse(buf: &[u8]) -> Vec<(u64, u64, u8, String)> {
+    let mut pos = 0;
+    let mut result = Vec::new();
+    while pos < buf.len() {
+        let rec = &buf[pos..];
+        let ino = u64::from_ne_bytes(rec[0..8].try_into().unwrap());
+        let off = u64::from_ne_bytes(rec[8..16].try_into().unwrap());
+        let reclen = u16::from_ne_bytes(rec[16..18].try_into().unwrap()) as usize;
+        assert_eq!(reclen % 8, 0);
+        let name_len = rec[19..reclen].iter().position(|&b| b == 0).unwrap();
+        let name = String::from_utf8(rec[19..19 + name_len].to_vec()).unwrap();
+        result.push((ino, off, rec[18], name));
+        pos += reclen;
+    }
+    result
+}
+
+#[test]
+fn test_dirent_reclen() {
+    let entries = entries();
+    // 19 bytes header + "a.txt\0" = 25, aligned to 32
+    assert_eq!(entries[0].reclen(), 32);
+    // 19 bytes header + "bin\0" = 23, aligned to 24
+    assert_eq!(entries[1].reclen(), 24);
+}
+
+#[test]
+fn test_dirents_two_calls() {
+    let entries = entries();
+    let mut buf = [0u8; 64];
+
+    // only the first two entries fit
+    let (len, next) = write_dirents64(&entries, 0, &mut buf);
+    assert_eq!((len, next), (56, 2));
+    let parsed = parse(&buf[..len]);
+    assert_eq!(parsed[0], (1, 1, DT_REG, String::from("a.txt")));
+    assert_eq!(parsed[1], (2, 2, DT_DIR, String::from("bin")));
+
+    // continue from the cursor
+    let (len, next) = write_dirents64(&entries, next, &mut buf);
+    assert_eq!(next, 3);
+    let parsed = parse(&buf[..len]);
+    assert_eq!(parsed, vec![(3, 3, DT_REG, String::from("longer_file_name.txt"))]);
+
+    // end of directory
+    assert_eq!(write_dirents64(&entries, next, &mut buf), (0, 3));
+}
@@ -0,0 +1,33 @@
+extern crate std;
+
+use errno::Errno;
+use vfs::File;
+
+struct RegularFile;
+
+impl File for RegularFile {
+    fn is_reg(&self) -> bool {
+        true
+    }
+}
+
+#[test]
+fn test_ioctl_enotty() {
+    let file = RegularFile;
+    assert_eq!(file.ioctl(0x5413, 0), Err(Errno::ENOTTY));
+}
+
+#[test]
+fn test_seek_data_or_hole() {
+    use vfs::SeekWhence;
+
+    assert_eq!(SeekWhence::try_from(3), Ok(SeekWhence::Data));
+    assert_eq!(SeekWhence::try_from(4), Ok(SeekWhence::Hole));
+    assert!(SeekWhence::try_from(5).is_err());
+    // The whole file is data, followed by an implicit hole at the end.
+    assert_eq!(SeekWhence::Data.data_or_hole(3, 10), Some(3));
+    assert_eq!(SeekWhence::Hole.data_or_hole(3, 10), Some(10));
+    assert_eq!(SeekWhence::Data.data_or_hole(10, 10), None);
+    assert_eq!(SeekWhence::Hole.data_or_hole(11, 10), None);
+    assert_eq!(SeekWhence::Set.data_or_hole(3, 10), None);
+}
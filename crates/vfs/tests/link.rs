@@ -0,0 +1,80 @@
+use std::{collections::BTreeSet, sync::Arc};
+
+use vfs::{
+    add_symlink, get_nlink, get_path, read_symlink, remove_link, remove_symlink, Errno, File,
+    OpenFlags, Path, StatMode, VFS,
+};
+
+#[test]
+fn test_symlink() {
+    let link = Path::new("/test_symlink/link");
+    assert_eq!(read_symlink(&link), None);
+    assert!(add_symlink(&link, "../target"));
+    assert!(!add_symlink(&link, "/other"));
+    // The target is kept as it is.
+    assert_eq!(read_symlink(&link).as_deref(), Some("../target"));
+    assert!(remove_symlink(&link));
+    assert!(!remove_symlink(&link));
+    assert_eq!(read_symlink(&link), None);
+}
+
+/// A filesystem with a fixed set of paths, which cannot link files by itself.
+struct SetFs(BTreeSet<Path>);
+
+impl VFS for SetFs {
+    fn open(&self, _pdir: &Path, _name: &str, _flags: OpenFlags) -> Result<Arc<dyn File>, Errno> {
+        Err(Errno::ENOENT)
+    }
+
+    fn mkdir(&self, _pdir: &Path, _name: &str, _mode: StatMode) -> Result<(), Errno> {
+        Err(Errno::EROFS)
+    }
+
+    fn check(&self, path: &Path) -> bool {
+        self.0.contains(path)
+    }
+
+    fn remove(&self, _pdir: &Path, _name: &str) -> Result<(), Errno> {
+        Err(Errno::EROFS)
+    }
+
+    fn rename(
+        &self,
+        _old_pdir: &Path,
+        _old_name: &str,
+        _new_pdir: &Path,
+        _new_name: &str,
+    ) -> Result<(), Errno> {
+        Err(Errno::EROFS)
+    }
+}
+
+#[test]
+fn test_default_link() {
+    let dir = Path::new("/test_link/");
+    let file = Path::new("/test_link/file");
+    let fs = SetFs([dir, file.clone()].into_iter().collect());
+    let new = Path::new("/test_link/new");
+
+    assert_eq!(fs.link(&file, &new), Ok(()));
+    assert_eq!(get_path(&new), file);
+    assert_eq!(get_nlink(&file), 2);
+    assert_eq!(fs.link(&file, &new), Err(Errno::EEXIST));
+    assert_eq!(fs.link(&file, &file), Err(Errno::EEXIST));
+    assert_eq!(
+        fs.link(
+            &Path::new("/test_link/none"),
+            &Path::new("/test_link/other")
+        ),
+        Err(Errno::ENOENT)
+    );
+    assert_eq!(
+        fs.link(&file, &Path::new("/none/other")),
+        Err(Errno::ENOENT)
+    );
+
+    // The file is removed with the last link.
+    assert_eq!(remove_link(&new), None);
+    assert_eq!(get_nlink(&file), 1);
+    assert_eq!(remove_link(&file), Some(file));
+}
@@ -2,7 +2,7 @@ extern crate std;
 
 use std::println;
 
-use vfs::{OpenFlags, Path};
+use vfs::{check_name, Errno, OpenFlags, Path, NAME_MAX};
 
 #[test]
 fn test_open_flags() {
@@ -24,3 +24,57 @@ fn test_path() {
     assert_eq!(path, Path::new("/a/d/a/a/d/////"));
     assert_ne!(path, Path::new("/a/d/a/a/d"))
 }
+
+#[test]
+fn test_path_cwd() {
+    // chdir into a directory given without a trailing '/'
+    let mut cwd = Path::root();
+    cwd.join("/a/b");
+    assert!(cwd.is_dir());
+    assert_eq!(cwd, Path::new("/a/b/"));
+
+    // resolve a relative path against the working directory
+    let mut path = cwd.clone();
+    path.extend("../c/test.txt");
+    assert_eq!(path, Path::new("/a/c/test.txt"));
+
+    let mut root = Path::root();
+    root.join("");
+    assert!(root.is_root());
+}
+
+#[test]
+fn test_check_name() {
+    assert_eq!(check_name(""), Err(Errno::EINVAL));
+    assert_eq!(check_name("a/b"), Err(Errno::EINVAL));
+    assert_eq!(check_name(&"a".repeat(NAME_MAX + 1)), Err(Errno::ENAMETOOLONG));
+    assert_eq!(check_name(&"a".repeat(NAME_MAX)), Ok(()));
+    assert_eq!(check_name("test.txt"), Ok(()));
+}
+
+#[test]
+fn test_path_chroot() {
+    let root = Path::new("/jail/");
+
+    // ".." cannot escape the new root
+    let mut path = Path::root();
+    path.extend("/../../sub/../etc/passwd");
+    assert_eq!(path.under(&root), Path::new("/jail/etc/passwd"));
+    assert_eq!(Path::root().under(&root), root);
+
+    assert_eq!(
+        Path::new("/jail/etc/passwd").strip_root(&root),
+        Some(Path::new("/etc/passwd"))
+    );
+    assert_eq!(
+        Path::new("/jail/a/").strip_root(&root),
+        Some(Path::new("/a/"))
+    );
+    assert_eq!(Path::new("/jail").strip_root(&root), Some(Path::root()));
+    assert_eq!(root.strip_root(&root), Some(Path::root()));
+    assert_eq!(Path::new("/jailbreak").strip_root(&root), None);
+    assert_eq!(
+        Path::new("/etc/passwd").strip_root(&Path::root()),
+        Some(Path::new("/etc/passwd"))
+    );
+}
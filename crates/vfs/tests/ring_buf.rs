@@ -0,0 +1,269 @@
+extern crate std;
+
+use std::{
+    boxed::Box,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    vec,
+    vec::Vec,
+};
+
+use vfs::{
+    File, SeekWhence,
+    ring_buf::{RingBuffer, WaitQueue},
+};
+
+/// A file backed by a vector, used as the storage of a ring buffer.
+struct VecFile {
+    inner: Mutex<(Vec<u8>, usize)>,
+}
+
+impl VecFile {
+    fn new(size: usize) -> Self {
+        Self {
+            inner: Mutex::new((vec![0; size], 0)),
+        }
+    }
+}
+
+impl File for VecFile {
+    fn read(&self, buf: &mut [u8]) -> Option<usize> {
+        let mut inner = self.inner.lock().unwrap();
+        let pos = inner.1;
+        let len = buf.len().min(inner.0.len() - pos);
+        buf[..len].copy_from_slice(&inner.0[pos..pos + len]);
+        inner.1 += len;
+        Some(len)
+    }
+
+    fn write(&self, buf: &[u8]) -> Option<usize> {
+        let mut inner = self.inner.lock().unwrap();
+        let pos = inner.1;
+        let len = buf.len().min(inner.0.len() - pos);
+        inner.0[pos..pos + len].copy_from_slice(&buf[..len]);
+        inner.1 += len;
+        Some(len)
+    }
+
+    fn seek(&self, offset: usize, whence: SeekWhence) -> Option<usize> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.1 = match whence {
+            SeekWhence::Set => offset,
+            SeekWhence::Current => inner.1 + offset,
+            SeekWhence::End => inner.0.len() + offset,
+            SeekWhence::Data | SeekWhence::Hole => whence.data_or_hole(offset, inner.0.len())?,
+        };
+        Some(inner.1)
+    }
+}
+
+#[test]
+fn test_ring_buf_len() {
+    let mut ring_buf = RingBuffer::new(8, VecFile::new(8));
+    assert!(ring_buf.is_empty());
+
+    assert_eq!(ring_buf.write(b"hello"), 5);
+    assert_eq!(ring_buf.len(), 5);
+
+    let mut buf = [0u8; 3];
+    assert_eq!(ring_buf.read(&mut buf), 3);
+    assert_eq!(&buf, b"hel");
+    assert_eq!(ring_buf.len(), 2);
+
+    // wraps around the end
+    assert_eq!(ring_buf.write(b"world!!"), 6);
+    assert_eq!(ring_buf.len(), 8);
+    assert!(ring_buf.is_full());
+
+    let mut buf = [0u8; 8];
+    assert_eq!(ring_buf.read(&mut buf), 8);
+    assert_eq!(&buf, b"loworld!");
+    assert!(ring_buf.is_empty());
+}
+
+/// Returns a waker counting how many times it is called.
+fn counter_waker(counter: &Arc<AtomicUsize>) -> Box<dyn FnOnce() + Send> {
+    let counter = counter.clone();
+    Box::new(move || {
+        counter.fetch_add(1, Ordering::SeqCst);
+    })
+}
+
+/// Id of the blocked reader.
+const READER: usize = 1;
+
+/// Id of the blocked writer.
+const WRITER: usize = 2;
+
+#[test]
+fn test_ring_buf_blocking() {
+    let mut ring_buf = RingBuffer::new(4, VecFile::new(4));
+    let reader_woken = Arc::new(AtomicUsize::new(0));
+    let writer_woken = Arc::new(AtomicUsize::new(0));
+    assert_eq!(ring_buf.available_read(), 0);
+    assert_eq!(ring_buf.available_write(), 4);
+
+    // The consumer blocks on an empty buffer.
+    let mut buf = [0u8; 8];
+    assert_eq!(
+        ring_buf.read_blocking(&mut buf, READER, || counter_waker(&reader_woken)),
+        None
+    );
+    assert_eq!(reader_woken.load(Ordering::SeqCst), 0);
+
+    // The producer fills the buffer and wakes the consumer once.
+    assert_eq!(
+        ring_buf.write_blocking(b"abcdef", WRITER, || counter_waker(&writer_woken)),
+        Some(4)
+    );
+    assert_eq!(reader_woken.load(Ordering::SeqCst), 1);
+    assert!(ring_buf.is_full());
+    assert_eq!(ring_buf.available_write(), 0);
+
+    // The producer blocks on a full buffer.
+    assert_eq!(
+        ring_buf.write_blocking(b"ef", WRITER, || counter_waker(&writer_woken)),
+        None
+    );
+
+    // The consumer wakes the producer once.
+    assert_eq!(
+        ring_buf.read_blocking(&mut buf[..3], READER, || counter_waker(&reader_woken)),
+        Some(3)
+    );
+    assert_eq!(&buf[..3], b"abc");
+    assert_eq!(writer_woken.load(Ordering::SeqCst), 1);
+    assert_eq!(
+        ring_buf.write_blocking(b"ef", WRITER, || counter_waker(&writer_woken)),
+        Some(2)
+    );
+
+    // Wakers are called once only.
+    assert_eq!(
+        ring_buf.read_blocking(&mut buf, READER, || counter_waker(&reader_woken)),
+        Some(3)
+    );
+    assert_eq!(&buf[..3], b"def");
+    assert_eq!(reader_woken.load(Ordering::SeqCst), 1);
+    assert_eq!(writer_woken.load(Ordering::SeqCst), 1);
+
+    // Blocked readers are woken when the peer is closed.
+    assert_eq!(
+        ring_buf.read_blocking(&mut buf, READER, || counter_waker(&reader_woken)),
+        None
+    );
+    assert!(!ring_buf.is_closed());
+    ring_buf.close();
+    assert!(ring_buf.is_closed());
+    assert_eq!(reader_woken.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_ring_buf_cancel_wait() {
+    let mut ring_buf = RingBuffer::new(4, VecFile::new(4));
+    let reader_woken = Arc::new(AtomicUsize::new(0));
+    let mut buf = [0u8; 4];
+    assert_eq!(
+        ring_buf.read_blocking(&mut buf, READER, || counter_waker(&reader_woken)),
+        None
+    );
+    assert_eq!(
+        ring_buf.read_blocking(&mut buf, WRITER, || counter_waker(&reader_woken)),
+        None
+    );
+
+    // The reader stops waiting, e.g. woken up by a signal, thus only the other one is
+    // woken up by the data.
+    ring_buf.cancel_wait(READER);
+    assert_eq!(ring_buf.write(b"ab"), 2);
+    assert_eq!(reader_woken.load(Ordering::SeqCst), 1);
+    ring_buf.wake_all();
+    assert_eq!(reader_woken.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_wait_queue() {
+    let woken = Arc::new(AtomicUsize::new(0));
+    let mut queue = WaitQueue::new();
+    queue.push(READER, counter_waker(&woken));
+    queue.push(READER, counter_waker(&woken));
+    queue.push(WRITER, counter_waker(&woken));
+    assert_eq!(queue.len(), 3);
+
+    queue.remove(READER);
+    assert_eq!(queue.len(), 1);
+    queue.wake_all();
+    assert!(queue.is_empty());
+    assert_eq!(woken.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_ring_buf_peek() {
+    let mut ring_buf = RingBuffer::new(8, VecFile::new(8));
+    assert_eq!(ring_buf.write(b"abcdef"), 6);
+    assert_eq!(ring_buf.skip(4), 4);
+    // wraps around the end
+    assert_eq!(ring_buf.write(b"ghij\n"), 5);
+
+    let mut peeked = [0u8; 8];
+    assert_eq!(ring_buf.peek(&mut peeked), 7);
+    assert_eq!(&peeked[..7], b"efghij\n");
+    assert_eq!(ring_buf.len(), 7);
+
+    // Peeked bytes equal the bytes read later.
+    let line = peeked.iter().position(|&b| b == b'\n').unwrap() + 1;
+    let mut buf = [0u8; 8];
+    assert_eq!(ring_buf.read(&mut buf[..line]), line);
+    assert_eq!(buf[..line], peeked[..line]);
+    assert!(ring_buf.is_empty());
+    assert_eq!(ring_buf.peek(&mut peeked), 0);
+    assert_eq!(ring_buf.skip(1), 0);
+}
+
+#[test]
+fn test_ring_buf_splice() {
+    let mut ring_buf = RingBuffer::new(8, VecFile::new(8));
+    assert_eq!(ring_buf.write(b"xyzxyz"), 6);
+    assert_eq!(ring_buf.skip(6), 6);
+
+    // Splices a file into the buffer, wrapping around the end.
+    let src = b"hello, splice";
+    let mut pos = 0;
+    let mut segs = Vec::new();
+    let filled = ring_buf.splice_in(usize::MAX, |data, off, len| {
+        segs.push((off, len));
+        data.seek(off, SeekWhence::Set);
+        let count = data.write(&src[pos..pos + len]).unwrap();
+        pos += count;
+        count
+    });
+    assert_eq!(filled, 8);
+    assert_eq!(segs, vec![(6, 2), (0, 6)]);
+    assert!(ring_buf.is_full());
+
+    // Sink consumes a short count and stops.
+    let mut dst = Vec::new();
+    let moved = ring_buf.splice_out(usize::MAX, |data, off, len| {
+        let mut buf = vec![0u8; len.min(1)];
+        data.seek(off, SeekWhence::Set);
+        data.read(&mut buf).unwrap();
+        dst.extend_from_slice(&buf);
+        buf.len()
+    });
+    assert_eq!(moved, 1);
+    assert_eq!(ring_buf.len(), 7);
+
+    let moved = ring_buf.splice_out(usize::MAX, |data, off, len| {
+        let mut buf = vec![0u8; len];
+        data.seek(off, SeekWhence::Set);
+        data.read(&mut buf).unwrap();
+        dst.extend_from_slice(&buf);
+        len
+    });
+    assert_eq!(moved, 7);
+    assert_eq!(dst, &src[..8]);
+    assert!(ring_buf.is_empty());
+    assert_eq!(ring_buf.splice_out(1, |_, _, len| len), 0);
+}
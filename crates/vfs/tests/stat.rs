@@ -0,0 +1,12 @@
+use vfs::{AccessMode, StatMode};
+
+#[test]
+fn test_owner_allows() {
+    let mode = StatMode::S_IFREG | StatMode::S_IRUSR | StatMode::S_IWUSR | StatMode::S_IRGRP;
+    assert!(mode.owner_allows(AccessMode::F_OK));
+    assert!(mode.owner_allows(AccessMode::R_OK | AccessMode::W_OK));
+    assert!(!mode.owner_allows(AccessMode::X_OK));
+    assert!(!mode.owner_allows(AccessMode::R_OK | AccessMode::X_OK));
+    // Permission of the group is not checked.
+    assert!(!(StatMode::S_IFREG | StatMode::S_IRWXG).owner_allows(AccessMode::R_OK));
+}
@@ -12,7 +12,8 @@ use mm_rv::*;
 use riscv::asm::{sfence_vma, sfence_vma_all};
 
 use crate::{
-    config::{BOOT_STACK_SIZE, PHYSICAL_MEMORY_END, TOTAL_BOOT_STACK_SIZE},
+    config::{BOOT_STACK_SIZE, CPU_NUM, PHYSICAL_MEMORY_END, TOTAL_BOOT_STACK_SIZE},
+    error::{KernelError, KernelResult},
     mm::KERNEL_MM,
     rust_main, rust_main_others,
 };
@@ -78,6 +79,53 @@ pub fn flush_tlb(va: Option<VirtAddr>) {
     }
 }
 
+/// Orders memory accesses, synchronizes the instruction cache and flushes tlb of this
+/// hart.
+pub fn sync_local() {
+    unsafe { core::arch::asm!("fence rw, rw", "fence.i") };
+    flush_tlb(None);
+}
+
+/// Flushes tlb of the harts in the mask `harts` after the page table of an address
+/// space running on them is modified.
+///
+/// Other harts are fenced through SBI, which returns only after all of them complete
+/// the fence, even if they run in the kernel with interrupts disabled. Thus frames
+/// unmapped can be reused safely after this function returns.
+pub fn flush_tlb_harts(harts: usize) -> KernelResult {
+    flush_tlb(None);
+    let others = harts & !(1 << get_cpu_id());
+    if others == 0 {
+        return Ok(());
+    }
+    // A full flush of the address range.
+    let ret = sbi_rt::remote_sfence_vma(others, 0, 0, usize::MAX);
+    if ret.is_ok() {
+        Ok(())
+    } else {
+        Err(KernelError::SBIFailed(ret.error))
+    }
+}
+
+/// Orders memory accesses and synchronizes the instruction cache of all harts, thus
+/// each running thread passes a memory barrier once this function returns.
+///
+/// Other harts trap into the SBI to execute `fence.i`, which serializes them, and this
+/// function returns only after all of them complete it, see [`flush_tlb_harts`].
+pub fn fence_all_harts() -> KernelResult {
+    sync_local();
+    let others = ((1 << CPU_NUM) - 1) & !(1 << get_cpu_id());
+    if others == 0 {
+        return Ok(());
+    }
+    let ret = sbi_rt::remote_fence_i(others, 0);
+    if ret.is_ok() {
+        Ok(())
+    } else {
+        Err(KernelError::SBIFailed(ret.error))
+    }
+}
+
 /// Gets cpu id.
 #[inline]
 pub fn get_cpu_id() -> usize {
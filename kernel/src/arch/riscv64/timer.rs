@@ -1,4 +1,4 @@
-use riscv::register::time;
+use riscv::register::{cycle, time};
 use time_subsys::{MSEC_PER_SEC, USEC_PER_SEC};
 
 use crate::config::CLOCK_FREQ;
@@ -7,6 +7,11 @@ pub fn get_time() -> usize {
     time::read()
 }
 
+/// Gets the number of clock cycles executed by this hart.
+pub fn get_cycle() -> usize {
+    cycle::read()
+}
+
 pub fn get_time_sec() -> usize {
     time::read() / CLOCK_FREQ
 }
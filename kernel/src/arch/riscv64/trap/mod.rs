@@ -2,20 +2,23 @@ mod trampoline;
 mod trapframe;
 
 use core::{arch::asm, panic};
+use errno::Errno;
 use log::trace;
 use riscv::register::{scause::*, utvec::TrapMode, *};
-pub use trampoline::__trampoline;
-pub use trapframe::TrapFrame;
+use signal_defs::{SEGV_MAPERR, SIGSEGV};
+pub use trampoline::{__sigreturn, __trampoline};
+pub use trapframe::{TrapFrame, UserContext};
 
 use crate::{
-    arch::mm::VirtAddr,
+    arch::{get_cpu_id, mm::VirtAddr},
     config::TRAMPOLINE_VA,
     error::KernelError,
+    fs::flush_expired,
     mm::{do_handle_page_fault, VMFlags},
     println,
     syscall::syscall,
     task::*,
-    timer::set_next_trigger,
+    timer::{set_next_trigger, update_tick_time, wake_expired},
 };
 
 use self::trapframe::KernelTrapContext;
@@ -84,6 +87,8 @@ pub fn user_trap_handler() -> ! {
         trace!("[U] Fatal exception {:#?}", err);
     };
 
+    // Set if the syscall is interrupted by a signal, see `do_signal`.
+    let mut restart = false;
     match scause.cause() {
         Trap::Exception(Exception::UserEnvCall) => {
             // pc + 4
@@ -93,29 +98,41 @@ pub fn user_trap_handler() -> ! {
 
             match syscall(trapframe.syscall_args().unwrap()) {
                 Ok(ret) => trapframe.set_a0(ret),
+                // Arguments are kept in case the syscall is restarted.
+                Err(Errno::ERESTART) => restart = true,
                 Err(errno) => {
                     trace!("{:#?} {:#?}", trapframe.syscall_args().unwrap().0, errno);
-                    trapframe.set_a0(-isize::from(errno) as usize)
+                    trapframe.set_a0(errno.as_ret())
                 }
             }
         }
-        Trap::Exception(Exception::StorePageFault) => {
+        Trap::Exception(cause @ (Exception::LoadPageFault | Exception::StorePageFault)) => {
             let curr = cpu().curr.as_ref().unwrap();
+            let stack_limit = curr.limits.lock().max_stack_size();
             let mut curr_mm = curr.mm();
             trap_info();
+            let access = if matches!(cause, Exception::StorePageFault) {
+                VMFlags::WRITE
+            } else {
+                VMFlags::READ
+            };
             if let Err(err) = do_handle_page_fault(
                 &mut curr_mm,
                 VirtAddr::from(stval),
-                VMFlags::USER | VMFlags::WRITE,
+                VMFlags::USER | access,
+                stack_limit,
             ) {
                 fatal_info(err);
                 drop(curr_mm);
-                unsafe { do_exit(-1) };
+                force_signal(SIGSEGV, SEGV_MAPERR);
             }
         }
         Trap::Interrupt(Interrupt::SupervisorTimer) => {
             trap_info();
             set_next_trigger();
+            update_tick_time();
+            wake_expired();
+            flush_expired();
             unsafe { do_yield() };
         }
         _ => {
@@ -125,6 +142,11 @@ pub fn user_trap_handler() -> ! {
             unsafe { do_exit(-1) };
         }
     }
+
+    if let Err(err) = do_signal(restart) {
+        fatal_info(err);
+        unsafe { do_exit(-1) };
+    }
     user_trap_return();
 }
 
@@ -142,6 +164,214 @@ pub fn user_trap_return() -> ! {
     #[cfg(feature = "sleeplock")]
     crate::tests::sleeplock::test();
 
+    #[cfg(feature = "ioctl")]
+    crate::tests::ioctl::test();
+
+    #[cfg(feature = "fionread")]
+    crate::tests::fionread::test();
+
+    #[cfg(feature = "fionbio")]
+    crate::tests::fionbio::test();
+
+    #[cfg(feature = "dup")]
+    crate::tests::dup::test();
+
+    #[cfg(feature = "devices")]
+    crate::tests::devices::test();
+
+    #[cfg(feature = "fcntl")]
+    crate::tests::fcntl::test();
+
+    #[cfg(feature = "chdir")]
+    crate::tests::chdir::test();
+
+    #[cfg(feature = "fstat")]
+    crate::tests::fstat::test();
+
+    #[cfg(feature = "pipe2")]
+    crate::tests::pipe2::test();
+
+    #[cfg(feature = "clone_stack")]
+    crate::tests::clone_stack::test();
+
+    #[cfg(feature = "interp")]
+    crate::tests::interp::test();
+
+    #[cfg(feature = "thread_group")]
+    crate::tests::thread_group::test();
+
+    #[cfg(feature = "rela")]
+    crate::tests::rela::test();
+
+    #[cfg(feature = "settid")]
+    crate::tests::settid::test();
+
+    #[cfg(feature = "shebang")]
+    crate::tests::shebang::test();
+
+    #[cfg(feature = "init_stack")]
+    crate::tests::init_stack::test();
+
+    #[cfg(feature = "enospc")]
+    crate::tests::enospc::test();
+
+    #[cfg(feature = "fat_resolve")]
+    crate::tests::fat_resolve::test();
+
+    #[cfg(feature = "futex")]
+    crate::tests::futex::test();
+
+    #[cfg(feature = "efs")]
+    crate::tests::efs::test();
+
+    #[cfg(feature = "splice")]
+    crate::tests::splice::test();
+
+    #[cfg(feature = "chroot")]
+    crate::tests::chroot::test();
+
+    #[cfg(feature = "sendfile")]
+    crate::tests::sendfile::test();
+
+    #[cfg(feature = "resolve")]
+    crate::tests::resolve::test();
+
+    #[cfg(feature = "symlink")]
+    crate::tests::symlink::test();
+
+    #[cfg(feature = "overlay")]
+    crate::tests::overlay::test();
+
+    #[cfg(feature = "access")]
+    crate::tests::access::test();
+
+    #[cfg(feature = "seek")]
+    crate::tests::seek::test();
+
+    #[cfg(feature = "sync")]
+    crate::tests::sync::test();
+
+    #[cfg(feature = "tmpfile")]
+    crate::tests::tmpfile::test();
+
+    #[cfg(feature = "tmpfs")]
+    crate::tests::tmpfs::test();
+
+    #[cfg(feature = "link")]
+    crate::tests::link::test();
+
+    #[cfg(feature = "rename")]
+    crate::tests::rename::test();
+
+    #[cfg(feature = "mkdirat")]
+    crate::tests::mkdirat::test();
+
+    #[cfg(feature = "getrandom")]
+    crate::tests::getrandom::test();
+
+    #[cfg(feature = "uname")]
+    crate::tests::uname::test();
+
+    #[cfg(feature = "rlimit")]
+    crate::tests::rlimit::test();
+
+    #[cfg(feature = "stack")]
+    crate::tests::stack::test();
+
+    #[cfg(feature = "madvise")]
+    crate::tests::madvise::test();
+
+    #[cfg(feature = "mlock")]
+    crate::tests::mlock::test();
+
+    #[cfg(feature = "frame")]
+    crate::tests::frame::test();
+
+    #[cfg(feature = "epoll")]
+    crate::tests::epoll::test();
+
+    #[cfg(feature = "iovec")]
+    crate::tests::iovec::test();
+
+    #[cfg(feature = "pwrite")]
+    crate::tests::pwrite::test();
+
+    #[cfg(feature = "restart")]
+    crate::tests::restart::test();
+
+    #[cfg(feature = "sigreturn")]
+    crate::tests::sigreturn::test();
+
+    #[cfg(feature = "tkill")]
+    crate::tests::tkill::test();
+
+    #[cfg(feature = "clock")]
+    crate::tests::clock::test();
+
+    #[cfg(feature = "process_vm")]
+    crate::tests::process_vm::test();
+
+    #[cfg(feature = "vma_merge")]
+    crate::tests::vma_merge::test();
+
+    #[cfg(feature = "maps")]
+    crate::tests::maps::test();
+
+    #[cfg(feature = "mmap_fixed")]
+    crate::tests::mmap_fixed::test();
+
+    #[cfg(feature = "mmap_file")]
+    crate::tests::mmap_file::test();
+
+    #[cfg(feature = "wait")]
+    crate::tests::wait::test();
+
+    #[cfg(feature = "pgid")]
+    crate::tests::pgid::test();
+
+    #[cfg(feature = "clone")]
+    crate::tests::clone::test();
+
+    #[cfg(feature = "robust_list")]
+    crate::tests::robust_list::test();
+
+    #[cfg(feature = "membarrier")]
+    crate::tests::membarrier::test();
+
+    #[cfg(feature = "eventfd")]
+    crate::tests::eventfd::test();
+
+    #[cfg(feature = "nanosleep")]
+    crate::tests::nanosleep::test();
+
+    #[cfg(feature = "itimer")]
+    crate::tests::itimer::test();
+
+    #[cfg(feature = "user_copy")]
+    crate::tests::user_copy::test();
+
+    #[cfg(feature = "pipe")]
+    crate::tests::pipe::test();
+
+    #[cfg(feature = "ppoll")]
+    crate::tests::ppoll::test();
+
+    #[cfg(feature = "efault")]
+    crate::tests::efault::test();
+
+    #[cfg(feature = "mknod")]
+    crate::tests::mknod::test();
+
+    #[cfg(feature = "open_count")]
+    crate::tests::open_count::test();
+
+    #[cfg(feature = "fd_table")]
+    crate::tests::fd_table::test();
+
+    // Shuts down the kernel.
+    #[cfg(feature = "lock_debug")]
+    crate::tests::lock_debug::test();
+
     #[cfg(feature = "uintr")]
     uintr_return();
 
@@ -151,7 +381,8 @@ pub fn user_trap_return() -> ! {
     }
     let (satp, trapframe_base, userret) = {
         let curr = cpu().curr.as_ref().unwrap();
-        let curr_mm = curr.mm();
+        let mut curr_mm = curr.mm();
+        curr_mm.run_on(get_cpu_id());
         (
             curr_mm.page_table.satp(),
             trapframe_base(curr.tid.0),
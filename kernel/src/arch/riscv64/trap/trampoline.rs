@@ -198,3 +198,20 @@ pub unsafe extern "C" fn __trampoline() {
         options(noreturn),
     );
 }
+
+/// Signal handlers return to this page mapped at [`crate::config::SIGRETURN_VA`] unless
+/// a restorer is provided, which issues `SIGRETURN` to restore the context before the
+/// signal is delivered.
+///
+/// Unlike [`__trampoline`], this page is accessible by user.
+#[naked]
+#[no_mangle]
+#[link_section = ".text.sigreturn"]
+pub unsafe extern "C" fn __sigreturn() {
+    core::arch::asm!(
+        // SyscallNO::SIGRETURN
+        "li a7, 139",
+        "ecall",
+        options(noreturn),
+    );
+}
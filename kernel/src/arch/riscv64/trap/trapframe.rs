@@ -29,6 +29,19 @@ pub struct TrapFrame {
     cpu_id: usize,
 }
 
+/// User context saved on the user stack when a signal is delivered.
+///
+/// Only the program counter and general registers are saved, since other fields of
+/// the trapframe are never modified by user, and kernel ones must not be exposed.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UserContext {
+    /// User program counter
+    epc: usize,
+    /// Saved global registers (arch dependent)
+    regs: [usize; 31],
+}
+
 impl TrapFrame {
     /// Create a new trap frame with user stack pointer.
     pub fn new(
@@ -122,6 +135,56 @@ impl TrapFrame {
         self.user_regs[1] = sp;
     }
 
+    /// Gets user stack pointer.
+    pub fn get_sp(&self) -> usize {
+        self.user_regs[1]
+    }
+
+    /// Set return address before jumping to signal handler.
+    pub fn set_ra(&mut self, ra: usize) {
+        self.user_regs[0] = ra;
+    }
+
+    /// Gets user return address.
+    pub fn get_ra(&self) -> usize {
+        self.user_regs[0]
+    }
+
+    /// Set user program counter.
+    pub fn set_epc(&mut self, epc: usize) {
+        self.user_epc = epc;
+    }
+
+    /// Gets user program counter.
+    pub fn get_epc(&self) -> usize {
+        self.user_epc
+    }
+
+    /// Gets the first argument of a syscall, or the return value after it.
+    pub fn get_a0(&self) -> usize {
+        self.user_regs[9]
+    }
+
+    /// Saves the user context before a signal is delivered.
+    pub fn user_context(&self) -> UserContext {
+        UserContext {
+            epc: self.user_epc,
+            regs: self.user_regs,
+        }
+    }
+
+    /// Restores the user context saved before a signal is delivered.
+    pub fn restore(&mut self, saved: &UserContext) {
+        self.user_epc = saved.epc;
+        self.user_regs = saved.regs;
+    }
+
+    /// Rewinds user program counter to issue the syscall again, the reverse of
+    /// [`Self::next_epc`].
+    pub fn restart_syscall(&mut self) {
+        self.user_epc -= 4;
+    }
+
     /// Set tp while cloning task with tls
     pub fn set_tp(&mut self, tp: usize) {
         self.user_regs[3] = tp;
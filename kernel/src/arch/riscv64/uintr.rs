@@ -55,7 +55,7 @@ impl UIntrSender {
 
     /// Allocates a new [`UISTE`].
     pub fn alloc(&mut self) -> Option<usize> {
-        let new = self.alloc.alloc();
+        let new = self.alloc.alloc_unchecked();
         if new < self.limit { Some(new) } else { None }
     }
 
@@ -114,7 +114,7 @@ pub struct UIntrReceiverTracker(pub usize);
 
 impl UIntrReceiverTracker {
     pub fn new() -> Self {
-        let new = UINTR_RECEIVER_ALLOC.lock().alloc();
+        let new = UINTR_RECEIVER_ALLOC.lock().alloc_unchecked();
         assert!(new < 512);
         Self(new)
     }
@@ -5,11 +5,17 @@ pub use mm_rv::{LOW_MAX_VA, MAX_VA, PAGE_SIZE};
 pub const ADDR_ALIGN: usize = core::mem::size_of::<usize>();
 
 /// Use guard page to avoid stack overflow.
+///
+/// No other area is mapped within a guard page below the user stack.
 pub const GUARD_PAGE: usize = PAGE_SIZE;
 
 /// Trampoline takes up the highest page both in user and kernel space.
 pub const TRAMPOLINE_VA: usize = MAX_VA - PAGE_SIZE + 1;
 
+/// Signal handlers return to the page below the trampoline, which is accessible by
+/// user, to issue `sigreturn`.
+pub const SIGRETURN_VA: usize = TRAMPOLINE_VA - PAGE_SIZE;
+
 /// CPUs
 pub const CPU_NUM: usize = 4;
 
@@ -53,15 +59,44 @@ pub const MMIO: &[(usize, usize)] = &[
 /// The number of block cache units for virtio.
 pub const CACHE_SIZE: usize = 32;
 
+/// Dirty blocks older than this (in milliseconds) are written back periodically,
+/// like `dirty_expire_centisecs` in Linux.
+pub const DIRTY_EXPIRE_MS: usize = 3000;
+
+/// Maximum number of dirty blocks written back in one timer tick.
+pub const DIRTY_FLUSH_BATCH: usize = 4;
+
 /// Size of virtual block device: 40 MB
 pub const FS_IMG_SIZE: usize = 40 * 1024 * 1024;
 
 /// Default maximum file descriptor limit.
 pub const DEFAULT_FD_LIMIT: usize = 0x100;
 
+/// Default hard limit of the number of file descriptors, which cannot be raised.
+pub const MAX_FD_LIMIT: usize = 0x1000;
+
 /// Boot root directory
 pub const ROOT_DIR: &str = "/";
 
+/// Operating system name returned by `uname`.
+pub const UTS_SYSNAME: &str = "tCore";
+
+/// Host name returned by `uname`.
+pub const UTS_NODENAME: &str = "tcore";
+
+/// Release returned by `uname`, which looks like a Linux release since the C library
+/// may check it.
+pub const UTS_RELEASE: &str = "5.15.0";
+
+/// Version returned by `uname`.
+pub const UTS_VERSION: &str = "#1 SMP";
+
+/// Hardware identifier returned by `uname`.
+pub const UTS_MACHINE: &str = "riscv64";
+
+/// NIS domain name returned by `uname`, the same as Linux if not set.
+pub const UTS_DOMAINNAME: &str = "(none)";
+
 /// Absolute path of init task
 pub const INIT_TASK_PATH: &str = "hello_world";
 
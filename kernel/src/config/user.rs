@@ -9,9 +9,18 @@ pub const USER_HEAP_SIZE: usize = 0x40_0000;
 /// User heap pages
 pub const USER_HEAP_PAGES: usize = USER_HEAP_SIZE >> PAGE_SIZE_BITS;
 
-/// User stack size
+/// User stack size, which grows down on demand.
 pub const USER_STACK_SIZE: usize = 0x20_0000;
 
+/// Default soft limit of `RLIMIT_STACK`, up to which the user stack grows.
+pub const USER_STACK_MAX_SIZE: usize = 0x80_0000;
+
+/// Default limit of `RLIMIT_MEMLOCK`, the bytes of memory locked by `mlock`.
+pub const USER_MEMLOCK_SIZE: usize = 0x1_0000;
+
+/// Store page faults at most this far below the user stack grow it.
+pub const USER_STACK_GROW_WINDOW: usize = 0x1_0000;
+
 /// User stack pages
 pub const USER_STACK_PAGES: usize = USER_STACK_SIZE >> PAGE_SIZE_BITS;
 
@@ -19,4 +28,8 @@ pub const USER_STACK_PAGES: usize = USER_STACK_SIZE >> PAGE_SIZE_BITS;
 pub const USER_STACK_BASE: usize = LOW_MAX_VA + 1;
 
 /// Relocatable file address
-pub const ELF_BASE_RELOCATE: usize = 0x8000_0000;
\ No newline at end of file
+pub const ELF_BASE_RELOCATE: usize = 0x8000_0000;
+
+/// Relocatable program interpreter address, used if the program itself is
+/// relocated to [`ELF_BASE_RELOCATE`].
+pub const INTERP_BASE_RELOCATE: usize = 0x20_0000_0000;
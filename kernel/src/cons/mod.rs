@@ -4,6 +4,8 @@ mod panic;
 use core::fmt::{Arguments, Write};
 use kernel_sync::SpinLock;
 pub use logger::init;
+#[cfg(feature = "lock_debug")]
+pub use panic::expect_panic;
 use spin::Lazy;
 
 struct Stdin;
@@ -11,6 +11,17 @@ use crate::{
 
 static PANIC_COUNT: Lazy<SpinLock<usize>> = Lazy::new(|| SpinLock::new(0));
 
+/// Part of the message of a panic expected by a test.
+#[cfg(feature = "lock_debug")]
+static PANIC_EXPECTED: Lazy<SpinLock<Option<&'static str>>> = Lazy::new(|| SpinLock::new(None));
+
+/// Expects the next panic to contain `pattern` in its message, which then shuts down
+/// with success, since a panic cannot be caught without unwinding.
+#[cfg(feature = "lock_debug")]
+pub fn expect_panic(pattern: &'static str) {
+    *PANIC_EXPECTED.lock() = Some(pattern);
+}
+
 #[panic_handler]
 fn panic(info: &PanicInfo) -> ! {
     if let Some(location) = info.location() {
@@ -29,6 +40,14 @@ fn panic(info: &PanicInfo) -> ! {
         );
     }
 
+    #[cfg(feature = "lock_debug")]
+    if let Some(pattern) = PANIC_EXPECTED.lock().take() {
+        if alloc::format!("{}", info.message().unwrap()).contains(pattern) {
+            println!("Expected panic caught! Shutting down...");
+            system_reset(Shutdown, NoReason);
+        }
+    }
+
     let mut panic_count = PANIC_COUNT.lock();
     *panic_count += 1;
     if *panic_count == CPU_NUM {
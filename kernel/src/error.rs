@@ -72,10 +72,19 @@ pub enum KernelError {
 
     /// Run out of free memory
     VMAAllocFailed,
+
+    /// An SBI call failed with the error code.
+    SBIFailed(usize),
 }
 
 pub type KernelResult<T = ()> = Result<T, KernelError>;
 
+impl From<Errno> for KernelError {
+    fn from(value: Errno) -> Self {
+        KernelError::Errno(value)
+    }
+}
+
 impl From<KernelError> for Errno {
     fn from(value: KernelError) -> Self {
         match value {
@@ -83,8 +92,11 @@ impl From<KernelError> for Errno {
             KernelError::PageTableInvalid => Errno::EFAULT,
             KernelError::InvalidArgs => Errno::EINVAL,
             KernelError::FDNotFound => Errno::EBADF,
+            KernelError::FDOutOfBound => Errno::EMFILE,
             KernelError::VMANotFound | KernelError::VMAAllocFailed => Errno::ENOMEM,
+            KernelError::FrameAllocFailed => Errno::ENOMEM,
             KernelError::VMAFailedIO => Errno::EACCES,
+            KernelError::IOWriteZero => Errno::ENOSPC,
             
             // TODO
             _ => Errno::EINVAL,
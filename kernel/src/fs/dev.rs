@@ -0,0 +1,153 @@
+//! Character and block devices are identified by a major number, which identifies
+//! the driver associated with the device, and a minor number, which distinguishes
+//! the devices handled by the same driver.
+//!
+//! See `<https://www.kernel.org/doc/Documentation/admin-guide/devices.txt>`.
+
+use alloc::{collections::BTreeMap, string::String, sync::Arc};
+use errno::Errno;
+use kernel_sync::SpinLock;
+use spin::Lazy;
+use vfs::File;
+
+use super::{
+    mem::{NullFile, RandomFile, ZeroFile},
+    Tty,
+};
+
+/// Creates a new open file description of the device.
+pub type DeviceFactory = fn() -> Arc<dyn File>;
+
+/// Makes a device number (`dev_t`) from major and minor numbers.
+pub const fn makedev(major: usize, minor: usize) -> u64 {
+    (((major & 0xfff) << 8) | (minor & 0xff) | ((minor & !0xff) << 12)) as u64
+}
+
+/// Extracts the major number from a device number.
+pub const fn major(dev: u64) -> usize {
+    ((dev >> 8) & 0xfff) as usize
+}
+
+/// Extracts the minor number from a device number.
+pub const fn minor(dev: u64) -> usize {
+    ((dev & 0xff) | ((dev >> 12) & !0xff)) as usize
+}
+
+/// Anonymous devices, e.g. filesystems in memory
+pub const UNNAMED_MAJOR: usize = 0;
+
+/// Memory devices
+pub const MEM_MAJOR: usize = 1;
+
+/// Terminal devices
+pub const TTYAUX_MAJOR: usize = 5;
+
+/// Virtio block devices
+pub const VIRTBLK_MAJOR: usize = 254;
+
+/// Device registry mapping `(major, minor)` to device factories.
+pub struct DeviceRegistry {
+    /// Registered devices.
+    devices: BTreeMap<(usize, usize), DeviceFactory>,
+
+    /// Device nodes under `/dev`.
+    nodes: BTreeMap<String, (usize, usize)>,
+}
+
+impl DeviceRegistry {
+    /// Creates an empty device registry.
+    pub fn new() -> Self {
+        Self {
+            devices: BTreeMap::new(),
+            nodes: BTreeMap::new(),
+        }
+    }
+
+    /// Registers a device with the given device number.
+    ///
+    /// # Error
+    /// - `EBUSY`: The device number has been registered.
+    pub fn register(
+        &mut self,
+        major: usize,
+        minor: usize,
+        factory: DeviceFactory,
+    ) -> Result<(), Errno> {
+        if self.devices.contains_key(&(major, minor)) {
+            return Err(Errno::EBUSY);
+        }
+        self.devices.insert((major, minor), factory);
+        Ok(())
+    }
+
+    /// Unregisters a device.
+    pub fn unregister(&mut self, major: usize, minor: usize) -> Option<DeviceFactory> {
+        self.devices.remove(&(major, minor))
+    }
+
+    /// Returns true if the device number has been registered.
+    pub fn contains(&self, major: usize, minor: usize) -> bool {
+        self.devices.contains_key(&(major, minor))
+    }
+
+    /// Opens a registered device.
+    ///
+    /// # Error
+    /// - `ENXIO`: No device is registered with the device number.
+    pub fn open(&self, major: usize, minor: usize) -> Result<Arc<dyn File>, Errno> {
+        self.devices
+            .get(&(major, minor))
+            .map(|factory| factory())
+            .ok_or(Errno::ENXIO)
+    }
+
+    /// Adds a device node under `/dev`.
+    pub fn add_node(&mut self, name: &str, major: usize, minor: usize) {
+        self.nodes.insert(String::from(name), (major, minor));
+    }
+
+    /// Creates a device node under `/dev` referencing the device number.
+    ///
+    /// The device need not be registered yet; opening the node fails with `ENXIO`
+    /// until it is registered.
+    ///
+    /// # Error
+    /// - `EEXIST`: The node already exists.
+    pub fn mknod(&mut self, name: &str, major: usize, minor: usize) -> Result<(), Errno> {
+        if self.nodes.contains_key(name) {
+            return Err(Errno::EEXIST);
+        }
+        self.add_node(name, major, minor);
+        Ok(())
+    }
+
+    /// Looks up the device number of a node under `/dev`.
+    pub fn lookup(&self, name: &str) -> Option<(usize, usize)> {
+        self.nodes.get(name).copied()
+    }
+}
+
+/// Global device registry, populated with devices in canonical numbers.
+pub static DEVICES: Lazy<SpinLock<DeviceRegistry>> = Lazy::new(|| {
+    let mut registry = DeviceRegistry::new();
+    let devices: [(&str, usize, usize, DeviceFactory); 6] = [
+        ("null", MEM_MAJOR, 3, || Arc::new(NullFile)),
+        ("zero", MEM_MAJOR, 5, || Arc::new(ZeroFile)),
+        ("random", MEM_MAJOR, 8, || Arc::new(RandomFile::new())),
+        ("urandom", MEM_MAJOR, 9, || Arc::new(RandomFile::new())),
+        ("tty", TTYAUX_MAJOR, 0, || Arc::new(Tty)),
+        ("console", TTYAUX_MAJOR, 1, || Arc::new(Tty)),
+    ];
+    for (name, major, minor, factory) in devices {
+        registry.register(major, minor, factory).unwrap();
+        registry.add_node(name, major, minor);
+    }
+    SpinLock::new(registry)
+});
+
+/// Opens a device node under `/dev`.
+pub fn open_dev(name: &str) -> Option<Result<Arc<dyn File>, Errno>> {
+    let devices = DEVICES.lock();
+    let (major, minor) = devices.lookup(name)?;
+    Some(devices.open(major, minor))
+}
@@ -0,0 +1,375 @@
+use alloc::{sync::Arc, vec::Vec};
+use easy_fs::{EasyFileSystem, FsError, Inode, BLOCK_SZ};
+use errno::Errno;
+use kernel_sync::SpinLock;
+use syscall_interface::FIONREAD;
+use vfs::*;
+
+use super::{fat::path_ino, put_nread};
+
+/// Longest name stored in a directory entry of easy-fs.
+const EFS_NAME_MAX: usize = 27;
+
+/// A file or directory in easy-fs.
+pub struct EasyFile {
+    /// Absolute path including the mount point.
+    path: Path,
+
+    /// Device number of the filesystem.
+    dev: u64,
+
+    inode: Arc<Inode>,
+
+    flags: SpinLock<OpenFlags>,
+
+    /// Offset of a regular file, or index of the next entry of a directory.
+    pos: SpinLock<usize>,
+}
+
+impl EasyFile {
+    pub fn new(path: Path, dev: u64, inode: Arc<Inode>, flags: OpenFlags) -> Self {
+        Self {
+            path,
+            dev,
+            inode,
+            flags: SpinLock::new(flags),
+            pos: SpinLock::new(0),
+        }
+    }
+}
+
+impl File for EasyFile {
+    fn read(&self, buf: &mut [u8]) -> Option<usize> {
+        if !self.readable() || self.inode.is_dir() {
+            return None;
+        }
+        let mut pos = self.pos.lock();
+        let read_len = self.inode.read_at(*pos, buf);
+        *pos += read_len;
+        Some(read_len)
+    }
+
+    fn write(&self, buf: &[u8]) -> Option<usize> {
+        self.try_write(buf).ok()
+    }
+
+    /// Returns `ENOSPC` if nothing was written since no block is free.
+    fn try_write(&self, buf: &[u8]) -> Result<usize, Errno> {
+        if !self.writable() || self.inode.is_dir() {
+            return Err(Errno::EBADF);
+        }
+        let mut pos = self.pos.lock();
+        if self.flags.lock().contains(OpenFlags::O_APPEND) {
+            *pos = self.inode.size();
+        }
+        let write_len = self.inode.write_at(*pos, buf);
+        if write_len == 0 && !buf.is_empty() {
+            return Err(Errno::ENOSPC);
+        }
+        *pos += write_len;
+        Ok(write_len)
+    }
+
+    fn readable(&self) -> bool {
+        self.flags.lock().readable()
+    }
+
+    fn writable(&self) -> bool {
+        self.flags.lock().writable()
+    }
+
+    fn clear(&self) {
+        self.inode.clear();
+        *self.pos.lock() = 0;
+    }
+
+    unsafe fn read_all(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.resize(self.inode.size(), 0);
+        let len = self.inode.read_at(0, &mut buf);
+        buf.truncate(len);
+        buf
+    }
+
+    fn read_at_off(&self, off: usize, buf: &mut [u8]) -> Option<usize> {
+        if !self.readable() || self.inode.is_dir() {
+            return None;
+        }
+        Some(self.inode.read_at(off, buf))
+    }
+
+    fn write_at_off(&self, off: usize, buf: &[u8]) -> Option<usize> {
+        if !self.writable() || self.inode.is_dir() {
+            return None;
+        }
+        Some(self.inode.write_at(off, buf))
+    }
+
+    fn read_ready(&self) -> bool {
+        self.readable() && *self.pos.lock() < self.inode.size()
+    }
+
+    fn write_ready(&self) -> bool {
+        self.writable()
+    }
+
+    fn seek(&self, offset: usize, whence: SeekWhence) -> Option<usize> {
+        let mut pos = self.pos.lock();
+        match whence {
+            SeekWhence::Set => *pos = offset,
+            SeekWhence::Current => *pos = (*pos as isize + offset as isize) as usize,
+            SeekWhence::End => {
+                if self.inode.is_dir() {
+                    return None;
+                }
+                *pos = (self.inode.size() as isize + offset as isize) as usize;
+            }
+            SeekWhence::Data | SeekWhence::Hole => {
+                if self.inode.is_dir() {
+                    return None;
+                }
+                *pos = whence.data_or_hole(offset, self.inode.size())?;
+            }
+        }
+        Some(*pos)
+    }
+
+    fn open_flags(&self) -> OpenFlags {
+        *self.flags.lock()
+    }
+
+    fn set_open_flags(&self, flags: OpenFlags) {
+        let changeable = OpenFlags::O_APPEND | OpenFlags::O_NONBLOCK;
+        let mut inner = self.flags.lock();
+        *inner = (*inner - changeable) | (flags & changeable);
+    }
+
+    fn get_stat(&self, stat_ptr: *mut Stat) -> bool {
+        let mut stat = Stat::default();
+        let file_type = if self.inode.is_dir() {
+            StatMode::S_IFDIR
+        } else {
+            StatMode::S_IFREG
+        };
+        stat.st_mode = (file_type | StatMode::from_bits_truncate(self.inode.mode() as u32)).bits();
+        stat.st_nlink = 1;
+        stat.st_dev = self.dev;
+        stat.st_ino = path_ino(&self.path);
+        stat.st_size = self.inode.size() as u64;
+        stat.st_blksize = BLOCK_SZ as u32;
+        stat.st_blocks = (stat.st_size + stat.st_blksize as u64 - 1) / stat.st_blksize as u64;
+        unsafe { *stat_ptr = stat };
+        true
+    }
+
+    fn get_size(&self) -> Option<usize> {
+        Some(self.inode.size())
+    }
+
+    fn get_off(&self) -> usize {
+        *self.pos.lock()
+    }
+
+    fn read_dir(&self) -> Option<Vec<DirEntry>> {
+        if !self.inode.is_dir() {
+            return None;
+        }
+        let entries = self
+            .inode
+            .readdir()
+            .into_iter()
+            .filter_map(|name| {
+                let inode = self.inode.find(name.as_str())?;
+                let mut path = self.path.clone();
+                path.extend(name.as_str());
+                Some(DirEntry {
+                    ino: path_ino(&path),
+                    d_type: if inode.is_dir() { DT_DIR } else { DT_REG },
+                    name,
+                })
+            })
+            .collect();
+        Some(entries)
+    }
+
+    fn is_dir(&self) -> bool {
+        self.inode.is_dir()
+    }
+
+    fn is_reg(&self) -> bool {
+        !self.inode.is_dir()
+    }
+
+    fn get_path(&self) -> Option<Path> {
+        Some(self.path.clone())
+    }
+
+    fn ioctl(&self, cmd: usize, arg: usize) -> Result<usize, Errno> {
+        match cmd {
+            FIONREAD if !self.inode.is_dir() => {
+                let pos = *self.pos.lock();
+                put_nread(arg, self.inode.size().saturating_sub(pos))
+            }
+            _ => Err(Errno::ENOTTY),
+        }
+    }
+}
+
+/// Adapter of easy-fs to [`VFS`], which is mounted through [`super::mount`].
+pub struct EasyFs {
+    /// Mount point, which always ends with `'/'`.
+    mount_point: Path,
+
+    /// Device number of the filesystem.
+    dev: u64,
+
+    efs: Arc<SpinLock<EasyFileSystem>>,
+}
+
+impl EasyFs {
+    pub fn new(efs: Arc<SpinLock<EasyFileSystem>>, mount_point: Path, dev: u64) -> Self {
+        Self {
+            mount_point,
+            dev,
+            efs,
+        }
+    }
+
+    /// Finds the inode of an absolute path under the mount point.
+    ///
+    /// Returns `ENOENT` if a component does not exist, or `ENOTDIR` if a component
+    /// except the last one is not a directory.
+    fn resolve(&self, path: &Path) -> Result<Arc<Inode>, Errno> {
+        let rela = path
+            .as_str()
+            .strip_prefix(self.mount_point.as_str())
+            .ok_or(Errno::ENOENT)?;
+        let mut inode = Arc::new(EasyFileSystem::root_inode(&self.efs));
+        for name in rela.split('/').filter(|name| !name.is_empty()) {
+            if !inode.is_dir() {
+                return Err(Errno::ENOTDIR);
+            }
+            inode = inode.find(name).ok_or(Errno::ENOENT)?;
+        }
+        Ok(inode)
+    }
+
+    /// Finds the parent directory.
+    fn resolve_dir(&self, pdir: &Path) -> Result<Arc<Inode>, Errno> {
+        let inode = self.resolve(pdir)?;
+        if inode.is_dir() {
+            Ok(inode)
+        } else {
+            Err(Errno::ENOTDIR)
+        }
+    }
+}
+
+/// Checks the length of a name stored in easy-fs.
+fn check_efs_name(name: &str) -> Result<(), Errno> {
+    check_name(name)?;
+    if name.len() > EFS_NAME_MAX {
+        Err(Errno::ENAMETOOLONG)
+    } else {
+        Ok(())
+    }
+}
+
+/// Converts errors of easy-fs to [`Errno`].
+fn from(value: FsError) -> Errno {
+    match value {
+        FsError::Exists => Errno::EEXIST,
+        FsError::NotFound => Errno::ENOENT,
+        FsError::NoSpace => Errno::ENOSPC,
+        FsError::BadMagic | FsError::BadLayout => Errno::EINVAL,
+    }
+}
+
+impl VFS for EasyFs {
+    fn open(&self, pdir: &Path, name: &str, flags: OpenFlags) -> Result<Arc<dyn File>, Errno> {
+        let mut path = pdir.clone();
+        path.extend(name);
+        let pdir = self.resolve_dir(pdir)?;
+        let name = name.trim_end_matches('/');
+
+        let inode = match pdir.find(name) {
+            Some(inode) => {
+                if flags.contains(OpenFlags::O_CREAT | OpenFlags::O_EXCL) {
+                    return Err(Errno::EEXIST);
+                }
+                if inode.is_dir() {
+                    if flags.writable() {
+                        return Err(Errno::EISDIR);
+                    }
+                } else if flags.contains(OpenFlags::O_DIRECTORY) || path.is_dir() {
+                    return Err(Errno::ENOTDIR);
+                } else if flags.contains(OpenFlags::O_TRUNC) {
+                    inode.clear();
+                }
+                inode
+            }
+            None => {
+                if !flags.contains(OpenFlags::O_CREAT) || path.is_dir() {
+                    return Err(Errno::ENOENT);
+                }
+                check_efs_name(name)?;
+                pdir.create(name).map_err(from)?
+            }
+        };
+        Ok(Arc::new(EasyFile::new(path, self.dev, inode, flags)))
+    }
+
+    fn mkdir(&self, pdir: &Path, name: &str, mode: StatMode) -> Result<(), Errno> {
+        let name = name.trim_end_matches('/');
+        check_efs_name(name)?;
+        let pdir = self.resolve_dir(pdir)?;
+        if pdir.find(name).is_some() {
+            return Err(Errno::EEXIST);
+        }
+        let mode = mode & (StatMode::S_ISVTX | StatMode::S_IRWXUGO);
+        pdir.create_dir(name, mode.bits() as u16).map_err(from)?;
+        Ok(())
+    }
+
+    fn check(&self, path: &Path) -> bool {
+        match self.resolve(path) {
+            Ok(inode) => !path.is_dir() || inode.is_dir(),
+            Err(_) => false,
+        }
+    }
+
+    fn remove(&self, pdir: &Path, name: &str) -> Result<(), Errno> {
+        let name = name.trim_end_matches('/');
+        let pdir = self.resolve_dir(pdir)?;
+        let inode = pdir.find(name).ok_or(Errno::ENOENT)?;
+        if inode.is_dir() && !inode.readdir().is_empty() {
+            return Err(Errno::ENOTEMPTY);
+        }
+        pdir.unlink(name);
+        Ok(())
+    }
+
+    fn rename(
+        &self,
+        old_pdir: &Path,
+        old_name: &str,
+        new_pdir: &Path,
+        new_name: &str,
+    ) -> Result<(), Errno> {
+        let old_name = old_name.trim_end_matches('/');
+        let new_name = new_name.trim_end_matches('/');
+        check_efs_name(new_name)?;
+        let old_pdir = self.resolve_dir(old_pdir)?;
+        let new_pdir = self.resolve_dir(new_pdir)?;
+        let inode = old_pdir.find(old_name).ok_or(Errno::ENOENT)?;
+        if let Some(replaced) = new_pdir.find(new_name) {
+            match (inode.is_dir(), replaced.is_dir()) {
+                (false, true) => return Err(Errno::EISDIR),
+                (true, false) => return Err(Errno::ENOTDIR),
+                (true, true) if !replaced.readdir().is_empty() => return Err(Errno::ENOTEMPTY),
+                _ => {}
+            }
+        }
+        old_pdir.rename(old_name, &new_pdir, new_name).map_err(from)
+    }
+}
@@ -0,0 +1,217 @@
+use alloc::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Weak},
+    vec::Vec,
+};
+use errno::Errno;
+use kernel_sync::SpinLock;
+use syscall_interface::{EpollEvent, EpollEvents, EPOLL_CTL_ADD, EPOLL_CTL_DEL, EPOLL_CTL_MOD};
+use vfs::{ring_buf::Waker, File, OpenFlags};
+
+/// An entry in the interest list of an [`EpollFile`].
+struct EpollItem {
+    /// The registered file, which is removed from the interest list once it is closed.
+    file: Weak<dyn File>,
+
+    /// Requested events and user data, the events of which are cleared after reported
+    /// with `EPOLLONESHOT`.
+    event: EpollEvent,
+}
+
+impl EpollItem {
+    /// Returns the events occurred on the file, or [`None`] if it has been closed.
+    fn poll(&self) -> Option<EpollEvents> {
+        let file = self.file.upgrade()?;
+        if (self.event.events - EpollEvents::EPOLLONESHOT).is_empty() {
+            return Some(EpollEvents::empty());
+        }
+        Some(
+            self.event
+                .events
+                .occurred(file.read_ready(), file.write_ready(), file.hangup()),
+        )
+    }
+}
+
+/// An epoll instance created by `epoll_create1(2)`.
+///
+/// The interest list is polled for readiness in [`EpollFile::wait`] without blocking.
+/// The epoll file is readable once any file in the interest list is ready, and its
+/// wakers are registered on all of these files, so that `epoll_pwait(2)` sleeps on it
+/// the same as `ppoll(2)`. Events are always level-triggered, even with `EPOLLET`.
+pub struct EpollFile {
+    /// Interest list by file descriptors.
+    interest: SpinLock<BTreeMap<usize, EpollItem>>,
+
+    /// File descriptors found ready but not reported yet, which are reported first
+    /// in the next wait, thus no file descriptor starves with a small `maxevents`.
+    ready: SpinLock<VecDeque<usize>>,
+
+    flags: SpinLock<OpenFlags>,
+}
+
+impl EpollFile {
+    pub fn new() -> Self {
+        Self {
+            interest: SpinLock::new(BTreeMap::new()),
+            ready: SpinLock::new(VecDeque::new()),
+            flags: SpinLock::new(OpenFlags::O_RDWR),
+        }
+    }
+
+    /// Adds, modifies or removes the interest of `fd` referring to `file`.
+    ///
+    /// `event` is ignored with `EPOLL_CTL_DEL`.
+    pub fn ctl(
+        &self,
+        op: usize,
+        fd: usize,
+        file: &Arc<dyn File>,
+        event: EpollEvent,
+    ) -> Result<(), Errno> {
+        // An epoll instance cannot watch itself.
+        if (**file)
+            .as_any()
+            .downcast_ref::<Self>()
+            .map_or(false, |epoll| core::ptr::eq(epoll, self))
+        {
+            return Err(Errno::EINVAL);
+        }
+        if file.is_reg() || file.is_dir() {
+            return Err(Errno::EPERM);
+        }
+
+        let mut interest = self.interest.lock();
+        // A closed file may leave its entry until the next wait.
+        let registered = interest
+            .get(&fd)
+            .map_or(false, |item| item.file.strong_count() != 0);
+        match op {
+            EPOLL_CTL_ADD => {
+                if registered {
+                    return Err(Errno::EEXIST);
+                }
+                interest.insert(
+                    fd,
+                    EpollItem {
+                        file: Arc::downgrade(file),
+                        event,
+                    },
+                );
+            }
+            EPOLL_CTL_MOD => {
+                if !registered {
+                    return Err(Errno::ENOENT);
+                }
+                if event.events.contains(EpollEvents::EPOLLEXCLUSIVE) {
+                    return Err(Errno::EINVAL);
+                }
+                interest.get_mut(&fd).unwrap().event = event;
+            }
+            EPOLL_CTL_DEL => {
+                if !registered {
+                    return Err(Errno::ENOENT);
+                }
+                interest.remove(&fd);
+            }
+            _ => return Err(Errno::EINVAL),
+        }
+        Ok(())
+    }
+
+    /// Returns at most `maxevents` ready events without blocking.
+    ///
+    /// File descriptors left in the ready list are reported first, then the ready list
+    /// is refilled by polling all files in the interest list. Each entry is checked
+    /// again before reported.
+    pub fn wait(&self, maxevents: usize) -> Vec<EpollEvent> {
+        let mut interest = self.interest.lock();
+        let mut ready = self.ready.lock();
+        interest.retain(|_, item| item.file.strong_count() != 0);
+
+        let mut events = Vec::new();
+        let mut reported = Vec::new();
+        let mut refilled = false;
+        while events.len() < maxevents {
+            let fd = match ready.pop_front() {
+                Some(fd) => fd,
+                None if !refilled => {
+                    refilled = true;
+                    ready.extend(
+                        interest
+                            .iter()
+                            .filter(|(fd, item)| {
+                                !reported.contains(*fd)
+                                    && item.poll().map_or(false, |events| !events.is_empty())
+                            })
+                            .map(|(fd, _)| *fd),
+                    );
+                    continue;
+                }
+                None => break,
+            };
+            let item = match interest.get_mut(&fd) {
+                Some(item) => item,
+                None => continue,
+            };
+            let occurred = item.poll().unwrap_or(EpollEvents::empty());
+            if occurred.is_empty() {
+                continue;
+            }
+            events.push(EpollEvent {
+                events: occurred,
+                data: item.event.data,
+            });
+            reported.push(fd);
+            if item.event.events.contains(EpollEvents::EPOLLONESHOT) {
+                item.event.events = EpollEvents::EPOLLONESHOT;
+            }
+        }
+        events
+    }
+
+    /// Returns the files in the interest list, which are accessed without the lock,
+    /// since they may be epoll files as well.
+    fn files(&self) -> Vec<Arc<dyn File>> {
+        self.interest
+            .lock()
+            .values()
+            .filter_map(|item| item.file.upgrade())
+            .collect()
+    }
+}
+
+impl File for EpollFile {
+    /// An epoll file descriptor is readable if any file in the interest list is ready.
+    fn read_ready(&self) -> bool {
+        self.interest
+            .lock()
+            .values()
+            .any(|item| item.poll().map_or(false, |events| !events.is_empty()))
+    }
+
+    /// Registers wakers on all files in the interest list.
+    fn poll_wait(&self, id: usize, waker: &dyn Fn() -> Waker) -> bool {
+        let mut waitable = true;
+        for file in self.files() {
+            waitable &= file.poll_wait(id, waker);
+        }
+        waitable
+    }
+
+    fn poll_cancel(&self, id: usize) {
+        for file in self.files() {
+            file.poll_cancel(id);
+        }
+    }
+
+    fn open_flags(&self) -> OpenFlags {
+        *self.flags.lock()
+    }
+
+    fn set_open_flags(&self, flags: OpenFlags) {
+        self.flags
+            .lock()
+            .set(OpenFlags::O_NONBLOCK, flags.contains(OpenFlags::O_NONBLOCK));
+    }
+}
@@ -0,0 +1,185 @@
+use core::mem::size_of;
+use errno::Errno;
+use kernel_sync::SpinLock;
+use vfs::{
+    ring_buf::{WaitQueue, Waker},
+    File, OpenFlags,
+};
+
+use crate::task::{cpu, do_sleep, signal_pending, TaskState};
+
+use super::pipe::{sleep_waker, waiter_id};
+
+/// Maximum value of the counter, so that a write blocks if it would be exceeded.
+const MAX_COUNT: u64 = u64::MAX - 1;
+
+struct Counter {
+    value: u64,
+
+    /// Readers waiting for the counter to be nonzero.
+    read_wakers: WaitQueue,
+
+    /// Writers waiting for the counter to drop.
+    write_wakers: WaitQueue,
+}
+
+/// An event notification file created by `eventfd2(2)`.
+///
+/// Both reads and writes transfer an 8-byte integer in native byte order, so buffers
+/// shorter than that are rejected.
+pub struct EventFd {
+    counter: SpinLock<Counter>,
+
+    /// Reads decrement the counter by one instead of resetting it.
+    semaphore: bool,
+
+    /// Status flags of the open file description.
+    flags: SpinLock<OpenFlags>,
+}
+
+impl EventFd {
+    pub fn new(initval: u64, semaphore: bool, flags: OpenFlags) -> Self {
+        Self {
+            counter: SpinLock::new(Counter {
+                value: initval,
+                read_wakers: WaitQueue::new(),
+                write_wakers: WaitQueue::new(),
+            }),
+            semaphore,
+            flags: SpinLock::new(OpenFlags::O_RDWR | flags),
+        }
+    }
+
+    /// Returns true if the eventfd is in non-blocking mode.
+    fn is_nonblock(&self) -> bool {
+        self.flags.lock().contains(OpenFlags::O_NONBLOCK)
+    }
+}
+
+/// Registers a waker of current task on `wakers` before it blocks.
+///
+/// Returns `EAGAIN` in non-blocking mode, or `ERESTART` if interrupted by a signal, in
+/// which case the waker is removed and the task keeps running. Signals are checked
+/// after the waker is registered, so that a signal queued in between is never missed.
+fn wait_on(wakers: &mut WaitQueue, nonblock: bool) -> Result<(), Errno> {
+    if nonblock {
+        return Err(Errno::EAGAIN);
+    }
+    wakers.push(waiter_id(), sleep_waker());
+    if signal_pending() {
+        wakers.remove(waiter_id());
+        cpu().curr.as_ref().unwrap().locked_inner().state = TaskState::RUNNING;
+        return Err(Errno::ERESTART);
+    }
+    Ok(())
+}
+
+impl File for EventFd {
+    fn read(&self, buf: &mut [u8]) -> Option<usize> {
+        self.try_read(buf).ok()
+    }
+
+    fn write(&self, buf: &[u8]) -> Option<usize> {
+        self.try_write(buf).ok()
+    }
+
+    /// Returns `EINVAL` if the buffer is shorter than 8 bytes, `EAGAIN` if the counter
+    /// is zero in non-blocking mode, or `ERESTART` if interrupted by a signal.
+    fn try_read(&self, buf: &mut [u8]) -> Result<usize, Errno> {
+        if buf.len() < size_of::<u64>() {
+            return Err(Errno::EINVAL);
+        }
+
+        loop {
+            let mut counter = self.counter.lock();
+            if counter.value != 0 {
+                let value = if self.semaphore { 1 } else { counter.value };
+                counter.value -= value;
+                counter.write_wakers.wake_all();
+                buf[..size_of::<u64>()].copy_from_slice(&value.to_ne_bytes());
+                return Ok(size_of::<u64>());
+            }
+            wait_on(&mut counter.read_wakers, self.is_nonblock())?;
+            // Release the lock and wait for a write.
+            drop(counter);
+            unsafe { do_sleep() };
+            // Woken up by a signal, the waker is never called.
+            self.counter.lock().read_wakers.remove(waiter_id());
+        }
+    }
+
+    /// Returns `EINVAL` if the buffer is shorter than 8 bytes or holds `u64::MAX`,
+    /// `EAGAIN` if the counter would overflow in non-blocking mode, or `ERESTART` if
+    /// interrupted by a signal.
+    fn try_write(&self, buf: &[u8]) -> Result<usize, Errno> {
+        if buf.len() < size_of::<u64>() {
+            return Err(Errno::EINVAL);
+        }
+        let value = u64::from_ne_bytes(buf[..size_of::<u64>()].try_into().unwrap());
+        if value == u64::MAX {
+            return Err(Errno::EINVAL);
+        }
+
+        loop {
+            let mut counter = self.counter.lock();
+            if value <= MAX_COUNT - counter.value {
+                counter.value += value;
+                if value != 0 {
+                    counter.read_wakers.wake_all();
+                }
+                return Ok(size_of::<u64>());
+            }
+            wait_on(&mut counter.write_wakers, self.is_nonblock())?;
+            // Release the lock and wait for a read.
+            drop(counter);
+            unsafe { do_sleep() };
+            // Woken up by a signal, the waker is never called.
+            self.counter.lock().write_wakers.remove(waiter_id());
+        }
+    }
+
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    /// Ready if the counter is nonzero.
+    fn read_ready(&self) -> bool {
+        self.counter.lock().value != 0
+    }
+
+    /// Ready if at least 1 can be added to the counter without blocking.
+    fn write_ready(&self) -> bool {
+        self.counter.lock().value < MAX_COUNT
+    }
+
+    fn poll_wait(&self, id: usize, waker: &dyn Fn() -> Waker) -> bool {
+        let mut counter = self.counter.lock();
+        counter.read_wakers.push(id, waker());
+        counter.write_wakers.push(id, waker());
+        true
+    }
+
+    fn poll_cancel(&self, id: usize) {
+        let mut counter = self.counter.lock();
+        counter.read_wakers.remove(id);
+        counter.write_wakers.remove(id);
+    }
+
+    fn get_off(&self) -> usize {
+        0
+    }
+
+    fn open_flags(&self) -> OpenFlags {
+        *self.flags.lock()
+    }
+
+    fn set_open_flags(&self, flags: OpenFlags) {
+        self.flags
+            .lock()
+            .set(OpenFlags::O_NONBLOCK, flags.contains(OpenFlags::O_NONBLOCK));
+    }
+}
@@ -1,20 +1,24 @@
-use alloc::{sync::Arc, vec::Vec};
-use core::cell::SyncUnsafeCell;
+use alloc::{collections::BTreeMap, format, sync::Arc, vec::Vec};
+use core::{cell::SyncUnsafeCell, mem::ManuallyDrop};
 use device_cache::{BlockCache, CacheUnit, LRUBlockCache, BLOCK_SIZE};
 use errno::Errno;
 use fatfs::{
-    DefaultTimeProvider, FsOptions, IoBase, LossyOemCpConverter, Read, Seek, SeekFrom, Write,
+    DefaultTimeProvider, FsOptions, IoBase, IoError as _, LossyOemCpConverter, Read, Seek,
+    SeekFrom, Write,
 };
-use kernel_sync::SpinLock;
 use log::{trace, warn};
 use spin::Lazy;
+use syscall_interface::FIONREAD;
 use time_subsys::TimeSpec;
 use vfs::*;
 
+use super::{makedev, put_nread, VIRTBLK_MAJOR};
 use crate::{
-    config::{CACHE_SIZE, FS_IMG_SIZE},
+    arch::timer::get_time_ms,
+    config::{CACHE_SIZE, DIRTY_EXPIRE_MS, DIRTY_FLUSH_BATCH, FS_IMG_SIZE},
     driver::virtio_block::BLOCK_DEVICE,
     error::KernelError,
+    sync::SpinLock,
 };
 
 type FatTP = DefaultTimeProvider;
@@ -23,11 +27,26 @@ type FatBlock = [u8; BLOCK_SIZE];
 type FatFile = fatfs::File<'static, FatIO, FatTP, FatOCC>;
 type FatDir = fatfs::Dir<'static, FatIO, FatTP, FatOCC>;
 
+/// Block cache shared by [`FatIO`] and the periodic flush.
+static FAT_CACHE: Lazy<SpinLock<LRUBlockCache>> =
+    Lazy::new(|| SpinLock::new(LRUBlockCache::new(CACHE_SIZE)));
+
+/// Writes back blocks which have been dirty for a while, called on timer ticks.
+///
+/// Does nothing if the cache is being used, thus foreground I/O is never blocked.
+pub fn flush_expired() {
+    if let Some(cache) = FAT_CACHE.try_lock() {
+        cache.sync_expired(get_time_ms(), DIRTY_EXPIRE_MS, DIRTY_FLUSH_BATCH);
+    }
+}
+
+/// Counts blocks in the cache not written back yet.
+pub fn dirty_blocks() -> usize {
+    FAT_CACHE.lock().dirty_count()
+}
+
 /// IO wrapper for FAT.
 pub struct FatIO {
-    /// Inner block cache.
-    pub cache: LRUBlockCache,
-
     /// Can move within the range of memory mapped block device for `Seek` operation.
     ///
     /// Attention: `pos` is the offset from the start.
@@ -41,7 +60,6 @@ impl FatIO {
     /// Create a new wrapper.
     pub fn new() -> Self {
         Self {
-            cache: LRUBlockCache::new(CACHE_SIZE),
             pos: 0,
             max_size: FS_IMG_SIZE,
         }
@@ -65,11 +83,16 @@ impl fatfs::IoError for IoError {
     }
 }
 
+/// Converts errors of FAT to [`Errno`].
+///
+/// A full fixed-size root directory cannot grow, which is reported as `WriteZero`.
 fn from(value: fatfs::Error<IoError>) -> Errno {
     match value {
         fatfs::Error::NotFound => Errno::ENOENT,
         fatfs::Error::AlreadyExists => Errno::EEXIST,
         fatfs::Error::InvalidFileNameLength => Errno::ENAMETOOLONG,
+        fatfs::Error::NotEnoughSpace | fatfs::Error::WriteZero => Errno::ENOSPC,
+        fatfs::Error::Io(IoError(err)) => err.into(),
         _ => Errno::EINVAL,
     }
 }
@@ -81,6 +104,9 @@ impl IoBase for FatIO {
 impl Read for FatIO {
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
         let len = core::cmp::min(self.max_size - self.pos, buf.len());
+        if len == 0 {
+            return Ok(0);
+        }
         let start_id = self.pos / BLOCK_SIZE;
         let end_id = (self.pos + len - 1) / BLOCK_SIZE;
         let mut block_ptr = self.pos;
@@ -93,7 +119,8 @@ impl Read for FatIO {
             } else {
                 BLOCK_SIZE - block_off
             };
-            self.cache
+            FAT_CACHE
+                .lock()
                 .get_block(block_id, BLOCK_DEVICE.clone())
                 .lock()
                 .read(0, |block: &FatBlock| {
@@ -112,6 +139,13 @@ impl Read for FatIO {
 
 impl Write for FatIO {
     fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        // No progress can be made at the end of the image.
+        if self.pos >= self.max_size {
+            return Err(IoError::new_write_zero_error());
+        }
         let len = core::cmp::min(self.max_size - self.pos, buf.len());
         let start_id = self.pos / BLOCK_SIZE;
         let end_id = (self.pos + len - 1) / BLOCK_SIZE;
@@ -125,7 +159,8 @@ impl Write for FatIO {
             } else {
                 BLOCK_SIZE - block_off
             };
-            self.cache
+            FAT_CACHE
+                .lock()
                 .get_block(block_id, BLOCK_DEVICE.clone())
                 .lock()
                 .write(0, |block: &mut FatBlock| {
@@ -143,7 +178,7 @@ impl Write for FatIO {
 
     fn flush(&mut self) -> Result<(), Self::Error> {
         // The kernel might crash before sync finished.
-        self.cache.sync_all();
+        FAT_CACHE.lock().sync_all();
         Ok(())
     }
 }
@@ -166,6 +201,9 @@ impl Seek for FatIO {
 
 /// Mutable data owned by [`FSFile`].
 pub struct FSFileInner {
+    /// File access mode and status flags.
+    pub flags: OpenFlags,
+
     /// Last access.
     pub atime: TimeSpec,
 
@@ -176,6 +214,25 @@ pub struct FSFileInner {
     pub ctime: TimeSpec,
 }
 
+/// Identity of a file on the disk, which is kept across renames unlike the path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum FileKey {
+    /// First cluster of the data.
+    Cluster(u32),
+
+    /// Position of the directory entry of an empty file, which owns no cluster.
+    Entry(u64),
+}
+
+impl FileKey {
+    fn of(file: &FatFile) -> Self {
+        match file.first_cluster() {
+            Some(cluster) => Self::Cluster(cluster),
+            None => Self::Entry(file.entry_pos().unwrap_or(0)),
+        }
+    }
+}
+
 /// A wrapper for [`FatFile`] to implement [`File`].
 ///
 /// We use three types of regions to maintain the task metadata:
@@ -183,46 +240,90 @@ pub struct FSFileInner {
 /// - Shared and mutable: uses [`Arc<SpinLock<T>>`].
 /// - Local and mutable: uses [`SpinLock<TaskInner>`] to wrap the data together.
 pub struct FSFile {
-    pub flags: OpenFlags,
-
     /// Real directory path and file name.
     pub path: Path,
 
     /// Local and mutable data.
     pub inner: SpinLock<FSFileInner>,
 
-    /// Real file in fat.
-    pub file: SyncUnsafeCell<FatFile>,
+    /// Real file in fat, which is closed manually in [`Drop`].
+    pub file: SyncUnsafeCell<ManuallyDrop<FatFile>>,
+
+    /// Key in [`OPEN_COUNT`] taken when opened.
+    key: FileKey,
 }
 
 impl FSFile {
     pub fn new(path: Path, file: FatFile, flags: OpenFlags) -> Self {
+        let key = FileKey::of(&file);
+        *OPEN_COUNT.lock().entry(key).or_insert(0) += 1;
         Self {
-            flags,
             path,
+            key,
             inner: SpinLock::new(FSFileInner {
+                flags,
                 atime: TimeSpec::default(),
                 mtime: TimeSpec::default(),
                 ctime: TimeSpec::default(),
             }),
-            file: SyncUnsafeCell::new(file),
+            file: SyncUnsafeCell::new(ManuallyDrop::new(file)),
         }
     }
 
+    /// Number of [`FSFile`]s opened for the same file as this one.
+    pub fn open_count(&self) -> usize {
+        OPEN_COUNT.lock().get(&self.key).copied().unwrap_or(0)
+    }
+
     /// Gets the raw mutable reference to inner file without any borrow check.
     pub fn file(&self) -> &'static mut FatFile {
-        unsafe { &mut *self.file.get() }
+        unsafe { &mut **self.file.get() }
+    }
+
+    /// Appends `len` zeros at the cursor of this file, since FAT files are not sparse.
+    /// [`GLOBAL_FS`] must be locked by the caller.
+    ///
+    /// Returns the error if the zeros cannot be written, e.g. `ENOSPC` if the image is
+    /// full, in which case some of the zeros may have been written.
+    fn zero_fill(&self, len: u64) -> Result<(), Errno> {
+        let zeros = [0u8; BLOCK_SIZE];
+        let mut pos = 0;
+        while pos < len {
+            let chunk = (len - pos).min(BLOCK_SIZE as u64) as usize;
+            match self.file().write(&zeros[..chunk]).map_err(from)? {
+                0 => return Err(Errno::ENOSPC),
+                write_len => pos += write_len as u64,
+            }
+        }
+        Ok(())
     }
 }
 
 impl Drop for FSFile {
     fn drop(&mut self) {
         trace!("Drop FSfile");
-        // Flush the file to disk manually.
         let _guard = GLOBAL_FS.lock();
-        if let Err(err) = self.file().flush() {
-            warn!("flush failed {:?}", err);
+        let last = {
+            let mut open_count = OPEN_COUNT.lock();
+            let count = open_count.get_mut(&self.key).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                open_count.remove(&self.key);
+                true
+            } else {
+                false
+            }
+        };
+        if last {
+            // Flush the file to disk manually.
+            if let Err(err) = self.file().flush() {
+                warn!("flush failed {:?}", err);
+            }
+        } else if let Err(err) = self.file().flush_dir_entry() {
+            warn!("flush dir entry failed {:?}", err);
         }
+        // The inner file is never dropped, which would flush again after the
+        // lock is released.
         drop(_guard);
     }
 }
@@ -260,8 +361,13 @@ impl File for FSFile {
 
     fn write(&self, buf: &[u8]) -> Option<usize> {
         trace!("FSFile::write");
+        self.try_write(buf).ok()
+    }
+
+    /// Returns the error if nothing was written, e.g. `ENOSPC` if the image is full.
+    fn try_write(&self, buf: &[u8]) -> Result<usize, Errno> {
         if !self.writable() {
-            return None;
+            return Err(Errno::EBADF);
         }
         let len = buf.len();
         let mut pos = 0;
@@ -275,25 +381,25 @@ impl File for FSFile {
                         pos += write_len;
                     }
                 }
-                Err(_) => {
+                Err(err) => {
                     if pos == 0 {
-                        return None;
+                        return Err(from(err));
                     } else {
-                        return Some(pos);
+                        return Ok(pos);
                     }
                 }
             }
             drop(_guard);
         }
-        Some(pos)
+        Ok(pos)
     }
 
     fn readable(&self) -> bool {
-        self.flags.readable()
+        self.inner.lock().flags.readable()
     }
 
     fn writable(&self) -> bool {
-        self.flags.writable()
+        self.inner.lock().flags.writable()
     }
 
     #[no_mangle]
@@ -310,35 +416,37 @@ impl File for FSFile {
             SeekWhence::Current => SeekFrom::Current(offset as i64),
             SeekWhence::Set => SeekFrom::Start(offset as u64),
             SeekWhence::End => SeekFrom::End(offset as i64),
+            // FAT files are not sparse.
+            SeekWhence::Data | SeekWhence::Hole => {
+                let _guard = GLOBAL_FS.lock();
+                let curr_pos = self.file().seek(SeekFrom::Current(0)).unwrap();
+                let len = self.file().seek(SeekFrom::End(0)).unwrap();
+                let pos = whence.data_or_hole(offset, len as usize);
+                self.file()
+                    .seek(SeekFrom::Start(pos.map_or(curr_pos, |pos| pos as u64)))
+                    .unwrap();
+                return pos;
+            }
         };
         let _guard = GLOBAL_FS.lock();
         let curr_pos = self.file().seek(SeekFrom::Current(0)).unwrap();
         let result = self
             .file()
             .seek(seek_from)
-            .map(|pos| match seek_from {
-                SeekFrom::Start(offset) => {
-                    let len = self.file().seek(SeekFrom::End(0)).unwrap();
-                    if len < offset && offset <= FS_IMG_SIZE as u64 {
-                        let mut buf: Vec<u8> = Vec::new();
-                        buf.resize(offset as usize - len as usize, 0);
-                        self.file().write(buf.as_slice()).unwrap();
-                    }
-                    self.file().seek(SeekFrom::Start(offset)).unwrap();
-                    Some(offset as usize)
+            .map(|pos| {
+                let now = match seek_from {
+                    SeekFrom::Start(offset) => offset,
+                    SeekFrom::Current(offset) => (curr_pos as i64 + offset) as u64,
+                    SeekFrom::End(_) => return Some(pos as usize),
+                };
+                let len = self.file().seek(SeekFrom::End(0)).unwrap();
+                if len < now && now <= FS_IMG_SIZE as u64 && self.zero_fill(now - len).is_err() {
+                    // The image is full, the cursor stays where it was.
+                    self.file().seek(SeekFrom::Start(curr_pos)).unwrap();
+                    return None;
                 }
-                SeekFrom::Current(offset) => {
-                    let len = self.file().seek(SeekFrom::End(0)).unwrap();
-                    let now = (curr_pos as i64 + offset) as u64;
-                    if len < now && now <= FS_IMG_SIZE as u64 {
-                        let mut buf: Vec<u8> = Vec::new();
-                        buf.resize(now as usize - len as usize, 0);
-                        self.file().write(buf.as_slice()).unwrap();
-                    }
-                    self.file().seek(SeekFrom::Start(now)).unwrap();
-                    Some(now as usize)
-                }
-                SeekFrom::End(_) => Some(pos as usize),
+                self.file().seek(SeekFrom::Start(now)).unwrap();
+                Some(now as usize)
             })
             .unwrap_or_else(|_| {
                 trace!("Seek {:?}", seek_from);
@@ -349,7 +457,13 @@ impl File for FSFile {
     }
 
     fn open_flags(&self) -> OpenFlags {
-        self.flags
+        self.inner.lock().flags
+    }
+
+    fn set_open_flags(&self, flags: OpenFlags) {
+        let changeable = OpenFlags::O_APPEND | OpenFlags::O_NONBLOCK;
+        let mut inner = self.inner.lock();
+        inner.flags = (inner.flags - changeable) | (flags & changeable);
     }
 
     fn get_stat(&self, stat_ptr: *mut Stat) -> bool {
@@ -357,6 +471,8 @@ impl File for FSFile {
         stat.st_mode =
             (StatMode::S_IFREG | StatMode::S_IRWXU | StatMode::S_IRWXG | StatMode::S_IRWXO).bits();
         stat.st_nlink = get_nlink(&self.path) as u32;
+        stat.st_dev = FAT_DEV;
+        stat.st_ino = path_ino(&self.path);
 
         let _guard = GLOBAL_FS.lock();
         stat.st_size = self.get_size().unwrap() as u64;
@@ -366,11 +482,11 @@ impl File for FSFile {
         stat.st_blksize = BLOCK_SIZE as u32;
         stat.st_blocks = (stat.st_size + stat.st_blksize as u64 - 1) / stat.st_blksize as u64;
         stat.st_atime_sec = inner.atime.tv_sec;
-        stat.st_atime_sec = inner.atime.tv_nsec;
+        stat.st_atime_nsec = inner.atime.tv_nsec;
         stat.st_mtime_sec = inner.mtime.tv_sec;
-        stat.st_mtime_sec = inner.mtime.tv_nsec;
+        stat.st_mtime_nsec = inner.mtime.tv_nsec;
         stat.st_ctime_sec = inner.ctime.tv_sec;
-        stat.st_ctime_sec = inner.ctime.tv_nsec;
+        stat.st_ctime_nsec = inner.ctime.tv_nsec;
         unsafe { *stat_ptr = stat };
         true
     }
@@ -418,6 +534,10 @@ impl File for FSFile {
         true
     }
 
+    fn get_nlink(&self) -> Option<usize> {
+        Some(get_nlink(&self.path))
+    }
+
     fn get_path(&self) -> Option<Path> {
         Some(self.path.clone())
     }
@@ -428,17 +548,52 @@ impl File for FSFile {
         self.file().seek(SeekFrom::Start(curr_pos)).unwrap();
         Some(len as usize)
     }
+
+    fn ioctl(&self, cmd: usize, arg: usize) -> Result<usize, Errno> {
+        match cmd {
+            FIONREAD => {
+                let _guard = GLOBAL_FS.lock();
+                let curr_pos = self.file().seek(SeekFrom::Current(0)).unwrap();
+                let len = self.get_size().unwrap() as u64;
+                drop(_guard);
+                put_nread(arg, len.saturating_sub(curr_pos) as usize)
+            }
+            _ => Err(Errno::ENOTTY),
+        }
+    }
+}
+
+/// Device number of the disk filesystem.
+pub const FAT_DEV: u64 = makedev(VIRTBLK_MAJOR, 0);
+
+/// Generates an inode number from the absolute path, since FAT has no inodes.
+///
+/// The same path always maps to the same number.
+pub fn path_ino(path: &Path) -> u64 {
+    // FNV-1a
+    path.as_str()
+        .trim_end_matches('/')
+        .bytes()
+        .fold(0xcbf29ce484222325, |hash, b| {
+            (hash ^ b as u64).wrapping_mul(0x100000001b3)
+        })
 }
 
 /// A wrapper for directory path to implement [`File`].
 pub struct FSDir {
     /// Real directory path.
     pub path: Path,
+
+    /// Index of the next entry to read in `getdents64`.
+    pub pos: SpinLock<usize>,
 }
 
 impl FSDir {
     pub fn new(path: Path) -> Self {
-        Self { path }
+        Self {
+            path,
+            pos: SpinLock::new(0),
+        }
     }
 }
 
@@ -447,6 +602,54 @@ impl File for FSDir {
         Some(self.path.clone())
     }
 
+    fn seek(&self, offset: usize, whence: SeekWhence) -> Option<usize> {
+        let mut pos = self.pos.lock();
+        match whence {
+            SeekWhence::Set => *pos = offset,
+            SeekWhence::Current => *pos += offset,
+            SeekWhence::End | SeekWhence::Data | SeekWhence::Hole => return None,
+        }
+        Some(*pos)
+    }
+
+    fn get_stat(&self, stat_ptr: *mut Stat) -> bool {
+        let mut stat = Stat::default();
+        stat.st_dev = FAT_DEV;
+        stat.st_ino = path_ino(&self.path);
+        stat.st_mode =
+            (StatMode::S_IFDIR | StatMode::S_IRWXU | StatMode::S_IRWXG | StatMode::S_IRWXO).bits();
+        stat.st_nlink = 1;
+        stat.st_blksize = BLOCK_SIZE as u32;
+        unsafe { *stat_ptr = stat };
+        true
+    }
+
+    fn read_dir(&self) -> Option<Vec<DirEntry>> {
+        let _guard = GLOBAL_FS.lock();
+        let root = FAT_FS.root_dir();
+        let dir = if self.path.is_root() {
+            root
+        } else {
+            root.open_dir(self.path.rela()).ok()?
+        };
+        let entries = dir
+            .iter()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| {
+                let name = entry.file_name();
+                let mut path = self.path.clone();
+                path.extend(name.as_str());
+                DirEntry {
+                    ino: path_ino(&path),
+                    d_type: if entry.is_dir() { DT_DIR } else { DT_REG },
+                    name,
+                }
+            })
+            .collect();
+        drop(_guard);
+        Some(entries)
+    }
+
     fn is_dir(&self) -> bool {
         true
     }
@@ -458,13 +661,21 @@ pub struct FileSystem;
 impl Drop for FileSystem {
     fn drop(&mut self) {
         let _guard = GLOBAL_FS.lock();
-        if let Err(err) = FAT_FS.unmount_internal() {
+        if let Err(err) = self.unmount() {
             warn!("unmount failed {:?}", err);
         }
         drop(_guard);
     }
 }
 
+/// Number of [`FSFile`]s opened for each file.
+///
+/// The file is flushed to disk only when the last one is dropped. An empty file gets
+/// another key once a cluster is allocated, so handles opened before and after that
+/// are counted apart, which only costs an extra flush.
+static OPEN_COUNT: Lazy<SpinLock<BTreeMap<FileKey, usize>>> =
+    Lazy::new(|| SpinLock::new(BTreeMap::new()));
+
 /// Global disk filesystem.
 ///
 /// TODO: A big lock on the filesystem!
@@ -472,9 +683,9 @@ pub static GLOBAL_FS: Lazy<SpinLock<FileSystem>> = Lazy::new(|| {
     let fs = FileSystem;
 
     let root = Path::root();
-    fs.mkdir(&root, "dev").unwrap();
-    fs.mkdir(&root, "lib").unwrap();
-    fs.mkdir(&root, "tmp").unwrap();
+    fs.mkdir(&root, "dev", StatMode::S_IRWXUGO).unwrap();
+    fs.mkdir(&root, "lib", StatMode::S_IRWXUGO).unwrap();
+    fs.mkdir(&root, "tmp", StatMode::S_IRWXUGO).unwrap();
 
     SpinLock::new(fs)
 });
@@ -484,19 +695,31 @@ static FAT_FS: Lazy<fatfs::FileSystem<FatIO, FatTP, FatOCC>> = Lazy::new(|| {
     fatfs::FileSystem::new(FatIO::new(), FsOptions::new().update_accessed_date(true)).unwrap()
 });
 
+/// Opens the directory with absolute path `pdir`.
+///
+/// Returns `ENOENT` if a component does not exist, or `ENOTDIR` if a component
+/// is not a directory.
+fn resolve_dir(pdir: &Path) -> Result<FatDir, Errno> {
+    let mut dir = FAT_FS.root_dir();
+    for name in pdir.rela().split('/').filter(|name| !name.is_empty()) {
+        dir = match dir.open_dir(name) {
+            Ok(dir) => dir,
+            Err(fatfs::Error::NotFound) => return Err(Errno::ENOENT),
+            // The entry exists but is a file.
+            Err(fatfs::Error::InvalidInput) => return Err(Errno::ENOTDIR),
+            Err(err) => return Err(from(err)),
+        };
+    }
+    Ok(dir)
+}
+
 impl VFS for FileSystem {
     fn open(&self, pdir: &Path, name: &str, flags: OpenFlags) -> Result<Arc<dyn File>, Errno> {
         let mut ori_path = pdir.clone();
         ori_path.extend(name);
         trace!("FileSystem::open {:x?}", ori_path);
 
-        let root = FAT_FS.root_dir();
-        // Find in the root directory
-        let pdir = if pdir.is_root() {
-            root
-        } else {
-            root.open_dir(pdir.rela()).map_err(|_| Errno::ENOENT)?
-        };
+        let pdir = resolve_dir(pdir)?;
 
         if flags.contains(OpenFlags::O_DIRECTORY | OpenFlags::O_DSYNC) || ori_path.is_dir() {
             match pdir.open_dir(name) {
@@ -519,7 +742,8 @@ impl VFS for FileSystem {
                 Err(fatfs::Error::NotFound) => {
                     // Create if the file not existing
                     if flags.contains(OpenFlags::O_CREAT) {
-                        let file = pdir.create_file(name).unwrap();
+                        check_name(name)?;
+                        let file = pdir.create_file(name).map_err(|err| from(err))?;
                         Ok(Arc::new(FSFile::new(ori_path, file, flags)))
                     } else {
                         Err(Errno::ENOENT)
@@ -530,15 +754,12 @@ impl VFS for FileSystem {
         }
     }
 
-    fn mkdir(&self, pdir: &Path, name: &str) -> Result<(), Errno> {
+    /// FAT stores no permission bits, thus `mode` is ignored.
+    fn mkdir(&self, pdir: &Path, name: &str, mode: StatMode) -> Result<(), Errno> {
+        check_name(name)?;
         let mut ori_path = pdir.clone();
         ori_path.extend(name);
-        let root = FAT_FS.root_dir();
-        let pdir = if pdir.is_root() {
-            root
-        } else {
-            root.open_dir(pdir.rela()).map_err(|_| Errno::ENOENT)?
-        };
+        let pdir = resolve_dir(pdir)?;
         for entry in pdir.iter() {
             if entry.unwrap().file_name() == name {
                 return Err(Errno::EEXIST);
@@ -561,12 +782,80 @@ impl VFS for FileSystem {
     }
 
     fn remove(&self, pdir: &Path, name: &str) -> Result<(), Errno> {
-        let root = FAT_FS.root_dir();
-        let pdir = if pdir.is_root() {
-            root
-        } else {
-            root.open_dir(pdir.rela()).map_err(|_| Errno::ENOENT)?
-        };
+        let pdir = resolve_dir(pdir)?;
         pdir.remove(name).map_err(|err| from(err))
     }
+
+    fn rename(
+        &self,
+        old_pdir: &Path,
+        old_name: &str,
+        new_pdir: &Path,
+        new_name: &str,
+    ) -> Result<(), Errno> {
+        check_name(new_name)?;
+        let old_pdir = resolve_dir(old_pdir)?;
+        let new_pdir = resolve_dir(new_pdir)?;
+        // FAT refuses to overwrite an existing entry.
+        match new_pdir.open_file(new_name) {
+            Ok(_) => new_pdir.remove(new_name).map_err(|err| from(err))?,
+            Err(fatfs::Error::NotFound) => {}
+            // The entry exists but is a directory.
+            Err(fatfs::Error::InvalidInput) => return Err(Errno::EISDIR),
+            Err(err) => return Err(from(err)),
+        }
+        old_pdir
+            .rename(old_name, &new_pdir, new_name)
+            .map_err(|err| from(err))
+    }
+
+    /// FAT cannot swap two directory entries, so they are moved through a temporary
+    /// name in the directory of `old_name`, which is never seen by others since the
+    /// [`GLOBAL_FS`] lock is held. Moves done are undone if a later one fails.
+    fn exchange(
+        &self,
+        old_pdir: &Path,
+        old_name: &str,
+        new_pdir: &Path,
+        new_name: &str,
+    ) -> Result<(), Errno> {
+        let old_pdir = resolve_dir(old_pdir)?;
+        let new_pdir = resolve_dir(new_pdir)?;
+        let names: Vec<_> = old_pdir
+            .iter()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name())
+            .collect();
+        let tmp = (0..)
+            .map(|i| format!(".exchange{}", i))
+            .find(|tmp| !names.contains(tmp))
+            .unwrap();
+
+        old_pdir
+            .rename(old_name, &old_pdir, &tmp)
+            .map_err(|err| from(err))?;
+        if let Err(err) = new_pdir.rename(new_name, &old_pdir, old_name) {
+            let _ = old_pdir.rename(&tmp, &old_pdir, old_name);
+            return Err(from(err));
+        }
+        if let Err(err) = old_pdir.rename(&tmp, &new_pdir, new_name) {
+            let _ = old_pdir.rename(old_name, &new_pdir, new_name);
+            let _ = old_pdir.rename(&tmp, &old_pdir, old_name);
+            return Err(from(err));
+        }
+        Ok(())
+    }
+
+    /// Directory entries of opened files are updated when the last ones are dropped.
+    fn sync(&self) -> Result<(), Errno> {
+        let cache = FAT_CACHE.lock();
+        trace!("FileSystem::sync {} dirty blocks", cache.dirty_count());
+        cache.sync_all();
+        Ok(())
+    }
+
+    fn unmount(&self) -> Result<(), Errno> {
+        FAT_FS.unmount_internal().map_err(from)?;
+        self.sync()
+    }
 }
@@ -1,23 +1,42 @@
 use alloc::{fmt, sync::Arc, vec::Vec};
+use core::{array, ops::Deref};
+use rcu::{RcuCell, RcuReadGuard};
 use vfs::{File, OpenFlags};
 
 use crate::{
     config::DEFAULT_FD_LIMIT,
     error::{KernelError, KernelResult},
+    task::cpu,
 };
 
 use super::{Stderr, Stdin, Stdout};
 
+/// An entry in the file descriptor table.
+#[derive(Clone)]
+pub struct FileDescriptor {
+    /// The open file description shared by duplicated file descriptors.
+    pub file: Arc<dyn File>,
+
+    /// Close-on-exec flag of this file descriptor.
+    pub cloexec: bool,
+}
+
+/// Number of file descriptors in a chunk of [`FDManager`].
+const FD_CHUNK: usize = 64;
+
+/// File descriptors shared by copies of [`FDManager`] until one of them is modified.
+type FdChunk = [Option<FileDescriptor>; FD_CHUNK];
+
 /// File descriptor manager.
+///
+/// Descriptors are kept in chunks shared between copies, so copying the manager
+/// and modifying the copy only duplicates the chunks modified.
 #[derive(Clone)]
 pub struct FDManager {
     /// List of `file descriptor`s:
     /// A process-unique identifier for a file or other input/output resource,
     /// such as a pipe or network socket.
-    list: Vec<Option<Arc<dyn File>>>,
-
-    /// Recycled index in the file descriptor list.
-    recycled: Vec<usize>,
+    list: Vec<Arc<FdChunk>>,
 
     /// Maximum file descriptor limit.
     limit: usize,
@@ -28,7 +47,6 @@ impl FDManager {
     pub fn new() -> Self {
         let mut fd_manager = Self {
             list: Vec::new(),
-            recycled: Vec::new(),
             limit: DEFAULT_FD_LIMIT,
         };
         fd_manager.push(Arc::new(Stdin)).unwrap();
@@ -37,59 +55,146 @@ impl FDManager {
         fd_manager
     }
 
+    /// Returns the number of slots allocated.
+    fn len(&self) -> usize {
+        self.list.len() * FD_CHUNK
+    }
+
+    /// Allocates slots to hold at least `len` file descriptors.
+    fn resize(&mut self, len: usize) {
+        while self.len() < len {
+            self.list.push(Arc::new(array::from_fn(|_| None)));
+        }
+    }
+
+    /// Returns the entry of a file descriptor if it is open.
+    fn slot(&self, fd: usize) -> Option<&FileDescriptor> {
+        self.list
+            .get(fd / FD_CHUNK)
+            .and_then(|chunk| chunk[fd % FD_CHUNK].as_ref())
+    }
+
+    /// Returns the mutable slot of an allocated file descriptor, copying its chunk
+    /// if it is shared.
+    fn slot_mut(&mut self, fd: usize) -> &mut Option<FileDescriptor> {
+        &mut Arc::make_mut(&mut self.list[fd / FD_CHUNK])[fd % FD_CHUNK]
+    }
+
     /// Returns the shared reference of a [`File`].
     pub fn get(&self, fd: usize) -> KernelResult<Arc<dyn File>> {
-        if fd >= self.list.len() || self.list[fd].is_none() {
-            Err(KernelError::FDNotFound)
-        } else {
-            Ok(self.list[fd].as_ref().unwrap().clone())
-        }
+        self.slot(fd)
+            .map(|fd| fd.file.clone())
+            .ok_or(KernelError::FDNotFound)
     }
 
     /// Takes the shared reference of a [`File`], leaving a [`None`] in its place.
     pub fn take(&mut self, fd: usize) -> KernelResult<Arc<dyn File>> {
-        if fd >= self.list.len() || self.list[fd].is_none() {
+        if self.slot(fd).is_none() {
             Err(KernelError::FDNotFound)
         } else {
-            self.recycled.push(fd);
-            Ok(self.list[fd].take().unwrap())
+            Ok(self.slot_mut(fd).take().unwrap().file)
         }
     }
 
     /// Removes the shared reference of a [`File`].
     pub fn remove(&mut self, fd: usize) -> KernelResult {
-        self.recycled.push(fd);
         self.take(fd)?;
         Ok(())
     }
 
-    /// Allocates a new file descriptor.
+    /// Allocates the lowest-numbered file descriptor not currently open.
     pub fn alloc(&mut self) -> KernelResult<usize> {
-        if let Some(fd) = self.recycled.pop() {
+        self.alloc_from(0)
+    }
+
+    /// Allocates the lowest-numbered file descriptor not currently open that is
+    /// greater than or equal to `min`.
+    ///
+    /// File descriptors are always less than the limit, even if there are unused
+    /// ones above it after the limit is lowered.
+    pub fn alloc_from(&mut self, min: usize) -> KernelResult<usize> {
+        let end = self.len().min(self.limit);
+        if let Some(fd) = (min..end).find(|&fd| self.slot(fd).is_none()) {
+            return Ok(fd);
+        }
+        let fd = self.len().max(min);
+        if fd + 1 <= self.limit {
+            self.resize(fd + 1);
             Ok(fd)
         } else {
-            let fd = self.list.len();
-            if fd + 1 <= self.limit {
-                self.list.resize(fd + 1, None);
-                Ok(fd)
-            } else {
-                Err(KernelError::FDOutOfBound)
-            }
+            Err(KernelError::FDOutOfBound)
         }
     }
 
     /// Pushes a shared reference of a [`File`], resizing the list if possible.
     ///
+    /// The close-on-exec flag is set if the file is opened with `O_CLOEXEC`.
+    ///
     /// Returns the file descriptor.
     pub fn push(&mut self, file: Arc<dyn File>) -> KernelResult<usize> {
         let fd = self.alloc()?;
-        self.list[fd] = Some(file);
+        let cloexec = file.open_flags().contains(OpenFlags::O_CLOEXEC);
+        *self.slot_mut(fd) = Some(FileDescriptor { file, cloexec });
         Ok(fd)
     }
 
+    /// Duplicates `oldfd` to the lowest-numbered unused file descriptor, which
+    /// refers to the same open file description.
+    ///
+    /// The close-on-exec flag for the duplicate descriptor is off.
+    pub fn dup(&mut self, oldfd: usize) -> KernelResult<usize> {
+        self.dup_from(oldfd, 0, false)
+    }
+
+    /// Duplicates `oldfd` to the lowest-numbered unused file descriptor greater
+    /// than or equal to `min`, used by `fcntl(F_DUPFD)`.
+    pub fn dup_from(&mut self, oldfd: usize, min: usize, cloexec: bool) -> KernelResult<usize> {
+        let file = self.get(oldfd)?;
+        let fd = self.alloc_from(min)?;
+        *self.slot_mut(fd) = Some(FileDescriptor { file, cloexec });
+        Ok(fd)
+    }
+
+    /// Duplicates `oldfd` to `newfd`, used by `dup2` and `dup3`.
+    ///
+    /// If `newfd` was previously open, it is closed before being reused. If `oldfd`
+    /// equals `newfd`, then nothing is done and `newfd` is returned.
+    pub fn dup_to(&mut self, oldfd: usize, newfd: usize, cloexec: bool) -> KernelResult<usize> {
+        let file = self.get(oldfd)?;
+        if newfd >= self.limit {
+            return Err(KernelError::FDNotFound);
+        }
+        if oldfd == newfd {
+            return Ok(newfd);
+        }
+        self.resize(newfd + 1);
+        // the old file is closed silently
+        *self.slot_mut(newfd) = Some(FileDescriptor { file, cloexec });
+        Ok(newfd)
+    }
+
+    /// Returns the close-on-exec flag of a file descriptor.
+    pub fn get_cloexec(&self, fd: usize) -> KernelResult<bool> {
+        self.slot(fd)
+            .map(|fd| fd.cloexec)
+            .ok_or(KernelError::FDNotFound)
+    }
+
+    /// Sets the close-on-exec flag of a file descriptor.
+    pub fn set_cloexec(&mut self, fd: usize, cloexec: bool) -> KernelResult {
+        if self.slot(fd).is_none() {
+            return Err(KernelError::FDNotFound);
+        }
+        self.slot_mut(fd).as_mut().unwrap().cloexec = cloexec;
+        Ok(())
+    }
+
     /// Returns the number of file descriptors.
     pub fn count(&self) -> usize {
-        self.list.len() - self.recycled.len()
+        self.list
+            .iter()
+            .map(|chunk| chunk.iter().filter(|fd| fd.is_some()).count())
+            .sum()
     }
 
     /// Returns the limit of number.
@@ -109,15 +214,15 @@ impl FDManager {
 
     /// Close files when sys_exec called
     pub fn cloexec(&mut self) {
-        for file in &mut self.list {
-            if file.is_some()
-                && file
-                    .as_ref()
-                    .unwrap()
-                    .open_flags()
-                    .contains(OpenFlags::O_CLOEXEC)
-            {
-                file.take();
+        let is_cloexec = |fd: &Option<FileDescriptor>| fd.as_ref().map_or(false, |fd| fd.cloexec);
+        for chunk in &mut self.list {
+            // Chunks shared with other tables are copied only if modified.
+            if chunk.iter().any(is_cloexec) {
+                for fd in Arc::make_mut(chunk).iter_mut() {
+                    if is_cloexec(fd) {
+                        fd.take();
+                    }
+                }
             }
         }
     }
@@ -128,8 +233,96 @@ impl fmt::Debug for FDManager {
         write!(
             f,
             "File Descriptor Manager: len={:X}, limit={:X}",
-            self.list.len(),
+            self.len(),
             self.limit,
         )
     }
 }
+
+/// File descriptor table shared by tasks created with `CLONE_FILES`.
+///
+/// Lookups read the current [`FDManager`] without locking, while modifications
+/// copy the table and publish the new one. Compound modifications that must be
+/// atomic, e.g. checking the limit before pushing files, are done in [`Self::update`].
+pub struct FdTable(RcuCell<FDManager>);
+
+impl FdTable {
+    pub fn new(files: FDManager) -> Self {
+        Self(RcuCell::new(files))
+    }
+
+    /// Gets the current table, which is not affected by later modifications.
+    ///
+    /// The guard is counted on this hart until dropped, so it must not be held
+    /// across a context switch.
+    pub fn read(&self) -> FdTableGuard<'_> {
+        cpu().rcu_readers += 1;
+        FdTableGuard(self.0.read())
+    }
+
+    /// Modifies a copy of the table with `f` and publishes it.
+    ///
+    /// Writers wait for the readers of old tables if too many are kept, thus the
+    /// caller must not hold a guard returned by [`Self::read`].
+    pub fn update<R>(&self, f: impl FnOnce(&mut FDManager) -> R) -> R {
+        debug_assert_eq!(cpu().rcu_readers, 0, "fd table updated in a read section");
+        self.0.update(f)
+    }
+
+    /// See [`FDManager::get`].
+    pub fn get(&self, fd: usize) -> KernelResult<Arc<dyn File>> {
+        self.read().get(fd)
+    }
+
+    /// See [`FDManager::get_cloexec`].
+    pub fn get_cloexec(&self, fd: usize) -> KernelResult<bool> {
+        self.read().get_cloexec(fd)
+    }
+
+    /// See [`FDManager::get_limit`].
+    pub fn get_limit(&self) -> usize {
+        self.read().get_limit()
+    }
+
+    /// See [`FDManager::push`].
+    pub fn push(&self, file: Arc<dyn File>) -> KernelResult<usize> {
+        self.update(|files| files.push(file))
+    }
+
+    /// See [`FDManager::remove`].
+    pub fn remove(&self, fd: usize) -> KernelResult {
+        self.update(|files| files.remove(fd))
+    }
+
+    /// See [`FDManager::dup`].
+    pub fn dup(&self, oldfd: usize) -> KernelResult<usize> {
+        self.update(|files| files.dup(oldfd))
+    }
+
+    /// See [`FDManager::dup_to`].
+    pub fn dup_to(&self, oldfd: usize, newfd: usize, cloexec: bool) -> KernelResult<usize> {
+        self.update(|files| files.dup_to(oldfd, newfd, cloexec))
+    }
+
+    /// See [`FDManager::set_limit`].
+    pub fn set_limit(&self, limit: usize) {
+        self.update(|files| files.set_limit(limit))
+    }
+}
+
+/// A guard returned by [`FdTable::read`].
+pub struct FdTableGuard<'a>(RcuReadGuard<'a, FDManager>);
+
+impl Deref for FdTableGuard<'_> {
+    type Target = FDManager;
+
+    fn deref(&self) -> &FDManager {
+        &self.0
+    }
+}
+
+impl Drop for FdTableGuard<'_> {
+    fn drop(&mut self) {
+        cpu().rcu_readers -= 1;
+    }
+}
@@ -1,4 +1,4 @@
-use alloc::string::String;
+use vfs::Path;
 
 #[derive(Debug, Clone)]
 pub struct FSInfo {
@@ -7,9 +7,11 @@ pub struct FSInfo {
     /// is `(mode & ~umask)`.
     pub umask: u32,
 
-    /// Current working directory.
-    pub cwd: String,
+    /// Current working directory, which always ends with `'/'`.
+    pub cwd: Path,
 
-    /// Filesystem root directory
-    pub root: String,
+    /// Root directory changed by `chroot`, which always ends with `'/'`.
+    ///
+    /// Absolute paths and `".."` are resolved below this directory.
+    pub root: Path,
 }
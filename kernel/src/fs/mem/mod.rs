@@ -5,8 +5,15 @@ use vfs::{File, SeekWhence};
 use crate::arch::mm::{AllocatedFrame, PAGE_SIZE};
 
 mod null;
+mod random;
+mod tmp;
 mod zero;
 
+pub use null::*;
+pub use random::*;
+pub use tmp::*;
+pub use zero::*;
+
 struct MemFileInner {
     /// Allocated frames to store file data temporarily.
     frames: Vec<AllocatedFrame>,
@@ -47,6 +54,32 @@ impl MemFile {
     pub fn get_limit(&self) -> usize {
         self.max_size
     }
+
+    /// Calls `f` on the data in `[off, off + len)` in place, one page at a time, until
+    /// `f` handles fewer bytes than given.
+    ///
+    /// Returns the total number of bytes handled.
+    pub fn for_each_slice(
+        &self,
+        off: usize,
+        len: usize,
+        mut f: impl FnMut(&mut [u8]) -> usize,
+    ) -> usize {
+        let inner = self.inner.lock();
+        let end = (off + len).min(self.max_size);
+        let mut pos = off;
+        while pos < end {
+            let page_off = pos & (PAGE_SIZE - 1);
+            let page_len = (PAGE_SIZE - page_off).min(end - pos);
+            let frame = inner.frames[pos / PAGE_SIZE].as_slice_mut();
+            let count = f(&mut frame[page_off..page_off + page_len]).min(page_len);
+            pos += count;
+            if count < page_len {
+                break;
+            }
+        }
+        pos - off
+    }
 }
 
 impl File for MemFile {
@@ -102,6 +135,9 @@ impl File for MemFile {
                 }
                 inner.pos = new_pos as usize;
             }
+            SeekWhence::Data | SeekWhence::Hole => {
+                inner.pos = whence.data_or_hole(offset, self.max_size)?;
+            }
         };
         Some(inner.pos)
     }
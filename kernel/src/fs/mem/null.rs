@@ -1,5 +1,9 @@
+use errno::Errno;
+use syscall_interface::FIONREAD;
 use vfs::File;
 
+use crate::fs::put_nread;
+
 /// Data written to `/dev/null` will always be discarded.
 pub struct NullFile;
 
@@ -31,4 +35,11 @@ impl File for NullFile {
     fn seek(&self, _offset: usize, _whence: vfs::SeekWhence) -> Option<usize> {
         Some(0)
     }
+
+    fn ioctl(&self, cmd: usize, arg: usize) -> Result<usize, Errno> {
+        match cmd {
+            FIONREAD => put_nread(arg, 0),
+            _ => Err(Errno::ENOTTY),
+        }
+    }
 }
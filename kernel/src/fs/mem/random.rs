@@ -0,0 +1,54 @@
+use errno::Errno;
+use syscall_interface::FIONREAD;
+use vfs::File;
+
+use crate::{fs::put_nread, random::fill_random};
+
+/// Reading from `/dev/random` or `/dev/urandom` returns pseudo-random bytes.
+/// Data written to it will always be discarded.
+pub struct RandomFile;
+
+impl RandomFile {
+    /// Creates a new random file sharing the generator of the kernel.
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl File for RandomFile {
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    fn read_ready(&self) -> bool {
+        true
+    }
+
+    fn write_ready(&self) -> bool {
+        true
+    }
+
+    fn read(&self, buf: &mut [u8]) -> Option<usize> {
+        fill_random(buf);
+        Some(buf.len())
+    }
+
+    fn write(&self, buf: &[u8]) -> Option<usize> {
+        Some(buf.len())
+    }
+
+    fn seek(&self, _offset: usize, _whence: vfs::SeekWhence) -> Option<usize> {
+        Some(0)
+    }
+
+    fn ioctl(&self, cmd: usize, arg: usize) -> Result<usize, Errno> {
+        match cmd {
+            FIONREAD => put_nread(arg, 0),
+            _ => Err(Errno::ENOTTY),
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::sync::atomic::{AtomicU64, Ordering};
+use errno::Errno;
+use kernel_sync::SpinLock;
+use syscall_interface::FIONREAD;
+use vfs::{get_nlink, File, OpenFlags, Path, SeekWhence, Stat, StatMode};
+
+use crate::{
+    arch::mm::{AllocatedFrame, PAGE_SIZE},
+    fs::{fat::path_ino, put_nread, TMPFS_DEV},
+};
+
+/// Next inode number of files in memory, which have no path to be hashed.
+static NEXT_INO: AtomicU64 = AtomicU64::new(1);
+
+/// Data of a regular file in memory, growing one zeroed frame at a time.
+pub struct TmpData {
+    frames: Vec<AllocatedFrame>,
+
+    /// Size of the file, which is not larger than the allocated frames.
+    size: usize,
+}
+
+impl TmpData {
+    pub fn new() -> Self {
+        Self {
+            frames: Vec::new(),
+            size: 0,
+        }
+    }
+
+    /// Truncates the file to zero length.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.size = 0;
+    }
+
+    /// Reads bytes at `off` until the end of file.
+    fn read_at(&self, off: usize, buf: &mut [u8]) -> usize {
+        let end = (off + buf.len()).min(self.size);
+        let mut pos = off;
+        while pos < end {
+            let page_off = pos & (PAGE_SIZE - 1);
+            let len = (PAGE_SIZE - page_off).min(end - pos);
+            let frame = self.frames[pos / PAGE_SIZE].as_slice();
+            buf[pos - off..pos - off + len].copy_from_slice(&frame[page_off..page_off + len]);
+            pos += len;
+        }
+        pos - off
+    }
+
+    /// Writes bytes at `off`, filling the gap after the end of file with zeros.
+    ///
+    /// Fewer bytes are written if frames run out.
+    fn write_at(&mut self, off: usize, buf: &[u8]) -> usize {
+        let mut end = off + buf.len();
+        while self.frames.len() * PAGE_SIZE < end {
+            match AllocatedFrame::new(true) {
+                Ok(frame) => self.frames.push(frame),
+                Err(_) => {
+                    end = self.frames.len() * PAGE_SIZE;
+                    break;
+                }
+            }
+        }
+        let mut pos = off;
+        while pos < end {
+            let page_off = pos & (PAGE_SIZE - 1);
+            let len = (PAGE_SIZE - page_off).min(end - pos);
+            let frame = self.frames[pos / PAGE_SIZE].as_slice_mut();
+            frame[page_off..page_off + len].copy_from_slice(&buf[pos - off..pos - off + len]);
+            pos += len;
+        }
+        if pos <= off {
+            return 0;
+        }
+        self.size = self.size.max(pos);
+        pos - off
+    }
+}
+
+/// A regular file in memory, either in a [`crate::fs::TmpFs`] or unnamed created by
+/// `open` with `O_TMPFILE`.
+///
+/// An unnamed file has no directory entry, so it is freed when the last file
+/// descriptor referring to it is closed.
+pub struct TmpFile {
+    data: Arc<SpinLock<TmpData>>,
+
+    /// Absolute path, or [`None`] if unnamed.
+    path: Option<Path>,
+
+    flags: SpinLock<OpenFlags>,
+
+    /// Offset of this file.
+    pos: SpinLock<usize>,
+
+    ino: u64,
+}
+
+impl TmpFile {
+    /// Creates an empty unnamed file.
+    pub fn new(flags: OpenFlags) -> Self {
+        Self {
+            data: Arc::new(SpinLock::new(TmpData::new())),
+            path: None,
+            flags: SpinLock::new(flags),
+            pos: SpinLock::new(0),
+            ino: NEXT_INO.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    /// Opens the file `path` sharing `data` with other open file descriptions.
+    pub fn open(path: Path, data: Arc<SpinLock<TmpData>>, flags: OpenFlags) -> Self {
+        Self {
+            data,
+            ino: path_ino(&path),
+            path: Some(path),
+            flags: SpinLock::new(flags),
+            pos: SpinLock::new(0),
+        }
+    }
+}
+
+impl File for TmpFile {
+    fn read(&self, buf: &mut [u8]) -> Option<usize> {
+        if !self.readable() {
+            return None;
+        }
+        let mut pos = self.pos.lock();
+        let read_len = self.data.lock().read_at(*pos, buf);
+        *pos += read_len;
+        Some(read_len)
+    }
+
+    fn write(&self, buf: &[u8]) -> Option<usize> {
+        if !self.writable() {
+            return None;
+        }
+        let mut pos = self.pos.lock();
+        let mut data = self.data.lock();
+        if self.flags.lock().contains(OpenFlags::O_APPEND) {
+            *pos = data.size;
+        }
+        let write_len = data.write_at(*pos, buf);
+        *pos += write_len;
+        Some(write_len)
+    }
+
+    fn readable(&self) -> bool {
+        self.flags.lock().readable()
+    }
+
+    fn writable(&self) -> bool {
+        self.flags.lock().writable()
+    }
+
+    fn clear(&self) {
+        self.data.lock().clear();
+        *self.pos.lock() = 0;
+    }
+
+    unsafe fn read_all(&self) -> Vec<u8> {
+        let data = self.data.lock();
+        let mut buf = Vec::new();
+        buf.resize(data.size, 0);
+        data.read_at(0, &mut buf);
+        buf
+    }
+
+    fn read_at_off(&self, off: usize, buf: &mut [u8]) -> Option<usize> {
+        if !self.readable() {
+            return None;
+        }
+        Some(self.data.lock().read_at(off, buf))
+    }
+
+    fn write_at_off(&self, off: usize, buf: &[u8]) -> Option<usize> {
+        if !self.writable() {
+            return None;
+        }
+        Some(self.data.lock().write_at(off, buf))
+    }
+
+    fn read_ready(&self) -> bool {
+        self.readable() && *self.pos.lock() < self.data.lock().size
+    }
+
+    fn write_ready(&self) -> bool {
+        self.writable()
+    }
+
+    fn seek(&self, offset: usize, whence: SeekWhence) -> Option<usize> {
+        let mut pos = self.pos.lock();
+        let size = self.data.lock().size;
+        match whence {
+            SeekWhence::Set => *pos = offset,
+            SeekWhence::Current => *pos = (*pos as isize + offset as isize) as usize,
+            SeekWhence::End => *pos = (size as isize + offset as isize) as usize,
+            SeekWhence::Data | SeekWhence::Hole => *pos = whence.data_or_hole(offset, size)?,
+        }
+        Some(*pos)
+    }
+
+    fn open_flags(&self) -> OpenFlags {
+        *self.flags.lock()
+    }
+
+    fn set_open_flags(&self, flags: OpenFlags) {
+        let changeable = OpenFlags::O_APPEND | OpenFlags::O_NONBLOCK;
+        let mut inner = self.flags.lock();
+        *inner = (*inner - changeable) | (flags & changeable);
+    }
+
+    fn get_stat(&self, stat_ptr: *mut Stat) -> bool {
+        let mut stat = Stat::default();
+        stat.st_mode = (StatMode::S_IFREG | StatMode::S_IRUSR | StatMode::S_IWUSR).bits();
+        // An unnamed file is not linked into any directory.
+        stat.st_nlink = self.path.as_ref().map_or(0, |path| get_nlink(path) as u32);
+        stat.st_dev = TMPFS_DEV;
+        stat.st_ino = self.ino;
+        stat.st_size = self.data.lock().size as u64;
+        stat.st_blksize = PAGE_SIZE as u32;
+        stat.st_blocks = (stat.st_size + stat.st_blksize as u64 - 1) / stat.st_blksize as u64;
+        unsafe { *stat_ptr = stat };
+        true
+    }
+
+    fn get_size(&self) -> Option<usize> {
+        Some(self.data.lock().size)
+    }
+
+    fn get_off(&self) -> usize {
+        *self.pos.lock()
+    }
+
+    fn is_reg(&self) -> bool {
+        true
+    }
+
+    fn get_path(&self) -> Option<Path> {
+        self.path.clone()
+    }
+
+    fn ioctl(&self, cmd: usize, arg: usize) -> Result<usize, Errno> {
+        match cmd {
+            FIONREAD => {
+                let pos = *self.pos.lock();
+                put_nread(arg, self.data.lock().size.saturating_sub(pos))
+            }
+            _ => Err(Errno::ENOTTY),
+        }
+    }
+}
@@ -1,5 +1,9 @@
+use errno::Errno;
+use syscall_interface::FIONREAD;
 use vfs::File;
 
+use crate::fs::put_nread;
+
 /// Data written to `/dev/zero` will always be discarded.
 /// Buffer will be filled with zero after reading from it.
 pub struct ZeroFile;
@@ -33,4 +37,11 @@ impl File for ZeroFile {
     fn seek(&self, _offset: usize, _whence: vfs::SeekWhence) -> Option<usize> {
         Some(0)
     }
+
+    fn ioctl(&self, cmd: usize, arg: usize) -> Result<usize, Errno> {
+        match cmd {
+            FIONREAD => put_nread(arg, 0),
+            _ => Err(Errno::ENOTTY),
+        }
+    }
 }
@@ -1,21 +1,45 @@
-use alloc::sync::Arc;
+use alloc::{string::String, sync::Arc, vec};
 use errno::Errno;
 use vfs::*;
 
+mod dev;
+mod efs;
+mod epoll;
+mod eventfd;
 mod fat;
 mod fd;
 pub mod mem;
+mod mount;
+mod overlay;
 mod pipe;
+mod proc;
+mod resolve;
 mod stdio;
+mod tmpfs;
 mod info;
 
-pub use fat::GLOBAL_FS;
+pub use dev::*;
+pub use efs::{EasyFile, EasyFs};
+pub use epoll::EpollFile;
+pub use eventfd::EventFd;
+pub use fat::{dirty_blocks, flush_expired, FSFile, GLOBAL_FS};
 pub use fd::*;
+pub use mount::*;
+pub use overlay::OverlayFs;
 pub use pipe::*;
+pub use proc::*;
+pub use resolve::*;
 pub use stdio::*;
+pub use tmpfs::{TmpFs, TMPFS_DEV};
 pub use info::*;
 
-use self::fat::FSDir;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    task::cpu,
+    write_user,
+};
+
+use self::{fat::FSDir, mem::TmpFile};
 
 /// Opens a file object.
 ///
@@ -24,31 +48,132 @@ use self::fat::FSDir;
 ///
 /// See `<https://man7.org/linux/man-pages/man2/open.2.html>`.
 ///
-/// 1. Check if the file exists in the [`MEM_FS`].
-/// 2. Check if the file exists in the [`GLOBAL_FS`].
+/// The filesystem is found by [`lookup`], see [`open_in`].
 pub fn open(path: Path, flags: OpenFlags) -> Result<Arc<dyn File>, Errno> {
-    // Root is always opened.
+    let (fs, path) = lookup(path, ResolveFlags::from_open(flags))?;
+    open_in(&fs, path, flags)
+}
+
+/// Opens a file object in the filesystem `fs` found by [`resolve_path`] or [`lookup`].
+///
+/// 1. An unnamed file is created in memory with `O_TMPFILE`, see [`open_tmpfile`].
+/// 2. Root is always opened.
+/// 3. Check if the file is a device node in `/dev`.
+/// 4. Check if the file is generated from the current task in `/proc/self`.
+/// 5. Check if the file exists in `fs`, which is either a filesystem in the
+///    [`MOUNT_TABLE`] or the [`GLOBAL_FS`].
+///
+/// Returns `ELOOP` if the path is a symbolic link not followed with `O_NOFOLLOW`.
+pub fn open_in(fs: &Arc<dyn VFS>, path: Path, flags: OpenFlags) -> Result<Arc<dyn File>, Errno> {
+    if flags.contains(OpenFlags::O_TMPFILE) {
+        return open_tmpfile(fs, path, flags);
+    }
     if path.is_root() {
         return Ok(Arc::new(FSDir::new(path)));
     }
-    let mut path = path;
-    let name = path.pop().unwrap();
-    let pdir = get_path(&path);
+    if flags.contains(OpenFlags::O_NOFOLLOW) && read_symlink(&path).is_some() {
+        return Err(Errno::ELOOP);
+    }
+    let mut pdir = path;
+    let name = pdir.pop().unwrap();
 
-    // TODO: Try to open file in VFS.
+    // Device nodes are populated from the device registry.
+    if pdir.as_str() == "/dev/" {
+        if let Some(dev) = open_dev(name.as_str()) {
+            return dev;
+        }
+    }
+
+    // Files in `/proc/self` are generated from the current task.
+    if pdir.as_str() == "/proc/self/" {
+        if let Some(file) = open_proc(name.as_str()) {
+            return file;
+        }
+    }
+
+    fs.open(&pdir, name.as_str(), flags)
+}
+
+/// Creates an unnamed regular file in memory with `O_TMPFILE`, which is never visible
+/// in the directory `path`.
+///
+/// Returns `EINVAL` if the file is not opened for writing, or `ENOENT` if the
+/// directory does not exist.
+fn open_tmpfile(fs: &Arc<dyn VFS>, path: Path, flags: OpenFlags) -> Result<Arc<dyn File>, Errno> {
+    if !flags.writable() {
+        return Err(Errno::EINVAL);
+    }
+    let mut dir = path;
+    dir.join("");
+    if !dir.is_root() && !fs.check(&dir) {
+        return Err(Errno::ENOENT);
+    }
+    Ok(Arc::new(TmpFile::new(flags - OpenFlags::O_TMPFILE)))
+}
+
+/// Creates a filesystem node.
+///
+/// - `path`: Absolute path which must start with '/'.
+/// - `mode`: File type and permission bits, see [`mknod_in`].
+/// - `dev`: Device number made by [`makedev`].
+///
+/// The filesystem is found by [`lookup`], see [`mknod_in`].
+pub fn mknod(path: Path, mode: StatMode, dev: u64) -> Result<(), Errno> {
+    let (fs, path) = lookup(path, ResolveFlags::NOFOLLOW)?;
+    mknod_in(&fs, path, mode, dev)
+}
+
+/// Creates a filesystem node in the filesystem `fs` found by [`resolve_path`] or
+/// [`lookup`].
+///
+/// 1. A regular file is created empty if the file type is `S_IFREG` or zero, in any
+///    filesystem including `/tmp`.
+/// 2. A device special file with `S_IFCHR` or `S_IFBLK` lives in the in-memory `/dev`
+///    directory and is resolved through the [`DEVICES`] registry when opened. Other
+///    directories do not support device nodes, thus `EPERM` is returned.
+/// 3. Neither FIFOs nor sockets are supported by any filesystem, thus `EPERM` is
+///    returned. Other file types are invalid.
+///
+/// Permission bits are ignored, since nodes are always created with full permissions.
+pub fn mknod_in(fs: &Arc<dyn VFS>, path: Path, mode: StatMode, dev: u64) -> Result<(), Errno> {
+    let file_type = mode & StatMode::S_IFMT;
+    if path.is_root() || path.is_dir() {
+        return Err(Errno::EEXIST);
+    }
 
-    let disk_file = GLOBAL_FS.lock().open(&pdir, name.as_str(), flags)?;
+    if file_type.is_empty() || file_type == StatMode::S_IFREG {
+        open_in(fs, path, OpenFlags::O_CREAT | OpenFlags::O_EXCL)?;
+        return Ok(());
+    }
+    if file_type == StatMode::S_IFIFO || file_type == StatMode::S_IFSOCK {
+        return Err(Errno::EPERM);
+    }
+    if file_type != StatMode::S_IFCHR && file_type != StatMode::S_IFBLK {
+        return Err(Errno::EINVAL);
+    }
 
-    Ok(disk_file)
+    let mut pdir = path;
+    let name = pdir.pop().unwrap();
+    if pdir.as_str() != "/dev/" {
+        return Err(Errno::EPERM);
+    }
+    DEVICES.lock().mknod(name.as_str(), major(dev), minor(dev))
 }
 
-/// Creates a directory.
+/// Creates a directory accessible to everyone.
 ///
 /// - `path`: Absolute path which must start and end with '/'.
 ///
-/// 1. Check if parent directory is in the [`MEM_FS`].
-/// 2. Try to create the directory in the [`GLOBAL_FS`].
+/// The filesystem is found by [`lookup`], see [`mkdir_in`].
 pub fn mkdir(path: Path) -> Result<(), Errno> {
+    let (fs, path) = lookup(path, ResolveFlags::NOFOLLOW)?;
+    mkdir_in(&fs, path, StatMode::S_IRWXUGO)
+}
+
+/// Creates a directory in the filesystem `fs` found by [`resolve_path`] or [`lookup`].
+///
+/// - `mode`: Permission bits of the new directory, see [`VFS::mkdir`].
+pub fn mkdir_in(fs: &Arc<dyn VFS>, path: Path, mode: StatMode) -> Result<(), Errno> {
     // Root exists.
     if path.is_root() {
         return Err(Errno::EEXIST);
@@ -59,30 +184,323 @@ pub fn mkdir(path: Path) -> Result<(), Errno> {
         return Err(Errno::ENOTDIR);
     }
 
-    let mut path = path;
-    let name = path.pop().unwrap();
-    let pdir = get_path(&path);
-
-    // TODO: Try to create directory in VFS
-
-    GLOBAL_FS.lock().mkdir(&pdir, name.as_str())?;
-
-    Ok(())
+    let mut pdir = path;
+    let name = pdir.pop().unwrap();
+    fs.mkdir(&pdir, name.as_str(), mode)
 }
 
 /// Unlinks a path.
+///
+/// The filesystem is found by [`lookup`], see [`unlink_in`].
 pub fn unlink(path: Path) -> Result<(), Errno> {
+    let (fs, path) = lookup(path, ResolveFlags::NOFOLLOW)?;
+    unlink_in(&fs, path)
+}
+
+/// Unlinks a path in the filesystem `fs` found by [`resolve_path`] or [`lookup`].
+///
+/// The file is removed only if the last link to it is removed. Symbolic links are
+/// removed without the files they refer to.
+pub fn unlink_in(fs: &Arc<dyn VFS>, path: Path) -> Result<(), Errno> {
     // Root cannot be unlinked.
     if path.is_root() {
         return Err(Errno::EINVAL);
     }
 
+    if remove_symlink(&path) {
+        return Ok(());
+    }
+
+    // The file is kept if other links to it remain.
     if let Some(mut path) = remove_link(&path) {
         let name = path.pop().unwrap();
-        GLOBAL_FS.lock().remove(&path, name.as_str())?;
-    } else {
+        fs.remove(&path, name.as_str())?;
+    }
+
+    Ok(())
+}
+
+/// Creates a hard link.
+///
+/// The filesystems are found by [`lookup`], see [`link_in`].
+pub fn link(old: Path, new: Path) -> Result<(), Errno> {
+    let (old_fs, old) = lookup(old, ResolveFlags::NOFOLLOW)?;
+    let (new_fs, new) = lookup(new, ResolveFlags::NOFOLLOW)?;
+    link_in(&old_fs, old, &new_fs, new)
+}
+
+/// Creates a hard link `new` to the file `old` in the filesystems found by
+/// [`resolve_path`] or [`lookup`].
+///
+/// A symbolic link `old` is linked itself, which is copied since it only lives in the
+/// symbolic link table.
+///
+/// Returns `EXDEV` if they are in different filesystems, or `EPERM` if `old` is a
+/// directory.
+pub fn link_in(
+    old_fs: &Arc<dyn VFS>,
+    old: Path,
+    new_fs: &Arc<dyn VFS>,
+    new: Path,
+) -> Result<(), Errno> {
+    // Compares the data pointers only, since vtables may be duplicated.
+    if Arc::as_ptr(old_fs) as *const u8 != Arc::as_ptr(new_fs) as *const u8 {
+        return Err(Errno::EXDEV);
+    }
+    if let Some(target) = read_symlink(&old) {
+        if new_fs.check(&new) || !add_symlink(&new, &target) {
+            return Err(Errno::EEXIST);
+        }
+        return Ok(());
+    }
+    let mut dir = old.clone();
+    dir.join("");
+    if old.is_root() || old.is_dir() || old_fs.check(&dir) {
+        return Err(Errno::EPERM);
+    }
+    old_fs.link(&get_path(&old), &new)
+}
+
+/// Renames a file.
+///
+/// The filesystems are found by [`lookup`], see [`rename_in`].
+pub fn rename(old: Path, new: Path, flags: RenameFlags) -> Result<(), Errno> {
+    let (old_fs, old) = lookup(old, ResolveFlags::NOFOLLOW)?;
+    let (new_fs, new) = lookup(new, ResolveFlags::NOFOLLOW)?;
+    rename_in(&old_fs, old, &new_fs, new, flags)
+}
+
+/// Renames the file `old` to `new` in the filesystems found by [`resolve_path`] or
+/// [`lookup`], replacing `new` if it exists.
+///
+/// With `RENAME_EXCHANGE`, both files must exist and are swapped atomically by
+/// [`VFS::exchange`], see [`exchange_in`].
+///
+/// Returns `EINVAL` if `new` is below the directory `old` or the reverse with
+/// `RENAME_EXCHANGE`, `EXDEV` if they are in different filesystems, `EBUSY` if either
+/// is the root, `EEXIST` if `new` exists with `RENAME_NOREPLACE`, or `EISDIR` and
+/// `ENOTDIR` if a file replaces a directory and the reverse.
+pub fn rename_in(
+    old_fs: &Arc<dyn VFS>,
+    old: Path,
+    new_fs: &Arc<dyn VFS>,
+    new: Path,
+    flags: RenameFlags,
+) -> Result<(), Errno> {
+    let exchange = flags.contains(RenameFlags::RENAME_EXCHANGE);
+    if exchange && flags.contains(RenameFlags::RENAME_NOREPLACE) {
+        return Err(Errno::EINVAL);
+    }
+    // Compares the data pointers only, since vtables may be duplicated.
+    if Arc::as_ptr(old_fs) as *const u8 != Arc::as_ptr(new_fs) as *const u8 {
+        return Err(Errno::EXDEV);
+    }
+    if old.is_root() || new.is_root() {
+        return Err(Errno::EBUSY);
+    }
+    let fs = old_fs;
+    let new_exists = exists_in(fs, &new);
+    if !exists_in(fs, &old) || (exchange && !new_exists) {
         return Err(Errno::ENOENT);
     }
+    if old == new {
+        return Ok(());
+    }
+    // A directory cannot be moved into its own subtree.
+    if is_below(&new, &old) || (exchange && is_below(&old, &new)) {
+        return Err(Errno::EINVAL);
+    }
+    if exchange {
+        return exchange_in(fs, &old, &new);
+    }
+    if new_exists {
+        if flags.contains(RenameFlags::RENAME_NOREPLACE) {
+            return Err(Errno::EEXIST);
+        }
+        match (is_dir_in(fs, &old), is_dir_in(fs, &new)) {
+            (false, true) => return Err(Errno::EISDIR),
+            (true, false) => return Err(Errno::ENOTDIR),
+            _ => {}
+        }
+    }
+    move_in(fs, &old, &new)
+}
 
+/// Checks if `path` is below the directory `dir`.
+fn is_below(path: &Path, dir: &Path) -> bool {
+    let mut subtree = String::from(dir.as_str().trim_end_matches('/'));
+    subtree.push('/');
+    path.as_str().starts_with(subtree.as_str())
+}
+
+/// Checks if a file, a directory or a symbolic link exists.
+fn exists_in(fs: &Arc<dyn VFS>, path: &Path) -> bool {
+    if read_symlink(path).is_some() || fs.check(path) {
+        return true;
+    }
+    // The path may refer to a directory without a trailing '/'.
+    let mut dir = path.clone();
+    dir.join("");
+    fs.check(&dir)
+}
+
+/// Renames a file or a symbolic link, which replaces `new`.
+fn move_in(fs: &Arc<dyn VFS>, old: &Path, new: &Path) -> Result<(), Errno> {
+    let mut old_pdir = old.clone();
+    let old_name = old_pdir.pop().unwrap();
+    let mut new_pdir = new.clone();
+    let new_name = new_pdir.pop().unwrap();
+    let new_name = new_name.trim_end_matches('/');
+    match read_symlink(old) {
+        // Symbolic links only live in the symbolic link table.
+        Some(target) => {
+            if fs.check(new) {
+                fs.remove(&new_pdir, new_name)?;
+            }
+            remove_symlink(new);
+            remove_symlink(old);
+            add_symlink(new, &target);
+        }
+        None => {
+            fs.rename(
+                &old_pdir,
+                old_name.trim_end_matches('/'),
+                &new_pdir,
+                new_name,
+            )?;
+            remove_symlink(new);
+        }
+    }
     Ok(())
 }
+
+/// Checks if the path refers to a directory, with or without a trailing '/'.
+fn is_dir_in(fs: &Arc<dyn VFS>, path: &Path) -> bool {
+    let mut dir = path.clone();
+    dir.join("");
+    read_symlink(path).is_none() && fs.check(&dir)
+}
+
+/// Exchanges two existing files or symbolic links.
+///
+/// Symbolic links only live in the symbolic link table, thus a link is swapped with
+/// a file by moving the file and then the link.
+fn exchange_in(fs: &Arc<dyn VFS>, old: &Path, new: &Path) -> Result<(), Errno> {
+    match (read_symlink(old), read_symlink(new)) {
+        (Some(old_target), Some(new_target)) => {
+            remove_symlink(old);
+            remove_symlink(new);
+            add_symlink(old, &new_target);
+            add_symlink(new, &old_target);
+        }
+        (Some(target), None) => {
+            remove_symlink(old);
+            move_in(fs, new, old).map_err(|err| {
+                add_symlink(old, &target);
+                err
+            })?;
+            add_symlink(new, &target);
+        }
+        (None, Some(target)) => {
+            remove_symlink(new);
+            move_in(fs, old, new).map_err(|err| {
+                add_symlink(new, &target);
+                err
+            })?;
+            add_symlink(old, &target);
+        }
+        (None, None) => {
+            let mut old_pdir = old.clone();
+            let old_name = old_pdir.pop().unwrap();
+            let mut new_pdir = new.clone();
+            let new_name = new_pdir.pop().unwrap();
+            fs.exchange(
+                &old_pdir,
+                old_name.trim_end_matches('/'),
+                &new_pdir,
+                new_name.trim_end_matches('/'),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks whether the file `path` in the filesystem `fs` found by [`resolve_path`] or
+/// [`lookup`] can be accessed with `mode`.
+///
+/// The current task is treated as the owner of all files for now. Files without
+/// attributes, e.g. device nodes, are always accessible.
+///
+/// Returns `ENOENT` if the file does not exist, or `EACCES` if the access is denied.
+pub fn access_in(fs: &Arc<dyn VFS>, path: Path, mode: AccessMode) -> Result<(), Errno> {
+    // A symbolic link is only found here if not followed, whose permission is 0777.
+    if read_symlink(&path).is_some() {
+        return Ok(());
+    }
+    let file = open_in(fs, path.clone(), OpenFlags::O_RDONLY).or_else(|err| {
+        // The path may refer to a directory without a trailing '/'.
+        let mut dir = path;
+        dir.join("");
+        open_in(fs, dir, OpenFlags::O_RDONLY).map_err(|_| err)
+    })?;
+    let mut stat = Stat::default();
+    if mode.is_empty() || !file.get_stat(&mut stat as *mut Stat) {
+        return Ok(());
+    }
+    if StatMode::from_bits_truncate(stat.st_mode).owner_allows(mode) {
+        Ok(())
+    } else {
+        Err(Errno::EACCES)
+    }
+}
+
+/// Copies at most `count` bytes from `in_file` starting at `*offset` to `out_file`
+/// in the kernel, until `count` bytes are copied or the end of `in_file`.
+///
+/// `*offset` is advanced by the number of bytes copied, while the file offset of
+/// `in_file` is not changed.
+///
+/// Returns `EINVAL` if `in_file` cannot be read at an offset, e.g. a pipe, or the
+/// error of [`File::try_write`] if nothing is written to `out_file`, e.g. `EPIPE` if
+/// `out_file` is a pipe whose read end is closed.
+pub fn sendfile(
+    out_file: &Arc<dyn File>,
+    in_file: &Arc<dyn File>,
+    offset: &mut usize,
+    count: usize,
+) -> Result<usize, Errno> {
+    let mut buf = vec![0u8; count.min(PAGE_SIZE)];
+    let mut copied = 0;
+    while copied < count {
+        let len = buf.len().min(count - copied);
+        let read_len = in_file
+            .read_at_off(*offset, &mut buf[..len])
+            .ok_or(Errno::EINVAL)?;
+        if read_len == 0 {
+            break;
+        }
+        let write_len = match out_file.try_write(&buf[..read_len]) {
+            Ok(write_len) => write_len,
+            Err(_) if copied > 0 => break,
+            Err(errno) => return Err(errno),
+        };
+        *offset += write_len;
+        copied += write_len;
+        // Short write, e.g. a full pipe in non-blocking mode.
+        if write_len < read_len {
+            break;
+        }
+    }
+    Ok(copied)
+}
+
+/// Writes the number of bytes available to read as an `int` to the user address
+/// `arg`, used by `ioctl(FIONREAD)`.
+///
+/// Regular files count the bytes left until the end of file.
+pub fn put_nread(arg: usize, nread: usize) -> Result<usize, Errno> {
+    let nread = nread.min(i32::MAX as usize) as i32;
+    let curr = cpu().curr.as_ref().unwrap();
+    write_user!(curr.mm(), VirtAddr::from(arg), nread, i32)?;
+    Ok(0)
+}
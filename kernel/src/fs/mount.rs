@@ -0,0 +1,102 @@
+use alloc::{sync::Arc, vec::Vec};
+use errno::Errno;
+use kernel_sync::SpinLock;
+use spin::Lazy;
+use vfs::{Path, VFS};
+
+use super::TmpFs;
+
+/// A filesystem mounted on a directory.
+struct Mount {
+    /// Mount point, which always ends with `'/'`.
+    path: Path,
+
+    /// Mounted filesystem.
+    fs: Arc<dyn VFS>,
+}
+
+/// Filesystems mounted on top of the [`super::GLOBAL_FS`].
+///
+/// Paths are passed to the mounted filesystem as they are, thus each filesystem
+/// must strip its own mount point.
+pub struct MountTable {
+    mounts: Vec<Mount>,
+}
+
+impl MountTable {
+    pub fn new() -> Self {
+        Self { mounts: Vec::new() }
+    }
+
+    /// Mounts a filesystem on the directory `path`.
+    ///
+    /// Returns `EBUSY` if a filesystem has been mounted on this directory.
+    pub fn mount(&mut self, path: Path, fs: Arc<dyn VFS>) -> Result<(), Errno> {
+        if !path.is_dir() {
+            return Err(Errno::ENOTDIR);
+        }
+        if path.is_root() || self.mounts.iter().any(|mount| mount.path == path) {
+            return Err(Errno::EBUSY);
+        }
+        self.mounts.push(Mount { path, fs });
+        Ok(())
+    }
+
+    /// Unmounts the filesystem on the directory `path`.
+    ///
+    /// Returns `EINVAL` if `path` is not a mount point.
+    pub fn umount(&mut self, path: &Path) -> Result<Arc<dyn VFS>, Errno> {
+        let index = self
+            .mounts
+            .iter()
+            .position(|mount| &mount.path == path)
+            .ok_or(Errno::EINVAL)?;
+        Ok(self.mounts.remove(index).fs)
+    }
+
+    /// Gets all mounted filesystems.
+    pub fn filesystems(&self) -> Vec<Arc<dyn VFS>> {
+        self.mounts.iter().map(|mount| mount.fs.clone()).collect()
+    }
+
+    /// Finds the filesystem with the longest mount point containing `path`.
+    pub fn lookup(&self, path: &Path) -> Option<Arc<dyn VFS>> {
+        self.mounts
+            .iter()
+            .filter(|mount| path.as_str().starts_with(mount.path.as_str()))
+            .max_by_key(|mount| mount.path.as_str().len())
+            .map(|mount| mount.fs.clone())
+    }
+}
+
+/// Global mount table, where a [`TmpFs`] is mounted on `/tmp` at first.
+///
+/// The directory `/tmp` itself is still found in the parent filesystem.
+pub static MOUNT_TABLE: Lazy<SpinLock<MountTable>> = Lazy::new(|| {
+    let mut table = MountTable::new();
+    let tmp = Path::new("/tmp/");
+    table
+        .mount(tmp.clone(), Arc::new(TmpFs::new(&tmp)))
+        .unwrap();
+    SpinLock::new(table)
+});
+
+/// Mounts a filesystem on the directory `path`.
+pub fn mount(path: Path, fs: Arc<dyn VFS>) -> Result<(), Errno> {
+    MOUNT_TABLE.lock().mount(path, fs)
+}
+
+/// Unmounts the filesystem on the directory `path`, which is written back.
+pub fn umount(path: &Path) -> Result<(), Errno> {
+    let fs = MOUNT_TABLE.lock().umount(path)?;
+    fs.unmount()
+}
+
+/// Writes back all mounted filesystems and the [`super::GLOBAL_FS`].
+pub fn sync() -> Result<(), Errno> {
+    let mounted = MOUNT_TABLE.lock().filesystems();
+    for fs in mounted {
+        fs.sync()?;
+    }
+    super::global_fs().sync()
+}
@@ -0,0 +1,182 @@
+use alloc::{collections::BTreeSet, sync::Arc, vec};
+use errno::Errno;
+use kernel_sync::SpinLock;
+use vfs::{File, OpenFlags, Path, StatMode, VFS};
+
+use crate::arch::mm::PAGE_SIZE;
+
+/// A union of a writable upper filesystem over a read-only lower filesystem.
+///
+/// Files are read from the upper filesystem, falling through to the lower one if
+/// absent. A file in the lower filesystem is copied up before it is modified, and a
+/// deleted one is hidden by a whiteout, thus the lower filesystem is never written.
+///
+/// Paths are passed to both filesystems as they are, e.g. the lower filesystem may
+/// be the [`super::GLOBAL_FS`] with the directory under the mount point.
+///
+/// TODO: Entries of a directory are read from a single filesystem without merging.
+pub struct OverlayFs {
+    /// Mount point, which always ends with `'/'`.
+    mount_point: Path,
+
+    /// Writable filesystem receiving all modifications.
+    upper: Arc<dyn VFS>,
+
+    /// Read-only filesystem.
+    lower: Arc<dyn VFS>,
+
+    /// Paths removed from the lower filesystem.
+    whiteouts: SpinLock<BTreeSet<Path>>,
+}
+
+impl OverlayFs {
+    pub fn new(mount_point: Path, upper: Arc<dyn VFS>, lower: Arc<dyn VFS>) -> Self {
+        Self {
+            mount_point,
+            upper,
+            lower,
+            whiteouts: SpinLock::new(BTreeSet::new()),
+        }
+    }
+
+    /// Checks if a path is visible in the lower filesystem.
+    fn in_lower(&self, path: &Path) -> bool {
+        !self.whiteouts.lock().contains(path) && self.lower.check(path)
+    }
+
+    /// Creates the missing directories of `pdir` in the upper filesystem.
+    fn copy_up_dir(&self, pdir: &Path) -> Result<(), Errno> {
+        let rela = pdir
+            .as_str()
+            .strip_prefix(self.mount_point.as_str())
+            .ok_or(Errno::ENOENT)?;
+        let mut dir = self.mount_point.clone();
+        for name in rela.split('/').filter(|name| !name.is_empty()) {
+            let mut next = dir.clone();
+            next.join(name);
+            if !self.upper.check(&next) {
+                self.upper.mkdir(&dir, name, StatMode::S_IRWXUGO)?;
+            }
+            dir = next;
+        }
+        Ok(())
+    }
+
+    /// Copies a file in the lower filesystem to the upper filesystem.
+    fn copy_up(&self, pdir: &Path, name: &str) -> Result<(), Errno> {
+        self.copy_up_dir(pdir)?;
+        let src = self.lower.open(pdir, name, OpenFlags::O_RDONLY)?;
+        let dst = self.upper.open(
+            pdir,
+            name,
+            OpenFlags::O_CREAT | OpenFlags::O_TRUNC | OpenFlags::O_WRONLY,
+        )?;
+        let mut buf = vec![0u8; PAGE_SIZE];
+        loop {
+            let len = src.read(&mut buf).ok_or(Errno::EIO)?;
+            if len == 0 {
+                break;
+            }
+            if dst.write(&buf[..len]) != Some(len) {
+                return Err(Errno::ENOSPC);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl VFS for OverlayFs {
+    fn open(&self, pdir: &Path, name: &str, flags: OpenFlags) -> Result<Arc<dyn File>, Errno> {
+        let mut path = pdir.clone();
+        path.extend(name);
+        if self.upper.check(&path) {
+            return self.upper.open(pdir, name, flags);
+        }
+        if self.in_lower(&path) {
+            if path.is_dir() || !(flags.writable() || flags.contains(OpenFlags::O_TRUNC)) {
+                return self.lower.open(pdir, name, flags);
+            }
+            self.copy_up(pdir, name)?;
+            return self.upper.open(pdir, name, flags);
+        }
+        if !flags.contains(OpenFlags::O_CREAT) {
+            return Err(Errno::ENOENT);
+        }
+        self.copy_up_dir(pdir)?;
+        let file = self.upper.open(pdir, name, flags)?;
+        self.whiteouts.lock().remove(&path);
+        Ok(file)
+    }
+
+    fn mkdir(&self, pdir: &Path, name: &str, mode: StatMode) -> Result<(), Errno> {
+        let mut path = pdir.clone();
+        path.join(name);
+        if self.in_lower(&path) {
+            return Err(Errno::EEXIST);
+        }
+        self.copy_up_dir(pdir)?;
+        self.upper.mkdir(pdir, name, mode)?;
+        self.whiteouts.lock().remove(&path);
+        Ok(())
+    }
+
+    fn check(&self, path: &Path) -> bool {
+        self.upper.check(path) || self.in_lower(path)
+    }
+
+    fn remove(&self, pdir: &Path, name: &str) -> Result<(), Errno> {
+        let mut path = pdir.clone();
+        path.extend(name);
+        let in_upper = self.upper.check(&path);
+        let in_lower = self.in_lower(&path);
+        if !in_upper && !in_lower {
+            return Err(Errno::ENOENT);
+        }
+        if in_upper {
+            self.upper.remove(pdir, name)?;
+        }
+        if in_lower {
+            self.whiteouts.lock().insert(path);
+        }
+        Ok(())
+    }
+
+    fn rename(
+        &self,
+        old_pdir: &Path,
+        old_name: &str,
+        new_pdir: &Path,
+        new_name: &str,
+    ) -> Result<(), Errno> {
+        let mut old_path = old_pdir.clone();
+        old_path.extend(old_name);
+        let mut new_path = new_pdir.clone();
+        new_path.extend(new_name);
+        if !self.upper.check(&old_path) {
+            if !self.in_lower(&old_path) {
+                return Err(Errno::ENOENT);
+            }
+            // Directories are not copied up recursively.
+            if old_path.is_dir() {
+                return Err(Errno::EXDEV);
+            }
+            self.copy_up(old_pdir, old_name)?;
+        }
+        self.copy_up_dir(new_pdir)?;
+        self.upper.rename(old_pdir, old_name, new_pdir, new_name)?;
+        let mut whiteouts = self.whiteouts.lock();
+        whiteouts.remove(&new_path);
+        if self.lower.check(&old_path) {
+            whiteouts.insert(old_path);
+        }
+        Ok(())
+    }
+
+    fn sync(&self) -> Result<(), Errno> {
+        self.upper.sync()
+    }
+
+    fn unmount(&self) -> Result<(), Errno> {
+        self.upper.unmount()
+    }
+}
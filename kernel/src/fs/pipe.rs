@@ -1,13 +1,25 @@
-use alloc::sync::Arc;
+use alloc::{boxed::Box, sync::Arc, vec};
+use errno::Errno;
 use kernel_sync::SpinLock;
-use vfs::{ring_buf::RingBuffer, File};
+use syscall_interface::FIONREAD;
+use vfs::{
+    ring_buf::{RingBuffer, Waker},
+    File, OpenFlags,
+};
 
-use crate::{config::MAX_PIPE_BUF, fs::mem::MemFile, task::do_yield};
+use crate::{
+    config::MAX_PIPE_BUF,
+    fs::{mem::MemFile, put_nread},
+    task::{cpu, do_sleep, do_wake, signal_pending, TaskState},
+};
 
 pub struct Pipe {
     /// If this is a read end of pipe.
     is_read: bool,
 
+    /// Status flags of the open file description.
+    flags: SpinLock<OpenFlags>,
+
     /// Inner data in a ring buffer.
     buf: Arc<SpinLock<RingBuffer<MemFile>>>,
 }
@@ -22,66 +34,316 @@ impl Pipe {
         (
             Self {
                 is_read: true,
+                flags: SpinLock::new(OpenFlags::O_RDONLY),
                 buf: buf.clone(),
             },
             Self {
                 is_read: false,
+                flags: SpinLock::new(OpenFlags::O_WRONLY),
                 buf,
             },
         )
     }
-}
 
-impl File for Pipe {
-    fn read(&self, buf: &mut [u8]) -> Option<usize> {
+    /// Returns true if the other end of this pipe has been closed.
+    pub fn is_broken(&self) -> bool {
+        self.buf.lock().is_closed()
+    }
+
+    /// Returns true if the pipe is in non-blocking mode.
+    fn is_nonblock(&self) -> bool {
+        self.flags.lock().contains(OpenFlags::O_NONBLOCK)
+    }
+
+    /// Moves at most `len` bytes out of this read end, handing the data in the pipe
+    /// buffer to `write` in place. `write` returns the number of bytes it consumed.
+    ///
+    /// Blocks until any data arrives unless `nonblock` is set or the pipe is in
+    /// non-blocking mode, in which case `EAGAIN` is returned. Returns 0 if the write
+    /// end is closed and no data is left, or `ERESTART` if interrupted by a signal.
+    pub fn splice_to(
+        &self,
+        len: usize,
+        nonblock: bool,
+        mut write: impl FnMut(&[u8]) -> Result<usize, Errno>,
+    ) -> Result<usize, Errno> {
         if !self.is_read {
-            return None;
+            return Err(Errno::EBADF);
+        }
+
+        loop {
+            let mut ring_buf = self.buf.lock();
+            if ring_buf.is_empty() && len != 0 {
+                // Write end closed.
+                if ring_buf.is_closed() {
+                    return Ok(0);
+                }
+                if nonblock || self.is_nonblock() {
+                    return Err(Errno::EAGAIN);
+                }
+                ring_buf.wait_read(waiter_id(), sleep_waker());
+                cancel_on_signal(&mut ring_buf)?;
+                // Release the lock and wait for data.
+                drop(ring_buf);
+                sleep_on(&self.buf);
+                continue;
+            }
+
+            let mut error = None;
+            let moved = ring_buf.splice_out(len, |data, off, len| {
+                data.for_each_slice(off, len, |slice| {
+                    write(slice).unwrap_or_else(|errno| {
+                        error = Some(errno);
+                        0
+                    })
+                })
+            });
+            return match error {
+                Some(errno) if moved == 0 => Err(errno),
+                _ => Ok(moved),
+            };
+        }
+    }
+
+    /// Copies at most `len` bytes from this read end to the write end `out` of another
+    /// pipe without consuming them, so that the data is still available for later reads.
+    ///
+    /// Blocks until any data arrives in this pipe and any space frees in `out`, unless
+    /// `nonblock` is set or either pipe is in non-blocking mode, in which case `EAGAIN`
+    /// is returned. Returns `EINVAL` if both ends refer to the same pipe, `EPIPE` if
+    /// the read end of `out` is closed, or `ERESTART` if interrupted by a signal.
+    pub fn tee_to(&self, out: &Pipe, len: usize, nonblock: bool) -> Result<usize, Errno> {
+        if !self.is_read || out.is_read {
+            return Err(Errno::EBADF);
+        }
+        if Arc::ptr_eq(&self.buf, &out.buf) {
+            return Err(Errno::EINVAL);
+        }
+        if len == 0 {
+            return Ok(0);
         }
 
-        let mut read_len = 0;
         loop {
-            let buf_rc = Arc::strong_count(&self.buf);
             let mut ring_buf = self.buf.lock();
             if ring_buf.is_empty() {
                 // Write end closed.
-                if buf_rc == 1 {
-                    return Some(0);
+                if ring_buf.is_closed() {
+                    return Ok(0);
+                }
+                if nonblock || self.is_nonblock() {
+                    return Err(Errno::EAGAIN);
                 }
-                // Release the lock.
+                ring_buf.wait_read(waiter_id(), sleep_waker());
+                cancel_on_signal(&mut ring_buf)?;
+                // Release the lock and wait for data.
                 drop(ring_buf);
-                unsafe { do_yield() };
+                sleep_on(&self.buf);
                 continue;
             }
-            read_len += ring_buf.read(&mut buf[read_len..]);
-            break;
+            let mut buf = vec![0u8; len.min(ring_buf.len())];
+            ring_buf.peek(&mut buf);
+            // Never hold locks of both pipes, the data is still in this pipe anyway.
+            drop(ring_buf);
+
+            let mut out_buf = out.buf.lock();
+            if out_buf.is_closed() {
+                return Err(Errno::EPIPE);
+            }
+            if out_buf.is_full() {
+                if nonblock || out.is_nonblock() {
+                    return Err(Errno::EAGAIN);
+                }
+                out_buf.wait_write(waiter_id(), sleep_waker());
+                cancel_on_signal(&mut out_buf)?;
+                // Release the lock and wait for space.
+                drop(out_buf);
+                sleep_on(&out.buf);
+                continue;
+            }
+            return Ok(out_buf.write(&buf));
         }
-        Some(read_len)
     }
 
-    fn write(&self, buf: &[u8]) -> Option<usize> {
+    /// Moves data in `segs` into this write end in order, copying each segment into the
+    /// pipe buffer directly.
+    ///
+    /// Blocks until any data is moved unless `nonblock` is set or the pipe is in
+    /// non-blocking mode, and never blocks once any data has been moved.
+    pub fn splice_segments(&self, segs: &[&[u8]], nonblock: bool) -> Result<usize, Errno> {
+        let mut moved = 0;
+        for seg in segs {
+            let mut pos = 0;
+            while pos < seg.len() {
+                let result = self.splice_from(seg.len() - pos, nonblock || moved > 0, |buf| {
+                    buf.copy_from_slice(&seg[pos..pos + buf.len()]);
+                    pos += buf.len();
+                    Ok(buf.len())
+                });
+                match result {
+                    Ok(count) if count > 0 => moved += count,
+                    Ok(_) => return Ok(moved),
+                    Err(_) if moved > 0 => return Ok(moved),
+                    Err(errno) => return Err(errno),
+                }
+            }
+        }
+        Ok(moved)
+    }
+
+    /// Moves at most `len` bytes into this write end, handing the free space in the
+    /// pipe buffer to `read` in place. `read` returns the number of bytes it filled.
+    ///
+    /// Blocks until any space frees unless `nonblock` is set or the pipe is in
+    /// non-blocking mode, in which case `EAGAIN` is returned. Returns `EPIPE` if the
+    /// read end is closed, and the caller raises `SIGPIPE`, or `ERESTART` if
+    /// interrupted by a signal.
+    pub fn splice_from(
+        &self,
+        len: usize,
+        nonblock: bool,
+        mut read: impl FnMut(&mut [u8]) -> Result<usize, Errno>,
+    ) -> Result<usize, Errno> {
         if self.is_read {
-            return None;
+            return Err(Errno::EBADF);
         }
 
-        let mut write_len = 0;
         loop {
-            let buf_rc = Arc::strong_count(&self.buf);
             let mut ring_buf = self.buf.lock();
-            if ring_buf.is_full() {
-                // Read end closed.
-                if buf_rc == 1 {
-                    // TODO: raise SIGPIPE and fail with EPIPE
-                    return Some(0);
+            if ring_buf.is_closed() {
+                return Err(Errno::EPIPE);
+            }
+            if ring_buf.is_full() && len != 0 {
+                if nonblock || self.is_nonblock() {
+                    return Err(Errno::EAGAIN);
                 }
-                // Release the lock.
+                ring_buf.wait_write(waiter_id(), sleep_waker());
+                cancel_on_signal(&mut ring_buf)?;
+                // Release the lock and wait for space.
                 drop(ring_buf);
-                unsafe { do_yield() };
+                sleep_on(&self.buf);
                 continue;
             }
-            write_len += ring_buf.write(&buf[write_len..]);
-            break;
+
+            let mut error = None;
+            let moved = ring_buf.splice_in(len, |data, off, len| {
+                data.for_each_slice(off, len, |slice| {
+                    read(slice).unwrap_or_else(|errno| {
+                        error = Some(errno);
+                        0
+                    })
+                })
+            });
+            return match error {
+                Some(errno) if moved == 0 => Err(errno),
+                _ => Ok(moved),
+            };
+        }
+    }
+}
+
+/// Marks current task sleeping and returns a waker to wake it up.
+pub(super) fn sleep_waker() -> Waker {
+    let curr = cpu().curr.as_ref().unwrap().clone();
+    curr.locked_inner().state = TaskState::INTERRUPTIBLE;
+    Box::new(move || do_wake(&curr))
+}
+
+/// Checks pending signals after the waker of current task is registered on `ring_buf`,
+/// so that a signal queued in between is never missed.
+///
+/// Returns `ERESTART` with the waker removed and the task running again if interrupted.
+fn cancel_on_signal(ring_buf: &mut RingBuffer<MemFile>) -> Result<(), Errno> {
+    if signal_pending() {
+        ring_buf.cancel_wait(waiter_id());
+        cpu().curr.as_ref().unwrap().locked_inner().state = TaskState::RUNNING;
+        return Err(Errno::ERESTART);
+    }
+    Ok(())
+}
+
+/// Id of current task, with which its wakers are registered.
+pub(super) fn waiter_id() -> usize {
+    cpu().curr.as_ref().unwrap().tid.0
+}
+
+/// Current task sleeps after its waker is registered on `buf`, and removes the waker
+/// once woken up in case it is not called, e.g. woken up by a signal.
+fn sleep_on(buf: &SpinLock<RingBuffer<MemFile>>) {
+    unsafe { do_sleep() };
+    buf.lock().cancel_wait(waiter_id());
+}
+
+impl Drop for Pipe {
+    fn drop(&mut self) {
+        // Peers blocked on this pipe need to know that this end is closed.
+        self.buf.lock().close();
+    }
+}
+
+impl File for Pipe {
+    fn read(&self, buf: &mut [u8]) -> Option<usize> {
+        self.try_read(buf).ok()
+    }
+
+    fn write(&self, buf: &[u8]) -> Option<usize> {
+        self.try_write(buf).ok()
+    }
+
+    /// Returns 0 if the write end is closed and no data is left, `EAGAIN` if the pipe is
+    /// empty in non-blocking mode, or `ERESTART` if interrupted by a signal before any
+    /// data is read.
+    fn try_read(&self, buf: &mut [u8]) -> Result<usize, Errno> {
+        if !self.is_read {
+            return Err(Errno::EBADF);
+        }
+
+        loop {
+            let mut ring_buf = self.buf.lock();
+            if ring_buf.is_empty() {
+                // Write end closed.
+                if ring_buf.is_closed() {
+                    return Ok(0);
+                }
+                if self.is_nonblock() {
+                    return Err(Errno::EAGAIN);
+                }
+            }
+            if let Some(read_len) = ring_buf.read_blocking(buf, waiter_id(), sleep_waker) {
+                return Ok(read_len);
+            }
+            cancel_on_signal(&mut ring_buf)?;
+            // Release the lock and wait for data.
+            drop(ring_buf);
+            sleep_on(&self.buf);
+        }
+    }
+
+    /// Returns `EPIPE` if the read end is closed, and the caller raises `SIGPIPE`,
+    /// `EAGAIN` if the pipe is full in non-blocking mode, or `ERESTART` if interrupted
+    /// by a signal before any data is written.
+    fn try_write(&self, buf: &[u8]) -> Result<usize, Errno> {
+        if self.is_read {
+            return Err(Errno::EBADF);
+        }
+
+        loop {
+            let mut ring_buf = self.buf.lock();
+            if ring_buf.is_closed() {
+                return Err(Errno::EPIPE);
+            }
+            if ring_buf.is_full() {
+                if self.is_nonblock() {
+                    return Err(Errno::EAGAIN);
+                }
+            }
+            if let Some(write_len) = ring_buf.write_blocking(buf, waiter_id(), sleep_waker) {
+                return Ok(write_len);
+            }
+            cancel_on_signal(&mut ring_buf)?;
+            // Release the lock and wait for space.
+            drop(ring_buf);
+            sleep_on(&self.buf);
         }
-        Some(write_len)
     }
 
     fn readable(&self) -> bool {
@@ -92,15 +354,56 @@ impl File for Pipe {
         !self.is_read
     }
 
+    /// Ready if any data can be read, or the write end is closed so that read
+    /// returns 0 immediately.
     fn read_ready(&self) -> bool {
-        self.is_read && !self.buf.lock().is_empty()
+        let ring_buf = self.buf.lock();
+        self.is_read && (ring_buf.is_closed() || !ring_buf.is_empty())
     }
 
+    /// Ready if any space is available, or the read end is closed so that write
+    /// fails with `EPIPE` immediately.
     fn write_ready(&self) -> bool {
-        !self.is_read && !self.buf.lock().is_full()
+        let ring_buf = self.buf.lock();
+        !self.is_read && (ring_buf.is_closed() || !ring_buf.is_full())
+    }
+
+    fn hangup(&self) -> bool {
+        self.is_broken()
+    }
+
+    fn poll_wait(&self, id: usize, waker: &dyn Fn() -> Waker) -> bool {
+        let mut ring_buf = self.buf.lock();
+        if self.is_read {
+            ring_buf.wait_read(id, waker());
+        } else {
+            ring_buf.wait_write(id, waker());
+        }
+        true
+    }
+
+    fn poll_cancel(&self, id: usize) {
+        self.buf.lock().cancel_wait(id);
     }
 
     fn get_off(&self) -> usize {
         0
     }
+
+    fn open_flags(&self) -> OpenFlags {
+        *self.flags.lock()
+    }
+
+    fn set_open_flags(&self, flags: OpenFlags) {
+        self.flags
+            .lock()
+            .set(OpenFlags::O_NONBLOCK, flags.contains(OpenFlags::O_NONBLOCK));
+    }
+
+    fn ioctl(&self, cmd: usize, arg: usize) -> Result<usize, Errno> {
+        match cmd {
+            FIONREAD => put_nread(arg, self.buf.lock().len()),
+            _ => Err(Errno::ENOTTY),
+        }
+    }
 }
@@ -0,0 +1,70 @@
+use alloc::{sync::Arc, vec::Vec};
+use errno::Errno;
+use kernel_sync::SpinLock;
+use vfs::{File, OpenFlags};
+
+use crate::{mm::MM, task::cpu};
+
+/// Memory map of a task read from `/proc/self/maps`, see [`MM::maps`].
+///
+/// The contents are generated on the first read and kept until the file is closed,
+/// thus sequential reads are consistent even if the address space is changed.
+pub struct MapsFile {
+    mm: Arc<SpinLock<MM>>,
+
+    /// Generated contents.
+    data: SpinLock<Option<Vec<u8>>>,
+
+    /// Offset of this file.
+    pos: SpinLock<usize>,
+}
+
+impl MapsFile {
+    pub fn new(mm: Arc<SpinLock<MM>>) -> Self {
+        Self {
+            mm,
+            data: SpinLock::new(None),
+            pos: SpinLock::new(0),
+        }
+    }
+}
+
+impl File for MapsFile {
+    fn read(&self, buf: &mut [u8]) -> Option<usize> {
+        let mut data = self.data.lock();
+        let data = data.get_or_insert_with(|| self.mm.lock().maps().into_bytes());
+        let mut pos = self.pos.lock();
+        let read_len = buf.len().min(data.len() - *pos);
+        buf[..read_len].copy_from_slice(&data[*pos..*pos + read_len]);
+        *pos += read_len;
+        Some(read_len)
+    }
+
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn read_ready(&self) -> bool {
+        true
+    }
+
+    fn open_flags(&self) -> OpenFlags {
+        OpenFlags::O_RDONLY
+    }
+
+    fn get_off(&self) -> usize {
+        *self.pos.lock()
+    }
+}
+
+/// Opens a file under `/proc/self` generated from the current task.
+pub fn open_proc(name: &str) -> Option<Result<Arc<dyn File>, Errno>> {
+    let curr = cpu().curr.as_ref();
+    match name {
+        "maps" => Some(
+            curr.map(|task| Arc::new(MapsFile::new(task.inner().mm.clone())) as Arc<dyn File>)
+                .ok_or(Errno::ENOENT),
+        ),
+        _ => None,
+    }
+}
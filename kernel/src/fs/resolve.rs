@@ -0,0 +1,220 @@
+use alloc::{collections::VecDeque, string::String, sync::Arc};
+use errno::Errno;
+use spin::Lazy;
+use syscall_interface::AT_SYMLINK_NOFOLLOW;
+use vfs::{get_path, read_symlink, File, OpenFlags, Path, StatMode, VFS};
+
+use super::{GLOBAL_FS, MOUNT_TABLE};
+use crate::task::Task;
+
+bitflags::bitflags! {
+    /// Flags controlling how [`resolve_path`] walks a path.
+    pub struct ResolveFlags: u32 {
+        /// Do not follow the link in the last component of the path.
+        const NOFOLLOW = 1 << 0;
+    }
+}
+
+impl ResolveFlags {
+    /// Converts `O_NOFOLLOW` in [`OpenFlags`].
+    pub fn from_open(flags: OpenFlags) -> Self {
+        if flags.contains(OpenFlags::O_NOFOLLOW) {
+            Self::NOFOLLOW
+        } else {
+            Self::empty()
+        }
+    }
+
+    /// Converts `AT_SYMLINK_NOFOLLOW` in flags of `*at` syscalls.
+    pub fn from_at(flags: usize) -> Self {
+        if flags & AT_SYMLINK_NOFOLLOW != 0 {
+            Self::NOFOLLOW
+        } else {
+            Self::empty()
+        }
+    }
+}
+
+/// The [`GLOBAL_FS`] behind the [`VFS`] trait, taking the big lock in each operation.
+struct GlobalFs;
+
+impl VFS for GlobalFs {
+    fn open(&self, pdir: &Path, name: &str, flags: OpenFlags) -> Result<Arc<dyn File>, Errno> {
+        GLOBAL_FS.lock().open(pdir, name, flags)
+    }
+
+    fn mkdir(&self, pdir: &Path, name: &str, mode: StatMode) -> Result<(), Errno> {
+        GLOBAL_FS.lock().mkdir(pdir, name, mode)
+    }
+
+    fn check(&self, path: &Path) -> bool {
+        GLOBAL_FS.lock().check(path)
+    }
+
+    fn remove(&self, pdir: &Path, name: &str) -> Result<(), Errno> {
+        GLOBAL_FS.lock().remove(pdir, name)
+    }
+
+    fn rename(
+        &self,
+        old_pdir: &Path,
+        old_name: &str,
+        new_pdir: &Path,
+        new_name: &str,
+    ) -> Result<(), Errno> {
+        GLOBAL_FS
+            .lock()
+            .rename(old_pdir, old_name, new_pdir, new_name)
+    }
+
+    fn exchange(
+        &self,
+        old_pdir: &Path,
+        old_name: &str,
+        new_pdir: &Path,
+        new_name: &str,
+    ) -> Result<(), Errno> {
+        GLOBAL_FS
+            .lock()
+            .exchange(old_pdir, old_name, new_pdir, new_name)
+    }
+
+    fn sync(&self) -> Result<(), Errno> {
+        GLOBAL_FS.lock().sync()
+    }
+
+    fn unmount(&self) -> Result<(), Errno> {
+        GLOBAL_FS.lock().unmount()
+    }
+}
+
+static GLOBAL_VFS: Lazy<Arc<dyn VFS>> = Lazy::new(|| Arc::new(GlobalFs));
+
+/// Gets the [`GLOBAL_FS`] as a [`VFS`], e.g. the lower filesystem of an [`super::OverlayFs`].
+pub fn global_fs() -> Arc<dyn VFS> {
+    GLOBAL_VFS.clone()
+}
+
+/// Maximum number of symbolic links followed in one path resolution, the same as
+/// `MAXSYMLINKS` in Linux.
+pub const MAX_SYMLINKS: usize = 40;
+
+/// Follows symbolic links in an absolute path from the real root.
+///
+/// Targets starting with `'/'` are resolved below `root`, and `".."` in targets never
+/// escapes `root`. The link in the last component is not followed if
+/// [`ResolveFlags::NOFOLLOW`] is set.
+///
+/// Returns `ELOOP` if more than [`MAX_SYMLINKS`] links are followed.
+fn follow_symlinks(root: &Path, path: Path, flags: ResolveFlags) -> Result<Path, Errno> {
+    let is_dir = path.is_dir();
+    let mut items: VecDeque<String> = path.split().into_iter().map(String::from).collect();
+    let mut dir = Path::root();
+    let mut followed = 0;
+    while let Some(item) = items.pop_front() {
+        if item == ".." {
+            if &dir != root {
+                dir.pop();
+            }
+            continue;
+        }
+        let mut next = dir.clone();
+        next.extend(item.as_str());
+        let is_last = items.is_empty();
+        let target = if is_last && flags.contains(ResolveFlags::NOFOLLOW) {
+            None
+        } else {
+            read_symlink(&next)
+        };
+        match target {
+            Some(target) => {
+                followed += 1;
+                if followed > MAX_SYMLINKS {
+                    return Err(Errno::ELOOP);
+                }
+                if target.starts_with('/') {
+                    dir = root.clone();
+                }
+                for item in target
+                    .rsplit('/')
+                    .filter(|item| !item.is_empty() && *item != ".")
+                {
+                    items.push_front(String::from(item));
+                }
+            }
+            None if is_last && !is_dir => return Ok(next),
+            None => dir.join(item.as_str()),
+        }
+    }
+    Ok(dir)
+}
+
+/// Finds the filesystem of an absolute path from the real root.
+///
+/// Symbolic links are followed, see [`ResolveFlags::NOFOLLOW`] for the last component.
+/// Hard links in the parent directory are always followed, while the one in the last
+/// component is kept with [`ResolveFlags::NOFOLLOW`] to be unlinked.
+///
+/// Returns the filesystem mounted on the longest prefix of the parent directory, or
+/// the [`GLOBAL_FS`], together with the path after following links.
+pub fn lookup(path: Path, flags: ResolveFlags) -> Result<(Arc<dyn VFS>, Path), Errno> {
+    lookup_under(&Path::root(), path, flags)
+}
+
+fn lookup_under(
+    root: &Path,
+    path: Path,
+    flags: ResolveFlags,
+) -> Result<(Arc<dyn VFS>, Path), Errno> {
+    let path = follow_symlinks(root, path, flags)?;
+    if path.is_root() {
+        return Ok((GLOBAL_VFS.clone(), path));
+    }
+    let mut pdir = path;
+    let name = pdir.pop().unwrap();
+    let pdir = get_path(&pdir);
+    let mut path = pdir.clone();
+    path.extend(name.as_str());
+    if !flags.contains(ResolveFlags::NOFOLLOW) {
+        path = get_path(&path);
+    }
+
+    let mounted = MOUNT_TABLE.lock().lookup(&pdir);
+    Ok((mounted.unwrap_or_else(|| GLOBAL_VFS.clone()), path))
+}
+
+/// Resolves the pathname of a `*at` syscall into the filesystem and the absolute
+/// path from the real root.
+///
+/// If the pathname is relative, then it is interpreted relative to the directory
+/// referred to by the file descriptor `dirfd`, or the current working directory of
+/// `task` if `dirfd` is `AT_FDCWD`.
+///
+/// Absolute paths are resolved below the root directory of the task, and `".."`
+/// never escapes the root directory unless the directory is already out of it, e.g.
+/// opened before `chroot`.
+///
+/// See [`lookup`] for links and mounted filesystems.
+pub fn resolve_path(
+    task: &Task,
+    dirfd: usize,
+    pathname: &str,
+    flags: ResolveFlags,
+) -> Result<(Arc<dyn VFS>, Path), Errno> {
+    let root = task.fs_info.lock().root.clone();
+    let mut path = if pathname.starts_with("/") {
+        Path::root()
+    } else {
+        let dir = task.get_dir(dirfd)?;
+        match dir.strip_root(&root) {
+            Some(dir) => dir,
+            None => {
+                let mut path = dir;
+                path.extend(pathname);
+                return lookup_under(&root, path, flags);
+            }
+        }
+    };
+    path.extend(pathname);
+    lookup_under(&root, path.under(&root), flags)
+}
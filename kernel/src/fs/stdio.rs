@@ -6,9 +6,53 @@
 //! - 1: Standard output (STDOUT)
 //! - 2: Standard error (STDERR)
 
+use errno::Errno;
+use kernel_sync::SpinLock;
+use spin::Lazy;
+use syscall_interface::*;
 use vfs::File;
 
-use crate::{cons::getchar, eprint, print, task::do_yield};
+use super::put_nread;
+use crate::{
+    arch::mm::VirtAddr,
+    cons::getchar,
+    eprint, print, read_user,
+    task::{cpu, do_yield},
+    write_user,
+};
+
+/// Default window size of the console.
+const CONSOLE_WINSIZE: WinSize = WinSize {
+    ws_row: 24,
+    ws_col: 80,
+    ws_xpixel: 0,
+    ws_ypixel: 0,
+};
+
+/// Settings of the console, initialized as `stty sane` in Linux.
+///
+/// Settings are only recorded without changing how the console works.
+static CONSOLE_TERMIOS: Lazy<SpinLock<Termios>> = Lazy::new(|| {
+    SpinLock::new(Termios {
+        // ICRNL | IXON
+        c_iflag: 0o2400,
+        // OPOST | ONLCR
+        c_oflag: 0o5,
+        // B38400 | CS8 | CREAD | HUPCL
+        c_cflag: 0o2277,
+        // ISIG | ICANON | ECHO | ECHOE | ECHOK | IEXTEN | ECHOCTL | ECHOKE
+        c_lflag: 0o105073,
+        c_line: 0,
+        c_cc: [
+            3, 28, 127, 21, 4, 0, 1, 0, 17, 19, 26, 0, 18, 15, 23, 22, 0, 0, 0,
+        ],
+    })
+});
+
+/// Gets the settings of the console.
+pub fn console_termios() -> Termios {
+    *CONSOLE_TERMIOS.lock()
+}
 
 pub struct Stdin;
 
@@ -32,6 +76,10 @@ impl File for Stdin {
     fn read_ready(&self) -> bool {
         true
     }
+
+    fn ioctl(&self, cmd: usize, arg: usize) -> Result<usize, Errno> {
+        Tty.ioctl(cmd, arg)
+    }
 }
 
 pub struct Stdout;
@@ -49,6 +97,10 @@ impl File for Stdout {
     fn write_ready(&self) -> bool {
         true
     }
+
+    fn ioctl(&self, cmd: usize, arg: usize) -> Result<usize, Errno> {
+        Tty.ioctl(cmd, arg)
+    }
 }
 
 pub struct Stderr;
@@ -66,4 +118,69 @@ impl File for Stderr {
     fn write_ready(&self) -> bool {
         true
     }
+
+    fn ioctl(&self, cmd: usize, arg: usize) -> Result<usize, Errno> {
+        Tty.ioctl(cmd, arg)
+    }
+}
+
+/// The controlling terminal, which reads from the console input and writes to
+/// the console output.
+pub struct Tty;
+
+impl File for Tty {
+    fn read(&self, buf: &mut [u8]) -> Option<usize> {
+        Stdin.read(buf)
+    }
+
+    fn write(&self, buf: &[u8]) -> Option<usize> {
+        Stdout.write(buf)
+    }
+
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    fn read_ready(&self) -> bool {
+        true
+    }
+
+    fn write_ready(&self) -> bool {
+        true
+    }
+
+    /// Terminal requests shared by standard streams.
+    fn ioctl(&self, cmd: usize, arg: usize) -> Result<usize, Errno> {
+        let curr = cpu().curr.as_ref().unwrap();
+        match cmd {
+            TCGETS => {
+                write_user!(curr.mm(), VirtAddr::from(arg), console_termios(), Termios)?;
+                Ok(0)
+            }
+            TCSETS | TCSETSW | TCSETSF => {
+                let mut termios = Termios::default();
+                read_user!(curr.mm(), VirtAddr::from(arg), termios, Termios)?;
+                *CONSOLE_TERMIOS.lock() = termios;
+                Ok(0)
+            }
+            TIOCGWINSZ => {
+                write_user!(curr.mm(), VirtAddr::from(arg), CONSOLE_WINSIZE, WinSize)?;
+                Ok(0)
+            }
+            // Sessions are not supported, thus the caller is always in the foreground.
+            TIOCGPGRP => {
+                let pgid = curr.thread_group.pgid() as i32;
+                write_user!(curr.mm(), VirtAddr::from(arg), pgid, i32)?;
+                Ok(0)
+            }
+            TIOCSPGRP => Err(Errno::EINVAL),
+            // console input is not buffered
+            FIONREAD => put_nread(arg, 0),
+            _ => Err(Errno::ENOTTY),
+        }
+    }
 }
@@ -0,0 +1,336 @@
+use alloc::{collections::BTreeMap, string::String, sync::Arc, vec::Vec};
+use errno::Errno;
+use kernel_sync::SpinLock;
+use vfs::*;
+
+use super::{
+    fat::path_ino,
+    makedev,
+    mem::{TmpData, TmpFile},
+    UNNAMED_MAJOR,
+};
+use crate::arch::mm::PAGE_SIZE;
+
+/// Device number of files in memory.
+pub const TMPFS_DEV: u64 = makedev(UNNAMED_MAJOR, 1);
+
+/// A node in [`TmpFs`].
+enum TmpNode {
+    /// A directory with its permission bits.
+    Dir(StatMode),
+    File(Arc<SpinLock<TmpData>>),
+}
+
+/// Nodes of a [`TmpFs`] by absolute paths without the trailing `'/'`.
+type TmpNodes = Arc<SpinLock<BTreeMap<Path, TmpNode>>>;
+
+/// Gets the key of a path in [`TmpNodes`].
+fn node_key(path: &Path) -> Path {
+    Path::new(path.as_str().trim_end_matches('/'))
+}
+
+/// Checks if `key` is below the directory `dir`.
+fn is_below(key: &Path, dir: &Path) -> bool {
+    key.as_str()
+        .strip_prefix(dir.as_str())
+        .map_or(false, |rela| rela.starts_with('/'))
+}
+
+/// A writable filesystem in memory, which is mounted on `/tmp` by default.
+///
+/// All data is lost when it is unmounted.
+pub struct TmpFs {
+    nodes: TmpNodes,
+}
+
+impl TmpFs {
+    /// Creates an empty filesystem with only the directory of the mount point.
+    pub fn new(mount_point: &Path) -> Self {
+        let mut nodes = BTreeMap::new();
+        nodes.insert(node_key(mount_point), TmpNode::Dir(StatMode::S_IRWXUGO));
+        Self {
+            nodes: Arc::new(SpinLock::new(nodes)),
+        }
+    }
+}
+
+/// Checks if the parent directory exists.
+///
+/// Returns `ENOENT` if it does not exist, or `ENOTDIR` if it is a file.
+fn check_dir(nodes: &BTreeMap<Path, TmpNode>, pdir: &Path) -> Result<(), Errno> {
+    match nodes.get(&node_key(pdir)) {
+        Some(TmpNode::Dir(_)) => Ok(()),
+        Some(TmpNode::File(_)) => Err(Errno::ENOTDIR),
+        None => Err(Errno::ENOENT),
+    }
+}
+
+impl VFS for TmpFs {
+    fn open(&self, pdir: &Path, name: &str, flags: OpenFlags) -> Result<Arc<dyn File>, Errno> {
+        let mut path = pdir.clone();
+        path.extend(name);
+        let key = node_key(&path);
+        let mut nodes = self.nodes.lock();
+        check_dir(&nodes, pdir)?;
+
+        let data = match nodes.get(&key) {
+            Some(TmpNode::Dir(_)) => {
+                if flags.contains(OpenFlags::O_CREAT | OpenFlags::O_EXCL) {
+                    return Err(Errno::EEXIST);
+                }
+                if flags.writable() {
+                    return Err(Errno::EISDIR);
+                }
+                path.join("");
+                return Ok(Arc::new(TmpDir::new(path, self.nodes.clone())));
+            }
+            Some(TmpNode::File(data)) => {
+                if flags.contains(OpenFlags::O_CREAT | OpenFlags::O_EXCL) {
+                    return Err(Errno::EEXIST);
+                }
+                if flags.contains(OpenFlags::O_DIRECTORY) || path.is_dir() {
+                    return Err(Errno::ENOTDIR);
+                }
+                if flags.contains(OpenFlags::O_TRUNC) {
+                    data.lock().clear();
+                }
+                data.clone()
+            }
+            None => {
+                if !flags.contains(OpenFlags::O_CREAT) || path.is_dir() {
+                    return Err(Errno::ENOENT);
+                }
+                check_name(name)?;
+                let data = Arc::new(SpinLock::new(TmpData::new()));
+                nodes.insert(key.clone(), TmpNode::File(data.clone()));
+                data
+            }
+        };
+        Ok(Arc::new(TmpFile::open(key, data, flags)))
+    }
+
+    fn mkdir(&self, pdir: &Path, name: &str, mode: StatMode) -> Result<(), Errno> {
+        let name = name.trim_end_matches('/');
+        check_name(name)?;
+        let mut nodes = self.nodes.lock();
+        check_dir(&nodes, pdir)?;
+        let mut path = pdir.clone();
+        path.extend(name);
+        if nodes.contains_key(&path) {
+            return Err(Errno::EEXIST);
+        }
+        nodes.insert(path, TmpNode::Dir(mode));
+        Ok(())
+    }
+
+    fn check(&self, path: &Path) -> bool {
+        match self.nodes.lock().get(&node_key(path)) {
+            Some(TmpNode::Dir(_)) => true,
+            Some(TmpNode::File(_)) => !path.is_dir(),
+            None => false,
+        }
+    }
+
+    fn remove(&self, pdir: &Path, name: &str) -> Result<(), Errno> {
+        let mut path = pdir.clone();
+        path.extend(name.trim_end_matches('/'));
+        let mut nodes = self.nodes.lock();
+        match nodes.get(&path) {
+            Some(TmpNode::Dir(_)) if nodes.keys().any(|key| is_below(key, &path)) => {
+                return Err(Errno::ENOTEMPTY)
+            }
+            Some(_) => {}
+            None => return Err(Errno::ENOENT),
+        }
+        nodes.remove(&path);
+        Ok(())
+    }
+
+    fn rename(
+        &self,
+        old_pdir: &Path,
+        old_name: &str,
+        new_pdir: &Path,
+        new_name: &str,
+    ) -> Result<(), Errno> {
+        let new_name = new_name.trim_end_matches('/');
+        check_name(new_name)?;
+        let mut old_path = old_pdir.clone();
+        old_path.extend(old_name.trim_end_matches('/'));
+        let mut new_path = new_pdir.clone();
+        new_path.extend(new_name);
+
+        let mut nodes = self.nodes.lock();
+        check_dir(&nodes, old_pdir)?;
+        check_dir(&nodes, new_pdir)?;
+        let is_dir = match nodes.get(&old_path) {
+            Some(TmpNode::Dir(_)) => true,
+            Some(TmpNode::File(_)) => false,
+            None => return Err(Errno::ENOENT),
+        };
+        if old_path == new_path {
+            return Ok(());
+        }
+        // A directory cannot be moved into itself.
+        if is_dir && is_below(&new_path, &old_path) {
+            return Err(Errno::EINVAL);
+        }
+        match nodes.get(&new_path) {
+            Some(TmpNode::Dir(_)) if !is_dir => return Err(Errno::EISDIR),
+            Some(TmpNode::File(_)) if is_dir => return Err(Errno::ENOTDIR),
+            Some(TmpNode::Dir(_)) if nodes.keys().any(|key| is_below(key, &new_path)) => {
+                return Err(Errno::ENOTEMPTY)
+            }
+            _ => {}
+        }
+
+        // Moves the node and all nodes below it.
+        let moved: Vec<Path> = nodes
+            .keys()
+            .filter(|key| *key == &old_path || is_below(key, &old_path))
+            .cloned()
+            .collect();
+        for key in moved {
+            let node = nodes.remove(&key).unwrap();
+            let rela = &key.as_str()[old_path.as_str().len()..];
+            nodes.insert(Path::new(&(String::from(new_path.as_str()) + rela)), node);
+        }
+        Ok(())
+    }
+
+    fn exchange(
+        &self,
+        old_pdir: &Path,
+        old_name: &str,
+        new_pdir: &Path,
+        new_name: &str,
+    ) -> Result<(), Errno> {
+        let mut old_path = old_pdir.clone();
+        old_path.extend(old_name.trim_end_matches('/'));
+        let mut new_path = new_pdir.clone();
+        new_path.extend(new_name.trim_end_matches('/'));
+
+        let mut nodes = self.nodes.lock();
+        if !nodes.contains_key(&old_path) || !nodes.contains_key(&new_path) {
+            return Err(Errno::ENOENT);
+        }
+        if old_path == new_path {
+            return Ok(());
+        }
+        // Neither can be moved into the other.
+        if is_below(&new_path, &old_path) || is_below(&old_path, &new_path) {
+            return Err(Errno::EINVAL);
+        }
+
+        // Swaps the nodes and all nodes below them.
+        let moved: Vec<Path> = nodes
+            .keys()
+            .filter(|key| {
+                *key == &old_path
+                    || *key == &new_path
+                    || is_below(key, &old_path)
+                    || is_below(key, &new_path)
+            })
+            .cloned()
+            .collect();
+        let moved: Vec<(Path, TmpNode)> = moved
+            .into_iter()
+            .map(|key| {
+                let node = nodes.remove(&key).unwrap();
+                (key, node)
+            })
+            .collect();
+        for (key, node) in moved {
+            let (from, to) = if key == old_path || is_below(&key, &old_path) {
+                (&old_path, &new_path)
+            } else {
+                (&new_path, &old_path)
+            };
+            let rela = &key.as_str()[from.as_str().len()..];
+            nodes.insert(Path::new(&(String::from(to.as_str()) + rela)), node);
+        }
+        Ok(())
+    }
+}
+
+/// A directory in [`TmpFs`].
+struct TmpDir {
+    /// Absolute path, which always ends with `'/'`.
+    path: Path,
+
+    nodes: TmpNodes,
+
+    /// Index of the next entry.
+    pos: SpinLock<usize>,
+}
+
+impl TmpDir {
+    fn new(path: Path, nodes: TmpNodes) -> Self {
+        Self {
+            path,
+            nodes,
+            pos: SpinLock::new(0),
+        }
+    }
+}
+
+impl File for TmpDir {
+    fn get_path(&self) -> Option<Path> {
+        Some(self.path.clone())
+    }
+
+    fn seek(&self, offset: usize, whence: SeekWhence) -> Option<usize> {
+        let mut pos = self.pos.lock();
+        match whence {
+            SeekWhence::Set => *pos = offset,
+            SeekWhence::Current => *pos += offset,
+            SeekWhence::End | SeekWhence::Data | SeekWhence::Hole => return None,
+        }
+        Some(*pos)
+    }
+
+    fn get_stat(&self, stat_ptr: *mut Stat) -> bool {
+        let mut stat = Stat::default();
+        stat.st_dev = TMPFS_DEV;
+        stat.st_ino = path_ino(&self.path);
+        // The directory may have been removed after it is opened.
+        let mode = match self.nodes.lock().get(&node_key(&self.path)) {
+            Some(TmpNode::Dir(mode)) => *mode,
+            _ => StatMode::S_IRWXUGO,
+        };
+        stat.st_mode = (StatMode::S_IFDIR | mode).bits();
+        stat.st_nlink = 1;
+        stat.st_blksize = PAGE_SIZE as u32;
+        unsafe { *stat_ptr = stat };
+        true
+    }
+
+    fn read_dir(&self) -> Option<Vec<DirEntry>> {
+        let dir = node_key(&self.path);
+        let nodes = self.nodes.lock();
+        let entries = nodes
+            .iter()
+            .filter(|(key, _)| is_below(key, &dir))
+            .filter_map(|(key, node)| {
+                let name = key.as_str().strip_prefix(dir.as_str())?.strip_prefix('/')?;
+                if name.contains('/') {
+                    return None;
+                }
+                Some(DirEntry {
+                    ino: path_ino(key),
+                    d_type: if matches!(node, TmpNode::Dir(_)) {
+                        DT_DIR
+                    } else {
+                        DT_REG
+                    },
+                    name: String::from(name),
+                })
+            })
+            .collect();
+        Some(entries)
+    }
+
+    fn is_dir(&self) -> bool {
+        true
+    }
+}
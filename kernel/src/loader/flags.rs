@@ -38,7 +38,9 @@ numeric_enum_macro::numeric_enum! {
         AT_CLKTCK = 17,
         /// Secure mode boolean
         AT_SECURE = 23,
-        /// Random
+        /// Address of 16 random bytes
         AT_RANDOM = 25,
+        /// Filename of program
+        AT_EXECFN = 31,
     }
 }
@@ -22,6 +22,9 @@ pub struct InitInfo {
     /// Environment strings
     pub envs: Vec<String>,
 
+    /// Random bytes pointed to by `AT_RANDOM`
+    pub random: [u8; 16],
+
     /// Auxiliary value
     pub auxv: BTreeMap<AuxType, usize>,
 }
@@ -79,9 +82,10 @@ impl InitStack {
     /// Serialized args, envp, auxv.
     pub fn serialize(v: InitInfo, sp: PhysAddr, vsp: VirtAddr) -> Self {
         let mut stack = InitStack::new(sp, vsp);
-        stack.push_str(&v.args[0]);
+        // executable filename
+        let execfn = stack.push_str(&v.args[0]);
         // random string: 16 bytes
-        let random = stack.push_slice(&[0usize, 0usize]);
+        let random = stack.push_slice(&v.random);
         // environment strings
         let envs: Vec<VirtAddr> = v
             .envs
@@ -100,6 +104,7 @@ impl InitStack {
         for (&type_, &value) in v.auxv.iter() {
             match type_ {
                 AuxType::AT_RANDOM => stack.push_slice(&[type_.into(), random.value()]),
+                AuxType::AT_EXECFN => stack.push_slice(&[type_.into(), execfn.value()]),
                 _ => stack.push_slice(&[type_.into(), value]),
             };
         }
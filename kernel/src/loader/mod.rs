@@ -1,21 +1,23 @@
 pub mod flags;
 mod init;
 
-use alloc::{collections::BTreeMap, string::String, sync::Arc, vec::Vec};
-use vfs::{OpenFlags, Path};
+use alloc::{collections::BTreeMap, string::String, sync::Arc, vec, vec::Vec};
+use errno::Errno;
+use vfs::{File, OpenFlags, Path};
 use xmas_elf::{
     header,
     program::{self, SegmentData},
+    sections::{SectionData, ShType},
     ElfFile,
 };
 
 use crate::{
     arch::mm::{Page, VirtAddr, PAGE_SIZE},
-    config::{ADDR_ALIGN, ELF_BASE_RELOCATE, USER_STACK_BASE, USER_STACK_SIZE},
+    config::{ADDR_ALIGN, ELF_BASE_RELOCATE, INTERP_BASE_RELOCATE, USER_STACK_BASE},
     error::{KernelError, KernelResult},
-    fs::open,
+    fs::{mem::RandomFile, open},
     mm::{VMFlags, MM},
-    task::Task,
+    task::{register_task, Task},
 };
 
 use self::{
@@ -23,27 +25,81 @@ use self::{
     init::{InitInfo, InitStack},
 };
 
+/// Maximum length of the shebang line, including `#!`.
+const SHEBANG_MAX_LEN: usize = 256;
+
+/// Maximum depth of scripts interpreted by another script.
+const SHEBANG_MAX_DEPTH: usize = 4;
+
+/// Parses the interpreter path and the optional argument in the first line
+/// starting with `#!`.
+///
+/// Returns `None` if the file is not a script.
+fn parse_shebang(data: &[u8]) -> KernelResult<Option<(String, Option<String>)>> {
+    if !data.starts_with(b"#!") {
+        return Ok(None);
+    }
+    let line = match data.iter().take(SHEBANG_MAX_LEN).position(|&b| b == b'\n') {
+        Some(end) => &data[2..end],
+        None if data.len() <= SHEBANG_MAX_LEN => &data[2..],
+        None => return Err(KernelError::Errno(Errno::ENOEXEC)),
+    };
+    let line = core::str::from_utf8(line).map_err(|_| KernelError::Errno(Errno::ENOEXEC))?;
+    // Like Linux, everything after the interpreter is passed as a single argument.
+    let line = line.trim();
+    let (interp, arg) = match line.find(|c: char| c == ' ' || c == '\t') {
+        Some(pos) => (&line[..pos], Some(String::from(line[pos..].trim()))),
+        None => (line, None),
+    };
+    if interp.is_empty() {
+        return Err(KernelError::Errno(Errno::ENOEXEC));
+    }
+    Ok(Some((String::from(interp), arg)))
+}
+
 /// Finds the user ELF in the given directory and creates the task.
-pub fn from_args(dir: String, args: Vec<String>) -> KernelResult<Arc<Task>> {
+///
+/// If the file is a script starting with `#!`, the interpreter will be loaded
+/// with the path of the script prepended to `args`.
+pub fn from_args(dir: String, args: Vec<String>, envs: Vec<String>) -> KernelResult<Arc<Task>> {
     if args.len() < 1 {
         return Err(KernelError::InvalidArgs);
     }
-    let name = args[0].as_str();
-    let path = dir.clone() + "/" + name;
-    let file = unsafe {
-        open(Path::from(path), OpenFlags::O_RDONLY)
-            .map_err(|errno| KernelError::Errno(errno))?
-            .read_all()
-    };
-    Ok(Arc::new(Task::new(dir, file.as_slice(), args)?))
+    let mut args = args;
+    let mut path = dir.clone() + "/" + args[0].as_str();
+    for _ in 0..=SHEBANG_MAX_DEPTH {
+        let file = unsafe {
+            open(Path::from(path.clone()), OpenFlags::O_RDONLY)
+                .map_err(|errno| KernelError::Errno(errno))?
+                .read_all()
+        };
+        match parse_shebang(file.as_slice())? {
+            Some((interp, arg)) => {
+                // argv[0] is replaced by the path of the script.
+                let mut new_args = vec![interp.clone()];
+                new_args.extend(arg);
+                new_args.push(path);
+                new_args.extend(args.drain(1..));
+                args = new_args;
+                path = if interp.starts_with('/') {
+                    interp
+                } else {
+                    dir.clone() + "/" + interp.as_str()
+                };
+            }
+            None => {
+                let task = Arc::new(Task::new(dir, file.as_slice(), args, envs)?);
+                register_task(&task);
+                return Ok(task);
+            }
+        }
+    }
+    Err(KernelError::Errno(Errno::ELOOP))
 }
 
-/// Create address space from elf.
-pub fn from_elf(elf_data: &[u8], args: Vec<String>, mm: &mut MM) -> KernelResult<VirtAddr> {
-    let elf = ElfFile::new(elf_data).unwrap();
+/// Checks the header of ELF.
+fn check_elf(elf: &ElfFile) -> KernelResult {
     let elf_hdr = elf.header;
-
-    // Check elf type
     if (elf_hdr.pt2.type_().as_type() != header::Type::Executable
         && elf_hdr.pt2.type_().as_type() != header::Type::SharedObject)
         // 64-bit format
@@ -55,7 +111,25 @@ pub fn from_elf(elf_data: &[u8], args: Vec<String>, mm: &mut MM) -> KernelResult
     {
         return Err(KernelError::ELFInvalidHeader);
     }
+    Ok(())
+}
+
+/// Information of an ELF loaded into the address space.
+struct ElfLoadInfo {
+    /// Offset added to all virtual addresses in the ELF.
+    dyn_base: usize,
+
+    /// Virtual address where the ELF (including its headers) starts.
+    elf_base_va: usize,
 
+    /// The page next to the last loaded segment.
+    max_page: Page,
+}
+
+/// Loads `PT_LOAD` segments of ELF into the address space.
+///
+/// If the first segment starts at 0, the ELF will be relocated to `reloc_base`.
+fn load_segments(elf: &ElfFile, reloc_base: usize, mm: &mut MM) -> KernelResult<ElfLoadInfo> {
     // Dynamic address
     let mut dyn_base = 0;
     let elf_base_va = if let Some(phdr) = elf
@@ -68,8 +142,8 @@ pub fn from_elf(elf_data: &[u8], args: Vec<String>, mm: &mut MM) -> KernelResult
         } else {
             // If the first segment starts at 0, we need to put it at a higher address
             // to avoid conflicts with user programs.
-            dyn_base = ELF_BASE_RELOCATE;
-            ELF_BASE_RELOCATE
+            dyn_base = reloc_base;
+            reloc_base
         }
     } else {
         0
@@ -78,77 +152,188 @@ pub fn from_elf(elf_data: &[u8], args: Vec<String>, mm: &mut MM) -> KernelResult
     // Load program header
     let mut max_page = Page::from(0);
     for phdr in elf.program_iter() {
-        match phdr.get_type().unwrap() {
-            program::Type::Load => {
-                let start_va: VirtAddr = (phdr.virtual_addr() as usize).into();
-                let end_va: VirtAddr = ((phdr.virtual_addr() + phdr.mem_size()) as usize).into();
-                max_page = Page::floor(end_va - 1) + 1;
-
-                // Map flags
-                let mut map_flags = VMFlags::USER;
-                let phdr_flags = phdr.flags();
-                if phdr_flags.is_read() {
-                    map_flags |= VMFlags::READ;
-                }
-                if phdr_flags.is_write() {
-                    map_flags |= VMFlags::WRITE;
-                }
-                if phdr_flags.is_execute() {
-                    map_flags |= VMFlags::EXEC;
-                }
+        // Empty segments occupy no page.
+        if phdr.get_type() != Ok(program::Type::Load) || phdr.mem_size() == 0 {
+            continue;
+        }
+        let start_va: VirtAddr = (phdr.virtual_addr() as usize).into();
+        let end_va: VirtAddr = ((phdr.virtual_addr() + phdr.mem_size()) as usize).into();
+        max_page = Page::floor(end_va - 1) + 1;
 
-                // Allocate a new virtual memory area
-                let data = match phdr.get_data(&elf).unwrap() {
-                    SegmentData::Undefined(data) => data,
-                    _ => return Err(KernelError::ELFInvalidSegment),
-                };
-                
-                // Address may not be aligned.
-                mm.alloc_write_vma(
-                    Some(data),
-                    start_va + dyn_base,
-                    end_va + dyn_base,
-                    map_flags,
-                )?;
-            }
-            program::Type::Interp => {
-                // let data = match phdr.get_data(&elf).unwrap() {
-                //     SegmentData::Undefined(data) => data,
-                //     _ => return Err(KernelError::ELFInvalidSegment),
-                // };
-                // let path = unsafe {raw_ptr_to}
-            }
-            _ => {}
+        // Map flags
+        let mut map_flags = VMFlags::USER;
+        let phdr_flags = phdr.flags();
+        if phdr_flags.is_read() {
+            map_flags |= VMFlags::READ;
+        }
+        if phdr_flags.is_write() {
+            map_flags |= VMFlags::WRITE;
+        }
+        if phdr_flags.is_execute() {
+            map_flags |= VMFlags::EXEC;
+        }
+
+        // Allocate a new virtual memory area
+        let data = match phdr
+            .get_data(elf)
+            .map_err(|_| KernelError::ELFInvalidSegment)?
+        {
+            SegmentData::Undefined(data) => data,
+            _ => return Err(KernelError::ELFInvalidSegment),
+        };
+
+        // Address may not be aligned.
+        mm.alloc_write_vma(
+            Some(data),
+            start_va + dyn_base,
+            end_va + dyn_base,
+            map_flags,
+        )?;
+    }
+
+    Ok(ElfLoadInfo {
+        dyn_base,
+        elf_base_va,
+        max_page,
+    })
+}
+
+/// Relocation type: `B + A`.
+const R_RISCV_RELATIVE: u32 = 3;
+
+/// Applies relocations in `SHT_RELA` sections, e.g. `.rela.dyn`.
+///
+/// Only `R_RISCV_RELATIVE` is supported, which does not need symbol lookup.
+/// Dynamically linked programs are relocated by the program interpreter.
+fn relocate(elf: &ElfFile, dyn_base: usize, mm: &mut MM) -> KernelResult {
+    for sh in elf.section_iter() {
+        if sh.get_type() != Ok(ShType::Rela) {
+            continue;
+        }
+        let entries = match sh.get_data(elf) {
+            Ok(SectionData::Rela64(entries)) => entries,
+            _ => return Err(KernelError::ELFInvalidSegment),
         };
+        for entry in entries {
+            match entry.get_type() {
+                R_RISCV_RELATIVE => {
+                    let va = VirtAddr::from(entry.get_offset() as usize + dyn_base);
+                    let value = entry.get_addend() as usize + dyn_base;
+                    mm.alloc_write_type(va, &value)?;
+                }
+                other => log::warn!(
+                    "Unsupported relocation type {} in section {}",
+                    other,
+                    sh.get_name(elf).unwrap_or("?")
+                ),
+            }
+        }
     }
+    Ok(())
+}
+
+/// Gets the path of the program interpreter in `PT_INTERP` segment.
+fn get_interp(elf: &ElfFile) -> KernelResult<Option<String>> {
+    let phdr = match elf
+        .program_iter()
+        .find(|phdr| phdr.get_type() == Ok(program::Type::Interp))
+    {
+        Some(phdr) => phdr,
+        None => return Ok(None),
+    };
+    let data = match phdr
+        .get_data(elf)
+        .map_err(|_| KernelError::ELFInvalidSegment)?
+    {
+        SegmentData::Undefined(data) => data,
+        _ => return Err(KernelError::ELFInvalidSegment),
+    };
+    // The path is terminated by '\0'.
+    let len = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    core::str::from_utf8(&data[..len])
+        .map(|path| Some(String::from(path)))
+        .map_err(|_| KernelError::ELFInvalidSegment)
+}
+
+/// Create address space from elf.
+///
+/// The user stack of `stack_size` bytes is allocated right below [`USER_STACK_BASE`].
+pub fn from_elf(
+    elf_data: &[u8],
+    args: Vec<String>,
+    envs: Vec<String>,
+    stack_size: usize,
+    mm: &mut MM,
+) -> KernelResult<VirtAddr> {
+    let elf = ElfFile::new(elf_data).map_err(|_| KernelError::ELFInvalidHeader)?;
+    check_elf(&elf)?;
+    let elf_hdr = elf.header;
 
-    // .rela.dyn
+    let ElfLoadInfo {
+        dyn_base,
+        elf_base_va,
+        max_page,
+    } = load_segments(&elf, ELF_BASE_RELOCATE, mm)?;
 
-    // .rela.plt
+    // Load the program interpreter (dynamic linker), which will load shared
+    // libraries and jump to the entry of this program.
+    let interp_base = if let Some(interp) = get_interp(&elf)? {
+        let interp_data = unsafe {
+            open(Path::new(interp.as_str()), OpenFlags::O_RDONLY)
+                .map_err(|errno| KernelError::Errno(errno))?
+                .read_all()
+        };
+        let interp_elf =
+            ElfFile::new(interp_data.as_slice()).map_err(|_| KernelError::ELFInvalidHeader)?;
+        check_elf(&interp_elf)?;
+        let reloc_base = if dyn_base == 0 {
+            ELF_BASE_RELOCATE
+        } else {
+            INTERP_BASE_RELOCATE
+        };
+        let interp_info = load_segments(&interp_elf, reloc_base, mm)?;
+        Some((
+            interp_info.dyn_base,
+            interp_elf.header.pt2.entry_point() as usize + interp_info.dyn_base,
+        ))
+    } else {
+        // .rela.dyn and .rela.plt of static PIE
+        relocate(&elf, dyn_base, mm)?;
+        None
+    };
 
     // Set brk location
     mm.start_brk = max_page.start_address() + dyn_base;
     mm.brk = mm.start_brk;
 
     // Set user entry
-    mm.entry = VirtAddr::from(elf_hdr.pt2.entry_point() as usize) + dyn_base;
+    let entry = elf_hdr.pt2.entry_point() as usize + dyn_base;
+    mm.entry = match interp_base {
+        Some((_, interp_entry)) => VirtAddr::from(interp_entry),
+        None => VirtAddr::from(entry),
+    };
 
     // Initialize user stack
     let ustack_base = USER_STACK_BASE - ADDR_ALIGN;
-    let ustack_top = USER_STACK_BASE - USER_STACK_SIZE;
+    let ustack_top = USER_STACK_BASE - stack_size;
     mm.alloc_write_vma(
         None,
         ustack_top.into(),
         ustack_base.into(),
-        VMFlags::READ | VMFlags::WRITE | VMFlags::USER,
+        VMFlags::READ | VMFlags::WRITE | VMFlags::USER | VMFlags::GROWSDOWN,
     )?;
     let mut vsp = VirtAddr::from(ustack_base);
     let sp = mm.translate(vsp)?;
     let init_stack = InitStack::serialize(
         InitInfo {
             args,
-            // TODO
-            envs: Vec::new(),
+            envs,
+            random: {
+                // Seeded with current time.
+                let mut random = [0u8; 16];
+                RandomFile::new().read(&mut random);
+                random
+            },
             auxv: {
                 let mut at_table = BTreeMap::new();
                 at_table.insert(
@@ -157,7 +342,11 @@ pub fn from_elf(elf_data: &[u8], args: Vec<String>, mm: &mut MM) -> KernelResult
                 );
                 at_table.insert(AuxType::AT_PHENT, elf_hdr.pt2.ph_entry_size() as usize);
                 at_table.insert(AuxType::AT_PHNUM, elf_hdr.pt2.ph_count() as usize);
+                at_table.insert(AuxType::AT_BASE, interp_base.map_or(0, |(base, _)| base));
+                at_table.insert(AuxType::AT_ENTRY, entry);
+                // Filled by the init stack
                 at_table.insert(AuxType::AT_RANDOM, 0);
+                at_table.insert(AuxType::AT_EXECFN, 0);
                 at_table.insert(AuxType::AT_PAGESZ, PAGE_SIZE);
                 at_table
             },
@@ -17,6 +17,8 @@ mod fs;
 mod heap;
 mod loader;
 mod mm;
+mod random;
+mod sync;
 mod syscall;
 mod task;
 mod tests;
@@ -54,10 +56,11 @@ pub extern "C" fn rust_main(hartid: usize) -> ! {
     arch::init(hartid, true);
     // Initialize oscomp testcases, which will be loaded from disk.
     if IS_TEST_ENV {
+        let clock = || time_subsys::TimeSpec::new(arch::timer::get_time_sec_f64());
         #[cfg(not(feature = "uintr"))]
-        oscomp::init(oscomp::testcases::FORMAT_LIBC_STATIC);
+        oscomp::init(oscomp::testcases::FORMAT_LIBC_STATIC, clock, cons::print);
         #[cfg(feature = "uintr")]
-        oscomp::init(crate::arch::uintr::UINTR_TESTCASES);
+        oscomp::init(crate::arch::uintr::UINTR_TESTCASES, clock, cons::print);
     }
     // Wake up other harts.
     for cpu_id in 0..CPU_NUM {
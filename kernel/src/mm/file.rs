@@ -19,6 +19,16 @@ impl MmapFile {
         Self { file, offset }
     }
 
+    /// Returns the inner file.
+    pub fn file(&self) -> &Arc<dyn File> {
+        &self.file
+    }
+
+    /// Returns the offset in the file where the mapping starts.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
     /// Reads at `off` starting from `self.offset`.
     pub fn read(&self, off: usize, buf: &mut [u8]) -> Option<usize> {
         self.file.read_at_off(off + self.offset, buf)
@@ -37,6 +47,12 @@ impl MmapFile {
         }
     }
 
+    /// Returns if `next` maps the same file right after `len` bytes mapped by this one.
+    pub fn is_contiguous(&self, next: &Self, len: usize) -> bool {
+        Arc::as_ptr(&self.file) as *const u8 == Arc::as_ptr(&next.file) as *const u8
+            && self.offset + len == next.offset
+    }
+
     /// Checks the given access flags.
     pub fn mprot(&self, prot: MmapProt) -> bool {
         (self.file.readable() || !prot.contains(MmapProt::PROT_READ))
@@ -154,5 +154,11 @@ bitflags::bitflags! {
         /// When swap space is not reserved one might get SIGSEGV upon a write if no
         /// physical memory is available.
         const MAP_NONRESERVE = 1 << 14;
+
+        /// This flag provides behavior that is similar to `MAP_FIXED` with respect to
+        /// the `addr` enforcement, but differs in that `MAP_FIXED_NOREPLACE` never
+        /// clobbers a preexisting mapped range. If the requested range would collide
+        /// with an existing mapping, then this call fails with the error `EEXIST`.
+        const MAP_FIXED_NOREPLACE = 1 << 20;
     }
 }
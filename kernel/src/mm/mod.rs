@@ -3,17 +3,36 @@ mod flags;
 mod kernel;
 pub mod vma;
 
-use alloc::{collections::BTreeMap, string::String, sync::Arc, vec::Vec};
-use core::{fmt, mem::size_of, slice};
+use alloc::{
+    collections::BTreeMap,
+    string::String,
+    sync::{Arc, Weak},
+    vec,
+    vec::Vec,
+};
+use core::{
+    fmt::{self, Write},
+    mem::size_of,
+    slice,
+};
 use errno::Errno;
-use syscall_interface::SyscallResult;
+use kernel_sync::SpinLock;
+use syscall_interface::{
+    SyscallResult, MADV_DONTNEED, MADV_NORMAL, MADV_RANDOM, MADV_SEQUENTIAL, MADV_WILLNEED,
+};
 use ubuf::UserBuffer;
+use vfs::Stat;
 
 use crate::{
-    arch::{mm::*, trap::__trampoline},
+    arch::{
+        flush_tlb_harts,
+        mm::*,
+        trap::{__sigreturn, __trampoline},
+    },
     config::*,
     error::*,
-    task::Task,
+    fs::{major, minor},
+    task::{ResourceLimits, Task},
 };
 
 pub use file::MmapFile;
@@ -49,6 +68,38 @@ pub struct MM {
 
     /// Heap pointer managed by `sys_brk`.
     pub brk: VirtAddr,
+
+    /// Bit mask of harts that have run in this address space, the tlb of which may
+    /// hold stale entries after the page table is modified.
+    harts: usize,
+
+    /// Resource limits of the process owning this address space, which bound the
+    /// stack even if it is accessed by other processes, see [`MM::check_user_access`].
+    limits: Weak<SpinLock<ResourceLimits>>,
+}
+
+/// Maps the trampoline and the page signal handlers return to, see [`MM::new`].
+fn map_trampoline(page_table: &mut PageTable) -> KernelResult {
+    page_table
+        .map(
+            VirtAddr::from(TRAMPOLINE_VA).into(),
+            PhysAddr::from(__trampoline as usize).into(),
+            PTEFlags::READABLE | PTEFlags::EXECUTABLE | PTEFlags::VALID,
+        )
+        .and_then(|_| {
+            page_table.map(
+                VirtAddr::from(SIGRETURN_VA).into(),
+                PhysAddr::from(__sigreturn as usize).into(),
+                PTEFlags::READABLE
+                    | PTEFlags::EXECUTABLE
+                    | PTEFlags::USER_ACCESSIBLE
+                    | PTEFlags::VALID,
+            )
+        })
+        .map_err(|err| {
+            log::warn!("{}", err);
+            KernelError::PageTableInvalid
+        })
 }
 
 /* Global operations */
@@ -60,6 +111,8 @@ impl MM {
     /// `Trampoline` is not collected or recorded by VMAs, since this area cannot
     /// be unmapped or modified manually by user. We set the page table flags without
     /// [`PTEFlags::USER_ACCESSIBLE`] so that malicious user cannot jump to this area.
+    /// The page at [`SIGRETURN_VA`] is mapped the same way but accessible by user,
+    /// which only issues `sigreturn`.
     pub fn new() -> KernelResult<Self> {
         match PageTable::new() {
             Ok(page_table) => {
@@ -72,18 +125,10 @@ impl MM {
                     entry: VirtAddr::zero(),
                     start_brk: VirtAddr::zero(),
                     brk: VirtAddr::zero(),
+                    harts: 0,
+                    limits: Weak::new(),
                 };
-                mm.page_table
-                    .map(
-                        VirtAddr::from(TRAMPOLINE_VA).into(),
-                        PhysAddr::from(__trampoline as usize).into(),
-                        PTEFlags::READABLE | PTEFlags::EXECUTABLE | PTEFlags::VALID,
-                    )
-                    .map_err(|err| {
-                        log::warn!("{}", err);
-                        KernelError::PageTableInvalid
-                    })
-                    .and(Ok(mm))
+                map_trampoline(&mut mm.page_table).and(Ok(mm))
             }
             Err(_) => Err(KernelError::FrameAllocFailed),
         }
@@ -104,6 +149,9 @@ impl MM {
                     end_va: vma.end_va,
                     frames: vma.frames.clone(),
                     file: vma.file.clone(),
+                    // locks are not inherited
+                    locked: vec![false; vma.locked.len()],
+                    backed: vma.backed.clone(),
                 };
 
                 // read-only
@@ -120,16 +168,9 @@ impl MM {
                 new_vma_list.push(None);
             }
         }
-        page_table
-            .map(
-                VirtAddr::from(TRAMPOLINE_VA).into(),
-                PhysAddr::from(__trampoline as usize).into(),
-                PTEFlags::READABLE | PTEFlags::EXECUTABLE | PTEFlags::VALID,
-            )
-            .map_err(|err| {
-                log::warn!("{}", err);
-                KernelError::PageTableInvalid
-            })?;
+        // Threads of the parent may write the pages through stale entries.
+        self.flush_tlb()?;
+        map_trampoline(&mut page_table)?;
         Ok(Self {
             page_table,
             vma_list: new_vma_list,
@@ -139,9 +180,27 @@ impl MM {
             entry: self.entry,
             start_brk: self.start_brk,
             brk: self.brk,
+            harts: 0,
+            limits: self.limits.clone(),
         })
     }
 
+    /// Sets the resource limits of the process owning this address space.
+    pub fn set_limits(&mut self, limits: &Arc<SpinLock<ResourceLimits>>) {
+        self.limits = Arc::downgrade(limits);
+    }
+
+    /// Records that the hart `hart` runs in this address space.
+    pub fn run_on(&mut self, hart: usize) {
+        self.harts |= 1 << hart;
+    }
+
+    /// Flushes tlb after the page table is modified, including other harts if threads
+    /// sharing this address space have run on them, which completes before returning.
+    pub fn flush_tlb(&self) -> KernelResult {
+        flush_tlb_harts(self.harts)
+    }
+
     /// A warpper for `translate` in `PageTable`.
     pub fn translate(&mut self, va: VirtAddr) -> KernelResult<PhysAddr> {
         self.page_table
@@ -149,11 +208,69 @@ impl MM {
             .map_err(|_| KernelError::PageTableInvalid)
     }
 
+    /// The number of pages locked by `mlock`.
+    pub fn locked_pages(&self) -> usize {
+        self.vma_list
+            .iter()
+            .flatten()
+            .map(|vma| vma.locked.iter().filter(|&&locked| locked).count())
+            .sum()
+    }
+
     /// The number of virtual memory areas.
     pub fn map_count(&mut self) -> usize {
         self.vma_map.len()
     }
 
+    /// Formats the memory map the same as `/proc/[pid]/maps`, one area a line in the
+    /// form of `start-end perms offset dev inode pathname`.
+    ///
+    /// Anonymous areas have no pathname, except `[heap]` and `[stack]`.
+    pub fn maps(&self) -> String {
+        let mut maps = String::new();
+        for (_, index) in &self.vma_map {
+            let vma = match &self.vma_list[*index] {
+                Some(vma) => vma,
+                None => continue,
+            };
+            let perm = |flag: VMFlags, ch: char| if vma.flags.contains(flag) { ch } else { '-' };
+            let shared = if vma.flags.contains(VMFlags::SHARED) {
+                's'
+            } else {
+                'p'
+            };
+            let mut stat = Stat::default();
+            let (offset, name) = match &vma.file {
+                Some(file) => {
+                    file.file().get_stat(&mut stat as *mut Stat);
+                    let path = file.file().get_path();
+                    let name = path.map_or(String::new(), |path| path.as_str().into());
+                    (file.offset(), name)
+                }
+                None if vma.contains(self.start_brk) => (0, "[heap]".into()),
+                None if vma.flags.contains(VMFlags::GROWSDOWN) => (0, "[stack]".into()),
+                None => (0, String::new()),
+            };
+            writeln!(
+                maps,
+                "{:08x}-{:08x} {}{}{}{} {:08x} {:02x}:{:02x} {} {}",
+                vma.start_va.value(),
+                vma.end_va.value(),
+                perm(VMFlags::READ, 'r'),
+                perm(VMFlags::WRITE, 'w'),
+                perm(VMFlags::EXEC, 'x'),
+                shared,
+                offset,
+                major(stat.st_dev),
+                minor(stat.st_dev),
+                stat.st_ino,
+                name
+            )
+            .unwrap();
+        }
+        maps
+    }
+
     pub fn mmap_min_addr(&self) -> VirtAddr {
         self.start_brk + USER_HEAP_SIZE
     }
@@ -278,18 +395,63 @@ impl MM {
 
         // No need to fllush TLB explicitly; old maps have been cleaned.
         self.add_vma(vma)?;
+        self.try_merge_vma(self.vma_cache.unwrap());
 
         Ok(start)
     }
 
+    /// Merges the area at `index` with its previous and next areas if they are
+    /// compatible, see [`VMArea::can_merge`]. Slots of the absorbed areas are recycled.
+    ///
+    /// Returns the index of the merged area. Nothing happens if the slot is empty.
+    ///
+    /// Unmapping only leaves holes between areas, so this function is called after
+    /// new areas are added or the flags of areas are changed.
+    pub fn try_merge_vma(&mut self, mut index: usize) -> usize {
+        let (start_va, end_va) = match &self.vma_list[index] {
+            Some(vma) => (vma.start_va, vma.end_va),
+            None => return index,
+        };
+
+        if let Some(&next) = self.vma_map.get(&end_va) {
+            let vma = self.vma_list[index].as_ref().unwrap();
+            if vma.can_merge(self.vma_list[next].as_ref().unwrap()) {
+                let next_vma = self.vma_list[next].take().unwrap();
+                self.vma_map.remove(&end_va);
+                self.vma_recycled.push(next);
+                self.vma_list[index].as_mut().unwrap().merge(next_vma);
+            }
+        }
+
+        if let Some((_, &prev)) = self.vma_map.range(..start_va).next_back() {
+            let prev_vma = self.vma_list[prev].as_ref().unwrap();
+            if prev_vma.can_merge(self.vma_list[index].as_ref().unwrap()) {
+                let vma = self.vma_list[index].take().unwrap();
+                self.vma_map.remove(&start_va);
+                self.vma_recycled.push(index);
+                self.vma_list[prev].as_mut().unwrap().merge(vma);
+                index = prev;
+            }
+        }
+
+        self.vma_cache = Some(index);
+        index
+    }
+
     /// Finds a free area.
     pub fn find_free_area(&self, hint: VirtAddr, len: usize) -> KernelResult<VirtAddr> {
         let mut last_end = VirtAddr::zero();
         let min_addr = self.mmap_min_addr();
         for (_, index) in self.vma_map.range(hint..) {
             if let Some(vma) = &self.vma_list[*index] {
-                if (vma.start_va - last_end).value() >= len && vma.start_va - len >= min_addr {
-                    return Ok(vma.start_va - len);
+                // Keep the guard page below the stack unmapped.
+                let end = if vma.flags.contains(VMFlags::GROWSDOWN) {
+                    vma.start_va - GUARD_PAGE
+                } else {
+                    vma.start_va
+                };
+                if end >= last_end && (end - last_end).value() >= len && end - len >= min_addr {
+                    return Ok(end - len);
                 }
                 last_end = vma.end_va;
             }
@@ -297,6 +459,48 @@ impl MM {
         Err(KernelError::VMAAllocFailed)
     }
 
+    /// Grows the stack down to cover the virtual address, if it is at most
+    /// [`USER_STACK_GROW_WINDOW`] below an area with [`VMFlags::GROWSDOWN`].
+    ///
+    /// The stack cannot be larger than `max_size`, and a [`GUARD_PAGE`] is kept
+    /// between the stack and the area below it. Frames of new pages are allocated
+    /// lazily.
+    pub fn grow_stack(&mut self, va: VirtAddr, max_size: usize) -> KernelResult {
+        let index = match self.vma_map.range(va..).next() {
+            Some((_, index)) => *index,
+            None => return Err(KernelError::PageUnmapped),
+        };
+        let prev_end = self
+            .vma_map
+            .range(..va)
+            .last()
+            .and_then(|(_, index)| self.vma_list[*index].as_ref())
+            .map_or(VirtAddr::zero(), |vma| vma.end_va);
+        let new_start = Page::from(va).start_address();
+
+        let vma = self.vma_list[index]
+            .as_mut()
+            .ok_or(KernelError::PageUnmapped)?;
+        if vma.start_va <= va {
+            return Ok(());
+        }
+        if !vma.flags.contains(VMFlags::GROWSDOWN)
+            || (vma.start_va - va).value() > USER_STACK_GROW_WINDOW
+        {
+            return Err(KernelError::PageUnmapped);
+        }
+        if (vma.end_va - new_start).value() > max_size || prev_end + GUARD_PAGE > new_start {
+            return Err(KernelError::FatalPageFault);
+        }
+        let old_start = vma.start_va;
+        unsafe { vma.extend_down(new_start) };
+
+        self.vma_map.remove(&old_start);
+        self.vma_map.insert(new_start, index);
+        self.vma_cache = Some(index);
+        Ok(())
+    }
+
     /// Gets the virtual memory area that contains the virutal address.
     /// Applies the given operation to the target area.
     ///
@@ -415,7 +619,16 @@ impl MM {
     /// # Argument
     /// - `va`: starting virtual address
     /// - `len`: total length of the buffer
-    pub fn get_buf_mut(&mut self, va: VirtAddr, len: usize) -> KernelResult<UserBuffer> {
+    /// - `flags`: access to the buffer, e.g. [`VMFlags::WRITE`] if the kernel writes it
+    ///
+    /// Returns `EFAULT` if the range is not covered by mappings granting `flags`.
+    pub fn get_buf_mut(
+        &mut self,
+        va: VirtAddr,
+        len: usize,
+        flags: VMFlags,
+    ) -> KernelResult<UserBuffer> {
+        self.check_user_access(va, len, flags)?;
         let mut start_va = va;
         let end_va = start_va + len;
         let mut v = Vec::new();
@@ -432,6 +645,110 @@ impl MM {
         Ok(UserBuffer::new(v))
     }
 
+    /// Checks if the range of [va, va + len) is covered by virtual memory areas,
+    /// each of which grants all of the access `flags`.
+    ///
+    /// The stack grows down to cover the range if needed, the same as a page fault from
+    /// user, up to the stack limit of the process owning this address space, see
+    /// [`MM::grow_stack`].
+    ///
+    /// Returns `EFAULT` if any page in this range is unmapped or the access is
+    /// not permitted, e.g. writing to a read-only mapping.
+    pub fn check_user_access(&mut self, va: VirtAddr, len: usize, flags: VMFlags) -> KernelResult {
+        if va.value().checked_add(len).is_none() {
+            return Err(KernelError::Errno(Errno::EFAULT));
+        }
+        let end_va = va + len;
+        let mut start_va = va;
+        while start_va < end_va {
+            if self.get_vma(start_va, |_, _, _| Ok(())).is_err() {
+                let stack_limit = self
+                    .limits
+                    .upgrade()
+                    .map_or(0, |limits| limits.lock().max_stack_size());
+                self.grow_stack(start_va, stack_limit)
+                    .map_err(|_| KernelError::Errno(Errno::EFAULT))?;
+            }
+            start_va = self
+                .get_vma(start_va, |vma, _, _| {
+                    if vma.flags.contains(flags) {
+                        Ok(vma.end_va)
+                    } else {
+                        Err(KernelError::Errno(Errno::EFAULT))
+                    }
+                })
+                .map_err(|_| KernelError::Errno(Errno::EFAULT))?;
+        }
+        Ok(())
+    }
+
+    /// Translates the range of [va, va + len) page by page and applies `op` on each
+    /// piece of the user buffer with its offset in the range.
+    ///
+    /// The range must have been checked by [`MM::check_user_access`]. Returns `ENOMEM`
+    /// if a frame cannot be allocated, or `EFAULT` if a page cannot be mapped.
+    fn for_each_user_page(
+        &mut self,
+        va: VirtAddr,
+        len: usize,
+        mut op: impl FnMut(&mut [u8], usize),
+    ) -> KernelResult {
+        let end_va = va + len;
+        let mut start_va = va;
+        let mut pos = 0;
+        while start_va < end_va {
+            let next_page = Page::from(start_va) + 1;
+            let page_off = start_va.page_offset();
+            let page_len: usize = (end_va - start_va)
+                .min(next_page.start_address() - start_va)
+                .into();
+            let frame = self.alloc_frame(start_va).map_err(|err| match err {
+                KernelError::FrameAllocFailed => KernelError::Errno(Errno::ENOMEM),
+                _ => KernelError::Errno(Errno::EFAULT),
+            })?;
+            op(&mut frame.as_slice_mut()[page_off..page_off + page_len], pos);
+            start_va += page_len;
+            pos += page_len;
+        }
+        Ok(())
+    }
+
+    /// Copies `len` bytes from user address space starting at `va`.
+    ///
+    /// Returns `EFAULT` if any page in the range is unmapped or not readable, or
+    /// `ENOMEM` if the buffer of `len` bytes cannot be allocated. Large transfers
+    /// should use [`MM::copy_from_user_into`] with a bounded buffer instead.
+    pub fn copy_from_user(&mut self, va: VirtAddr, len: usize) -> KernelResult<Vec<u8>> {
+        self.check_user_access(va, len, VMFlags::READ)?;
+        let mut data = Vec::new();
+        data.try_reserve_exact(len)
+            .map_err(|_| KernelError::Errno(Errno::ENOMEM))?;
+        data.resize(len, 0);
+        self.copy_from_user_into(va, &mut data)?;
+        Ok(data)
+    }
+
+    /// Copies bytes from user address space starting at `va` to fill `buf`.
+    ///
+    /// Returns `EFAULT` if any page in the range is unmapped or not readable.
+    pub fn copy_from_user_into(&mut self, va: VirtAddr, buf: &mut [u8]) -> KernelResult {
+        self.check_user_access(va, buf.len(), VMFlags::READ)?;
+        self.for_each_user_page(va, buf.len(), |src, pos| {
+            buf[pos..pos + src.len()].copy_from_slice(src)
+        })
+    }
+
+    /// Copies bytes to user address space starting at `va`.
+    ///
+    /// Returns `EFAULT` if any page in the range is unmapped or not writable, in
+    /// which case nothing is written.
+    pub fn copy_to_user(&mut self, va: VirtAddr, data: &[u8]) -> KernelResult {
+        self.check_user_access(va, data.len(), VMFlags::WRITE)?;
+        self.for_each_user_page(va, data.len(), |dst, pos| {
+            dst.copy_from_slice(&data[pos..pos + dst.len()])
+        })
+    }
+
     /// Gets a string loaded from starting virtual address.
     ///
     /// # Argument
@@ -449,6 +766,7 @@ impl MM {
                 alloc = true;
             }
             if alloc {
+                self.check_user_access(va, 1, VMFlags::READ)?;
                 frame = self.alloc_frame(va)?;
                 alloc = false;
             }
@@ -510,8 +828,10 @@ pub fn page_range(start_va: VirtAddr, end_va: VirtAddr) -> PageRange {
 #[macro_export]
 macro_rules! read_user {
     ($mm:expr, $addr:expr, $item:expr, $ty:ty) => {{
-        let ubuf = $mm.get_buf_mut($addr, core::mem::size_of::<$ty>())?;
-        ubuf::read_user_buf!(ubuf, $ty, $item);
+        let buf = unsafe {
+            core::slice::from_raw_parts_mut(&mut $item as *mut _ as *mut u8, core::mem::size_of::<$ty>())
+        };
+        $mm.copy_from_user_into($addr, buf)?;
         Ok::<(), Errno>(())
     }};
 }
@@ -520,8 +840,12 @@ macro_rules! read_user {
 #[macro_export]
 macro_rules! write_user {
     ($mm:expr, $addr:expr, $item:expr, $ty:ty) => {{
-        let ubuf = $mm.get_buf_mut($addr, core::mem::size_of::<$ty>())?;
-        ubuf::write_user_buf!(ubuf, $ty, $item);
+        // Bind the value first, so that a temporary outlives the slice over it.
+        let item: $ty = $item;
+        let data = unsafe {
+            core::slice::from_raw_parts(&item as *const $ty as *const u8, core::mem::size_of::<$ty>())
+        };
+        $mm.copy_to_user($addr, data)?;
         Ok::<(), Errno>(())
     }};
 }
@@ -628,7 +952,8 @@ pub fn do_munmap(mm: &mut MM, start: VirtAddr, len: usize) -> KernelResult {
             mm.add_vma(new_vma).unwrap();
         }
     }
-    Ok(())
+    // Other threads may still access the frames unmapped.
+    mm.flush_tlb()
 }
 
 /// A helper for [`syscall_interface::SyscallProc::mprotect`].
@@ -694,11 +1019,141 @@ pub fn do_mprotect(mm: &mut MM, start: VirtAddr, len: usize, prot: MmapProt) ->
         }
     }
 
+    for index in mm.get_vma_range(start, end)? {
+        mm.vma_list[index]
+            .as_ref()
+            .unwrap()
+            .protect(&mut mm.page_table);
+    }
+    mm.flush_tlb()?;
+
+    // Areas split above may have the same flags as their neighbors now.
+    for index in mm.get_vma_range(start, end)? {
+        mm.try_merge_vma(index);
+    }
+    Ok(0)
+}
+
+/// Checks if all pages in the range are mapped, returning whether each page is locked.
+///
+/// Returns `ENOMEM` if some page is not mapped, or `EINVAL` if it cannot be locked.
+fn get_locked(mm: &mut MM, pages: &PageRange) -> Result<Vec<bool>, Errno> {
+    pages
+        .range()
+        .map(|page| {
+            mm.get_vma(page.start_address(), |vma, _, _| {
+                if vma.flags.contains(VMFlags::IDENTICAL) {
+                    return Err(KernelError::InvalidArgs);
+                }
+                Ok(vma.locked[page_index(vma.start_va, page.start_address())])
+            })
+            .map_err(|err| match err {
+                KernelError::PageUnmapped => Errno::ENOMEM,
+                _ => Errno::EINVAL,
+            })
+        })
+        .collect()
+}
+
+/// A helper for [`syscall_interface::SyscallProc::madvise`].
+///
+/// `MADV_DONTNEED` drops the frames in the range except locked ones, while `MADV_WILLNEED` faults them in
+/// eagerly. Other advice only gives hints about the access pattern, which is ignored.
+pub fn do_madvise(mm: &mut MM, start: VirtAddr, len: usize, advice: usize) -> SyscallResult {
+    log::trace!("MADVISE [{:?}, {:?}), {}", start, start + len, advice);
+
+    if !matches!(
+        advice,
+        MADV_NORMAL | MADV_RANDOM | MADV_SEQUENTIAL | MADV_WILLNEED | MADV_DONTNEED
+    ) || !start.is_aligned()
+    {
+        return Err(Errno::EINVAL);
+    }
+    if len == 0 {
+        return Ok(0);
+    }
+    let pages = page_range(start, start + len);
+    get_locked(mm, &pages)?;
+
+    match advice {
+        MADV_WILLNEED => {
+            for page in pages.range() {
+                mm.get_vma(page.start_address(), |vma, pt, _| {
+                    if vma.frames[page_index(vma.start_va, page.start_address())].is_none() {
+                        vma.alloc_frame(page, pt)?;
+                    }
+                    Ok(())
+                })?;
+            }
+        }
+        MADV_DONTNEED => {
+            for page in pages.range() {
+                mm.get_vma(page.start_address(), |vma, pt, _| {
+                    vma.dealloc_frame(page, pt);
+                    Ok(())
+                })?;
+            }
+            // Other threads may still access the frames dropped.
+            mm.flush_tlb()?;
+        }
+        _ => {}
+    }
+    Ok(0)
+}
+
+/// A helper for [`syscall_interface::SyscallProc::mlock`].
+///
+/// Pages in the range are faulted in and locked, while the total size of locked pages
+/// must not exceed `limit` bytes.
+pub fn do_mlock(mm: &mut MM, start: VirtAddr, len: usize, limit: usize) -> SyscallResult {
+    log::trace!("MLOCK [{:?}, {:?})", start, start + len);
+
+    if len == 0 {
+        return Ok(0);
+    }
+    let pages = page_range(start, start + len);
+    let new_locked = get_locked(mm, &pages)?
+        .into_iter()
+        .filter(|&locked| !locked)
+        .count();
+    if (mm.locked_pages() + new_locked) * PAGE_SIZE > limit {
+        return Err(Errno::ENOMEM);
+    }
+
+    for page in pages.range() {
+        mm.get_vma(page.start_address(), |vma, pt, _| {
+            vma.alloc_frame(page, pt)?;
+            vma.locked[page_index(vma.start_va, page.start_address())] = true;
+            Ok(())
+        })?;
+    }
+    Ok(0)
+}
+
+/// A helper for [`syscall_interface::SyscallProc::munlock`].
+pub fn do_munlock(mm: &mut MM, start: VirtAddr, len: usize) -> SyscallResult {
+    log::trace!("MUNLOCK [{:?}, {:?})", start, start + len);
+
+    if len == 0 {
+        return Ok(0);
+    }
+    let pages = page_range(start, start + len);
+    get_locked(mm, &pages)?;
+
+    for page in pages.range() {
+        mm.get_vma(page.start_address(), |vma, _, _| {
+            vma.locked[page_index(vma.start_va, page.start_address())] = false;
+            Ok(())
+        })?;
+    }
     Ok(0)
 }
 
 /// A helper for [`syscall_interface::SyscallProc::mmap`].
 ///
+/// Without `MAP_FIXED` or `MAP_FIXED_NOREPLACE`, a non-zero `hint` is taken only if
+/// the range is not mapped, otherwise the kernel finds another area.
+///
 /// TODO: MAP_SHARED and MAP_PRIVATE
 pub fn do_mmap(
     task: &Task,
@@ -733,8 +1188,19 @@ pub fn do_mmap(
         return Err(Errno::ENOMEM);
     }
 
-    // Find an available area by kernel.
-    let anywhere = hint == VirtAddr::zero() && !flags.contains(MmapFlags::MAP_FIXED);
+    // A fixed address must not be lower than the heap, which is replaced if mapped
+    // unless `MAP_FIXED_NOREPLACE` is set.
+    let fixed = flags.intersects(MmapFlags::MAP_FIXED | MmapFlags::MAP_FIXED_NOREPLACE);
+    if fixed && hint < mm.mmap_min_addr() {
+        return Err(Errno::EPERM);
+    }
+    let mapped = !mm.get_vma_range(hint, hint + len)?.is_empty();
+    if mapped && flags.contains(MmapFlags::MAP_FIXED_NOREPLACE) {
+        return Err(Errno::EEXIST);
+    }
+
+    // Find an available area by kernel, if the hint cannot be taken.
+    let anywhere = !fixed && (mapped || hint < mm.mmap_min_addr());
 
     // Handle different cases indicated by `MmapFlags`.
     if flags.contains(MmapFlags::MAP_ANONYMOUS) {
@@ -750,7 +1216,7 @@ pub fn do_mmap(
 
     // Map to backend file.
     if let Ok(file) = task.files().get(fd) {
-        if !file.is_reg() || !file.read_ready() {
+        if !file.is_reg() || !file.readable() {
             return Err(Errno::EACCES);
         }
         if let Some(_) = file.seek(off, vfs::SeekWhence::Set) {
@@ -781,15 +1247,30 @@ pub fn do_mmap(
 
 /// A page fault helper for [`crate::trap::user_trap_handler`].
 ///
-/// Store page fault might be caused by:
+/// Load or store page fault might be caused by:
 /// 1. Frame not allocated yet;
 /// 2. Unable to write (COW);
-pub fn do_handle_page_fault(mm: &mut MM, va: VirtAddr, flags: VMFlags) -> KernelResult {
+/// 3. Stack growing down, up to `stack_limit` bytes, see [`MM::grow_stack`].
+pub fn do_handle_page_fault(
+    mm: &mut MM,
+    va: VirtAddr,
+    flags: VMFlags,
+    stack_limit: usize,
+) -> KernelResult {
+    if mm.get_vma(va, |_, _, _| Ok(())).is_err() {
+        mm.grow_stack(va, stack_limit)?;
+    }
+
+    // Set if a read-only page is copied on write.
+    let mut cow = false;
     mm.get_vma(va, |vma, pt, _| {
         if !vma.flags.contains(flags) {
             return Err(KernelError::FatalPageFault);
         }
 
+        cow = pt
+            .walk(Page::from(va))
+            .map_or(false, |(_, pte)| pte.flags().is_valid());
         let (_, alloc) = vma.alloc_frame(Page::from(va), pt)?;
 
         if !alloc {
@@ -797,5 +1278,11 @@ pub fn do_handle_page_fault(mm: &mut MM, va: VirtAddr, flags: VMFlags) -> Kernel
         }
 
         Ok(())
-    })
+    })?;
+
+    // Other threads may still read the old frame.
+    if cow {
+        mm.flush_tlb()?;
+    }
+    Ok(())
 }
@@ -1,6 +1,6 @@
 use core::fmt;
 
-use alloc::{sync::Arc, vec::Vec};
+use alloc::{sync::Arc, vec, vec::Vec};
 use log::warn;
 
 use crate::{
@@ -27,6 +27,14 @@ pub struct VMArea {
 
     /// Backed by file wihch can be None.
     pub file: Option<Arc<MmapFile>>,
+
+    /// Pages locked by `mlock`, which are always resident.
+    pub locked: Vec<bool>,
+
+    /// Bytes of each page read from the backend file, which are written back when the
+    /// frame is reclaimed. Pages faulted in beyond the end of file are anonymous, thus
+    /// zero bytes are backed.
+    pub backed: Vec<usize>,
 }
 
 impl VMArea {
@@ -45,6 +53,8 @@ impl VMArea {
             flags,
             start_va,
             end_va,
+            locked: vec![false; frames.len()],
+            backed: vec![0; frames.len()],
             frames,
             file,
         })
@@ -71,6 +81,8 @@ impl VMArea {
             end_va,
             frames,
             file,
+            locked: vec![false; count],
+            backed: vec![0; count],
         })
     }
 
@@ -91,6 +103,8 @@ impl VMArea {
             flags,
             start_va,
             end_va,
+            locked: vec![false; frames.len()],
+            backed: vec![0; frames.len()],
             frames,
             file: None,
         })
@@ -118,18 +132,59 @@ impl VMArea {
     pub unsafe fn extend(&mut self, new_end: VirtAddr) {
         self.end_va = new_end;
         self.frames.resize_with(self.size_in_pages(), || None);
+        self.locked.resize(self.size_in_pages(), false);
+        self.backed.resize(self.size_in_pages(), 0);
+    }
+
+    /// Extends an area with new start, used by the stack growing down.
+    ///
+    /// The same as [`Self::extend`], this function does not check overlaps.
+    pub unsafe fn extend_down(&mut self, new_start: VirtAddr) {
+        let count = page_count(new_start, self.start_va);
+        self.frames.splice(0..0, (0..count).map(|_| None));
+        self.locked.splice(0..0, (0..count).map(|_| false));
+        self.backed.splice(0..0, (0..count).map(|_| 0));
+        self.start_va = new_start;
+    }
+
+    /// Returns if `next` starts right at the end of this area and can be merged into it,
+    /// which requires the same flags and either no backend file or contiguous offsets
+    /// of the same file.
+    pub fn can_merge(&self, next: &VMArea) -> bool {
+        self.end_va == next.start_va
+            && self.flags == next.flags
+            && match (&self.file, &next.file) {
+                (None, None) => true,
+                (Some(file), Some(next_file)) => {
+                    file.is_contiguous(next_file, self.size_in_pages() * PAGE_SIZE)
+                }
+                _ => false,
+            }
+    }
+
+    /// Merges the following area into this one, see [`Self::can_merge`].
+    ///
+    /// Frames of the following area are moved without modifying the page table.
+    pub fn merge(&mut self, mut next: VMArea) {
+        self.end_va = next.end_va;
+        self.frames.append(&mut next.frames);
+        self.locked.append(&mut next.locked);
+        self.backed.append(&mut next.backed);
     }
 
     /// Gets the frame by index.
+    ///
+    /// A new frame is filled with the page of the backend file if any, the tail of
+    /// which beyond the end of file is left zeroed.
     pub fn get_frame(&mut self, index: usize, alloc: bool) -> KernelResult<Frame> {
         if let Some(frame) = &self.frames[index] {
             Ok((*frame.as_ref()).clone())
         } else if alloc {
             let frame = AllocatedFrame::new(true).map_err(|_| KernelError::FrameAllocFailed)?;
             if let Some(file) = &self.file {
-                if file.read(index * PAGE_SIZE, frame.as_slice_mut()).is_none() {
-                    return Err(KernelError::VMAFailedIO);
-                }
+                self.backed[index] = file
+                    .read(index * PAGE_SIZE, frame.as_slice_mut())
+                    .ok_or(KernelError::VMAFailedIO)?;
             }
             let frame_inner = frame.clone();
             // ownership moved
@@ -141,14 +196,17 @@ impl VMArea {
     }
 
     /// Reclaims the frame by index, writing back to file if before the [`AllocatedFrame`] dropped.
+    ///
+    /// Only the bytes read from the file are written back, thus the file never grows.
     pub fn reclaim_frame(&mut self, index: usize) -> Option<Arc<AllocatedFrame>> {
         if let Some(frame) = self.frames[index].take() {
-            if self.file.is_some() && Arc::strong_count(&frame) == 1 {
+            let backed = core::mem::take(&mut self.backed[index]);
+            if backed != 0 && Arc::strong_count(&frame) == 1 {
                 // TODO: wirte if dirty
                 self.file
                     .as_ref()
                     .unwrap()
-                    .write(index * PAGE_SIZE, frame.as_slice());
+                    .write(index * PAGE_SIZE, &frame.as_slice()[..backed]);
             }
             Some(frame)
         } else {
@@ -219,6 +277,32 @@ impl VMArea {
         Ok(())
     }
 
+    /// Updates the permissions of mapped pages to the flags of this area.
+    ///
+    /// Write permission is only revoked but never granted here, since the frame may be
+    /// shared copy-on-write, which is handled by the store page fault. TLB is not flushed.
+    pub fn protect(&self, pt: &mut PageTable) {
+        let perms = PTEFlags::READABLE | PTEFlags::WRITABLE | PTEFlags::EXECUTABLE;
+        let flags = PTEFlags::from(self.flags);
+        for (page, frame) in page_range(self.start_va, self.end_va)
+            .range()
+            .zip(self.frames.iter())
+        {
+            if frame.is_none() {
+                continue;
+            }
+            if let Ok((pte_pa, mut pte)) = pt.walk(page) {
+                let old = pte.flags();
+                if !old.is_valid() {
+                    continue;
+                }
+                let granted = flags & (PTEFlags::READABLE | PTEFlags::EXECUTABLE);
+                pte.set_flags((old - perms) | granted | (old & flags & PTEFlags::WRITABLE));
+                pte.write(pte_pa);
+            }
+        }
+    }
+
     /// Allocates a frame for mapped page.
     ///
     /// Returns true if a new frame is really allocated.
@@ -252,6 +336,20 @@ impl VMArea {
         Ok((pte.frame(), false))
     }
 
+    /// Deallocates the frame mapped to the page and clears its page table entry, thus
+    /// the next access faults in a zeroed frame, or the contents of the file again.
+    ///
+    /// Locked pages are kept, and the TLB is not flushed.
+    pub fn dealloc_frame(&mut self, page: Page, pt: &mut PageTable) {
+        let index = page_index(self.start_va, page.start_address());
+        if self.locked[index] {
+            return;
+        }
+        if self.reclaim_frame(index).is_some() {
+            pt.unmap(page);
+        }
+    }
+
     /// Splits an area with aligned virtual address range.
     ///
     /// Six cases in total:
@@ -284,7 +382,7 @@ impl VMArea {
         {
             (None, None)
         } else if self.start_va < start && end < self.end_va {
-            let right_vma = Some(
+            let mut right_vma = Some(
                 Self::new(
                     end,
                     self.end_va,
@@ -296,7 +394,7 @@ impl VMArea {
                 )
                 .unwrap(),
             );
-            let mid_vma = Some(
+            let mut mid_vma = Some(
                 Self::new(
                     start,
                     end,
@@ -310,10 +408,14 @@ impl VMArea {
             );
 
             self.end_va = start;
+            right_vma.as_mut().unwrap().locked = self.locked.split_off(end_idx);
+            mid_vma.as_mut().unwrap().locked = self.locked.split_off(start_idx);
+            right_vma.as_mut().unwrap().backed = self.backed.split_off(end_idx);
+            mid_vma.as_mut().unwrap().backed = self.backed.split_off(start_idx);
 
             (mid_vma, right_vma)
         } else if self.start_va < start && self.end_va <= end {
-            let right_vma = Some(
+            let mut right_vma = Some(
                 Self::new(
                     start,
                     self.end_va,
@@ -327,10 +429,12 @@ impl VMArea {
             );
 
             self.end_va = start;
+            right_vma.as_mut().unwrap().locked = self.locked.split_off(start_idx);
+            right_vma.as_mut().unwrap().backed = self.backed.split_off(start_idx);
 
             (right_vma, None)
         } else if start <= self.start_va && end < self.end_va {
-            let left_vma = Some(
+            let mut left_vma = Some(
                 Self::new(
                     self.start_va,
                     end,
@@ -342,6 +446,8 @@ impl VMArea {
             );
 
             self.start_va = end;
+            left_vma.as_mut().unwrap().locked = self.locked.drain(..end_idx).collect();
+            left_vma.as_mut().unwrap().backed = self.backed.drain(..end_idx).collect();
             self.file = self
                 .file
                 .as_ref()
@@ -0,0 +1,21 @@
+//! A pseudo-random number generator shared by the kernel, which is fast but not
+//! cryptographically secure.
+
+use kernel_sync::SpinLock;
+use spin::Lazy;
+
+use crate::arch::timer::get_cycle;
+
+/// State of the xorshift generator, seeded with the cycle counter on first use.
+static STATE: Lazy<SpinLock<u64>> = Lazy::new(|| SpinLock::new(get_cycle() as u64 | 1));
+
+/// Fills the buffer with pseudo-random bytes.
+pub fn fill_random(buf: &mut [u8]) {
+    let mut state = STATE.lock();
+    for chunk in buf.chunks_mut(8) {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        chunk.copy_from_slice(&state.to_le_bytes()[..chunk.len()]);
+    }
+}
@@ -0,0 +1,146 @@
+//! Locks that can be debugged.
+//!
+//! With the feature `lock_debug`, [`SpinLock`] records the hart holding it and where it
+//! is acquired, and panics with the holder's information if the same hart acquires it
+//! again or a hart spins too long on it. Otherwise it is the bare
+//! [`kernel_sync::SpinLock`].
+
+#[cfg(not(feature = "lock_debug"))]
+pub use kernel_sync::{SpinLock, SpinLockGuard};
+
+#[cfg(feature = "lock_debug")]
+pub use debug::{SpinLock, SpinLockGuard};
+
+#[cfg(feature = "lock_debug")]
+mod debug {
+    use core::{
+        mem::ManuallyDrop,
+        ops::{Deref, DerefMut},
+        panic::Location,
+    };
+    use time_subsys::SeqLock;
+
+    use crate::arch::get_cpu_id;
+
+    /// Number of failed attempts before a hart is considered deadlocked.
+    const SPIN_LIMIT: usize = 1 << 26;
+
+    /// A [`kernel_sync::SpinLock`] tracking its holder.
+    pub struct SpinLock<T> {
+        inner: kernel_sync::SpinLock<T>,
+
+        /// Hart holding this lock and the caller location where it is acquired,
+        /// which are always read together.
+        holder: SeqLock<Option<(usize, &'static Location<'static>)>>,
+    }
+
+    impl<T> SpinLock<T> {
+        pub fn new(data: T) -> Self {
+            Self {
+                inner: kernel_sync::SpinLock::new(data),
+                holder: SeqLock::new(None),
+            }
+        }
+
+        /// Returns the hart holding this lock and where it is acquired.
+        pub fn holder(&self) -> Option<(usize, &'static Location<'static>)> {
+            self.holder.read()
+        }
+
+        /// Returns where this lock is acquired if current hart already holds it, thus
+        /// acquiring it again deadlocks.
+        pub fn self_deadlock(&self) -> Option<&'static Location<'static>> {
+            self.holder()
+                .filter(|(owner, _)| *owner == get_cpu_id())
+                .map(|(_, location)| location)
+        }
+
+        /// Acquires this lock, recording the caller location.
+        ///
+        /// # Panic
+        ///
+        /// Panics if current hart already holds this lock, or fails to acquire it after
+        /// [`SPIN_LIMIT`] attempts.
+        #[track_caller]
+        pub fn lock(&self) -> SpinLockGuard<T> {
+            let hart = get_cpu_id();
+            if let Some(location) = self.self_deadlock() {
+                panic!(
+                    "Hart {} acquires the lock at {} again, held since {}",
+                    hart,
+                    Location::caller(),
+                    location
+                );
+            }
+
+            let mut spins = 0;
+            let guard = loop {
+                if let Some(guard) = self.inner.try_lock() {
+                    break guard;
+                }
+                spins += 1;
+                if spins == SPIN_LIMIT {
+                    match self.holder() {
+                        Some((owner, location)) => panic!(
+                            "Hart {} spins too long at {} on the lock held by hart {} since {}",
+                            hart,
+                            Location::caller(),
+                            owner,
+                            location
+                        ),
+                        None => panic!("Hart {} spins too long at {}", hart, Location::caller()),
+                    }
+                }
+                core::hint::spin_loop();
+            };
+            self.hold(guard)
+        }
+
+        /// Tries to acquire this lock without spinning, recording the caller location.
+        #[track_caller]
+        pub fn try_lock(&self) -> Option<SpinLockGuard<T>> {
+            let guard = self.inner.try_lock()?;
+            Some(self.hold(guard))
+        }
+
+        /// Records current hart as the holder of the acquired lock.
+        #[track_caller]
+        fn hold<'a>(&'a self, guard: kernel_sync::SpinLockGuard<'a, T>) -> SpinLockGuard<'a, T> {
+            let location = Location::caller();
+            self.holder
+                .write(|holder| *holder = Some((get_cpu_id(), location)));
+            SpinLockGuard {
+                lock: self,
+                guard: ManuallyDrop::new(guard),
+            }
+        }
+    }
+
+    /// A guard of [`SpinLock`] clearing the holder once dropped.
+    pub struct SpinLockGuard<'a, T> {
+        lock: &'a SpinLock<T>,
+        guard: ManuallyDrop<kernel_sync::SpinLockGuard<'a, T>>,
+    }
+
+    impl<T> Deref for SpinLockGuard<'_, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T {
+            &self.guard
+        }
+    }
+
+    impl<T> DerefMut for SpinLockGuard<'_, T> {
+        fn deref_mut(&mut self) -> &mut T {
+            &mut self.guard
+        }
+    }
+
+    impl<T> Drop for SpinLockGuard<'_, T> {
+        fn drop(&mut self) {
+            // Cleared before released, so the next holder is not overwritten.
+            self.lock.holder.write(|holder| *holder = None);
+            unsafe { ManuallyDrop::drop(&mut self.guard) };
+        }
+    }
+}
@@ -1,26 +1,174 @@
-use alloc::sync::Arc;
+use alloc::{
+    sync::{Arc, Weak},
+    vec,
+    vec::Vec,
+};
+use core::mem::size_of;
 use errno::Errno;
 use signal_defs::*;
-use syscall_interface::{SyscallComm, SyscallResult};
+use syscall_interface::*;
+use time_subsys::{TimeSpec, NSEC_PER_SEC};
+use vfs::{File, OpenFlags};
 
-use crate::{arch::mm::VirtAddr, fs::Pipe, read_user, task::cpu, write_user};
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    config::MAX_PIPE_BUF,
+    fs::Pipe,
+    read_user,
+    task::{
+        cpu, do_futex_wait, do_futex_wake, do_sigreturn, find_task, send_signal,
+        send_signal_thread, FutexKey, Task, TaskState,
+    },
+    write_user,
+};
 
-use super::SyscallImpl;
+use super::{
+    file::{raise_sigpipe, read_iovecs},
+    SyscallImpl,
+};
+
+/// Splits `len` bytes starting at byte `off` of the concatenated buffers in `iovs`
+/// into pieces of user memory.
+fn iovec_segments(iovs: &[IoVec], mut off: usize, mut len: usize) -> Vec<(VirtAddr, usize)> {
+    let mut segs = Vec::new();
+    for vec in iovs {
+        if len == 0 {
+            break;
+        }
+        if off >= vec.iov_len {
+            off -= vec.iov_len;
+            continue;
+        }
+        let seg_len = (vec.iov_len - off).min(len);
+        segs.push((VirtAddr::from(vec.iov_base + off), seg_len));
+        off = 0;
+        len -= seg_len;
+    }
+    segs
+}
+
+/// Returns true if `curr` may access the address space of `target`, which belongs to
+/// the thread group of `curr` or one of its descendants.
+///
+/// Credentials are not supported, thus all tasks are owned by the same user, and the
+/// access is restricted like the Yama security module of Linux does for `ptrace(2)`.
+fn may_access_mm(curr: &Task, target: &Arc<Task>) -> bool {
+    let mut task = Some(target.clone());
+    while let Some(ancestor) = task {
+        if ancestor.pid == curr.pid {
+            return true;
+        }
+        task = ancestor
+            .locked_inner()
+            .parent
+            .as_ref()
+            .and_then(Weak::upgrade);
+    }
+    false
+}
+
+/// A helper for [`SyscallComm::process_vm_readv`] and [`SyscallComm::process_vm_writev`],
+/// like `process_vm_rw` in Linux.
+///
+/// Data is transferred through a bounce buffer of a page, with the address spaces
+/// locked one at a time, since the remote process may be current process itself.
+/// The transfer stops at the first fault, returning the bytes transferred before it.
+///
+/// Returns `EPERM` if current task may not access the remote process, see
+/// [`may_access_mm`].
+fn process_vm_rw(
+    pid: usize,
+    local_iov: *const IoVec,
+    liovcnt: usize,
+    remote_iov: *const IoVec,
+    riovcnt: usize,
+    flags: usize,
+    write: bool,
+) -> SyscallResult {
+    if flags != 0 {
+        return Err(Errno::EINVAL);
+    }
+    let curr = cpu().curr.as_ref().unwrap();
+    let local = read_iovecs(curr, local_iov, liovcnt)?;
+    let remote = read_iovecs(curr, remote_iov, riovcnt)?;
+    let target = find_task(pid)
+        .filter(|task| task.get_state() != TaskState::ZOMBIE)
+        .ok_or(Errno::ESRCH)?;
+    if !may_access_mm(curr, &target) {
+        return Err(Errno::EPERM);
+    }
+
+    let local_len: usize = local.iter().map(|vec| vec.iov_len).sum();
+    let mut buf = vec![0u8; PAGE_SIZE];
+    let mut done = 0;
+    for vec in remote.iter().filter(|vec| vec.iov_len != 0) {
+        let len = vec.iov_len.min(local_len - done);
+        if len == 0 {
+            break;
+        }
+        let base = VirtAddr::from(vec.iov_base);
+        let mut pos = 0;
+        let mut result = || -> Result<(), Errno> {
+            while pos < len {
+                let chunk = &mut buf[..(len - pos).min(PAGE_SIZE)];
+                let segs = iovec_segments(&local, done + pos, chunk.len());
+                if write {
+                    let mut off = 0;
+                    for (va, seg_len) in segs {
+                        curr.mm()
+                            .copy_from_user_into(va, &mut chunk[off..off + seg_len])?;
+                        off += seg_len;
+                    }
+                    target.mm().copy_to_user(base + pos, chunk)?;
+                } else {
+                    target.mm().copy_from_user_into(base + pos, chunk)?;
+                    let mut off = 0;
+                    for (va, seg_len) in segs {
+                        curr.mm().copy_to_user(va, &chunk[off..off + seg_len])?;
+                        off += seg_len;
+                    }
+                }
+                pos += chunk.len();
+            }
+            Ok(())
+        };
+        let result = result();
+        done += pos;
+        match result {
+            Ok(()) => {}
+            Err(errno) if done == 0 => return Err(errno),
+            Err(_) => break,
+        }
+    }
+    Ok(done)
+}
 
 impl SyscallComm for SyscallImpl {
-    fn pipe(pipefd: *const u32, _flags: usize) -> SyscallResult {
+    fn pipe2(pipefd: *const u32, flags: usize) -> SyscallResult {
+        let flags = OpenFlags::from_bits(flags as u32).ok_or(Errno::EINVAL)?;
+        if !(flags - (OpenFlags::O_CLOEXEC | OpenFlags::O_NONBLOCK)).is_empty() {
+            return Err(Errno::EINVAL);
+        }
+
         let curr = cpu().curr.as_ref().unwrap();
 
-        let mut files = curr.files();
         let (pipe_read, pipe_write) = Pipe::new();
+        pipe_read.set_open_flags(flags);
+        pipe_write.set_open_flags(flags);
 
-        if files.count() + 2 > files.get_limit() {
-            return Err(Errno::EMFILE);
-        }
+        let (fd_read, fd_write) = curr.files().update(|files| {
+            if files.count() + 2 > files.get_limit() {
+                return Err(Errno::EMFILE);
+            }
 
-        let fd_read = files.push(Arc::new(pipe_read)).unwrap();
-        let fd_write = files.push(Arc::new(pipe_write)).unwrap();
-        drop(files);
+            let fd_read = files.push(Arc::new(pipe_read)).unwrap();
+            let fd_write = files.push(Arc::new(pipe_write)).unwrap();
+            if flags.contains(OpenFlags::O_CLOEXEC) {
+                files.set_cloexec(fd_read, true)?;
+                files.set_cloexec(fd_write, true)?;
+            }
+            Ok((fd_read, fd_write))
+        })?;
 
         let fd_data = ((fd_write << 32) | (fd_read & 0xffffffff)) as u64;
         write_user!(curr.mm(), VirtAddr::from(pipefd as usize), fd_data, u64)?;
@@ -28,6 +176,187 @@ impl SyscallComm for SyscallImpl {
         Ok(0)
     }
 
+    fn splice(
+        fd_in: usize,
+        off_in: usize,
+        fd_out: usize,
+        off_out: usize,
+        len: usize,
+        flags: usize,
+    ) -> SyscallResult {
+        if flags & !(SPLICE_F_MOVE | SPLICE_F_NONBLOCK | SPLICE_F_MORE | SPLICE_F_GIFT) != 0 {
+            return Err(Errno::EINVAL);
+        }
+        let nonblock = flags & SPLICE_F_NONBLOCK != 0;
+
+        let curr = cpu().curr.as_ref().unwrap();
+        let file_in = curr.files().get(fd_in)?;
+        let file_out = curr.files().get(fd_out)?;
+        if !file_in.readable() || !file_out.writable() {
+            return Err(Errno::EBADF);
+        }
+
+        let pipe_in = (*file_in).as_any().downcast_ref::<Pipe>();
+        let pipe_out = (*file_out).as_any().downcast_ref::<Pipe>();
+        match (pipe_in, pipe_out) {
+            // Pipe to file
+            (Some(pipe), None) => {
+                if off_in != 0 {
+                    return Err(Errno::ESPIPE);
+                }
+                let mut off = None;
+                if off_out != 0 {
+                    let mut pos = 0usize;
+                    read_user!(curr.mm(), VirtAddr::from(off_out), pos, usize)?;
+                    off = Some(pos);
+                }
+                let count = pipe.splice_to(len, nonblock, |buf| {
+                    let count = match off.as_mut() {
+                        Some(pos) => file_out.write_at_off(*pos, buf).map(|count| {
+                            *pos += count;
+                            count
+                        }),
+                        None => file_out.write(buf),
+                    };
+                    count.ok_or(Errno::EINVAL)
+                })?;
+                if let Some(pos) = off {
+                    write_user!(curr.mm(), VirtAddr::from(off_out), pos, usize)?;
+                }
+                Ok(count)
+            }
+            // File to pipe
+            (None, Some(pipe)) => {
+                if off_out != 0 {
+                    return Err(Errno::ESPIPE);
+                }
+                let mut off = None;
+                if off_in != 0 {
+                    let mut pos = 0usize;
+                    read_user!(curr.mm(), VirtAddr::from(off_in), pos, usize)?;
+                    off = Some(pos);
+                }
+                let count = pipe
+                    .splice_from(len, nonblock, |buf| {
+                        let count = match off.as_mut() {
+                            Some(pos) => file_in.read_at_off(*pos, buf).map(|count| {
+                                *pos += count;
+                                count
+                            }),
+                            None => file_in.read(buf),
+                        };
+                        count.ok_or(Errno::EINVAL)
+                    })
+                    .map_err(raise_sigpipe)?;
+                if let Some(pos) = off {
+                    write_user!(curr.mm(), VirtAddr::from(off_in), pos, usize)?;
+                }
+                Ok(count)
+            }
+            _ => Err(Errno::EINVAL),
+        }
+    }
+
+    fn vmsplice(fd: usize, iov: *const IoVec, nr_segs: usize, flags: usize) -> SyscallResult {
+        if flags & !(SPLICE_F_MOVE | SPLICE_F_NONBLOCK | SPLICE_F_MORE | SPLICE_F_GIFT) != 0
+            || nr_segs > IOV_MAX
+        {
+            return Err(Errno::EINVAL);
+        }
+
+        let curr = cpu().curr.as_ref().unwrap();
+        let file = curr.files().get(fd)?;
+        let pipe = (*file)
+            .as_any()
+            .downcast_ref::<Pipe>()
+            .ok_or(Errno::EBADF)?;
+        if !pipe.writable() {
+            return Err(Errno::EBADF);
+        }
+
+        // User pages may be unmapped while the pipe sleeps, so user buffers are copied
+        // into the kernel with the address space locked. A call never moves more than a
+        // full pipe buffer, which bounds the copy.
+        let iovs = read_iovecs(curr, iov, nr_segs)?;
+        let mut data = Vec::new();
+        let mut curr_mm = curr.mm();
+        for (base, len) in iovec_segments(&iovs, 0, MAX_PIPE_BUF) {
+            let pos = data.len();
+            data.resize(pos + len, 0);
+            curr_mm.copy_from_user_into(base, &mut data[pos..])?;
+        }
+        drop(curr_mm);
+
+        pipe.splice_segments(&[&data], flags & SPLICE_F_NONBLOCK != 0)
+            .map_err(raise_sigpipe)
+    }
+
+    fn tee(fd_in: usize, fd_out: usize, len: usize, flags: usize) -> SyscallResult {
+        if flags & !(SPLICE_F_MOVE | SPLICE_F_NONBLOCK | SPLICE_F_MORE | SPLICE_F_GIFT) != 0 {
+            return Err(Errno::EINVAL);
+        }
+
+        let curr = cpu().curr.as_ref().unwrap();
+        let file_in = curr.files().get(fd_in)?;
+        let file_out = curr.files().get(fd_out)?;
+        let pipe_in = (*file_in).as_any().downcast_ref::<Pipe>();
+        let pipe_out = (*file_out).as_any().downcast_ref::<Pipe>();
+        match (pipe_in, pipe_out) {
+            (Some(pipe_in), Some(pipe_out)) => {
+                if !pipe_in.readable() || !pipe_out.writable() {
+                    return Err(Errno::EBADF);
+                }
+                pipe_in
+                    .tee_to(pipe_out, len, flags & SPLICE_F_NONBLOCK != 0)
+                    .map_err(raise_sigpipe)
+            }
+            _ => Err(Errno::EINVAL),
+        }
+    }
+
+    fn process_vm_readv(
+        pid: usize,
+        local_iov: *const IoVec,
+        liovcnt: usize,
+        remote_iov: *const IoVec,
+        riovcnt: usize,
+        flags: usize,
+    ) -> SyscallResult {
+        process_vm_rw(pid, local_iov, liovcnt, remote_iov, riovcnt, flags, false)
+    }
+
+    fn process_vm_writev(
+        pid: usize,
+        local_iov: *const IoVec,
+        liovcnt: usize,
+        remote_iov: *const IoVec,
+        riovcnt: usize,
+        flags: usize,
+    ) -> SyscallResult {
+        process_vm_rw(pid, local_iov, liovcnt, remote_iov, riovcnt, flags, true)
+    }
+
+    fn kill(pid: isize, sig: usize) -> SyscallResult {
+        send_signal(pid, sig)?;
+        Ok(0)
+    }
+
+    fn tkill(tid: usize, sig: usize) -> SyscallResult {
+        if tid as isize <= 0 {
+            return Err(Errno::EINVAL);
+        }
+        send_signal_thread(None, tid, sig)?;
+        Ok(0)
+    }
+
+    fn tgkill(tgid: usize, tid: usize, sig: usize) -> SyscallResult {
+        if tgid as isize <= 0 || tid as isize <= 0 {
+            return Err(Errno::EINVAL);
+        }
+        send_signal_thread(Some(tgid), tid, sig)?;
+        Ok(0)
+    }
+
     fn sigaction(signum: usize, act: usize, oldact: usize) -> SyscallResult {
         if !sigvalid(signum) || (act != 0 && sig_kernel_only(signum)) {
             return Err(Errno::EINVAL);
@@ -71,7 +400,112 @@ impl SyscallComm for SyscallImpl {
         Ok(0)
     }
 
+    fn sigaltstack(ss: usize, old_ss: usize) -> SyscallResult {
+        let curr = cpu().curr.as_ref().unwrap();
+        let mut curr_mm = curr.mm();
+
+        let new = if ss != 0 {
+            let mut new = SigAltStack::new();
+            read_user!(curr_mm, ss.into(), new, SigAltStack)?;
+            Some(new)
+        } else {
+            None
+        };
+
+        let old = sigaltstack(
+            &mut curr.inner().sig_altstack,
+            new.as_ref(),
+            curr.trapframe().get_sp(),
+        )?;
+
+        if old_ss != 0 {
+            write_user!(curr_mm, old_ss.into(), old, SigAltStack)?;
+        }
+
+        Ok(0)
+    }
+
+    fn sigreturn() -> SyscallResult {
+        Ok(do_sigreturn()?)
+    }
+
     fn sigprocmask(how: usize, set: usize, oldset: usize, sigsetsize: usize) -> SyscallResult {
         Ok(0)
     }
+
+    fn futex(
+        uaddr: usize,
+        futex_op: usize,
+        val: usize,
+        timeout: usize,
+        _uaddr2: usize,
+        _val3: usize,
+    ) -> SyscallResult {
+        if uaddr % core::mem::size_of::<u32>() != 0 {
+            return Err(Errno::EINVAL);
+        }
+
+        let curr = cpu().curr.as_ref().unwrap();
+        let uaddr = VirtAddr::from(uaddr);
+        // Reads the futex word first, so that the frame is allocated before translation.
+        let mut word = 0u32;
+        read_user!(curr.mm(), uaddr, word, u32)?;
+        let key = FutexKey::new(curr, uaddr, futex_op & FUTEX_PRIVATE_FLAG != 0)?;
+
+        match futex_op & FUTEX_CMD_MASK {
+            FUTEX_WAIT => {
+                let timeout = if timeout != 0 {
+                    let mut ts = TimeSpec::new(0.0);
+                    read_user!(curr.mm(), VirtAddr::from(timeout), ts, TimeSpec)?;
+                    if ts.tv_nsec >= NSEC_PER_SEC {
+                        return Err(Errno::EINVAL);
+                    }
+                    Some(ts)
+                } else {
+                    None
+                };
+                // Re-checks the futex word with the queue locked.
+                do_futex_wait(
+                    key,
+                    || {
+                        read_user!(curr.mm(), uaddr, word, u32)?;
+                        Ok(word == val as u32)
+                    },
+                    timeout,
+                )?;
+                Ok(0)
+            }
+            FUTEX_WAKE => Ok(do_futex_wake(key, val)),
+            _ => Err(Errno::ENOSYS),
+        }
+    }
+
+    fn set_robust_list(head: usize, len: usize) -> SyscallResult {
+        if len != size_of::<RobustListHead>() {
+            return Err(Errno::EINVAL);
+        }
+        cpu().curr.as_ref().unwrap().inner().robust_list = head;
+        Ok(0)
+    }
+
+    fn get_robust_list(pid: usize, head_ptr: usize, len_ptr: usize) -> SyscallResult {
+        let curr = cpu().curr.as_ref().unwrap();
+        let head = if pid == 0 {
+            curr.inner().robust_list
+        } else {
+            find_task(pid)
+                .filter(|task| task.get_state() != TaskState::ZOMBIE)
+                .ok_or(Errno::ESRCH)?
+                .inner()
+                .robust_list
+        };
+        write_user!(curr.mm(), VirtAddr::from(head_ptr), head, usize)?;
+        write_user!(
+            curr.mm(),
+            VirtAddr::from(len_ptr),
+            size_of::<RobustListHead>(),
+            usize
+        )?;
+        Ok(0)
+    }
 }
@@ -1,74 +1,168 @@
-use alloc::string::String;
+use alloc::{string::String, sync::Arc, vec, vec::Vec};
 use core::mem::size_of;
 use errno::Errno;
-use log::trace;
+use log::{trace, warn};
+use signal_defs::{SigInfo, SIGPIPE};
 use syscall_interface::*;
-use vfs::{OpenFlags, Path, SeekWhence, StatMode};
+use vfs::{
+    read_symlink, write_dirents64, AccessMode, File, OpenFlags, Path, RenameFlags, SeekWhence,
+    Stat, StatMode, VFS,
+};
 
 use crate::{
-    arch::mm::VirtAddr,
-    error::KernelResult,
-    fs::{open, unlink},
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::{
+        access_in, link_in, lookup, mkdir_in, mknod_in, open_in, rename_in, resolve_path, sendfile,
+        sync, unlink_in, ResolveFlags,
+    },
+    mm::VMFlags,
+    read_user,
     task::{cpu, Task},
+    write_user,
 };
 
 use super::SyscallImpl;
 
-/// Resolves absolute path with directory file descriptor and pathname.
-///
-/// If the pathname is relative, then it is interpreted relative to the directory
-/// referred to by the file descriptor dirfd .
-///
-/// If pathname is relative and dirfd is the special value [`AT_FDCWD`], then pathname
-/// is interpreted relative to the current working directory of the calling process.
+/// Writes the `stat` of a file to user space.
+fn do_stat(task: &Task, file: Arc<dyn File>, statbuf: usize) -> SyscallResult {
+    task.mm()
+        .check_user_access(VirtAddr::from(statbuf), size_of::<Stat>(), VMFlags::WRITE)?;
+    let mut stat = Stat::default();
+    if !file.get_stat(&mut stat as *mut Stat) {
+        return Err(Errno::EINVAL);
+    }
+    write_user!(task.mm(), VirtAddr::from(statbuf), stat, Stat)?;
+    Ok(0)
+}
+
+/// Reads an array of `iovcnt` [`IoVec`] from user space.
 ///
-/// If pathname is absolute, then dirfd is ignored.
-pub fn resolve_path(task: &Task, dirfd: usize, pathname: String) -> KernelResult<Path> {
-    if pathname.starts_with("/") {
-        Ok(Path::new(pathname.as_str()))
-    } else {
-        let mut path = task.get_dir(dirfd)?;
-        path.extend(pathname.as_str());
-        Ok(path)
+/// Returns `EINVAL` if `iovcnt` is greater than [`IOV_MAX`], or the sum of lengths
+/// overflows an `ssize_t`.
+pub fn read_iovecs(task: &Task, iov: *const IoVec, iovcnt: usize) -> Result<Vec<IoVec>, Errno> {
+    if iovcnt > IOV_MAX {
+        return Err(Errno::EINVAL);
     }
+    let mut mm = task.mm();
+    let mut iovs = Vec::with_capacity(iovcnt);
+    let mut total: usize = 0;
+    for i in 0..iovcnt {
+        let mut vec = IoVec {
+            iov_base: 0,
+            iov_len: 0,
+        };
+        read_user!(
+            mm,
+            VirtAddr::from(iov as usize + i * size_of::<IoVec>()),
+            vec,
+            IoVec
+        )?;
+        total = total
+            .checked_add(vec.iov_len)
+            .filter(|total| *total <= isize::MAX as usize)
+            .ok_or(Errno::EINVAL)?;
+        iovs.push(vec);
+    }
+    Ok(iovs)
 }
 
-impl SyscallFile for SyscallImpl {
-    fn write(fd: usize, buf: *const u8, count: usize) -> SyscallResult {
+/// Checks that every buffer in `iovs` grants the access `flags` to the kernel, so that
+/// nothing is transferred if any of them faults.
+fn check_iovecs(task: &Task, iovs: &[IoVec], flags: VMFlags) -> Result<(), Errno> {
+    let mut mm = task.mm();
+    for iov in iovs {
+        mm.check_user_access(VirtAddr::from(iov.iov_base), iov.iov_len, flags)?;
+    }
+    Ok(())
+}
+
+/// Raises `SIGPIPE` for current task if a write fails with `EPIPE` because the read end
+/// of a pipe is closed.
+pub fn raise_sigpipe(errno: Errno) -> Errno {
+    if errno == Errno::EPIPE {
         let curr = cpu().curr.as_ref().unwrap();
+        curr.locked_inner().sig_pending.add(SigInfo {
+            signo: SIGPIPE as i32,
+            errno: 0,
+            code: 0,
+        });
+    }
+    errno
+}
 
-        // Translate user buffer into kernel string.
-        let buf = curr.mm().get_buf_mut(VirtAddr::from(buf as usize), count)?;
+/// Writes `count` bytes from the user buffer to the file `fd`, leaving `SIGPIPE` to the
+/// caller, since a vector of buffers may be partially written.
+fn do_write(fd: usize, buf: *const u8, count: usize) -> SyscallResult {
+    let curr = cpu().curr.as_ref().unwrap();
 
-        // Get the file with the given file descriptor.
-        let file = curr.files().get(fd)?;
+    // Get the file with the given file descriptor.
+    let file = curr.files().get(fd)?;
 
-        let mut write_len = 0;
-        for bytes in buf.inner {
-            if let Some(count) = file.write(bytes) {
-                write_len += count;
-            } else {
-                break;
-            }
+    // Translate user buffer into kernel string.
+    let addr = VirtAddr::from(buf as usize);
+    let mut curr_mm = curr.mm();
+    let buf = curr_mm.get_buf_mut(addr, count, VMFlags::READ)?;
+
+    // A buffer no larger than a page is written at once even if it straddles pages, so
+    // that a message is never split, e.g. the 8-byte integer taken by an eventfd.
+    if buf.inner.len() > 1 && count <= PAGE_SIZE {
+        let data = curr_mm.copy_from_user(addr, count)?;
+        drop(curr_mm);
+        return file.try_write(&data);
+    }
+    drop(curr_mm);
+
+    let mut write_len = 0;
+    for bytes in buf.inner {
+        match file.try_write(bytes) {
+            Ok(count) => write_len += count,
+            Err(errno) if write_len == 0 => return Err(errno),
+            // The error is reported by the next write.
+            Err(_) => break,
         }
-        Ok(write_len)
+    }
+    Ok(write_len)
+}
+
+impl SyscallFile for SyscallImpl {
+    fn write(fd: usize, buf: *const u8, count: usize) -> SyscallResult {
+        do_write(fd, buf, count).map_err(raise_sigpipe)
     }
 
     fn read(fd: usize, buf: *mut u8, count: usize) -> SyscallResult {
         let curr = cpu().curr.as_ref().unwrap();
 
-        // Get the real buffer translated into physical address.
-        let buf = curr.mm().get_buf_mut(VirtAddr::from(buf as usize), count)?;
-
         // Get the file with the given file descriptor.
         let file = curr.files().get(fd)?;
 
+        // Get the real buffer translated into physical address.
+        let addr = VirtAddr::from(buf as usize);
+        let mut curr_mm = curr.mm();
+        let buf = curr_mm.get_buf_mut(addr, count, VMFlags::WRITE)?;
+        drop(curr_mm);
+
+        // A buffer no larger than a page is read at once even if it straddles pages, so
+        // that a message is never split, e.g. the 8-byte counter of an eventfd.
+        if buf.inner.len() > 1 && count <= PAGE_SIZE {
+            let mut data = vec![0u8; count];
+            let read_len = file.try_read(&mut data)?;
+            curr.mm().copy_to_user(addr, &data[..read_len])?;
+            return Ok(read_len);
+        }
+
         let mut read_len = 0;
         for bytes in buf.inner {
-            if let Some(count) = file.read(bytes) {
-                read_len += count;
-            } else {
-                break;
+            match file.try_read(bytes) {
+                Ok(count) => {
+                    read_len += count;
+                    // Do not block for the rest of the buffer after a short read.
+                    if count < bytes.len() {
+                        break;
+                    }
+                }
+                // Restarted or interrupted when returning to user if nothing is read.
+                Err(errno) if read_len == 0 => return Err(errno),
+                Err(_) => break,
             }
         }
         Ok(read_len)
@@ -79,6 +173,47 @@ impl SyscallFile for SyscallImpl {
         Ok(0)
     }
 
+    fn dup(oldfd: usize) -> SyscallResult {
+        Ok(cpu().curr.as_ref().unwrap().files().dup(oldfd)?)
+    }
+
+    fn dup3(oldfd: usize, newfd: usize, flags: usize) -> SyscallResult {
+        let flags = OpenFlags::from_bits(flags as u32).ok_or(Errno::EINVAL)?;
+        if oldfd == newfd || !(flags - OpenFlags::O_CLOEXEC).is_empty() {
+            return Err(Errno::EINVAL);
+        }
+        Ok(cpu().curr.as_ref().unwrap().files().dup_to(
+            oldfd,
+            newfd,
+            flags.contains(OpenFlags::O_CLOEXEC),
+        )?)
+    }
+
+    fn fcntl(fd: usize, cmd: usize, arg: usize) -> SyscallResult {
+        let curr = cpu().curr.as_ref().unwrap();
+        let files = curr.files();
+        match cmd {
+            F_DUPFD | F_DUPFD_CLOEXEC => files.update(|files| {
+                if arg >= files.get_limit() {
+                    return Err(Errno::EINVAL);
+                }
+                Ok(files.dup_from(fd, arg, cmd == F_DUPFD_CLOEXEC)?)
+            }),
+            F_GETFD => Ok(if files.get_cloexec(fd)? { FD_CLOEXEC } else { 0 }),
+            F_SETFD => {
+                files.update(|files| files.set_cloexec(fd, arg & FD_CLOEXEC != 0))?;
+                Ok(0)
+            }
+            F_GETFL => Ok(files.get(fd)?.open_flags().bits() as usize),
+            F_SETFL => {
+                let file = files.get(fd)?;
+                file.set_open_flags(OpenFlags::from_bits_truncate(arg as u32));
+                Ok(0)
+            }
+            _ => Err(Errno::EINVAL),
+        }
+    }
+
     fn openat(dirfd: usize, pathname: *const u8, flags: usize, mode: usize) -> SyscallResult {
         let flags = OpenFlags::from_bits(flags as u32);
         let mode = StatMode::from_bits(mode as u32);
@@ -89,63 +224,49 @@ impl SyscallFile for SyscallImpl {
         let curr = cpu().curr.as_ref().unwrap();
         let flags = flags.unwrap();
 
-        if flags.contains(OpenFlags::O_CREAT) && mode.is_none()
+        if flags.intersects(OpenFlags::O_CREAT | OpenFlags::O_TMPFILE) && mode.is_none()
             || flags.contains(OpenFlags::O_WRONLY | OpenFlags::O_RDWR)
         {
             return Err(Errno::EINVAL);
         }
 
-        let mut curr_mm = curr.mm();
-        let path = resolve_path(
-            &curr,
-            dirfd,
-            curr_mm.get_str(VirtAddr::from(pathname as usize))?,
-        )?;
+        let pathname = curr.mm().get_str(VirtAddr::from(pathname as usize))?;
+        let (fs, path) = resolve_path(&curr, dirfd, &pathname, ResolveFlags::from_open(flags))?;
 
         trace!("OPEN {:?} {:?}", path, flags);
 
-        Ok(curr.files().push(open(path, flags)?)?)
+        Ok(curr.files().push(open_in(&fs, path, flags)?)?)
     }
 
     fn lseek(fd: usize, off: usize, whence: usize) -> SyscallResult {
-        match (|| {
-            let whence = SeekWhence::try_from(whence);
-            if whence.is_err() {
-                return Err(Errno::EINVAL);
-            }
-
-            let file = cpu().curr.as_ref().unwrap().files().get(fd)?;
+        let whence = SeekWhence::try_from(whence).map_err(|_| Errno::EINVAL)?;
+        let file = cpu().curr.as_ref().unwrap().files().get(fd)?;
 
-            if usize::MAX - file.get_off() < off {
-                return Err(Errno::EINVAL);
-            }
-
-            if let Some(off) = file.seek(off, whence.unwrap()) {
-                Ok(off)
-            } else {
-                Err(Errno::ESPIPE)
-            }
-        })() {
-            Ok(off) => Ok(off),
-            Err(_) => Ok(usize::MAX),
+        if usize::MAX - file.get_off() < off {
+            return Err(Errno::EINVAL);
         }
+
+        file.seek(off, whence).ok_or(match whence {
+            // No data or hole found at or after the offset.
+            SeekWhence::Data | SeekWhence::Hole if file.is_reg() => Errno::ENXIO,
+            _ => Errno::ESPIPE,
+        })
     }
 
     fn readv(fd: usize, iov: *const IoVec, iovcnt: usize) -> SyscallResult {
-        let iov_size = size_of::<IoVec>();
-        let iov = VirtAddr::from(iov as usize);
-        let buf = cpu()
-            .curr
-            .as_ref()
-            .unwrap()
-            .mm()
-            .get_buf_mut(iov, iovcnt * iov_size)?;
-
+        let curr = cpu().curr.as_ref().unwrap();
+        let iovs = read_iovecs(curr, iov, iovcnt)?;
+        check_iovecs(curr, &iovs, VMFlags::WRITE)?;
         let mut read_len = 0;
-        for bytes in buf.into_iter().step_by(iov_size) {
-            let iov = unsafe { &*(bytes as *const IoVec) };
+        for iov in iovs.iter().filter(|iov| iov.iov_len != 0) {
             match Self::read(fd, iov.iov_base as *mut _, iov.iov_len) {
-                Ok(count) => read_len += count,
+                Ok(count) => {
+                    read_len += count;
+                    if count < iov.iov_len {
+                        break;
+                    }
+                }
+                Err(errno) if read_len == 0 => return Err(errno),
                 Err(_) => break,
             }
         }
@@ -153,44 +274,119 @@ impl SyscallFile for SyscallImpl {
     }
 
     fn writev(fd: usize, iov: *const IoVec, iovcnt: usize) -> SyscallResult {
-        let iov_size = size_of::<IoVec>();
-        let iov = VirtAddr::from(iov as usize);
-        let buf = cpu()
-            .curr
-            .as_ref()
-            .unwrap()
-            .mm()
-            .get_buf_mut(iov, iovcnt * iov_size)?;
+        let curr = cpu().curr.as_ref().unwrap();
+        let iovs = read_iovecs(curr, iov, iovcnt)?;
+        check_iovecs(curr, &iovs, VMFlags::READ)?;
+        let mut write_len = 0;
+        for iov in iovs.iter().filter(|iov| iov.iov_len != 0) {
+            match do_write(fd, iov.iov_base as *const _, iov.iov_len) {
+                Ok(count) => {
+                    write_len += count;
+                    if count < iov.iov_len {
+                        break;
+                    }
+                }
+                Err(errno) if write_len == 0 => return Err(raise_sigpipe(errno)),
+                // The error is reported by the next write.
+                Err(_) => break,
+            }
+        }
+        Ok(write_len)
+    }
+
+    fn pwrite(fd: usize, buf: *const u8, count: usize, offset: usize) -> SyscallResult {
+        if (offset as isize) < 0 {
+            return Err(Errno::EINVAL);
+        }
+        let curr = cpu().curr.as_ref().unwrap();
 
+        let mut curr_mm = curr.mm();
+        let buf = curr_mm.get_buf_mut(VirtAddr::from(buf as usize), count, VMFlags::READ)?;
+        drop(curr_mm);
+
+        let file = curr.files().get(fd)?;
+        if !file.writable() {
+            return Err(Errno::EBADF);
+        }
+        // Files without a cursor cannot be written at an offset.
+        if file.seek(0, SeekWhence::Current).is_none() {
+            return Err(Errno::ESPIPE);
+        }
+
+        let mut write_len = 0;
+        for bytes in buf.inner {
+            match file.write_at_off(offset + write_len, bytes) {
+                Some(count) => {
+                    write_len += count;
+                    if count < bytes.len() {
+                        break;
+                    }
+                }
+                None if write_len == 0 => return Err(Errno::EIO),
+                None => break,
+            }
+        }
+        Ok(write_len)
+    }
+
+    fn pwritev(fd: usize, iov: *const IoVec, iovcnt: usize, offset: usize) -> SyscallResult {
+        let curr = cpu().curr.as_ref().unwrap();
+        let iovs = read_iovecs(curr, iov, iovcnt)?;
+        check_iovecs(curr, &iovs, VMFlags::READ)?;
         let mut write_len = 0;
-        for bytes in buf.into_iter().step_by(iov_size) {
-            let iov = unsafe { &*(bytes as *const IoVec) };
-            match Self::write(fd, iov.iov_base as *const _, iov.iov_len) {
-                Ok(count) => write_len += count,
+        for iov in iovs.iter().filter(|iov| iov.iov_len != 0) {
+            match Self::pwrite(
+                fd,
+                iov.iov_base as *const _,
+                iov.iov_len,
+                offset + write_len,
+            ) {
+                Ok(count) => {
+                    write_len += count;
+                    if count < iov.iov_len {
+                        break;
+                    }
+                }
+                Err(errno) if write_len == 0 => return Err(errno),
                 Err(_) => break,
             }
         }
         Ok(write_len)
     }
 
+    fn sendfile(out_fd: usize, in_fd: usize, offset: usize, count: usize) -> SyscallResult {
+        let curr = cpu().curr.as_ref().unwrap();
+        let out_file = curr.files().get(out_fd)?;
+        let in_file = curr.files().get(in_fd)?;
+        if !in_file.readable() || !out_file.writable() {
+            return Err(Errno::EBADF);
+        }
+
+        let mut off = in_file.get_off();
+        if offset != 0 {
+            read_user!(curr.mm(), VirtAddr::from(offset), off, usize)?;
+        }
+        let count = sendfile(&out_file, &in_file, &mut off, count).map_err(raise_sigpipe)?;
+        if offset != 0 {
+            write_user!(curr.mm(), VirtAddr::from(offset), off, usize)?;
+        } else {
+            in_file.seek(off, SeekWhence::Set);
+        }
+        Ok(count)
+    }
+
     fn unlinkat(dirfd: usize, pathname: *const u8, flags: usize) -> SyscallResult {
         if flags == AT_REMOVEDIR {
             unimplemented!()
         } else if flags == 0 {
             {
                 let curr = cpu().curr.as_ref().unwrap();
-                let mut curr_mm = curr.mm();
-                let path = {
-                    resolve_path(
-                        &curr,
-                        dirfd,
-                        curr_mm.get_str(VirtAddr::from(pathname as usize))?,
-                    )?
-                };
+                let pathname = curr.mm().get_str(VirtAddr::from(pathname as usize))?;
+                let (fs, path) = resolve_path(&curr, dirfd, &pathname, ResolveFlags::NOFOLLOW)?;
 
                 trace!("UNLINKAT {:?}", path);
 
-                unlink(path)?;
+                unlink_in(&fs, path)?;
 
                 Ok(0)
             }
@@ -198,4 +394,297 @@ impl SyscallFile for SyscallImpl {
             Err(Errno::EINVAL)
         }
     }
+
+    fn linkat(
+        olddirfd: usize,
+        oldpath: *const u8,
+        newdirfd: usize,
+        newpath: *const u8,
+        flags: usize,
+    ) -> SyscallResult {
+        if flags & !(AT_SYMLINK_FOLLOW | AT_EMPTY_PATH) != 0 {
+            return Err(Errno::EINVAL);
+        }
+
+        let curr = cpu().curr.as_ref().unwrap();
+        let oldpath = curr.mm().get_str(VirtAddr::from(oldpath as usize))?;
+        let newpath = curr.mm().get_str(VirtAddr::from(newpath as usize))?;
+        let (old_fs, old) = if oldpath.is_empty() && flags & AT_EMPTY_PATH != 0 {
+            // Unnamed files cannot be linked.
+            let path = curr
+                .files()
+                .get(olddirfd)?
+                .get_path()
+                .ok_or(Errno::ENOENT)?;
+            lookup(path, ResolveFlags::NOFOLLOW)?
+        } else {
+            let resolve_flags = if flags & AT_SYMLINK_FOLLOW != 0 {
+                ResolveFlags::empty()
+            } else {
+                ResolveFlags::NOFOLLOW
+            };
+            resolve_path(&curr, olddirfd, &oldpath, resolve_flags)?
+        };
+        let (new_fs, new) = resolve_path(&curr, newdirfd, &newpath, ResolveFlags::NOFOLLOW)?;
+
+        trace!("LINKAT {:?} {:?}", old, new);
+
+        link_in(&old_fs, old, &new_fs, new)?;
+        Ok(0)
+    }
+
+    fn renameat2(
+        olddirfd: usize,
+        oldpath: *const u8,
+        newdirfd: usize,
+        newpath: *const u8,
+        flags: usize,
+    ) -> SyscallResult {
+        let flags = u32::try_from(flags)
+            .ok()
+            .and_then(RenameFlags::from_bits)
+            .ok_or(Errno::EINVAL)?;
+
+        let curr = cpu().curr.as_ref().unwrap();
+        let oldpath = curr.mm().get_str(VirtAddr::from(oldpath as usize))?;
+        let newpath = curr.mm().get_str(VirtAddr::from(newpath as usize))?;
+        let (old_fs, old) = resolve_path(&curr, olddirfd, &oldpath, ResolveFlags::NOFOLLOW)?;
+        let (new_fs, new) = resolve_path(&curr, newdirfd, &newpath, ResolveFlags::NOFOLLOW)?;
+
+        trace!("RENAMEAT2 {:?} {:?} {:?}", old, new, flags);
+
+        rename_in(&old_fs, old, &new_fs, new, flags)?;
+        Ok(0)
+    }
+
+    fn getdents64(fd: usize, dirp: *mut u8, count: usize) -> SyscallResult {
+        let curr = cpu().curr.as_ref().unwrap();
+        let file = curr.files().get(fd)?;
+        let entries = file.read_dir().ok_or(Errno::ENOTDIR)?;
+        curr.mm()
+            .check_user_access(VirtAddr::from(dirp as usize), count, VMFlags::WRITE)?;
+
+        // The entries are serialized in chunks of a page, since `count` is given by user.
+        // Any entry fits in a page, thus only the last chunk may stop short of `count`.
+        let mut buf = vec![0u8; count.min(PAGE_SIZE)];
+        let mut next = file.get_off();
+        let mut total = 0;
+        while total < count {
+            let chunk = (count - total).min(PAGE_SIZE);
+            let (len, end) = write_dirents64(&entries, next, &mut buf[..chunk]);
+            if len == 0 {
+                break;
+            }
+            curr.mm()
+                .copy_to_user(VirtAddr::from(dirp as usize + total), &buf[..len])?;
+            total += len;
+            next = end;
+        }
+        if total == 0 && next < entries.len() {
+            return Err(Errno::EINVAL);
+        }
+        file.seek(next, SeekWhence::Set);
+        Ok(total)
+    }
+
+    fn fstat(fd: usize, statbuf: usize) -> SyscallResult {
+        let curr = cpu().curr.as_ref().unwrap();
+        let file = curr.files().get(fd)?;
+        do_stat(&curr, file, statbuf)
+    }
+
+    fn fstatat(dirfd: usize, pathname: *const u8, statbuf: usize, flags: usize) -> SyscallResult {
+        if flags & !(AT_SYMLINK_NOFOLLOW | AT_EMPTY_PATH) != 0 {
+            return Err(Errno::EINVAL);
+        }
+
+        let curr = cpu().curr.as_ref().unwrap();
+        let pathname = curr.mm().get_str(VirtAddr::from(pathname as usize))?;
+        if pathname.is_empty() {
+            if flags & AT_EMPTY_PATH == 0 {
+                return Err(Errno::ENOENT);
+            }
+            if dirfd != AT_FDCWD {
+                let file = curr.files().get(dirfd)?;
+                return do_stat(&curr, file, statbuf);
+            }
+        }
+
+        let (fs, path) = resolve_path(&curr, dirfd, &pathname, ResolveFlags::from_at(flags))?;
+
+        trace!("FSTATAT {:?}", path);
+
+        // A symbolic link is only found here if not followed.
+        if let Some(target) = read_symlink(&path) {
+            let stat = Stat {
+                st_mode: StatMode::S_IFLNK.bits() | 0o777,
+                st_nlink: 1,
+                st_size: target.len() as u64,
+                ..Default::default()
+            };
+            write_user!(curr.mm(), VirtAddr::from(statbuf), stat, Stat)?;
+            return Ok(0);
+        }
+
+        let file = open_in(&fs, path.clone(), OpenFlags::O_RDONLY).or_else(|err| {
+            // The path may refer to a directory without a trailing '/'.
+            let mut dir = path;
+            dir.join("");
+            open_in(&fs, dir, OpenFlags::O_RDONLY).map_err(|_| err)
+        })?;
+        do_stat(&curr, file, statbuf)
+    }
+
+    fn faccessat(dirfd: usize, pathname: *const u8, mode: usize, flags: usize) -> SyscallResult {
+        let mode = u32::try_from(mode)
+            .ok()
+            .and_then(AccessMode::from_bits)
+            .ok_or(Errno::EINVAL)?;
+        if flags & !(AT_EACCESS | AT_SYMLINK_NOFOLLOW) != 0 {
+            return Err(Errno::EINVAL);
+        }
+
+        let curr = cpu().curr.as_ref().unwrap();
+        let pathname = curr.mm().get_str(VirtAddr::from(pathname as usize))?;
+        let (fs, path) = resolve_path(&curr, dirfd, &pathname, ResolveFlags::from_at(flags))?;
+
+        trace!("FACCESSAT {:?} {:?}", path, mode);
+
+        access_in(&fs, path, mode)?;
+        Ok(0)
+    }
+
+    fn readlinkat(dirfd: usize, pathname: *const u8, buf: *mut u8, bufsiz: usize) -> SyscallResult {
+        if bufsiz as isize <= 0 {
+            return Err(Errno::EINVAL);
+        }
+
+        let curr = cpu().curr.as_ref().unwrap();
+        let pathname = curr.mm().get_str(VirtAddr::from(pathname as usize))?;
+        let (fs, path) = resolve_path(&curr, dirfd, &pathname, ResolveFlags::NOFOLLOW)?;
+
+        trace!("READLINKAT {:?}", path);
+
+        let target = match read_symlink(&path) {
+            Some(target) => target,
+            None => {
+                let mut dir = path.clone();
+                dir.join("");
+                return Err(if fs.check(&path) || fs.check(&dir) {
+                    Errno::EINVAL
+                } else {
+                    Errno::ENOENT
+                });
+            }
+        };
+        let len = target.len().min(bufsiz);
+        curr.mm()
+            .copy_to_user(VirtAddr::from(buf as usize), &target.as_bytes()[..len])?;
+        Ok(len)
+    }
+
+    fn getcwd(buf: *mut u8, size: usize) -> SyscallResult {
+        if size == 0 && !buf.is_null() {
+            return Err(Errno::EINVAL);
+        }
+
+        let curr = cpu().curr.as_ref().unwrap();
+        let fs_info = curr.fs_info.lock();
+        // The working directory may be out of the root directory.
+        let cwd = fs_info
+            .cwd
+            .strip_root(&fs_info.root)
+            .unwrap_or(fs_info.cwd.clone());
+        drop(fs_info);
+        let cwd = if cwd.is_root() {
+            cwd.as_str()
+        } else {
+            cwd.as_str().trim_end_matches('/')
+        };
+        if cwd.len() + 1 > size {
+            return Err(Errno::ERANGE);
+        }
+
+        let mut data = Vec::from(cwd.as_bytes());
+        data.push(0);
+        curr.mm().copy_to_user(VirtAddr::from(buf as usize), &data)?;
+        Ok(buf as usize)
+    }
+
+    fn sync() -> SyscallResult {
+        // Errors are not reported to the user.
+        if let Err(err) = sync() {
+            warn!("sync failed {:?}", err);
+        }
+        Ok(0)
+    }
+
+    fn chdir(path: *const u8) -> SyscallResult {
+        let curr = cpu().curr.as_ref().unwrap();
+        let path = curr.mm().get_str(VirtAddr::from(path as usize))?;
+        let (fs, mut path) = resolve_path(&curr, AT_FDCWD, &path, ResolveFlags::empty())?;
+        if !path.is_dir() {
+            path.join("");
+        }
+
+        trace!("CHDIR {:?}", path);
+
+        if !fs.check(&path) {
+            let file = Path::from(String::from(path.as_str().trim_end_matches('/')));
+            return Err(if fs.check(&file) {
+                Errno::ENOTDIR
+            } else {
+                Errno::ENOENT
+            });
+        }
+
+        curr.fs_info.lock().cwd = path;
+        Ok(0)
+    }
+
+    fn chroot(path: *const u8) -> SyscallResult {
+        let curr = cpu().curr.as_ref().unwrap();
+        let path = curr.mm().get_str(VirtAddr::from(path as usize))?;
+        let (fs, mut path) = resolve_path(&curr, AT_FDCWD, &path, ResolveFlags::empty())?;
+        if !path.is_dir() {
+            path.join("");
+        }
+
+        trace!("CHROOT {:?}", path);
+
+        open_in(&fs, path.clone(), OpenFlags::O_RDONLY)?;
+
+        curr.fs_info.lock().root = path;
+        Ok(0)
+    }
+
+    fn mknodat(dirfd: usize, pathname: *const u8, mode: usize, dev: usize) -> SyscallResult {
+        let mode = StatMode::from_bits_truncate(mode as u32);
+        let curr = cpu().curr.as_ref().unwrap();
+        let pathname = curr.mm().get_str(VirtAddr::from(pathname as usize))?;
+        let (fs, path) = resolve_path(&curr, dirfd, &pathname, ResolveFlags::NOFOLLOW)?;
+
+        trace!("MKNODAT {:?} {:?} {:#x}", path, mode, dev);
+
+        mknod_in(&fs, path, mode, dev as u64)?;
+        Ok(0)
+    }
+
+    fn mkdirat(dirfd: usize, pathname: *const u8, mode: usize) -> SyscallResult {
+        let mode = StatMode::from_bits_truncate(mode as u32)
+            & (StatMode::S_ISVTX | StatMode::S_IRWXU | StatMode::S_IRWXG | StatMode::S_IRWXO);
+        let curr = cpu().curr.as_ref().unwrap();
+        let mut pathname = curr.mm().get_str(VirtAddr::from(pathname as usize))?;
+        if pathname.is_empty() {
+            return Err(Errno::ENOENT);
+        }
+        // Resolves the path of a directory, whose last component is never followed.
+        pathname.push('/');
+        let (fs, path) = resolve_path(&curr, dirfd, &pathname, ResolveFlags::NOFOLLOW)?;
+
+        trace!("MKDIRAT {:?} {:?}", path, mode);
+
+        mkdir_in(&fs, path, mode)?;
+        Ok(0)
+    }
 }
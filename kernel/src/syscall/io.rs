@@ -1,26 +1,316 @@
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::mem::size_of;
 use errno::Errno;
-use syscall_interface::{SyscallIO, SyscallResult};
+use signal_defs::SigSet;
+use syscall_interface::{
+    EpollEvent, PollEvents, PollFd, SyscallIO, SyscallResult, EFD_CLOEXEC, EFD_NONBLOCK,
+    EFD_SEMAPHORE, EPOLL_CLOEXEC, EPOLL_CTL_DEL, FIONBIO, GRND_INSECURE, GRND_NONBLOCK,
+    GRND_RANDOM,
+};
+use time_subsys::{TimeSpec, NSEC_PER_MSEC, NSEC_PER_SEC};
+use vfs::{ring_buf::Waker, File, OpenFlags};
 
-use crate::{arch::mm::VirtAddr, task::cpu};
+use crate::{
+    arch::{mm::VirtAddr, timer::get_time_sec_f64},
+    fs::{EpollFile, EventFd},
+    mm::VMFlags,
+    random::fill_random,
+    read_user,
+    task::{
+        cpu, do_sleep, do_wake, do_yield, restore_saved_sigmask_unless, set_user_sigmask,
+        signal_pending, TaskState,
+    },
+    timer::TIMER_WHEEL,
+};
 
 use super::SyscallImpl;
 
+/// Sets `revents` of `poll_fds` from the readiness of their files, returning the number
+/// of files with any event.
+fn poll_once(poll_fds: &mut [PollFd], files: &[Option<Arc<dyn File>>]) -> usize {
+    let mut count = 0;
+    for (poll_fd, file) in poll_fds.iter_mut().zip(files) {
+        poll_fd.revents = match file {
+            _ if poll_fd.fd < 0 => PollEvents::empty(),
+            Some(file) => {
+                poll_fd
+                    .events
+                    .occurred(file.read_ready(), file.write_ready(), file.hangup())
+            }
+            None => PollEvents::POLLNVAL,
+        };
+        if !poll_fd.revents.is_empty() {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Current task waits until any file in `poll_fds` is ready, the `deadline` expires, or
+/// a signal arrives, in which case `EINTR` is returned.
+///
+/// The task sleeps on the wakers registered on the files, which are registered before
+/// the readiness is checked, so that an event in between is never lost. The task yields
+/// instead if any file cannot wake it up.
+fn do_poll(poll_fds: &mut [PollFd], deadline: Option<TimeSpec>) -> Result<usize, Errno> {
+    let curr = cpu().curr.as_ref().unwrap();
+    let tid = curr.tid.0;
+    let waker = || -> Waker {
+        let task = curr.clone();
+        Box::new(move || do_wake(&task))
+    };
+
+    loop {
+        let files: Vec<Option<Arc<dyn File>>> = poll_fds
+            .iter()
+            .map(|poll_fd| curr.files().get(poll_fd.fd as usize).ok())
+            .collect();
+        curr.locked_inner().state = TaskState::INTERRUPTIBLE;
+        let mut waitable = true;
+        for file in files.iter().flatten() {
+            waitable &= file.poll_wait(tid, &waker);
+        }
+
+        let count = poll_once(poll_fds, &files);
+        let now = TimeSpec::new(get_time_sec_f64());
+        let expired = deadline.map_or(false, |deadline| now >= deadline);
+        let interrupted = signal_pending();
+        if count == 0 && !expired && !interrupted && waitable {
+            if let Some(deadline) = deadline {
+                TIMER_WHEEL.lock().add(deadline, tid);
+            }
+            unsafe { do_sleep() };
+            TIMER_WHEEL.lock().cancel(tid);
+        } else {
+            curr.locked_inner().state = TaskState::RUNNING;
+        }
+        for file in files.iter().flatten() {
+            file.poll_cancel(tid);
+        }
+
+        if count > 0 || expired {
+            return Ok(count);
+        }
+        if interrupted {
+            return Err(Errno::EINTR);
+        }
+        if !waitable {
+            unsafe { do_yield() };
+        }
+    }
+}
+
+/// Current task waits until any file in the interest list of `epoll` referred to by
+/// `epfd` is ready, returning at most `maxevents` ready events.
+///
+/// The task sleeps on the epoll file by [`do_poll`], until the `deadline` expires, or
+/// a signal arrives, in which case `EINTR` is returned.
+fn do_epoll_wait(
+    epfd: usize,
+    epoll: &EpollFile,
+    maxevents: usize,
+    deadline: Option<TimeSpec>,
+) -> Result<Vec<EpollEvent>, Errno> {
+    let mut poll_fds = [PollFd {
+        fd: epfd as i32,
+        events: PollEvents::POLLIN,
+        revents: PollEvents::empty(),
+    }];
+    loop {
+        let ready = epoll.wait(maxevents);
+        if !ready.is_empty() {
+            return Ok(ready);
+        }
+        // Events found ready may have been reported to another thread, or the epoll
+        // file descriptor is closed meanwhile.
+        if do_poll(&mut poll_fds, deadline)? == 0
+            || poll_fds[0].revents.contains(PollEvents::POLLNVAL)
+        {
+            return Ok(Vec::new());
+        }
+    }
+}
+
 impl SyscallIO for SyscallImpl {
-    fn ioctl(fd: usize, _request: usize, argp: *const usize) -> SyscallResult {
+    fn ioctl(fd: usize, request: usize, argp: *const usize) -> SyscallResult {
         let curr = cpu().curr.as_ref().unwrap();
 
-        if curr.files().get(fd).is_err() {
-            return Err(Errno::EBADF);
+        let file = curr.files().get(fd)?;
+        // Applies to every open file description like `fcntl(F_SETFL)`.
+        if request == FIONBIO {
+            let mut nonblock = 0i32;
+            read_user!(curr.mm(), VirtAddr::from(argp as usize), nonblock, i32)?;
+            let mut flags = file.open_flags();
+            flags.set(OpenFlags::O_NONBLOCK, nonblock != 0);
+            file.set_open_flags(flags);
+            return Ok(0);
+        }
+        file.ioctl(request, argp as usize)
+    }
+
+    fn ppoll(fds: usize, nfds: usize, tmo_p: usize, sigmask: usize) -> SyscallResult {
+        let curr = cpu().curr.as_ref().unwrap();
+        if nfds > curr.files().get_limit() {
+            return Err(Errno::EINVAL);
         }
 
-        if curr
+        let fds_addr = VirtAddr::from(fds);
+        let size = nfds * size_of::<PollFd>();
+        let mut poll_fds: Vec<PollFd> = curr
             .mm()
-            .get_vma(VirtAddr::from(argp as usize), |_, _, _| Ok(()))
-            .is_err()
+            .copy_from_user(fds_addr, size)?
+            .chunks_exact(size_of::<PollFd>())
+            .map(|bytes| unsafe { (bytes.as_ptr() as *const PollFd).read_unaligned() })
+            .collect();
+
+        // Blocks indefinitely if the timeout is not provided.
+        let deadline = if tmo_p != 0 {
+            let mut tmo = TimeSpec::default();
+            read_user!(curr.mm(), VirtAddr::from(tmo_p), tmo, TimeSpec)?;
+            if tmo.tv_nsec >= NSEC_PER_SEC {
+                return Err(Errno::EINVAL);
+            }
+            Some(TimeSpec::new(get_time_sec_f64()) + tmo)
+        } else {
+            None
+        };
+
+        // The signal mask is replaced while waiting, and kept until the signal
+        // interrupting the wait is delivered.
+        if sigmask != 0 {
+            let mut mask = SigSet::new();
+            read_user!(curr.mm(), VirtAddr::from(sigmask), mask, SigSet)?;
+            set_user_sigmask(mask);
+        }
+        let result = do_poll(&mut poll_fds, deadline);
+        restore_saved_sigmask_unless(result == Err(Errno::EINTR));
+        let count = result?;
+
+        let data = unsafe { core::slice::from_raw_parts(poll_fds.as_ptr() as *const u8, size) };
+        curr.mm().copy_to_user(fds_addr, data)?;
+        Ok(count)
+    }
+
+    fn getrandom(buf: *mut u8, buflen: usize, flags: usize) -> SyscallResult {
+        if flags & !(GRND_NONBLOCK | GRND_RANDOM | GRND_INSECURE) != 0
+            || flags & GRND_RANDOM != 0 && flags & GRND_INSECURE != 0
         {
-            return Err(Errno::EFAULT);
+            return Err(Errno::EINVAL);
+        }
+
+        // The generator is seeded at first use, so it never blocks even without
+        // GRND_NONBLOCK.
+        let curr = cpu().curr.as_ref().unwrap();
+        let mut curr_mm = curr.mm();
+        let buf = curr_mm.get_buf_mut(VirtAddr::from(buf as usize), buflen, VMFlags::WRITE)?;
+        for bytes in buf.inner {
+            fill_random(bytes);
         }
+        Ok(buflen)
+    }
 
+    fn eventfd2(initval: usize, flags: usize) -> SyscallResult {
+        if flags & !(EFD_SEMAPHORE | EFD_CLOEXEC | EFD_NONBLOCK) != 0 {
+            return Err(Errno::EINVAL);
+        }
+
+        let open_flags = if flags & EFD_NONBLOCK != 0 {
+            OpenFlags::O_NONBLOCK
+        } else {
+            OpenFlags::empty()
+        };
+        let eventfd = EventFd::new(initval as u64, flags & EFD_SEMAPHORE != 0, open_flags);
+        let curr = cpu().curr.as_ref().unwrap();
+        curr.files().update(|files| {
+            let fd = files.push(Arc::new(eventfd))?;
+            if flags & EFD_CLOEXEC != 0 {
+                files.set_cloexec(fd, true)?;
+            }
+            Ok(fd)
+        })
+    }
+
+    fn epoll_create1(flags: usize) -> SyscallResult {
+        if flags & !EPOLL_CLOEXEC != 0 {
+            return Err(Errno::EINVAL);
+        }
+
+        let curr = cpu().curr.as_ref().unwrap();
+        curr.files().update(|files| {
+            let fd = files.push(Arc::new(EpollFile::new()))?;
+            if flags & EPOLL_CLOEXEC != 0 {
+                files.set_cloexec(fd, true)?;
+            }
+            Ok(fd)
+        })
+    }
+
+    fn epoll_ctl(epfd: usize, op: usize, fd: usize, event: usize) -> SyscallResult {
+        let curr = cpu().curr.as_ref().unwrap();
+        let epoll_file = curr.files().get(epfd)?;
+        let file = curr.files().get(fd)?;
+        let epoll = (*epoll_file)
+            .as_any()
+            .downcast_ref::<EpollFile>()
+            .ok_or(Errno::EINVAL)?;
+
+        let mut epoll_event = EpollEvent::default();
+        if op != EPOLL_CTL_DEL {
+            read_user!(curr.mm(), VirtAddr::from(event), epoll_event, EpollEvent)?;
+        }
+        epoll.ctl(op, fd, &file, epoll_event)?;
         Ok(0)
     }
+
+    fn epoll_pwait(
+        epfd: usize,
+        events: usize,
+        maxevents: usize,
+        timeout: isize,
+        sigmask: usize,
+    ) -> SyscallResult {
+        if maxevents == 0 || maxevents > i32::MAX as usize / size_of::<EpollEvent>() {
+            return Err(Errno::EINVAL);
+        }
+
+        let curr = cpu().curr.as_ref().unwrap();
+        let epoll_file = curr.files().get(epfd)?;
+        let epoll = (*epoll_file)
+            .as_any()
+            .downcast_ref::<EpollFile>()
+            .ok_or(Errno::EINVAL)?;
+        let events_addr = VirtAddr::from(events);
+        curr.mm().check_user_access(
+            events_addr,
+            maxevents * size_of::<EpollEvent>(),
+            VMFlags::WRITE,
+        )?;
+
+        // Blocks indefinitely if the timeout is negative.
+        let deadline = if timeout >= 0 {
+            let timeout = TimeSpec::from_nanos((timeout as usize * NSEC_PER_MSEC) as u64);
+            Some(TimeSpec::new(get_time_sec_f64()) + timeout)
+        } else {
+            None
+        };
+
+        // The signal mask is replaced while waiting, the same as `ppoll`.
+        if sigmask != 0 {
+            let mut mask = SigSet::new();
+            read_user!(curr.mm(), VirtAddr::from(sigmask), mask, SigSet)?;
+            set_user_sigmask(mask);
+        }
+        let result = do_epoll_wait(epfd, epoll, maxevents, deadline);
+        restore_saved_sigmask_unless(result == Err(Errno::EINTR));
+        let ready = result?;
+
+        let data = unsafe {
+            core::slice::from_raw_parts(
+                ready.as_ptr() as *const u8,
+                ready.len() * size_of::<EpollEvent>(),
+            )
+        };
+        curr.mm().copy_to_user(events_addr, data)?;
+        Ok(ready.len())
+    }
 }
@@ -1,6 +1,7 @@
 use log::trace;
 use syscall_interface::{
-    IoVec, SyscallComm, SyscallFile, SyscallIO, SyscallNO, SyscallProc, SyscallResult, SyscallTimer,
+    IoVec, SyscallCategory, SyscallComm, SyscallFile, SyscallIO, SyscallNO, SyscallProc,
+    SyscallResult, SyscallTimer, SyscallTrace,
 };
 
 mod comm;
@@ -8,37 +9,161 @@ mod file;
 mod io;
 mod proc;
 mod timer;
+#[cfg(any(feature = "trace-file", feature = "trace-proc", feature = "trace-mem"))]
+mod trace;
+
+#[cfg(any(feature = "trace-file", feature = "trace-proc", feature = "trace-mem"))]
+use trace::decode_args;
+
+/// Arguments are not decoded without tracing.
+#[cfg(not(any(feature = "trace-file", feature = "trace-proc", feature = "trace-mem")))]
+fn decode_args(_id: SyscallNO, args: &[usize; 6]) -> alloc::vec::Vec<syscall_interface::SyscallArg> {
+    syscall_interface::raw_args(args)
+}
 
 #[derive(Debug)]
 pub struct SyscallArgs(pub SyscallNO, pub [usize; 6]);
 
 pub struct SyscallImpl;
 
+/// Returns true if tracing is enabled for the category by `trace-*` features.
+fn trace_enabled(category: SyscallCategory) -> bool {
+    match category {
+        SyscallCategory::File => cfg!(feature = "trace-file"),
+        SyscallCategory::Proc => cfg!(feature = "trace-proc"),
+        SyscallCategory::Mem => cfg!(feature = "trace-mem"),
+    }
+}
+
 pub fn syscall(args: SyscallArgs) -> SyscallResult {
     trace!("[U] SYSCALL {:X?}", args);
-    let id = args.0;
-    let args = args.1;
+    let SyscallArgs(id, args) = args;
+    let result = dispatch(id, args);
+    if trace_enabled(id.category()) {
+        trace!(
+            "[U] {}",
+            SyscallTrace {
+                id: &id,
+                args: &decode_args(id, &args),
+                result: &result,
+            }
+        );
+    }
+    result
+}
+
+fn dispatch(id: SyscallNO, args: [usize; 6]) -> SyscallResult {
     match id {
+        SyscallNO::GETCWD => SyscallImpl::getcwd(args[0] as *mut u8, args[1]),
+        SyscallNO::CHDIR => SyscallImpl::chdir(args[0] as *const u8),
+        SyscallNO::CHROOT => SyscallImpl::chroot(args[0] as *const u8),
+        SyscallNO::DUP => SyscallImpl::dup(args[0]),
+        SyscallNO::DUP3 => SyscallImpl::dup3(args[0], args[1], args[2]),
+        SyscallNO::FCNTL => SyscallImpl::fcntl(args[0], args[1], args[2]),
         SyscallNO::IOCTL => SyscallImpl::ioctl(args[0], args[1], args[2] as *const usize),
+        SyscallNO::MKNODAT => SyscallImpl::mknodat(args[0], args[1] as *const u8, args[2], args[3]),
+        SyscallNO::MKDIRAT => SyscallImpl::mkdirat(args[0], args[1] as *const u8, args[2]),
         SyscallNO::UNLINKAT => SyscallImpl::unlinkat(args[0], args[1] as *const u8, args[2]),
+        SyscallNO::LINKAT => SyscallImpl::linkat(
+            args[0],
+            args[1] as *const u8,
+            args[2],
+            args[3] as *const u8,
+            args[4],
+        ),
+        SyscallNO::RENAMEAT2 => SyscallImpl::renameat2(
+            args[0],
+            args[1] as *const u8,
+            args[2],
+            args[3] as *const u8,
+            args[4],
+        ),
+        SyscallNO::FACCESSAT => {
+            SyscallImpl::faccessat(args[0], args[1] as *const u8, args[2], args[3])
+        }
         SyscallNO::OPENAT => SyscallImpl::openat(args[0], args[1] as *const u8, args[2], args[3]),
         SyscallNO::CLOSE => SyscallImpl::close(args[0]),
-        SyscallNO::PIPE => SyscallImpl::pipe(args[0] as *const u32, args[1]),
+        SyscallNO::PIPE2 => SyscallImpl::pipe2(args[0] as *const u32, args[1]),
+        SyscallNO::GETDENTS64 => SyscallImpl::getdents64(args[0], args[1] as *mut u8, args[2]),
+        SyscallNO::FSTAT => SyscallImpl::fstat(args[0], args[1]),
+        SyscallNO::SYNC => SyscallImpl::sync(),
+        SyscallNO::READLINKAT => {
+            SyscallImpl::readlinkat(args[0], args[1] as *const u8, args[2] as *mut u8, args[3])
+        }
+        SyscallNO::NEWFSTATAT => {
+            SyscallImpl::fstatat(args[0], args[1] as *const u8, args[2], args[3])
+        }
+        SyscallNO::PPOLL => SyscallImpl::ppoll(args[0], args[1], args[2], args[3]),
+        SyscallNO::EVENTFD2 => SyscallImpl::eventfd2(args[0], args[1]),
+        SyscallNO::EPOLL_CREATE1 => SyscallImpl::epoll_create1(args[0]),
+        SyscallNO::EPOLL_CTL => SyscallImpl::epoll_ctl(args[0], args[1], args[2], args[3]),
+        SyscallNO::EPOLL_PWAIT => {
+            SyscallImpl::epoll_pwait(args[0], args[1], args[2], args[3] as isize, args[4])
+        }
+        SyscallNO::GETRANDOM => SyscallImpl::getrandom(args[0] as *mut u8, args[1], args[2]),
+        SyscallNO::SPLICE => {
+            SyscallImpl::splice(args[0], args[1], args[2], args[3], args[4], args[5])
+        }
+        SyscallNO::VMSPLICE => {
+            SyscallImpl::vmsplice(args[0], args[1] as *const IoVec, args[2], args[3])
+        }
+        SyscallNO::TEE => SyscallImpl::tee(args[0], args[1], args[2], args[3]),
+        SyscallNO::PROCESS_VM_READV => SyscallImpl::process_vm_readv(
+            args[0],
+            args[1] as *const IoVec,
+            args[2],
+            args[3] as *const IoVec,
+            args[4],
+            args[5],
+        ),
+        SyscallNO::PROCESS_VM_WRITEV => SyscallImpl::process_vm_writev(
+            args[0],
+            args[1] as *const IoVec,
+            args[2],
+            args[3] as *const IoVec,
+            args[4],
+            args[5],
+        ),
         SyscallNO::LSEEK => SyscallImpl::lseek(args[0], args[1], args[2]),
         SyscallNO::READ => SyscallImpl::read(args[0], args[1] as *mut u8, args[2]),
         SyscallNO::WRTIE => SyscallImpl::write(args[0], args[1] as *const u8, args[2]),
         SyscallNO::READV => SyscallImpl::readv(args[0], args[1] as *const IoVec, args[2]),
         SyscallNO::WRITEV => SyscallImpl::writev(args[0], args[1] as *const IoVec, args[2]),
+        SyscallNO::PWRITE => SyscallImpl::pwrite(args[0], args[1] as *const u8, args[2], args[3]),
+        SyscallNO::PWRITEV => {
+            SyscallImpl::pwritev(args[0], args[1] as *const IoVec, args[2], args[3])
+        }
+        SyscallNO::SENDFILE => SyscallImpl::sendfile(args[0], args[1], args[2], args[3]),
         SyscallNO::EXIT | SyscallNO::EXIT_GROUP => SyscallImpl::exit(args[0]),
         SyscallNO::SET_TID_ADDRESS => SyscallImpl::set_tid_address(args[0]),
+        SyscallNO::FUTEX => {
+            SyscallImpl::futex(args[0], args[1], args[2], args[3], args[4], args[5])
+        }
+        SyscallNO::SET_ROBUST_LIST => SyscallImpl::set_robust_list(args[0], args[1]),
+        SyscallNO::GET_ROBUST_LIST => SyscallImpl::get_robust_list(args[0], args[1], args[2]),
         SyscallNO::NANOSLEEP => SyscallImpl::nanosleep(args[0], args[1]),
+        SyscallNO::GETITIMER => SyscallImpl::getitimer(args[0], args[1]),
+        SyscallNO::SETITIMER => SyscallImpl::setitimer(args[0], args[1], args[2]),
         SyscallNO::CLOCK_GET_TIME => SyscallImpl::clock_gettime(args[0], args[1]),
+        SyscallNO::CLOCK_GETRES => SyscallImpl::clock_getres(args[0], args[1]),
+        SyscallNO::CLOCK_NANOSLEEP => {
+            SyscallImpl::clock_nanosleep(args[0], args[1], args[2], args[3])
+        }
+        SyscallNO::KILL => SyscallImpl::kill(args[0] as isize, args[1]),
+        SyscallNO::TKILL => SyscallImpl::tkill(args[0], args[1]),
+        SyscallNO::TGKILL => SyscallImpl::tgkill(args[0], args[1], args[2]),
+        SyscallNO::SIGALTSTACK => SyscallImpl::sigaltstack(args[0], args[1]),
         SyscallNO::SIGACTION => SyscallImpl::sigaction(args[0], args[1], args[2]),
+        SyscallNO::SIGRETURN => SyscallImpl::sigreturn(),
         SyscallNO::SIGPROCMASK => SyscallImpl::sigprocmask(args[0], args[1], args[2], args[3]),
         SyscallNO::SIGTIMEDWAIT => SyscallImpl::sigtimedwait(args[0], args[1], args[2]),
+        SyscallNO::UNAME => SyscallImpl::uname(args[0]),
         SyscallNO::GET_TIME_OF_DAY => SyscallImpl::gettimeofday(args[0]),
         SyscallNO::GETPID => SyscallImpl::getpid(),
         SyscallNO::GETTID => SyscallImpl::gettid(),
+        SyscallNO::SETPGID => SyscallImpl::setpgid(args[0], args[1]),
+        SyscallNO::GETPGID => SyscallImpl::getpgid(args[0]),
+        SyscallNO::SETSID => SyscallImpl::setsid(),
         SyscallNO::BRK => SyscallImpl::brk(args[0]),
         SyscallNO::MUNMAP => SyscallImpl::munmap(args[0], args[1]),
         SyscallNO::CLONE => SyscallImpl::clone(args[0], args[1], args[2], args[3], args[4]),
@@ -49,6 +174,10 @@ pub fn syscall(args: SyscallArgs) -> SyscallResult {
         }
         SyscallNO::MMAP => SyscallImpl::mmap(args[0], args[1], args[2], args[3], args[4], args[5]),
         SyscallNO::MPROTECT => SyscallImpl::mprotect(args[0], args[1], args[2]),
+        SyscallNO::MLOCK => SyscallImpl::mlock(args[0], args[1]),
+        SyscallNO::MUNLOCK => SyscallImpl::munlock(args[0], args[1]),
+        SyscallNO::MADVISE => SyscallImpl::madvise(args[0], args[1], args[2]),
+        SyscallNO::MEMBARRIER => SyscallImpl::membarrier(args[0], args[1], args[2]),
 
         // UINTR
         #[cfg(feature = "uintr")]
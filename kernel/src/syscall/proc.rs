@@ -1,12 +1,16 @@
-use alloc::{string::String, vec::Vec};
+use alloc::{string::String, sync::Weak, vec::Vec};
 use errno::Errno;
 use syscall_interface::*;
-use vfs::{OpenFlags, Path};
+use vfs::OpenFlags;
 
 use crate::{
-    arch::{__move_to_next, mm::VirtAddr},
-    fs::open,
-    mm::{do_brk, do_mmap, do_mprotect, do_munmap, MmapFlags, MmapProt},
+    arch::{__move_to_next, fence_all_harts, mm::VirtAddr},
+    config::{UTS_DOMAINNAME, UTS_MACHINE, UTS_NODENAME, UTS_RELEASE, UTS_SYSNAME, UTS_VERSION},
+    fs::{open_in, resolve_path, ResolveFlags},
+    mm::{
+        do_brk, do_madvise, do_mlock, do_mmap, do_mprotect, do_munlock, do_munmap, MmapFlags,
+        MmapProt, VMFlags,
+    },
     read_user,
     task::*,
 };
@@ -65,19 +69,17 @@ impl SyscallProc for SyscallImpl {
         }
     }
 
-    fn execve(pathname: usize, argv: usize, _envp: usize) -> SyscallResult {
+    fn execve(pathname: usize, argv: usize, envp: usize) -> SyscallResult {
         let curr = cpu().curr.as_ref().unwrap();
 
         // get relative path under current working directory
         let rela_path = curr.mm().get_str(VirtAddr::from(pathname))?;
 
         // get absolute path of the file to execute
-        let fs_info = curr.fs_info.lock();
-        let mut path = Path::from(fs_info.cwd.clone() + "/" + rela_path.as_str());
-        drop(fs_info);
+        let (fs, mut path) = resolve_path(&curr, AT_FDCWD, &rela_path, ResolveFlags::empty())?;
 
         // read file from disk
-        let file = open(path.clone(), OpenFlags::O_RDONLY)?;
+        let file = open_in(&fs, path.clone(), OpenFlags::O_RDONLY)?;
         if !file.is_reg() {
             return Err(Errno::EACCES);
         }
@@ -96,16 +98,58 @@ impl SyscallProc for SyscallImpl {
             args.push(curr_mm.get_str(VirtAddr::from(argc))?);
             argv += core::mem::size_of::<usize>();
         }
+
+        // get environment list, which might be NULL
+        let mut envs = Vec::new();
+        let mut envp = envp;
+        let mut env: usize = 0;
+        while envp != 0 {
+            read_user!(curr_mm, VirtAddr::from(envp), env, usize)?;
+            if env == 0 {
+                break;
+            }
+            envs.push(curr_mm.get_str(VirtAddr::from(env))?);
+            envp += core::mem::size_of::<usize>();
+        }
         drop(curr_mm);
 
         path.pop().unwrap(); // unwrap a regular filename freely
-        do_exec(String::from(path.as_str()), elf_data.as_slice(), args)?;
+        do_exec(String::from(path.as_str()), elf_data.as_slice(), args, envs)?;
 
         unsafe { __move_to_next(curr_ctx()) };
 
         unreachable!()
     }
 
+    fn uname(buf: usize) -> SyscallResult {
+        let uts = UtsName {
+            sysname: UtsName::field(UTS_SYSNAME),
+            nodename: UtsName::field(UTS_NODENAME),
+            release: UtsName::field(UTS_RELEASE),
+            version: UtsName::field(UTS_VERSION),
+            machine: UtsName::field(UTS_MACHINE),
+            domainname: UtsName::field(UTS_DOMAINNAME),
+        };
+        let data = unsafe {
+            core::slice::from_raw_parts(
+                &uts as *const _ as *const u8,
+                core::mem::size_of::<UtsName>(),
+            )
+        };
+
+        let curr = cpu().curr.as_ref().unwrap();
+        let mut curr_mm = curr.mm();
+        let mut pos = 0;
+        for bytes in curr_mm
+            .get_buf_mut(VirtAddr::from(buf), data.len(), VMFlags::WRITE)?
+            .inner
+        {
+            bytes.copy_from_slice(&data[pos..pos + bytes.len()]);
+            pos += bytes.len();
+        }
+        Ok(0)
+    }
+
     fn getpid() -> SyscallResult {
         Ok(cpu().curr.as_ref().unwrap().pid)
     }
@@ -114,6 +158,64 @@ impl SyscallProc for SyscallImpl {
         Ok(cpu().curr.as_ref().unwrap().tid.0)
     }
 
+    fn setpgid(pid: usize, pgid: usize) -> SyscallResult {
+        if (pgid as isize) < 0 {
+            return Err(Errno::EINVAL);
+        }
+        let curr = cpu().curr.as_ref().unwrap();
+        let pid = if pid == 0 { curr.pid } else { pid };
+        let pgid = if pgid == 0 { pid } else { pgid };
+
+        // Only the calling process or its children can be moved.
+        let task = find_processes(|task| task.pid == pid)
+            .pop()
+            .filter(|task| {
+                task.pid == curr.pid
+                    || task
+                        .locked_inner()
+                        .parent
+                        .as_ref()
+                        .and_then(Weak::upgrade)
+                        .map_or(false, |parent| parent.pid == curr.pid)
+            })
+            .ok_or(Errno::ESRCH)?;
+        let sid = curr.thread_group.sid();
+        if task.thread_group.sid() != sid || task.thread_group.sid() == pid {
+            return Err(Errno::EPERM);
+        }
+        if pgid != pid
+            && find_processes(|task| {
+                task.thread_group.pgid() == pgid && task.thread_group.sid() == sid
+            })
+            .is_empty()
+        {
+            return Err(Errno::EPERM);
+        }
+        task.thread_group.set_pgid(pgid);
+        Ok(0)
+    }
+
+    fn getpgid(pid: usize) -> SyscallResult {
+        let curr = cpu().curr.as_ref().unwrap();
+        if pid == 0 {
+            return Ok(curr.thread_group.pgid());
+        }
+        find_processes(|task| task.pid == pid)
+            .pop()
+            .map(|task| task.thread_group.pgid())
+            .ok_or(Errno::ESRCH)
+    }
+
+    fn setsid() -> SyscallResult {
+        let curr = cpu().curr.as_ref().unwrap();
+        if !find_processes(|task| task.thread_group.pgid() == curr.pid).is_empty() {
+            return Err(Errno::EPERM);
+        }
+        curr.thread_group.set_sid(curr.pid);
+        curr.thread_group.set_pgid(curr.pid);
+        Ok(curr.pid)
+    }
+
     fn set_tid_address(tidptr: usize) -> SyscallResult {
         let curr = cpu().curr.as_ref().unwrap();
         curr.inner().clear_child_tid = tidptr;
@@ -167,4 +269,51 @@ impl SyscallProc for SyscallImpl {
             prot.unwrap(),
         )
     }
+
+    fn mlock(addr: usize, len: usize) -> SyscallResult {
+        let curr = cpu().curr.as_ref().unwrap();
+        let limit = curr.limits.lock().max_locked_size();
+        do_mlock(&mut curr.mm(), addr.into(), len, limit)
+    }
+
+    fn munlock(addr: usize, len: usize) -> SyscallResult {
+        do_munlock(&mut cpu().curr.as_ref().unwrap().mm(), addr.into(), len)
+    }
+
+    fn madvise(addr: usize, len: usize, advice: usize) -> SyscallResult {
+        do_madvise(
+            &mut cpu().curr.as_ref().unwrap().mm(),
+            addr.into(),
+            len,
+            advice,
+        )
+    }
+
+    fn membarrier(cmd: usize, flags: usize, _cpu_id: usize) -> SyscallResult {
+        if flags != 0 {
+            return Err(Errno::EINVAL);
+        }
+        // Barriers are issued on all harts for every command, thus registration is not
+        // required before using the expedited commands.
+        match cmd {
+            MEMBARRIER_CMD_QUERY => Ok(MEMBARRIER_CMD_GLOBAL
+                | MEMBARRIER_CMD_GLOBAL_EXPEDITED
+                | MEMBARRIER_CMD_REGISTER_GLOBAL_EXPEDITED
+                | MEMBARRIER_CMD_PRIVATE_EXPEDITED
+                | MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED
+                | MEMBARRIER_CMD_PRIVATE_EXPEDITED_SYNC_CORE
+                | MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED_SYNC_CORE),
+            MEMBARRIER_CMD_GLOBAL
+            | MEMBARRIER_CMD_GLOBAL_EXPEDITED
+            | MEMBARRIER_CMD_PRIVATE_EXPEDITED
+            | MEMBARRIER_CMD_PRIVATE_EXPEDITED_SYNC_CORE => {
+                fence_all_harts()?;
+                Ok(0)
+            }
+            MEMBARRIER_CMD_REGISTER_GLOBAL_EXPEDITED
+            | MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED
+            | MEMBARRIER_CMD_REGISTER_PRIVATE_EXPEDITED_SYNC_CORE => Ok(0),
+            _ => Err(Errno::EINVAL),
+        }
+    }
 }
@@ -1,19 +1,24 @@
 use errno::Errno;
 use syscall_interface::*;
-use time_subsys::{TimeSpec, TimeVal, NSEC_PER_SEC};
+use time_subsys::*;
 
 use crate::{
     arch::{mm::VirtAddr, timer::get_time_sec_f64},
     read_user,
-    task::{cpu, do_yield},
+    task::cpu,
+    timer::{current_time, do_nanosleep, set_real_timer, KernelClock},
     write_user,
 };
 
 use super::SyscallImpl;
 
 impl SyscallTimer for SyscallImpl {
-    fn clock_gettime(_clockid: usize, tp: usize) -> SyscallResult {
-        let time = TimeSpec::new(get_time_sec_f64());
+    fn clock_gettime(clockid: usize, tp: usize) -> SyscallResult {
+        let mut time = TimeSpec::default();
+        let err = KernelClock::clock_get(ClockID::new(clockid), &mut time);
+        if err != 0 {
+            return Err(Errno::try_from(err as isize).unwrap());
+        }
         write_user!(
             cpu().curr.as_ref().unwrap().mm(),
             VirtAddr::from(tp),
@@ -23,8 +28,58 @@ impl SyscallTimer for SyscallImpl {
         Ok(0)
     }
 
+    fn clock_getres(clockid: usize, res: usize) -> SyscallResult {
+        let mut time = TimeSpec::default();
+        let err = KernelClock::clock_getres(ClockID::new(clockid), &mut time);
+        if err != 0 {
+            return Err(Errno::try_from(err as isize).unwrap());
+        }
+        if res != 0 {
+            write_user!(
+                cpu().curr.as_ref().unwrap().mm(),
+                VirtAddr::from(res),
+                time,
+                TimeSpec
+            )?;
+        }
+        Ok(0)
+    }
+
+    fn getitimer(which: usize, curr_value: usize) -> SyscallResult {
+        if ITimerType::try_from(which).map_err(|_| Errno::EINVAL)? != ITimerType::REAL {
+            return Err(Errno::EINVAL);
+        }
+
+        let curr = cpu().curr.as_ref().unwrap();
+        let now = TimeSpec::new(get_time_sec_f64());
+        let value = ITimerVal::from(curr.real_timer.lock().get(now));
+        write_user!(curr.mm(), VirtAddr::from(curr_value), value, ITimerVal)?;
+        Ok(0)
+    }
+
+    fn setitimer(which: usize, new_value: usize, old_value: usize) -> SyscallResult {
+        // only wall-clock timer supported
+        if ITimerType::try_from(which).map_err(|_| Errno::EINVAL)? != ITimerType::REAL {
+            return Err(Errno::EINVAL);
+        }
+
+        let curr = cpu().curr.as_ref().unwrap();
+        let mut new = ITimerVal::default();
+        read_user!(curr.mm(), VirtAddr::from(new_value), new, ITimerVal)?;
+        if new.it_value.tv_usec >= USEC_PER_SEC || new.it_interval.tv_usec >= USEC_PER_SEC {
+            return Err(Errno::EINVAL);
+        }
+
+        let old = set_real_timer(curr.pid, &mut curr.real_timer.lock(), ITimer::from(&new));
+        if old_value != 0 {
+            let old = ITimerVal::from(old);
+            write_user!(curr.mm(), VirtAddr::from(old_value), old, ITimerVal)?;
+        }
+        Ok(0)
+    }
+
     fn gettimeofday(tv: usize) -> SyscallResult {
-        let time = TimeVal::new(get_time_sec_f64());
+        let time = TimeVal::from(current_time());
         write_user!(
             cpu().curr.as_ref().unwrap().mm(),
             VirtAddr::from(tv),
@@ -43,15 +98,47 @@ impl SyscallTimer for SyscallImpl {
             return Err(Errno::EINVAL);
         }
 
-        let end = get_time_sec_f64() + req.time_in_sec();
-        while get_time_sec_f64() < end {
-            unsafe { do_yield() };
+        let now = TimeSpec::new(get_time_sec_f64());
+        if let Some(remain) = do_nanosleep(sleep_deadline(now, req, 0)) {
+            if rem != 0 {
+                write_user!(
+                    cpu().curr.as_ref().unwrap().mm(),
+                    VirtAddr::from(rem),
+                    remain,
+                    TimeSpec
+                )?;
+            }
+            return Err(Errno::EINTR);
+        }
+
+        Ok(0)
+    }
+
+    fn clock_nanosleep(
+        clockid: usize,
+        flags: usize,
+        request: usize,
+        remain: usize,
+    ) -> SyscallResult {
+        match ClockType::try_from(clockid).map_err(|_| Errno::EINVAL)? {
+            // Both clocks are measured by the time since boot.
+            ClockType::REALTIME | ClockType::MONOTONIC => {}
+            _ => return Err(Errno::EOPNOTSUPP),
+        }
+
+        let curr = cpu().curr.as_ref().unwrap();
+        let mut req = TimeSpec::new(0.0);
+        read_user!(curr.mm(), VirtAddr::from(request), req, TimeSpec)?;
+        if req.tv_nsec >= NSEC_PER_SEC {
+            return Err(Errno::EINVAL);
         }
 
-        if rem != 0 {
-            let rem_addr = VirtAddr::from(rem);
-            let rem = TimeSpec::new(0.0);
-            write_user!(cpu().curr.as_ref().unwrap().mm(), rem_addr, rem, TimeSpec)?;
+        let now = TimeSpec::new(get_time_sec_f64());
+        if let Some(rem) = do_nanosleep(sleep_deadline(now, req, flags)) {
+            if flags & TIMER_ABSTIME == 0 && remain != 0 {
+                write_user!(curr.mm(), VirtAddr::from(remain), rem, TimeSpec)?;
+            }
+            return Err(Errno::EINTR);
         }
 
         Ok(0)
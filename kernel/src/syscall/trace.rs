@@ -0,0 +1,76 @@
+//! Decodes syscall arguments for tracing, like what `strace` does.
+
+use alloc::{format, vec, vec::Vec};
+use syscall_interface::{raw_args, SyscallArg, SyscallNO};
+use vfs::{OpenFlags, StatMode};
+
+use crate::{arch::mm::VirtAddr, task::cpu};
+
+/// Reads a string from user space, or keeps the raw address if failed.
+fn user_str(addr: usize) -> SyscallArg {
+    let curr = cpu().curr.as_ref().unwrap();
+    let s = curr.mm().get_str(VirtAddr::from(addr));
+    match s {
+        Ok(s) => SyscallArg::Str(s),
+        Err(_) => SyscallArg::Hex(addr),
+    }
+}
+
+fn open_flags(flags: usize) -> SyscallArg {
+    match OpenFlags::from_bits(flags as u32) {
+        Some(flags) => SyscallArg::Named(format!("{:?}", flags)),
+        None => SyscallArg::Hex(flags),
+    }
+}
+
+fn mode(mode: usize) -> SyscallArg {
+    match StatMode::from_bits(mode as u32) {
+        Some(mode) if !mode.is_empty() => SyscallArg::Named(format!("{:#o}", mode.bits())),
+        _ => SyscallArg::Hex(mode),
+    }
+}
+
+/// Decodes arguments of a finished syscall.
+///
+/// Strings are read from user space again, thus arguments of syscalls replacing
+/// the address space (e.g. `execve`) are kept raw.
+pub fn decode_args(id: SyscallNO, args: &[usize; 6]) -> Vec<SyscallArg> {
+    use SyscallArg::*;
+
+    match id {
+        SyscallNO::OPENAT => vec![Fd(args[0]), user_str(args[1]), open_flags(args[2]), mode(args[3])],
+        SyscallNO::MKDIRAT => vec![Fd(args[0]), user_str(args[1]), mode(args[2])],
+        SyscallNO::MKNODAT => vec![Fd(args[0]), user_str(args[1]), mode(args[2]), Hex(args[3])],
+        SyscallNO::UNLINKAT => vec![Fd(args[0]), user_str(args[1]), Hex(args[2])],
+        SyscallNO::LINKAT | SyscallNO::RENAMEAT2 => vec![
+            Fd(args[0]),
+            user_str(args[1]),
+            Fd(args[2]),
+            user_str(args[3]),
+            Hex(args[4]),
+        ],
+        SyscallNO::FACCESSAT => vec![Fd(args[0]), user_str(args[1]), Hex(args[2]), Hex(args[3])],
+        SyscallNO::READLINKAT => {
+            vec![Fd(args[0]), user_str(args[1]), Hex(args[2]), Int(args[3] as isize)]
+        }
+        SyscallNO::NEWFSTATAT => vec![Fd(args[0]), user_str(args[1]), Hex(args[2]), Hex(args[3])],
+        SyscallNO::CHDIR => vec![user_str(args[0])],
+        SyscallNO::CLOSE | SyscallNO::DUP => vec![Fd(args[0])],
+        SyscallNO::DUP3 => vec![Fd(args[0]), Fd(args[1]), open_flags(args[2])],
+        SyscallNO::FCNTL | SyscallNO::IOCTL => vec![Fd(args[0]), Hex(args[1]), Hex(args[2])],
+        SyscallNO::READ
+        | SyscallNO::WRTIE
+        | SyscallNO::READV
+        | SyscallNO::WRITEV
+        | SyscallNO::GETDENTS64 => vec![Fd(args[0]), Hex(args[1]), Int(args[2] as isize)],
+        SyscallNO::PWRITE | SyscallNO::PWRITEV => vec![
+            Fd(args[0]),
+            Hex(args[1]),
+            Int(args[2] as isize),
+            Int(args[3] as isize),
+        ],
+        SyscallNO::LSEEK => vec![Fd(args[0]), Int(args[1] as isize), Int(args[2] as isize)],
+        SyscallNO::FSTAT => vec![Fd(args[0]), Hex(args[1])],
+        _ => raw_args(args),
+    }
+}
@@ -1,4 +1,4 @@
-use core::cell::SyncUnsafeCell;
+use core::{cell::SyncUnsafeCell, mem::size_of};
 
 use alloc::{collections::LinkedList, string::String, sync::Arc, vec::Vec};
 use errno::Errno;
@@ -6,6 +6,7 @@ use kernel_sync::SpinLock;
 use mm_rv::{Frame, PTEFlags, Page};
 use signal_defs::*;
 use syscall_interface::SyscallResult;
+use time_subsys::CpuTime;
 
 use crate::{
     arch::{
@@ -14,9 +15,12 @@ use crate::{
         TaskContext,
     },
     error::*,
+    fs::{FDManager, FdTable},
     loader::from_elf,
-    mm::{KERNEL_MM, MM},
+    mm::{VMFlags, KERNEL_MM, MM},
     task::{TrapFrameTracker, TID},
+    timer::RealTimer,
+    write_user,
 };
 
 #[cfg(feature = "uintr")]
@@ -117,13 +121,38 @@ pub fn do_clone(
         return Err(Errno::EINVAL);
     }
 
+    // The stack pointer must be aligned to 16 bytes in the standard ABI.
+    if stack & 0xf != 0 {
+        return Err(Errno::EINVAL);
+    }
+
     // Clone address space
+    let limits = if flags.contains(CloneFlags::CLONE_THREAD) {
+        curr.limits.clone()
+    } else {
+        Arc::new(SpinLock::new(*curr.limits.lock()))
+    };
     let mm = if flags.contains(CloneFlags::CLONE_VM) {
         curr.inner().mm.clone()
     } else {
-        Arc::new(SpinLock::new(curr.mm().clone()?))
+        let mut mm = curr.mm().clone()?;
+        mm.set_limits(&limits);
+        Arc::new(SpinLock::new(mm))
     };
 
+    /*
+     * The C library saves the entry function and its argument at the top of
+     * the new stack, which will be popped by the child right after returning
+     * from clone, so these two words must be accessible.
+     */
+    if stack != 0 {
+        mm.lock().check_user_access(
+            VirtAddr::from(stack),
+            2 * size_of::<usize>(),
+            VMFlags::READ | VMFlags::WRITE,
+        )?;
+    }
+
     // New kernel stack
     let kstack = KernelStack::new()?;
     let tid = TID::new();
@@ -163,6 +192,14 @@ pub fn do_clone(
             }
             sig
         },
+        thread_group: if flags.contains(CloneFlags::CLONE_THREAD) {
+            curr.thread_group.clone()
+        } else {
+            Arc::new(ThreadGroup::new(
+                curr.thread_group.pgid(),
+                curr.thread_group.sid(),
+            ))
+        },
         fs_info: if flags.contains(CloneFlags::CLONE_FS) {
             curr.fs_info.clone()
         } else {
@@ -175,9 +212,20 @@ pub fn do_clone(
             let orig = curr.sig_actions.lock();
             Arc::new(SpinLock::new(orig.clone()))
         },
+        // a child created via fork(2) does not inherit its parent's interval timers
+        real_timer: if flags.contains(CloneFlags::CLONE_THREAD) {
+            curr.real_timer.clone()
+        } else {
+            Arc::new(SpinLock::new(RealTimer::new()))
+        },
+        limits,
+        cpu_time: SpinLock::new(CpuTime::new()),
         locked_inner: SpinLock::new(TaskLockedInner {
             state: TaskState::RUNNABLE,
             sleeping_on: None,
+            sig_pending: SigPending::new(),
+            sig_blocked: SigSet::new(),
+            saved_sigmask: None,
             parent: if flags.intersects(CloneFlags::CLONE_PARENT | CloneFlags::CLONE_THREAD) {
                 let locked = curr.locked_inner();
                 locked.parent.clone()
@@ -200,13 +248,24 @@ pub fn do_clone(
             } else {
                 0
             },
-            sig_pending: SigPending::new(),
-            sig_blocked: SigSet::new(),
+            robust_list: 0,
+            /*
+             * sigaltstack should be cleared when sharing the same VM,
+             * while a child created via fork(2) inherits a copy of its
+             * parent's alternate signal stack settings.
+             */
+            sig_altstack: if flags.contains(CloneFlags::CLONE_VM)
+                && !flags.contains(CloneFlags::CLONE_VFORK)
+            {
+                SigAltStack::new()
+            } else {
+                curr.inner().sig_altstack
+            },
             mm,
             files: if flags.contains(CloneFlags::CLONE_FILES) {
                 curr.inner().files.clone()
             } else {
-                Arc::new(SpinLock::new(curr.files().clone()))
+                Arc::new(FdTable::new(FDManager::clone(&curr.files().read())))
             },
         }),
         #[cfg(feature = "uintr")]
@@ -215,24 +274,21 @@ pub fn do_clone(
 
     // Set tid in parent address space
     if flags.contains(CloneFlags::CLONE_PARENT_SETTID) {
-        let ptid = curr.mm().alloc_frame(ptid)?.start_address() + ptid.page_offset();
-        unsafe { *(ptid.get_mut() as *mut i32) = tid_num as i32 };
+        write_user!(curr.mm(), ptid, tid_num as i32, i32)?;
     }
 
-    // Set tid in child address space (COW)
-    if flags.intersects(CloneFlags::CLONE_CHILD_SETTID | CloneFlags::CLONE_CHILD_CLEARTID) {
-        let ctid = new_task.mm().alloc_frame(ctid)?.start_address() + ctid.page_offset();
-        unsafe {
-            *(ctid.get_mut() as *mut i32) = if flags.contains(CloneFlags::CLONE_CHILD_SETTID) {
-                tid_num as i32
-            } else {
-                0
-            }
-        };
+    // Set tid in child address space (COW) before it first runs
+    if flags.contains(CloneFlags::CLONE_CHILD_SETTID) {
+        write_user!(new_task.mm(), ctid, tid_num as i32, i32)?;
     }
 
     /* New task will not be dropped from now on. */
 
+    if flags.contains(CloneFlags::CLONE_THREAD) {
+        new_task.thread_group.join();
+    }
+
+    register_task(&new_task);
     TASK_MANAGER.lock().add(new_task.clone());
 
     // we don't need to lock the new task
@@ -248,13 +304,20 @@ pub fn do_clone(
 }
 
 /// A helper for [`syscall_interface::SyscallProc::execve`]
-pub fn do_exec(dir: String, elf_data: &[u8], args: Vec<String>) -> KernelResult {
+pub fn do_exec(
+    dir: String,
+    elf_data: &[u8],
+    args: Vec<String>,
+    envs: Vec<String>,
+) -> KernelResult {
     let curr = cpu().curr.as_ref().unwrap();
     log::trace!("EXEC {:?} DIR [{}] {:?}", &curr, &dir, &args);
 
     // memory mappings are not preserved
     let mut mm = MM::new()?;
-    let sp = from_elf(elf_data, args, &mut mm)?;
+    mm.set_limits(&curr.limits);
+    let stack_size = curr.limits.lock().stack_size();
+    let sp = from_elf(elf_data, args, envs, stack_size, &mut mm)?;
 
     // re-initialize kernel stack
     curr.inner().kstack = KernelStack::new()?;
@@ -280,6 +343,7 @@ pub fn do_exec(dir: String, elf_data: &[u8], args: Vec<String>) -> KernelResult
 
     // the dispositions of any signals that are being caught are reset to the default
     *curr.sig_actions.lock() = [SigAction::default(); NSIG];
+    curr.inner().sig_altstack = SigAltStack::new();
 
     /*
      * The file descriptor table is unshared, undoing the effect of the
@@ -287,8 +351,9 @@ pub fn do_exec(dir: String, elf_data: &[u8], args: Vec<String>) -> KernelResult
      * open across an execve(). File descriptors that are marked close-on-exec
      * are closed; see the description of FD_CLOEXEC in fcntl(2).
      */
-    curr.inner().files = Arc::new(SpinLock::new(curr.files().clone()));
-    unsafe { &mut *curr.inner().files.as_mut_ptr() }.cloexec();
+    let mut files = FDManager::clone(&curr.files().read());
+    files.cloexec();
+    curr.inner().files = Arc::new(FdTable::new(files));
 
     curr.inner().ctx = TaskContext::new(user_trap_return as usize, kstack_base);
 
@@ -4,9 +4,11 @@ use mm_rv::VirtAddr;
 use oscomp::finish_test;
 use signal_defs::*;
 use syscall_interface::SyscallResult;
+use time_subsys::Rusage;
 
 use crate::{
     arch::{TaskContext, __move_to_next},
+    timer::{cancel_real_timer, cycles_to_timespec},
     write_user,
 };
 
@@ -20,10 +22,16 @@ use super::*;
 pub unsafe fn do_exit(exit_code: i32) {
     let curr = cpu().curr.as_ref().unwrap();
     log::trace!("{:?} exited with code {}", curr, exit_code);
+    // The futexes held cannot be recovered if the list is broken.
+    let _ = exit_robust_list();
     let curr_ctx = {
         let mut locked_inner = curr.locked_inner();
         curr.inner().exit_code = exit_code;
         locked_inner.state = TaskState::ZOMBIE;
+        if curr.thread_group.leave() {
+            log::trace!("Thread group {} exited", curr.pid);
+            cancel_real_timer(curr.pid);
+        }
         &curr.inner().ctx as *const TaskContext
     };
 
@@ -145,19 +153,26 @@ fn valid_child(pid: isize, options: WaitOptions, task: &Task) -> bool {
 }
 
 /// A helper for [`syscall_interface::SyscallProc::wait4`].
+///
+/// Returns 0 at once with `WNOHANG` if no child has changed state yet, or `ECHILD` if
+/// no child matches `pid`. A child stopped by a signal is reported once with
+/// `WUNTRACED`.
+///
+/// `rusage` is filled with the CPU time of the child if not NULL, which is all counted
+/// as user time.
 pub fn do_wait(
     pid: isize,
     options: WaitOptions,
     _infop: usize,
     wstatus: usize,
-    _rusage: usize,
+    rusage: usize,
 ) -> SyscallResult {
     log::trace!("WAIT4 {} {:?} status=0x{:x}", pid, options, wstatus);
 
     loop {
         let mut flag = false;
         let mut need_sched = false;
-        let mut child: usize = 0;
+        let mut child_index: usize = 0;
         let curr = cpu().curr.as_ref().unwrap();
         let mut locked = curr.locked_inner();
         for (index, task) in locked.children.iter().enumerate() {
@@ -169,7 +184,12 @@ pub fn do_wait(
 
             let state = task.get_state();
             if state == TaskState::STOPPED {
-                todo!()
+                // The stop signal is kept in the exit code until reported.
+                if options.contains(WaitOptions::WUNTRACED) && task.inner().exit_code != 0 {
+                    flag = true;
+                    child_index = index;
+                    break;
+                }
             } else {
                 if state == TaskState::DEAD {
                     continue;
@@ -178,9 +198,14 @@ pub fn do_wait(
                     if !options.contains(WaitOptions::WEXITED) {
                         continue;
                     }
+                    // The thread group leader cannot be reaped until all threads
+                    // in this group have exited.
+                    if task.tid.0 == task.pid && !task.thread_group.is_dead() {
+                        continue;
+                    }
                     // a child with changed state exists
                     flag = true;
-                    child = index;
+                    child_index = index;
                     break;
                 }
                 if !options.contains(WaitOptions::WCONTINUED) {
@@ -189,23 +214,47 @@ pub fn do_wait(
             }
         }
         if !flag {
-            if options.contains(WaitOptions::WNONHANG) || !need_sched {
+            if !need_sched {
                 log::info!("{:?}", locked.children);
                 return Err(Errno::ECHILD);
             }
+            if options.contains(WaitOptions::WNONHANG) {
+                return Ok(0);
+            }
 
             // schedule current task
             drop(locked);
             unsafe { do_yield() };
         } else {
-            // reclaim resources
-            let child = locked.children.remove(child);
+            let child = locked.children.iter().nth(child_index).unwrap().clone();
+            let exit_code = child.inner().exit_code;
+            let status = if child.get_state() == TaskState::STOPPED {
+                // reported only once
+                child.inner().exit_code = 0;
+                (exit_code << 8) | 0x7f
+            } else {
+                // reclaim resources
+                locked.children.remove(child_index);
+                // killed by a signal
+                if exit_code < 0 {
+                    -exit_code
+                } else {
+                    exit_code << 8
+                }
+            };
+            drop(locked);
 
             // store status information
             if wstatus != 0 {
-                let status = (child.inner().exit_code << 8) as i32;
                 write_user!(curr.mm(), VirtAddr::from(wstatus), status, i32)?;
             }
+            if rusage != 0 {
+                let usage = Rusage {
+                    ru_utime: cycles_to_timespec(child.thread_group.cpu_time()).into(),
+                    ..Rusage::default()
+                };
+                write_user!(curr.mm(), VirtAddr::from(rusage), usage, Rusage)?;
+            }
 
             return Ok(child.pid);
         }
@@ -0,0 +1,245 @@
+use alloc::{
+    collections::{BTreeMap, VecDeque},
+    sync::{Arc, Weak},
+};
+use errno::Errno;
+use kernel_sync::SpinLock;
+use spin::Lazy;
+use syscall_interface::{RobustListHead, FUTEX_OWNER_DIED, FUTEX_TID_MASK, FUTEX_WAITERS};
+use time_subsys::TimeSpec;
+
+use crate::{
+    arch::{mm::VirtAddr, timer::get_time_sec_f64},
+    mm::VMFlags,
+    read_user,
+    timer::TIMER_WHEEL,
+    write_user,
+};
+
+use super::{cpu, do_sleep, do_wake, signal_pending, Task, TaskState};
+
+/// Identity of a futex word, see `get_futex_key` in Linux.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FutexKey {
+    /// A word in a private mapping, identified by the address space and the virtual
+    /// address, since the frame mapped may be replaced by copy-on-write after `fork`.
+    Private { mm: usize, uaddr: usize },
+
+    /// A word in a shared mapping, identified by the physical address, so that the same
+    /// word mapped in different address spaces shares the same queue.
+    Shared(usize),
+}
+
+impl FutexKey {
+    /// Gets the key of the futex word at `uaddr` in the address space of `task`.
+    ///
+    /// The word must have been read, so that the frame is allocated before translation.
+    /// All words are treated as private if `private` is set, i.e. `FUTEX_PRIVATE_FLAG`.
+    pub fn new(task: &Task, uaddr: VirtAddr, private: bool) -> Result<Self, Errno> {
+        let mm_id = Arc::as_ptr(&task.inner().mm) as usize;
+        let mut mm = task.mm();
+        let shared = !private
+            && mm
+                .get_vma(uaddr, |vma, _, _| Ok(vma.flags.contains(VMFlags::SHARED)))
+                .map_err(|_| Errno::EFAULT)?;
+        if shared {
+            let pa = mm.translate(uaddr).map_err(|_| Errno::EFAULT)?;
+            Ok(Self::Shared(pa.value()))
+        } else {
+            Ok(Self::Private {
+                mm: mm_id,
+                uaddr: uaddr.value(),
+            })
+        }
+    }
+}
+
+/// Tasks waiting on futex words, keyed by [`FutexKey`].
+pub struct FutexQueue {
+    waiters: BTreeMap<FutexKey, VecDeque<Weak<Task>>>,
+}
+
+impl FutexQueue {
+    pub fn new() -> Self {
+        Self {
+            waiters: BTreeMap::new(),
+        }
+    }
+
+    /// Appends a waiter to the queue of `key`.
+    fn push(&mut self, key: FutexKey, task: &Arc<Task>) {
+        self.waiters
+            .entry(key)
+            .or_insert_with(VecDeque::new)
+            .push_back(Arc::downgrade(task));
+    }
+
+    /// Removes the waiter from the queue of `key`.
+    ///
+    /// Returns false if the waiter has already been removed by [`FutexQueue::pop`].
+    fn remove(&mut self, key: FutexKey, task: &Arc<Task>) -> bool {
+        let queue = match self.waiters.get_mut(&key) {
+            Some(queue) => queue,
+            None => return false,
+        };
+        let len = queue.len();
+        queue.retain(|waiter| !core::ptr::eq(waiter.as_ptr(), Arc::as_ptr(task)));
+        let removed = queue.len() != len;
+        if queue.is_empty() {
+            self.waiters.remove(&key);
+        }
+        removed
+    }
+
+    /// Pops at most `n` live waiters in the queue of `key` in FIFO order.
+    fn pop(&mut self, key: FutexKey, n: usize) -> VecDeque<Arc<Task>> {
+        let mut tasks = VecDeque::new();
+        if let Some(queue) = self.waiters.get_mut(&key) {
+            while tasks.len() < n {
+                match queue.pop_front() {
+                    // Waiters that have exited are skipped.
+                    Some(waiter) => tasks.extend(waiter.upgrade()),
+                    None => break,
+                }
+            }
+            if queue.is_empty() {
+                self.waiters.remove(&key);
+            }
+        }
+        tasks
+    }
+}
+
+/// Maximum number of entries walked in a robust futex list, which may be corrupted
+/// into a loop by user.
+const ROBUST_LIST_LIMIT: usize = 2048;
+
+/// Global futex wait queues.
+pub static FUTEX_QUEUE: Lazy<SpinLock<FutexQueue>> = Lazy::new(|| SpinLock::new(FutexQueue::new()));
+
+/// Current task sleeps on the futex `key` if `check` still returns true with the
+/// queue locked, so that a [`do_futex_wake`] racing with this function will not be lost.
+///
+/// Returns `EAGAIN` if `check` fails, `ETIMEDOUT` if `timeout` expires before the task
+/// is woken up, or `EINTR` if the task is woken up by others, e.g. a signal.
+pub fn do_futex_wait(
+    key: FutexKey,
+    check: impl FnOnce() -> Result<bool, Errno>,
+    timeout: Option<TimeSpec>,
+) -> Result<(), Errno> {
+    let curr = cpu().curr.as_ref().unwrap().clone();
+    let tid = curr.tid.0;
+
+    let mut queue = FUTEX_QUEUE.lock();
+    if !check()? {
+        return Err(Errno::EAGAIN);
+    }
+    queue.push(key, &curr);
+    curr.locked_inner().state = TaskState::INTERRUPTIBLE;
+    // Checked after the state is set, so that a signal queued in between wakes it up.
+    if signal_pending() {
+        queue.remove(key, &curr);
+        curr.locked_inner().state = TaskState::RUNNING;
+        return Err(Errno::EINTR);
+    }
+    if let Some(timeout) = timeout {
+        let deadline = TimeSpec::new(get_time_sec_f64()) + timeout;
+        TIMER_WHEEL.lock().add(deadline, tid);
+    }
+    drop(queue);
+
+    unsafe { do_sleep() };
+
+    // The waiter is still in the queue if not woken up by `FUTEX_WAKE`.
+    if !FUTEX_QUEUE.lock().remove(key, &curr) {
+        TIMER_WHEEL.lock().cancel(tid);
+        return Ok(());
+    }
+    // The timer has been removed from the wheel if expired, or by a signal.
+    let expired = timeout.is_some() && TIMER_WHEEL.lock().cancel(tid).is_none();
+    if expired && !signal_pending() {
+        Err(Errno::ETIMEDOUT)
+    } else {
+        Err(Errno::EINTR)
+    }
+}
+
+/// Wakes up at most `n` tasks sleeping on the futex `key`.
+///
+/// Returns the number of tasks woken up.
+pub fn do_futex_wake(key: FutexKey, n: usize) -> usize {
+    let tasks = FUTEX_QUEUE.lock().pop(key, n);
+    tasks.iter().for_each(|task| do_wake(task));
+    tasks.len()
+}
+
+/// Walks the robust futex list of current task before it exits, like `exit_robust_list`
+/// in Linux.
+///
+/// Each futex word still owned by the task is marked with [`FUTEX_OWNER_DIED`], keeping
+/// [`FUTEX_WAITERS`], and a waiter is woken up to recover the lock. The pending entry
+/// is handled last, waking up a waiter if the word has been released to zero, since the
+/// task may exit right after unlocking it.
+///
+/// The walk stops at the first invalid pointer. Priority-inheritance futexes are
+/// treated the same as normal ones.
+pub fn exit_robust_list() -> Result<(), Errno> {
+    let curr = cpu().curr.as_ref().unwrap();
+    let head_addr = curr.inner().robust_list;
+    if head_addr == 0 {
+        return Ok(());
+    }
+
+    let mut head = RobustListHead::default();
+    read_user!(curr.mm(), VirtAddr::from(head_addr), head, RobustListHead)?;
+    let pending = head.list_op_pending & !1;
+    let futex_addr = |entry: usize| VirtAddr::from((entry as isize + head.futex_offset) as usize);
+
+    let mut entry = head.list & !1;
+    for _ in 0..ROBUST_LIST_LIMIT {
+        if entry == head_addr {
+            break;
+        }
+        // Fetches the next entry first, since the lock may be freed once woken up.
+        let mut next = 0usize;
+        read_user!(curr.mm(), VirtAddr::from(entry), next, usize)?;
+        if entry != pending {
+            handle_futex_death(futex_addr(entry), false)?;
+        }
+        entry = next & !1;
+    }
+    if pending != 0 {
+        handle_futex_death(futex_addr(pending), true)?;
+    }
+    Ok(())
+}
+
+/// Marks the futex word at `uaddr` if it is owned by current task, or released while
+/// the operation on it is `pending`, and wakes up a waiter.
+fn handle_futex_death(uaddr: VirtAddr, pending: bool) -> Result<(), Errno> {
+    let curr = cpu().curr.as_ref().unwrap();
+    if uaddr.value() % core::mem::size_of::<u32>() != 0 {
+        return Err(Errno::EINVAL);
+    }
+    let mut word = 0u32;
+    read_user!(curr.mm(), uaddr, word, u32)?;
+    let key = FutexKey::new(curr, uaddr, false)?;
+
+    if pending && word == 0 {
+        do_futex_wake(key, 1);
+        return Ok(());
+    }
+    if word & FUTEX_TID_MASK != curr.tid.0 as u32 {
+        return Ok(());
+    }
+    write_user!(
+        curr.mm(),
+        uaddr,
+        (word & FUTEX_WAITERS) | FUTEX_OWNER_DIED,
+        u32
+    )?;
+    if word & FUTEX_WAITERS != 0 {
+        do_futex_wake(key, 1);
+    }
+    Ok(())
+}
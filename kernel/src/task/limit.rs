@@ -2,16 +2,93 @@ use errno::Errno;
 use syscall_interface::*;
 
 use crate::{
-    arch::mm::{VirtAddr, LOW_MAX_VA},
-    config::USER_STACK_SIZE,
+    arch::mm::{VirtAddr, LOW_MAX_VA, PAGE_SIZE},
+    config::{
+        DEFAULT_FD_LIMIT, MAX_FD_LIMIT, USER_MEMLOCK_SIZE, USER_STACK_MAX_SIZE, USER_STACK_SIZE,
+    },
+    mm::page_align,
     read_user, write_user,
 };
 
 use super::*;
 
+/// Resource limits of a process, shared by threads and inherited by children.
+///
+/// There are no users or capabilities, thus no task is privileged to raise its hard
+/// limits.
+#[derive(Debug, Clone, Copy)]
+pub struct ResourceLimits {
+    /// Maximum size of the user stack, which grows down on demand.
+    pub stack: Rlimit,
+
+    /// Maximum number of file descriptors, the soft limit of which is enforced by
+    /// [`crate::fs::FDManager`].
+    pub nofile: Rlimit,
+
+    /// Maximum size of the address space, which is not enforced.
+    pub addr_space: Rlimit,
+
+    /// Maximum size of memory locked by `mlock`.
+    pub memlock: Rlimit,
+}
+
+impl ResourceLimits {
+    /// Creates the default limits of the init task.
+    pub fn new() -> Self {
+        Self {
+            stack: Rlimit {
+                rlim_cur: USER_STACK_MAX_SIZE as u64,
+                rlim_max: RLIM_INFINITY,
+            },
+            nofile: Rlimit {
+                rlim_cur: DEFAULT_FD_LIMIT as u64,
+                rlim_max: MAX_FD_LIMIT as u64,
+            },
+            addr_space: Rlimit {
+                rlim_cur: (LOW_MAX_VA + 1) as u64,
+                rlim_max: (LOW_MAX_VA + 1) as u64,
+            },
+            memlock: Rlimit {
+                rlim_cur: USER_MEMLOCK_SIZE as u64,
+                rlim_max: USER_MEMLOCK_SIZE as u64,
+            },
+        }
+    }
+
+    /// Gets the mutable limit of a resource.
+    ///
+    /// Returns `EINVAL` if the resource is not supported.
+    pub fn get_mut(&mut self, resource: i32) -> Result<&mut Rlimit, Errno> {
+        match resource {
+            RLIMIT_STACK => Ok(&mut self.stack),
+            RLIMIT_NOFILE => Ok(&mut self.nofile),
+            RLIMIT_AS => Ok(&mut self.addr_space),
+            RLIMIT_MEMLOCK => Ok(&mut self.memlock),
+            _ => Err(Errno::EINVAL),
+        }
+    }
+
+    /// Returns the size of the initial user stack, which is the soft limit of
+    /// `RLIMIT_STACK` aligned to pages, but at least one page and at most
+    /// [`USER_STACK_SIZE`].
+    pub fn stack_size(&self) -> usize {
+        page_align(self.max_stack_size().min(USER_STACK_SIZE)).max(PAGE_SIZE)
+    }
+
+    /// Returns the size up to which the user stack grows.
+    pub fn max_stack_size(&self) -> usize {
+        self.stack.rlim_cur.min(usize::MAX as u64) as usize
+    }
+
+    /// Returns the size up to which memory is locked.
+    pub fn max_locked_size(&self) -> usize {
+        self.memlock.rlim_cur.min(usize::MAX as u64) as usize
+    }
+}
+
+/// A helper for [`syscall_interface::SyscallProc::prlimit64`].
 pub fn do_prlimit(resource: i32, new_limit: usize, old_limit: usize) -> SyscallResult {
     let curr = cpu().curr.as_ref().unwrap();
-    let mut old_rlimit = Rlimit::default();
     let mut new_rlimit = Rlimit::default();
 
     if new_limit != 0 {
@@ -21,33 +98,19 @@ pub fn do_prlimit(resource: i32, new_limit: usize, old_limit: usize) -> SyscallR
         }
     }
 
-    match resource {
-        RLIMIT_STACK => {
-            old_rlimit = Rlimit {
-                rlim_cur: USER_STACK_SIZE as u64,
-                rlim_max: USER_STACK_SIZE as u64,
-            };
-        }
-        RLIMIT_NOFILE => {
-            let limit = curr.files().get_limit() as u64;
-            old_rlimit = Rlimit {
-                rlim_cur: limit,
-                rlim_max: limit,
-            };
-            if new_limit != 0 {
-                curr.files().set_limit(new_rlimit.rlim_cur as usize);
-            }
-        }
-        RLIMIT_AS => {
-            old_rlimit = Rlimit {
-                rlim_cur: (LOW_MAX_VA + 1) as u64,
-                rlim_max: (LOW_MAX_VA + 1) as u64,
-            }
+    let mut limits = curr.limits.lock();
+    let rlimit = limits.get_mut(resource)?;
+    let old_rlimit = *rlimit;
+    if new_limit != 0 {
+        if new_rlimit.rlim_max > old_rlimit.rlim_max {
+            return Err(Errno::EPERM);
         }
-        _ => {
-            return Err(Errno::EINVAL);
+        *rlimit = new_rlimit;
+        if resource == RLIMIT_NOFILE {
+            curr.files().set_limit(new_rlimit.rlim_cur as usize);
         }
     }
+    drop(limits);
 
     if old_limit != 0 {
         write_user!(curr.mm(), VirtAddr::from(old_limit), old_rlimit, Rlimit)?;
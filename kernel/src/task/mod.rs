@@ -1,12 +1,16 @@
 mod clone;
 mod exit;
+mod futex;
 mod sched;
+mod signal;
 mod task;
 mod limit;
 
 pub use clone::*;
 pub use exit::*;
+pub use futex::*;
 pub use sched::*;
+pub use signal::*;
 pub use task::*;
 pub use sched::*;
 pub use limit::*;
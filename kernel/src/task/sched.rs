@@ -10,9 +10,10 @@ use oscomp::fetch_test;
 use spin::Lazy;
 
 use crate::{
-    arch::{get_cpu_id, TaskContext, __switch},
+    arch::{get_cpu_id, timer::get_time, TaskContext, __switch},
     config::*,
     loader::from_args,
+    timer::wake_expired,
 };
 
 use super::{Task, TaskState, handle_zombie};
@@ -51,7 +52,7 @@ impl Scheduler for QueueScheduler {
     fn fetch(&mut self) -> Option<Arc<Task>> {
         if IS_TEST_ENV && self.queue.is_empty() {
             if let Some(args) = fetch_test() {
-                if let Some(task) = from_args(String::from(ROOT_DIR), args)
+                if let Some(task) = from_args(String::from(ROOT_DIR), args, Vec::new())
                     .map_err(|_| log::warn!("TEST NOT FOUND"))
                     .ok()
                 {
@@ -84,6 +85,11 @@ pub struct CPUContext {
 
     /// Idle task context.
     pub idle_ctx: TaskContext,
+
+    /// Number of living guards of RCU cells on this hart, e.g. [`FdTableGuard`].
+    ///
+    /// [`FdTableGuard`]: crate::fs::FdTableGuard
+    pub rcu_readers: usize,
 }
 
 impl CPUContext {
@@ -92,6 +98,7 @@ impl CPUContext {
         Self {
             curr: None,
             idle_ctx: TaskContext::zero(),
+            rcu_readers: 0,
         }
     }
 }
@@ -162,17 +169,30 @@ pub unsafe fn idle() -> ! {
             // Release the lock.
             drop(task_manager);
 
+            cpu().curr.as_ref().unwrap().cpu_time.lock().start(get_time());
             __switch(idle_ctx(), next_ctx);
-            
+            debug_assert_eq!(cpu().rcu_readers, 0, "task switched in a read section");
+
             let curr = cpu().curr.take().unwrap();
+            // Added under the lock, so the slice is counted once by `process_time`.
+            let mut cpu_time = curr.cpu_time.lock();
+            curr.thread_group.add_cpu_time(cpu_time.stop(get_time()));
+            drop(cpu_time);
             let state = curr.get_state();
-            if state == TaskState::RUNNABLE {
+            // Sleeping and stopped tasks stay in the queue until woken up.
+            if state.intersects(TaskState::RUNNABLE | TaskState::INTERRUPTIBLE | TaskState::STOPPED)
+            {
                 TASK_MANAGER.lock().add(curr);
             } else if state == TaskState::ZOMBIE {
                 handle_zombie(curr);
             } else {
                 panic!("Unexpected state {:#?}", state);
             }
+        } else {
+            // Timer interrupts are disabled in kernel, thus sleeping tasks are woken
+            // up here if all tasks are sleeping.
+            drop(task_manager);
+            wake_expired();
         }
     }
 }
@@ -196,3 +216,31 @@ pub unsafe fn do_yield() {
     __switch(curr_ctx, idle_ctx());
     CPUs[get_cpu_id()].intena = intena;
 }
+
+/// Current task sleeps until woken up by [`do_wake`]. Run next task.
+///
+/// The state of current task must be set to [`TaskState::INTERRUPTIBLE`] before
+/// the resource it waits for is released, so that a wakeup between that and this
+/// function will not be lost.
+///
+/// # Safety
+///
+/// Unsafe context switch will be called in this function.
+pub unsafe fn do_sleep() {
+    let curr = cpu().curr.as_ref().unwrap();
+    log::trace!("{:#?} sleeping", curr);
+    let curr_ctx = &curr.inner().ctx as *const TaskContext;
+
+    // Saves and restores CPU local variable, intena.
+    let intena = CPUs[get_cpu_id()].intena;
+    __switch(curr_ctx, idle_ctx());
+    CPUs[get_cpu_id()].intena = intena;
+}
+
+/// Wakes up the task if it is sleeping.
+pub fn do_wake(task: &Task) {
+    let mut locked_inner = task.locked_inner();
+    if locked_inner.state == TaskState::INTERRUPTIBLE {
+        locked_inner.state = TaskState::RUNNABLE;
+    }
+}
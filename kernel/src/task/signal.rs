@@ -0,0 +1,371 @@
+use errno::Errno;
+use kernel_sync::SpinLockGuard;
+use signal_defs::*;
+
+use crate::{
+    arch::{mm::VirtAddr, trap::UserContext},
+    config::SIGRETURN_VA,
+    error::KernelResult,
+    read_user,
+    timer::TIMER_WHEEL,
+    write_user,
+};
+
+use super::*;
+
+/// Context saved on the user stack before jumping to the signal handler.
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct SignalFrame {
+    /// User context when the signal is delivered.
+    pub context: UserContext,
+
+    /// Signal mask to restore after the handler returns.
+    pub blocked: SigSet,
+}
+
+/// Sends a signal caused by an exception of current task, e.g. `SIGSEGV` for an
+/// invalid memory access.
+///
+/// Like `force_sig` in Linux, the signal is unblocked and the action is reset to the
+/// default if it is ignored, since the task cannot continue anyway.
+pub fn force_signal(signo: usize, code: usize) {
+    let curr = cpu().curr.as_ref().unwrap();
+    let mut sig_actions = curr.sig_actions.lock();
+    if sig_actions[signo - 1].handler == SIG_IGN {
+        sig_actions[signo - 1] = SigAction::default();
+    }
+    let mut locked_inner = curr.locked_inner();
+    locked_inner.sig_blocked.unset(signo - 1);
+    locked_inner.sig_pending.add(SigInfo {
+        signo: signo as i32,
+        errno: 0,
+        code: code as i32,
+    });
+}
+
+/// Sends a signal to the thread `tid` directly, which must belong to the thread group
+/// `tgid` if given, like `do_send_specific` in Linux.
+///
+/// The signal is added to the pending signals of the target thread only, thus it is
+/// never handled by other threads in the group. A standard signal already pending is
+/// not queued again. Null signal `0` only checks if the thread exists. The thread may
+/// be running on another hart, which reads its pending signals under the same lock.
+///
+/// Returns `EINVAL` if the signal is invalid, or `ESRCH` if the thread is not found.
+pub fn send_signal_thread(tgid: Option<usize>, tid: usize, sig: usize) -> Result<(), Errno> {
+    if sig != SIGNONE && !sigvalid(sig) {
+        return Err(Errno::EINVAL);
+    }
+    let task = find_task(tid)
+        .filter(|task| tgid.map_or(true, |tgid| task.pid == tgid))
+        .filter(|task| task.get_state() != TaskState::ZOMBIE)
+        .ok_or(Errno::ESRCH)?;
+    if sig != SIGNONE {
+        queue_signal(&task, sig, SI_TKILL);
+    }
+    Ok(())
+}
+
+/// Sends a signal to processes selected by `pid` the same as `kill(2)`:
+///
+/// - `pid > 0`: the process `pid`.
+/// - `pid == 0`: every process in the process group of current task.
+/// - `pid == -1`: every process except the init process and current task.
+/// - `pid < -1`: every process in the process group `-pid`.
+///
+/// The signal is added to the pending signals of each thread group leader. Null signal
+/// `0` only checks if any process exists.
+///
+/// Returns `EINVAL` if the signal is invalid, or `ESRCH` if no process is found.
+pub fn send_signal(pid: isize, sig: usize) -> Result<(), Errno> {
+    if sig != SIGNONE && !sigvalid(sig) {
+        return Err(Errno::EINVAL);
+    }
+    let curr = cpu().curr.as_ref().unwrap();
+    let targets = match pid {
+        0 => {
+            let pgid = curr.thread_group.pgid();
+            find_processes(|task| task.thread_group.pgid() == pgid)
+        }
+        -1 => find_processes(|task| task.pid != 1 && task.pid != curr.pid),
+        pid if pid < 0 => find_processes(|task| task.thread_group.pgid() == (-pid) as usize),
+        pid => find_processes(|task| task.pid == pid as usize),
+    };
+    if targets.is_empty() {
+        return Err(Errno::ESRCH);
+    }
+    if sig != SIGNONE {
+        for task in &targets {
+            queue_signal(task, sig, SI_USER);
+        }
+    }
+    Ok(())
+}
+
+/// Adds a signal to the pending signals of `task` unless it is ignored. A standard
+/// signal already pending is not queued again.
+///
+/// `SIGCONT` and `SIGKILL` continue the task if it is stopped, see [`do_signal_stop`].
+///
+/// The task is woken up if it sleeps interruptibly and does not block the signal, so
+/// that its blocking syscall sees the signal and is interrupted.
+pub fn queue_signal(task: &Task, sig: usize, code: i32) {
+    // A stopped task is continued even if the signal is ignored or blocked.
+    if sig == SIGCONT || sig == SIGKILL {
+        let mut locked_inner = task.locked_inner();
+        if locked_inner.state == TaskState::STOPPED {
+            locked_inner.state = TaskState::RUNNABLE;
+        }
+    }
+    if task.sig_ignored(&task.sig_actions.lock(), sig) {
+        return;
+    }
+    let mut locked_inner = task.locked_inner();
+    if sig < SIGRTMIN && locked_inner.sig_pending.mask.get(sig - 1) {
+        return;
+    }
+    locked_inner.sig_pending.add(SigInfo {
+        signo: sig as i32,
+        errno: 0,
+        code,
+    });
+    if sig == SIGKILL || !locked_inner.sig_blocked.get(sig - 1) {
+        signal_wake_up(task, locked_inner);
+    }
+}
+
+/// Wakes up `task` sleeping interruptibly, like `signal_wake_up` in Linux.
+///
+/// The signal is queued under the same lock, so that the task either sees it before
+/// going to sleep or is woken up here. Its pending timeout is removed from the timer
+/// wheel. Wakers registered on files are removed by the task itself once it stops
+/// waiting.
+fn signal_wake_up(task: &Task, mut locked_inner: SpinLockGuard<TaskLockedInner>) {
+    if locked_inner.state != TaskState::INTERRUPTIBLE {
+        return;
+    }
+    locked_inner.state = TaskState::RUNNABLE;
+    drop(locked_inner);
+    TIMER_WHEEL.lock().cancel(task.tid.0);
+}
+
+/// Returns true if current task has a pending signal that is not blocked, which
+/// interrupts a blocking syscall.
+pub fn signal_pending() -> bool {
+    let locked_inner = cpu().curr.as_ref().unwrap().locked_inner();
+    let mut pending = locked_inner.sig_pending.mask;
+    pending.difference(&locked_inner.sig_blocked);
+    !pending.is_empty()
+}
+
+/// Replaces the signal mask of current task with `mask` while a syscall waits, like
+/// `set_user_sigmask` in Linux. `SIGKILL` and `SIGSTOP` are never blocked.
+///
+/// The previous mask is saved and restored by [`restore_saved_sigmask_unless`].
+pub fn set_user_sigmask(mut mask: SigSet) {
+    mask.unset_mask(sigmask(SIGKILL) | sigmask(SIGSTOP));
+    let mut locked_inner = cpu().curr.as_ref().unwrap().locked_inner();
+    locked_inner.saved_sigmask = Some(locked_inner.sig_blocked);
+    locked_inner.sig_blocked = mask;
+}
+
+/// Restores the signal mask saved by [`set_user_sigmask`] unless the syscall is
+/// `interrupted` by a signal, like `restore_saved_sigmask_unless` in Linux.
+///
+/// Otherwise the signal is delivered with the replaced mask, and the saved mask is
+/// restored when the handler returns, or by [`do_signal`] if no handler is called.
+pub fn restore_saved_sigmask_unless(interrupted: bool) {
+    if !interrupted {
+        restore_saved_sigmask();
+    }
+}
+
+/// Restores the signal mask saved by [`set_user_sigmask`] if any.
+fn restore_saved_sigmask() {
+    let mut locked_inner = cpu().curr.as_ref().unwrap().locked_inner();
+    if let Some(saved) = locked_inner.saved_sigmask.take() {
+        locked_inner.sig_blocked = saved;
+    }
+}
+
+/// Delivers a pending unblocked signal of current task before returning to user.
+///
+/// If the signal is caught, a [`SignalFrame`] is pushed to the user stack, or the
+/// alternate signal stack if the action is established with `SA_ONSTACK`, and the
+/// trapframe is modified to jump to the handler.
+///
+/// `restart` is set if the last syscall is interrupted by a signal with `ERESTART`.
+/// The syscall is issued again if no handler is called, or the handler is established
+/// with `SA_RESTART`, after it returns. Otherwise the syscall returns `EINTR`.
+///
+/// The signal mask saved by [`set_user_sigmask`] is kept in the frame to be restored
+/// when the handler returns, or restored here if no handler is called.
+///
+/// If the frame cannot be written, `SIGSEGV` is forced on the task and delivered
+/// instead.
+pub fn do_signal(restart: bool) -> KernelResult {
+    if !deliver_signal(restart)? {
+        restore_saved_sigmask();
+    }
+    Ok(())
+}
+
+/// Delivers a pending unblocked signal for [`do_signal`], returning true if a handler
+/// is called.
+fn deliver_signal(restart: bool) -> KernelResult<bool> {
+    let curr = cpu().curr.as_ref().unwrap();
+    let inner = curr.inner();
+    let trapframe = curr.trapframe();
+    if restart {
+        trapframe.restart_syscall();
+    }
+
+    let (siginfo, blocked) = {
+        let mut locked_inner = curr.locked_inner();
+        let blocked = locked_inner.sig_blocked;
+        let pending = &mut locked_inner.sig_pending;
+        match pending
+            .list
+            .iter()
+            .position(|sig| !blocked.get(sig.signo as usize - 1))
+        {
+            Some(pos) => {
+                let sig = pending.list.remove(pos);
+                pending.mask.unset(sig.signo as usize - 1);
+                (sig, blocked)
+            }
+            None => return Ok(false),
+        }
+    };
+    let signo = siginfo.signo as usize;
+
+    let mut sig_actions = curr.sig_actions.lock();
+    let action = sig_actions[signo - 1];
+    if action.handler == SIG_IGN {
+        return Ok(false);
+    }
+    if action.handler == SIG_DFL {
+        if sig_kernel_ignore(signo) {
+            return Ok(false);
+        }
+        drop(sig_actions);
+        if sig_kernel_stop(signo) {
+            do_signal_stop(signo);
+            // Delivers the signal continuing the task, e.g. `SIGKILL`.
+            return deliver_signal(false);
+        }
+        unsafe { do_exit(-(signo as i32)) };
+        return Ok(false);
+    }
+
+    if restart && !action.flags.contains(SigActionFlags::SA_RESTART) {
+        trapframe.next_epc();
+        trapframe.set_a0(Errno::EINTR.as_ret());
+    }
+
+    let altstack = &mut inner.sig_altstack;
+    let mut sp = altstack.handler_sp(trapframe.get_sp(), &action);
+    if !altstack.on_stack(trapframe.get_sp())
+        && altstack.on_stack(sp)
+        && altstack.flags & SS_AUTODISARM != 0
+    {
+        *altstack = SigAltStack::new();
+    }
+
+    sp = (sp - core::mem::size_of::<SignalFrame>()) & !0xf;
+    let frame = SignalFrame {
+        context: trapframe.user_context(),
+        blocked: curr.locked_inner().saved_sigmask.unwrap_or(blocked),
+    };
+    if write_user!(curr.mm(), VirtAddr::from(sp), frame, SignalFrame).is_err() {
+        // The handler cannot run on a bad stack, thus the task gets `SIGSEGV` instead,
+        // like `force_sigsegv` in Linux, which kills it if the same happens again.
+        if signo == SIGSEGV {
+            sig_actions[signo - 1] = SigAction::default();
+        }
+        drop(sig_actions);
+        force_signal(SIGSEGV, SI_KERNEL as usize);
+        return deliver_signal(false);
+    }
+
+    let mut locked_inner = curr.locked_inner();
+    locked_inner.saved_sigmask = None;
+    locked_inner.sig_blocked.union(&action.mask);
+    if !action.flags.contains(SigActionFlags::SA_NODEFER) {
+        locked_inner.sig_blocked.set(signo - 1);
+    }
+    drop(locked_inner);
+    if action.flags.contains(SigActionFlags::SA_RESETHAND) {
+        sig_actions[signo - 1] = SigAction::default();
+    }
+
+    trapframe.set_sp(sp);
+    trapframe.set_epc(action.handler);
+    trapframe.set_a0(signo);
+    trapframe.set_ra(if action.flags.contains(SigActionFlags::SA_RESTORER) {
+        action.restorer
+    } else {
+        SIGRETURN_VA
+    });
+
+    Ok(true)
+}
+
+/// Stops current task by the stop signal `signo` with the default action, like
+/// `do_signal_stop` in Linux, until it is continued by `SIGCONT` or `SIGKILL`.
+///
+/// The signal is kept in the exit code until the parent reports it by `wait4` with
+/// `WUNTRACED`. The parent is notified with `SIGCHLD` unless its action is
+/// established with `SA_NOCLDSTOP`.
+fn do_signal_stop(signo: usize) {
+    let curr = cpu().curr.as_ref().unwrap();
+    let parent = {
+        let mut locked_inner = curr.locked_inner();
+        curr.inner().exit_code = signo as i32;
+        locked_inner.state = TaskState::STOPPED;
+        locked_inner
+            .parent
+            .as_ref()
+            .and_then(|parent| parent.upgrade())
+    };
+    if let Some(parent) = parent {
+        let nocldstop = parent.sig_actions.lock()[SIGCHLD - 1]
+            .flags
+            .contains(SigActionFlags::SA_NOCLDSTOP);
+        if !nocldstop {
+            queue_signal(&parent, SIGCHLD, CLD_STOPPED as i32);
+        }
+    }
+    log::trace!("{:?} stopped by signal {}", curr, signo);
+    unsafe { do_sleep() };
+}
+
+/// Restores the context of current task saved in the [`SignalFrame`] when the signal
+/// handler returns, which is on top of the user stack.
+///
+/// The signal mask is restored as well, thus signals blocked during the handler may be
+/// delivered before returning to user, e.g. nested signals of the same number.
+///
+/// Returns the restored `a0`, which is written back as the return value of `sigreturn`.
+pub fn do_sigreturn() -> KernelResult<usize> {
+    let curr = cpu().curr.as_ref().unwrap();
+    let trapframe = curr.trapframe();
+    let mut frame = SignalFrame {
+        context: UserContext::default(),
+        blocked: SigSet::new(),
+    };
+    read_user!(
+        curr.mm(),
+        VirtAddr::from(trapframe.get_sp()),
+        frame,
+        SignalFrame
+    )?;
+
+    trapframe.restore(&frame.context);
+    frame
+        .blocked
+        .unset_mask(sigmask(SIGKILL) | sigmask(SIGSTOP));
+    curr.locked_inner().sig_blocked = frame.blocked;
+    Ok(trapframe.get_a0())
+}
@@ -1,10 +1,14 @@
 use alloc::{
-    collections::LinkedList,
+    collections::{BTreeMap, LinkedList},
     string::String,
     sync::{Arc, Weak},
     vec::Vec,
 };
-use core::{cell::SyncUnsafeCell, fmt};
+use core::{
+    cell::SyncUnsafeCell,
+    fmt,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 use errno::Errno;
 use id_alloc::*;
 use kernel_sync::{SpinLock, SpinLockGuard};
@@ -12,6 +16,7 @@ use log::trace;
 use signal_defs::*;
 use spin::Lazy;
 use syscall_interface::AT_FDCWD;
+use time_subsys::CpuTime;
 use vfs::Path;
 
 use crate::{
@@ -22,10 +27,11 @@ use crate::{
     },
     config::*,
     error::{KernelError, KernelResult},
-    fs::{FDManager, FSInfo},
+    fs::{FDManager, FSInfo, FdTable},
     loader::from_elf,
     mm::{KERNEL_MM, MM},
     task::sched::Scheduler,
+    timer::RealTimer,
 };
 
 #[cfg(feature = "uintr")]
@@ -73,12 +79,13 @@ pub struct TID(pub usize);
 impl TID {
     /// Creates a new [`TID`].
     pub fn new() -> Self {
-        Self(TID_ALLOCATOR.lock().alloc())
+        Self(TID_ALLOCATOR.lock().alloc_unchecked())
     }
 }
 
 impl Drop for TID {
     fn drop(&mut self) {
+        TASK_MAP.lock().remove(&self.0);
         TID_ALLOCATOR.lock().dealloc(self.0)
     }
 }
@@ -87,6 +94,39 @@ impl Drop for TID {
 static TID_ALLOCATOR: Lazy<SpinLock<RecycleAllocator>> =
     Lazy::new(|| SpinLock::new(RecycleAllocator::new(1)));
 
+/// Tasks by [`TID`], which are removed when the [`TID`] is deallocated.
+static TASK_MAP: Lazy<SpinLock<BTreeMap<usize, Weak<Task>>>> =
+    Lazy::new(|| SpinLock::new(BTreeMap::new()));
+
+/// Registers a new task to be found by [`find_task`].
+pub fn register_task(task: &Arc<Task>) {
+    TASK_MAP.lock().insert(task.tid.0, Arc::downgrade(task));
+}
+
+/// Finds a task by [`TID`], including zombies not reaped yet.
+pub fn find_task(tid: usize) -> Option<Arc<Task>> {
+    TASK_MAP.lock().get(&tid)?.upgrade()
+}
+
+/// Finds thread group leaders not exited yet for which `filter` returns true.
+pub fn find_processes(filter: impl Fn(&Task) -> bool) -> Vec<Arc<Task>> {
+    // The last reference to a task may be dropped here, which locks the map again.
+    let tasks: Vec<Arc<Task>> = TASK_MAP.lock().values().filter_map(Weak::upgrade).collect();
+    tasks
+        .into_iter()
+        .filter(|task| {
+            task.tid.0 == task.pid && task.get_state() != TaskState::ZOMBIE && filter(task)
+        })
+        .collect()
+}
+
+/// Finds all threads in the thread group of `pid`, including zombies not reaped yet.
+pub fn find_threads(pid: usize) -> Vec<Arc<Task>> {
+    // The last reference to a task may be dropped here, which locks the map again.
+    let tasks: Vec<Arc<Task>> = TASK_MAP.lock().values().filter_map(Weak::upgrade).collect();
+    tasks.into_iter().filter(|task| task.pid == pid).collect()
+}
+
 /// A wrapper for kernel stack.
 pub struct KernelStack(AllocatedFrameRange);
 
@@ -94,7 +134,7 @@ impl KernelStack {
     /// Creates a new kernel stack.
     pub fn new() -> KernelResult<Self> {
         Ok(Self(
-            AllocatedFrameRange::new(KERNEL_STACK_PAGES, true)
+            AllocatedFrameRange::new_zeroed(KERNEL_STACK_PAGES)
                 .map_err(|_| KernelError::FrameAllocFailed)?,
         ))
     }
@@ -141,18 +181,19 @@ pub struct TaskInner {
     /// clear_child_tid is set to the value passed in the ctid argument of that system call.
     pub clear_child_tid: usize,
 
-    /// Pending signals.
-    pub sig_pending: SigPending,
+    /// Head of the robust futex list registered by `set_robust_list(2)`, which is not
+    /// inherited by new threads.
+    pub robust_list: usize,
 
-    /// Blocked signals.
-    pub sig_blocked: SigSet,
+    /// Alternate signal stack.
+    pub sig_altstack: SigAltStack,
 
     /* Shared and mutable */
     /// Address space metadata.
     pub mm: Arc<SpinLock<MM>>,
 
     /// File descriptor table.
-    pub files: Arc<SpinLock<FDManager>>,
+    pub files: Arc<FdTable>,
 }
 
 unsafe impl Send for TaskInner {}
@@ -165,6 +206,16 @@ pub struct TaskLockedInner {
     /// Sleep lock id.
     pub sleeping_on: Option<usize>,
 
+    /// Pending signals, which may be queued by other harts.
+    pub sig_pending: SigPending,
+
+    /// Blocked signals.
+    pub sig_blocked: SigSet,
+
+    /// Signal mask replaced temporarily by a syscall, e.g. `ppoll`, which is restored
+    /// once the signal interrupting the syscall is delivered.
+    pub saved_sigmask: Option<SigSet>,
+
     /// Hierarchy pointers in task management.
     /// INIT task has no parent task.
     pub parent: Option<Weak<Task>>,
@@ -180,6 +231,82 @@ pub struct TaskLockedInner {
 
 unsafe impl Send for TaskLockedInner {}
 
+/// Shared by all threads in the same thread group.
+pub struct ThreadGroup {
+    /// Number of threads that have not exited yet.
+    live: AtomicUsize,
+
+    /// Run time of finished time slices of all threads in clock cycles.
+    cpu_time: AtomicUsize,
+
+    /// Process group ID, inherited from the parent on fork.
+    pgid: AtomicUsize,
+
+    /// Session ID, inherited from the parent on fork.
+    sid: AtomicUsize,
+}
+
+impl ThreadGroup {
+    /// Creates a thread group with the leader alive, in the process group `pgid` of
+    /// the session `sid`.
+    pub fn new(pgid: usize, sid: usize) -> Self {
+        Self {
+            live: AtomicUsize::new(1),
+            cpu_time: AtomicUsize::new(0),
+            pgid: AtomicUsize::new(pgid),
+            sid: AtomicUsize::new(sid),
+        }
+    }
+
+    /// A new thread joins this group.
+    pub fn join(&self) {
+        self.live.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// A thread in this group exits.
+    ///
+    /// Returns true if it is the last thread alive.
+    pub fn leave(&self) -> bool {
+        self.live.fetch_sub(1, Ordering::AcqRel) == 1
+    }
+
+    /// Returns true if all threads in this group have exited, thus the
+    /// thread group leader can be reaped.
+    pub fn is_dead(&self) -> bool {
+        self.live.load(Ordering::Acquire) == 0
+    }
+
+    /// A thread in this group finishes a time slice.
+    pub fn add_cpu_time(&self, slice: usize) {
+        self.cpu_time.fetch_add(slice, Ordering::AcqRel);
+    }
+
+    /// Returns run time of finished time slices of all threads.
+    pub fn cpu_time(&self) -> usize {
+        self.cpu_time.load(Ordering::Acquire)
+    }
+
+    /// Returns the process group ID.
+    pub fn pgid(&self) -> usize {
+        self.pgid.load(Ordering::Acquire)
+    }
+
+    /// Moves this process to the process group `pgid`.
+    pub fn set_pgid(&self, pgid: usize) {
+        self.pgid.store(pgid, Ordering::Release);
+    }
+
+    /// Returns the session ID.
+    pub fn sid(&self) -> usize {
+        self.sid.load(Ordering::Acquire)
+    }
+
+    /// Moves this process to the session `sid`.
+    pub fn set_sid(&self, sid: usize) {
+        self.sid.store(sid, Ordering::Release);
+    }
+}
+
 /// In conventional opinion, process is the minimum unit of resource allocation, while task (or
 /// thread) is the minimum unit of scheduling. Process is always created with a main task. On
 /// the one hand, a process may have several tasks; on the other hand, these tasks shared the
@@ -227,13 +354,25 @@ pub struct Task {
     pub exit_signal: usize,
 
     /* Shared and mutable */
+    /// Live threads in the thread group.
+    pub thread_group: Arc<ThreadGroup>,
+
     /// Filesystem info
     pub fs_info: Arc<SpinLock<FSInfo>>,
 
     /// Signal actions.
     pub sig_actions: Arc<SpinLock<SigActions>>,
 
+    /// Real-time interval timer.
+    pub real_timer: Arc<SpinLock<RealTimer>>,
+
+    /// Resource limits.
+    pub limits: Arc<SpinLock<ResourceLimits>>,
+
     /* Local and mutable */
+    /// Run time of this task, updated by the scheduler.
+    pub cpu_time: SpinLock<CpuTime>,
+
     /// Inner data wrapped by [`SpinLock`].
     pub locked_inner: SpinLock<TaskLockedInner>,
 
@@ -255,15 +394,22 @@ impl Task {
             pid: 0,
             trapframe: None,
             exit_signal: SIGNONE,
+            thread_group: Arc::new(ThreadGroup::new(0, 0)),
             fs_info: Arc::new(SpinLock::new(FSInfo {
                 umask: 0,
-                cwd: String::from("/"),
-                root: String::from("/"),
+                cwd: Path::root(),
+                root: Path::root(),
             })),
             sig_actions: Arc::new(SpinLock::new([SigAction::default(); NSIG])),
+            real_timer: Arc::new(SpinLock::new(RealTimer::new())),
+            limits: Arc::new(SpinLock::new(ResourceLimits::new())),
+            cpu_time: SpinLock::new(CpuTime::new()),
             locked_inner: SpinLock::new(TaskLockedInner {
                 state: TaskState::RUNNABLE,
                 sleeping_on: None,
+                sig_pending: SigPending::new(),
+                sig_blocked: SigSet::new(),
+                saved_sigmask: None,
                 parent: None,
                 children: LinkedList::new(),
             }),
@@ -273,21 +419,29 @@ impl Task {
                 kstack: KernelStack::new()?,
                 set_child_tid: 0,
                 clear_child_tid: 0,
-                sig_pending: SigPending::new(),
-                sig_blocked: SigSet::new(),
+                robust_list: 0,
+                sig_altstack: SigAltStack::new(),
                 mm: Arc::new(SpinLock::new(MM::new()?)),
-                files: Arc::new(SpinLock::new(FDManager::new())),
+                files: Arc::new(FdTable::new(FDManager::new())),
             }),
             #[cfg(feature = "uintr")]
             uintr_inner: SyncUnsafeCell::new(TaskUIntrInner::new()),
         })
     }
     /// Create a new task from ELF data.
-    pub fn new(dir: String, elf_data: &[u8], args: Vec<String>) -> KernelResult<Self> {
+    pub fn new(
+        dir: String,
+        elf_data: &[u8],
+        args: Vec<String>,
+        envs: Vec<String>,
+    ) -> KernelResult<Self> {
         let name = args.join(" ");
 
+        let limits = Arc::new(SpinLock::new(ResourceLimits::new()));
         let mut mm = MM::new()?;
-        let sp = from_elf(elf_data, args, &mut mm)?;
+        mm.set_limits(&limits);
+        let stack_size = limits.lock().stack_size();
+        let sp = from_elf(elf_data, args, envs, stack_size, &mut mm)?;
         trace!("\nTask [{}]\n{:#?}", &name, mm);
 
         let kstack = KernelStack::new()?;
@@ -314,26 +468,37 @@ impl Task {
             pid: tid_num,
             trapframe: Some(TrapFrameTracker(trapframe_pa)),
             exit_signal: SIGNONE,
+            thread_group: Arc::new(ThreadGroup::new(tid_num, tid_num)),
             fs_info: Arc::new(SpinLock::new(FSInfo {
                 umask: 0,
-                cwd: dir,
-                root: String::from("/"),
+                cwd: {
+                    let mut cwd = Path::root();
+                    cwd.join(dir.as_str());
+                    cwd
+                },
+                root: Path::root(),
             })),
             sig_actions: Arc::new(SpinLock::new([SigAction::default(); NSIG])),
+            real_timer: Arc::new(SpinLock::new(RealTimer::new())),
+            limits,
+            cpu_time: SpinLock::new(CpuTime::new()),
             inner: SyncUnsafeCell::new(TaskInner {
                 exit_code: 0,
                 ctx: TaskContext::new(user_trap_return as usize, kstack_base),
                 kstack,
                 set_child_tid: 0,
                 clear_child_tid: 0,
-                sig_pending: SigPending::new(),
-                sig_blocked: SigSet::new(),
+                robust_list: 0,
+                sig_altstack: SigAltStack::new(),
                 mm: Arc::new(SpinLock::new(mm)),
-                files: Arc::new(SpinLock::new(fd_manager)),
+                files: Arc::new(FdTable::new(fd_manager)),
             }),
             locked_inner: SpinLock::new(TaskLockedInner {
                 state: TaskState::RUNNABLE,
                 sleeping_on: None,
+                sig_pending: SigPending::new(),
+                sig_blocked: SigSet::new(),
+                saved_sigmask: None,
                 parent: None,
                 children: LinkedList::new(),
             }),
@@ -369,15 +534,15 @@ impl Task {
         self.inner().mm.lock()
     }
 
-    /// Acquires inner lock to modify [`FDManager`].
-    pub fn files(&self) -> SpinLockGuard<FDManager> {
-        self.inner().files.lock()
+    /// Gets the file descriptor table, which is read without locking.
+    pub fn files(&self) -> &FdTable {
+        &self.inner().files
     }
 
     /// Gets the directory name from a file descriptor.
     pub fn get_dir(&self, dirfd: usize) -> KernelResult<Path> {
         if dirfd == AT_FDCWD {
-            Ok(Path::new(self.fs_info.lock().cwd.as_str()))
+            Ok(self.fs_info.lock().cwd.clone())
         } else {
             let dir = self.files().get(dirfd)?;
             if dir.is_dir() {
@@ -418,9 +583,10 @@ impl fmt::Debug for Task {
 
 /// Returns trapframe base of the task in the address space by task identification.
 ///
-/// Trapframes are located right below the Trampoline in each address space.
+/// Trapframes are located right below the Trampoline and the page signal handlers
+/// return to in each address space.
 pub fn trapframe_base(tid: usize) -> usize {
-    TRAMPOLINE_VA - PAGE_SIZE - tid * PAGE_SIZE
+    SIGRETURN_VA - PAGE_SIZE - tid * PAGE_SIZE
 }
 
 /// Initialize trapframe
@@ -490,7 +656,7 @@ impl Task {
          * signal handler may change by the time it is
          * unblocked.
          */
-        if self.inner().sig_blocked.get(sig - 1) {
+        if self.locked_inner().sig_blocked.get(sig - 1) {
             return false;
         }
 
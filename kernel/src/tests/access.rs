@@ -0,0 +1,44 @@
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::AT_FDCWD;
+use vfs::{AccessMode, OpenFlags, Path};
+
+use crate::{
+    fs::{access_in, open, resolve_path, unlink, ResolveFlags},
+    task::cpu,
+};
+
+static ONCE: Once = Once::new();
+
+/// Checks the accessibility of an existing file, a missing file and a directory.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let file = Path::new("/access.txt");
+        open(file.clone(), OpenFlags::O_CREAT | OpenFlags::O_WRONLY).unwrap();
+
+        let (fs, path) =
+            resolve_path(&curr, AT_FDCWD, "/access.txt", ResolveFlags::empty()).unwrap();
+        assert_eq!(access_in(&fs, path.clone(), AccessMode::F_OK), Ok(()));
+        assert_eq!(
+            access_in(&fs, path, AccessMode::R_OK | AccessMode::W_OK),
+            Ok(())
+        );
+
+        let (fs, path) =
+            resolve_path(&curr, AT_FDCWD, "/access.missing", ResolveFlags::empty()).unwrap();
+        assert_eq!(
+            access_in(&fs, path.clone(), AccessMode::F_OK),
+            Err(Errno::ENOENT)
+        );
+        assert_eq!(access_in(&fs, path, AccessMode::R_OK), Err(Errno::ENOENT));
+
+        // A directory without a trailing '/' is searchable.
+        let (fs, path) = resolve_path(&curr, AT_FDCWD, "/dev", ResolveFlags::empty()).unwrap();
+        assert_eq!(access_in(&fs, path, AccessMode::X_OK), Ok(()));
+        debug!("ACCESS checked {:?}", file);
+
+        unlink(file).unwrap();
+    });
+}
@@ -0,0 +1,86 @@
+use alloc::vec::Vec;
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{SyscallFile, AT_FDCWD};
+use vfs::{File, OpenFlags, Path};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::{mkdir, open, unlink},
+    mm::VMFlags,
+    syscall::SyscallImpl,
+    task::cpu,
+};
+
+static ONCE: Once = Once::new();
+
+/// Offset of the path strings.
+const PATHS: usize = 0;
+
+/// Offset of the buffer of the working directory and the data read.
+const BUF: usize = PAGE_SIZE / 2;
+
+/// Changes the working directory into a created directory, then opens an existing file
+/// and creates another one by relative names.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let old_cwd = curr.fs_info.lock().cwd.clone();
+        let _ = mkdir(Path::new("/chdir/"));
+        let a = Path::new("/chdir/a");
+        let b = Path::new("/chdir/b");
+
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            let file = open(a.clone(), OpenFlags::O_CREAT | OpenFlags::O_WRONLY)?;
+            assert_eq!(file.write(b"chdir"), Some(5));
+            drop(file);
+
+            // Copies a path to user space as a C string.
+            let path = |path: &str| -> Result<*const u8, Errno> {
+                let mut data = Vec::from(path.as_bytes());
+                data.push(0);
+                curr.mm().copy_to_user(start + PATHS, &data)?;
+                Ok((start + PATHS).value() as *const u8)
+            };
+            let buf = (start + BUF).value() as *mut u8;
+
+            assert_eq!(SyscallImpl::chdir(path("/chdir")?), Ok(0));
+            assert_eq!(SyscallImpl::getcwd(buf, 16), Ok(buf as usize));
+            assert_eq!(curr.mm().copy_from_user(start + BUF, 7)?, b"/chdir\0");
+
+            // An existing file is opened by a relative name.
+            let flags = OpenFlags::O_RDONLY.bits() as usize;
+            let fd = SyscallImpl::openat(AT_FDCWD, path("a")?, flags, 0)?;
+            assert_eq!(SyscallImpl::read(fd, buf, 16), Ok(5));
+            assert_eq!(curr.mm().copy_from_user(start + BUF, 5)?, b"chdir");
+            SyscallImpl::close(fd)?;
+
+            // A new file is created in the working directory.
+            let flags = (OpenFlags::O_CREAT | OpenFlags::O_WRONLY).bits() as usize;
+            let fd = SyscallImpl::openat(AT_FDCWD, path("./b")?, flags, 0o644)?;
+            SyscallImpl::close(fd)?;
+            assert!(open(b.clone(), OpenFlags::O_RDONLY).is_ok());
+
+            // The working directory is kept if the target is not a directory.
+            assert_eq!(SyscallImpl::chdir(path("a")?), Err(Errno::ENOTDIR));
+            assert_eq!(SyscallImpl::chdir(path("missing")?), Err(Errno::ENOENT));
+            assert_eq!(curr.fs_info.lock().cwd, Path::new("/chdir/"));
+
+            assert_eq!(SyscallImpl::chdir(path("..")?), Ok(0));
+            assert_eq!(curr.fs_info.lock().cwd, Path::root());
+            debug!("CHDIR opened {:?} by a relative name", a);
+            Ok(())
+        };
+        let result = with_scratch(
+            PAGE_SIZE,
+            VMFlags::USER | VMFlags::READ | VMFlags::WRITE,
+            test,
+        );
+        curr.fs_info.lock().cwd = old_cwd;
+        let _ = unlink(a.clone());
+        let _ = unlink(b.clone());
+        result.unwrap();
+    });
+}
@@ -0,0 +1,42 @@
+use log::debug;
+use spin::Once;
+use syscall_interface::AT_FDCWD;
+use vfs::{OpenFlags, Path};
+
+use crate::{
+    fs::{mkdir, open, resolve_path, unlink, ResolveFlags},
+    task::cpu,
+};
+
+static ONCE: Once = Once::new();
+
+/// Changes the root directory of current task to `/jail/`, then resolves absolute
+/// paths which must stay in the new root even with `".."`.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let _ = mkdir(Path::new("/jail/"));
+        let _ = mkdir(Path::new("/jail/sub/"));
+        let file = Path::new("/jail/sub/hello");
+        open(file.clone(), OpenFlags::O_CREAT | OpenFlags::O_WRONLY).unwrap();
+
+        let old_root = core::mem::replace(&mut curr.fs_info.lock().root, Path::new("/jail/"));
+
+        let (_, path) = resolve_path(&curr, AT_FDCWD, "/sub/hello", ResolveFlags::empty()).unwrap();
+        assert_eq!(path, file);
+        assert!(open(path, OpenFlags::O_RDONLY).is_ok());
+
+        // ".." stops at the new root.
+        let (_, path) =
+            resolve_path(&curr, AT_FDCWD, "/../../sub/hello", ResolveFlags::empty()).unwrap();
+        assert_eq!(path, file);
+        let (_, path) =
+            resolve_path(&curr, AT_FDCWD, "/../jail/sub/hello", ResolveFlags::empty()).unwrap();
+        assert_eq!(path, Path::new("/jail/jail/sub/hello"));
+        assert!(open(path, OpenFlags::O_RDONLY).is_err());
+        debug!("CHROOT resolved {:?}", file);
+
+        curr.fs_info.lock().root = old_root;
+        unlink(file).unwrap();
+    });
+}
@@ -0,0 +1,48 @@
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::SyscallTimer;
+use time_subsys::{ClockType, TimeSpec};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    mm::VMFlags,
+    read_user,
+    syscall::SyscallImpl,
+    task::cpu,
+};
+
+static ONCE: Once = Once::new();
+
+/// Gets the resolutions of clocks, which are not larger than a millisecond.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            let getres = |clock: ClockType| -> Result<TimeSpec, Errno> {
+                SyscallImpl::clock_getres(clock as usize, start.value())?;
+                let mut res = TimeSpec::default();
+                read_user!(curr.mm(), start, res, TimeSpec)?;
+                Ok(res)
+            };
+
+            let res = getres(ClockType::MONOTONIC)?;
+            assert_eq!(res.tv_sec, 0);
+            assert!(res.tv_nsec > 0 && res.tv_nsec <= 1_000_000);
+            assert_eq!(getres(ClockType::REALTIME)?, res);
+            assert_eq!(getres(ClockType::THREAD_CPUTIME_ID)?.tv_nsec, 1);
+
+            // The resolution is optional.
+            SyscallImpl::clock_getres(ClockType::MONOTONIC as usize, 0)?;
+            assert_eq!(
+                SyscallImpl::clock_getres(5, start.value()),
+                Err(Errno::EINVAL)
+            );
+            debug!("CLOCK_GETRES {} ns", res.tv_nsec);
+            Ok(())
+        };
+        with_scratch(PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test).unwrap();
+    });
+}
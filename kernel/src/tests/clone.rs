@@ -0,0 +1,93 @@
+use alloc::sync::Arc;
+use errno::Errno;
+use log::debug;
+use signal_defs::SIGCHLD;
+use syscall_interface::SyscallProc;
+
+use super::{clone_at, run_user_code, wait_exited, RECORD};
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    read_user,
+    syscall::SyscallImpl,
+    task::{cpu, do_yield, find_task, CloneFlags},
+    write_user,
+};
+
+/// Offset of the stack top of the thread.
+const STACK: usize = PAGE_SIZE - 16;
+
+/// Value written by the child.
+const VALUE: usize = 5678;
+
+/// Child in user space, writing [`VALUE`] and exiting once released.
+#[naked]
+unsafe extern "C" fn child_body() {
+    core::arch::asm!(
+        "li t0, {value}",
+        "sd t0, 0(tp)",
+        "1:",
+        "ld t0, 8(tp)",
+        "beqz t0, 1b",
+        // SyscallNO::EXIT
+        "li a7, 93",
+        "li a0, 0",
+        "ecall",
+        value = const VALUE,
+        options(noreturn),
+    );
+}
+
+/// Clones a process whose write is isolated in its own copy of the address space, and a
+/// thread sharing the address space, the file descriptor table and the process ID.
+pub fn test() {
+    let curr = cpu().curr.as_ref().unwrap();
+    let test = |start: VirtAddr| -> Result<(), Errno> {
+        let clone = |flags: CloneFlags, stack: usize| {
+            clone_at(start, || {
+                let tls = start.value() + RECORD;
+                SyscallImpl::clone(flags.bits() as usize, stack, 0, tls, 0)
+            })
+        };
+
+        // Invalid combinations of sharing.
+        let thread = CloneFlags::CLONE_VM | CloneFlags::CLONE_THREAD;
+        assert_eq!(clone(thread, 0), Err(Errno::EINVAL));
+        let sighand = CloneFlags::CLONE_SIGHAND | CloneFlags::CLONE_THREAD;
+        assert_eq!(clone(sighand, 0), Err(Errno::EINVAL));
+
+        // The process writes its own copy, released before it first runs.
+        write_user!(curr.mm(), start + RECORD, [0usize, 1], [usize; 2])?;
+        let child = clone(
+            CloneFlags::from_bits_truncate(SIGCHLD as u32) | CloneFlags::CLONE_SETTLS,
+            0,
+        )?;
+        assert_ne!(child, curr.pid);
+        assert_eq!(SyscallImpl::wait4(child as isize, 0, 0, 0), Ok(child));
+        let mut record = [0usize; 2];
+        read_user!(curr.mm(), start + RECORD, record, [usize; 2])?;
+        assert_eq!(record, [0, 1]);
+
+        // The thread writes the shared memory and waits to be released.
+        write_user!(curr.mm(), start + RECORD, [0usize; 2], [usize; 2])?;
+        let tid = clone(
+            thread | CloneFlags::CLONE_FILES | CloneFlags::CLONE_SIGHAND | CloneFlags::CLONE_SETTLS,
+            start.value() + STACK,
+        )?;
+        let task = find_task(tid).unwrap();
+        assert_eq!(task.pid, curr.pid);
+        assert!(Arc::ptr_eq(&task.inner().mm, &curr.inner().mm));
+        assert!(Arc::ptr_eq(&task.inner().files, &curr.inner().files));
+        assert!(Arc::ptr_eq(&task.thread_group, &curr.thread_group));
+        drop(task);
+        while record[0] != VALUE {
+            unsafe { do_yield() };
+            read_user!(curr.mm(), start + RECORD, record, [usize; 2])?;
+        }
+        write_user!(curr.mm(), start + RECORD + 8, 1usize, usize)?;
+
+        wait_exited(tid);
+        debug!("CLONE process {} and thread {}", child, tid);
+        Ok(())
+    };
+    run_user_code(&[child_body], PAGE_SIZE, test).unwrap();
+}
@@ -0,0 +1,72 @@
+use errno::Errno;
+use log::debug;
+use syscall_interface::SyscallProc;
+
+use super::{clone_at, run_user_code, wait_exited, RECORD, THREAD_FLAGS};
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    mm::{do_mprotect, MmapProt},
+    read_user,
+    syscall::SyscallImpl,
+    task::{cpu, do_yield},
+    write_user,
+};
+
+/// Offset of the stack top of the thread in the second page.
+const STACK: usize = 2 * PAGE_SIZE - 16;
+
+/// Thread body in user space, storing its stack pointer and the return value of
+/// `clone` in the record followed by a flag before exiting.
+#[naked]
+unsafe extern "C" fn thread_body() {
+    core::arch::asm!(
+        "sd sp, 0(tp)",
+        "sd a0, 8(tp)",
+        "li t0, 1",
+        "sd t0, 16(tp)",
+        // SyscallNO::EXIT
+        "li a7, 93",
+        "li a0, 0",
+        "ecall",
+        options(noreturn),
+    );
+}
+
+/// Creates a thread running on its own stack, which returns 0 from `clone`, and rejects
+/// a misaligned or read-only stack.
+pub fn test() {
+    let curr = cpu().curr.as_ref().unwrap();
+    let test = |start: VirtAddr| -> Result<(), Errno> {
+        let rdonly = start + 2 * PAGE_SIZE;
+        do_mprotect(&mut curr.mm(), rdonly, PAGE_SIZE, MmapProt::PROT_READ)?;
+        write_user!(curr.mm(), start + RECORD, [usize::MAX; 3], [usize; 3])?;
+
+        let clone = |stack: usize| {
+            clone_at(start, || {
+                let tls = start.value() + RECORD;
+                SyscallImpl::clone(THREAD_FLAGS.bits() as usize, stack, 0, tls, 0)
+            })
+        };
+
+        let stack = start.value() + STACK;
+        assert_eq!(clone(stack + 8), Err(Errno::EINVAL));
+        assert_eq!(clone(rdonly.value() + 16), Err(Errno::EFAULT));
+
+        let tid = clone(stack)?;
+        let mut record = [0usize; 3];
+        loop {
+            read_user!(curr.mm(), start + RECORD, record, [usize; 3])?;
+            if record[2] == 1 {
+                break;
+            }
+            unsafe { do_yield() };
+        }
+        assert_eq!(record[0], stack);
+        assert_eq!(record[1], 0);
+
+        wait_exited(tid);
+        debug!("CLONE thread {} on stack {:#x}", tid, stack);
+        Ok(())
+    };
+    run_user_code(&[thread_body], 3 * PAGE_SIZE, test).unwrap();
+}
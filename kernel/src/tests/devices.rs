@@ -0,0 +1,51 @@
+use alloc::sync::Arc;
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use vfs::{File, OpenFlags, Path};
+
+use crate::fs::{
+    mem::{NullFile, RandomFile, ZeroFile},
+    open, DeviceFactory, Tty, DEVICES, MEM_MAJOR, TTYAUX_MAJOR,
+};
+
+static ONCE: Once = Once::new();
+
+/// Major number of the device registered by the test, which is reserved for local use.
+const TEST_MAJOR: usize = 240;
+
+/// Opens devices in canonical numbers through their nodes, and registers a new device
+/// number whose node opens the file made by its factory.
+pub fn test() {
+    ONCE.call_once(|| {
+        let node = |path: &str| open(Path::new(path), OpenFlags::O_RDWR).unwrap();
+        assert!(node("/dev/null").as_any().is::<NullFile>());
+        assert!(node("/dev/zero").as_any().is::<ZeroFile>());
+        assert!(node("/dev/urandom").as_any().is::<RandomFile>());
+        assert!(node("/dev/tty").as_any().is::<Tty>());
+        let device = |major, minor| DEVICES.lock().open(major, minor).unwrap();
+        assert!(device(MEM_MAJOR, 3).as_any().is::<NullFile>());
+        assert!(device(TTYAUX_MAJOR, 1).as_any().is::<Tty>());
+
+        // The node opens nothing until the device is registered.
+        let path = Path::new("/dev/registry");
+        DEVICES.lock().mknod("registry", TEST_MAJOR, 0).unwrap();
+        assert_eq!(
+            open(path.clone(), OpenFlags::O_RDWR).err(),
+            Some(Errno::ENXIO)
+        );
+        let register = |factory: DeviceFactory| DEVICES.lock().register(TEST_MAJOR, 0, factory);
+        register(|| Arc::new(ZeroFile)).unwrap();
+        assert_eq!(register(|| Arc::new(NullFile)), Err(Errno::EBUSY));
+        let file = open(path.clone(), OpenFlags::O_RDWR).unwrap();
+        assert!(file.as_any().is::<ZeroFile>());
+        let mut buf = [0xffu8; 4];
+        assert_eq!(file.read(&mut buf), Some(4));
+        assert_eq!(buf, [0; 4]);
+
+        assert!(DEVICES.lock().unregister(TEST_MAJOR, 0).is_some());
+        assert!(!DEVICES.lock().contains(TEST_MAJOR, 0));
+        assert_eq!(open(path, OpenFlags::O_RDWR).err(), Some(Errno::ENXIO));
+        debug!("DEVICES registered {}:0", TEST_MAJOR);
+    });
+}
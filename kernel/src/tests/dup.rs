@@ -0,0 +1,134 @@
+use alloc::{sync::Arc, vec::Vec};
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{SyscallFile, SyscallIO, Termios, F_GETFL, F_SETFL, TCGETS, TCSETS};
+use vfs::{File, OpenFlags, Path, SeekWhence};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::{open, unlink, FSFile, Pipe, Stdin},
+    mm::VMFlags,
+    read_user,
+    syscall::SyscallImpl,
+    task::cpu,
+    write_user,
+};
+
+static ONCE: Once = Once::new();
+
+/// Reads a regular file through a descriptor and its duplicate, which share the offset
+/// and status flags, duplicates standard input which shares the terminal settings, and
+/// duplicates to the lowest unused or a given descriptor.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let path = Path::new("/dup");
+
+        let test = |buf: VirtAddr| -> Result<(), Errno> {
+            let file = open(path.clone(), OpenFlags::O_CREAT | OpenFlags::O_RDWR)?;
+            assert!((*file).as_any().is::<FSFile>());
+            assert_eq!(file.write(b"abcdef"), Some(6));
+            file.seek(0, SeekWhence::Set).ok_or(Errno::ESPIPE)?;
+            let fd = curr.files().push(file)?;
+            let new_fd = SyscallImpl::dup(fd)?;
+            assert!(Arc::ptr_eq(
+                &curr.files().get(fd)?,
+                &curr.files().get(new_fd)?
+            ));
+            let read = |fd: usize| -> Result<Vec<u8>, Errno> {
+                let len = SyscallImpl::read(fd, buf.value() as *mut u8, 2)?;
+                Ok(curr.mm().copy_from_user(buf, len)?)
+            };
+
+            // Either descriptor moves the shared offset.
+            assert_eq!(read(fd)?, b"ab");
+            assert_eq!(read(new_fd)?, b"cd");
+            assert_eq!(
+                SyscallImpl::lseek(new_fd, 1, SeekWhence::Set as usize),
+                Ok(1)
+            );
+            assert_eq!(read(fd)?, b"bc");
+
+            // Status flags are shared, but not the close-on-exec flag.
+            SyscallImpl::fcntl(new_fd, F_SETFL, OpenFlags::O_APPEND.bits() as usize)?;
+            let flags = SyscallImpl::fcntl(fd, F_GETFL, 0)?;
+            assert!(OpenFlags::from_bits_truncate(flags as u32).contains(OpenFlags::O_APPEND));
+            SyscallImpl::close(fd)?;
+            assert_eq!(read(new_fd)?, b"de");
+            SyscallImpl::close(new_fd)?;
+
+            // Standard input is shared as a whole, including the line discipline set
+            // through either descriptor.
+            let stdin: Arc<dyn File> = Arc::new(Stdin);
+            let fd = curr.files().push(stdin.clone())?;
+            let new_fd = SyscallImpl::dup(fd)?;
+            let dup = curr.files().get(new_fd)?;
+            assert!(Arc::ptr_eq(&stdin, &dup) && dup.read_ready());
+            let argp = buf.value() as *const usize;
+            let mut termios = Termios::default();
+            SyscallImpl::ioctl(fd, TCGETS, argp)?;
+            read_user!(curr.mm(), buf, termios, Termios)?;
+            let old = termios;
+            // Clears ICANON to read without waiting for a line.
+            termios.c_lflag &= !0o2;
+            write_user!(curr.mm(), buf, termios, Termios)?;
+            SyscallImpl::ioctl(new_fd, TCSETS, argp)?;
+            let mut shared = Termios::default();
+            SyscallImpl::ioctl(fd, TCGETS, argp)?;
+            read_user!(curr.mm(), buf, shared, Termios)?;
+            assert_eq!(shared.c_lflag, termios.c_lflag);
+            write_user!(curr.mm(), buf, old, Termios)?;
+            SyscallImpl::ioctl(fd, TCSETS, argp)?;
+            SyscallImpl::close(fd)?;
+            SyscallImpl::close(new_fd)?;
+            debug!("DUP {} shares the open file description", new_fd);
+
+            // The lowest unused descriptor is taken, without close-on-exec.
+            let (pipe_read, pipe_write) = Pipe::new();
+            let (pipe_read, pipe_write): (Arc<dyn File>, Arc<dyn File>) =
+                (Arc::new(pipe_read), Arc::new(pipe_write));
+            let fd_read = curr.files().push(pipe_read.clone())?;
+            let fd_write = curr.files().push(pipe_write.clone())?;
+            curr.files()
+                .update(|files| files.set_cloexec(fd_read, true))?;
+            let hole = SyscallImpl::dup(fd_read)?;
+            SyscallImpl::close(hole)?;
+            assert_eq!(SyscallImpl::dup(fd_read), Ok(hole));
+            assert_eq!(curr.files().get_cloexec(hole), Ok(false));
+
+            // The target is closed silently, with close-on-exec if requested.
+            let cloexec = OpenFlags::O_CLOEXEC.bits() as usize;
+            assert_eq!(SyscallImpl::dup3(fd_write, hole, cloexec), Ok(hole));
+            assert!(Arc::ptr_eq(&curr.files().get(hole)?, &pipe_write));
+            assert_eq!(curr.files().get_cloexec(hole), Ok(true));
+            assert_eq!(SyscallImpl::dup3(fd_read, hole, 0), Ok(hole));
+            assert!(Arc::ptr_eq(&curr.files().get(hole)?, &pipe_read));
+            assert_eq!(curr.files().get_cloexec(hole), Ok(false));
+
+            // Duplicating to itself keeps the descriptor as it is for dup2, but is
+            // rejected by dup3.
+            assert_eq!(curr.files().dup_to(fd_read, fd_read, false), Ok(fd_read));
+            assert_eq!(curr.files().get_cloexec(fd_read), Ok(true));
+            assert_eq!(SyscallImpl::dup3(fd_read, fd_read, 0), Err(Errno::EINVAL));
+            assert_eq!(
+                SyscallImpl::dup3(fd_read, hole, OpenFlags::O_APPEND.bits() as usize),
+                Err(Errno::EINVAL)
+            );
+
+            // Invalid descriptors.
+            let limit = curr.files().get_limit();
+            SyscallImpl::close(hole)?;
+            assert_eq!(SyscallImpl::dup(hole), Err(Errno::EBADF));
+            assert_eq!(SyscallImpl::dup3(hole, fd_read, 0), Err(Errno::EBADF));
+            assert_eq!(SyscallImpl::dup3(fd_read, limit, 0), Err(Errno::EBADF));
+            SyscallImpl::close(fd_read)?;
+            SyscallImpl::close(fd_write)?;
+            Ok(())
+        };
+        let result = with_scratch(PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test);
+        unlink(path).unwrap();
+        result.unwrap();
+    });
+}
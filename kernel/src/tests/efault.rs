@@ -0,0 +1,87 @@
+use alloc::sync::Arc;
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{IoVec, SyscallFile, SyscallIO, SyscallProc};
+use vfs::{File, OpenFlags, Path};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::{mkdir, open, unlink, Pipe},
+    mm::{do_mprotect, MmapProt, VMFlags},
+    syscall::SyscallImpl,
+    task::cpu,
+    write_user,
+};
+
+static ONCE: Once = Once::new();
+
+/// Syscalls writing into a read-only mapping fail with `EFAULT` before any data is
+/// consumed from the file.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let dir = Path::new("/tmp/efault/");
+        let entry = Path::new("/tmp/efault/entry");
+
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            let rdonly = start + PAGE_SIZE;
+            do_mprotect(&mut curr.mm(), rdonly, PAGE_SIZE, MmapProt::PROT_READ)?;
+            let (pipe_read, pipe_write) = Pipe::new();
+            let pipe_read = Arc::new(pipe_read);
+            assert_eq!(pipe_write.try_write(b"efault"), Ok(6));
+            let fd = curr.files().push(pipe_read.clone())?;
+            let buf = rdonly.value();
+
+            // The data is left in the pipe.
+            assert_eq!(SyscallImpl::read(fd, buf as *mut u8, 6), Err(Errno::EFAULT));
+            let iovs = [
+                IoVec {
+                    iov_base: start.value() + PAGE_SIZE / 2,
+                    iov_len: 2,
+                },
+                IoVec {
+                    iov_base: buf,
+                    iov_len: 4,
+                },
+            ];
+            write_user!(curr.mm(), start, iovs, [IoVec; 2])?;
+            assert_eq!(
+                SyscallImpl::readv(fd, start.value() as *const IoVec, 2),
+                Err(Errno::EFAULT)
+            );
+            assert!(pipe_read.read_ready());
+            assert_eq!(SyscallImpl::fstat(fd, buf), Err(Errno::EFAULT));
+            SyscallImpl::close(fd)?;
+
+            // Neither are the entries consumed.
+            mkdir(dir.clone())?;
+            open(entry.clone(), OpenFlags::O_CREAT | OpenFlags::O_RDWR)?;
+            let fd = curr.files().push(open(dir.clone(), OpenFlags::O_RDONLY)?)?;
+            assert_eq!(
+                SyscallImpl::getdents64(fd, buf as *mut u8, PAGE_SIZE),
+                Err(Errno::EFAULT)
+            );
+            assert_eq!(curr.files().get(fd)?.get_off(), 0);
+            SyscallImpl::close(fd)?;
+
+            assert_eq!(
+                SyscallImpl::getrandom(buf as *mut u8, 16, 0),
+                Err(Errno::EFAULT)
+            );
+            assert_eq!(SyscallImpl::uname(buf), Err(Errno::EFAULT));
+            assert_eq!(curr.mm().copy_from_user(rdonly, PAGE_SIZE)?, [0; PAGE_SIZE]);
+            debug!("EFAULT on read-only mapping {:?}", rdonly);
+            Ok(())
+        };
+        let result = with_scratch(
+            2 * PAGE_SIZE,
+            VMFlags::USER | VMFlags::READ | VMFlags::WRITE,
+            test,
+        );
+        let _ = unlink(entry);
+        let _ = unlink(dir);
+        result.unwrap();
+    });
+}
@@ -0,0 +1,64 @@
+use alloc::{sync::Arc, vec, vec::Vec};
+use device_cache::BlockDevice;
+use easy_fs::{EasyFileSystem, BLOCK_SZ};
+use kernel_sync::SpinLock;
+use log::debug;
+use spin::Once;
+use vfs::{OpenFlags, Path};
+
+use crate::fs::{makedev, mkdir, mount, open, umount, EasyFs, VIRTBLK_MAJOR};
+
+/// A block device in memory.
+pub struct RamDisk(pub SpinLock<Vec<u8>>);
+
+impl BlockDevice for RamDisk {
+    fn read_block(&self, block_id: usize, buf: &mut [u8]) {
+        let data = self.0.lock();
+        let start = block_id * BLOCK_SZ;
+        buf.copy_from_slice(&data[start..start + buf.len()]);
+    }
+
+    fn write_block(&self, block_id: usize, buf: &[u8]) {
+        let mut data = self.0.lock();
+        let start = block_id * BLOCK_SZ;
+        data[start..start + buf.len()].copy_from_slice(buf);
+    }
+}
+
+/// Total blocks of the easy-fs image.
+pub const EFS_BLOCKS: usize = 2048;
+
+static ONCE: Once = Once::new();
+
+/// Mounts an easy-fs image at `/efs`, then writes a file and reads it back.
+pub fn test() {
+    ONCE.call_once(|| {
+        let disk = Arc::new(RamDisk(SpinLock::new(vec![0; EFS_BLOCKS * BLOCK_SZ])));
+        let efs = EasyFileSystem::format(disk, EFS_BLOCKS, 1).unwrap();
+        let mount_point = Path::new("/efs/");
+        // Mount point must exist in the parent filesystem.
+        let _ = mkdir(mount_point.clone());
+        mount(
+            mount_point.clone(),
+            Arc::new(EasyFs::new(efs, mount_point.clone(), makedev(VIRTBLK_MAJOR, 1))),
+        )
+        .unwrap();
+
+        mkdir(Path::new("/efs/dir/")).unwrap();
+        let file = open(
+            Path::new("/efs/dir/hello"),
+            OpenFlags::O_CREAT | OpenFlags::O_WRONLY,
+        )
+        .unwrap();
+        assert_eq!(file.write(b"hello, efs"), Some(10));
+        drop(file);
+
+        let file = open(Path::new("/efs/dir/hello"), OpenFlags::O_RDONLY).unwrap();
+        let mut buf = [0u8; 16];
+        assert_eq!(file.read(&mut buf), Some(10));
+        assert_eq!(&buf[..10], b"hello, efs");
+        debug!("EFS read {:?}", core::str::from_utf8(&buf[..10]).unwrap());
+
+        umount(&mount_point).unwrap();
+    });
+}
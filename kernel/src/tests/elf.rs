@@ -0,0 +1,212 @@
+//! Minimal RISC-V ELF64 images built in memory for tests of the loader.
+//!
+//! The whole image is loaded by a single `PT_LOAD` segment starting at offset 0,
+//! so a position-independent image is relocated by the loader.
+
+use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+use core::mem::size_of;
+
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    config::{ADDR_ALIGN, USER_STACK_BASE},
+    error::KernelResult,
+    loader::flags::AuxType,
+    mm::MM,
+};
+
+/// Code of a program exiting with status 0.
+pub const EXIT_CODE: [u32; 3] = [
+    // li a7, 93 (SyscallNO::EXIT)
+    0x05d0_0893,
+    // li a0, 0
+    0x0000_0513,
+    // ecall
+    0x0000_0073,
+];
+
+/// Offset of the path of the program interpreter.
+const INTERP: usize = 0x100;
+
+/// Offset of the code, where the program starts.
+pub const CODE: usize = 0x200;
+
+/// Offset of zero-filled words, which may be patched by relocations.
+pub const DATA: usize = 0x300;
+
+/// Offset of `Elf64_Rela` entries.
+const RELA: usize = 0x400;
+
+/// Size of the ELF header.
+const EHDR_SIZE: usize = 64;
+
+/// Size of a program header.
+const PHDR_SIZE: usize = 56;
+
+/// Size of a section header.
+const SHDR_SIZE: usize = 64;
+
+/// Size of an `Elf64_Rela` entry.
+const RELA_SIZE: usize = 24;
+
+/// Type of an executable file.
+const ET_EXEC: u16 = 2;
+
+/// Type of a shared object file, including a position-independent executable.
+const ET_DYN: u16 = 3;
+
+/// Machine: RISC-V.
+const EM_RISCV: u16 = 0xf3;
+
+/// Loadable segment.
+const PT_LOAD: u32 = 1;
+
+/// Segment holding the path of the program interpreter.
+const PT_INTERP: u32 = 3;
+
+/// Section of relocations with addends.
+const SHT_RELA: u32 = 4;
+
+/// Relocation type: `B + A`.
+pub const R_RISCV_RELATIVE: u64 = 3;
+
+/// Description of an ELF image.
+#[derive(Default)]
+pub struct Elf<'a> {
+    /// Virtual address of the image, or 0 for a position-independent image.
+    pub base: usize,
+
+    /// Code placed at [`CODE`].
+    pub code: &'a [u32],
+
+    /// Path of the program interpreter in `PT_INTERP`.
+    pub interp: Option<&'a str>,
+
+    /// Relocations in a `SHT_RELA` section, as `(offset, type, addend)`.
+    pub relas: &'a [(usize, u64, usize)],
+}
+
+/// Copies `bytes` to `data` at `off`.
+fn put(data: &mut [u8], off: usize, bytes: &[u8]) {
+    data[off..off + bytes.len()].copy_from_slice(bytes);
+}
+
+/// Writes a program header at `off` for a segment of `filesz` bytes at `seg` in the
+/// file and `memsz` bytes at `va` in memory.
+///
+/// A loadable segment is readable, writable and executable.
+fn put_phdr(
+    data: &mut [u8],
+    off: usize,
+    type_: u32,
+    seg: usize,
+    va: usize,
+    filesz: usize,
+    memsz: usize,
+) {
+    let flags: u32 = if type_ == PT_LOAD { 0b111 } else { 0b100 };
+    put(data, off, &type_.to_le_bytes());
+    put(data, off + 4, &flags.to_le_bytes());
+    put(data, off + 8, &(seg as u64).to_le_bytes());
+    put(data, off + 16, &(va as u64).to_le_bytes());
+    put(data, off + 24, &(va as u64).to_le_bytes());
+    put(data, off + 32, &(filesz as u64).to_le_bytes());
+    put(data, off + 40, &(memsz as u64).to_le_bytes());
+    put(data, off + 48, &(PAGE_SIZE as u64).to_le_bytes());
+}
+
+impl<'a> Elf<'a> {
+    /// Serializes the image.
+    pub fn build(&self) -> Vec<u8> {
+        let phnum = 1 + self.interp.is_some() as usize;
+        let shoff = RELA + self.relas.len() * RELA_SIZE;
+        let mut data = vec![0u8; shoff + 2 * SHDR_SIZE];
+
+        // ELF header
+        put(&mut data, 0, &[0x7f, b'E', b'L', b'F', 2, 1, 1]);
+        let type_: u16 = if self.base == 0 { ET_DYN } else { ET_EXEC };
+        put(&mut data, 16, &type_.to_le_bytes());
+        put(&mut data, 18, &EM_RISCV.to_le_bytes());
+        put(&mut data, 20, &1u32.to_le_bytes());
+        put(&mut data, 24, &((self.base + CODE) as u64).to_le_bytes());
+        put(&mut data, 32, &(EHDR_SIZE as u64).to_le_bytes());
+        put(&mut data, 40, &(shoff as u64).to_le_bytes());
+        put(&mut data, 52, &(EHDR_SIZE as u16).to_le_bytes());
+        put(&mut data, 54, &(PHDR_SIZE as u16).to_le_bytes());
+        put(&mut data, 56, &(phnum as u16).to_le_bytes());
+        put(&mut data, 58, &(SHDR_SIZE as u16).to_le_bytes());
+        put(&mut data, 60, &2u16.to_le_bytes());
+
+        // The image up to the relocations is loaded into a page.
+        put_phdr(&mut data, EHDR_SIZE, PT_LOAD, 0, self.base, RELA, PAGE_SIZE);
+        if let Some(interp) = self.interp {
+            put(&mut data, INTERP, interp.as_bytes());
+            let (va, len) = (self.base + INTERP, interp.len() + 1);
+            put_phdr(
+                &mut data,
+                EHDR_SIZE + PHDR_SIZE,
+                PT_INTERP,
+                INTERP,
+                va,
+                len,
+                len,
+            );
+        }
+
+        for (i, &word) in self.code.iter().enumerate() {
+            put(&mut data, CODE + 4 * i, &word.to_le_bytes());
+        }
+        for (i, &(offset, type_, addend)) in self.relas.iter().enumerate() {
+            let entry = RELA + i * RELA_SIZE;
+            put(&mut data, entry, &(offset as u64).to_le_bytes());
+            put(&mut data, entry + 8, &type_.to_le_bytes());
+            put(&mut data, entry + 16, &(addend as u64).to_le_bytes());
+        }
+
+        // A null section followed by the relocations.
+        let shdr = shoff + SHDR_SIZE;
+        put(&mut data, shdr + 4, &SHT_RELA.to_le_bytes());
+        put(&mut data, shdr + 24, &(RELA as u64).to_le_bytes());
+        put(&mut data, shdr + 32, &((shoff - RELA) as u64).to_le_bytes());
+        put(&mut data, shdr + 48, &8u64.to_le_bytes());
+        put(&mut data, shdr + 56, &(RELA_SIZE as u64).to_le_bytes());
+        data
+    }
+}
+
+/// Contents of the initial user stack.
+pub struct InitStack {
+    /// Strings pointed to by `argv`.
+    pub args: Vec<String>,
+
+    /// Strings pointed to by `envp`.
+    pub envs: Vec<String>,
+
+    /// Auxiliary vector from the type to the value.
+    pub auxv: BTreeMap<AuxType, usize>,
+}
+
+impl InitStack {
+    /// Parses the initial stack from `sp` up to the base of the user stack.
+    pub fn parse(mm: &mut MM, sp: VirtAddr) -> KernelResult<Self> {
+        let data = mm.copy_from_user(sp, USER_STACK_BASE - ADDR_ALIGN - sp.value())?;
+        let mut words = data
+            .chunks_exact(size_of::<usize>())
+            .map(|word| usize::from_le_bytes(word.try_into().unwrap()));
+        // Strings in a vector ended with NULL.
+        let strings = |mm: &mut MM, words: &mut dyn Iterator<Item = usize>| {
+            words
+                .take_while(|&ptr| ptr != 0)
+                .map(|ptr| mm.get_str(VirtAddr::from(ptr)))
+                .collect::<KernelResult<Vec<String>>>()
+        };
+        let argc = words.next().unwrap();
+        let args = strings(mm, &mut words)?;
+        assert_eq!(args.len(), argc);
+        let envs = strings(mm, &mut words)?;
+        let mut auxv = BTreeMap::new();
+        while let Some(type_) = words.next().filter(|&type_| type_ != 0) {
+            auxv.insert(AuxType::try_from(type_).unwrap(), words.next().unwrap());
+        }
+        Ok(Self { args, envs, auxv })
+    }
+}
@@ -0,0 +1,74 @@
+use alloc::{format, vec};
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use vfs::{File, OpenFlags, Path, SeekWhence};
+
+use crate::{
+    config::FS_IMG_SIZE,
+    fs::{mkdir, open, unlink},
+};
+
+static ONCE: Once = Once::new();
+
+/// Size of each write to fill the image.
+const CHUNK: usize = 64 * 1024;
+
+/// Fills the image with a file until writes fail with `ENOSPC` instead of writing
+/// nothing, and then extending the file by seeking past its end fails as well. Files
+/// are then created in a directory until it cannot grow, which fails with `ENOSPC`.
+pub fn test() {
+    ONCE.call_once(|| {
+        let dir = Path::new("/enospc/");
+        mkdir(dir.clone()).unwrap();
+        let path = Path::new("/enospc.bin");
+        let file = open(
+            path.clone(),
+            OpenFlags::O_CREAT | OpenFlags::O_TRUNC | OpenFlags::O_RDWR,
+        )
+        .unwrap();
+
+        let buf = vec![0x5au8; CHUNK];
+        let mut size = 0;
+        let errno = loop {
+            match file.try_write(&buf) {
+                Ok(count) => {
+                    assert_ne!(count, 0);
+                    size += count;
+                }
+                Err(errno) => break errno,
+            }
+            assert!(size <= FS_IMG_SIZE);
+        };
+        assert_eq!(errno, Errno::ENOSPC);
+        assert!(size > 0);
+        assert_eq!(file.try_write(b"full"), Err(Errno::ENOSPC));
+        assert_eq!(file.write(b"full"), None);
+
+        // The file is not extended, and the cursor stays at the end.
+        assert_eq!(file.seek(size + CHUNK, SeekWhence::Set), None);
+        assert_eq!(file.get_off(), size);
+        assert_eq!(file.get_size(), Some(size));
+        debug!("ENOSPC after writing {} bytes to {:?}", size, path);
+
+        // Entries fill the cluster of the directory.
+        let entry = |i: usize| Path::from(format!("/enospc/{}", i));
+        let mut count = 0;
+        let errno = loop {
+            match open(entry(count), OpenFlags::O_CREAT | OpenFlags::O_RDWR) {
+                Ok(_) => count += 1,
+                Err(errno) => break errno,
+            }
+        };
+        assert_eq!(errno, Errno::ENOSPC);
+        assert!(count > 0);
+        debug!("ENOSPC after creating {} files in {:?}", count, dir);
+
+        for i in 0..count {
+            unlink(entry(i)).unwrap();
+        }
+        unlink(dir).unwrap();
+        drop(file);
+        unlink(path).unwrap();
+    });
+}
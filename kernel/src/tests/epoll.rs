@@ -0,0 +1,142 @@
+use alloc::sync::Arc;
+use core::mem::{size_of, take};
+use errno::Errno;
+use log::debug;
+use signal_defs::{sigmask, SigInfo, SigSet, SIGUSR1};
+use spin::Once;
+use syscall_interface::{
+    EpollEvent, EpollEvents, SyscallIO, EPOLL_CLOEXEC, EPOLL_CTL_ADD, EPOLL_CTL_DEL, EPOLL_CTL_MOD,
+};
+use vfs::File;
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::Pipe,
+    mm::VMFlags,
+    read_user,
+    syscall::SyscallImpl,
+    task::cpu,
+    write_user,
+};
+
+/// User data registered with the read end of the pipe.
+const DATA: u64 = 0x1234;
+
+static ONCE: Once = Once::new();
+
+/// Registers the read end of a pipe in an epoll instance, which becomes ready after
+/// a write, and then hangs up after the write end is closed.
+///
+/// A wait on the empty interest list sleeps until the timeout expires, unless a signal
+/// unblocked by the mask interrupts it.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            let event_addr = start;
+            let events_addr = start + size_of::<EpollEvent>();
+            let mask_addr = events_addr + 2 * size_of::<EpollEvent>();
+
+            let (pipe_read, pipe_write) = Pipe::new();
+            let pipe_write = Arc::new(pipe_write);
+            let fd_read = curr.files().push(Arc::new(pipe_read))?;
+            let fd_write = curr.files().push(pipe_write.clone())?;
+            let epfd = SyscallImpl::epoll_create1(EPOLL_CLOEXEC)?;
+            assert!(curr.files().get_cloexec(epfd)?);
+            assert_eq!(SyscallImpl::epoll_create1(1), Err(Errno::EINVAL));
+
+            let ctl = |op: usize, fd: usize, events: EpollEvents| {
+                let event = EpollEvent { events, data: DATA };
+                write_user!(curr.mm(), event_addr, event, EpollEvent)?;
+                SyscallImpl::epoll_ctl(epfd, op, fd, event_addr.value())
+            };
+            let wait = |timeout: isize| -> Result<Option<EpollEvent>, Errno> {
+                let count = SyscallImpl::epoll_pwait(epfd, events_addr.value(), 2, timeout, 0)?;
+                assert!(count <= 1);
+                if count == 0 {
+                    return Ok(None);
+                }
+                let mut event = EpollEvent::default();
+                read_user!(curr.mm(), events_addr, event, EpollEvent)?;
+                Ok(Some(event))
+            };
+
+            ctl(EPOLL_CTL_ADD, fd_read, EpollEvents::EPOLLIN)?;
+            assert_eq!(
+                ctl(EPOLL_CTL_ADD, fd_read, EpollEvents::EPOLLIN),
+                Err(Errno::EEXIST)
+            );
+            assert_eq!(
+                ctl(EPOLL_CTL_ADD, epfd, EpollEvents::EPOLLIN),
+                Err(Errno::EINVAL)
+            );
+            assert_eq!(wait(0)?, None);
+            assert_eq!(wait(10)?, None);
+
+            // Ready after a write, as long as the data is not read.
+            assert_eq!(pipe_write.write(b"epoll"), Some(5));
+            let ready = Some(EpollEvent {
+                events: EpollEvents::EPOLLIN,
+                data: DATA,
+            });
+            assert_eq!(wait(-1)?, ready);
+            assert_eq!(wait(0)?, ready);
+
+            // Reported only once.
+            ctl(
+                EPOLL_CTL_MOD,
+                fd_read,
+                EpollEvents::EPOLLIN | EpollEvents::EPOLLONESHOT,
+            )?;
+            assert_eq!(wait(0)?, ready);
+            assert_eq!(wait(0)?, None);
+
+            // Hangs up after the write end is closed.
+            ctl(EPOLL_CTL_MOD, fd_read, EpollEvents::EPOLLIN)?;
+            curr.files().remove(fd_write)?;
+            drop(pipe_write);
+            assert_eq!(
+                wait(0)?.map(|event| event.events),
+                Some(EpollEvents::EPOLLIN | EpollEvents::EPOLLHUP)
+            );
+
+            ctl(EPOLL_CTL_DEL, fd_read, EpollEvents::empty())?;
+            assert_eq!(
+                ctl(EPOLL_CTL_DEL, fd_read, EpollEvents::empty()),
+                Err(Errno::ENOENT)
+            );
+            assert_eq!(wait(0)?, None);
+            debug!("EPOLL {:?}", ready);
+
+            let pending = take(&mut curr.locked_inner().sig_pending);
+            let blocked = curr.locked_inner().sig_blocked;
+            let mut mask = SigSet::new();
+            mask.set_mask(sigmask(SIGUSR1));
+            curr.locked_inner().sig_blocked = mask;
+            curr.locked_inner().sig_pending.add(SigInfo {
+                signo: SIGUSR1 as i32,
+                errno: 0,
+                code: 0,
+            });
+            assert_eq!(wait(10)?, None);
+            write_user!(curr.mm(), mask_addr, SigSet::new(), SigSet)?;
+            let result =
+                SyscallImpl::epoll_pwait(epfd, events_addr.value(), 2, -1, mask_addr.value());
+            // The mask is kept until the signal is delivered.
+            let mask_kept = !curr.locked_inner().sig_blocked.get(SIGUSR1 - 1);
+            let mut locked_inner = curr.locked_inner();
+            locked_inner.saved_sigmask = None;
+            locked_inner.sig_pending = pending;
+            locked_inner.sig_blocked = blocked;
+            drop(locked_inner);
+            assert_eq!(result, Err(Errno::EINTR));
+            assert!(mask_kept);
+
+            curr.files().remove(fd_read)?;
+            curr.files().remove(epfd)?;
+            Ok(())
+        };
+        with_scratch(PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test).unwrap();
+    });
+}
@@ -0,0 +1,111 @@
+use core::mem::{size_of, take};
+use errno::Errno;
+use log::debug;
+use signal_defs::SIGALRM;
+use spin::Once;
+use syscall_interface::{SyscallFile, SyscallIO, EFD_CLOEXEC, EFD_NONBLOCK, EFD_SEMAPHORE};
+use time_subsys::{ITimer, TimeSpec};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    mm::VMFlags,
+    read_user,
+    syscall::SyscallImpl,
+    task::cpu,
+    timer::set_real_timer,
+    write_user,
+};
+
+static ONCE: Once = Once::new();
+
+/// Reads and writes a non-blocking eventfd in normal and semaphore modes, through a
+/// buffer straddling two pages, and interrupts a blocked read with a signal.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            let buf = start + PAGE_SIZE - size_of::<u64>() / 2;
+
+            let write = |fd: usize, value: u64| {
+                write_user!(curr.mm(), buf, value, u64)?;
+                SyscallImpl::write(fd, buf.value() as *const u8, size_of::<u64>())
+            };
+            let read = |fd: usize| -> Result<u64, Errno> {
+                assert_eq!(
+                    SyscallImpl::read(fd, buf.value() as *mut u8, size_of::<u64>())?,
+                    size_of::<u64>()
+                );
+                let mut value = 0u64;
+                read_user!(curr.mm(), buf, value, u64)?;
+                Ok(value)
+            };
+            assert_eq!(SyscallImpl::eventfd2(0, 0x10), Err(Errno::EINVAL));
+
+            // Writes are summed up until read.
+            let fd = SyscallImpl::eventfd2(0, EFD_NONBLOCK | EFD_CLOEXEC)?;
+            assert!(curr.files().get_cloexec(fd)?);
+            let file = curr.files().get(fd)?;
+            assert!(!file.read_ready());
+            assert_eq!(read(fd), Err(Errno::EAGAIN));
+            assert_eq!(write(fd, 3), Ok(size_of::<u64>()));
+            assert_eq!(write(fd, 4), Ok(size_of::<u64>()));
+            assert!(file.read_ready());
+            assert_eq!(
+                SyscallImpl::read(fd, buf.value() as *mut u8, size_of::<u32>()),
+                Err(Errno::EINVAL)
+            );
+            assert_eq!(read(fd), Ok(7));
+            assert_eq!(read(fd), Err(Errno::EAGAIN));
+
+            // The counter never exceeds `u64::MAX - 1`.
+            assert_eq!(write(fd, u64::MAX), Err(Errno::EINVAL));
+            assert_eq!(write(fd, u64::MAX - 1), Ok(size_of::<u64>()));
+            assert!(!file.write_ready());
+            assert_eq!(write(fd, 1), Err(Errno::EAGAIN));
+            assert_eq!(read(fd), Ok(u64::MAX - 1));
+            assert!(file.write_ready());
+
+            // Each read decrements the counter by one.
+            let sem_fd = SyscallImpl::eventfd2(2, EFD_NONBLOCK | EFD_SEMAPHORE)?;
+            assert!(!curr.files().get_cloexec(sem_fd)?);
+            assert_eq!(read(sem_fd), Ok(1));
+            assert_eq!(read(sem_fd), Ok(1));
+            assert_eq!(read(sem_fd), Err(Errno::EAGAIN));
+            assert_eq!(write(sem_fd, 3), Ok(size_of::<u64>()));
+            let values = [read(sem_fd)?, read(sem_fd)?, read(sem_fd)?];
+            assert_eq!(values, [1; 3]);
+            assert_eq!(read(sem_fd), Err(Errno::EAGAIN));
+
+            // A blocked read is interrupted by a signal, and the eventfd still works.
+            let block_fd = SyscallImpl::eventfd2(0, 0)?;
+            let pending = take(&mut curr.locked_inner().sig_pending);
+            let blocked = curr.locked_inner().sig_blocked;
+            curr.locked_inner().sig_blocked.unset(SIGALRM - 1);
+            let timer = ITimer {
+                interval: TimeSpec::default(),
+                value: TimeSpec {
+                    tv_sec: 0,
+                    tv_nsec: 10_000_000,
+                },
+            };
+            set_real_timer(curr.pid, &mut curr.real_timer.lock(), timer);
+            let result = read(block_fd);
+            set_real_timer(curr.pid, &mut curr.real_timer.lock(), ITimer::new());
+            assert!(curr.locked_inner().sig_pending.mask.get(SIGALRM - 1));
+            curr.locked_inner().sig_pending = pending;
+            curr.locked_inner().sig_blocked = blocked;
+            assert_eq!(result, Err(Errno::ERESTART));
+            assert_eq!(write(block_fd, 5), Ok(size_of::<u64>()));
+            assert_eq!(read(block_fd), Ok(5));
+            debug!("EVENTFD {:?}", values);
+
+            drop(file);
+            SyscallImpl::close(fd)?;
+            SyscallImpl::close(sem_fd)?;
+            SyscallImpl::close(block_fd)?;
+            Ok(())
+        };
+        with_scratch(2 * PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test).unwrap();
+    });
+}
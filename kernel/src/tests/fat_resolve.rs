@@ -0,0 +1,49 @@
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use vfs::{OpenFlags, Path};
+
+use crate::fs::{mkdir, open, unlink};
+
+static ONCE: Once = Once::new();
+
+/// Resolves parent directories in FAT from the root through nested directories, and
+/// fails with `ENOENT` on a missing directory or `ENOTDIR` on a file in the path.
+pub fn test() {
+    ONCE.call_once(|| {
+        let create = |path: &str| open(Path::new(path), OpenFlags::O_CREAT | OpenFlags::O_RDWR);
+        let _ = mkdir(Path::new("/fat_resolve/"));
+        let _ = mkdir(Path::new("/fat_resolve/nested/"));
+
+        // In the root directory.
+        create("/fat_resolve.txt").unwrap();
+        assert!(open(Path::new("/fat_resolve/"), OpenFlags::O_RDONLY).is_ok());
+
+        // In a nested directory.
+        create("/fat_resolve/nested/file").unwrap();
+        assert!(open(Path::new("/fat_resolve/nested/file"), OpenFlags::O_RDONLY).is_ok());
+
+        assert_eq!(
+            create("/fat_resolve/missing/file").err(),
+            Some(Errno::ENOENT)
+        );
+        assert_eq!(
+            mkdir(Path::new("/fat_resolve/missing/dir/")),
+            Err(Errno::ENOENT)
+        );
+        assert_eq!(
+            create("/fat_resolve/nested/file/file").err(),
+            Some(Errno::ENOTDIR)
+        );
+        assert_eq!(
+            unlink(Path::new("/fat_resolve.txt/file")),
+            Err(Errno::ENOTDIR)
+        );
+        debug!("FAT resolved {:?}", Path::new("/fat_resolve/nested/"));
+
+        unlink(Path::new("/fat_resolve/nested/file")).unwrap();
+        unlink(Path::new("/fat_resolve/nested/")).unwrap();
+        unlink(Path::new("/fat_resolve/")).unwrap();
+        unlink(Path::new("/fat_resolve.txt")).unwrap();
+    });
+}
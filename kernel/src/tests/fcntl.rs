@@ -0,0 +1,78 @@
+use alloc::sync::Arc;
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{
+    SyscallFile, FD_CLOEXEC, F_DUPFD, F_DUPFD_CLOEXEC, F_GETFD, F_GETFL, F_SETFD, F_SETFL,
+};
+use vfs::{File, OpenFlags};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::Pipe,
+    mm::VMFlags,
+    syscall::SyscallImpl,
+    task::cpu,
+};
+
+static ONCE: Once = Once::new();
+
+/// Flips the close-on-exec flag, toggles `O_NONBLOCK` of a pipe which then fails to
+/// read when empty, and duplicates descriptors above a given number.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+
+        let test = |buf: VirtAddr| -> Result<(), Errno> {
+            let (pipe_read, _pipe_write) = Pipe::new();
+            let pipe_read = Arc::new(pipe_read);
+            let fd = curr.files().push(pipe_read.clone())?;
+
+            // Close-on-exec.
+            assert_eq!(SyscallImpl::fcntl(fd, F_GETFD, 0), Ok(0));
+            assert_eq!(SyscallImpl::fcntl(fd, F_SETFD, FD_CLOEXEC), Ok(0));
+            assert_eq!(SyscallImpl::fcntl(fd, F_GETFD, 0), Ok(FD_CLOEXEC));
+            assert_eq!(SyscallImpl::fcntl(fd, F_SETFD, 0), Ok(0));
+            assert_eq!(SyscallImpl::fcntl(fd, F_GETFD, 0), Ok(0));
+
+            // Only O_NONBLOCK of a pipe changes, leaving the access mode.
+            let getfl = || -> Result<OpenFlags, Errno> {
+                let flags = SyscallImpl::fcntl(fd, F_GETFL, 0)?;
+                Ok(OpenFlags::from_bits_truncate(flags as u32))
+            };
+            let mode = getfl()? & (OpenFlags::O_WRONLY | OpenFlags::O_RDWR);
+            let flags = OpenFlags::O_NONBLOCK | OpenFlags::O_RDWR;
+            SyscallImpl::fcntl(fd, F_SETFL, flags.bits() as usize)?;
+            assert!(getfl()?.contains(OpenFlags::O_NONBLOCK));
+            assert_eq!(getfl()? & (OpenFlags::O_WRONLY | OpenFlags::O_RDWR), mode);
+            let data = buf.value() as *mut u8;
+            assert_eq!(SyscallImpl::read(fd, data, 8), Err(Errno::EAGAIN));
+            SyscallImpl::fcntl(fd, F_SETFL, 0)?;
+            assert!(!pipe_read.open_flags().contains(OpenFlags::O_NONBLOCK));
+
+            // Duplicates take the lowest unused descriptor from the argument.
+            let min = fd + 4;
+            let new_fd = SyscallImpl::fcntl(fd, F_DUPFD, min)?;
+            assert!(new_fd >= min);
+            assert_eq!(SyscallImpl::fcntl(new_fd, F_GETFD, 0), Ok(0));
+            let cloexec_fd = SyscallImpl::fcntl(fd, F_DUPFD_CLOEXEC, min)?;
+            assert!(cloexec_fd >= min && cloexec_fd != new_fd);
+            assert_eq!(SyscallImpl::fcntl(cloexec_fd, F_GETFD, 0), Ok(FD_CLOEXEC));
+            let limit = curr.files().get_limit();
+            assert_eq!(SyscallImpl::fcntl(fd, F_DUPFD, limit), Err(Errno::EINVAL));
+
+            // Unknown commands and closed descriptors.
+            assert_eq!(SyscallImpl::fcntl(fd, usize::MAX, 0), Err(Errno::EINVAL));
+            SyscallImpl::close(new_fd)?;
+            assert_eq!(SyscallImpl::fcntl(new_fd, F_GETFD, 0), Err(Errno::EBADF));
+            assert_eq!(SyscallImpl::fcntl(new_fd, F_GETFL, 0), Err(Errno::EBADF));
+            SyscallImpl::close(cloexec_fd)?;
+            SyscallImpl::close(fd)?;
+            debug!("FCNTL duplicated {} to {}", fd, new_fd);
+            Ok(())
+        };
+        let result = with_scratch(PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test);
+        result.unwrap();
+    });
+}
@@ -0,0 +1,95 @@
+use alloc::sync::Arc;
+use errno::Errno;
+use log::debug;
+use syscall_interface::{SyscallFile, SyscallProc};
+use vfs::File;
+
+use super::{run_user_code, spawn_threads, wait_exited, RECORD};
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::Pipe,
+    read_user,
+    syscall::SyscallImpl,
+    task::{cpu, do_yield},
+    write_user,
+};
+
+/// Number of reader threads.
+const READERS: usize = 4;
+
+/// Number of times the descriptor is replaced.
+const UPDATES: usize = 1000;
+
+/// Reader thread in user space, looking up the descriptor with `fcntl(F_GETFD)` and
+/// counting failures until stopped.
+///
+/// The record holds the descriptor, the flag to stop, the number of failed lookups and
+/// the number of all lookups.
+#[naked]
+unsafe extern "C" fn reader_body() {
+    core::arch::asm!(
+        "1:",
+        "ld a0, 0(tp)",
+        // F_GETFD
+        "li a1, 1",
+        "li a2, 0",
+        // SyscallNO::FCNTL
+        "li a7, 25",
+        "ecall",
+        "bgez a0, 2f",
+        "li t0, 1",
+        "addi t1, tp, 16",
+        "amoadd.d zero, t0, (t1)",
+        "2:",
+        "li t0, 1",
+        "addi t1, tp, 24",
+        "amoadd.d zero, t0, (t1)",
+        "ld t0, 8(tp)",
+        "beqz t0, 1b",
+        // SyscallNO::EXIT
+        "li a7, 93",
+        "li a0, 0",
+        "ecall",
+        options(noreturn),
+    );
+}
+
+/// Replaces a descriptor shared with reader threads by `dup3` many times, which is
+/// always found by the readers since they never see a table modified halfway.
+pub fn test() {
+    let curr = cpu().curr.as_ref().unwrap();
+    let test = |start: VirtAddr| -> Result<(), Errno> {
+        let (pipe_read, pipe_write) = Pipe::new();
+        let (pipe_read, pipe_write): (Arc<dyn File>, Arc<dyn File>) =
+            (Arc::new(pipe_read), Arc::new(pipe_write));
+        let fd_read = curr.files().push(pipe_read)?;
+        let fd_write = curr.files().push(pipe_write)?;
+        let fd = SyscallImpl::dup(fd_read)?;
+        write_user!(curr.mm(), start + RECORD, [fd, 0, 0, 0], [usize; 4])?;
+
+        let tids = spawn_threads(start, start + RECORD, &[0; READERS])?;
+
+        for i in 0..UPDATES {
+            let oldfd = if i % 2 == 0 { fd_write } else { fd_read };
+            assert_eq!(SyscallImpl::dup3(oldfd, fd, 0), Ok(fd));
+            if i % 16 == 0 {
+                unsafe { do_yield() };
+            }
+        }
+        write_user!(curr.mm(), start + RECORD + 8, 1usize, usize)?;
+
+        for &tid in &tids {
+            wait_exited(tid);
+        }
+        let mut record = [0usize; 4];
+        read_user!(curr.mm(), start + RECORD, record, [usize; 4])?;
+        assert_eq!(record[2], 0);
+        assert!(record[3] > 0);
+        SyscallImpl::close(fd)?;
+        SyscallImpl::close(fd_read)?;
+        SyscallImpl::close(fd_write)?;
+        debug!("FD_TABLE {} lookups by {:?}", record[3], tids);
+        Ok(())
+    };
+    run_user_code(&[reader_body], PAGE_SIZE, test).unwrap();
+}
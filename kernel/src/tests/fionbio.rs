@@ -0,0 +1,66 @@
+use alloc::sync::Arc;
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{SyscallFile, SyscallIO, FIONBIO};
+use vfs::{File, OpenFlags, Path};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::{open, unlink, Pipe},
+    mm::VMFlags,
+    syscall::SyscallImpl,
+    task::cpu,
+    write_user,
+};
+
+static ONCE: Once = Once::new();
+
+/// Toggles non-blocking mode of a pipe, which fails to read when empty, and of a
+/// regular file, which keeps its other status flags.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let path = Path::new("/fionbio");
+
+        let test = |buf: VirtAddr| -> Result<(), Errno> {
+            let argp = buf.value() as *const usize;
+            let fionbio = |fd: usize, nonblock: i32| -> Result<usize, Errno> {
+                write_user!(curr.mm(), buf, nonblock, i32)?;
+                SyscallImpl::ioctl(fd, FIONBIO, argp)
+            };
+
+            let (pipe_read, _pipe_write) = Pipe::new();
+            let pipe_read = Arc::new(pipe_read);
+            let fd = curr.files().push(pipe_read.clone())?;
+            assert!(!pipe_read.open_flags().contains(OpenFlags::O_NONBLOCK));
+            assert_eq!(fionbio(fd, 1), Ok(0));
+            assert!(pipe_read.open_flags().contains(OpenFlags::O_NONBLOCK));
+            let data = (buf + PAGE_SIZE / 2).value() as *mut u8;
+            assert_eq!(SyscallImpl::read(fd, data, 8), Err(Errno::EAGAIN));
+            assert_eq!(fionbio(fd, 0), Ok(0));
+            assert!(!pipe_read.open_flags().contains(OpenFlags::O_NONBLOCK));
+            curr.files().remove(fd)?;
+
+            let file = open(
+                path.clone(),
+                OpenFlags::O_CREAT | OpenFlags::O_RDWR | OpenFlags::O_APPEND,
+            )?;
+            let fd = curr.files().push(file.clone())?;
+            assert_eq!(fionbio(fd, 1), Ok(0));
+            assert!(file
+                .open_flags()
+                .contains(OpenFlags::O_NONBLOCK | OpenFlags::O_APPEND));
+            assert_eq!(fionbio(fd, 0), Ok(0));
+            assert!(!file.open_flags().contains(OpenFlags::O_NONBLOCK));
+            assert!(file.open_flags().contains(OpenFlags::O_APPEND));
+            curr.files().remove(fd)?;
+            debug!("FIONBIO toggled O_NONBLOCK of a pipe and a file");
+            Ok(())
+        };
+        let result = with_scratch(PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test);
+        let _ = unlink(path);
+        result.unwrap();
+    });
+}
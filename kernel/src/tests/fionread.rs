@@ -0,0 +1,100 @@
+use alloc::{sync::Arc, vec};
+use easy_fs::{EasyFileSystem, BLOCK_SZ};
+use errno::Errno;
+use kernel_sync::SpinLock;
+use log::debug;
+use spin::Once;
+use syscall_interface::{SyscallIO, FIONREAD};
+use vfs::{File, OpenFlags, Path, SeekWhence};
+
+use super::{
+    efs::{RamDisk, EFS_BLOCKS},
+    with_scratch,
+};
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::{makedev, mkdir, mount, open, umount, unlink, EasyFs, Pipe, VIRTBLK_MAJOR},
+    mm::VMFlags,
+    read_user,
+    syscall::SyscallImpl,
+    task::cpu,
+};
+
+static ONCE: Once = Once::new();
+
+/// Counts the bytes left to read in a partially filled pipe, in a regular file of
+/// each filesystem positioned in the middle, and in a character device.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let path = Path::new("/fionread");
+        let tmp_path = Path::new("/tmp/fionread");
+        let mount_point = Path::new("/fionread_efs/");
+
+        let disk = Arc::new(RamDisk(SpinLock::new(vec![0; EFS_BLOCKS * BLOCK_SZ])));
+        let efs = EasyFileSystem::format(disk, EFS_BLOCKS, 1).unwrap();
+        let _ = mkdir(mount_point.clone());
+        mount(
+            mount_point.clone(),
+            Arc::new(EasyFs::new(
+                efs,
+                mount_point.clone(),
+                makedev(VIRTBLK_MAJOR, 3),
+            )),
+        )
+        .unwrap();
+
+        let test = |buf: VirtAddr| -> Result<(), Errno> {
+            let fionread = |fd: usize| -> Result<i32, Errno> {
+                SyscallImpl::ioctl(fd, FIONREAD, buf.value() as *const usize)?;
+                let mut count = -1i32;
+                read_user!(curr.mm(), buf, count, i32)?;
+                Ok(count)
+            };
+
+            let (pipe_read, pipe_write) = Pipe::new();
+            let fd_read = curr.files().push(Arc::new(pipe_read))?;
+            assert_eq!(fionread(fd_read)?, 0);
+            assert_eq!(pipe_write.try_write(b"fionread"), Ok(8));
+            assert_eq!(fionread(fd_read)?, 8);
+            let mut data = [0u8; 3];
+            assert_eq!(curr.files().get(fd_read)?.read(&mut data), Some(3));
+            assert_eq!(fionread(fd_read)?, 5);
+            curr.files().remove(fd_read)?;
+
+            let check_file = |file: Arc<dyn File>| -> Result<(), Errno> {
+                assert_eq!(file.write(b"0123456789"), Some(10));
+                file.seek(4, SeekWhence::Set).ok_or(Errno::ESPIPE)?;
+                let fd = curr.files().push(file.clone())?;
+                assert_eq!(fionread(fd)?, 6);
+                // Nothing is left at the end.
+                file.seek(0, SeekWhence::End).ok_or(Errno::ESPIPE)?;
+                assert_eq!(fionread(fd)?, 0);
+                curr.files().remove(fd)?;
+                Ok(())
+            };
+            let flags = OpenFlags::O_CREAT | OpenFlags::O_RDWR;
+            check_file(open(path.clone(), flags)?)?;
+            check_file(open(tmp_path.clone(), flags)?)?;
+            check_file(open(
+                Path::new("/tmp/"),
+                OpenFlags::O_TMPFILE | OpenFlags::O_RDWR,
+            )?)?;
+            check_file(open(Path::new("/fionread_efs/file"), flags)?)?;
+
+            // Devices have nothing buffered.
+            let fd = curr
+                .files()
+                .push(open(Path::new("/dev/zero"), OpenFlags::O_RDONLY)?)?;
+            assert_eq!(fionread(fd)?, 0);
+            curr.files().remove(fd)?;
+            debug!("FIONREAD counted pipe, file and device bytes");
+            Ok(())
+        };
+        let result = with_scratch(PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test);
+        let _ = unlink(path);
+        let _ = unlink(tmp_path);
+        umount(&mount_point).unwrap();
+        result.unwrap();
+    });
+}
@@ -0,0 +1,37 @@
+use log::debug;
+use spin::Once;
+
+use crate::arch::mm::AllocatedFrameRange;
+
+/// Number of frames allocated, which is a power of two to fit a buddy block.
+const COUNT: usize = 8;
+
+static ONCE: Once = Once::new();
+
+/// Allocates a zeroed range of frames, writes to it and drops it, then gets the same
+/// frames zeroed again, also after dropping both halves of a split range.
+pub fn test() {
+    ONCE.call_once(|| {
+        let mut frames = AllocatedFrameRange::new_zeroed(COUNT).unwrap();
+        let start = frames.start;
+        assert_eq!(frames.size_in_frames(), COUNT);
+        assert!(frames.as_slice().iter().all(|&b| b == 0));
+        frames.as_slice_mut().fill(0xa5);
+        drop(frames);
+
+        let mut frames = AllocatedFrameRange::new_zeroed(COUNT).unwrap();
+        assert_eq!(frames.start, start);
+        assert!(frames.as_slice().iter().all(|&b| b == 0));
+        frames.as_slice_mut().fill(0xa5);
+
+        let left = frames.split_at(start + COUNT / 2, true).unwrap();
+        assert_eq!(left.size_in_frames() + frames.size_in_frames(), COUNT);
+        drop(left);
+        drop(frames);
+
+        let frames = AllocatedFrameRange::new_zeroed(COUNT).unwrap();
+        assert_eq!(frames.start, start);
+        assert!(frames.as_slice().iter().all(|&b| b == 0));
+        debug!("FRAME {:?}", frames);
+    });
+}
@@ -0,0 +1,104 @@
+use alloc::vec::Vec;
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{SyscallFile, AT_EMPTY_PATH, AT_FDCWD};
+use vfs::{File, OpenFlags, Path, Stat, StatMode};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::{mkdir, open, unlink},
+    mm::VMFlags,
+    read_user,
+    syscall::SyscallImpl,
+    task::cpu,
+};
+
+static ONCE: Once = Once::new();
+
+/// Offset of the path string.
+const PATH: usize = 0;
+
+/// Offset of the status.
+const STAT: usize = PAGE_SIZE / 2;
+
+/// Returns true if the file type of `stat` is `mode`.
+fn is_type(stat: &Stat, mode: StatMode) -> bool {
+    stat.st_mode & StatMode::S_IFMT.bits() == mode.bits()
+}
+
+/// Stats a regular file and a directory by paths and descriptors, which agree on the
+/// device and inode numbers.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let _ = mkdir(Path::new("/fstat/"));
+        let a = Path::new("/fstat/a");
+        let b = Path::new("/fstat/b");
+
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            let statbuf = (start + STAT).value();
+            let read_stat = || -> Result<Stat, Errno> {
+                let mut stat = Stat::default();
+                read_user!(curr.mm(), start + STAT, stat, Stat)?;
+                Ok(stat)
+            };
+            let fstatat = |dirfd: usize, path: &str, flags: usize| -> Result<Stat, Errno> {
+                let mut data = Vec::from(path.as_bytes());
+                data.push(0);
+                curr.mm().copy_to_user(start + PATH, &data)?;
+                let pathname = (start + PATH).value() as *const u8;
+                SyscallImpl::fstatat(dirfd, pathname, statbuf, flags)?;
+                read_stat()
+            };
+            let fstat = |fd: usize| -> Result<Stat, Errno> {
+                SyscallImpl::fstat(fd, statbuf)?;
+                read_stat()
+            };
+
+            let file = open(a.clone(), OpenFlags::O_CREAT | OpenFlags::O_RDWR)?;
+            assert_eq!(file.write(b"fstat"), Some(5));
+            open(b.clone(), OpenFlags::O_CREAT | OpenFlags::O_RDWR)?;
+
+            let stat = fstatat(AT_FDCWD, "/fstat/a", 0)?;
+            assert!(is_type(&stat, StatMode::S_IFREG));
+            assert_eq!(stat.st_size, 5);
+            let again = fstatat(AT_FDCWD, "/fstat/a", 0)?;
+            assert_eq!((again.st_dev, again.st_ino), (stat.st_dev, stat.st_ino));
+            let fd = curr.files().push(file)?;
+            let by_fd = fstat(fd)?;
+            assert!(is_type(&by_fd, StatMode::S_IFREG));
+            assert_eq!((by_fd.st_dev, by_fd.st_ino), (stat.st_dev, stat.st_ino));
+            let empty = fstatat(fd, "", AT_EMPTY_PATH)?;
+            assert_eq!(empty.st_ino, stat.st_ino);
+            let other = fstatat(AT_FDCWD, "/fstat/b", 0)?;
+            assert_eq!(other.st_dev, stat.st_dev);
+            assert_ne!(other.st_ino, stat.st_ino);
+
+            // Directories are found with or without a trailing slash.
+            let dir = fstatat(AT_FDCWD, "/fstat", 0)?;
+            assert!(is_type(&dir, StatMode::S_IFDIR));
+            assert_eq!(fstatat(AT_FDCWD, "/fstat/", 0)?.st_ino, dir.st_ino);
+            assert_ne!(dir.st_ino, stat.st_ino);
+
+            assert_eq!(fstatat(AT_FDCWD, "/fstat/c", 0).err(), Some(Errno::ENOENT));
+            assert_eq!(
+                fstatat(AT_FDCWD, "/fstat/a", usize::MAX).err(),
+                Some(Errno::EINVAL)
+            );
+            SyscallImpl::close(fd)?;
+            assert_eq!(fstat(fd).err(), Some(Errno::EBADF));
+            debug!("FSTAT {:?} inode {}", a, stat.st_ino);
+            Ok(())
+        };
+        let result = with_scratch(
+            PAGE_SIZE,
+            VMFlags::USER | VMFlags::READ | VMFlags::WRITE,
+            test,
+        );
+        let _ = unlink(a.clone());
+        let _ = unlink(b.clone());
+        result.unwrap();
+    });
+}
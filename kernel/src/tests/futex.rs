@@ -0,0 +1,93 @@
+use core::mem::size_of;
+use errno::Errno;
+use log::debug;
+use syscall_interface::{SyscallComm, FUTEX_PRIVATE_FLAG, FUTEX_WAIT, FUTEX_WAKE};
+use time_subsys::TimeSpec;
+
+use super::{run_user_code, spawn_threads, RECORD};
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    read_user,
+    syscall::SyscallImpl,
+    task::{cpu, do_yield, find_task, TaskState},
+    write_user,
+};
+
+/// Offset of the futex word followed by the result of the waiter, pointed to by `tp`
+/// of the thread.
+const WORD: usize = RECORD;
+
+/// Offset of the timeout.
+const TIMEOUT: usize = WORD + 2 * size_of::<usize>();
+
+/// Thread body in user space, waiting on the futex word while it is 1 and storing the
+/// result of `futex` after the word.
+#[naked]
+unsafe extern "C" fn thread_body() {
+    core::arch::asm!(
+        // SyscallNO::FUTEX
+        "li a7, 98",
+        "mv a0, tp",
+        "li a1, {op}",
+        "li a2, 1",
+        "li a3, 0",
+        "ecall",
+        "sd a0, 8(tp)",
+        // SyscallNO::EXIT
+        "li a7, 93",
+        "li a0, 0",
+        "ecall",
+        op = const FUTEX_WAIT,
+        options(noreturn),
+    );
+}
+
+/// Checks `FUTEX_WAIT` with a mismatched value and with a timeout, and then wakes up a
+/// thread waiting on the same word without `FUTEX_PRIVATE_FLAG`, which shares the key
+/// in a private mapping.
+pub fn test() {
+    let curr = cpu().curr.as_ref().unwrap();
+    let test = |start: VirtAddr| -> Result<(), Errno> {
+        let uaddr = (start + WORD).value();
+        let futex = |op: usize, val: usize, timeout: usize| {
+            SyscallImpl::futex(uaddr, op | FUTEX_PRIVATE_FLAG, val, timeout, 0, 0)
+        };
+        write_user!(curr.mm(), start + WORD, [1usize, usize::MAX], [usize; 2])?;
+
+        // The word does not match.
+        assert_eq!(futex(FUTEX_WAIT, 0, 0), Err(Errno::EAGAIN));
+
+        // Nobody wakes up the waiter.
+        let timeout = TimeSpec {
+            tv_sec: 0,
+            tv_nsec: 10_000_000,
+        };
+        write_user!(curr.mm(), start + TIMEOUT, timeout, TimeSpec)?;
+        assert_eq!(
+            futex(FUTEX_WAIT, 1, (start + TIMEOUT).value()),
+            Err(Errno::ETIMEDOUT)
+        );
+        assert_eq!(futex(FUTEX_WAKE, 1, 0), Ok(0));
+
+        // The thread starts from the body with its stack in the second page.
+        let stack = start.value() + 2 * PAGE_SIZE;
+        let tid = spawn_threads(start, start + WORD, &[stack])?[0];
+        let state = || find_task(tid).map_or(TaskState::ZOMBIE, |thread| thread.get_state());
+        while state() != TaskState::INTERRUPTIBLE {
+            assert_ne!(state(), TaskState::ZOMBIE);
+            unsafe { do_yield() };
+        }
+
+        write_user!(curr.mm(), start + WORD, 0u32, u32)?;
+        assert_eq!(futex(FUTEX_WAKE, usize::MAX, 0), Ok(1));
+        while state() != TaskState::ZOMBIE {
+            unsafe { do_yield() };
+        }
+        let mut result = usize::MAX;
+        read_user!(curr.mm(), start + WORD + size_of::<usize>(), result, usize)?;
+        assert_eq!(result, 0);
+        debug!("FUTEX thread {} woken up", tid);
+        Ok(())
+    };
+    run_user_code(&[thread_body], 2 * PAGE_SIZE, test).unwrap();
+}
@@ -0,0 +1,39 @@
+use log::debug;
+use spin::Once;
+use syscall_interface::{SyscallIO, GRND_NONBLOCK};
+
+use super::with_scratch;
+use crate::{arch::mm::PAGE_SIZE, mm::VMFlags, syscall::SyscallImpl, task::cpu};
+
+/// Length of the buffer, which crosses a page boundary.
+const LEN: usize = 100;
+
+static ONCE: Once = Once::new();
+
+/// Fills a user buffer across two pages with random bytes twice.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        with_scratch(2 * PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, |start| {
+            let buf = start + (PAGE_SIZE - LEN / 2);
+
+            let getrandom = |flags: usize| {
+                let zeros = [0u8; LEN];
+                curr.mm().copy_to_user(buf, &zeros).unwrap();
+                let len = SyscallImpl::getrandom(buf.value() as *mut u8, LEN, flags).unwrap();
+                assert_eq!(len, LEN);
+                curr.mm().copy_from_user(buf, LEN).unwrap()
+            };
+            let first = getrandom(0);
+            let second = getrandom(GRND_NONBLOCK);
+            assert_ne!(first, second);
+            // Both ends of the buffer are written, which are all zeros with little chance.
+            for data in [&first, &second] {
+                assert!(data[..8].iter().any(|&b| b != 0));
+                assert!(data[LEN - 8..].iter().any(|&b| b != 0));
+            }
+            assert!(SyscallImpl::getrandom(buf.value() as *mut u8, LEN, 0x80).is_err());
+            debug!("GETRANDOM {:x?}", &first[..8]);
+        });
+    });
+}
@@ -0,0 +1,51 @@
+use alloc::string::String;
+use log::debug;
+use spin::Once;
+
+use super::elf::{Elf, InitStack};
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    error::KernelResult,
+    loader::{flags::AuxType, from_elf},
+    mm::MM,
+};
+
+static ONCE: Once = Once::new();
+
+/// Passes the arguments and the environment variables to the initial stack of a new
+/// program in order, including an empty environment, with `AT_EXECFN` pointing to the
+/// program name and `AT_RANDOM` to 16 random bytes.
+pub fn test() {
+    ONCE.call_once(|| {
+        let test = || -> KernelResult {
+            let elf = Elf::default().build();
+            let load = |args: &[&str], envs: &[&str]| -> KernelResult<(MM, InitStack)> {
+                let strings = |strs: &[&str]| strs.iter().map(|&s| String::from(s)).collect();
+                let mut mm = MM::new()?;
+                let sp = from_elf(&elf, strings(args), strings(envs), PAGE_SIZE, &mut mm)?;
+                let stack = InitStack::parse(&mut mm, sp)?;
+                Ok((mm, stack))
+            };
+
+            let args = ["init_stack", "-v"];
+            let envs = ["PATH=/bin", "HOME=/", "EMPTY="];
+            let (mut mm, stack) = load(&args, &envs)?;
+            assert_eq!(stack.args, args);
+            assert_eq!(stack.envs, envs);
+
+            // Points to the first argument and random bytes on the stack.
+            let execfn = stack.auxv[&AuxType::AT_EXECFN];
+            assert_eq!(mm.get_str(VirtAddr::from(execfn))?, args[0]);
+            let random = mm.copy_from_user(VirtAddr::from(stack.auxv[&AuxType::AT_RANDOM]), 16)?;
+            assert!(random.iter().any(|&b| b != 0));
+            assert_eq!(stack.auxv[&AuxType::AT_PAGESZ], PAGE_SIZE);
+
+            let (_, stack) = load(&args[..1], &[])?;
+            assert_eq!(stack.args, args[..1]);
+            assert!(stack.envs.is_empty());
+            debug!("INIT STACK envs {:?}", envs);
+            Ok(())
+        };
+        test().unwrap();
+    });
+}
@@ -0,0 +1,81 @@
+use alloc::{string::String, vec, vec::Vec};
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use vfs::{OpenFlags, Path};
+
+use super::elf::{Elf, InitStack, CODE, EXIT_CODE};
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    config::{ELF_BASE_RELOCATE, INTERP_BASE_RELOCATE},
+    error::{KernelError, KernelResult},
+    fs::{open, unlink},
+    loader::{flags::AuxType, from_elf},
+    mm::MM,
+};
+
+/// Path of the program interpreter.
+const INTERP: &str = "/tmp/interp.so";
+
+/// Address of the program which is not position-independent.
+const BASE: usize = 0x1_0000;
+
+static ONCE: Once = Once::new();
+
+/// Loads a program whose `PT_INTERP` names an interpreter, which is entered first and
+/// placed away from the program, while a missing interpreter fails the load with
+/// `ENOENT`.
+pub fn test() {
+    ONCE.call_once(|| {
+        let path = Path::new(INTERP);
+        let flags = OpenFlags::O_CREAT | OpenFlags::O_TRUNC | OpenFlags::O_WRONLY;
+        let file = open(path.clone(), flags).unwrap();
+        let interp = Elf {
+            code: &EXIT_CODE,
+            ..Elf::default()
+        }
+        .build();
+        assert_eq!(file.write(&interp), Some(interp.len()));
+        drop(file);
+
+        let test = || -> KernelResult {
+            let load = |base: usize, interp: &str| -> KernelResult<(MM, InitStack)> {
+                let elf = Elf {
+                    base,
+                    interp: Some(interp),
+                    ..Elf::default()
+                }
+                .build();
+                let mut mm = MM::new()?;
+                let args = vec![String::from("interp")];
+                let sp = from_elf(&elf, args, Vec::new(), PAGE_SIZE, &mut mm)?;
+                let stack = InitStack::parse(&mut mm, sp)?;
+                Ok((mm, stack))
+            };
+
+            // The interpreter of a position-independent program is placed higher.
+            let (mut mm, stack) = load(0, INTERP)?;
+            assert_eq!(mm.entry, VirtAddr::from(INTERP_BASE_RELOCATE + CODE));
+            assert_eq!(stack.auxv[&AuxType::AT_BASE], INTERP_BASE_RELOCATE);
+            assert_eq!(stack.auxv[&AuxType::AT_ENTRY], ELF_BASE_RELOCATE + CODE);
+            let code = mm.copy_from_user(mm.entry, 4 * EXIT_CODE.len())?;
+            assert_eq!(code, interp[CODE..CODE + code.len()]);
+
+            // The interpreter takes the place of a program at a fixed address.
+            let (mm, stack) = load(BASE, INTERP)?;
+            assert_eq!(mm.entry, VirtAddr::from(ELF_BASE_RELOCATE + CODE));
+            assert_eq!(stack.auxv[&AuxType::AT_BASE], ELF_BASE_RELOCATE);
+            assert_eq!(stack.auxv[&AuxType::AT_ENTRY], BASE + CODE);
+
+            assert_eq!(
+                load(0, "/tmp/missing.so").map(|_| ()),
+                Err(KernelError::Errno(Errno::ENOENT))
+            );
+            debug!("INTERP entered at {:#x}", INTERP_BASE_RELOCATE + CODE);
+            Ok(())
+        };
+        let result = test();
+        unlink(path).unwrap();
+        result.unwrap();
+    });
+}
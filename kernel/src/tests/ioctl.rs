@@ -0,0 +1,88 @@
+use alloc::sync::Arc;
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{
+    SyscallIO, Termios, WinSize, TCGETS, TCSETS, TIOCGPGRP, TIOCGWINSZ, TIOCSPGRP, TIOCSWINSZ,
+};
+use vfs::{File, OpenFlags, Path};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::{console_termios, open, unlink, Stdin},
+    mm::VMFlags,
+    read_user,
+    syscall::SyscallImpl,
+    task::cpu,
+    write_user,
+};
+
+static ONCE: Once = Once::new();
+
+/// Gets the window size, terminal settings and foreground process group of standard
+/// input through a user buffer, and sends an unknown request to a regular file, both
+/// directly and through the descriptors.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+
+        let test = |buf: VirtAddr| -> Result<(), Errno> {
+            Stdin.ioctl(TIOCGWINSZ, buf.value())?;
+            let mut winsize = WinSize::default();
+            read_user!(curr.mm(), buf, winsize, WinSize)?;
+            assert!(winsize.ws_row > 0 && winsize.ws_col > 0);
+
+            // Settings are written back as they are read.
+            Stdin.ioctl(TCGETS, buf.value())?;
+            let mut termios = Termios::default();
+            read_user!(curr.mm(), buf, termios, Termios)?;
+            assert_eq!(termios.c_cc, console_termios().c_cc);
+            let old = termios;
+            termios.c_lflag = 0;
+            write_user!(curr.mm(), buf, termios, Termios)?;
+            Stdin.ioctl(TCSETS, buf.value())?;
+            assert_eq!(console_termios().c_lflag, 0);
+            write_user!(curr.mm(), buf, old, Termios)?;
+            Stdin.ioctl(TCSETS, buf.value())?;
+
+            // The caller is in the foreground, which cannot be changed.
+            Stdin.ioctl(TIOCGPGRP, buf.value())?;
+            let mut pgid = -1i32;
+            read_user!(curr.mm(), buf, pgid, i32)?;
+            assert_eq!(pgid as usize, curr.thread_group.pgid());
+            assert_eq!(Stdin.ioctl(TIOCSPGRP, buf.value()), Err(Errno::EINVAL));
+            assert_eq!(Stdin.ioctl(TIOCSWINSZ, buf.value()), Err(Errno::ENOTTY));
+
+            // The request is dispatched to the file of the descriptor.
+            let fd = curr.files().push(Arc::new(Stdin))?;
+            write_user!(curr.mm(), buf, WinSize::default(), WinSize)?;
+            let argp = buf.value() as *const usize;
+            assert_eq!(SyscallImpl::ioctl(fd, TIOCGWINSZ, argp), Ok(0));
+            let mut dispatched = WinSize::default();
+            read_user!(curr.mm(), buf, dispatched, WinSize)?;
+            assert_eq!(
+                (dispatched.ws_row, dispatched.ws_col),
+                (winsize.ws_row, winsize.ws_col)
+            );
+            curr.files().remove(fd)?;
+            assert_eq!(SyscallImpl::ioctl(fd, TIOCGWINSZ, argp), Err(Errno::EBADF));
+            debug!("IOCTL {:?} {:?}", winsize, console_termios());
+            Ok(())
+        };
+        with_scratch(PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test).unwrap();
+
+        // Regular files are not terminals.
+        let path = Path::new("/ioctl");
+        let file = open(path.clone(), OpenFlags::O_CREAT | OpenFlags::O_RDWR).unwrap();
+        assert_eq!(file.ioctl(TCGETS, 0).err(), Some(Errno::ENOTTY));
+        assert_eq!(file.ioctl(usize::MAX, 0).err(), Some(Errno::ENOTTY));
+        let fd = curr.files().push(file).unwrap();
+        assert_eq!(
+            SyscallImpl::ioctl(fd, TIOCGWINSZ, core::ptr::null()),
+            Err(Errno::ENOTTY)
+        );
+        curr.files().remove(fd).unwrap();
+        unlink(path).unwrap();
+    });
+}
@@ -0,0 +1,68 @@
+use alloc::sync::Arc;
+use core::mem::size_of;
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{IoVec, SyscallFile};
+use vfs::File;
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::Pipe,
+    mm::VMFlags,
+    syscall::SyscallImpl,
+    task::cpu,
+    write_user,
+};
+
+static ONCE: Once = Once::new();
+
+/// Reads a pipe into a vector of buffers which is partially filled, and checks that
+/// a vector whose lengths overflow is rejected.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            let iov_addr = start;
+            let buf_addr = start + PAGE_SIZE / 2;
+
+            let (pipe_read, pipe_write) = Pipe::new();
+            let pipe_write = Arc::new(pipe_write);
+            let fd_read = curr.files().push(Arc::new(pipe_read))?;
+            let fd_write = curr.files().push(pipe_write.clone())?;
+            let write_iovs = |iovs: &[(usize, usize)]| -> Result<(), Errno> {
+                for (i, &(off, len)) in iovs.iter().enumerate() {
+                    let iov = IoVec {
+                        iov_base: buf_addr.value() + off,
+                        iov_len: len,
+                    };
+                    write_user!(curr.mm(), iov_addr + i * size_of::<IoVec>(), iov, IoVec)?;
+                }
+                Ok(())
+            };
+            let iov = iov_addr.value() as *const IoVec;
+
+            // The empty buffer is skipped, and the read stops at the second buffer
+            // without blocking for the last one.
+            assert_eq!(pipe_write.write(b"iovec!"), Some(6));
+            write_iovs(&[(0, 4), (4, 0), (4, 8), (12, 4)])?;
+            assert_eq!(SyscallImpl::readv(fd_read, iov, 4), Ok(6));
+            let data = curr.mm().copy_from_user(buf_addr, 6)?;
+            assert_eq!(&data[..], b"iovec!");
+
+            // Lengths overflow an ssize_t.
+            write_iovs(&[(0, isize::MAX as usize), (0, 1)])?;
+            assert_eq!(SyscallImpl::readv(fd_read, iov, 2), Err(Errno::EINVAL));
+            assert_eq!(SyscallImpl::writev(fd_write, iov, 2), Err(Errno::EINVAL));
+            write_iovs(&[(0, usize::MAX), (0, 2)])?;
+            assert_eq!(SyscallImpl::readv(fd_read, iov, 2), Err(Errno::EINVAL));
+            debug!("IOVEC {:?}", core::str::from_utf8(&data));
+
+            curr.files().remove(fd_read)?;
+            curr.files().remove(fd_write)?;
+            Ok(())
+        };
+        with_scratch(PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test).unwrap();
+    });
+}
@@ -0,0 +1,103 @@
+use core::mem::size_of;
+use errno::Errno;
+use log::debug;
+use signal_defs::{SigAction, SIGALRM, SIG_IGN};
+use syscall_interface::SyscallTimer;
+use time_subsys::{ITimer, ITimerType, ITimerVal, TimeSpec, TimeVal};
+
+use super::run_user_code;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    read_user,
+    syscall::SyscallImpl,
+    task::{cpu, do_signal},
+    timer::set_real_timer,
+    write_user,
+};
+
+/// Address of the signal handler, which is never called.
+const HANDLER: usize = 0x1000;
+
+/// Offset of the new value of the timer.
+const NEW: usize = 0;
+
+/// Offset of the old value of the timer.
+const OLD: usize = NEW + size_of::<ITimerVal>();
+
+/// Offset of the requested time to sleep.
+const REQ: usize = OLD + size_of::<ITimerVal>();
+
+/// Sleeps until `SIGALRM` raised by a single-shot and then a periodic timer interrupts
+/// the sleep, since the timer expires even if the process is not running.
+pub fn test() {
+    let curr = cpu().curr.as_ref().unwrap();
+    let test = |start: VirtAddr| -> Result<(), Errno> {
+        let which = ITimerType::REAL as usize;
+        let setitimer = |interval: usize, value: usize| -> Result<ITimerVal, Errno> {
+            let new = ITimerVal {
+                it_interval: TimeVal {
+                    tv_sec: 0,
+                    tv_usec: interval,
+                },
+                it_value: TimeVal {
+                    tv_sec: 0,
+                    tv_usec: value,
+                },
+            };
+            write_user!(curr.mm(), start + NEW, new, ITimerVal)?;
+            SyscallImpl::setitimer(which, (start + NEW).value(), (start + OLD).value())?;
+            let mut old = ITimerVal::default();
+            read_user!(curr.mm(), start + OLD, old, ITimerVal)?;
+            Ok(old)
+        };
+        let getitimer = || -> Result<ITimerVal, Errno> {
+            SyscallImpl::getitimer(which, (start + OLD).value())?;
+            let mut value = ITimerVal::default();
+            read_user!(curr.mm(), start + OLD, value, ITimerVal)?;
+            Ok(value)
+        };
+        let sleep_interrupted = || -> Result<(), Errno> {
+            assert_eq!(
+                SyscallImpl::nanosleep((start + REQ).value(), 0),
+                Err(Errno::EINTR)
+            );
+            assert!(curr.locked_inner().sig_pending.mask.get(SIGALRM - 1));
+            // Discards the signal.
+            curr.sig_actions.lock()[SIGALRM - 1].handler = SIG_IGN;
+            do_signal(false)?;
+            curr.sig_actions.lock()[SIGALRM - 1].handler = HANDLER;
+            Ok(())
+        };
+
+        curr.sig_actions.lock()[SIGALRM - 1] = SigAction {
+            handler: HANDLER,
+            ..SigAction::default()
+        };
+        curr.locked_inner().sig_blocked.unset(SIGALRM - 1);
+        let req = TimeSpec {
+            tv_sec: 10,
+            tv_nsec: 0,
+        };
+        write_user!(curr.mm(), start + REQ, req, TimeSpec)?;
+
+        // A single-shot timer is disarmed once expired.
+        setitimer(0, 20_000)?;
+        sleep_interrupted()?;
+        assert_eq!(getitimer()?.it_value, TimeVal::default());
+
+        // A periodic timer is rearmed from the interval each time.
+        setitimer(10_000, 10_000)?;
+        sleep_interrupted()?;
+        sleep_interrupted()?;
+        let old = setitimer(0, 0)?;
+        assert_eq!(old.it_interval.tv_usec, 10_000);
+        assert!(old.it_value != TimeVal::default() && old.it_value.tv_usec <= 10_000);
+        assert_eq!(getitimer()?.it_value, TimeVal::default());
+        debug!("ITIMER_REAL expired while sleeping");
+        Ok(())
+    };
+    let result = run_user_code(&[], PAGE_SIZE, test);
+    // Disarms the timer in case of failure.
+    set_real_timer(curr.pid, &mut curr.real_timer.lock(), ITimer::new());
+    result.unwrap();
+}
@@ -0,0 +1,52 @@
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use vfs::{File, OpenFlags, Path, Stat};
+
+use crate::fs::{link, open, unlink};
+
+static ONCE: Once = Once::new();
+
+fn nlink(file: &dyn File) -> u32 {
+    let mut stat = Stat::default();
+    assert!(file.get_stat(&mut stat as *mut Stat));
+    stat.st_nlink
+}
+
+/// Links a file on disk, which is counted in `st_nlink` until the link is removed.
+pub fn test() {
+    ONCE.call_once(|| {
+        let path = Path::new("/link_file");
+        let new = Path::new("/link_new");
+        let file = open(
+            path.clone(),
+            OpenFlags::O_CREAT | OpenFlags::O_TRUNC | OpenFlags::O_WRONLY,
+        )
+        .unwrap();
+        assert_eq!(file.write(b"hello, link"), Some(11));
+        drop(file);
+
+        link(path.clone(), new.clone()).unwrap();
+        assert_eq!(link(path.clone(), new.clone()).err(), Some(Errno::EEXIST));
+        assert_eq!(
+            link(Path::new("/"), Path::new("/link_root")).err(),
+            Some(Errno::EPERM)
+        );
+
+        let file = open(new.clone(), OpenFlags::O_RDONLY).unwrap();
+        assert_eq!(nlink(file.as_ref()), 2);
+        assert_eq!(file.get_nlink(), Some(2));
+        let mut buf = [0u8; 16];
+        assert_eq!(file.read(&mut buf), Some(11));
+        assert_eq!(&buf[..11], b"hello, link");
+        drop(file);
+        debug!("LINK {:?} to {:?}", new, path);
+
+        unlink(path.clone()).unwrap();
+        let file = open(new.clone(), OpenFlags::O_RDONLY).unwrap();
+        assert_eq!(nlink(file.as_ref()), 1);
+        drop(file);
+        unlink(new.clone()).unwrap();
+        assert!(open(new, OpenFlags::O_RDONLY).is_err());
+    });
+}
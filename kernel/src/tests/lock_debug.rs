@@ -0,0 +1,36 @@
+use log::debug;
+use spin::Once;
+
+use crate::{arch::get_cpu_id, cons::expect_panic, sync::SpinLock};
+
+static ONCE: Once = Once::new();
+
+/// Acquires a lock and checks that acquiring it again on the same hart is detected as
+/// a self-deadlock with the location where it is held, instead of spinning forever.
+///
+/// The second acquisition panics as expected, which shuts down the kernel, thus this
+/// test runs after all others.
+pub fn test() {
+    ONCE.call_once(|| {
+        let lock = SpinLock::new(0usize);
+        assert!(lock.holder().is_none());
+
+        let (mut guard, line) = (lock.lock(), line!());
+        *guard += 1;
+        let location = lock.self_deadlock().unwrap();
+        assert_eq!(location.file(), file!());
+        assert_eq!(location.line(), line);
+        assert_eq!(lock.holder().unwrap().0, get_cpu_id());
+        assert!(lock.try_lock().is_none());
+        drop(guard);
+
+        assert!(lock.self_deadlock().is_none());
+        let guard = lock.lock();
+        assert_eq!(*guard, 1);
+        debug!("LOCK_DEBUG self-deadlock detected at {}", location);
+
+        expect_panic("acquires the lock at");
+        let _deadlock = lock.lock();
+        panic!("Hart {} holds the lock twice", get_cpu_id());
+    });
+}
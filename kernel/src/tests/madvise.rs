@@ -0,0 +1,63 @@
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{SyscallProc, MADV_DONTNEED, MADV_NORMAL, MADV_WILLNEED};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    mm::VMFlags,
+    syscall::SyscallImpl,
+    task::cpu,
+};
+
+static ONCE: Once = Once::new();
+
+/// Drops a written page with `MADV_DONTNEED`, which reads back as zeros, and faults
+/// it in again with `MADV_WILLNEED`.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            let data = [0x5au8; PAGE_SIZE];
+            curr.mm().copy_to_user(start, &data)?;
+            curr.mm().copy_to_user(start + PAGE_SIZE, &data)?;
+            SyscallImpl::madvise(start.value(), PAGE_SIZE, MADV_NORMAL)?;
+            assert_eq!(curr.mm().copy_from_user(start, PAGE_SIZE)?, data);
+
+            // Only the first page is dropped.
+            SyscallImpl::madvise(start.value(), PAGE_SIZE, MADV_DONTNEED)?;
+            assert!(curr.mm().translate(start).is_err());
+            assert!(curr
+                .mm()
+                .copy_from_user(start, PAGE_SIZE)?
+                .iter()
+                .all(|&b| b == 0));
+            assert_eq!(
+                curr.mm().copy_from_user(start + PAGE_SIZE, PAGE_SIZE)?,
+                data
+            );
+
+            SyscallImpl::madvise(start.value(), 2 * PAGE_SIZE, MADV_DONTNEED)?;
+            SyscallImpl::madvise(start.value(), 2 * PAGE_SIZE, MADV_WILLNEED)?;
+            assert!(curr.mm().translate(start + PAGE_SIZE).is_ok());
+
+            assert_eq!(
+                SyscallImpl::madvise(start.value() + 1, PAGE_SIZE, MADV_DONTNEED),
+                Err(Errno::EINVAL)
+            );
+            assert_eq!(
+                SyscallImpl::madvise(start.value(), PAGE_SIZE, 0x100),
+                Err(Errno::EINVAL)
+            );
+            assert_eq!(
+                SyscallImpl::madvise(start.value(), 3 * PAGE_SIZE, MADV_DONTNEED),
+                Err(Errno::ENOMEM)
+            );
+            debug!("MADVISE {:?}", start);
+            Ok(())
+        };
+        with_scratch(2 * PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test).unwrap();
+    });
+}
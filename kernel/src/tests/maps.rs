@@ -0,0 +1,57 @@
+use alloc::{format, string::String};
+use log::debug;
+use spin::Once;
+use syscall_interface::SyscallProc;
+use vfs::{OpenFlags, Path};
+
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::open,
+    syscall::SyscallImpl,
+    task::cpu,
+};
+
+/// Length of each read, smaller than a line.
+const CHUNK: usize = 16;
+
+static ONCE: Once = Once::new();
+
+/// Reads `/proc/self/maps` in small chunks, where the heap and the stack are found.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let (start_brk, brk) = (curr.mm().start_brk, curr.mm().brk);
+        SyscallImpl::brk((brk + PAGE_SIZE).value()).unwrap();
+        let range = |va: VirtAddr| {
+            let (start, end) = curr
+                .mm()
+                .get_vma(va, |vma, _, _| Ok((vma.start_va, vma.end_va)))
+                .unwrap();
+            format!("{:08x}-{:08x}", start.value(), end.value())
+        };
+        let heap = range(start_brk);
+        let stack = range(VirtAddr::from(curr.trapframe().get_sp()));
+
+        let file = open(Path::new("/proc/self/maps"), OpenFlags::O_RDONLY).unwrap();
+        let mut maps = String::new();
+        let mut buf = [0u8; CHUNK];
+        loop {
+            let len = file.read(&mut buf).unwrap();
+            if len == 0 {
+                break;
+            }
+            maps.push_str(core::str::from_utf8(&buf[..len]).unwrap());
+        }
+        assert_eq!(file.write(b"\n"), None);
+        debug!("MAPS\n{}", maps);
+
+        assert!(maps
+            .lines()
+            .any(|line| line == format!("{} rw-p 00000000 00:00 0 [heap]", heap)));
+        assert!(maps
+            .lines()
+            .any(|line| line == format!("{} rw-p 00000000 00:00 0 [stack]", stack)));
+
+        SyscallImpl::brk(brk.value()).unwrap();
+    });
+}
@@ -0,0 +1,75 @@
+use errno::Errno;
+use log::debug;
+use syscall_interface::{
+    SyscallProc, MEMBARRIER_CMD_GLOBAL, MEMBARRIER_CMD_PRIVATE_EXPEDITED, MEMBARRIER_CMD_QUERY,
+};
+
+use super::{run_user_code, spawn_threads, wait_exited};
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    mm::MmapProt,
+    read_user,
+    syscall::SyscallImpl,
+    task::{cpu, do_yield},
+};
+
+/// Thread body in user space, counting in the page pointed to by `tp` until the page
+/// becomes read-only and the store faults.
+#[naked]
+unsafe extern "C" fn thread_body() {
+    core::arch::asm!(
+        "1:",
+        "ld t0, 0(tp)",
+        "addi t0, t0, 1",
+        "sd t0, 0(tp)",
+        "j 1b",
+        options(noreturn),
+    );
+}
+
+/// Spawns a thread writing a shared page on another hart, which is made read-only by
+/// `mprotect` later, then checks that the thread stops writing the page and is killed
+/// by the page fault. Memory barriers are issued by `membarrier` at last.
+///
+/// This test requires a multi-hart build.
+pub fn test() {
+    let curr = cpu().curr.as_ref().unwrap();
+    let test = |start: VirtAddr| -> Result<(), Errno> {
+        let data = start + PAGE_SIZE;
+
+        let tid = spawn_threads(start, data, &[0])?[0];
+
+        let mut count = 0usize;
+        while count == 0 {
+            unsafe { do_yield() };
+            read_user!(curr.mm(), data, count, usize)?;
+        }
+        assert_eq!(
+            SyscallImpl::mprotect(data.value(), PAGE_SIZE, MmapProt::PROT_READ.bits()),
+            Ok(0)
+        );
+
+        wait_exited(tid);
+        read_user!(curr.mm(), data, count, usize)?;
+        unsafe { do_yield() };
+        let mut last = 0usize;
+        read_user!(curr.mm(), data, last, usize)?;
+        assert_eq!(last, count);
+
+        let supported = SyscallImpl::membarrier(MEMBARRIER_CMD_QUERY, 0, 0)?;
+        assert_ne!(supported & MEMBARRIER_CMD_GLOBAL, 0);
+        assert_eq!(SyscallImpl::membarrier(MEMBARRIER_CMD_GLOBAL, 0, 0), Ok(0));
+        assert_eq!(
+            SyscallImpl::membarrier(MEMBARRIER_CMD_PRIVATE_EXPEDITED, 0, 0),
+            Ok(0)
+        );
+        assert_eq!(
+            SyscallImpl::membarrier(MEMBARRIER_CMD_GLOBAL, 1, 0),
+            Err(Errno::EINVAL)
+        );
+        assert_eq!(SyscallImpl::membarrier(1 << 10, 0, 0), Err(Errno::EINVAL));
+        debug!("MEMBARRIER thread {} stopped at {}", tid, count);
+        Ok(())
+    };
+    run_user_code(&[thread_body], 2 * PAGE_SIZE, test).unwrap();
+}
@@ -0,0 +1,84 @@
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{SyscallFile, AT_FDCWD};
+use vfs::{OpenFlags, Path, Stat, StatMode};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::{mkdir, mkdir_in, open, resolve_path, unlink, ResolveFlags},
+    mm::VMFlags,
+    read_user,
+    syscall::SyscallImpl,
+    task::cpu,
+};
+
+static ONCE: Once = Once::new();
+
+/// Offset of the path string.
+const PATH: usize = 0;
+
+/// Offset of the status.
+const STAT: usize = PAGE_SIZE / 2;
+
+/// Makes directories relative to an opened directory and the working directory, the
+/// same as `mkdirat` does, and keeps the permission bits of a new directory in memory.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let _ = mkdir(Path::new("/mkdirat/"));
+        let mkdirat = |dirfd: usize, pathname: &str| {
+            let (fs, path) = resolve_path(&curr, dirfd, pathname, ResolveFlags::NOFOLLOW)?;
+            mkdir_in(&fs, path, StatMode::S_IRWXUGO)
+        };
+
+        // Relative to a directory file descriptor.
+        let dir = open(Path::new("/mkdirat/"), OpenFlags::O_RDONLY).unwrap();
+        let dirfd = curr.files().push(dir).unwrap();
+        mkdirat(dirfd, "fd/").unwrap();
+        assert!(open(Path::new("/mkdirat/fd/"), OpenFlags::O_RDONLY).is_ok());
+        assert_eq!(mkdirat(dirfd, "fd/").err(), Some(Errno::EEXIST));
+
+        // Relative to the working directory.
+        let old_cwd = core::mem::replace(&mut curr.fs_info.lock().cwd, Path::new("/mkdirat/"));
+        mkdirat(AT_FDCWD, "cwd/").unwrap();
+        curr.fs_info.lock().cwd = old_cwd;
+        assert!(open(Path::new("/mkdirat/cwd/"), OpenFlags::O_RDONLY).is_ok());
+
+        // The file descriptor must refer to a directory.
+        let file = Path::new("/mkdirat/file");
+        let filefd = curr
+            .files()
+            .push(open(file.clone(), OpenFlags::O_CREAT | OpenFlags::O_RDWR).unwrap())
+            .unwrap();
+        assert_eq!(mkdirat(filefd, "sub/").err(), Some(Errno::ENOTDIR));
+        debug!("MKDIRAT in {:?}", Path::new("/mkdirat/"));
+
+        curr.files().remove(dirfd).unwrap();
+        curr.files().remove(filefd).unwrap();
+        unlink(file).unwrap();
+        unlink(Path::new("/mkdirat/fd/")).unwrap();
+        unlink(Path::new("/mkdirat/cwd/")).unwrap();
+
+        // The mode is kept without the file type bits.
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            curr.mm().copy_to_user(start + PATH, b"/tmp/mkdirat\0")?;
+            let pathname = (start + PATH).value() as *const u8;
+            SyscallImpl::mkdirat(
+                AT_FDCWD,
+                pathname,
+                (StatMode::S_IFREG.bits() | 0o750) as usize,
+            )?;
+            SyscallImpl::fstatat(AT_FDCWD, pathname, (start + STAT).value(), 0)?;
+            let mut stat = Stat::default();
+            read_user!(curr.mm(), start + STAT, stat, Stat)?;
+            assert_eq!(stat.st_mode, StatMode::S_IFDIR.bits() | 0o750);
+            debug!("MKDIRAT mode {:o}", stat.st_mode);
+            Ok(())
+        };
+        let result = with_scratch(PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test);
+        let _ = unlink(Path::new("/tmp/mkdirat/"));
+        result.unwrap();
+    });
+}
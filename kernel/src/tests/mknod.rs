@@ -0,0 +1,59 @@
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use vfs::{File, OpenFlags, Path, StatMode};
+
+use crate::fs::{makedev, mknod, open, unlink, MEM_MAJOR};
+
+static ONCE: Once = Once::new();
+
+/// Creates a regular file under `/tmp` and a device node under `/dev`, while other
+/// file types or device nodes outside `/dev` are rejected.
+pub fn test() {
+    ONCE.call_once(|| {
+        let perm = StatMode::S_IRUSR | StatMode::S_IWUSR;
+        let null = makedev(MEM_MAJOR, 3);
+
+        // Regular files, with or without the file type.
+        let path = Path::new("/tmp/mknod");
+        mknod(path.clone(), StatMode::S_IFREG | perm, 0).unwrap();
+        assert_eq!(mknod(path.clone(), perm, 0).err(), Some(Errno::EEXIST));
+        let file = open(path.clone(), OpenFlags::O_RDONLY).unwrap();
+        assert!(file.is_reg());
+        unlink(path.clone()).unwrap();
+        mknod(path.clone(), perm, 0).unwrap();
+        unlink(path.clone()).unwrap();
+
+        // Device nodes live in `/dev` only.
+        for file_type in [StatMode::S_IFCHR, StatMode::S_IFBLK] {
+            assert_eq!(
+                mknod(path.clone(), file_type | perm, null).err(),
+                Some(Errno::EPERM)
+            );
+        }
+        let dev = Path::new("/dev/mknod-null");
+        mknod(dev.clone(), StatMode::S_IFCHR | perm, null).unwrap();
+        assert_eq!(
+            mknod(dev.clone(), StatMode::S_IFCHR | perm, null).err(),
+            Some(Errno::EEXIST)
+        );
+        let file = open(dev, OpenFlags::O_RDWR).unwrap();
+        assert_eq!(file.write(b"mknod"), Some(5));
+
+        // Unsupported or invalid file types.
+        for file_type in [StatMode::S_IFIFO, StatMode::S_IFSOCK] {
+            assert_eq!(
+                mknod(path.clone(), file_type | perm, 0).err(),
+                Some(Errno::EPERM)
+            );
+        }
+        for file_type in [StatMode::S_IFDIR, StatMode::S_IFLNK] {
+            assert_eq!(
+                mknod(path.clone(), file_type | perm, 0).err(),
+                Some(Errno::EINVAL)
+            );
+        }
+        assert_eq!(open(path, OpenFlags::O_RDONLY).err(), Some(Errno::ENOENT));
+        debug!("MKNOD regular files and device nodes");
+    });
+}
@@ -0,0 +1,64 @@
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{SyscallProc, MADV_DONTNEED};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    mm::VMFlags,
+    syscall::SyscallImpl,
+    task::cpu,
+};
+
+/// Number of pages in the locked range.
+const PAGES: usize = 3;
+
+static ONCE: Once = Once::new();
+
+/// Locks a range which is present at once and kept by `MADV_DONTNEED`, until it is
+/// unlocked.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let len = PAGES * PAGE_SIZE;
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            let present = |page: usize| curr.mm().translate(start + page * PAGE_SIZE).is_ok();
+
+            // Exceeds the limit.
+            let old_limit = curr.limits.lock().memlock;
+            curr.limits.lock().memlock.rlim_cur = (len - PAGE_SIZE) as u64;
+            assert_eq!(SyscallImpl::mlock(start.value(), len), Err(Errno::ENOMEM));
+            curr.limits.lock().memlock = old_limit;
+            assert!((0..PAGES).all(|page| !present(page)));
+
+            // All pages are faulted in, even if the start is unaligned.
+            SyscallImpl::mlock(start.value() + 1, len - 1)?;
+            assert!((0..PAGES).all(|page| present(page)));
+            assert_eq!(curr.mm().locked_pages(), PAGES);
+
+            let data = [0x5au8; PAGE_SIZE];
+            curr.mm().copy_to_user(start, &data)?;
+            SyscallImpl::madvise(start.value(), len, MADV_DONTNEED)?;
+            assert!((0..PAGES).all(|page| present(page)));
+            assert_eq!(curr.mm().copy_from_user(start, PAGE_SIZE)?, data);
+
+            // Only the last page is kept after unlocking the others.
+            SyscallImpl::munlock(start.value(), len - PAGE_SIZE)?;
+            assert_eq!(curr.mm().locked_pages(), 1);
+            SyscallImpl::madvise(start.value(), len, MADV_DONTNEED)?;
+            assert!((0..PAGES - 1).all(|page| !present(page)));
+            assert!(present(PAGES - 1));
+
+            assert_eq!(
+                SyscallImpl::mlock(start.value(), len + PAGE_SIZE),
+                Err(Errno::ENOMEM)
+            );
+            SyscallImpl::munlock(start.value(), len)?;
+            assert_eq!(curr.mm().locked_pages(), 0);
+            debug!("MLOCK {:?}", start);
+            Ok(())
+        };
+        with_scratch(len, VMFlags::READ | VMFlags::WRITE, test).unwrap();
+    });
+}
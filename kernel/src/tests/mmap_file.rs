@@ -0,0 +1,63 @@
+use alloc::vec::Vec;
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{SyscallFile, SyscallProc, MADV_DONTNEED};
+use vfs::{OpenFlags, Path};
+
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::{open, unlink},
+    mm::{MmapFlags, MmapProt},
+    syscall::SyscallImpl,
+    task::cpu,
+};
+
+/// Length of the file, the last page of which is partially backed.
+const LEN: usize = PAGE_SIZE + 100;
+
+static ONCE: Once = Once::new();
+
+/// Maps a file whose contents are faulted in, while the tail beyond the end of file
+/// is zeroed and never written back.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let path = Path::new("/tmp/mmap_file.txt");
+        let flags = OpenFlags::O_CREAT | OpenFlags::O_TRUNC | OpenFlags::O_RDWR;
+        let file = open(path.clone(), flags).unwrap();
+        let data: Vec<u8> = (0..LEN).map(|i| (i % 251) as u8).collect();
+        assert_eq!(file.write(&data), Some(LEN));
+        let fd = curr.files().push(file.clone()).unwrap();
+
+        let start = SyscallImpl::mmap(
+            0,
+            2 * PAGE_SIZE,
+            MmapProt::PROT_READ.bits(),
+            MmapFlags::MAP_PRIVATE.bits(),
+            fd,
+            0,
+        )
+        .unwrap();
+
+        let test = || -> Result<(), Errno> {
+            let mapped = curr
+                .mm()
+                .copy_from_user(VirtAddr::from(start), 2 * PAGE_SIZE)?;
+            assert_eq!(&mapped[..LEN], data.as_slice());
+            assert!(mapped[LEN..].iter().all(|&byte| byte == 0));
+
+            // Frames are written back when dropped.
+            SyscallImpl::madvise(start, 2 * PAGE_SIZE, MADV_DONTNEED)?;
+            assert_eq!(file.get_size(), Some(LEN));
+            Ok(())
+        };
+        test().unwrap();
+        debug!("MMAP_FILE {:#x}", start);
+
+        SyscallImpl::munmap(start, 2 * PAGE_SIZE).unwrap();
+        SyscallImpl::close(fd).unwrap();
+        drop(file);
+        unlink(path).unwrap();
+    });
+}
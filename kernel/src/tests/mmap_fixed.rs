@@ -0,0 +1,66 @@
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::SyscallProc;
+
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    mm::{MmapFlags, MmapProt, VMFlags},
+    syscall::SyscallImpl,
+    task::cpu,
+};
+
+static ONCE: Once = Once::new();
+
+/// Maps pages at fixed addresses, replacing the existing mapping only with `MAP_FIXED`.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let rw = (MmapProt::PROT_READ | MmapProt::PROT_WRITE).bits();
+        let anon = MmapFlags::MAP_PRIVATE | MmapFlags::MAP_ANONYMOUS;
+        let mmap = |addr: usize, prot: usize, flags: MmapFlags| {
+            SyscallImpl::mmap(
+                addr,
+                PAGE_SIZE,
+                prot,
+                (anon | flags).bits(),
+                -1isize as usize,
+                0,
+            )
+        };
+        let start =
+            SyscallImpl::mmap(0, 2 * PAGE_SIZE, rw, anon.bits(), -1isize as usize, 0).unwrap();
+
+        let test = || -> Result<(), Errno> {
+            // The second page is replaced.
+            let read = MmapProt::PROT_READ.bits();
+            let fixed = start + PAGE_SIZE;
+            assert_eq!(mmap(fixed, read, MmapFlags::MAP_FIXED), Ok(fixed));
+            let flags = curr
+                .mm()
+                .get_vma(VirtAddr::from(fixed), |vma, _, _| Ok(vma.flags))?;
+            assert!(!flags.contains(VMFlags::WRITE));
+
+            // Only a hint without fixed flags.
+            let other = mmap(start, rw, MmapFlags::empty())?;
+            assert_ne!(other, start);
+            SyscallImpl::munmap(other, PAGE_SIZE)?;
+
+            assert_eq!(
+                mmap(fixed, rw, MmapFlags::MAP_FIXED_NOREPLACE),
+                Err(Errno::EEXIST)
+            );
+            SyscallImpl::munmap(fixed, PAGE_SIZE)?;
+            assert_eq!(mmap(fixed, rw, MmapFlags::MAP_FIXED_NOREPLACE), Ok(fixed));
+
+            // Lower than the heap.
+            let low = curr.mm().mmap_min_addr().value() - PAGE_SIZE;
+            assert_eq!(mmap(low, rw, MmapFlags::MAP_FIXED), Err(Errno::EPERM));
+            Ok(())
+        };
+        test().unwrap();
+        debug!("MMAP_FIXED {:#x}", start);
+
+        SyscallImpl::munmap(start, 2 * PAGE_SIZE).unwrap();
+    });
+}
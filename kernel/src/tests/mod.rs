@@ -1,3 +1,205 @@
 #![allow(unused)]
 
+pub mod access;
+pub mod chdir;
+pub mod chroot;
+pub mod clock;
+pub mod clone;
+pub mod clone_stack;
+pub mod devices;
+pub mod dup;
+pub mod efault;
+pub mod efs;
+pub mod elf;
+pub mod enospc;
+pub mod epoll;
+pub mod eventfd;
+pub mod fat_resolve;
+pub mod fcntl;
+pub mod fd_table;
+pub mod fionbio;
+pub mod fionread;
+pub mod frame;
+pub mod fstat;
+pub mod futex;
+pub mod getrandom;
+pub mod init_stack;
+pub mod interp;
+pub mod ioctl;
+pub mod iovec;
+pub mod itimer;
+pub mod link;
+#[cfg(feature = "lock_debug")]
+pub mod lock_debug;
+pub mod madvise;
+pub mod maps;
+pub mod membarrier;
+pub mod mkdirat;
+pub mod mknod;
+pub mod mlock;
+pub mod mmap_file;
+pub mod mmap_fixed;
+pub mod nanosleep;
+pub mod open_count;
+pub mod overlay;
+pub mod pgid;
+pub mod pipe;
+pub mod pipe2;
+pub mod ppoll;
+pub mod process_vm;
+pub mod pwrite;
+pub mod rela;
+pub mod rename;
+pub mod resolve;
+pub mod restart;
+pub mod rlimit;
+pub mod robust_list;
+pub mod seek;
+pub mod sendfile;
+pub mod settid;
+pub mod shebang;
+pub mod sigreturn;
 pub mod sleeplock;
+pub mod splice;
+pub mod stack;
+pub mod symlink;
+pub mod sync;
+pub mod thread_group;
+pub mod tkill;
+pub mod tmpfile;
+pub mod tmpfs;
+pub mod uname;
+pub mod user_copy;
+pub mod vma_merge;
+pub mod wait;
+
+use alloc::vec::Vec;
+use core::panic::Location;
+use errno::Errno;
+use kernel_sync::SpinLock;
+use spin::Lazy;
+
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    mm::{do_munmap, VMFlags},
+    task::{cpu, do_clone, do_yield, find_task, CloneFlags, TaskState},
+};
+
+/// Size of the code copied to user space for each function, see [`run_user_code`].
+pub const CODE_SIZE: usize = 128;
+
+/// Offset of the record shared with user code in the scratch memory, usually pointed
+/// to by `tp` of the tasks spawned.
+pub const RECORD: usize = PAGE_SIZE / 2;
+
+/// Flags of threads spawned by [`spawn_threads`].
+pub const THREAD_FLAGS: CloneFlags = CloneFlags::from_bits_truncate(
+    CloneFlags::CLONE_VM.bits()
+        | CloneFlags::CLONE_FILES.bits()
+        | CloneFlags::CLONE_SIGHAND.bits()
+        | CloneFlags::CLONE_THREAD.bits()
+        | CloneFlags::CLONE_SETTLS.bits(),
+);
+
+/// Call sites of [`run_user_code`] already run.
+static STARTED: Lazy<SpinLock<Vec<&'static Location<'static>>>> =
+    Lazy::new(|| SpinLock::new(Vec::new()));
+
+/// Maps `len` bytes of scratch memory with `flags` in the current task, runs `test` with
+/// its start address, and unmaps it before returning the result of `test`.
+pub fn with_scratch<R>(len: usize, flags: VMFlags, test: impl FnOnce(VirtAddr) -> R) -> R {
+    let curr = cpu().curr.as_ref().unwrap();
+    let start = curr
+        .mm()
+        .alloc_vma(VirtAddr::zero(), VirtAddr::from(len), flags, true, None)
+        .unwrap();
+    let result = test(start);
+    do_munmap(&mut curr.mm(), start, len).unwrap();
+    result
+}
+
+/// Copies the functions in `code` to user space and runs `test` with the start address
+/// of `len` bytes of executable scratch memory, see [`with_scratch`].
+///
+/// Each function is copied to [`CODE_SIZE`] bytes in order from the start. Tasks
+/// spawned by a test also return to user through the tests, thus `test` only runs the
+/// first time this is called from the same location. The trapframe, signal actions and
+/// signal mask of the current task are restored before returning the result of `test`.
+#[track_caller]
+pub fn run_user_code(
+    code: &[unsafe extern "C" fn()],
+    len: usize,
+    test: impl FnOnce(VirtAddr) -> Result<(), Errno>,
+) -> Result<(), Errno> {
+    let caller = Location::caller();
+    {
+        let mut started = STARTED.lock();
+        if started.contains(&caller) {
+            return Ok(());
+        }
+        started.push(caller);
+    }
+
+    let curr = cpu().curr.as_ref().unwrap();
+    let trapframe = *curr.trapframe();
+    let actions = *curr.sig_actions.lock();
+    let blocked = curr.locked_inner().sig_blocked;
+    let flags = VMFlags::USER | VMFlags::READ | VMFlags::WRITE | VMFlags::EXEC;
+    let result = with_scratch(len, flags, |start| {
+        for (i, f) in code.iter().enumerate() {
+            let bytes = unsafe { *(*f as usize as *const [u8; CODE_SIZE]) };
+            curr.mm().copy_to_user(start + i * CODE_SIZE, &bytes)?;
+        }
+        unsafe { core::arch::asm!("fence.i") };
+        test(start)
+    });
+    *curr.trapframe() = trapframe;
+    *curr.sig_actions.lock() = actions;
+    curr.locked_inner().sig_blocked = blocked;
+    result
+}
+
+/// Runs `clone` with the trapframe of the current task returning to `entry`, so that
+/// the task created starts from there.
+pub fn clone_at<R>(entry: VirtAddr, clone: impl FnOnce() -> R) -> R {
+    let curr = cpu().curr.as_ref().unwrap();
+    let saved = *curr.trapframe();
+    curr.trapframe().set_epc(entry.value());
+    let result = clone();
+    *curr.trapframe() = saved;
+    result
+}
+
+/// Spawns a thread of the current task with [`THREAD_FLAGS`] for each stack, which
+/// starts from `entry` with `tp` set to `tls`.
+///
+/// Returns the thread IDs.
+pub fn spawn_threads(
+    entry: VirtAddr,
+    tls: VirtAddr,
+    stacks: &[usize],
+) -> Result<Vec<usize>, Errno> {
+    stacks
+        .iter()
+        .map(|&stack| {
+            clone_at(entry, || {
+                do_clone(
+                    THREAD_FLAGS,
+                    stack,
+                    tls.value(),
+                    VirtAddr::zero(),
+                    VirtAddr::zero(),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Yields until the task exits.
+///
+/// Zombie threads may be reaped before checked, thus a task not found has exited.
+pub fn wait_exited(tid: usize) {
+    while find_task(tid).map_or(false, |task| task.get_state() != TaskState::ZOMBIE) {
+        unsafe { do_yield() };
+    }
+}
@@ -0,0 +1,117 @@
+use core::mem::size_of;
+use errno::Errno;
+use log::debug;
+use signal_defs::{SigAction, SIGUSR1, SIG_IGN};
+use syscall_interface::SyscallTimer;
+use time_subsys::{ClockType, TimeSpec, TIMER_ABSTIME};
+
+use super::{run_user_code, spawn_threads, wait_exited, RECORD};
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    read_user,
+    syscall::SyscallImpl,
+    task::{cpu, do_signal},
+    write_user,
+};
+
+/// Address of the signal handler, which is never called.
+const HANDLER: usize = 0x1000;
+
+/// Offset of the requested time.
+const REQ: usize = RECORD + 2 * size_of::<usize>();
+
+/// Offset of the remaining time.
+const REM: usize = REQ + size_of::<TimeSpec>();
+
+/// Thread body in user space, waiting until the sleeper is about to sleep and then
+/// sending `SIGUSR1` to it with `tkill`.
+#[naked]
+unsafe extern "C" fn thread_body() {
+    core::arch::asm!(
+        "1:",
+        "ld t0, 0(tp)",
+        "beqz t0, 1b",
+        // SyscallNO::TKILL
+        "li a7, 130",
+        "ld a0, 8(tp)",
+        "li a1, {sig}",
+        "ecall",
+        // SyscallNO::EXIT
+        "li a7, 93",
+        "li a0, 0",
+        "ecall",
+        sig = const SIGUSR1,
+        options(noreturn),
+    );
+}
+
+/// Sleeps for a relative time and until an absolute time in the past, and then wakes up
+/// early from a long sleep by a signal sent by another thread, which reports the
+/// remaining time.
+pub fn test() {
+    let curr = cpu().curr.as_ref().unwrap();
+    let test = |start: VirtAddr| -> Result<(), Errno> {
+        let now = || -> Result<TimeSpec, Errno> {
+            SyscallImpl::clock_gettime(ClockType::MONOTONIC as usize, (start + REM).value())?;
+            let mut now = TimeSpec::default();
+            read_user!(curr.mm(), start + REM, now, TimeSpec)?;
+            Ok(now)
+        };
+        let set_req = |req: TimeSpec| write_user!(curr.mm(), start + REQ, req, TimeSpec);
+
+        // A relative sleep advances the monotonic clock at least by the request.
+        let req = TimeSpec {
+            tv_sec: 0,
+            tv_nsec: 20_000_000,
+        };
+        set_req(req)?;
+        let before = now()?;
+        SyscallImpl::nanosleep((start + REQ).value(), 0)?;
+        assert!(now()? - before >= req);
+
+        // An absolute time in the past returns immediately.
+        set_req(before)?;
+        SyscallImpl::clock_nanosleep(
+            ClockType::MONOTONIC as usize,
+            TIMER_ABSTIME,
+            (start + REQ).value(),
+            0,
+        )?;
+
+        curr.sig_actions.lock()[SIGUSR1 - 1] = SigAction {
+            handler: HANDLER,
+            ..SigAction::default()
+        };
+        curr.locked_inner().sig_blocked.unset(SIGUSR1 - 1);
+
+        // The thread starts from the body with its stack in the second page.
+        let record = [0, curr.tid.0];
+        write_user!(curr.mm(), start + RECORD, record, [usize; 2])?;
+        let tid = spawn_threads(start, start + RECORD, &[start.value() + 2 * PAGE_SIZE])?[0];
+
+        // Interrupted long before the deadline.
+        let req = TimeSpec {
+            tv_sec: 10,
+            tv_nsec: 0,
+        };
+        set_req(req)?;
+        write_user!(curr.mm(), start + RECORD, 1usize, usize)?;
+        let before = now()?;
+        assert_eq!(
+            SyscallImpl::nanosleep((start + REQ).value(), (start + REM).value()),
+            Err(Errno::EINTR)
+        );
+        let mut rem = TimeSpec::default();
+        read_user!(curr.mm(), start + REM, rem, TimeSpec)?;
+        assert!(rem > TimeSpec::default() && rem <= req);
+        assert!(now()? - before < req);
+        wait_exited(tid);
+
+        // Discards the signal.
+        curr.sig_actions.lock()[SIGUSR1 - 1].handler = SIG_IGN;
+        do_signal(false)?;
+        debug!("NANOSLEEP interrupted with {:?} left", rem);
+        Ok(())
+    };
+    run_user_code(&[thread_body], 2 * PAGE_SIZE, test).unwrap();
+}
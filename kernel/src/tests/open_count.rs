@@ -0,0 +1,66 @@
+use alloc::sync::Arc;
+use log::debug;
+use spin::Once;
+use vfs::{File, OpenFlags, Path, RenameFlags};
+
+use crate::fs::{mkdir, open, rename, unlink, FSFile};
+
+static ONCE: Once = Once::new();
+
+/// Number of files opened for the same file as `file` on disk.
+fn open_count(file: &Arc<dyn File>) -> usize {
+    file.as_any().downcast_ref::<FSFile>().unwrap().open_count()
+}
+
+/// Counts the files opened on disk across a rename, which does not share the count
+/// with a new file created at the old path, and drops the count when closed.
+pub fn test() {
+    ONCE.call_once(|| {
+        let _ = mkdir(Path::new("/open_count/"));
+        let a = Path::new("/open_count/a");
+        let b = Path::new("/open_count/b");
+
+        // The file owns a cluster, which identifies it.
+        let file = open(
+            a.clone(),
+            OpenFlags::O_CREAT | OpenFlags::O_TRUNC | OpenFlags::O_WRONLY,
+        )
+        .unwrap();
+        assert_eq!(file.write(b"a"), Some(1));
+        drop(file);
+
+        let first = open(a.clone(), OpenFlags::O_RDWR).unwrap();
+        assert_eq!(open_count(&first), 1);
+        let second = open(a.clone(), OpenFlags::O_RDONLY).unwrap();
+        assert_eq!(open_count(&first), 2);
+
+        rename(a.clone(), b.clone(), RenameFlags::empty()).unwrap();
+        let third = open(b.clone(), OpenFlags::O_RDONLY).unwrap();
+        assert_eq!(open_count(&third), 3);
+
+        let other = open(a.clone(), OpenFlags::O_CREAT | OpenFlags::O_RDWR).unwrap();
+        assert_eq!(other.write(b"b"), Some(1));
+        assert_eq!(open_count(&other), 1);
+        assert_eq!(open_count(&first), 3);
+
+        drop(second);
+        drop(third);
+        assert_eq!(open_count(&first), 1);
+        drop(other);
+        assert_eq!(open_count(&first), 1);
+
+        // The data written through the last file is kept once closed.
+        assert_eq!(first.write(b"c"), Some(1));
+        drop(first);
+        let file = open(b.clone(), OpenFlags::O_RDONLY).unwrap();
+        let mut buf = [0u8; 2];
+        assert_eq!(file.read(&mut buf), Some(1));
+        assert_eq!(&buf[..1], b"c");
+        assert_eq!(open_count(&file), 1);
+        debug!("OPEN COUNT kept across rename of {:?}", b);
+
+        drop(file);
+        unlink(a).unwrap();
+        unlink(b).unwrap();
+    });
+}
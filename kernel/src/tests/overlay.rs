@@ -0,0 +1,78 @@
+use alloc::{sync::Arc, vec};
+use easy_fs::{EasyFileSystem, BLOCK_SZ};
+use kernel_sync::SpinLock;
+use log::debug;
+use spin::Once;
+use vfs::{File, OpenFlags, Path, SeekWhence, VFS};
+
+use super::efs::{RamDisk, EFS_BLOCKS};
+use crate::fs::{
+    global_fs, makedev, mkdir, mount, open, umount, unlink, EasyFs, OverlayFs, VIRTBLK_MAJOR,
+};
+
+static ONCE: Once = Once::new();
+
+/// Reads a file from the beginning.
+fn read_to_end(file: &Arc<dyn File>, buf: &mut [u8]) -> usize {
+    file.seek(0, SeekWhence::Set);
+    file.read(buf).unwrap()
+}
+
+/// Mounts an overlay with an easy-fs upper over the FAT directory `/overlay`, then
+/// modifies and removes a file only in the lower filesystem.
+pub fn test() {
+    ONCE.call_once(|| {
+        let mount_point = Path::new("/overlay/");
+        let _ = mkdir(mount_point.clone());
+        let file = Path::new("/overlay/hello");
+        let lower = open(
+            file.clone(),
+            OpenFlags::O_CREAT | OpenFlags::O_TRUNC | OpenFlags::O_WRONLY,
+        )
+        .unwrap();
+        assert_eq!(lower.write(b"lower"), Some(5));
+        drop(lower);
+
+        let disk = Arc::new(RamDisk(SpinLock::new(vec![0; EFS_BLOCKS * BLOCK_SZ])));
+        let efs = EasyFileSystem::format(disk, EFS_BLOCKS, 1).unwrap();
+        let upper = Arc::new(EasyFs::new(
+            efs,
+            mount_point.clone(),
+            makedev(VIRTBLK_MAJOR, 2),
+        ));
+        let overlay = Arc::new(OverlayFs::new(
+            mount_point.clone(),
+            upper.clone(),
+            global_fs(),
+        ));
+        mount(mount_point.clone(), overlay).unwrap();
+
+        // Falls through to the lower filesystem.
+        let mut buf = [0u8; 16];
+        let merged = open(file.clone(), OpenFlags::O_RDONLY).unwrap();
+        assert_eq!(read_to_end(&merged, &mut buf), 5);
+        assert_eq!(&buf[..5], b"lower");
+        assert!(!upper.check(&file));
+
+        // Copied up before writing.
+        let merged = open(file.clone(), OpenFlags::O_RDWR | OpenFlags::O_APPEND).unwrap();
+        assert_eq!(merged.write(b", upper"), Some(7));
+        assert!(upper.check(&file));
+        assert_eq!(read_to_end(&merged, &mut buf), 12);
+        assert_eq!(&buf[..12], b"lower, upper");
+        let lower = global_fs()
+            .open(&mount_point, "hello", OpenFlags::O_RDONLY)
+            .unwrap();
+        assert_eq!(read_to_end(&lower, &mut buf), 5);
+        assert_eq!(&buf[..5], b"lower");
+        debug!("OVERLAY copied up {:?}", file);
+
+        // Hidden by a whiteout while the lower one is kept.
+        unlink(file.clone()).unwrap();
+        assert!(open(file.clone(), OpenFlags::O_RDONLY).is_err());
+        assert!(global_fs().check(&file));
+
+        umount(&mount_point).unwrap();
+        unlink(file).unwrap();
+    });
+}
@@ -0,0 +1,86 @@
+use errno::Errno;
+use log::debug;
+use signal_defs::{SIGCHLD, SIGKILL, SIGUSR1};
+use syscall_interface::{SyscallComm, SyscallProc};
+
+use super::{clone_at, run_user_code, RECORD};
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    read_user,
+    syscall::SyscallImpl,
+    task::{cpu, do_clone, find_task, CloneFlags},
+};
+
+/// Offset of the status word.
+const STATUS: usize = RECORD;
+
+/// Child process in user space spinning until killed.
+#[naked]
+unsafe extern "C" fn spin_body() {
+    core::arch::asm!("1:", "j 1b", options(noreturn));
+}
+
+/// Forks three children, two of which are moved into a new process group and killed
+/// together by signaling the group, while the last one in another session cannot join.
+pub fn test() {
+    let curr = cpu().curr.as_ref().unwrap();
+
+    let test = |start: VirtAddr| -> Result<(), Errno> {
+        let fork = || {
+            clone_at(start, || {
+                do_clone(
+                    CloneFlags::from_bits_truncate(SIGCHLD as u32),
+                    0,
+                    0,
+                    VirtAddr::zero(),
+                    VirtAddr::zero(),
+                )
+            })
+        };
+        let mut children = [0; 3];
+        for pid in children.iter_mut() {
+            *pid = fork()?;
+        }
+        let [leader, member, other] = children;
+
+        // Inherited from the parent.
+        let pgid = SyscallImpl::getpgid(0)?;
+        assert_eq!(SyscallImpl::getpgid(other), Ok(pgid));
+
+        // A new group led by the first child, joined by the second one.
+        assert_eq!(SyscallImpl::setpgid(leader, 0), Ok(0));
+        assert_eq!(SyscallImpl::getpgid(leader), Ok(leader));
+        assert_eq!(SyscallImpl::setpgid(member, leader), Ok(0));
+        assert_eq!(SyscallImpl::getpgid(member), Ok(leader));
+        assert_eq!(SyscallImpl::setpgid(other, other + 1000), Err(Errno::EPERM));
+
+        // The last child creates a new session as if it calls `setsid`.
+        let task = find_task(other).unwrap();
+        task.thread_group.set_sid(other);
+        task.thread_group.set_pgid(other);
+        drop(task);
+        assert_eq!(SyscallImpl::setpgid(other, leader), Err(Errno::EPERM));
+        assert_eq!(SyscallImpl::setpgid(other, 0), Err(Errno::EPERM));
+
+        // Both processes in the group are terminated by the default action.
+        assert_eq!(SyscallImpl::kill(-(leader as isize), SIGUSR1), Ok(0));
+        let status = start.value() + STATUS;
+        let mut value = 0i32;
+        for pid in [leader, member] {
+            assert_eq!(SyscallImpl::wait4(pid as isize, status, 0, 0), Ok(pid));
+            read_user!(curr.mm(), VirtAddr::from(status), value, i32)?;
+            assert_eq!(value, SIGUSR1 as i32);
+        }
+        assert_eq!(
+            SyscallImpl::kill(-(leader as isize), SIGUSR1),
+            Err(Errno::ESRCH)
+        );
+        assert_eq!(SyscallImpl::getpgid(leader), Err(Errno::ESRCH));
+
+        assert_eq!(SyscallImpl::kill(other as isize, SIGKILL), Ok(0));
+        assert_eq!(SyscallImpl::wait4(other as isize, 0, 0, 0), Ok(other));
+        debug!("PGID {} signaled with {}", leader, member);
+        Ok(())
+    };
+    run_user_code(&[spin_body], PAGE_SIZE, test).unwrap();
+}
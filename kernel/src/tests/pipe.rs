@@ -0,0 +1,106 @@
+use alloc::{sync::Arc, vec};
+use core::mem::take;
+use errno::Errno;
+use log::debug;
+use signal_defs::SIGPIPE;
+use spin::Once;
+use syscall_interface::{IoVec, SyscallFile};
+use vfs::{File, OpenFlags};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    config::MAX_PIPE_BUF,
+    fs::Pipe,
+    mm::VMFlags,
+    syscall::SyscallImpl,
+    task::cpu,
+    write_user,
+};
+
+static ONCE: Once = Once::new();
+
+/// Checks readiness of both ends of an empty, full and broken pipe, and that a write
+/// stopped by an error after transferring data returns the count, leaving `EPIPE` and
+/// `SIGPIPE` to the next write.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let pending = take(&mut curr.locked_inner().sig_pending);
+
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            let iov_addr = start;
+            let buf_addr = start + PAGE_SIZE / 2;
+            let sigpipe = || curr.locked_inner().sig_pending.mask.get(SIGPIPE - 1);
+            let (pipe_read, pipe_write) = Pipe::new();
+            let (pipe_read, pipe_write) = (Arc::new(pipe_read), Arc::new(pipe_write));
+            pipe_read.set_open_flags(OpenFlags::O_NONBLOCK);
+            pipe_write.set_open_flags(OpenFlags::O_NONBLOCK);
+            let fd_read = curr.files().push(pipe_read.clone())?;
+            let fd_write = curr.files().push(pipe_write.clone())?;
+            let buf = buf_addr.value() as *mut u8;
+
+            // Empty.
+            assert!(!pipe_read.read_ready() && pipe_write.write_ready());
+            assert_eq!(SyscallImpl::read(fd_read, buf, 8), Err(Errno::EAGAIN));
+
+            // Full, with 16 bytes read out.
+            let data = vec![0x5a; MAX_PIPE_BUF];
+            assert_eq!(pipe_write.try_write(&data), Ok(MAX_PIPE_BUF));
+            assert!(pipe_read.read_ready() && !pipe_write.write_ready());
+            assert_eq!(pipe_write.try_write(&data), Err(Errno::EAGAIN));
+            assert_eq!(SyscallImpl::read(fd_read, buf, 16), Ok(16));
+            assert!(pipe_write.write_ready());
+
+            // The second buffer fails with EAGAIN after the first one is written.
+            let iovs = [
+                IoVec {
+                    iov_base: buf_addr.value(),
+                    iov_len: 16,
+                },
+                IoVec {
+                    iov_base: buf_addr.value(),
+                    iov_len: 8,
+                },
+            ];
+            write_user!(curr.mm(), iov_addr, iovs, [IoVec; 2])?;
+            let iov = iov_addr.value() as *const IoVec;
+            assert_eq!(SyscallImpl::writev(fd_write, iov, 2), Ok(16));
+            assert!(!pipe_write.write_ready());
+
+            // The write end of a broken pipe is always ready, and fails with EPIPE.
+            drop(pipe_read);
+            curr.files().remove(fd_read)?;
+            assert!(pipe_write.write_ready() && pipe_write.hangup());
+            assert!(!sigpipe());
+            assert_eq!(SyscallImpl::writev(fd_write, iov, 2), Err(Errno::EPIPE));
+            assert!(sigpipe());
+            curr.locked_inner().sig_pending = Default::default();
+            assert_eq!(
+                SyscallImpl::write(fd_write, buf as *const u8, 8),
+                Err(Errno::EPIPE)
+            );
+            assert!(sigpipe());
+            curr.locked_inner().sig_pending = Default::default();
+            drop(pipe_write);
+            curr.files().remove(fd_write)?;
+
+            // The read end of a broken pipe is always ready, and reads 0 once drained.
+            let (pipe_read, pipe_write) = Pipe::new();
+            assert_eq!(pipe_write.try_write(b"pipe"), Ok(4));
+            drop(pipe_write);
+            let pipe_read = Arc::new(pipe_read);
+            let fd_read = curr.files().push(pipe_read.clone())?;
+            assert!(pipe_read.read_ready() && pipe_read.hangup());
+            assert_eq!(SyscallImpl::read(fd_read, buf, 8), Ok(4));
+            assert!(pipe_read.read_ready());
+            assert_eq!(SyscallImpl::read(fd_read, buf, 8), Ok(0));
+            curr.files().remove(fd_read)?;
+            debug!("PIPE {} bytes buffered", MAX_PIPE_BUF);
+            Ok(())
+        };
+        let result = with_scratch(PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test);
+        curr.locked_inner().sig_pending = pending;
+        result.unwrap();
+    });
+}
@@ -0,0 +1,75 @@
+use alloc::vec;
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{SyscallComm, SyscallFile};
+use vfs::{File, OpenFlags};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    config::MAX_PIPE_BUF,
+    mm::VMFlags,
+    read_user,
+    syscall::SyscallImpl,
+    task::cpu,
+};
+
+static ONCE: Once = Once::new();
+
+/// Creates pipes with and without flags through `pipe2`, then fills and drains one,
+/// whose write end is ready only if the buffer has free space.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            let pipe2 = |flags: OpenFlags| -> Result<[u32; 2], Errno> {
+                SyscallImpl::pipe2(start.value() as *const u32, flags.bits() as usize)?;
+                let mut fds = [0u32; 2];
+                read_user!(curr.mm(), start, fds, [u32; 2])?;
+                Ok(fds)
+            };
+
+            let [fd_read, fd_write] = pipe2(OpenFlags::empty())?.map(|fd| fd as usize);
+            let (pipe_read, pipe_write) = (curr.files().get(fd_read)?, curr.files().get(fd_write)?);
+            assert!(!pipe_read.open_flags().contains(OpenFlags::O_NONBLOCK));
+            assert_eq!(curr.files().get_cloexec(fd_read), Ok(false));
+            assert_eq!(curr.files().get_cloexec(fd_write), Ok(false));
+            assert!(!pipe_read.read_ready() && pipe_write.write_ready());
+
+            // Fills the pipe, then drains it.
+            let data = vec![0x5a; MAX_PIPE_BUF];
+            assert_eq!(pipe_write.try_write(&data), Ok(MAX_PIPE_BUF));
+            assert!(pipe_read.read_ready() && !pipe_write.write_ready());
+            let mut buf = vec![0; MAX_PIPE_BUF];
+            assert_eq!(pipe_read.read(&mut buf), Some(MAX_PIPE_BUF));
+            assert!(!pipe_read.read_ready() && pipe_write.write_ready());
+
+            // The read end is ready once the write end is closed.
+            drop(pipe_write);
+            SyscallImpl::close(fd_write)?;
+            assert!(pipe_read.read_ready());
+            drop(pipe_read);
+            SyscallImpl::close(fd_read)?;
+
+            let flags = OpenFlags::O_NONBLOCK | OpenFlags::O_CLOEXEC;
+            let [fd_read, fd_write] = pipe2(flags)?.map(|fd| fd as usize);
+            for fd in [fd_read, fd_write] {
+                let file = curr.files().get(fd)?;
+                assert!(file.open_flags().contains(OpenFlags::O_NONBLOCK));
+                assert_eq!(curr.files().get_cloexec(fd), Ok(true));
+            }
+            let buf = (start + PAGE_SIZE / 2).value() as *mut u8;
+            assert_eq!(SyscallImpl::read(fd_read, buf, 8), Err(Errno::EAGAIN));
+            SyscallImpl::close(fd_read)?;
+            SyscallImpl::close(fd_write)?;
+
+            assert_eq!(pipe2(OpenFlags::O_APPEND).err(), Some(Errno::EINVAL));
+            debug!("PIPE2 with {:?}", flags);
+            Ok(())
+        };
+        let result = with_scratch(PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test);
+        result.unwrap();
+    });
+}
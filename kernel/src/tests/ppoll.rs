@@ -0,0 +1,174 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::mem::{size_of, take};
+use errno::Errno;
+use log::debug;
+use signal_defs::{sigmask, SigAction, SigSet, SIGALRM, SIGUSR1};
+use spin::Once;
+use syscall_interface::{PollEvents, PollFd, SyscallIO};
+use time_subsys::{ITimer, TimeSpec};
+use vfs::File;
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::{Pipe, Stdin},
+    mm::VMFlags,
+    read_user,
+    syscall::SyscallImpl,
+    task::{cpu, do_signal, do_sigreturn},
+    timer::set_real_timer,
+    write_user,
+};
+
+static ONCE: Once = Once::new();
+
+/// Address of the signal handler, which is never called.
+const HANDLER: usize = 0x1000;
+
+/// Offset of the timeout.
+const TIMEOUT: usize = PAGE_SIZE / 2;
+
+/// Offset of the signal mask.
+const SIGMASK: usize = TIMEOUT + size_of::<TimeSpec>();
+
+/// Polls both ends of a pipe and standard input, sleeping until the timeout expires or
+/// a signal unblocked by the mask arrives.
+///
+/// The handler of the signal is called with the mask of `ppoll`, and the original mask
+/// is restored when it returns.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let pending = take(&mut curr.locked_inner().sig_pending);
+        let blocked = curr.locked_inner().sig_blocked;
+        let action = curr.sig_actions.lock()[SIGALRM - 1];
+        let saved = *curr.trapframe();
+
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            let (pipe_read, pipe_write) = Pipe::new();
+            let pipe_write = Arc::new(pipe_write);
+            let fd_read = curr.files().push(Arc::new(pipe_read))?;
+            let fd_write = curr.files().push(pipe_write.clone())?;
+            let fd_stdin = curr.files().push(Arc::new(Stdin))?;
+            let timeout = TimeSpec {
+                tv_sec: 0,
+                tv_nsec: 10_000_000,
+            };
+            write_user!(curr.mm(), start + TIMEOUT, timeout, TimeSpec)?;
+            let ppoll = |fds: &[(i32, PollEvents)],
+                         tmo_p: usize,
+                         mask_p: usize|
+             -> Result<(usize, Vec<PollEvents>), Errno> {
+                for (i, &(fd, events)) in fds.iter().enumerate() {
+                    let poll_fd = PollFd {
+                        fd,
+                        events,
+                        revents: PollEvents::empty(),
+                    };
+                    write_user!(curr.mm(), start + i * size_of::<PollFd>(), poll_fd, PollFd)?;
+                }
+                let count = SyscallImpl::ppoll(start.value(), fds.len(), tmo_p, mask_p)?;
+                let mut revents = Vec::new();
+                for i in 0..fds.len() {
+                    let mut poll_fd = PollFd::default();
+                    read_user!(curr.mm(), start + i * size_of::<PollFd>(), poll_fd, PollFd)?;
+                    revents.push(poll_fd.revents);
+                }
+                Ok((count, revents))
+            };
+            let tmo_p = (start + TIMEOUT).value();
+            let (pollin, pollout) = (PollEvents::POLLIN, PollEvents::POLLOUT);
+
+            // Nothing to read until the timeout expires.
+            assert_eq!(
+                ppoll(&[(fd_read as i32, pollin)], tmo_p, 0)?,
+                (0, [PollEvents::empty()].to_vec())
+            );
+            assert_eq!(
+                ppoll(&[(-1, pollin), (fd_write as i32, pollout)], 0, 0)?,
+                (1, [PollEvents::empty(), pollout].to_vec())
+            );
+            assert_eq!(pipe_write.try_write(b"ppoll"), Ok(5));
+            assert_eq!(
+                ppoll(&[(fd_read as i32, pollin), (fd_stdin as i32, pollin)], 0, 0)?,
+                (2, [pollin, pollin].to_vec())
+            );
+            assert_eq!(
+                ppoll(&[(i32::MAX, pollin)], tmo_p, 0)?,
+                (1, [PollEvents::POLLNVAL].to_vec())
+            );
+            curr.files().remove(fd_read)?;
+            assert_eq!(
+                ppoll(&[(fd_write as i32, PollEvents::empty())], 0, 0)?,
+                (1, [PollEvents::POLLHUP].to_vec())
+            );
+
+            // The mask unblocks the signal only while waiting.
+            let (pipe_read, _pipe_write) = Pipe::new();
+            let fd_read = curr.files().push(Arc::new(pipe_read))?;
+            let mut mask = SigSet::new();
+            mask.set_mask(sigmask(SIGALRM) | sigmask(SIGUSR1));
+            curr.locked_inner().sig_blocked = mask;
+            write_user!(curr.mm(), start + SIGMASK, SigSet::new(), SigSet)?;
+            let timer = ITimer {
+                interval: TimeSpec::default(),
+                value: timeout,
+            };
+            set_real_timer(curr.pid, &mut curr.real_timer.lock(), timer);
+            assert_eq!(
+                ppoll(&[(fd_read as i32, pollin)], 0, (start + SIGMASK).value()),
+                Err(Errno::EINTR)
+            );
+            assert!(curr.locked_inner().sig_pending.mask.get(SIGALRM - 1));
+            assert!(!curr.locked_inner().sig_blocked.get(SIGALRM - 1));
+
+            curr.sig_actions.lock()[SIGALRM - 1] = SigAction {
+                handler: HANDLER,
+                ..SigAction::default()
+            };
+            let trapframe = curr.trapframe();
+            trapframe.set_sp((start + PAGE_SIZE).value());
+            do_signal(false)?;
+            assert_eq!(trapframe.get_epc(), HANDLER);
+            assert_eq!(trapframe.get_a0(), SIGALRM);
+            assert!(!curr.locked_inner().sig_pending.mask.get(SIGALRM - 1));
+            assert!(curr.locked_inner().sig_blocked.get(SIGALRM - 1));
+            assert!(!curr.locked_inner().sig_blocked.get(SIGUSR1 - 1));
+            do_sigreturn()?;
+            assert!(curr.locked_inner().sig_blocked.get(SIGUSR1 - 1));
+            assert!(curr.locked_inner().saved_sigmask.is_none());
+            *trapframe = saved;
+
+            // The signal stays pending while blocked by the mask.
+            write_user!(curr.mm(), start + SIGMASK, mask, SigSet)?;
+            set_real_timer(curr.pid, &mut curr.real_timer.lock(), timer);
+            let timeout = TimeSpec {
+                tv_sec: 0,
+                tv_nsec: 30_000_000,
+            };
+            write_user!(curr.mm(), start + TIMEOUT, timeout, TimeSpec)?;
+            assert_eq!(
+                ppoll(
+                    &[(fd_read as i32, pollin)],
+                    tmo_p,
+                    (start + SIGMASK).value()
+                )?,
+                (0, [PollEvents::empty()].to_vec())
+            );
+            assert!(curr.locked_inner().sig_pending.mask.get(SIGALRM - 1));
+            debug!("PPOLL slept until the timeout or SIGALRM");
+
+            curr.files().remove(fd_read)?;
+            curr.files().remove(fd_write)?;
+            curr.files().remove(fd_stdin)?;
+            Ok(())
+        };
+        let result = with_scratch(PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test);
+        set_real_timer(curr.pid, &mut curr.real_timer.lock(), ITimer::new());
+        curr.sig_actions.lock()[SIGALRM - 1] = action;
+        *curr.trapframe() = saved;
+        curr.locked_inner().sig_pending = pending;
+        curr.locked_inner().sig_blocked = blocked;
+        result.unwrap();
+    });
+}
@@ -0,0 +1,140 @@
+use core::mem::size_of;
+use errno::Errno;
+use log::debug;
+use signal_defs::{SIGCHLD, SIGKILL};
+use syscall_interface::{IoVec, SyscallComm, SyscallProc};
+
+use super::{clone_at, run_user_code, wait_exited, RECORD};
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    read_user,
+    syscall::SyscallImpl,
+    task::{cpu, do_clone, do_yield, send_signal_thread, CloneFlags},
+    write_user,
+};
+
+/// Offset of the local buffer of the parent.
+const LOCAL: usize = RECORD + 2 * size_of::<usize>();
+
+/// Offset of the iovecs, local ones followed by remote ones.
+const IOVECS: usize = LOCAL + 2 * size_of::<usize>();
+
+/// Value written by the child.
+const VALUE: usize = 1234;
+
+/// Child process in user space, writing [`VALUE`] and spinning until killed.
+#[naked]
+unsafe extern "C" fn child_body() {
+    core::arch::asm!(
+        "li t0, {value}",
+        "sd t0, 0(tp)",
+        "1:",
+        "j 1b",
+        value = const VALUE,
+        options(noreturn),
+    );
+}
+
+/// Forks a child process writing a value, which is read back by the parent through
+/// `process_vm_readv`, while the same address of the parent is left untouched.
+///
+/// A sibling process forked with `CLONE_PARENT` cannot be accessed.
+pub fn test() {
+    let curr = cpu().curr.as_ref().unwrap();
+
+    let test = |start: VirtAddr| -> Result<(), Errno> {
+        // The child starts from the body with its own copy of the page.
+        let child = clone_at(start, || {
+            do_clone(
+                CloneFlags::from_bits_truncate(SIGCHLD as u32) | CloneFlags::CLONE_SETTLS,
+                0,
+                start.value() + RECORD,
+                VirtAddr::zero(),
+                VirtAddr::zero(),
+            )
+        })?;
+
+        let iovecs = [
+            IoVec {
+                iov_base: start.value() + LOCAL,
+                iov_len: size_of::<usize>(),
+            },
+            IoVec {
+                iov_base: start.value() + RECORD,
+                iov_len: size_of::<usize>(),
+            },
+        ];
+        write_user!(curr.mm(), start + IOVECS, iovecs, [IoVec; 2])?;
+        let local_iov = (start.value() + IOVECS) as *const IoVec;
+        let remote_iov = (start.value() + IOVECS + size_of::<IoVec>()) as *const IoVec;
+
+        let mut value = 0usize;
+        while value != VALUE {
+            assert_eq!(
+                SyscallImpl::process_vm_readv(child, local_iov, 1, remote_iov, 1, 0),
+                Ok(size_of::<usize>())
+            );
+            read_user!(curr.mm(), start + LOCAL, value, usize)?;
+            unsafe { do_yield() };
+        }
+        read_user!(curr.mm(), start + RECORD, value, usize)?;
+        assert_eq!(value, 0);
+
+        // Writes the value back to the parent itself.
+        assert_eq!(
+            SyscallImpl::process_vm_writev(curr.pid, local_iov, 1, remote_iov, 1, 0),
+            Ok(size_of::<usize>())
+        );
+        read_user!(curr.mm(), start + RECORD, value, usize)?;
+        assert_eq!(value, VALUE);
+
+        // Unmapped remote memory.
+        let unmapped = [IoVec {
+            iov_base: 0,
+            iov_len: size_of::<usize>(),
+        }];
+        write_user!(
+            curr.mm(),
+            VirtAddr::from(remote_iov as usize),
+            unmapped,
+            [IoVec; 1]
+        )?;
+        assert_eq!(
+            SyscallImpl::process_vm_readv(child, local_iov, 1, remote_iov, 1, 0),
+            Err(Errno::EFAULT)
+        );
+        assert_eq!(
+            SyscallImpl::process_vm_readv(child, local_iov, 1, remote_iov, 1, 1),
+            Err(Errno::EINVAL)
+        );
+
+        // Neither the caller nor its descendant.
+        let sibling = clone_at(start, || {
+            do_clone(
+                CloneFlags::from_bits_truncate(SIGCHLD as u32)
+                    | CloneFlags::CLONE_PARENT
+                    | CloneFlags::CLONE_SETTLS,
+                0,
+                start.value() + RECORD,
+                VirtAddr::zero(),
+                VirtAddr::zero(),
+            )
+        })?;
+        assert_eq!(
+            SyscallImpl::process_vm_readv(sibling, local_iov, 1, remote_iov, 1, 0),
+            Err(Errno::EPERM)
+        );
+        send_signal_thread(None, sibling, SIGKILL)?;
+        wait_exited(sibling);
+
+        send_signal_thread(None, child, SIGKILL)?;
+        assert_eq!(SyscallImpl::wait4(child as isize, 0, 0, 0), Ok(child));
+        assert_eq!(
+            SyscallImpl::process_vm_readv(child, local_iov, 1, remote_iov, 1, 0),
+            Err(Errno::ESRCH)
+        );
+        debug!("PROCESS_VM {} read from {}", VALUE, child);
+        Ok(())
+    };
+    run_user_code(&[child_body], PAGE_SIZE, test).unwrap();
+}
@@ -0,0 +1,71 @@
+use alloc::sync::Arc;
+use core::mem::size_of;
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{IoVec, SyscallFile};
+use vfs::{File, OpenFlags, Path, SeekWhence};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::{open, Pipe},
+    mm::VMFlags,
+    syscall::SyscallImpl,
+    task::cpu,
+    write_user,
+};
+
+static ONCE: Once = Once::new();
+
+/// Writes an unnamed file at offsets, which leaves the cursor of the file unchanged.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            let iov_addr = start;
+            let buf_addr = start + PAGE_SIZE / 2;
+
+            let file = open(Path::root(), OpenFlags::O_TMPFILE | OpenFlags::O_RDWR)?;
+            let fd = curr.files().push(file.clone())?;
+            assert_eq!(file.write(b"0123456789"), Some(10));
+            file.seek(2, SeekWhence::Set);
+
+            curr.mm().copy_to_user(buf_addr, b"abcd")?;
+            let buf = buf_addr.value() as *const u8;
+            assert_eq!(SyscallImpl::pwrite(fd, buf, 2, 5), Ok(2));
+            assert_eq!(file.get_off(), 2);
+
+            // The second buffer extends the file.
+            for (i, (off, len)) in [(2, 2), (0, 1)].into_iter().enumerate() {
+                let iov = IoVec {
+                    iov_base: buf_addr.value() + off,
+                    iov_len: len,
+                };
+                write_user!(curr.mm(), iov_addr + i * size_of::<IoVec>(), iov, IoVec)?;
+            }
+            let iov = iov_addr.value() as *const IoVec;
+            assert_eq!(SyscallImpl::pwritev(fd, iov, 2, 9), Ok(3));
+            assert_eq!(file.get_off(), 2);
+
+            let mut data = [0u8; 16];
+            assert_eq!(file.read(&mut data), Some(10));
+            assert_eq!(&data[..10], b"234ab78cda");
+            assert_eq!(
+                SyscallImpl::pwrite(fd, buf, 2, usize::MAX),
+                Err(Errno::EINVAL)
+            );
+            debug!("PWRITE {:?}", core::str::from_utf8(&data[..10]));
+
+            let (pipe_read, pipe_write) = Pipe::new();
+            let fd_write = curr.files().push(Arc::new(pipe_write))?;
+            assert_eq!(SyscallImpl::pwrite(fd_write, buf, 2, 0), Err(Errno::ESPIPE));
+            drop(pipe_read);
+
+            curr.files().remove(fd)?;
+            curr.files().remove(fd_write)?;
+            Ok(())
+        };
+        with_scratch(PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test).unwrap();
+    });
+}
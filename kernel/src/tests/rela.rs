@@ -0,0 +1,57 @@
+use alloc::{string::String, vec, vec::Vec};
+use core::mem::size_of;
+use errno::Errno;
+use log::debug;
+use spin::Once;
+
+use super::elf::{Elf, CODE, DATA, R_RISCV_RELATIVE};
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    config::ELF_BASE_RELOCATE,
+    loader::from_elf,
+    mm::MM,
+    read_user,
+};
+
+/// Relocation type: `S + A`, which needs symbol lookup.
+const R_RISCV_64: u64 = 2;
+
+/// Address of the program which is not position-independent.
+const BASE: usize = 0x1_0000;
+
+static ONCE: Once = Once::new();
+
+/// Loads a static position-independent program whose pointer is patched by
+/// `R_RISCV_RELATIVE` to the relocated address, while other types are skipped.
+pub fn test() {
+    ONCE.call_once(|| {
+        let test = || -> Result<(), Errno> {
+            let load = |base: usize| -> Result<[usize; 2], Errno> {
+                let relas = [
+                    (base + DATA, R_RISCV_RELATIVE, base + CODE),
+                    (base + DATA + size_of::<usize>(), R_RISCV_64, base + CODE),
+                ];
+                let elf = Elf {
+                    base,
+                    relas: &relas,
+                    ..Elf::default()
+                }
+                .build();
+                let mut mm = MM::new()?;
+                let args = vec![String::from("rela")];
+                from_elf(&elf, args, Vec::new(), PAGE_SIZE, &mut mm)?;
+                let mut words = [usize::MAX; 2];
+                let data = VirtAddr::from(mm.entry.value() - CODE + DATA);
+                read_user!(mm, data, words, [usize; 2])?;
+                Ok(words)
+            };
+
+            assert_eq!(load(0)?, [ELF_BASE_RELOCATE + CODE, 0]);
+            // Nothing is added to a program at a fixed address.
+            assert_eq!(load(BASE)?, [BASE + CODE, 0]);
+            debug!("RELA pointer relocated to {:#x}", ELF_BASE_RELOCATE + CODE);
+            Ok(())
+        };
+        test().unwrap();
+    });
+}
@@ -0,0 +1,103 @@
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use vfs::{File, OpenFlags, Path, RenameFlags};
+
+use crate::fs::{mkdir, open, rename, unlink};
+
+static ONCE: Once = Once::new();
+
+/// Creates a file on disk with the content.
+fn create(path: &Path, content: &[u8]) {
+    let file = open(
+        path.clone(),
+        OpenFlags::O_CREAT | OpenFlags::O_TRUNC | OpenFlags::O_WRONLY,
+    )
+    .unwrap();
+    assert_eq!(file.write(content), Some(content.len()));
+}
+
+/// Reads the content of a file on disk.
+fn read(path: &Path) -> Result<[u8; 1], Errno> {
+    let file = open(path.clone(), OpenFlags::O_RDONLY)?;
+    let mut buf = [0u8; 1];
+    assert_eq!(file.read(&mut buf), Some(1));
+    Ok(buf)
+}
+
+/// Renames files within a directory and across directories, exchanges them, while
+/// moving a directory below itself or replacing a directory with a file fails.
+pub fn test() {
+    ONCE.call_once(|| {
+        let _ = mkdir(Path::new("/rename/"));
+        let _ = mkdir(Path::new("/rename/dir/"));
+        let a = Path::new("/rename/a");
+        let b = Path::new("/rename/b");
+        let c = Path::new("/rename/dir/c");
+        create(&a, b"a");
+
+        // Within a directory.
+        rename(a.clone(), b.clone(), RenameFlags::empty()).unwrap();
+        assert_eq!(read(&a).err(), Some(Errno::ENOENT));
+        assert_eq!(read(&b), Ok(*b"a"));
+
+        // Across directories.
+        rename(b.clone(), c.clone(), RenameFlags::empty()).unwrap();
+        assert_eq!(read(&b).err(), Some(Errno::ENOENT));
+        assert_eq!(read(&c), Ok(*b"a"));
+
+        // The target is replaced unless RENAME_NOREPLACE.
+        create(&b, b"b");
+        assert_eq!(
+            rename(b.clone(), c.clone(), RenameFlags::RENAME_NOREPLACE).err(),
+            Some(Errno::EEXIST)
+        );
+        assert_eq!(read(&c), Ok(*b"a"));
+
+        // Files are swapped atomically.
+        rename(b.clone(), c.clone(), RenameFlags::RENAME_EXCHANGE).unwrap();
+        assert_eq!(read(&b), Ok(*b"a"));
+        assert_eq!(read(&c), Ok(*b"b"));
+        assert_eq!(
+            rename(a.clone(), c.clone(), RenameFlags::RENAME_EXCHANGE).err(),
+            Some(Errno::ENOENT)
+        );
+        rename(b.clone(), c.clone(), RenameFlags::RENAME_EXCHANGE).unwrap();
+        assert_eq!(read(&b), Ok(*b"b"));
+        assert_eq!(read(&c), Ok(*b"a"));
+
+        // A directory is never moved below itself, while renaming it to itself does
+        // nothing.
+        assert_eq!(
+            rename(
+                Path::new("/rename/"),
+                Path::new("/rename/dir/sub"),
+                RenameFlags::empty()
+            )
+            .err(),
+            Some(Errno::EINVAL)
+        );
+        rename(
+            Path::new("/rename/dir/"),
+            Path::new("/rename/dir/"),
+            RenameFlags::empty(),
+        )
+        .unwrap();
+
+        // Files and directories never replace each other.
+        assert_eq!(
+            rename(b.clone(), Path::new("/rename/dir"), RenameFlags::empty()).err(),
+            Some(Errno::EISDIR)
+        );
+        assert_eq!(
+            rename(Path::new("/rename/dir/"), b.clone(), RenameFlags::empty()).err(),
+            Some(Errno::ENOTDIR)
+        );
+
+        rename(b.clone(), c.clone(), RenameFlags::empty()).unwrap();
+        assert_eq!(read(&c), Ok(*b"b"));
+        debug!("RENAME {:?}", c);
+
+        unlink(c).unwrap();
+    });
+}
@@ -0,0 +1,122 @@
+use alloc::sync::Arc;
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::AT_FDCWD;
+use vfs::{add_link, remove_link, File, OpenFlags, Path, StatMode, VFS};
+
+use crate::{
+    fs::{mkdir, mount, open, resolve_path, umount, unlink, ResolveFlags},
+    task::cpu,
+};
+
+/// A filesystem with nothing in it, only used to find out the resolved filesystem.
+struct NullFs;
+
+impl VFS for NullFs {
+    fn open(&self, _pdir: &Path, _name: &str, _flags: OpenFlags) -> Result<Arc<dyn File>, Errno> {
+        Err(Errno::ENOENT)
+    }
+
+    fn mkdir(&self, _pdir: &Path, _name: &str, _mode: StatMode) -> Result<(), Errno> {
+        Err(Errno::EROFS)
+    }
+
+    fn check(&self, _path: &Path) -> bool {
+        false
+    }
+
+    fn remove(&self, _pdir: &Path, _name: &str) -> Result<(), Errno> {
+        Err(Errno::ENOENT)
+    }
+
+    fn rename(
+        &self,
+        _old_pdir: &Path,
+        _old_name: &str,
+        _new_pdir: &Path,
+        _new_name: &str,
+    ) -> Result<(), Errno> {
+        Err(Errno::ENOENT)
+    }
+}
+
+/// Compares the data pointers only, since vtables may be duplicated.
+fn same_fs(a: &Arc<dyn VFS>, b: &Arc<dyn VFS>) -> bool {
+    Arc::as_ptr(a) as *const u8 == Arc::as_ptr(b) as *const u8
+}
+
+static ONCE: Once = Once::new();
+
+/// Resolves paths relative to the working directory, a directory file descriptor and
+/// the root directory, with and without following links, and into a mounted filesystem.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let _ = mkdir(Path::new("/resolve/"));
+        let file = Path::new("/resolve/hello");
+        open(file.clone(), OpenFlags::O_CREAT | OpenFlags::O_WRONLY).unwrap();
+
+        let (_, path) =
+            resolve_path(&curr, AT_FDCWD, "/resolve/./hello", ResolveFlags::empty()).unwrap();
+        assert_eq!(path, file);
+
+        // Relative to the working directory.
+        let old_cwd = core::mem::replace(&mut curr.fs_info.lock().cwd, Path::new("/resolve/"));
+        let (_, path) = resolve_path(&curr, AT_FDCWD, "hello", ResolveFlags::empty()).unwrap();
+        assert_eq!(path, file);
+        curr.fs_info.lock().cwd = old_cwd;
+
+        // Relative to a directory file descriptor, which must be a directory.
+        let dir = open(Path::new("/resolve/"), OpenFlags::O_RDONLY).unwrap();
+        let dirfd = curr.files().push(dir).unwrap();
+        let (_, path) =
+            resolve_path(&curr, dirfd, "../resolve/hello", ResolveFlags::empty()).unwrap();
+        assert_eq!(path, file);
+        let filefd = curr
+            .files()
+            .push(open(file.clone(), OpenFlags::O_RDONLY).unwrap())
+            .unwrap();
+        assert_eq!(
+            resolve_path(&curr, filefd, "hello", ResolveFlags::empty()).err(),
+            Some(Errno::ENOTDIR)
+        );
+        curr.files().remove(dirfd).unwrap();
+        curr.files().remove(filefd).unwrap();
+
+        // Below the root directory.
+        let old_root = core::mem::replace(&mut curr.fs_info.lock().root, Path::new("/resolve/"));
+        let (_, path) = resolve_path(&curr, AT_FDCWD, "/../hello", ResolveFlags::empty()).unwrap();
+        assert_eq!(path, file);
+        curr.fs_info.lock().root = old_root;
+
+        // The link in the last component is not followed with NOFOLLOW.
+        let link = Path::new("/resolve/link");
+        add_link(&file, &link);
+        let (_, path) =
+            resolve_path(&curr, AT_FDCWD, "/resolve/link", ResolveFlags::empty()).unwrap();
+        assert_eq!(path, file);
+        let (_, path) =
+            resolve_path(&curr, AT_FDCWD, "/resolve/link", ResolveFlags::NOFOLLOW).unwrap();
+        assert_eq!(path, link);
+        remove_link(&link);
+
+        // Paths below a mount point are dispatched to the mounted filesystem, while the
+        // mount point itself lives in the parent filesystem.
+        let mount_point = Path::new("/resolve/mnt/");
+        let _ = mkdir(mount_point.clone());
+        let null: Arc<dyn VFS> = Arc::new(NullFs);
+        mount(mount_point.clone(), null.clone()).unwrap();
+        let (fs, _) =
+            resolve_path(&curr, AT_FDCWD, "/resolve/mnt/hello", ResolveFlags::empty()).unwrap();
+        assert!(same_fs(&fs, &null));
+        let (fs, _) =
+            resolve_path(&curr, AT_FDCWD, "/resolve/mnt/", ResolveFlags::empty()).unwrap();
+        assert!(!same_fs(&fs, &null));
+        assert!(open(Path::new("/resolve/mnt/hello"), OpenFlags::O_RDONLY).is_err());
+        umount(&mount_point).unwrap();
+        debug!("RESOLVE {:?}", file);
+
+        unlink(file).unwrap();
+    });
+}
@@ -0,0 +1,132 @@
+use alloc::sync::Arc;
+use core::mem::size_of;
+use errno::Errno;
+use log::debug;
+use signal_defs::{SigAction, SigActionFlags, SIGUSR1, SIG_IGN};
+use syscall_interface::SyscallFile;
+
+use super::{run_user_code, spawn_threads, wait_exited, RECORD};
+use crate::{
+    arch::{
+        mm::{VirtAddr, PAGE_SIZE},
+        trap::{TrapFrame, UserContext},
+    },
+    fs::Pipe,
+    read_user,
+    syscall::SyscallImpl,
+    task::{cpu, do_signal, SignalFrame},
+    write_user,
+};
+
+/// Address of the signal handler, which is never called.
+const HANDLER: usize = 0x1000;
+
+/// Offset of the buffer read into.
+const BUF: usize = RECORD + 2 * size_of::<usize>();
+
+/// Thread body in user space, waiting until the reader is about to block and then
+/// sending `SIGUSR1` to it with `tkill`.
+#[naked]
+unsafe extern "C" fn thread_body() {
+    core::arch::asm!(
+        "1:",
+        "ld t0, 0(tp)",
+        "beqz t0, 1b",
+        // SyscallNO::TKILL
+        "li a7, 130",
+        "ld a0, 8(tp)",
+        "li a1, {sig}",
+        "ecall",
+        // SyscallNO::EXIT
+        "li a7, 93",
+        "li a0, 0",
+        "ecall",
+        sig = const SIGUSR1,
+        options(noreturn),
+    );
+}
+
+/// Interrupts a read blocked on an empty pipe with a signal sent by another thread, as
+/// if the task traps into the syscall and returns to user.
+///
+/// The read is issued again after a handler established with `SA_RESTART` returns,
+/// or returns `EINTR` with other handlers.
+pub fn test() {
+    let curr = cpu().curr.as_ref().unwrap();
+    let saved = *curr.trapframe();
+    let blocked = curr.locked_inner().sig_blocked;
+    let ecall = saved.get_epc();
+
+    let test = |start: VirtAddr| -> Result<(), Errno> {
+        let (pipe_read, pipe_write) = Pipe::new();
+        let fd = curr.files().push(Arc::new(pipe_read))?;
+
+        // Returns the trapframe to restore when the handler returns.
+        let interrupt = |handler: usize, flags: SigActionFlags| -> Result<TrapFrame, Errno> {
+            // An ignored signal is not sent, thus the action is changed after sent.
+            curr.sig_actions.lock()[SIGUSR1 - 1] = SigAction {
+                handler: HANDLER,
+                flags,
+                ..SigAction::default()
+            };
+            curr.locked_inner().sig_blocked.unset(SIGUSR1 - 1);
+
+            // The thread starts from the body with its stack in the second page.
+            let record = [0, curr.tid.0];
+            write_user!(curr.mm(), start + RECORD, record, [usize; 2])?;
+            let tid = spawn_threads(start, start + RECORD, &[start.value() + 2 * PAGE_SIZE])?[0];
+
+            let trapframe = curr.trapframe();
+            *trapframe = saved;
+            trapframe.set_epc(ecall);
+            trapframe.next_epc();
+            trapframe.set_a0(fd);
+            // Kernel runs with interrupts disabled, thus the thread sends the signal
+            // only after this task sleeps, unless it runs on another hart.
+            write_user!(curr.mm(), start + RECORD, 1usize, usize)?;
+            assert_eq!(
+                SyscallImpl::read(fd, (start.value() + BUF) as *mut u8, 1),
+                Err(Errno::ERESTART)
+            );
+            wait_exited(tid);
+
+            curr.sig_actions.lock()[SIGUSR1 - 1].handler = handler;
+            do_signal(true)?;
+            if handler == SIG_IGN {
+                return Ok(*trapframe);
+            }
+            assert_eq!(trapframe.get_epc(), HANDLER);
+            let mut frame = SignalFrame {
+                context: UserContext::default(),
+                blocked,
+            };
+            read_user!(
+                curr.mm(),
+                VirtAddr::from(trapframe.get_sp()),
+                frame,
+                SignalFrame
+            )?;
+            let mut restored = saved;
+            restored.restore(&frame.context);
+            Ok(restored)
+        };
+
+        let restored = interrupt(HANDLER, SigActionFlags::SA_RESTART)?;
+        assert_eq!(restored.get_epc(), ecall);
+        assert_eq!(restored.get_a0(), fd);
+
+        let restored = interrupt(HANDLER, SigActionFlags::empty())?;
+        assert_eq!(restored.get_epc(), ecall + 4);
+        assert_eq!(restored.get_a0(), Errno::EINTR.as_ret());
+
+        // Restarted without calling any handler.
+        let restored = interrupt(SIG_IGN, SigActionFlags::empty())?;
+        assert_eq!(restored.get_epc(), ecall);
+        debug!("RESTART {:#x}", restored.get_epc());
+
+        drop(pipe_write);
+        curr.files().remove(fd)?;
+        Ok(())
+    };
+    run_user_code(&[thread_body], 2 * PAGE_SIZE, test).unwrap();
+}
@@ -0,0 +1,86 @@
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{Rlimit, SyscallFile, SyscallProc, AT_FDCWD, F_DUPFD, RLIMIT_NOFILE};
+use vfs::{OpenFlags, Path};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    fs::unlink,
+    mm::VMFlags,
+    read_user,
+    syscall::SyscallImpl,
+    task::cpu,
+    write_user,
+};
+
+static ONCE: Once = Once::new();
+
+/// Lowers the soft limit of file descriptors to the lowest unused one, so that the
+/// next `open` fails, and tries to raise the hard limit.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let test = |buf: VirtAddr| -> Result<(), Errno> {
+            let limit_addr = buf;
+            let path_addr = buf + core::mem::size_of::<Rlimit>();
+
+            let prlimit = |rlimit: Rlimit| {
+                write_user!(curr.mm(), limit_addr, rlimit, Rlimit)?;
+                SyscallImpl::prlimit64(0, RLIMIT_NOFILE, limit_addr.value(), 0)
+            };
+            curr.mm().copy_to_user(path_addr, b"/rlimit\0")?;
+            let open = || {
+                SyscallImpl::openat(
+                    AT_FDCWD,
+                    path_addr.value() as *const u8,
+                    (OpenFlags::O_CREAT | OpenFlags::O_RDWR).bits() as usize,
+                    0o644,
+                )
+            };
+
+            SyscallImpl::prlimit64(0, RLIMIT_NOFILE, 0, limit_addr.value())?;
+            let mut old = Rlimit::default();
+            read_user!(curr.mm(), limit_addr, old, Rlimit)?;
+            assert_eq!(old.rlim_cur as usize, curr.files().get_limit());
+
+            // File descriptors below the limit are all in use.
+            let lowest = curr.files().dup(0)?;
+            curr.files().remove(lowest)?;
+            prlimit(Rlimit {
+                rlim_cur: lowest as u64,
+                rlim_max: old.rlim_max,
+            })?;
+            assert_eq!(open().err(), Some(Errno::EMFILE));
+            assert_eq!(SyscallImpl::dup(0).err(), Some(Errno::EMFILE));
+            // No descriptor can be at or above the limit.
+            assert_eq!(
+                SyscallImpl::fcntl(0, F_DUPFD, lowest).err(),
+                Some(Errno::EINVAL)
+            );
+
+            // The hard limit cannot be raised, and the soft limit cannot exceed it.
+            let raised = Rlimit {
+                rlim_cur: old.rlim_cur,
+                rlim_max: old.rlim_max + 1,
+            };
+            assert_eq!(prlimit(raised).err(), Some(Errno::EPERM));
+            let invalid = Rlimit {
+                rlim_cur: old.rlim_max + 1,
+                rlim_max: old.rlim_max,
+            };
+            assert_eq!(prlimit(invalid).err(), Some(Errno::EINVAL));
+
+            prlimit(old)?;
+            let fd = open()?;
+            assert_eq!(fd, lowest);
+            SyscallImpl::close(fd)?;
+            debug!("RLIMIT_NOFILE {:?}", old);
+            Ok(())
+        };
+        with_scratch(PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test).unwrap();
+
+        unlink(Path::new("/rlimit")).unwrap();
+    });
+}
@@ -0,0 +1,86 @@
+use core::mem::size_of;
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{
+    RobustListHead, SyscallComm, FUTEX_OWNER_DIED, FUTEX_TID_MASK, FUTEX_WAITERS,
+};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    mm::VMFlags,
+    read_user,
+    syscall::SyscallImpl,
+    task::{cpu, exit_robust_list},
+    write_user,
+};
+
+/// Offset of the first entry, followed by the second one.
+const ENTRY: usize = 64;
+
+/// Offset of the futex word relative to each entry.
+const FUTEX_OFFSET: usize = 8;
+
+/// Offset of the head and length returned by `get_robust_list`.
+const RESULT: usize = PAGE_SIZE / 2;
+
+static ONCE: Once = Once::new();
+
+/// Registers a robust list of two locks and reads it back, then walks the list as if
+/// current task exits. Only the lock owned by current task is marked as owner died.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let saved = curr.inner().robust_list;
+
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            let len = size_of::<RobustListHead>();
+            let head = RobustListHead {
+                list: start.value() + ENTRY,
+                futex_offset: FUTEX_OFFSET as isize,
+                list_op_pending: 0,
+            };
+            write_user!(curr.mm(), start, head, RobustListHead)?;
+            let tid = curr.tid.0 as u32;
+            let owned = [start.value() + 2 * ENTRY, (tid | FUTEX_WAITERS) as usize];
+            let other = [start.value(), ((tid + 1) & FUTEX_TID_MASK) as usize];
+            write_user!(curr.mm(), start + ENTRY, owned, [usize; 2])?;
+            write_user!(curr.mm(), start + 2 * ENTRY, other, [usize; 2])?;
+
+            assert_eq!(
+                SyscallImpl::set_robust_list(start.value(), len - 1),
+                Err(Errno::EINVAL)
+            );
+            assert_eq!(SyscallImpl::set_robust_list(start.value(), len), Ok(0));
+            let result = start.value() + RESULT;
+            assert_eq!(
+                SyscallImpl::get_robust_list(0, result, result + size_of::<usize>()),
+                Ok(0)
+            );
+            let mut value = [0usize; 2];
+            read_user!(curr.mm(), VirtAddr::from(result), value, [usize; 2])?;
+            assert_eq!(value, [start.value(), len]);
+            assert_eq!(
+                SyscallImpl::get_robust_list(curr.tid.0, 0, 0),
+                Err(Errno::EFAULT)
+            );
+
+            exit_robust_list()?;
+            let mut word = 0u32;
+            read_user!(curr.mm(), start + ENTRY + FUTEX_OFFSET, word, u32)?;
+            assert_eq!(word, FUTEX_WAITERS | FUTEX_OWNER_DIED);
+            read_user!(curr.mm(), start + 2 * ENTRY + FUTEX_OFFSET, word, u32)?;
+            assert_eq!(word, other[1] as u32);
+            debug!("ROBUST_LIST {:#x} walked", start.value());
+            Ok(())
+        };
+        let result = with_scratch(
+            PAGE_SIZE,
+            VMFlags::USER | VMFlags::READ | VMFlags::WRITE,
+            test,
+        );
+        curr.inner().robust_list = saved;
+        result.unwrap();
+    });
+}
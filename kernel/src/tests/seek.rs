@@ -0,0 +1,35 @@
+use log::debug;
+use spin::Once;
+use vfs::{File, OpenFlags, Path, SeekWhence};
+
+use crate::fs::{open, unlink};
+
+static ONCE: Once = Once::new();
+
+/// Seeks for data and holes inside and past the end of a file on disk.
+pub fn test() {
+    ONCE.call_once(|| {
+        let path = Path::new("/seek_data.txt");
+        let file = open(
+            path.clone(),
+            OpenFlags::O_CREAT | OpenFlags::O_TRUNC | OpenFlags::O_RDWR,
+        )
+        .unwrap();
+        assert_eq!(file.write(b"hello, seek"), Some(11));
+
+        assert_eq!(file.seek(4, SeekWhence::Data), Some(4));
+        assert_eq!(file.get_off(), 4);
+        assert_eq!(file.seek(4, SeekWhence::Hole), Some(11));
+        assert_eq!(file.get_off(), 11);
+
+        // The offset is kept if nothing is found.
+        file.seek(2, SeekWhence::Set);
+        assert_eq!(file.seek(11, SeekWhence::Data), None);
+        assert_eq!(file.seek(12, SeekWhence::Hole), None);
+        assert_eq!(file.get_off(), 2);
+        debug!("SEEK data and hole in {:?}", path);
+
+        drop(file);
+        unlink(path).unwrap();
+    });
+}
@@ -0,0 +1,52 @@
+use alloc::{sync::Arc, vec};
+use log::debug;
+use spin::Once;
+use vfs::{File, OpenFlags, Path, SeekWhence};
+
+use crate::fs::{open, sendfile, unlink, Pipe};
+
+static ONCE: Once = Once::new();
+
+/// Copies a file to another file and to a pipe in the kernel.
+pub fn test() {
+    ONCE.call_once(|| {
+        let src_path = Path::new("/sendfile_src.txt");
+        let dst_path = Path::new("/sendfile_dst.txt");
+        let data = b"hello, sendfile";
+        let flags = OpenFlags::O_CREAT | OpenFlags::O_TRUNC | OpenFlags::O_RDWR;
+        let src = open(src_path.clone(), flags).unwrap();
+        assert_eq!(src.write(data), Some(data.len()));
+        src.seek(0, SeekWhence::Set);
+
+        // File to file, starting at an offset
+        let dst = open(dst_path.clone(), flags).unwrap();
+        let mut off = 7;
+        assert_eq!(
+            sendfile(&dst, &src, &mut off, usize::MAX),
+            Ok(data.len() - 7)
+        );
+        assert_eq!(off, data.len());
+        // The file offset of the input file does not move.
+        assert_eq!(src.get_off(), 0);
+        let mut buf = vec![0u8; data.len()];
+        assert_eq!(dst.read_at_off(0, &mut buf), Some(data.len() - 7));
+        assert_eq!(&buf[..data.len() - 7], &data[7..]);
+
+        // File to pipe, limited by count
+        let (pipe_read, pipe_write) = Pipe::new();
+        let pipe_write: Arc<dyn File> = Arc::new(pipe_write);
+        let mut off = 0;
+        assert_eq!(sendfile(&pipe_write, &src, &mut off, 5), Ok(5));
+        assert_eq!(pipe_read.read(&mut buf[..5]), Some(5));
+        assert_eq!(&buf[..5], &data[..5]);
+        debug!(
+            "SENDFILE read {:?}",
+            core::str::from_utf8(&buf[..5]).unwrap()
+        );
+
+        drop(src);
+        drop(dst);
+        unlink(src_path).unwrap();
+        unlink(dst_path).unwrap();
+    });
+}
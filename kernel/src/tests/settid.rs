@@ -0,0 +1,81 @@
+use errno::Errno;
+use log::debug;
+use signal_defs::{SIGCHLD, SIGKILL};
+use syscall_interface::SyscallProc;
+
+use super::{clone_at, run_user_code, wait_exited, CODE_SIZE, RECORD};
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    read_user,
+    syscall::SyscallImpl,
+    task::{cpu, do_clone, find_task, send_signal_thread, CloneFlags},
+    write_user,
+};
+
+/// Offset of the tid written in the parent.
+const PTID: usize = RECORD;
+
+/// Offset of the tid written in the child.
+const CTID: usize = PTID + 4;
+
+/// Thread in user space exiting at once.
+#[naked]
+unsafe extern "C" fn exit_body() {
+    core::arch::asm!(
+        // SyscallNO::EXIT
+        "li a7, 93",
+        "li a0, 0",
+        "ecall",
+        options(noreturn),
+    );
+}
+
+/// Child process in user space spinning until killed.
+#[naked]
+unsafe extern "C" fn spin_body() {
+    core::arch::asm!("1:", "j 1b", options(noreturn));
+}
+
+/// `CLONE_PARENT_SETTID` and `CLONE_CHILD_SETTID` store the tid of a thread at both
+/// addresses of the shared memory, while a forked child stores it in its own copy.
+pub fn test() {
+    let curr = cpu().curr.as_ref().unwrap();
+
+    let test = |start: VirtAddr| -> Result<(), Errno> {
+        let settid = CloneFlags::CLONE_PARENT_SETTID | CloneFlags::CLONE_CHILD_SETTID;
+        let clone = |entry: VirtAddr, flags: CloneFlags| -> Result<usize, Errno> {
+            write_user!(curr.mm(), start + PTID, [0i32; 2], [i32; 2])?;
+            clone_at(entry, || {
+                do_clone(flags | settid, 0, 0, start + PTID, start + CTID)
+            })
+        };
+        let mut tids = [0i32; 2];
+
+        // Both addresses are in the memory shared with the thread.
+        let tid = clone(
+            start,
+            CloneFlags::CLONE_VM | CloneFlags::CLONE_SIGHAND | CloneFlags::CLONE_THREAD,
+        )?;
+        read_user!(curr.mm(), start + PTID, tids, [i32; 2])?;
+        assert_eq!(tids, [tid as i32; 2]);
+        wait_exited(tid);
+
+        // The child writes its copy of the memory.
+        let pid = clone(
+            start + CODE_SIZE,
+            CloneFlags::from_bits_truncate(SIGCHLD as u32),
+        )?;
+        read_user!(curr.mm(), start + PTID, tids, [i32; 2])?;
+        assert_eq!(tids, [pid as i32, 0]);
+        let child = find_task(pid).unwrap();
+        read_user!(child.mm(), start + PTID, tids, [i32; 2])?;
+        assert_eq!(tids, [0, pid as i32]);
+        drop(child);
+
+        send_signal_thread(None, pid, SIGKILL)?;
+        assert_eq!(SyscallImpl::wait4(pid as isize, 0, 0, 0), Ok(pid));
+        debug!("SETTID thread {} and child {}", tid, pid);
+        Ok(())
+    };
+    run_user_code(&[exit_body, spin_body], PAGE_SIZE, test).unwrap();
+}
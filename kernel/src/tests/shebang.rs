@@ -0,0 +1,83 @@
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use vfs::{OpenFlags, Path};
+
+use super::elf::{Elf, InitStack, CODE, EXIT_CODE};
+use crate::{
+    arch::mm::VirtAddr,
+    config::ELF_BASE_RELOCATE,
+    error::{KernelError, KernelResult},
+    fs::{open, unlink},
+    loader::from_args,
+};
+
+/// Directory of the files.
+const DIR: &str = "/tmp";
+
+/// Interpreter of the script.
+const INTERP: &str = "/tmp/shebang.elf";
+
+/// Script run by the interpreter with an argument.
+const SCRIPT: &str = "/tmp/shebang.sh";
+
+/// Script interpreted by itself.
+const LOOP: &str = "/tmp/shebang_loop.sh";
+
+/// Script with a line too long.
+const LONG: &str = "/tmp/shebang_long.sh";
+
+static ONCE: Once = Once::new();
+
+/// Runs a script through the interpreter with the path of the script and the
+/// arguments appended, and rejects a script interpreted by itself or with a line
+/// too long.
+pub fn test() {
+    ONCE.call_once(|| {
+        let create = |path: &str, data: &[u8]| {
+            let flags = OpenFlags::O_CREAT | OpenFlags::O_TRUNC | OpenFlags::O_WRONLY;
+            let file = open(Path::new(path), flags).unwrap();
+            assert_eq!(file.write(data), Some(data.len()));
+        };
+        let interp = Elf {
+            code: &EXIT_CODE,
+            ..Elf::default()
+        }
+        .build();
+        create(INTERP, &interp);
+        create(SCRIPT, b"#! /tmp/shebang.elf  -x -y \nexit 0\n");
+        create(LOOP, b"#!/tmp/shebang_loop.sh\n");
+        create(LONG, &[b"#!/".as_slice(), &[b'a'; 256]].concat());
+
+        let test = || -> KernelResult {
+            let run = |name: &str| {
+                let args = vec![name.to_string(), String::from("arg")];
+                from_args(String::from(DIR), args, Vec::new())
+            };
+
+            let task = run("shebang.sh")?;
+            assert_eq!(task.mm().entry, VirtAddr::from(ELF_BASE_RELOCATE + CODE));
+            let sp = VirtAddr::from(task.trapframe().get_sp());
+            let stack = InitStack::parse(&mut task.mm(), sp)?;
+            assert_eq!(stack.args, [INTERP, "-x -y", SCRIPT, "arg"]);
+            drop(task);
+
+            let enoexec = Err(KernelError::Errno(Errno::ENOEXEC));
+            assert_eq!(run("shebang_long.sh").map(|_| ()), enoexec);
+            let eloop = Err(KernelError::Errno(Errno::ELOOP));
+            assert_eq!(run("shebang_loop.sh").map(|_| ()), eloop);
+            debug!("SHEBANG {} run by {}", SCRIPT, INTERP);
+            Ok(())
+        };
+        let result = test();
+        for path in [INTERP, SCRIPT, LOOP, LONG] {
+            unlink(Path::new(path)).unwrap();
+        }
+        result.unwrap();
+    });
+}
@@ -0,0 +1,128 @@
+use core::sync::atomic::{AtomicUsize, Ordering};
+use errno::Errno;
+use log::debug;
+use signal_defs::{SigAction, SigActionFlags, SigAltStack, SigInfo, SIGSEGV, SIGUSR1};
+use spin::Once;
+use syscall_interface::SyscallComm;
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    config::SIGRETURN_VA,
+    mm::VMFlags,
+    syscall::SyscallImpl,
+    task::{cpu, do_signal},
+};
+
+/// Address of the signal handler, which is never called.
+const HANDLER: usize = 0x1000;
+
+/// Stack pointer of the task when the frame cannot be written, which is unmapped.
+const BAD_SP: usize = 0x2000;
+
+/// Number of signals handled.
+static HANDLED: AtomicUsize = AtomicUsize::new(0);
+
+static ONCE: Once = Once::new();
+
+/// Delivers a signal and returns from the handler through `sigreturn`, as if the task
+/// returns to user and the handler returns to [`SIGRETURN_VA`].
+///
+/// The same signal raised during the handler is blocked until `sigreturn` restores the
+/// signal mask, and the interrupted context is resumed after both handlers return.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let saved = *curr.trapframe();
+        let blocked = curr.locked_inner().sig_blocked;
+        let action = curr.sig_actions.lock()[SIGUSR1 - 1];
+
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            let raise = || {
+                curr.locked_inner().sig_pending.add(SigInfo {
+                    signo: SIGUSR1 as i32,
+                    errno: 0,
+                    code: 0,
+                })
+            };
+            // Runs the handler until it returns to the trampoline.
+            let handle = || {
+                let trapframe = curr.trapframe();
+                assert_eq!(trapframe.get_epc(), HANDLER);
+                assert_eq!(trapframe.get_a0(), SIGUSR1);
+                assert_eq!(trapframe.get_ra(), SIGRETURN_VA);
+                assert!(curr.locked_inner().sig_blocked.get(SIGUSR1 - 1));
+                HANDLED.fetch_add(1, Ordering::Relaxed);
+                trapframe.set_a0(0);
+                trapframe.set_ra(0);
+                trapframe.set_epc(SIGRETURN_VA + 4);
+            };
+
+            curr.sig_actions.lock()[SIGUSR1 - 1] = SigAction {
+                handler: HANDLER,
+                ..SigAction::default()
+            };
+            curr.locked_inner().sig_blocked.unset(SIGUSR1 - 1);
+            let trapframe = curr.trapframe();
+            trapframe.set_sp(start.value() + PAGE_SIZE);
+            trapframe.set_epc(start.value());
+            trapframe.set_a0(start.value());
+            let interrupted = *trapframe;
+
+            raise();
+            do_signal(false)?;
+            assert_ne!(trapframe.get_sp(), interrupted.get_sp());
+            // Blocked until the first handler returns.
+            raise();
+            do_signal(false)?;
+            handle();
+            assert_eq!(HANDLED.load(Ordering::Relaxed), 1);
+
+            let ret = SyscallImpl::sigreturn()?;
+            trapframe.set_a0(ret);
+            assert_eq!(trapframe.get_sp(), interrupted.get_sp());
+            assert!(!curr.locked_inner().sig_blocked.get(SIGUSR1 - 1));
+            do_signal(false)?;
+            handle();
+            assert_eq!(HANDLED.load(Ordering::Relaxed), 2);
+
+            let ret = SyscallImpl::sigreturn()?;
+            trapframe.set_a0(ret);
+            assert_eq!(trapframe.get_epc(), interrupted.get_epc());
+            assert_eq!(trapframe.get_sp(), interrupted.get_sp());
+            assert_eq!(trapframe.get_a0(), interrupted.get_a0());
+            assert!(!curr.locked_inner().sig_blocked.get(SIGUSR1 - 1));
+
+            // `SIGSEGV` is delivered on the alternate stack instead of a signal whose
+            // frame cannot be written to the bad stack.
+            let segv = curr.sig_actions.lock()[SIGSEGV - 1];
+            curr.sig_actions.lock()[SIGSEGV - 1] = SigAction {
+                handler: HANDLER,
+                flags: SigActionFlags::SA_ONSTACK,
+                ..SigAction::default()
+            };
+            curr.inner().sig_altstack = SigAltStack {
+                sp: start.value(),
+                flags: 0,
+                size: PAGE_SIZE,
+            };
+            trapframe.set_sp(BAD_SP);
+            raise();
+            let result = do_signal(false);
+            curr.sig_actions.lock()[SIGSEGV - 1] = segv;
+            curr.inner().sig_altstack = SigAltStack::new();
+            result?;
+            assert_eq!(trapframe.get_epc(), HANDLER);
+            assert_eq!(trapframe.get_a0(), SIGSEGV);
+            assert!(trapframe.get_sp() > start.value());
+            assert!(curr.locked_inner().sig_blocked.get(SIGSEGV - 1));
+            debug!("SIGRETURN {:#x}", interrupted.get_epc());
+            Ok(())
+        };
+        let result = with_scratch(PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test);
+        curr.sig_actions.lock()[SIGUSR1 - 1] = action;
+        curr.locked_inner().sig_blocked = blocked;
+        *curr.trapframe() = saved;
+        result.unwrap();
+    });
+}
@@ -0,0 +1,61 @@
+use alloc::vec;
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use vfs::{File, OpenFlags, Path, SeekWhence};
+
+use crate::fs::{open, unlink, Pipe};
+
+static ONCE: Once = Once::new();
+
+/// Splices a file into a pipe, then reads it out from the other end.
+///
+/// The data is also duplicated to another pipe by tee before it is read, which
+/// must not consume the data in the source pipe. At last, segments of a buffer are
+/// spliced into a pipe as vmsplice does.
+pub fn test() {
+    ONCE.call_once(|| {
+        let path = Path::new("/splice.txt");
+        let data = b"hello, splice";
+        let file = open(
+            path.clone(),
+            OpenFlags::O_CREAT | OpenFlags::O_TRUNC | OpenFlags::O_RDWR,
+        )
+        .unwrap();
+        assert_eq!(file.write(data), Some(data.len()));
+        file.seek(0, SeekWhence::Set);
+
+        let (pipe_read, pipe_write) = Pipe::new();
+        let count = pipe_write
+            .splice_from(usize::MAX, true, |buf| file.read(buf).ok_or(Errno::EINVAL))
+            .unwrap();
+        assert_eq!(count, data.len());
+        // End of file.
+        assert_eq!(
+            pipe_write.splice_from(usize::MAX, true, |buf| file.read(buf).ok_or(Errno::EINVAL)),
+            Ok(0)
+        );
+
+        let (tee_read, tee_write) = Pipe::new();
+        assert_eq!(pipe_read.tee_to(&tee_write, 5, true), Ok(5));
+        assert_eq!(pipe_read.tee_to(&pipe_write, 5, true), Err(Errno::EINVAL));
+
+        let mut buf = vec![0u8; data.len()];
+        assert_eq!(tee_read.read(&mut buf[..5]), Some(5));
+        assert_eq!(&buf[..5], &data[..5]);
+
+        // Source pipe still yields all data.
+        assert_eq!(pipe_read.read(&mut buf), Some(data.len()));
+        assert_eq!(buf.as_slice(), data);
+        debug!("SPLICE read {:?}", core::str::from_utf8(&buf).unwrap());
+
+        let segs: [&[u8]; 2] = [&data[..5], &data[5..]];
+        assert_eq!(pipe_write.splice_segments(&segs, true), Ok(data.len()));
+        buf.fill(0);
+        assert_eq!(pipe_read.read(&mut buf), Some(data.len()));
+        assert_eq!(buf.as_slice(), data);
+
+        drop(file);
+        unlink(path).unwrap();
+    });
+}
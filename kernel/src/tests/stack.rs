@@ -0,0 +1,49 @@
+use log::debug;
+use spin::Once;
+
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    config::USER_STACK_GROW_WINDOW,
+    error::KernelError,
+    mm::{do_handle_page_fault, VMFlags, MM},
+    task::cpu,
+};
+
+static ONCE: Once = Once::new();
+
+/// Grows the user stack of current task by a store fault one page below it, while
+/// faults far below it or beyond the limit are fatal.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let limit = curr.limits.lock().max_stack_size();
+        let mut mm = curr.mm();
+        let sp = VirtAddr::from(curr.trapframe().get_sp());
+        let stack = |mm: &mut MM| {
+            mm.get_vma(sp, |vma, _, _| Ok((vma.start_va, vma.end_va)))
+                .unwrap()
+        };
+        let flags = VMFlags::USER | VMFlags::WRITE;
+        let (start, end) = stack(&mut mm);
+
+        // One page below the stack.
+        let va = start - PAGE_SIZE + 8;
+        do_handle_page_fault(&mut mm, va, flags, limit).unwrap();
+        assert_eq!(stack(&mut mm), (start - PAGE_SIZE, end));
+        assert!(mm.translate(va).is_ok());
+
+        // Far below the stack.
+        let va = start - PAGE_SIZE - USER_STACK_GROW_WINDOW - PAGE_SIZE;
+        assert!(do_handle_page_fault(&mut mm, va, flags, limit).is_err());
+
+        // Beyond the limit.
+        let va = start - 2 * PAGE_SIZE;
+        let limit = (end - (start - PAGE_SIZE)).value();
+        assert_eq!(
+            do_handle_page_fault(&mut mm, va, flags, limit).err(),
+            Some(KernelError::FatalPageFault)
+        );
+        assert_eq!(stack(&mut mm), (start - PAGE_SIZE, end));
+        debug!("STACK grows to {:?}", start - PAGE_SIZE);
+    });
+}
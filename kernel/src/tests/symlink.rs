@@ -0,0 +1,70 @@
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use vfs::{add_symlink, read_symlink, File, OpenFlags, Path};
+
+use crate::fs::{mkdir, open, unlink};
+
+static ONCE: Once = Once::new();
+
+/// Opens a file through symbolic links, then checks `O_NOFOLLOW`, a self-referential
+/// loop and a link to a file used as a directory.
+pub fn test() {
+    ONCE.call_once(|| {
+        let _ = mkdir(Path::new("/symlink/"));
+        let target = Path::new("/symlink/target");
+        let file = open(
+            target.clone(),
+            OpenFlags::O_CREAT | OpenFlags::O_TRUNC | OpenFlags::O_WRONLY,
+        )
+        .unwrap();
+        assert_eq!(file.write(b"hello, symlink"), Some(14));
+        drop(file);
+
+        // A relative target is resolved in the directory of the link.
+        let link = Path::new("/symlink/link");
+        assert!(add_symlink(&link, "target"));
+        assert_eq!(read_symlink(&link).as_deref(), Some("target"));
+        let file = open(link.clone(), OpenFlags::O_RDONLY).unwrap();
+        let mut buf = [0u8; 16];
+        assert_eq!(file.read(&mut buf), Some(14));
+        assert_eq!(&buf[..14], b"hello, symlink");
+
+        // Links in the parent directory are always followed.
+        let dir_link = Path::new("/symlink/dir");
+        assert!(add_symlink(&dir_link, "/symlink/"));
+        assert!(open(Path::new("/symlink/dir/link"), OpenFlags::O_RDONLY).is_ok());
+        assert_eq!(
+            open(
+                Path::new("/symlink/dir/link"),
+                OpenFlags::O_RDONLY | OpenFlags::O_NOFOLLOW
+            )
+            .err(),
+            Some(Errno::ELOOP)
+        );
+
+        let self_loop = Path::new("/symlink/loop");
+        assert!(add_symlink(&self_loop, "loop"));
+        assert_eq!(
+            open(self_loop.clone(), OpenFlags::O_RDONLY).err(),
+            Some(Errno::ELOOP)
+        );
+
+        assert_eq!(
+            open(Path::new("/symlink/link/file"), OpenFlags::O_RDONLY).err(),
+            Some(Errno::ENOTDIR)
+        );
+        debug!(
+            "SYMLINK read {:?}",
+            core::str::from_utf8(&buf[..14]).unwrap()
+        );
+
+        // Symbolic links are removed without the target.
+        unlink(self_loop).unwrap();
+        unlink(dir_link).unwrap();
+        unlink(link.clone()).unwrap();
+        assert_eq!(read_symlink(&link), None);
+        assert!(open(target.clone(), OpenFlags::O_RDONLY).is_ok());
+        unlink(target).unwrap();
+    });
+}
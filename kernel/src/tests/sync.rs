@@ -0,0 +1,28 @@
+use log::debug;
+use spin::Once;
+use vfs::{File, OpenFlags, Path};
+
+use crate::fs::{dirty_blocks, open, sync, unlink};
+
+static ONCE: Once = Once::new();
+
+/// Writes back the blocks of a file on disk cached in memory.
+pub fn test() {
+    ONCE.call_once(|| {
+        let path = Path::new("/sync.txt");
+        let file = open(
+            path.clone(),
+            OpenFlags::O_CREAT | OpenFlags::O_TRUNC | OpenFlags::O_WRONLY,
+        )
+        .unwrap();
+        assert_eq!(file.write(b"hello, sync"), Some(11));
+        drop(file);
+
+        sync().unwrap();
+        assert_eq!(dirty_blocks(), 0);
+        debug!("SYNC {:?}", path);
+
+        unlink(path).unwrap();
+        sync().unwrap();
+    });
+}
@@ -0,0 +1,99 @@
+use errno::Errno;
+use log::debug;
+use signal_defs::SIGCHLD;
+use syscall_interface::SyscallProc;
+
+use super::{clone_at, run_user_code, RECORD};
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    read_user,
+    syscall::SyscallImpl,
+    task::{cpu, do_clone, do_yield, find_task, CloneFlags, TaskState, WaitOptions},
+    write_user,
+};
+
+/// Offset of the flag releasing the thread, pointed to by `tp` of the child.
+const RELEASE: usize = RECORD;
+
+/// Offset of the status word.
+const STATUS: usize = RELEASE + 8;
+
+/// Flags of the thread created by the child.
+const THREAD_FLAGS: usize = (CloneFlags::CLONE_VM.bits()
+    | CloneFlags::CLONE_SIGHAND.bits()
+    | CloneFlags::CLONE_THREAD.bits()) as usize;
+
+/// Child process in user space, whose main thread exits at once after creating a
+/// thread which yields until released.
+#[naked]
+unsafe extern "C" fn child_body() {
+    core::arch::asm!(
+        // SyscallNO::CLONE
+        "li a7, 220",
+        "li a0, {flags}",
+        "li a1, 0",
+        "li a2, 0",
+        "li a3, 0",
+        "li a4, 0",
+        "ecall",
+        "bnez a0, 2f",
+        "1:",
+        "ld t0, 0(tp)",
+        "bnez t0, 2f",
+        // SyscallNO::SCHED_YIELD
+        "li a7, 124",
+        "ecall",
+        "j 1b",
+        "2:",
+        // SyscallNO::EXIT
+        "li a7, 93",
+        "li a0, 0",
+        "ecall",
+        flags = const THREAD_FLAGS,
+        options(noreturn),
+    );
+}
+
+/// The main thread of a child exits before the other thread in its group, while the
+/// child is not reaped until the last thread exits.
+pub fn test() {
+    let curr = cpu().curr.as_ref().unwrap();
+
+    let test = |start: VirtAddr| -> Result<(), Errno> {
+        write_user!(curr.mm(), start + RELEASE, 0usize, usize)?;
+
+        // Shares the memory to release the thread.
+        let pid = clone_at(start, || {
+            do_clone(
+                CloneFlags::CLONE_VM
+                    | CloneFlags::CLONE_SETTLS
+                    | CloneFlags::from_bits_truncate(SIGCHLD as u32),
+                0,
+                start.value() + RELEASE,
+                VirtAddr::zero(),
+                VirtAddr::zero(),
+            )
+        })?;
+
+        let leader = find_task(pid).unwrap();
+        while leader.get_state() != TaskState::ZOMBIE {
+            unsafe { do_yield() };
+        }
+        assert!(!leader.thread_group.is_dead());
+        let wnohang = WaitOptions::WNONHANG.bits() as usize;
+        let status = start.value() + STATUS;
+        assert_eq!(SyscallImpl::wait4(pid as isize, status, wnohang, 0), Ok(0));
+
+        write_user!(curr.mm(), start + RELEASE, 1usize, usize)?;
+        while SyscallImpl::wait4(pid as isize, status, wnohang, 0)? == 0 {
+            unsafe { do_yield() };
+        }
+        assert!(leader.thread_group.is_dead());
+        let mut value = -1i32;
+        read_user!(curr.mm(), VirtAddr::from(status), value, i32)?;
+        assert_eq!(value, 0);
+        debug!("THREAD GROUP {} reaped after the last thread", pid);
+        Ok(())
+    };
+    run_user_code(&[child_body], PAGE_SIZE, test).unwrap();
+}
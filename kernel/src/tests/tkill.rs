@@ -0,0 +1,83 @@
+use errno::Errno;
+use log::debug;
+use signal_defs::{SigAction, SIGUSR1};
+use syscall_interface::SyscallComm;
+
+use super::{run_user_code, spawn_threads, wait_exited, CODE_SIZE, RECORD};
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    read_user,
+    syscall::SyscallImpl,
+    task::cpu,
+};
+
+/// Thread body in user space, spinning until a signal is handled by any thread and
+/// then exiting. Signals are delivered after timer interrupts.
+#[naked]
+unsafe extern "C" fn thread_body() {
+    core::arch::asm!(
+        "1:",
+        "ld t0, 0(tp)",
+        "beqz t0, 1b",
+        // SyscallNO::EXIT
+        "li a7, 93",
+        "li a0, 0",
+        "ecall",
+        options(noreturn),
+    );
+}
+
+/// Signal handler in user space, counting handled signals and recording the thread ID
+/// of the last thread handling it.
+#[naked]
+unsafe extern "C" fn handler() {
+    core::arch::asm!(
+        // SyscallNO::GETTID
+        "li a7, 178",
+        "ecall",
+        "sd a0, 8(tp)",
+        "li t0, 1",
+        "amoadd.d zero, t0, (tp)",
+        "ret",
+        options(noreturn),
+    );
+}
+
+/// Spawns two threads and signals only one of them with `tgkill`, then checks that the
+/// handler only runs in the target thread.
+pub fn test() {
+    let curr = cpu().curr.as_ref().unwrap();
+
+    let test = |start: VirtAddr| -> Result<(), Errno> {
+        curr.sig_actions.lock()[SIGUSR1 - 1] = SigAction {
+            handler: start.value() + CODE_SIZE,
+            ..SigAction::default()
+        };
+        // Threads start from the body with stacks in the second page.
+        let stacks = [3, 4].map(|i| start.value() + i * PAGE_SIZE / 2);
+        let threads = spawn_threads(start, start + RECORD, &stacks)?;
+
+        // Threads not in this group or invalid signals.
+        assert_eq!(
+            SyscallImpl::tgkill(curr.pid + 1, threads[0], SIGUSR1),
+            Err(Errno::ESRCH)
+        );
+        assert_eq!(
+            SyscallImpl::tgkill(curr.pid, threads[0], 65),
+            Err(Errno::EINVAL)
+        );
+        assert_eq!(SyscallImpl::tgkill(curr.pid, threads[0], 0), Ok(0));
+        assert_eq!(SyscallImpl::tgkill(curr.pid, threads[1], SIGUSR1), Ok(0));
+
+        for &tid in threads.iter() {
+            wait_exited(tid);
+        }
+        let mut record = [0usize; 2];
+        read_user!(curr.mm(), start + RECORD, record, [usize; 2])?;
+        assert_eq!(record, [1, threads[1]]);
+        assert_eq!(SyscallImpl::tkill(threads[1], SIGUSR1), Err(Errno::ESRCH));
+        debug!("TKILL {:?} handled by {}", threads, record[1]);
+        Ok(())
+    };
+    run_user_code(&[thread_body, handler], 2 * PAGE_SIZE, test).unwrap();
+}
@@ -0,0 +1,52 @@
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use vfs::{File, OpenFlags, Path, SeekWhence};
+
+use crate::fs::{mkdir, open};
+
+static ONCE: Once = Once::new();
+
+/// Writes and reads back an unnamed file, which is absent from its directory.
+pub fn test() {
+    ONCE.call_once(|| {
+        let dir = Path::new("/tmpfile/");
+        let _ = mkdir(dir.clone());
+
+        let file = open(dir.clone(), OpenFlags::O_TMPFILE | OpenFlags::O_RDWR).unwrap();
+        assert!(file.is_reg());
+        assert_eq!(file.write(b"hello, tmpfile"), Some(14));
+        assert_eq!(file.get_size(), Some(14));
+        file.seek(7, SeekWhence::Set);
+        let mut buf = [0u8; 16];
+        assert_eq!(file.read(&mut buf), Some(7));
+        assert_eq!(&buf[..7], b"tmpfile");
+
+        // Gaps are filled with zeros.
+        assert_eq!(file.write_at_off(4096 + 2, b"!"), Some(1));
+        assert_eq!(file.read_at_off(4096, &mut buf), Some(3));
+        assert_eq!(&buf[..3], b"\0\0!");
+
+        let entries = open(dir.clone(), OpenFlags::O_RDONLY)
+            .unwrap()
+            .read_dir()
+            .unwrap();
+        assert!(entries
+            .iter()
+            .all(|entry| entry.name == "." || entry.name == ".."));
+        debug!("TMPFILE in {:?}", dir);
+
+        assert_eq!(
+            open(dir.clone(), OpenFlags::O_TMPFILE | OpenFlags::O_RDONLY).err(),
+            Some(Errno::EINVAL)
+        );
+        assert_eq!(
+            open(
+                Path::new("/tmpfile/none/"),
+                OpenFlags::O_TMPFILE | OpenFlags::O_WRONLY
+            )
+            .err(),
+            Some(Errno::ENOENT)
+        );
+    });
+}
@@ -0,0 +1,53 @@
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use vfs::{File, OpenFlags, Path, Stat, VFS};
+
+use crate::fs::{global_fs, mkdir, open, unlink, TMPFS_DEV};
+
+static ONCE: Once = Once::new();
+
+/// Creates, reads and removes files under `/tmp` in memory without the disk.
+pub fn test() {
+    ONCE.call_once(|| {
+        let path = Path::new("/tmp/hello");
+        let file = open(path.clone(), OpenFlags::O_CREAT | OpenFlags::O_WRONLY).unwrap();
+        assert_eq!(file.write(b"hello, tmpfs"), Some(12));
+        let mut stat = Stat::default();
+        assert!(file.get_stat(&mut stat as *mut Stat));
+        assert_eq!(stat.st_dev, TMPFS_DEV);
+        assert_eq!(stat.st_size, 12);
+        drop(file);
+        assert!(!global_fs().check(&path));
+
+        // Data is shared by open file descriptions.
+        let file = open(path.clone(), OpenFlags::O_RDONLY).unwrap();
+        let mut buf = [0u8; 16];
+        assert_eq!(file.read(&mut buf), Some(12));
+        assert_eq!(&buf[..12], b"hello, tmpfs");
+        debug!("TMPFS read {:?}", core::str::from_utf8(&buf[..12]).unwrap());
+
+        let dir = Path::new("/tmp/dir/");
+        mkdir(dir.clone()).unwrap();
+        assert_eq!(mkdir(dir.clone()).err(), Some(Errno::EEXIST));
+        open(
+            Path::new("/tmp/dir/world"),
+            OpenFlags::O_CREAT | OpenFlags::O_RDWR,
+        )
+        .unwrap();
+        let entries = open(dir.clone(), OpenFlags::O_RDONLY)
+            .unwrap()
+            .read_dir()
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "world");
+        assert_eq!(unlink(dir.clone()).err(), Some(Errno::ENOTEMPTY));
+
+        unlink(Path::new("/tmp/dir/world")).unwrap();
+        unlink(dir.clone()).unwrap();
+        unlink(path.clone()).unwrap();
+        assert_eq!(open(path, OpenFlags::O_RDONLY).err(), Some(Errno::ENOENT));
+        // The file is still readable until closed.
+        assert_eq!(file.read_at_off(0, &mut buf), Some(12));
+    });
+}
@@ -0,0 +1,57 @@
+use core::mem::size_of;
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{SyscallProc, UtsName, UTSNAME_LEN};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    config::UTS_RELEASE,
+    mm::VMFlags,
+    read_user,
+    syscall::SyscallImpl,
+    task::cpu,
+};
+
+static ONCE: Once = Once::new();
+
+/// Gets the system information through a user buffer across two pages.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            let buf = start + (PAGE_SIZE - size_of::<UtsName>() / 2);
+
+            curr.mm().copy_to_user(buf, &[0xff; size_of::<UtsName>()])?;
+            SyscallImpl::uname(buf.value())?;
+            let mut uts = UtsName {
+                sysname: [0; UTSNAME_LEN],
+                nodename: [0; UTSNAME_LEN],
+                release: [0; UTSNAME_LEN],
+                version: [0; UTSNAME_LEN],
+                machine: [0; UTSNAME_LEN],
+                domainname: [0; UTSNAME_LEN],
+            };
+            read_user!(curr.mm(), buf, uts, UtsName)?;
+            assert_eq!(uts.machine, UtsName::field("riscv64"));
+            assert_eq!(uts.release, UtsName::field(UTS_RELEASE));
+            // Every field is terminated, with no garbage left in the tail.
+            for field in [
+                &uts.sysname,
+                &uts.nodename,
+                &uts.release,
+                &uts.version,
+                &uts.machine,
+                &uts.domainname,
+            ] {
+                assert_eq!(field[UTSNAME_LEN - 1], 0);
+                assert!(field.iter().all(|&b| b != 0xff));
+            }
+            debug!("UNAME {:?}", core::str::from_utf8(&uts.sysname));
+            Ok(())
+        };
+        with_scratch(2 * PAGE_SIZE, VMFlags::READ | VMFlags::WRITE, test).unwrap();
+        assert_eq!(SyscallImpl::uname(0).err(), Some(Errno::EFAULT));
+    });
+}
@@ -0,0 +1,112 @@
+use alloc::vec::Vec;
+use core::mem::size_of_val;
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::{IoVec, SyscallComm};
+
+use super::with_scratch;
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    error::KernelError,
+    mm::{do_mprotect, MmapProt, VMFlags},
+    syscall::SyscallImpl,
+    task::cpu,
+    write_user,
+};
+
+static ONCE: Once = Once::new();
+
+/// Pages of the writable mapping, followed by a read-only page and then a hole.
+const PAGES: usize = 6;
+
+/// Copies across page boundaries and through `process_vm_readv` larger than a page,
+/// and fails with `EFAULT` on an unmapped, read-only or overflowing range before any
+/// buffer is allocated.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+
+        let test = |start: VirtAddr| -> Result<(), Errno> {
+            let rdonly = start + PAGES * PAGE_SIZE;
+            do_mprotect(&mut curr.mm(), rdonly, PAGE_SIZE, MmapProt::PROT_READ)?;
+            let efault = Err(KernelError::Errno(Errno::EFAULT));
+            let data: Vec<u8> = (0..2 * PAGE_SIZE).map(|i| (i % 251) as u8).collect();
+
+            // Spans three pages.
+            let va = start + PAGE_SIZE / 2;
+            curr.mm().copy_to_user(va, &data)?;
+            assert_eq!(curr.mm().copy_from_user(va, data.len())?, data);
+            let mut buf = [0u8; 16];
+            curr.mm()
+                .copy_from_user_into(start + PAGE_SIZE - 8, &mut buf)?;
+            assert_eq!(buf[..], data[PAGE_SIZE / 2 - 8..PAGE_SIZE / 2 + 8]);
+
+            // The read-only page is readable but not writable.
+            assert!(curr
+                .mm()
+                .copy_from_user(rdonly, PAGE_SIZE)?
+                .iter()
+                .all(|&b| b == 0));
+            assert_eq!(curr.mm().copy_to_user(rdonly - 8, &buf), efault);
+            assert_eq!(curr.mm().copy_from_user(rdonly - 8, 8)?, [0; 8]);
+
+            // Faults before a buffer of the length is allocated.
+            assert_eq!(
+                curr.mm().copy_from_user(rdonly, 2 * PAGE_SIZE).map(|_| ()),
+                efault
+            );
+            assert_eq!(curr.mm().copy_from_user(start, 1 << 40).map(|_| ()), efault);
+            assert_eq!(
+                curr.mm().copy_from_user(start + 1, usize::MAX).map(|_| ()),
+                efault
+            );
+
+            // Reads the data into two local buffers through the bounce buffer.
+            let iovs = start + 5 * PAGE_SIZE;
+            let local = [
+                IoVec {
+                    iov_base: (start + 3 * PAGE_SIZE).value(),
+                    iov_len: PAGE_SIZE / 2,
+                },
+                IoVec {
+                    iov_base: (start + 4 * PAGE_SIZE - PAGE_SIZE / 2).value(),
+                    iov_len: PAGE_SIZE * 3 / 2,
+                },
+            ];
+            let remote = [IoVec {
+                iov_base: va.value(),
+                iov_len: data.len(),
+            }];
+            write_user!(curr.mm(), iovs, local, [IoVec; 2])?;
+            write_user!(curr.mm(), iovs + size_of_val(&local), remote, [IoVec; 1])?;
+            assert_eq!(
+                SyscallImpl::process_vm_readv(
+                    curr.pid,
+                    iovs.value() as *const IoVec,
+                    2,
+                    (iovs + size_of_val(&local)).value() as *const IoVec,
+                    1,
+                    0,
+                ),
+                Ok(data.len())
+            );
+            let mut read = curr
+                .mm()
+                .copy_from_user(start + 3 * PAGE_SIZE, PAGE_SIZE / 2)?;
+            read.extend(
+                curr.mm()
+                    .copy_from_user(start + 4 * PAGE_SIZE - PAGE_SIZE / 2, PAGE_SIZE * 3 / 2)?,
+            );
+            assert_eq!(read, data);
+            debug!("USER COPY faults without allocation");
+            Ok(())
+        };
+        let result = with_scratch(
+            (PAGES + 1) * PAGE_SIZE,
+            VMFlags::USER | VMFlags::READ | VMFlags::WRITE,
+            test,
+        );
+        result.unwrap();
+    });
+}
@@ -0,0 +1,87 @@
+use errno::Errno;
+use log::debug;
+use spin::Once;
+use syscall_interface::SyscallProc;
+
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    mm::{do_munmap, MmapProt, VMFlags},
+    syscall::SyscallImpl,
+    task::cpu,
+};
+
+/// Number of pages reserved, leaving a hole below and above the merged pages.
+const PAGES: usize = 5;
+
+static ONCE: Once = Once::new();
+
+/// Maps three adjacent pages one by one which collapse to a single area, while areas
+/// with different flags are kept apart.
+pub fn test() {
+    ONCE.call_once(|| {
+        let curr = cpu().curr.as_ref().unwrap();
+        let flags = VMFlags::USER | VMFlags::READ | VMFlags::WRITE;
+        let start = curr
+            .mm()
+            .alloc_vma(
+                VirtAddr::zero(),
+                VirtAddr::from(PAGES * PAGE_SIZE),
+                flags,
+                true,
+                None,
+            )
+            .unwrap();
+        do_munmap(&mut curr.mm(), start, PAGES * PAGE_SIZE).unwrap();
+        let base = start + PAGE_SIZE;
+        let count = curr.mm().map_count();
+        let area = |va: VirtAddr| {
+            curr.mm()
+                .get_vma(va, |vma, _, _| Ok((vma.start_va, vma.end_va)))
+                .unwrap()
+        };
+
+        let test = || -> Result<(), Errno> {
+            for page in 0..3 {
+                let va = base + page * PAGE_SIZE;
+                curr.mm()
+                    .alloc_vma(va, va + PAGE_SIZE, flags, false, None)?;
+            }
+            assert_eq!(curr.mm().map_count(), count + 1);
+            assert_eq!(area(base + PAGE_SIZE), (base, base + 3 * PAGE_SIZE));
+
+            // The middle page is split and merged back.
+            SyscallImpl::mprotect(
+                base.value() + PAGE_SIZE,
+                PAGE_SIZE,
+                MmapProt::PROT_READ.bits(),
+            )?;
+            assert_eq!(curr.mm().map_count(), count + 3);
+            assert_eq!(area(base), (base, base + PAGE_SIZE));
+            SyscallImpl::mprotect(
+                base.value() + PAGE_SIZE,
+                PAGE_SIZE,
+                (MmapProt::PROT_READ | MmapProt::PROT_WRITE).bits(),
+            )?;
+            assert_eq!(curr.mm().map_count(), count + 1);
+            assert_eq!(area(base), (base, base + 3 * PAGE_SIZE));
+
+            // Different flags prevent merging.
+            let va = base + 3 * PAGE_SIZE;
+            curr.mm().alloc_vma(
+                va,
+                va + PAGE_SIZE,
+                VMFlags::USER | VMFlags::READ,
+                false,
+                None,
+            )?;
+            assert_eq!(curr.mm().map_count(), count + 2);
+            assert_eq!(area(va), (va, va + PAGE_SIZE));
+            Ok(())
+        };
+        test().unwrap();
+        debug!("VMA_MERGE {:?}", base);
+
+        do_munmap(&mut curr.mm(), base, 4 * PAGE_SIZE).unwrap();
+        assert_eq!(curr.mm().map_count(), count);
+    });
+}
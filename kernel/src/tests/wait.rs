@@ -0,0 +1,139 @@
+use errno::Errno;
+use log::debug;
+use signal_defs::{SIGCHLD, SIGCONT, SIGKILL, SIGSTOP};
+use syscall_interface::SyscallProc;
+use time_subsys::{Rusage, TimeVal};
+
+use super::{clone_at, run_user_code, CODE_SIZE, RECORD};
+use crate::{
+    arch::mm::{VirtAddr, PAGE_SIZE},
+    read_user,
+    syscall::SyscallImpl,
+    task::{
+        cpu, do_clone, do_yield, find_task, send_signal_thread, CloneFlags, TaskState, WaitOptions,
+    },
+    write_user,
+};
+
+/// Offset of the status word.
+const STATUS: usize = RECORD;
+
+/// Offset of the resource usage.
+const RUSAGE: usize = STATUS + 8;
+
+/// Exit code of the child exiting at once.
+const EXIT_CODE: i32 = 7;
+
+/// Child process in user space exiting at once.
+#[naked]
+unsafe extern "C" fn exit_body() {
+    core::arch::asm!(
+        // SyscallNO::EXIT
+        "li a7, 93",
+        "li a0, {code}",
+        "ecall",
+        code = const EXIT_CODE,
+        options(noreturn),
+    );
+}
+
+/// Child process in user space spinning until killed.
+#[naked]
+unsafe extern "C" fn spin_body() {
+    core::arch::asm!("1:", "j 1b", options(noreturn));
+}
+
+/// Forks two children, one of which exits at once and is reaped by its pid, while the
+/// other is polled with `WNOHANG` before and after it is stopped, continued and killed.
+pub fn test() {
+    let curr = cpu().curr.as_ref().unwrap();
+
+    let test = |start: VirtAddr| -> Result<(), Errno> {
+        let fork = |entry: VirtAddr| {
+            clone_at(entry, || {
+                do_clone(
+                    CloneFlags::from_bits_truncate(SIGCHLD as u32),
+                    0,
+                    0,
+                    VirtAddr::zero(),
+                    VirtAddr::zero(),
+                )
+            })
+        };
+        let exited = fork(start)?;
+        let spinning = fork(start + CODE_SIZE)?;
+        let wnohang = WaitOptions::WNONHANG.bits() as usize;
+        let status = start.value() + STATUS;
+        let rusage = start.value() + RUSAGE;
+
+        // Still running.
+        assert_eq!(
+            SyscallImpl::wait4(spinning as isize, status, wnohang, 0),
+            Ok(0)
+        );
+
+        // Reaped by its pid.
+        write_user!(curr.mm(), VirtAddr::from(rusage), [0xffu8; 32], [u8; 32])?;
+        assert_eq!(
+            SyscallImpl::wait4(exited as isize, status, 0, rusage),
+            Ok(exited)
+        );
+        let mut value = 0i32;
+        read_user!(curr.mm(), VirtAddr::from(status), value, i32)?;
+        assert_eq!(value, EXIT_CODE << 8);
+        let mut usage = Rusage::default();
+        read_user!(curr.mm(), VirtAddr::from(rusage), usage, Rusage)?;
+        assert_eq!(usage.ru_stime, TimeVal::default());
+
+        // Stopped by a signal, which is reported once, and continued.
+        let wuntraced = wnohang | WaitOptions::WUNTRACED.bits() as usize;
+        assert_eq!(
+            SyscallImpl::wait4(spinning as isize, status, wuntraced, 0),
+            Ok(0)
+        );
+        send_signal_thread(None, spinning, SIGSTOP)?;
+        while SyscallImpl::wait4(spinning as isize, status, wuntraced, 0)? == 0 {
+            unsafe { do_yield() };
+        }
+        read_user!(curr.mm(), VirtAddr::from(status), value, i32)?;
+        assert_eq!(value, (SIGSTOP as i32) << 8 | 0x7f);
+        assert_eq!(
+            find_task(spinning).unwrap().locked_inner().state,
+            TaskState::STOPPED
+        );
+        assert_eq!(
+            SyscallImpl::wait4(spinning as isize, status, wuntraced, 0),
+            Ok(0)
+        );
+        send_signal_thread(None, spinning, SIGCONT)?;
+        assert_ne!(
+            find_task(spinning).unwrap().locked_inner().state,
+            TaskState::STOPPED
+        );
+
+        // Killed by a signal, even if stopped.
+        send_signal_thread(None, spinning, SIGSTOP)?;
+        while SyscallImpl::wait4(spinning as isize, status, wuntraced, 0)? == 0 {
+            unsafe { do_yield() };
+        }
+        send_signal_thread(None, spinning, SIGKILL)?;
+        loop {
+            match SyscallImpl::wait4(-1, status, wnohang, 0)? {
+                0 => unsafe { do_yield() },
+                pid => {
+                    assert_eq!(pid, spinning);
+                    break;
+                }
+            }
+        }
+        read_user!(curr.mm(), VirtAddr::from(status), value, i32)?;
+        assert_eq!(value, SIGKILL as i32);
+        assert_eq!(
+            SyscallImpl::wait4(spinning as isize, status, wnohang, 0),
+            Err(Errno::ECHILD)
+        );
+        debug!("WAIT {} exited and {} killed", exited, spinning);
+        Ok(())
+    };
+    run_user_code(&[exit_body, spin_body], PAGE_SIZE, test).unwrap();
+}
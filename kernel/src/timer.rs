@@ -1,8 +1,258 @@
+use alloc::vec::Vec;
+use errno::Errno;
+use kernel_sync::SpinLock;
+use signal_defs::{SIGALRM, SI_USER};
+use spin::Lazy;
+use time_subsys::{Clock, ClockID, ClockType, ITimer, SeqLock, TimeSpec, TimerWheel, NSEC_PER_SEC};
+
 use crate::{
-    arch::timer::{get_time, set_timer},
+    arch::timer::{get_time, get_time_sec_f64, set_timer},
     config::{CLOCK_FREQ, INTR_PER_SEC},
+    task::{
+        cpu, do_sleep, do_wake, find_task, find_threads, queue_signal, signal_pending, TaskState,
+    },
 };
 
 pub fn set_next_trigger() {
     set_timer((get_time() + CLOCK_FREQ / INTR_PER_SEC).try_into().unwrap());
 }
+
+/// Time since boot at the last timer interrupt, together with the clock cycles when
+/// it is updated.
+static TICK_TIME: SeqLock<(TimeSpec, usize)> = SeqLock::new((
+    TimeSpec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    },
+    0,
+));
+
+/// Updates the time at a timer interrupt.
+pub fn update_tick_time() {
+    let now = get_time();
+    TICK_TIME.write(|tick| *tick = (TimeSpec::new(now as f64 / CLOCK_FREQ as f64), now));
+}
+
+/// Gets the time since boot without blocking the timer interrupt, which is the time
+/// at the last tick plus the clock cycles elapsed since then.
+pub fn current_time() -> TimeSpec {
+    let (time, stamp) = TICK_TIME.read();
+    time + TimeSpec::new(get_time().saturating_sub(stamp) as f64 / CLOCK_FREQ as f64)
+}
+
+/// Per-process interval timer counting down in real (i.e., wall clock) time.
+///
+/// The timer expires through [`TIMER_WHEEL`], thus `SIGALRM` is raised even if no
+/// thread of the process is running.
+#[derive(Debug, Default)]
+pub struct RealTimer {
+    /// Interval for periodic timer, or zero for a single-shot timer.
+    interval: TimeSpec,
+
+    /// Time since boot when the timer expires next time, or `None` if disarmed.
+    deadline: Option<TimeSpec>,
+}
+
+impl RealTimer {
+    /// Creates a new disarmed timer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the time until the next expiration at `now`, together with the interval.
+    ///
+    /// An armed timer reports at least 1 microsecond like Linux, even if the deadline
+    /// has passed before the timer is handled.
+    pub fn get(&self, now: TimeSpec) -> ITimer {
+        ITimer {
+            interval: self.interval,
+            value: self.deadline.map_or(TimeSpec::default(), |deadline| {
+                if deadline > now {
+                    deadline - now
+                } else {
+                    TimeSpec::from_nanos(1_000)
+                }
+            }),
+        }
+    }
+}
+
+/// Timers of processes share [`TIMER_WHEEL`] with sleeping tasks, keyed by the pid
+/// with this bit set, which never conflicts with a tid.
+const REAL_TIMER_ID: usize = 1 << (usize::BITS - 1);
+
+/// Arms or disarms the real-time interval timer of the process `pid`, returning the
+/// previous value of the timer.
+pub fn set_real_timer(pid: usize, real: &mut RealTimer, new: ITimer) -> ITimer {
+    let now = TimeSpec::new(get_time_sec_f64());
+    let old = real.get(now);
+    real.interval = new.interval;
+    real.deadline = if new.is_armed() {
+        let deadline = now + new.value;
+        TIMER_WHEEL.lock().add(deadline, REAL_TIMER_ID | pid);
+        Some(deadline)
+    } else {
+        TIMER_WHEEL.lock().cancel(REAL_TIMER_ID | pid);
+        None
+    };
+    old
+}
+
+/// Removes the real-time interval timer of the process `pid` from [`TIMER_WHEEL`]
+/// once the process exits.
+pub fn cancel_real_timer(pid: usize) {
+    TIMER_WHEEL.lock().cancel(REAL_TIMER_ID | pid);
+}
+
+/// Raises `SIGALRM` for the process `pid` whose timer has expired at `now`, and rearms
+/// the timer from its interval.
+///
+/// Expirations overrun before the timer is handled are coalesced, just like a pending
+/// signal. The process may be running on another hart than the one handling the timer
+/// interrupt, thus `SIGALRM` is queued through [`queue_signal`] under its task lock.
+fn expire_real_timer(pid: usize, now: TimeSpec) {
+    let task = match find_task(pid) {
+        Some(task) => task,
+        None => return,
+    };
+    {
+        let mut real = task.real_timer.lock();
+        // The timer may be rearmed after expired.
+        match real.deadline {
+            Some(deadline) if deadline <= now => {
+                real.deadline = if real.interval.is_zero() {
+                    None
+                } else {
+                    let interval = real.interval.as_nanos();
+                    let overrun = (now - deadline).as_nanos() % interval;
+                    let next = now + TimeSpec::from_nanos(interval - overrun);
+                    TIMER_WHEEL.lock().add(next, REAL_TIMER_ID | pid);
+                    Some(next)
+                };
+            }
+            _ => return,
+        }
+    }
+    queue_signal(&task, SIGALRM, SI_USER);
+}
+
+/// Pending wakeups of sleeping tasks, keyed by task identification.
+pub static TIMER_WHEEL: Lazy<SpinLock<TimerWheel>> = Lazy::new(|| SpinLock::new(TimerWheel::new()));
+
+/// Wakes up the tasks whose timeout has expired, and raises `SIGALRM` for the
+/// processes whose real-time interval timer has expired.
+pub fn wake_expired() {
+    let now = TimeSpec::new(get_time_sec_f64());
+    let expired = TIMER_WHEEL.lock().expire(now);
+    for id in expired {
+        if id & REAL_TIMER_ID != 0 {
+            expire_real_timer(id & !REAL_TIMER_ID, now);
+        } else if let Some(task) = find_task(id) {
+            // The task may not be in the run queue yet if it is still switching out.
+            do_wake(&task);
+        }
+    }
+}
+
+/// Current task sleeps until `deadline`, measured by the time since boot.
+///
+/// Returns the remaining time if woken up early by a pending signal not blocked, which
+/// also removes the timeout from [`TIMER_WHEEL`].
+pub fn do_nanosleep(deadline: TimeSpec) -> Option<TimeSpec> {
+    let curr = cpu().curr.as_ref().unwrap();
+    let tid = curr.tid.0;
+    loop {
+        let now = TimeSpec::new(get_time_sec_f64());
+        if now >= deadline {
+            return None;
+        }
+
+        // Sleeps before the check, thus a signal queued after it wakes the task up.
+        curr.locked_inner().state = TaskState::INTERRUPTIBLE;
+        if signal_pending() {
+            curr.locked_inner().state = TaskState::RUNNING;
+            return Some(deadline - now);
+        }
+        TIMER_WHEEL.lock().add(deadline, tid);
+        unsafe { do_sleep() };
+        TIMER_WHEEL.lock().cancel(tid);
+    }
+}
+
+/// Converts clock cycles to time.
+pub fn cycles_to_timespec(cycles: usize) -> TimeSpec {
+    TimeSpec::new(cycles as f64 / CLOCK_FREQ as f64)
+}
+
+/// Clocks served by the kernel.
+///
+/// Both functions return 0 on success, or an [`Errno`] otherwise.
+pub struct KernelClock;
+
+impl Clock for KernelClock {
+    /// Clocks measured by the time since boot are read from the timer ticking at
+    /// [`CLOCK_FREQ`], thus the resolution is a tick rounded up to nanoseconds. CPU
+    /// clocks report 1 ns like Linux.
+    fn clock_getres(which: ClockID, tp: &mut TimeSpec) -> usize {
+        // Validates the clock id.
+        let mut now = TimeSpec::default();
+        let err = Self::clock_get(which, &mut now);
+        if err != 0 {
+            return err;
+        }
+
+        let nsec = match which.get_clock() {
+            Some(ClockType::REALTIME | ClockType::MONOTONIC | ClockType::BOOTTIME) => {
+                NSEC_PER_SEC.div_ceil(CLOCK_FREQ)
+            }
+            _ => 1,
+        };
+        *tp = TimeSpec {
+            tv_sec: 0,
+            tv_nsec: nsec,
+        };
+        0
+    }
+
+    /// CPU clocks are only available for the calling process or thread.
+    ///
+    /// `CLOCK_REALTIME`, `CLOCK_MONOTONIC` and `CLOCK_BOOTTIME` are all measured by the
+    /// time since boot, since there is neither RTC nor suspension.
+    fn clock_get(which: ClockID, tp: &mut TimeSpec) -> usize {
+        let curr = cpu().curr.as_ref().unwrap();
+        let now = get_time();
+        let thread_time = || curr.cpu_time.lock().get(now);
+        // Finished time slices of all threads have been added to the thread group,
+        // while threads running on other harts are still in their current slices.
+        let process_time = || {
+            let threads = find_threads(curr.pid);
+            let cpu_times: Vec<_> = threads
+                .iter()
+                .map(|thread| thread.cpu_time.lock())
+                .collect();
+            let running: usize = cpu_times.iter().map(|cpu_time| cpu_time.running(now)).sum();
+            curr.thread_group.cpu_time() + running
+        };
+
+        *tp = if which.is_dynamic() {
+            // Refers to a pid or a tid.
+            if which.is_thread() && which.get_pid() == curr.tid.0 {
+                cycles_to_timespec(thread_time())
+            } else if which.is_proc() && which.get_pid() == curr.pid {
+                cycles_to_timespec(process_time())
+            } else {
+                return Errno::EINVAL as usize;
+            }
+        } else {
+            match which.get_clock() {
+                Some(ClockType::REALTIME | ClockType::MONOTONIC | ClockType::BOOTTIME) => {
+                    TimeSpec::new(get_time_sec_f64())
+                }
+                Some(ClockType::PROCESS_CPUTIME_ID) => cycles_to_timespec(process_time()),
+                Some(ClockType::THREAD_CPUTIME_ID) => cycles_to_timespec(thread_time()),
+                None => return Errno::EINVAL as usize,
+            }
+        };
+        0
+    }
+}